@@ -3,6 +3,7 @@ pub mod sub_mut;
 
 use crate::backend::base::sub::BaseSubBackend;
 use crate::backend::base::sub_mut::BaseSubMutBackend;
+use crate::backend::growth::{Double, GrowthPolicy};
 use crate::backend::Backend;
 use crate::error::Error;
 use crate::header::BaseHeader;
@@ -13,6 +14,7 @@ use std::ops::{Deref, DerefMut, Range};
 pub struct BaseBackend<S> {
     storage: S,
     header: BaseHeader,
+    growth: Option<Box<dyn GrowthPolicy>>,
 }
 
 impl<S> BaseBackend<S> {
@@ -26,15 +28,46 @@ impl<S> BaseBackend<S> {
         &mut self.storage
     }
 
+    #[inline]
+    pub(crate) fn into_storage(self) -> S {
+        self.storage
+    }
+
+    /// Creates a backend from a storage and its already read header.
+    #[inline]
+    pub(crate) fn from_parts(storage: S, header: BaseHeader) -> Self {
+        Self {
+            storage,
+            header,
+            growth: None,
+        }
+    }
+
     #[inline]
     pub fn header(&self) -> &BaseHeader {
         &self.header
     }
 
+    #[inline]
+    pub(crate) fn header_mut(&mut self) -> &mut BaseHeader {
+        &mut self.header
+    }
+
     #[inline]
     pub fn header_len() -> usize {
         BaseHeader::len_bytes()
     }
+
+    /// Sets the policy components use to decide how much to grow this backend by.
+    #[inline]
+    pub fn set_growth_policy<P: GrowthPolicy + 'static>(&mut self, policy: P) {
+        self.growth = Some(Box::new(policy));
+    }
+
+    #[inline]
+    pub fn growth_policy(&self) -> &dyn GrowthPolicy {
+        self.growth.as_deref().unwrap_or(&Double)
+    }
 }
 
 impl<S> Creatable<S> for BaseBackend<S>
@@ -49,6 +82,7 @@ impl<S> Creatable<S> for BaseBackend<S>
         Ok(BaseBackend {
             storage: backend,
             header,
+            growth: None,
         })
     }
 }
@@ -63,7 +97,11 @@ impl<S> BaseBackend<S>
             .try_into()
             .map_err(|_| Error::InvalidHeader)?;
         let header = BaseHeader::from_bytes(header_bytes);
-        Ok(Self { storage, header })
+        Ok(Self {
+            storage,
+            header,
+            growth: None,
+        })
     }
 }
 
@@ -24,6 +24,22 @@ where
     //
 }
 
+impl<'a> BaseSubBackend<'a, &'a [u8]> {
+    /// Same as `Backend::data` but the returned data borrows the storage the backend has been created from instead of
+    /// the backend itself, so it can outlive it.
+    #[inline]
+    pub fn data_borrowed(&self) -> &'a [u8] {
+        self.storage
+    }
+
+    /// Same as `Backend::content_data` but the returned data borrows the storage the backend has been created from
+    /// instead of the backend itself, so it can outlive it.
+    #[inline]
+    pub fn content_data_borrowed(&self) -> &'a [u8] {
+        &self.storage[self.first_index()..self.last_index()]
+    }
+}
+
 impl<'a> MType for BaseSubBackend<'a, &[u8]> {
     #[inline]
     fn raw_data(&self) -> &[u8] {
@@ -0,0 +1,236 @@
+use crate::backend::growable::GrowableBackend;
+use crate::backend::growth::GrowthPolicy;
+use crate::backend::{Advice, Backend};
+use crate::error::Error;
+use std::ops::Range;
+
+/// Backend wrapper limiting the size the wrapped backend can grow to. Growing beyond `max_size` bytes, including the
+/// header, fails with `Error::QuotaExceeded` and leaves the backend untouched.
+pub struct BoundedBackend<B> {
+    inner: B,
+    max_size: usize,
+}
+
+impl<B> BoundedBackend<B> {
+    /// Limits `inner` to `max_size` bytes. A backend that is already bigger can't grow anymore but can still be
+    /// shrunk.
+    #[inline]
+    pub fn new(inner: B, max_size: usize) -> Self {
+        Self { inner, max_size }
+    }
+
+    #[inline]
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    #[inline]
+    pub fn set_max_size(&mut self, max_size: usize) {
+        self.max_size = max_size;
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B: Backend> BoundedBackend<B> {
+    /// Returns the amount of bytes the backend can still grow by.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.max_size.saturating_sub(self.inner.data().len())
+    }
+}
+
+impl<B: Backend> Backend for BoundedBackend<B> {
+    #[inline]
+    fn data(&self) -> &[u8] {
+        self.inner.data()
+    }
+
+    #[inline]
+    fn data_mut(&mut self) -> &mut [u8] {
+        self.inner.data_mut()
+    }
+
+    #[inline]
+    fn data_mut_range(&mut self, range: Range<usize>) -> &mut [u8] {
+        self.inner.data_mut_range(range)
+    }
+
+    #[inline]
+    fn first_index(&self) -> usize {
+        self.inner.first_index()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    #[inline]
+    fn set_len(&mut self, len: usize) -> Result<(), Error> {
+        self.inner.set_len(len)
+    }
+
+    #[inline]
+    fn push(&mut self, data: &[u8]) -> Result<usize, Error> {
+        self.inner.push(data)
+    }
+
+    #[inline]
+    fn push_fill(&mut self, data: u8, len: usize) -> Result<usize, Error> {
+        self.inner.push_fill(data, len)
+    }
+
+    #[inline]
+    fn push_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, Error> {
+        self.inner.push_vectored(bufs)
+    }
+
+    #[inline]
+    fn get(&self, index: usize, len: usize) -> Result<&[u8], Error> {
+        self.inner.get(index, len)
+    }
+
+    #[inline]
+    fn get_mut(&mut self, index: usize, len: usize) -> Result<&mut [u8], Error> {
+        self.inner.get_mut(index, len)
+    }
+
+    #[inline]
+    fn replace_fill(&mut self, index: usize, len: usize, data: u8, fill_len: usize) -> Result<usize, Error> {
+        self.inner.replace_fill(index, len, data, fill_len)
+    }
+
+    #[inline]
+    fn replace(&mut self, index: usize, len: usize, data: &[u8]) -> Result<usize, Error> {
+        self.inner.replace(index, len, data)
+    }
+
+    #[inline]
+    fn replace_vectored(&mut self, index: usize, len: usize, bufs: &[&[u8]]) -> Result<usize, Error> {
+        self.inner.replace_vectored(index, len, bufs)
+    }
+
+    #[inline]
+    fn replace_same_len(&mut self, index: usize, data: &[u8]) -> Result<usize, Error> {
+        self.inner.replace_same_len(index, data)
+    }
+
+    #[inline]
+    fn flush_range_impl(&mut self, start: usize, len: usize) -> Result<(), Error> {
+        self.inner.flush_range_impl(start, len)
+    }
+
+    #[inline]
+    fn prefetch(&self, index: usize, len: usize) {
+        self.inner.prefetch(index, len)
+    }
+
+    #[inline]
+    fn advise(&self, advice: Advice) {
+        self.inner.advise(advice)
+    }
+
+    #[inline]
+    fn move_range_to(&mut self, index: usize, len: usize, new_index: usize) -> Result<(), Error> {
+        self.inner.move_range_to(index, len, new_index)
+    }
+}
+
+impl<B: GrowableBackend> GrowableBackend for BoundedBackend<B> {
+    #[inline]
+    fn resize_impl(&mut self, new_size: usize, growing: bool) -> crate::Result<()> {
+        if growing && new_size > self.max_size {
+            return Err(Error::QuotaExceeded);
+        }
+        self.inner.resize_impl(new_size, growing)
+    }
+
+    fn resize(&mut self, delta: isize) -> crate::Result<()> {
+        if delta > 0 && self.inner.data().len() + delta as usize > self.max_size {
+            return Err(Error::QuotaExceeded);
+        }
+        self.inner.resize(delta)
+    }
+
+    #[inline]
+    fn growth_policy(&self) -> &dyn GrowthPolicy {
+        self.inner.growth_policy()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::super::test::*;
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+    use crate::components::indexed_file::IndexedFile;
+    use crate::components::map::FMap;
+    use crate::traits::creatable::Creatable;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_backend(&mut mem_backend);
+        test_quota(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./bounded", 10);
+        test_backend(&mut mmap_backend);
+        test_quota(&mut mmap_backend);
+    }
+
+    fn test_backend<B: GrowableBackend>(backend: &mut B) {
+        backend.clear();
+        let mut backend = BoundedBackend::new(backend, 1000);
+        backend.grow(100).unwrap();
+        be_clear(&mut backend);
+        be_remove(&mut backend);
+        be_fill(&mut backend);
+    }
+
+    fn test_quota<B: GrowableBackend>(backend: &mut B) {
+        backend.clear();
+        backend.shrink_to_fit().unwrap();
+
+        {
+            let mut backend = BoundedBackend::new(&mut *backend, 200);
+            assert_eq!(backend.grow(1000), Err(Error::QuotaExceeded));
+            backend.grow(100).unwrap();
+            assert!(backend.remaining() <= 100);
+
+            let mut ifile = IndexedFile::create(&mut backend).unwrap();
+            let mut inserted = 0;
+            let err = loop {
+                match ifile.insert_t(&"some text") {
+                    Ok(_) => inserted += 1,
+                    Err(err) => break err,
+                }
+            };
+            assert_eq!(err, Error::QuotaExceeded);
+            assert!(inserted > 0);
+            assert_eq!(ifile.count(), inserted);
+            assert_eq!(ifile.get_t::<String>(inserted - 1).unwrap(), "some text");
+        }
+
+        backend.clear();
+        let mut backend = BoundedBackend::new(backend, 4096);
+        let mut map: FMap<_, u32, u32> = FMap::create(&mut backend).unwrap();
+        let err = (0..10_000).find_map(|i| map.insert(&i, &i).err());
+        assert_eq!(err, Some(Error::QuotaExceeded));
+        drop(map);
+        assert!(backend.data().len() <= 4096);
+
+        backend.set_max_size(usize::MAX);
+        backend.grow(4096).unwrap();
+    }
+}
@@ -0,0 +1,387 @@
+use crate::backend::base::BaseBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::growth::GrowthPolicy;
+use crate::backend::Backend;
+use crate::components::indexed_file::IndexedFile;
+use crate::error::Error;
+use crate::header::BaseHeader;
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use std::ops::{Deref, DerefMut, Range};
+use std::sync::OnceLock;
+
+/// Amount of uncompressed bytes stored in a single page.
+pub const PAGE_SIZE: usize = 64 * 1024;
+
+pub type CompressedBackend<B> = BaseBackend<CompressedData<B>>;
+
+/// Storage keeping its data lz4 compressed in pages of `PAGE_SIZE` bytes within the wrapped backend. Loading only reads
+/// the sizes of the pages, the data gets decompressed once it's accessed for the first time. Flushing a range only
+/// compresses and replaces the pages written since they have been flushed the last time. Unflushed changes are written
+/// back when the storage gets dropped.
+///
+/// # Panics
+/// Accessing the data panics if a page is corrupted and can't be decompressed.
+pub struct CompressedData<B: GrowableBackend> {
+    pages: IndexedFile<B>,
+    /// Size of the uncompressed data.
+    size: usize,
+    data: OnceLock<Vec<u8>>,
+    /// Flags for each page of the data that has been written since its last flush.
+    dirty: Vec<bool>,
+    /// Amount of pages holding data in `pages`. Entries after them are left empty after shrinking.
+    stored: usize,
+}
+
+impl<B: GrowableBackend> CompressedData<B> {
+    /// Creates a new empty storage in `backend`.
+    pub fn create(backend: B) -> crate::Result<Self> {
+        let data = BaseHeader::new(0).bytes().to_vec();
+        let mut storage = Self {
+            pages: IndexedFile::create(backend)?,
+            size: data.len(),
+            data: OnceLock::from(data),
+            dirty: vec![true],
+            stored: 0,
+        };
+        storage.flush_range(0, BaseHeader::len_bytes())?;
+        Ok(storage)
+    }
+
+    /// Loads an existing storage from `backend` without decompressing any page.
+    pub fn load(backend: B) -> crate::Result<Self> {
+        let pages = IndexedFile::init(backend)?;
+
+        let mut size = 0;
+        let mut stored = 0;
+        for page in pages.iter().take_while(|i| !i.is_empty()) {
+            let (page_size, _) = lz4_flex::block::uncompressed_size(page).map_err(|_| Error::UnexpectedValue)?;
+
+            // Only the last page can be smaller than `PAGE_SIZE`.
+            if size % PAGE_SIZE != 0 || page_size > PAGE_SIZE {
+                return Err(Error::UnexpectedValue);
+            }
+
+            size += page_size;
+            stored += 1;
+        }
+
+        if size < BaseHeader::len_bytes() {
+            return Err(Error::Initialization);
+        }
+
+        Ok(Self {
+            pages,
+            size,
+            data: OnceLock::new(),
+            dirty: vec![false; stored],
+            stored,
+        })
+    }
+
+    /// Compresses all pages overlapping the given range that have been written since their last flush and replaces
+    /// them in the underlying backend.
+    pub fn flush_range(&mut self, start: usize, len: usize) -> crate::Result<()> {
+        // Data that has never been accessed can't have been written.
+        let Some(data) = self.data.get() else {
+            return Ok(());
+        };
+
+        // New pages have to be appended in order, so all pages not stored yet are flushed as well.
+        let first_page = (start / PAGE_SIZE).min(self.stored);
+        let last_page = (start + len).div_ceil(PAGE_SIZE).min(self.dirty.len());
+        for page in first_page..last_page {
+            if !self.dirty[page] && page < self.stored {
+                continue;
+            }
+
+            let end = ((page + 1) * PAGE_SIZE).min(data.len());
+            let compressed = lz4_flex::compress_prepend_size(&data[page * PAGE_SIZE..end]);
+            if page < self.pages.count() {
+                self.pages.entry(page)?.set(&compressed)?;
+            } else {
+                self.pages.insert(&compressed)?;
+            }
+            self.dirty[page] = false;
+        }
+        self.stored = self.stored.max(last_page);
+
+        // Clear pages cut off by shrinking the data.
+        for page in self.dirty.len()..self.stored {
+            self.pages.entry(page)?.set(&[])?;
+        }
+        self.stored = self.stored.min(self.dirty.len());
+
+        self.pages.flush()
+    }
+
+    /// Returns the amount of bytes the compressed pages take.
+    #[inline]
+    pub fn compressed_len(&self) -> usize {
+        self.pages.iter().take(self.stored).map(|i| i.len()).sum()
+    }
+
+    /// Returns `true` if the data has been decompressed already.
+    #[inline]
+    pub fn is_loaded(&self) -> bool {
+        self.data.get().is_some()
+    }
+
+    /// Returns the size of the uncompressed data.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the data within `range` mutably, marking only the pages overlapping it as written.
+    pub fn data_mut_range(&mut self, range: Range<usize>) -> &mut [u8] {
+        let pages = range.start / PAGE_SIZE..range.end.div_ceil(PAGE_SIZE);
+        if !range.is_empty() {
+            self.dirty[pages].fill(true);
+        }
+        &mut self.data_mut()[range]
+    }
+
+    /// Resizes the data to `new_len` bytes, filling new space with zeroes.
+    fn resize(&mut self, new_len: usize) {
+        let old_len = self.size;
+        self.data_mut().resize(new_len, 0);
+        self.size = new_len;
+        self.dirty.resize(new_len.div_ceil(PAGE_SIZE), true);
+
+        // The page the shorter data ended in gets new zeroes or loses its end.
+        let boundary = old_len.min(new_len);
+        if boundary % PAGE_SIZE != 0 {
+            self.dirty[boundary / PAGE_SIZE] = true;
+        }
+    }
+
+    /// Reads the header, decompressing only the first page if the data hasn't been accessed yet.
+    fn read_header(&self) -> crate::Result<BaseHeader> {
+        let first = match self.data.get() {
+            Some(data) => data[..BaseHeader::len_bytes()].to_vec(),
+            None => lz4_flex::decompress_size_prepended(self.pages.get(0)?).map_err(|_| Error::UnexpectedValue)?,
+        };
+        let bytes: [u8; 8] = first
+            .get(0..8)
+            .and_then(|i| i.try_into().ok())
+            .ok_or(Error::InvalidHeader)?;
+        Ok(BaseHeader::from_bytes(bytes))
+    }
+
+    #[inline]
+    fn is_dirty(&self) -> bool {
+        self.stored > self.dirty.len() || self.dirty.iter().any(|i| *i)
+    }
+
+    /// Returns the data, decompressing all pages on the first access.
+    fn data(&self) -> &Vec<u8> {
+        self.data.get_or_init(|| {
+            let mut data = Vec::with_capacity(self.size);
+            for page in self.pages.iter().take(self.stored) {
+                let decompressed = lz4_flex::decompress_size_prepended(page).expect("Corrupted compressed page");
+                data.extend_from_slice(&decompressed);
+            }
+            data
+        })
+    }
+
+    #[inline]
+    fn data_mut(&mut self) -> &mut Vec<u8> {
+        self.data();
+        self.data.get_mut().unwrap()
+    }
+}
+
+impl<B: GrowableBackend> Drop for CompressedData<B> {
+    fn drop(&mut self) {
+        if self.is_dirty() {
+            let _ = self.flush_range(0, self.size);
+        }
+    }
+}
+
+impl<B: GrowableBackend> CompressedBackend<B> {
+    /// Loads a backend from `backend`. Only the page holding the header gets decompressed, all other pages are
+    /// decompressed once the data is accessed.
+    pub fn load(backend: B) -> crate::Result<Self> {
+        let storage = CompressedData::load(backend)?;
+        let header = storage.read_header()?;
+        Ok(Self::from_parts(storage, header))
+    }
+}
+
+impl<B: GrowableBackend> Backend for CompressedBackend<B> {
+    #[inline]
+    fn data(&self) -> &[u8] {
+        self.storage().deref()
+    }
+
+    #[inline]
+    fn data_mut(&mut self) -> &mut [u8] {
+        self.storage_mut().deref_mut()
+    }
+
+    #[inline]
+    fn data_mut_range(&mut self, range: Range<usize>) -> &mut [u8] {
+        self.storage_mut().data_mut_range(range)
+    }
+
+    #[inline]
+    fn first_index(&self) -> usize {
+        BaseHeader::len_bytes()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.header().data_len()
+    }
+
+    #[inline]
+    fn set_len(&mut self, len: usize) -> Result<(), Error> {
+        self.header_mut().set_data_len(len);
+        let bytes = self.header().bytes();
+        self.data_mut_range(Self::header_range()).copy_from_slice(&bytes);
+        Ok(())
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.storage().size() - self.first_index()
+    }
+
+    #[inline]
+    fn end_index(&self) -> usize {
+        self.storage().size().saturating_sub(1)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), Error> {
+        let size = self.storage().size();
+        self.flush_range_impl(0, size)
+    }
+
+    #[inline]
+    fn flush_range_impl(&mut self, start: usize, len: usize) -> Result<(), Error> {
+        self.storage_mut().flush_range(start, len)
+    }
+}
+
+impl<B: GrowableBackend> GrowableBackend for CompressedBackend<B> {
+    fn resize_impl(&mut self, new_len: usize, _: bool) -> crate::Result<()> {
+        self.storage_mut().resize(new_len);
+        Ok(())
+    }
+
+    #[inline]
+    fn growth_policy(&self) -> &dyn GrowthPolicy {
+        BaseBackend::growth_policy(self)
+    }
+}
+
+impl<B: GrowableBackend> Deref for CompressedData<B> {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.data()
+    }
+}
+
+impl<B: GrowableBackend> DerefMut for CompressedData<B> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.dirty.fill(true);
+        self.data_mut()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::super::test::*;
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_backend(&mut mem_backend);
+        test_compress(&mut mem_backend);
+        test_page_replacement(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./compressed", 10);
+        test_backend(&mut mmap_backend);
+        test_compress(&mut mmap_backend);
+        test_page_replacement(&mut mmap_backend);
+    }
+
+    fn test_backend<B: GrowableBackend>(backend: &mut B) {
+        backend.clear();
+        let mut backend = CompressedBackend::from_storage(CompressedData::create(backend).unwrap()).unwrap();
+        backend.grow(100).unwrap();
+        be_clear(&mut backend);
+        be_push(&mut backend);
+        be_replace(&mut backend);
+        be_fill(&mut backend);
+    }
+
+    fn test_compress<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let text = "some highly compressible text. ".repeat(10_000);
+
+        {
+            let storage = CompressedData::create(&mut backend).unwrap();
+            let mut compressed = CompressedBackend::from_storage(storage).unwrap();
+            let mut ifile = IndexedFile::create(&mut compressed).unwrap();
+            for _ in 0..10 {
+                ifile.insert_t(&text).unwrap();
+            }
+            ifile.flush().unwrap();
+            assert!(compressed.storage().compressed_len() < compressed.data().len());
+        }
+
+        let compressed = CompressedBackend::load(backend).unwrap();
+        assert!(!compressed.storage().is_loaded());
+        let ifile = IndexedFile::init(compressed).unwrap();
+        assert_eq!(ifile.count(), 10);
+        assert_eq!(ifile.get_t::<String>(9).unwrap(), text);
+    }
+
+    fn test_page_replacement<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut compressed = CompressedBackend::from_storage(CompressedData::create(&mut backend).unwrap()).unwrap();
+        compressed.grow(PAGE_SIZE * 4).unwrap();
+        compressed.push_fill(1, PAGE_SIZE * 3).unwrap();
+        compressed.flush().unwrap();
+
+        let before = stored_pages(&compressed);
+        assert_eq!(before.len(), 5);
+
+        // Only the page holding the written range gets replaced.
+        compressed.replace_same_len(PAGE_SIZE * 2, &[2; 10]).unwrap();
+        compressed.flush().unwrap();
+        let after = stored_pages(&compressed);
+        let changed: Vec<_> = (0..before.len()).filter(|i| before[*i] != after[*i]).collect();
+        assert_eq!(changed, vec![2]);
+
+        // Pages cut off by shrinking are cleared.
+        compressed.set_len(10).unwrap();
+        compressed.shrink(PAGE_SIZE * 3).unwrap();
+        compressed.flush().unwrap();
+        assert_eq!(compressed.storage().stored, 2);
+        drop(compressed);
+
+        let compressed = CompressedBackend::load(&mut backend).unwrap();
+        assert!(!compressed.storage().is_loaded());
+        assert_eq!(compressed.len(), 10);
+        assert_eq!(compressed.capacity(), PAGE_SIZE);
+        assert_eq!(compressed.content_data(), &[1; 10]);
+        assert!(compressed.storage().is_loaded());
+    }
+
+    fn stored_pages<B: GrowableBackend>(backend: &CompressedBackend<B>) -> Vec<Vec<u8>> {
+        backend.storage().pages.iter().map(|i| i.to_vec()).collect()
+    }
+}
@@ -0,0 +1,303 @@
+use crate::backend::base::BaseBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::growth::GrowthPolicy;
+use crate::backend::Backend;
+use crate::error::Error;
+use crate::header::BaseHeader;
+use std::alloc::{self, Layout};
+use std::fs::{File, OpenOptions};
+use std::ops::{Deref, DerefMut};
+use std::os::unix::fs::{FileExt, OpenOptionsExt};
+use std::path::Path;
+use std::ptr::NonNull;
+
+/// Alignment of the buffer, file offsets and lengths used for direct I/O.
+pub const ALIGN: usize = 4096;
+
+pub type DirectBackend = BaseBackend<DirectData>;
+
+/// File storage using direct I/O (`O_DIRECT` on linux, `F_NOCACHE` on macOS) to bypass the page cache. The content is
+/// kept in an aligned buffer and flushing writes the touched blocks straight to the disk, which avoids evicting other
+/// data from the page cache for large write heavy workloads. Unflushed changes are written back when the storage gets
+/// dropped.
+pub struct DirectData {
+    file: File,
+    buf: AlignedBuf,
+    dirty: bool,
+}
+
+impl DirectData {
+    /// Creates a new file and truncates an existing one.
+    pub fn create<P: AsRef<Path>>(path: P, size: usize) -> Result<Self, Error> {
+        let mut options = OpenOptions::new();
+        options.read(true).write(true).create(true).truncate(true);
+        let file = open_direct(options, path.as_ref())?;
+
+        let len = size + BaseHeader::len_bytes();
+        file.set_len(len as u64)?;
+        Ok(Self {
+            file,
+            buf: AlignedBuf::new(len),
+            dirty: true,
+        })
+    }
+
+    /// Loads an existing file from a path.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let mut options = OpenOptions::new();
+        options.read(true).write(true);
+        let file = open_direct(options, path.as_ref())?;
+
+        let len = file.metadata()?.len() as usize;
+        let mut buf = AlignedBuf::new(len);
+        let blocks = buf.blocks_mut();
+        let mut pos = 0;
+        while pos < len {
+            let read = file.read_at(&mut blocks[pos..], pos as u64)?;
+            if read == 0 {
+                return Err(Error::Initialization);
+            }
+            pos += read;
+        }
+
+        // Reads at the end of the file can fill the padding of the last block.
+        blocks[len..].fill(0);
+
+        Ok(Self {
+            file,
+            buf,
+            dirty: false,
+        })
+    }
+
+    /// Writes all blocks overlapping the given range to the file.
+    pub fn flush_range(&mut self, start: usize, len: usize) -> crate::Result<()> {
+        let block_start = start / ALIGN * ALIGN;
+        let block_end = (start + len).next_multiple_of(ALIGN);
+        self.file.write_all_at(&self.buf.blocks()[block_start..block_end], block_start as u64)?;
+
+        // Remove the padding written with the last block.
+        if block_end > self.buf.len() {
+            self.file.set_len(self.buf.len() as u64)?;
+        }
+
+        if start == 0 && len == self.buf.len() {
+            self.dirty = false;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for DirectData {
+    fn drop(&mut self) {
+        if self.dirty {
+            let _ = self.flush_range(0, self.buf.len());
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn open_direct(mut options: OpenOptions, path: &Path) -> std::io::Result<File> {
+    options.custom_flags(libc::O_DIRECT).open(path)
+}
+
+#[cfg(target_os = "macos")]
+fn open_direct(options: OpenOptions, path: &Path) -> std::io::Result<File> {
+    use std::os::unix::io::AsRawFd;
+    let file = options.open(path)?;
+    if unsafe { libc::fcntl(file.as_raw_fd(), libc::F_NOCACHE, 1) } == -1 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(file)
+}
+
+/// Direct I/O isn't supported on other systems so the file is accessed through the page cache.
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+#[inline]
+fn open_direct(options: OpenOptions, path: &Path) -> std::io::Result<File> {
+    options.open(path)
+}
+
+/// Zero initialized heap buffer aligned to `ALIGN` whose allocation is a multiple of `ALIGN` bytes. Bytes behind `len`
+/// are always zero.
+struct AlignedBuf {
+    ptr: NonNull<u8>,
+    len: usize,
+    cap: usize,
+}
+
+// Safety: The buffer exclusively owns its allocation.
+unsafe impl Send for AlignedBuf {}
+unsafe impl Sync for AlignedBuf {}
+
+impl AlignedBuf {
+    fn new(len: usize) -> Self {
+        let cap = len.next_multiple_of(ALIGN).max(ALIGN);
+        let layout = Self::layout(cap);
+        let ptr = unsafe { alloc::alloc_zeroed(layout) };
+        let ptr = NonNull::new(ptr).unwrap_or_else(|| alloc::handle_alloc_error(layout));
+        Self { ptr, len, cap }
+    }
+
+    #[inline]
+    fn layout(cap: usize) -> Layout {
+        Layout::from_size_align(cap, ALIGN).expect("Invalid buffer size")
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the whole allocation, including the padding behind `len`.
+    #[inline]
+    fn blocks(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.cap) }
+    }
+
+    #[inline]
+    fn blocks_mut(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.cap) }
+    }
+
+    fn resize(&mut self, new_len: usize) {
+        if new_len <= self.len {
+            let len = self.len;
+            self.blocks_mut()[new_len..len].fill(0);
+            self.len = new_len;
+            return;
+        }
+
+        if new_len > self.cap {
+            let new_cap = new_len.next_multiple_of(ALIGN);
+            let layout = Self::layout(self.cap);
+            let ptr = unsafe { alloc::realloc(self.ptr.as_ptr(), layout, new_cap) };
+            self.ptr = NonNull::new(ptr).unwrap_or_else(|| alloc::handle_alloc_error(Self::layout(new_cap)));
+
+            let old_cap = self.cap;
+            self.cap = new_cap;
+            self.blocks_mut()[old_cap..].fill(0);
+        }
+
+        self.len = new_len;
+    }
+}
+
+impl Drop for AlignedBuf {
+    fn drop(&mut self) {
+        unsafe { alloc::dealloc(self.ptr.as_ptr(), Self::layout(self.cap)) };
+    }
+}
+
+impl Backend for DirectBackend {
+    #[inline]
+    fn data(&self) -> &[u8] {
+        self.storage().deref()
+    }
+
+    #[inline]
+    fn data_mut(&mut self) -> &mut [u8] {
+        self.storage_mut().deref_mut()
+    }
+
+    #[inline]
+    fn first_index(&self) -> usize {
+        BaseHeader::len_bytes()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.header().data_len()
+    }
+
+    #[inline]
+    fn set_len(&mut self, len: usize) -> Result<(), Error> {
+        self.set_len(len)
+    }
+
+    #[inline]
+    fn flush_range_impl(&mut self, start: usize, len: usize) -> Result<(), Error> {
+        self.storage_mut().flush_range(start, len)
+    }
+}
+
+impl GrowableBackend for DirectBackend {
+    fn resize_impl(&mut self, new_len: usize, _: bool) -> crate::Result<()> {
+        let storage = self.storage_mut();
+        storage.file.set_len(new_len as u64)?;
+        storage.buf.resize(new_len);
+        storage.dirty = true;
+        Ok(())
+    }
+
+    #[inline]
+    fn growth_policy(&self) -> &dyn GrowthPolicy {
+        BaseBackend::growth_policy(self)
+    }
+}
+
+impl Deref for DirectData {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.buf.blocks()[..self.buf.len()]
+    }
+}
+
+impl DerefMut for DirectData {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.dirty = true;
+        let len = self.buf.len();
+        &mut self.buf.blocks_mut()[..len]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::*;
+    use super::*;
+    use crate::components::indexed_file::IndexedFile;
+    use crate::traits::creatable::Creatable;
+    use crate::traits::initiable::Initiable;
+
+    fn make_direct_backend(file: &str, len: usize) -> DirectBackend {
+        let path = Path::new("./testfiles");
+        if !path.is_dir() {
+            std::fs::create_dir_all(path).unwrap();
+        }
+        DirectBackend::from_storage(DirectData::create(path.join(file), len).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn direct_backend() {
+        let mut backend = make_direct_backend("./direct", 100);
+        be_clear(&mut backend);
+        be_push(&mut backend);
+        be_replace(&mut backend);
+        be_fill(&mut backend);
+    }
+
+    #[test]
+    fn test_persist() {
+        {
+            let backend = make_direct_backend("./direct_persist", 10);
+            let mut ifile = IndexedFile::create(backend).unwrap();
+            for i in 0..10_000u32 {
+                ifile.insert_t(&format!("entry {i}")).unwrap();
+            }
+            ifile.flush().unwrap();
+            ifile.insert_t(&"not flushed").unwrap();
+        }
+
+        let path = "./testfiles/direct_persist";
+        let backend = DirectBackend::from_storage(DirectData::load(path).unwrap()).unwrap();
+        assert_eq!(std::fs::metadata(path).unwrap().len() as usize, backend.data().len());
+
+        let ifile = IndexedFile::init(backend).unwrap();
+        assert_eq!(ifile.count(), 10_001);
+        assert_eq!(ifile.get_t::<String>(1234).unwrap(), "entry 1234");
+        assert_eq!(ifile.get_t::<String>(10_000).unwrap(), "not flushed");
+    }
+}
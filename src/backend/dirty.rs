@@ -0,0 +1,427 @@
+use crate::backend::growable::GrowableBackend;
+use crate::backend::growth::GrowthPolicy;
+use crate::backend::{Advice, Backend};
+use crate::error::Error;
+use crate::utils::page_size;
+use std::ops::Range;
+
+/// Backend wrapper tracking which pages were written since the last flush, so flushing only writes back those pages
+/// instead of the whole backend.
+///
+/// Writes through the slice returned by `data_mut()` can't be tracked and mark the whole backend as dirty, writes through
+/// `data_mut_range()` only mark the pages of the given range.
+pub struct DirtyBackend<B> {
+    inner: B,
+    dirty: DirtyPages,
+}
+
+/// Bitmap of the pages of a storage that were written since they got written back the last time.
+pub(crate) struct DirtyPages {
+    page_size: usize,
+    /// One bit for each page of the storage.
+    bits: Vec<u64>,
+}
+
+impl DirtyPages {
+    /// Creates a bitmap for a storage of `len` bytes with no dirty pages.
+    pub(crate) fn new(len: usize) -> Self {
+        let mut pages = Self {
+            page_size: page_size(),
+            bits: vec![],
+        };
+        pages.resize(len);
+        pages
+    }
+
+    /// Returns `true` if any page is dirty.
+    #[inline]
+    pub(crate) fn is_dirty(&self) -> bool {
+        self.bits.iter().any(|i| *i != 0)
+    }
+
+    /// Returns the amount of bytes within dirty pages of a storage of `len` bytes.
+    pub(crate) fn dirty_len(&self, len: usize) -> usize {
+        let pages = self.bits.iter().map(|i| i.count_ones() as usize).sum::<usize>();
+        (pages * self.page_size).min(len)
+    }
+
+    /// Adjusts the bitmap to a storage of `len` bytes.
+    pub(crate) fn resize(&mut self, len: usize) {
+        let pages = len.div_ceil(self.page_size);
+        self.bits.resize(pages.div_ceil(64), 0);
+
+        // Clear bits of pages that don't exist anymore.
+        if let Some(last) = self.bits.last_mut() {
+            let used = pages - (pages - 1) / 64 * 64;
+            if used < 64 {
+                *last &= (1 << used) - 1;
+            }
+        }
+    }
+
+    #[inline]
+    fn is_page_dirty(&self, page: usize) -> bool {
+        self.bits[page / 64] & (1 << (page & 63)) != 0
+    }
+
+    /// Marks all pages overlapping `range` of a storage of `len` bytes as dirty.
+    pub(crate) fn mark(&mut self, range: Range<usize>, len: usize) {
+        let end = range.end.min(len);
+        if range.start >= end {
+            return;
+        }
+        for page in range.start / self.page_size..end.div_ceil(self.page_size) {
+            self.bits[page / 64] |= 1 << (page & 63);
+        }
+    }
+
+    /// Marks all pages of a storage of `len` bytes as dirty.
+    #[inline]
+    pub(crate) fn mark_all(&mut self, len: usize) {
+        self.bits.fill(u64::MAX);
+        self.resize(len);
+    }
+
+    /// Returns the next run of dirty pages overlapping `range` of a storage of `len` bytes as range of bytes within
+    /// `range` and marks them as clean.
+    pub(crate) fn take_run(&mut self, range: Range<usize>, len: usize) -> Option<Range<usize>> {
+        let end_page = range.end.div_ceil(self.page_size).min(len.div_ceil(self.page_size));
+        let mut page = (range.start / self.page_size..end_page).find(|i| self.is_page_dirty(*i))?;
+
+        let run_start = page;
+        while page < end_page && self.is_page_dirty(page) {
+            self.bits[page / 64] &= !(1 << (page & 63));
+            page += 1;
+        }
+
+        let from = (run_start * self.page_size).max(range.start);
+        let to = (page * self.page_size).min(range.end).min(len);
+        Some(from..to)
+    }
+}
+
+impl<B: Backend> DirtyBackend<B> {
+    #[inline]
+    pub fn new(inner: B) -> Self {
+        let dirty = DirtyPages::new(inner.data().len());
+        Self { inner, dirty }
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Returns `true` if there are unflushed writes.
+    #[inline]
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.is_dirty()
+    }
+
+    /// Returns the amount of bytes that will be written back by the next flush.
+    #[inline]
+    pub fn dirty_len(&self) -> usize {
+        self.dirty.dirty_len(self.inner.data().len())
+    }
+
+    /// Adjusts the dirty bits to the current size of the backend.
+    #[inline]
+    fn resize_pages(&mut self) {
+        self.dirty.resize(self.inner.data().len());
+    }
+
+    /// Marks all pages overlapping the given range of raw data as dirty.
+    #[inline]
+    fn mark(&mut self, range: Range<usize>) {
+        self.dirty.mark(range, self.inner.data().len());
+    }
+
+    #[inline]
+    fn mark_header(&mut self) {
+        self.mark(0..self.inner.first_index());
+    }
+
+    /// Marks the data from `index` to the end of the content as dirty.
+    #[inline]
+    fn mark_tail(&mut self, index: usize, new_len: usize) {
+        let start = self.inner.get_index(index);
+        let end = self.inner.get_index(self.inner.len().max(new_len));
+        self.mark(start..end);
+        self.mark_header();
+    }
+
+    #[inline]
+    fn mark_all(&mut self) {
+        self.dirty.mark_all(self.inner.data().len());
+    }
+}
+
+impl<B: Backend> Backend for DirtyBackend<B> {
+    #[inline]
+    fn data(&self) -> &[u8] {
+        self.inner.data()
+    }
+
+    #[inline]
+    fn data_mut(&mut self) -> &mut [u8] {
+        self.mark_all();
+        self.inner.data_mut()
+    }
+
+    #[inline]
+    fn data_mut_range(&mut self, range: Range<usize>) -> &mut [u8] {
+        self.mark(range.clone());
+        self.inner.data_mut_range(range)
+    }
+
+    #[inline]
+    fn first_index(&self) -> usize {
+        self.inner.first_index()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    #[inline]
+    fn set_len(&mut self, len: usize) -> Result<(), Error> {
+        self.mark_header();
+        self.inner.set_len(len)
+    }
+
+    #[inline]
+    fn push(&mut self, data: &[u8]) -> Result<usize, Error> {
+        self.mark_tail(self.inner.len(), self.inner.len() + data.len());
+        self.inner.push(data)
+    }
+
+    #[inline]
+    fn push_fill(&mut self, data: u8, len: usize) -> Result<usize, Error> {
+        self.mark_tail(self.inner.len(), self.inner.len() + len);
+        self.inner.push_fill(data, len)
+    }
+
+    #[inline]
+    fn push_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, Error> {
+        let total = bufs.iter().map(|i| i.len()).sum::<usize>();
+        self.mark_tail(self.inner.len(), self.inner.len() + total);
+        self.inner.push_vectored(bufs)
+    }
+
+    #[inline]
+    fn get_mut(&mut self, index: usize, len: usize) -> Result<&mut [u8], Error> {
+        let start = self.inner.get_index(index);
+        self.mark(start..start + len);
+        self.inner.get_mut(index, len)
+    }
+
+    #[inline]
+    fn swap_same_len(&mut self, a: usize, b: usize, len: usize) -> Result<(), Error> {
+        for i in [a, b] {
+            let start = self.inner.get_index(i);
+            self.mark(start..start + len);
+        }
+        self.inner.swap_same_len(a, b, len)
+    }
+
+    fn replace_fill(&mut self, index: usize, len: usize, data: u8, fill_len: usize) -> Result<usize, Error> {
+        let new_len = (self.inner.len() + fill_len).saturating_sub(len);
+        self.mark_tail(index, new_len);
+        self.inner.replace_fill(index, len, data, fill_len)
+    }
+
+    fn replace(&mut self, index: usize, len: usize, data: &[u8]) -> Result<usize, Error> {
+        if len == data.len() {
+            return self.replace_same_len(index, data);
+        }
+        let new_len = (self.inner.len() + data.len()).saturating_sub(len);
+        self.mark_tail(index, new_len);
+        self.inner.replace(index, len, data)
+    }
+
+    fn replace_vectored(&mut self, index: usize, len: usize, bufs: &[&[u8]]) -> Result<usize, Error> {
+        let total = bufs.iter().map(|i| i.len()).sum::<usize>();
+        let new_len = (self.inner.len() + total).saturating_sub(len);
+        self.mark_tail(index, new_len);
+        self.inner.replace_vectored(index, len, bufs)
+    }
+
+    #[inline]
+    fn replace_same_len_fill(&mut self, index: usize, data: u8, fill_len: usize) -> Result<usize, Error> {
+        let start = self.inner.get_index(index);
+        self.mark(start..start + fill_len);
+        self.mark_header();
+        self.inner.replace_same_len_fill(index, data, fill_len)
+    }
+
+    #[inline]
+    fn replace_same_len(&mut self, index: usize, data: &[u8]) -> Result<usize, Error> {
+        let start = self.inner.get_index(index);
+        self.mark(start..start + data.len());
+        self.mark_header();
+        self.inner.replace_same_len(index, data)
+    }
+
+    #[inline]
+    fn replace_same_len_direct(&mut self, index: usize, data: &[u8]) -> Result<usize, Error> {
+        let start = self.inner.get_index(index);
+        self.mark(start..start + data.len());
+        self.inner.replace_same_len_direct(index, data)
+    }
+
+    #[inline]
+    fn fill(&mut self, range: Range<usize>, val: u8) -> Result<(), Error> {
+        let start = self.inner.get_index(range.start);
+        let end = self.inner.get_index(range.end);
+        self.mark(start..end);
+        self.inner.fill(range, val)
+    }
+
+    /// Only writes back the dirty pages within the given range.
+    fn flush_range_impl(&mut self, start: usize, len: usize) -> Result<(), Error> {
+        let data_len = self.inner.data().len();
+        let mut from = start;
+        while let Some(run) = self.dirty.take_run(from..start + len, data_len) {
+            self.inner.flush_range_impl(run.start, run.len())?;
+            from = run.end;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn prefetch(&self, index: usize, len: usize) {
+        self.inner.prefetch(index, len)
+    }
+
+    #[inline]
+    fn advise(&self, advice: Advice) {
+        self.inner.advise(advice)
+    }
+
+    fn move_range_to(&mut self, index: usize, len: usize, new_index: usize) -> Result<(), Error> {
+        let start = self.inner.get_index(index.min(new_index));
+        let end = self.inner.get_index(index.max(new_index) + len);
+        self.mark(start..end);
+        self.inner.move_range_to(index, len, new_index)
+    }
+}
+
+impl<B: GrowableBackend> GrowableBackend for DirtyBackend<B> {
+    #[inline]
+    fn resize_impl(&mut self, new_size: usize, growing: bool) -> crate::Result<()> {
+        self.inner.resize_impl(new_size, growing)?;
+        self.resize_pages();
+        Ok(())
+    }
+
+    // Forward to the inner implementation since wrapped backends might resize differently.
+    #[inline]
+    fn resize(&mut self, delta: isize) -> crate::Result<()> {
+        self.inner.resize(delta)?;
+        self.resize_pages();
+        Ok(())
+    }
+
+    #[inline]
+    fn growth_policy(&self) -> &dyn GrowthPolicy {
+        self.inner.growth_policy()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::super::test::*;
+    use super::*;
+    use crate::backend::instrumented::InstrumentedBackend;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+    use crate::components::map::FMap;
+    use crate::traits::batch::Batch;
+    use crate::traits::creatable::Creatable;
+    use crate::traits::initiable::Initiable;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_backend(&mut mem_backend);
+        test_selective_flush(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./dirty", 10);
+        test_backend(&mut mmap_backend);
+        test_selective_flush(&mut mmap_backend);
+    }
+
+    fn test_backend<B: GrowableBackend>(backend: &mut B) {
+        backend.clear();
+        let mut backend = DirtyBackend::new(backend);
+        backend.grow(100).unwrap();
+        be_clear(&mut backend);
+        be_remove(&mut backend);
+        be_vectored(&mut backend);
+        be_fill(&mut backend);
+        backend.flush().unwrap();
+        assert!(!backend.is_dirty());
+    }
+
+    fn test_selective_flush<B: GrowableBackend>(backend: &mut B) {
+        backend.clear();
+        let page = page_size();
+        let mut backend = DirtyBackend::new(InstrumentedBackend::new(backend));
+        backend.grow_to(page * 20).unwrap();
+        backend.push_fill(1, page * 20).unwrap();
+        assert!(backend.dirty_len() >= page * 20);
+
+        backend.flush().unwrap();
+        assert!(!backend.is_dirty());
+        assert_eq!(backend.dirty_len(), 0);
+        assert_eq!(backend.inner().stats().flushes, 1);
+
+        backend.replace_same_len(page * 10, &[2, 2]).unwrap();
+        backend.get_mut(page * 15, 1).unwrap()[0] = 3;
+        // The header page gets marked as well.
+        assert_eq!(backend.dirty_len(), page * 3);
+        backend.flush().unwrap();
+        assert_eq!(backend.inner().stats().flushes, 4);
+
+        // Nothing to flush.
+        backend.flush().unwrap();
+        assert_eq!(backend.inner().stats().flushes, 4);
+
+        backend.clear();
+        {
+            let mut map: FMap<_, u32, u32> = FMap::with_capacity(&mut backend, 10_000).unwrap();
+            for i in 0..1000 {
+                map.insert(&i, &i).unwrap();
+            }
+            map.preallocate_entries(2, 64).unwrap();
+        }
+        assert!(backend.is_dirty());
+        backend.flush().unwrap();
+        assert!(!backend.is_dirty());
+
+        // A single insertion only marks the pages it wrote to instead of the whole map.
+        let mut map: FMap<_, u32, u32> = FMap::init(&mut backend).unwrap();
+        map.insert(&1000, &1000).unwrap();
+        let dirty_len = map.backend.inner().dirty_len();
+        assert!(dirty_len > 0);
+        assert!(dirty_len <= page * 8);
+        map.flush().unwrap();
+
+        // Rolling back a failed batch only restores the data written within the batch.
+        let res = map.batch(|map| {
+            map.insert(&1001, &1001)?;
+            Err::<(), _>(Error::UnexpectedValue)
+        });
+        assert_eq!(res, Err(Error::UnexpectedValue));
+        assert_eq!(map.get(&1001u32), None);
+        assert_eq!(map.get(&1000u32), Some(1000));
+        assert!(map.backend.inner().dirty_len() <= page * 8);
+    }
+}
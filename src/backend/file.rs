@@ -0,0 +1,227 @@
+use crate::backend::base::BaseBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::growth::GrowthPolicy;
+use crate::backend::Backend;
+use crate::error::Error;
+use crate::header::BaseHeader;
+use std::fs::{File, OpenOptions};
+use std::io::Read;
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+
+pub type FileBackend = BaseBackend<FileData>;
+
+/// File storage that doesn't use memory mapping. Since backends hand out slices of their data, the content is read
+/// into a buffer once and written back to the file using positioned writes when flushing. Unflushed changes are
+/// written back when the storage gets dropped.
+pub struct FileData {
+    file: File,
+    data: Vec<u8>,
+    dirty: bool,
+}
+
+impl FileData {
+    /// Creates a new file and truncates an existing one.
+    pub fn create<P: AsRef<Path>>(path: P, size: usize) -> Result<Self, Error> {
+        fn inner(path: &Path, size: usize) -> Result<FileData, Error> {
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(path)?;
+
+            let file_size = size + BaseHeader::len_bytes();
+            file.set_len(file_size as u64)?;
+
+            FileData::from_file(file)
+        }
+
+        inner(path.as_ref(), size)
+    }
+
+    /// Loads an existing file from a path.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        fn inner(path: &Path) -> Result<FileData, Error> {
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(false)
+                .open(path)?;
+            FileData::from_file(file)
+        }
+
+        inner(path.as_ref())
+    }
+
+    /// Loads the storage directly from a `File`.
+    pub fn from_file(mut file: File) -> crate::Result<Self> {
+        let mut data = Vec::with_capacity(file.metadata()?.len() as usize);
+        file.read_to_end(&mut data)?;
+        Ok(Self {
+            file,
+            data,
+            dirty: false,
+        })
+    }
+
+    /// Writes the given range of the buffer to the file.
+    pub fn flush_range(&mut self, start: usize, len: usize) -> crate::Result<()> {
+        write_at(&mut self.file, start as u64, &self.data[start..start + len])?;
+        if start == 0 && len == self.data.len() {
+            self.dirty = false;
+        }
+        Ok(())
+    }
+}
+
+/// Writes `data` to `file` at `offset` without moving the files cursor.
+#[cfg(unix)]
+#[inline]
+fn write_at(file: &mut File, offset: u64, data: &[u8]) -> std::io::Result<()> {
+    use std::os::unix::fs::FileExt;
+    file.write_all_at(data, offset)
+}
+
+/// Writes `data` to `file` at `offset`.
+#[cfg(not(unix))]
+#[inline]
+fn write_at(file: &mut File, offset: u64, data: &[u8]) -> std::io::Result<()> {
+    use std::io::{Seek, SeekFrom, Write};
+    file.seek(SeekFrom::Start(offset))?;
+    file.write_all(data)
+}
+
+impl Drop for FileData {
+    fn drop(&mut self) {
+        if self.dirty {
+            let _ = self.flush_range(0, self.data.len());
+        }
+    }
+}
+
+impl Backend for FileBackend {
+    #[inline]
+    fn data(&self) -> &[u8] {
+        self.storage().deref()
+    }
+
+    #[inline]
+    fn data_mut(&mut self) -> &mut [u8] {
+        self.storage_mut().deref_mut()
+    }
+
+    #[inline]
+    fn first_index(&self) -> usize {
+        BaseHeader::len_bytes()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.header().data_len()
+    }
+
+    #[inline]
+    fn set_len(&mut self, len: usize) -> Result<(), Error> {
+        self.set_len(len)
+    }
+
+    #[inline]
+    fn flush_range_impl(&mut self, start: usize, len: usize) -> Result<(), Error> {
+        self.storage_mut().flush_range(start, len)
+    }
+}
+
+impl GrowableBackend for FileBackend {
+    fn resize_impl(&mut self, new_len: usize, _: bool) -> crate::Result<()> {
+        let storage = self.storage_mut();
+        storage.file.set_len(new_len as u64)?;
+        storage.data.resize(new_len, 0);
+        storage.dirty = true;
+        Ok(())
+    }
+
+    #[inline]
+    fn growth_policy(&self) -> &dyn GrowthPolicy {
+        BaseBackend::growth_policy(self)
+    }
+}
+
+impl Deref for FileData {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+impl DerefMut for FileData {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.dirty = true;
+        &mut self.data
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::super::test::*;
+    use super::*;
+    use crate::components::indexed_file::IndexedFile;
+    use crate::traits::creatable::Creatable;
+    use crate::traits::initiable::Initiable;
+
+    pub fn make_file_backend(file: &str, len: usize) -> FileBackend {
+        let path = Path::new("./testfiles");
+
+        if !path.is_dir() {
+            std::fs::create_dir_all(path).unwrap();
+        }
+
+        let path = path.join(file);
+        FileBackend::from_storage(FileData::create(path, len).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn file_backend() {
+        let mut backend = make_file_backend("./file_backend", 100);
+        assert!(backend.is_empty());
+        be_clear(&mut backend);
+        be_push(&mut backend);
+        be_replace(&mut backend);
+        be_fill(&mut backend);
+    }
+
+    #[test]
+    fn test_load() {
+        let mut backend = make_file_backend("./file_load", 10);
+        backend.push(&[10, 10, 9, 123]).unwrap();
+        backend.flush().unwrap();
+
+        let loaded = FileBackend::from_storage(FileData::load("./testfiles/file_load").unwrap()).unwrap();
+        assert_eq!(loaded.get(0, 4), Ok(&[10, 10, 9, 123][..]));
+
+        // Unflushed changes get written on drop.
+        backend.push(&[1, 2]).unwrap();
+        drop(backend);
+        let loaded = FileBackend::from_storage(FileData::load("./testfiles/file_load").unwrap()).unwrap();
+        assert_eq!(loaded.get(0, 6), Ok(&[10, 10, 9, 123, 1, 2][..]));
+    }
+
+    #[test]
+    fn test_components() {
+        let backend = make_file_backend("./file_ifile", 0);
+        let mut ifile = IndexedFile::create(backend).unwrap();
+        for i in 0..100u32 {
+            ifile.insert_t(&i.to_string()).unwrap();
+        }
+        ifile.flush().unwrap();
+        drop(ifile);
+
+        let backend = FileBackend::from_storage(FileData::load("./testfiles/file_ifile").unwrap()).unwrap();
+        let ifile = IndexedFile::init(backend).unwrap();
+        assert_eq!(ifile.count(), 100);
+        assert_eq!(ifile.get_t::<String>(42).unwrap(), "42");
+    }
+}
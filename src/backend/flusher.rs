@@ -0,0 +1,230 @@
+use crate::backend::dirty::DirtyBackend;
+use crate::backend::Backend;
+use crate::error::Error;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Owns a backend and flushes it from a background thread, so writers get durability without calling `flush()`
+/// themselves. The backend gets flushed every `interval` and, if a threshold is set, as soon as the amount of unflushed
+/// bytes exceeds it. Only pages written since the last flush get written back, see `DirtyBackend`.
+///
+/// The backend is accessed through `lock()`. It gets flushed a last time when the flusher is dropped.
+pub struct BackgroundFlusher<B: Backend + Send + 'static> {
+    shared: Arc<Shared<B>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+struct Shared<B> {
+    state: Mutex<State<B>>,
+    wakeup: Condvar,
+    threshold: Option<usize>,
+}
+
+struct State<B> {
+    backend: DirtyBackend<B>,
+    stop: bool,
+    error: Option<Error>,
+}
+
+impl<B: Backend + Send + 'static> BackgroundFlusher<B> {
+    /// Starts flushing `backend` every `interval`.
+    #[inline]
+    pub fn new(backend: B, interval: Duration) -> Self {
+        Self::start(backend, interval, None)
+    }
+
+    /// Starts flushing `backend` every `interval` or once more than `threshold` bytes are unflushed.
+    #[inline]
+    pub fn with_threshold(backend: B, interval: Duration, threshold: usize) -> Self {
+        Self::start(backend, interval, Some(threshold))
+    }
+
+    fn start(backend: B, interval: Duration, threshold: Option<usize>) -> Self {
+        let state = State {
+            backend: DirtyBackend::new(backend),
+            stop: false,
+            error: None,
+        };
+        let shared = Arc::new(Shared {
+            state: Mutex::new(state),
+            wakeup: Condvar::new(),
+            threshold,
+        });
+
+        let thread_shared = shared.clone();
+        let handle = std::thread::spawn(move || thread_shared.run(interval));
+
+        Self {
+            shared,
+            handle: Some(handle),
+        }
+    }
+
+    /// Locks the backend for reading or writing.
+    #[inline]
+    pub fn lock(&self) -> FlushGuard<'_, B> {
+        FlushGuard {
+            state: self.shared.state.lock().unwrap(),
+            shared: &self.shared,
+        }
+    }
+
+    /// Returns the error of the last failed background flush, if any.
+    #[inline]
+    pub fn take_error(&self) -> Option<Error> {
+        self.shared.state.lock().unwrap().error.take()
+    }
+
+    /// Stops the background thread, flushes the backend and returns it.
+    pub fn into_inner(mut self) -> crate::Result<B> {
+        self.stop();
+        let shared = self.shared.clone();
+        drop(self);
+
+        let state = Arc::into_inner(shared)
+            .expect("Background thread not stopped")
+            .state
+            .into_inner()
+            .unwrap();
+        if let Some(err) = state.error {
+            return Err(err);
+        }
+        Ok(state.backend.into_inner())
+    }
+
+    fn stop(&mut self) {
+        let Some(handle) = self.handle.take() else {
+            return;
+        };
+        self.shared.state.lock().unwrap().stop = true;
+        self.shared.wakeup.notify_one();
+        let _ = handle.join();
+    }
+}
+
+impl<B: Backend> Shared<B> {
+    #[inline]
+    fn over_threshold(&self, backend: &DirtyBackend<B>) -> bool {
+        self.threshold.is_some_and(|threshold| backend.dirty_len() > threshold)
+    }
+
+    fn run(&self, interval: Duration) {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if !state.stop && !self.over_threshold(&state.backend) {
+                state = self.wakeup.wait_timeout(state, interval).unwrap().0;
+            }
+
+            if state.backend.is_dirty() {
+                if let Err(err) = state.backend.flush() {
+                    state.error = Some(err);
+                }
+            }
+
+            if state.stop {
+                return;
+            }
+        }
+    }
+}
+
+impl<B: Backend + Send + 'static> Drop for BackgroundFlusher<B> {
+    #[inline]
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Exclusive access to the backend of a `BackgroundFlusher`. Wakes up the flusher when dropped if the threshold of
+/// unflushed bytes got exceeded.
+pub struct FlushGuard<'a, B: Backend> {
+    state: MutexGuard<'a, State<B>>,
+    shared: &'a Shared<B>,
+}
+
+impl<B: Backend> Drop for FlushGuard<'_, B> {
+    #[inline]
+    fn drop(&mut self) {
+        if self.shared.over_threshold(&self.state.backend) {
+            self.shared.wakeup.notify_one();
+        }
+    }
+}
+
+impl<B: Backend> Deref for FlushGuard<'_, B> {
+    type Target = DirtyBackend<B>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.state.backend
+    }
+}
+
+impl<B: Backend> DerefMut for FlushGuard<'_, B> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.state.backend
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::growable::GrowableBackend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+    use crate::backend::mmap_mut::{MmapBackendMut, MmapFileMut};
+    use crate::components::map::FMap;
+    use crate::traits::creatable::Creatable;
+    use crate::traits::initiable::Initiable;
+    use std::time::Instant;
+
+    fn wait_clean<B: Backend + Send>(flusher: &BackgroundFlusher<B>) {
+        let start = Instant::now();
+        while flusher.lock().is_dirty() {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_interval() {
+        let backend = make_mmap_backend("./flusher", 10);
+        let flusher = BackgroundFlusher::new(backend, Duration::from_millis(5));
+        {
+            let mut backend = flusher.lock();
+            backend.grow(100).unwrap();
+            backend.push(&[1, 2, 3]).unwrap();
+            assert!(backend.is_dirty());
+        }
+        wait_clean(&flusher);
+        assert!(flusher.take_error().is_none());
+
+        {
+            let mut backend = flusher.lock();
+            let mut map: FMap<_, u32, u32> = FMap::create(&mut *backend).unwrap();
+            map.insert(&1, &2).unwrap();
+        }
+        drop(flusher.into_inner().unwrap());
+
+        let backend = MmapBackendMut::from_storage(MmapFileMut::load("./testfiles/flusher").unwrap()).unwrap();
+        let map: FMap<_, u32, u32> = FMap::init(backend).unwrap();
+        assert_eq!(map.get(&1), Some(2));
+    }
+
+    #[test]
+    fn test_threshold() {
+        let backend = make_mmap_backend("./flusher_threshold", 1024 * 1024);
+        let flusher = BackgroundFlusher::with_threshold(backend, Duration::from_secs(3600), 4096);
+        flusher.lock().push_fill(1, 100).unwrap();
+
+        // Below the threshold, so nothing gets flushed for an hour.
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(flusher.lock().is_dirty());
+
+        flusher.lock().push_fill(1, 10_000).unwrap();
+        wait_clean(&flusher);
+    }
+}
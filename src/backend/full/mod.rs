@@ -1,5 +1,7 @@
+use std::ops::Range;
 use std::ptr::slice_from_raw_parts;
 use crate::backend::growable::GrowableBackend;
+use crate::backend::growth::GrowthPolicy;
 use crate::backend::Backend;
 use crate::traits::mtype::MType;
 use crate::Error;
@@ -56,6 +58,11 @@ impl<B> Backend for FullBackend<B>
         self.storage.data_mut()
     }
 
+    #[inline]
+    fn data_mut_range(&mut self, range: Range<usize>) -> &mut [u8] {
+        self.storage.data_mut_range(range)
+    }
+
     #[inline]
     fn first_index(&self) -> usize {
         self.storage.first_index()
@@ -95,4 +102,9 @@ impl<S> GrowableBackend for FullBackend<S>
     fn resize(&mut self, delta: isize) -> crate::Result<()> {
         self.storage.resize(delta)
     }
+
+    #[inline]
+    fn growth_policy(&self) -> &dyn GrowthPolicy {
+        self.storage.growth_policy()
+    }
 }
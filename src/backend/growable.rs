@@ -1,3 +1,4 @@
+use crate::backend::growth::{Double, GrowthPolicy};
 use crate::backend::Backend;
 use crate::{Error, Result};
 
@@ -16,6 +17,12 @@ pub trait GrowableBackend: Backend {
         self.resize(-(size as isize))
     }
 
+    /// Returns the policy components use to decide how much to grow this backend by.
+    #[inline]
+    fn growth_policy(&self) -> &dyn GrowthPolicy {
+        &Double
+    }
+
     /// Shrinks the backend and truncates all free bytes.
     #[inline]
     fn shrink_to_fit(&mut self) -> Result<()> {
@@ -64,4 +71,24 @@ where
     fn resize_impl(&mut self, new_size: usize, growing: bool) -> Result<()> {
         (**self).resize_impl(new_size, growing)
     }
+
+    #[inline]
+    fn grow(&mut self, size: usize) -> Result<()> {
+        (**self).grow(size)
+    }
+
+    #[inline]
+    fn shrink(&mut self, size: usize) -> Result<()> {
+        (**self).shrink(size)
+    }
+
+    #[inline]
+    fn resize(&mut self, delta: isize) -> Result<()> {
+        (**self).resize(delta)
+    }
+
+    #[inline]
+    fn growth_policy(&self) -> &dyn GrowthPolicy {
+        (**self).growth_policy()
+    }
 }
@@ -0,0 +1,118 @@
+use crate::utils::page_size;
+
+/// Strategy deciding how much a component grows once its backend is full.
+pub trait GrowthPolicy: Send + Sync {
+    /// Returns the new capacity in bytes for a component that has `capacity` bytes but needs `required` bytes, with
+    /// `required > capacity`. The returned capacity has to be at least `required`.
+    fn next_capacity(&self, capacity: usize, required: usize) -> usize;
+}
+
+/// Doubles the capacity. This is the default policy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Double;
+
+impl GrowthPolicy for Double {
+    #[inline]
+    fn next_capacity(&self, capacity: usize, required: usize) -> usize {
+        required.max(capacity * 2)
+    }
+}
+
+/// Grows the capacity by 50%. This wastes less space than `Double` at the cost of more frequent resizing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OneAndHalf;
+
+impl GrowthPolicy for OneAndHalf {
+    #[inline]
+    fn next_capacity(&self, capacity: usize, required: usize) -> usize {
+        required.max(capacity + capacity / 2)
+    }
+}
+
+/// Grows the capacity in multiples of a fixed amount of bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedChunk(pub usize);
+
+impl GrowthPolicy for FixedChunk {
+    #[inline]
+    fn next_capacity(&self, capacity: usize, required: usize) -> usize {
+        let chunk = self.0.max(1);
+        capacity + required.saturating_sub(capacity).div_ceil(chunk) * chunk
+    }
+}
+
+/// Rounds the capacity chosen by another policy up to a multiple of the systems page size.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PageAligned<P>(pub P);
+
+impl<P: GrowthPolicy> GrowthPolicy for PageAligned<P> {
+    #[inline]
+    fn next_capacity(&self, capacity: usize, required: usize) -> usize {
+        self.0.next_capacity(capacity, required).next_multiple_of(page_size())
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::growable::GrowableBackend;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+    use crate::backend::Backend;
+    use crate::components::bitvec::BitVec;
+    use crate::components::indexed_file::IndexedFile;
+    use crate::components::list::List;
+    use crate::traits::creatable::Creatable;
+
+    #[test]
+    fn test_policies() {
+        assert_eq!(Double.next_capacity(0, 3), 3);
+        assert_eq!(Double.next_capacity(10, 11), 20);
+        assert_eq!(OneAndHalf.next_capacity(10, 11), 15);
+        assert_eq!(OneAndHalf.next_capacity(10, 30), 30);
+        assert_eq!(FixedChunk(100).next_capacity(10, 11), 110);
+        assert_eq!(FixedChunk(100).next_capacity(10, 250), 310);
+
+        let aligned = PageAligned(Double).next_capacity(10, 11);
+        assert!(aligned >= 20);
+        assert_eq!(aligned % page_size(), 0);
+    }
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(0);
+        mem_backend.set_growth_policy(FixedChunk(1024));
+        test_fixed_chunk(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./growth", 0);
+        mmap_backend.set_growth_policy(FixedChunk(1024));
+        test_fixed_chunk(&mut mmap_backend);
+    }
+
+    fn test_fixed_chunk<B: GrowableBackend>(backend: &mut B) {
+        backend.clear();
+        backend.shrink_to_fit().unwrap();
+        {
+            let mut list: List<_, u32, 4> = List::create(&mut *backend).unwrap();
+            list.push(&1).unwrap();
+            assert_eq!(list.capacity(), 256);
+            list.extend(0..256);
+            assert_eq!(list.capacity(), 512);
+        }
+
+        backend.clear();
+        backend.shrink_to_fit().unwrap();
+        {
+            let mut bvec = BitVec::create(&mut *backend).unwrap();
+            let capacity = bvec.capacity();
+            bvec.push_n(capacity + 1, true).unwrap();
+            assert_eq!(bvec.capacity(), capacity + 1024 * 8);
+        }
+
+        backend.clear();
+        let mut ifile = IndexedFile::create(&mut *backend).unwrap();
+        ifile.insert(&[1, 2, 3]).unwrap();
+        assert!(ifile.inner().capacity() <= 4 * 1024);
+    }
+}
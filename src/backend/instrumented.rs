@@ -0,0 +1,340 @@
+use crate::backend::growable::GrowableBackend;
+use crate::backend::growth::GrowthPolicy;
+use crate::backend::{Advice, Backend};
+use crate::error::Error;
+use std::cell::Cell;
+use std::ops::Range;
+
+/// Counters collected by an `InstrumentedBackend`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BackendStats {
+    /// Amount of read operations.
+    pub reads: u64,
+    pub bytes_read: u64,
+    /// Amount of write operations, including handing out mutable slices.
+    pub writes: u64,
+    pub bytes_written: u64,
+    /// Amount of bytes that had to be shifted or copied within the backend, eg. when inserting into the middle.
+    pub bytes_moved: u64,
+    pub flushes: u64,
+    pub grows: u64,
+    pub shrinks: u64,
+}
+
+/// Backend wrapper counting the operations done on the wrapped backend. Accessing the raw data using `data()` or
+/// `data_mut()` directly isn't counted.
+pub struct InstrumentedBackend<B> {
+    inner: B,
+    stats: Cell<BackendStats>,
+}
+
+impl<B> InstrumentedBackend<B> {
+    #[inline]
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            stats: Cell::new(BackendStats::default()),
+        }
+    }
+
+    /// Returns the collected counters.
+    #[inline]
+    pub fn stats(&self) -> BackendStats {
+        self.stats.get()
+    }
+
+    /// Sets all counters to 0.
+    #[inline]
+    pub fn reset_stats(&self) {
+        self.stats.set(BackendStats::default());
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    #[inline]
+    fn record<F: FnOnce(&mut BackendStats)>(&self, f: F) {
+        let mut stats = self.stats.get();
+        f(&mut stats);
+        self.stats.set(stats);
+    }
+
+    #[inline]
+    fn record_read(&self, bytes: usize) {
+        self.record(|s| {
+            s.reads += 1;
+            s.bytes_read += bytes as u64;
+        });
+    }
+
+    #[inline]
+    fn record_write(&self, bytes: usize) {
+        self.record(|s| {
+            s.writes += 1;
+            s.bytes_written += bytes as u64;
+        });
+    }
+
+    #[inline]
+    fn record_move(&self, bytes: usize) {
+        self.record(|s| s.bytes_moved += bytes as u64);
+    }
+}
+
+impl<B: Backend> InstrumentedBackend<B> {
+    /// Returns the amount of bytes behind the replaced range that need to be shifted for a replace.
+    #[inline]
+    fn shifted_bytes(&self, index: usize, len: usize, new_len: usize) -> usize {
+        if len == new_len {
+            return 0;
+        }
+        self.inner.len().saturating_sub(index + len)
+    }
+}
+
+impl<B: Backend> Backend for InstrumentedBackend<B> {
+    #[inline]
+    fn data(&self) -> &[u8] {
+        self.inner.data()
+    }
+
+    #[inline]
+    fn data_mut(&mut self) -> &mut [u8] {
+        self.inner.data_mut()
+    }
+
+    #[inline]
+    fn data_mut_range(&mut self, range: Range<usize>) -> &mut [u8] {
+        self.inner.data_mut_range(range)
+    }
+
+    #[inline]
+    fn first_index(&self) -> usize {
+        self.inner.first_index()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    #[inline]
+    fn set_len(&mut self, len: usize) -> Result<(), Error> {
+        self.inner.set_len(len)
+    }
+
+    #[inline]
+    fn push(&mut self, data: &[u8]) -> Result<usize, Error> {
+        self.record_write(data.len());
+        self.inner.push(data)
+    }
+
+    #[inline]
+    fn push_fill(&mut self, data: u8, len: usize) -> Result<usize, Error> {
+        self.record_write(len);
+        self.inner.push_fill(data, len)
+    }
+
+    #[inline]
+    fn push_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, Error> {
+        self.record_write(bufs.iter().map(|i| i.len()).sum());
+        self.inner.push_vectored(bufs)
+    }
+
+    #[inline]
+    fn get(&self, index: usize, len: usize) -> Result<&[u8], Error> {
+        self.record_read(len);
+        self.inner.get(index, len)
+    }
+
+    #[inline]
+    fn get_single(&self, index: usize) -> Result<u8, Error> {
+        self.record_read(1);
+        self.inner.get_single(index)
+    }
+
+    #[inline]
+    fn get_single_unchecked(&self, index: usize) -> u8 {
+        self.record_read(1);
+        self.inner.get_single_unchecked(index)
+    }
+
+    #[inline]
+    fn get_unchecked(&self, index: usize, len: usize) -> &[u8] {
+        self.record_read(len);
+        self.inner.get_unchecked(index, len)
+    }
+
+    #[inline]
+    fn get_mut(&mut self, index: usize, len: usize) -> Result<&mut [u8], Error> {
+        self.record_write(len);
+        self.inner.get_mut(index, len)
+    }
+
+    #[inline]
+    fn swap_same_len(&mut self, a: usize, b: usize, len: usize) -> Result<(), Error> {
+        self.record_move(len * 2);
+        self.inner.swap_same_len(a, b, len)
+    }
+
+    fn replace_fill(&mut self, index: usize, len: usize, data: u8, fill_len: usize) -> Result<usize, Error> {
+        self.record_write(fill_len);
+        self.record_move(self.shifted_bytes(index, len, fill_len));
+        self.inner.replace_fill(index, len, data, fill_len)
+    }
+
+    fn replace(&mut self, index: usize, len: usize, data: &[u8]) -> Result<usize, Error> {
+        self.record_write(data.len());
+        self.record_move(self.shifted_bytes(index, len, data.len()));
+        self.inner.replace(index, len, data)
+    }
+
+    fn replace_vectored(&mut self, index: usize, len: usize, bufs: &[&[u8]]) -> Result<usize, Error> {
+        let total = bufs.iter().map(|i| i.len()).sum();
+        self.record_write(total);
+        self.record_move(self.shifted_bytes(index, len, total));
+        self.inner.replace_vectored(index, len, bufs)
+    }
+
+    #[inline]
+    fn replace_same_len_fill(&mut self, index: usize, data: u8, fill_len: usize) -> Result<usize, Error> {
+        self.record_write(fill_len);
+        self.inner.replace_same_len_fill(index, data, fill_len)
+    }
+
+    #[inline]
+    fn replace_same_len(&mut self, index: usize, data: &[u8]) -> Result<usize, Error> {
+        self.record_write(data.len());
+        self.inner.replace_same_len(index, data)
+    }
+
+    #[inline]
+    fn replace_same_len_direct(&mut self, index: usize, data: &[u8]) -> Result<usize, Error> {
+        self.record_write(data.len());
+        self.inner.replace_same_len_direct(index, data)
+    }
+
+    #[inline]
+    fn fill(&mut self, range: Range<usize>, val: u8) -> Result<(), Error> {
+        self.record_write(range.len());
+        self.inner.fill(range, val)
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.inner.clear()
+    }
+
+    #[inline]
+    fn flush_range_impl(&mut self, start: usize, len: usize) -> Result<(), Error> {
+        self.record(|s| s.flushes += 1);
+        self.inner.flush_range_impl(start, len)
+    }
+
+    #[inline]
+    fn prefetch(&self, index: usize, len: usize) {
+        self.inner.prefetch(index, len)
+    }
+
+    #[inline]
+    fn advise(&self, advice: Advice) {
+        self.inner.advise(advice)
+    }
+
+    #[inline]
+    fn move_range_to(&mut self, index: usize, len: usize, new_index: usize) -> Result<(), Error> {
+        self.record_move(len);
+        self.inner.move_range_to(index, len, new_index)
+    }
+}
+
+impl<B: GrowableBackend> InstrumentedBackend<B> {
+    #[inline]
+    fn record_resize(&self, growing: bool) {
+        if growing {
+            self.record(|s| s.grows += 1);
+        } else {
+            self.record(|s| s.shrinks += 1);
+        }
+    }
+}
+
+impl<B: GrowableBackend> GrowableBackend for InstrumentedBackend<B> {
+    #[inline]
+    fn resize_impl(&mut self, new_size: usize, growing: bool) -> crate::Result<()> {
+        self.record_resize(growing);
+        self.inner.resize_impl(new_size, growing)
+    }
+
+    // Forward to the inner implementation since wrapped backends might resize differently.
+    fn resize(&mut self, delta: isize) -> crate::Result<()> {
+        if delta != 0 {
+            self.record_resize(delta > 0);
+        }
+        self.inner.resize(delta)
+    }
+
+    #[inline]
+    fn growth_policy(&self) -> &dyn GrowthPolicy {
+        self.inner.growth_policy()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+    use crate::components::map::FMap;
+    use crate::traits::creatable::Creatable;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_stats(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./instrumented", 10);
+        test_stats(&mut mmap_backend);
+    }
+
+    fn test_stats<B: GrowableBackend>(backend: &mut B) {
+        backend.clear();
+        let mut backend = InstrumentedBackend::new(backend);
+
+        backend.grow(100).unwrap();
+        backend.push(&[1, 2, 3, 4]).unwrap();
+        backend.get(0, 2).unwrap();
+        backend.replace(0, 0, &[9, 9]).unwrap();
+        backend.flush().unwrap();
+
+        let stats = backend.stats();
+        assert_eq!(stats.grows, 1);
+        assert_eq!(stats.reads, 1);
+        assert_eq!(stats.bytes_read, 2);
+        assert_eq!(stats.writes, 2);
+        assert_eq!(stats.bytes_written, 6);
+        assert_eq!(stats.bytes_moved, 4);
+        assert_eq!(stats.flushes, 1);
+        assert_eq!(backend.get(0, 6), Ok(&[9, 9, 1, 2, 3, 4][..]));
+
+        backend.reset_stats();
+        backend.clear();
+        {
+            let mut map: FMap<_, u32, u32> = FMap::create(&mut backend).unwrap();
+            for i in 0..100 {
+                map.insert(&i, &i).unwrap();
+            }
+        }
+        let stats = backend.stats();
+        assert!(stats.grows > 0);
+        assert!(stats.writes > 0);
+    }
+}
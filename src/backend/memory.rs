@@ -1,5 +1,6 @@
 use crate::backend::base::BaseBackend;
 use crate::backend::growable::GrowableBackend;
+use crate::backend::growth::GrowthPolicy;
 use crate::backend::Backend;
 use crate::error::Error;
 use crate::header::BaseHeader;
@@ -16,6 +17,46 @@ impl MemoryData {
     pub fn new(data: Vec<u8>) -> Self {
         Self { data }
     }
+
+    /// Resizes the buffer to `new_len` bytes. Returns `Error::AllocationFailed` instead of aborting if the memory
+    /// can't be allocated.
+    pub fn try_resize(&mut self, new_len: usize) -> crate::Result<()> {
+        let additional = new_len.saturating_sub(self.data.len());
+        self.data.try_reserve_exact(additional).map_err(|_| Error::AllocationFailed)?;
+        self.data.resize(new_len, 0u8);
+        Ok(())
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+impl MemoryBackend {
+    /// Creates a new empty backend with a capacity of `size` bytes.
+    #[inline]
+    pub fn new(size: usize) -> Self {
+        Self::from_storage(MemoryData::new(vec![0u8; size + BaseHeader::len_bytes()])).unwrap()
+    }
+
+    /// Loads a backend from bytes returned by `into_bytes`, eg. after reading them from a file or IndexedDB.
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, Error> {
+        if bytes.len() < BaseHeader::len_bytes() {
+            return Err(Error::InvalidHeader);
+        }
+        let backend = Self::from_storage(MemoryData::new(bytes))?;
+        if backend.len() > backend.data().len() - BaseHeader::len_bytes() {
+            return Err(Error::InvalidHeader);
+        }
+        Ok(backend)
+    }
+
+    /// Returns all bytes of the backend including its header so they can be persisted.
+    #[inline]
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.into_storage().into_inner()
+    }
 }
 
 impl Backend for MemoryBackend {
@@ -66,8 +107,12 @@ impl Backend for MemoryBackend {
 impl GrowableBackend for MemoryBackend {
     #[inline]
     fn resize_impl(&mut self, new_size: usize, _: bool) -> crate::Result<()> {
-        self.storage_mut().data.resize(new_size, 0u8);
-        Ok(())
+        self.storage_mut().try_resize(new_size)
+    }
+
+    #[inline]
+    fn growth_policy(&self) -> &dyn GrowthPolicy {
+        BaseBackend::growth_policy(self)
     }
 }
 
@@ -113,6 +158,7 @@ pub mod test {
         be_replace(&mut backend);
         be_push(&mut backend);
         be_remove(&mut backend);
+        be_vectored(&mut backend);
         be_fill(&mut backend);
     }
 
@@ -124,4 +170,29 @@ pub mod test {
         let sub = big_backend.sub_backend_mut(100).unwrap();
         assert_eq!(sub.capacity(), 100);
     }
+
+    #[test]
+    fn test_bytes() {
+        let mut backend = MemoryBackend::new(10);
+        be_clear(&mut backend);
+        backend.push(&[1, 2, 3]).unwrap();
+
+        let backend = MemoryBackend::from_bytes(backend.into_bytes()).unwrap();
+        assert_eq!(backend.get(0, 3), Ok(&[1, 2, 3][..]));
+
+        assert_eq!(MemoryBackend::from_bytes(vec![1; 4]).err(), Some(Error::InvalidHeader));
+        assert_eq!(MemoryBackend::from_bytes(vec![0xFF; 16]).err(), Some(Error::InvalidHeader));
+    }
+
+    #[test]
+    fn test_allocation_failed() {
+        let mut backend = make_mem_backend(10);
+        backend.push(&[1, 2, 3]).unwrap();
+        assert_eq!(backend.grow(isize::MAX as usize / 2), Err(Error::AllocationFailed));
+        assert_eq!(backend.capacity(), 10);
+        assert_eq!(backend.get(0, 3), Ok(&[1, 2, 3][..]));
+
+        backend.grow(10).unwrap();
+        assert_eq!(backend.capacity(), 20);
+    }
 }
@@ -0,0 +1,249 @@
+use crate::backend::growable::GrowableBackend;
+use crate::backend::growth::GrowthPolicy;
+use crate::backend::{Advice, Backend};
+use crate::error::Error;
+use std::ops::Range;
+
+/// Backend applying every write to two backends, eg. a memory backend and a memory mapped file. Reads are served from
+/// the primary backend and flushing flushes both backends.
+///
+/// Writes through the raw slices returned by `data_mut()` or `get_mut()` only reach the primary backend directly and
+/// get copied to the mirror when flushing.
+pub struct MirrorBackend<A, B> {
+    primary: A,
+    mirror: B,
+}
+
+impl<A: GrowableBackend, B: GrowableBackend> MirrorBackend<A, B> {
+    /// Creates a new `MirrorBackend`. The mirror gets resized to the size of `primary` and overwritten with its
+    /// content. Both backends need the same header length.
+    pub fn new(primary: A, mut mirror: B) -> crate::Result<Self> {
+        if primary.first_index() != mirror.first_index() {
+            return Err(Error::Initialization);
+        }
+
+        let delta = primary.data().len() as isize - mirror.data().len() as isize;
+        if delta != 0 {
+            // The mirror may not be shrinkable below its length.
+            mirror.set_len(0)?;
+            mirror.resize(delta)?;
+        }
+
+        let mut backend = Self { primary, mirror };
+        backend.sync_range(0, backend.primary.data().len())?;
+        Ok(backend)
+    }
+}
+
+impl<A: Backend, B: Backend> MirrorBackend<A, B> {
+    #[inline]
+    pub fn primary(&self) -> &A {
+        &self.primary
+    }
+
+    #[inline]
+    pub fn mirror(&self) -> &B {
+        &self.mirror
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> (A, B) {
+        (self.primary, self.mirror)
+    }
+
+    /// Copies the given range of the primary backend into the mirror.
+    fn sync_range(&mut self, start: usize, len: usize) -> crate::Result<()> {
+        let src = &self.primary.data()[start..start + len];
+        self.mirror.data_mut()[start..start + len].copy_from_slice(src);
+        self.mirror.set_len(self.primary.len())
+    }
+}
+
+impl<A: Backend, B: Backend> Backend for MirrorBackend<A, B> {
+    #[inline]
+    fn data(&self) -> &[u8] {
+        self.primary.data()
+    }
+
+    #[inline]
+    fn data_mut(&mut self) -> &mut [u8] {
+        self.primary.data_mut()
+    }
+
+    #[inline]
+    fn first_index(&self) -> usize {
+        self.primary.first_index()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.primary.len()
+    }
+
+    #[inline]
+    fn set_len(&mut self, len: usize) -> Result<(), Error> {
+        self.primary.set_len(len)?;
+        self.mirror.set_len(len)
+    }
+
+    #[inline]
+    fn push(&mut self, data: &[u8]) -> Result<usize, Error> {
+        let res = self.primary.push(data)?;
+        self.mirror.push(data)?;
+        Ok(res)
+    }
+
+    #[inline]
+    fn push_fill(&mut self, data: u8, len: usize) -> Result<usize, Error> {
+        let res = self.primary.push_fill(data, len)?;
+        self.mirror.push_fill(data, len)?;
+        Ok(res)
+    }
+
+    #[inline]
+    fn push_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, Error> {
+        let res = self.primary.push_vectored(bufs)?;
+        self.mirror.push_vectored(bufs)?;
+        Ok(res)
+    }
+
+    #[inline]
+    fn get(&self, index: usize, len: usize) -> Result<&[u8], Error> {
+        self.primary.get(index, len)
+    }
+
+    #[inline]
+    fn swap_same_len(&mut self, a: usize, b: usize, len: usize) -> Result<(), Error> {
+        self.primary.swap_same_len(a, b, len)?;
+        self.mirror.swap_same_len(a, b, len)
+    }
+
+    #[inline]
+    fn replace_fill(&mut self, index: usize, len: usize, data: u8, fill_len: usize) -> Result<usize, Error> {
+        let res = self.primary.replace_fill(index, len, data, fill_len)?;
+        self.mirror.replace_fill(index, len, data, fill_len)?;
+        Ok(res)
+    }
+
+    #[inline]
+    fn replace(&mut self, index: usize, len: usize, data: &[u8]) -> Result<usize, Error> {
+        let res = self.primary.replace(index, len, data)?;
+        self.mirror.replace(index, len, data)?;
+        Ok(res)
+    }
+
+    fn replace_vectored(&mut self, index: usize, len: usize, bufs: &[&[u8]]) -> Result<usize, Error> {
+        let res = self.primary.replace_vectored(index, len, bufs)?;
+        self.mirror.replace_vectored(index, len, bufs)?;
+        Ok(res)
+    }
+
+    #[inline]
+    fn replace_same_len_fill(&mut self, index: usize, data: u8, fill_len: usize) -> Result<usize, Error> {
+        let res = self.primary.replace_same_len_fill(index, data, fill_len)?;
+        self.mirror.replace_same_len_fill(index, data, fill_len)?;
+        Ok(res)
+    }
+
+    #[inline]
+    fn replace_same_len(&mut self, index: usize, data: &[u8]) -> Result<usize, Error> {
+        let res = self.primary.replace_same_len(index, data)?;
+        self.mirror.replace_same_len(index, data)?;
+        Ok(res)
+    }
+
+    #[inline]
+    fn replace_same_len_direct(&mut self, index: usize, data: &[u8]) -> Result<usize, Error> {
+        let res = self.primary.replace_same_len_direct(index, data)?;
+        self.mirror.replace_same_len_direct(index, data)?;
+        Ok(res)
+    }
+
+    #[inline]
+    fn fill(&mut self, range: Range<usize>, val: u8) -> Result<(), Error> {
+        self.primary.fill(range.clone(), val)?;
+        self.mirror.fill(range, val)
+    }
+
+    fn flush_range_impl(&mut self, start: usize, len: usize) -> Result<(), Error> {
+        self.sync_range(start, len)?;
+        self.primary.flush_range_impl(start, len)?;
+        self.mirror.flush_range_impl(start, len)
+    }
+
+    #[inline]
+    fn prefetch(&self, index: usize, len: usize) {
+        self.primary.prefetch(index, len)
+    }
+
+    #[inline]
+    fn advise(&self, advice: Advice) {
+        self.primary.advise(advice)
+    }
+
+    #[inline]
+    fn move_range_to(&mut self, index: usize, len: usize, new_index: usize) -> Result<(), Error> {
+        self.primary.move_range_to(index, len, new_index)?;
+        self.mirror.move_range_to(index, len, new_index)
+    }
+}
+
+impl<A: GrowableBackend, B: GrowableBackend> GrowableBackend for MirrorBackend<A, B> {
+    fn resize_impl(&mut self, new_size: usize, growing: bool) -> crate::Result<()> {
+        self.mirror.resize_impl(new_size, growing)?;
+        self.primary.resize_impl(new_size, growing)
+    }
+
+    #[inline]
+    fn growth_policy(&self) -> &dyn GrowthPolicy {
+        self.primary.growth_policy()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::super::test::*;
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+    use crate::backend::mmap_mut::{MmapBackendMut, MmapFileMut};
+    use crate::components::map::FMap;
+    use crate::traits::creatable::Creatable;
+    use crate::traits::initiable::Initiable;
+
+    #[test]
+    fn mirror_backend() {
+        let mut backend = MirrorBackend::new(make_mem_backend(100), make_mmap_backend("./mirror", 10)).unwrap();
+        assert_eq!(backend.mirror().capacity(), 100);
+        be_clear(&mut backend);
+        be_remove(&mut backend);
+        be_vectored(&mut backend);
+        be_fill(&mut backend);
+        backend.clear();
+        backend.push(&[1, 2, 3, 4]).unwrap();
+        backend.replace(1, 2, &[9]).unwrap();
+        backend.swap_same_len(0, 2, 1).unwrap();
+        assert_eq!(backend.get(0, 3), Ok(&[4, 9, 1][..]));
+        assert_eq!(backend.primary().content_data(), backend.mirror().content_data());
+    }
+
+    #[test]
+    fn test_mirror_map() {
+        let mut backend = MirrorBackend::new(make_mem_backend(10), make_mmap_backend("./mirror_map", 10)).unwrap();
+        {
+            let mut map: FMap<_, u32, u32> = FMap::create(&mut backend).unwrap();
+            for i in 0..1000 {
+                map.insert(&i, &(i * 2)).unwrap();
+            }
+        }
+        backend.flush().unwrap();
+        assert_eq!(backend.primary().data(), backend.mirror().data());
+        drop(backend);
+
+        let backend = MmapBackendMut::from_storage(MmapFileMut::load("./testfiles/mirror_map").unwrap()).unwrap();
+        let map: FMap<_, u32, u32> = FMap::init(backend).unwrap();
+        assert_eq!(map.len(), 1000);
+        assert_eq!(map.get(&999), Some(1998));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::backend::base::BaseBackend;
+use crate::backend::mmap_mut::MmapFileMut;
 use crate::backend::Backend;
 use crate::error::Error;
 use crate::header::BaseHeader;
@@ -35,6 +36,18 @@ impl MmapFile {
     }
 }
 
+impl MmapBackend {
+    /// Creates a read only backend holding a copy of `content`. The data gets stored in a temporary file which is
+    /// removed right away on unix, so it only lives as long as the backend.
+    pub fn from_content(content: &[u8]) -> Result<Self, Error> {
+        let mut temp = MmapFileMut::create_temp(content.len())?;
+        let header_len = BaseHeader::len_bytes();
+        temp[..header_len].copy_from_slice(&BaseHeader::new(content.len()).bytes());
+        temp[header_len..].copy_from_slice(content);
+        Self::from_storage(MmapFile::from_file(temp.file().try_clone()?)?)
+    }
+}
+
 impl Backend for MmapBackend {
     #[inline]
     fn data(&self) -> &[u8] {
@@ -78,7 +91,40 @@ impl Deref for MmapFile {
 
 #[cfg(test)]
 pub mod test {
-    // use super::*;
+    use super::*;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+    use crate::components::indexed_file::IndexedFile;
+    use crate::components::map::FMap;
+    use crate::components::multi_file::MultiFile;
+    use crate::traits::creatable::Creatable;
+    use crate::traits::initiable::Initiable;
+
+    #[test]
+    fn test_readonly() {
+        {
+            let backend = make_mmap_backend("./readonly", 10);
+            let mut mfile = MultiFile::create(backend).unwrap();
+            {
+                let mut map = mfile.insert_new_backend::<FMap<_, u32, u32>>().unwrap();
+                for i in 0..100 {
+                    map.insert(&i, &(i * 3)).unwrap();
+                }
+            }
+            {
+                let mut ifile = mfile.insert_new_backend::<IndexedFile<_>>().unwrap();
+                ifile.insert_t(&"hello").unwrap();
+            }
+            mfile.flush().unwrap();
+        }
+
+        let backend = MmapBackend::from_storage(MmapFile::load("./testfiles/readonly").unwrap()).unwrap();
+        let mfile = MultiFile::init(backend).unwrap();
 
-    // TODO!
+        let map = FMap::<_, u32, u32>::init_readonly(mfile.get(0).unwrap()).unwrap();
+        assert_eq!(map.len(), 100);
+        assert_eq!(map.get(&99), Some(297));
+
+        let ifile = IndexedFile::init_readonly(mfile.get(1).unwrap()).unwrap();
+        assert_eq!(ifile.get_t::<String>(0).unwrap(), "hello");
+    }
 }
@@ -0,0 +1,129 @@
+use crate::backend::base::BaseBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::growth::GrowthPolicy;
+use crate::backend::Backend;
+use crate::error::Error;
+use crate::header::BaseHeader;
+use memmap2::{MmapMut, RemapOptions};
+use std::ops::{Deref, DerefMut};
+
+pub type MmapAnonBackend = BaseBackend<MmapAnon>;
+
+/// Anonymous memory map that isn't backed by a file. Useful for large temporary data that should live in virtual
+/// memory without touching disk.
+pub struct MmapAnon {
+    map: MmapMut,
+}
+
+impl MmapAnon {
+    /// Creates a new anonymous map holding `size` bytes of data.
+    pub fn new(size: usize) -> Result<Self, Error> {
+        let map = MmapMut::map_anon(size + BaseHeader::len_bytes())?;
+        Ok(Self { map })
+    }
+}
+
+impl MmapAnonBackend {
+    /// Creates a new empty backend with a capacity of `size` bytes.
+    #[inline]
+    pub fn new(size: usize) -> Result<Self, Error> {
+        Self::from_storage(MmapAnon::new(size)?)
+    }
+}
+
+impl Backend for MmapAnonBackend {
+    #[inline]
+    fn data(&self) -> &[u8] {
+        self.storage().deref()
+    }
+
+    #[inline]
+    fn data_mut(&mut self) -> &mut [u8] {
+        self.storage_mut().deref_mut()
+    }
+
+    #[inline]
+    fn first_index(&self) -> usize {
+        BaseHeader::len_bytes()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.header().data_len()
+    }
+
+    #[inline]
+    fn set_len(&mut self, len: usize) -> Result<(), Error> {
+        self.set_len(len)
+    }
+}
+
+impl GrowableBackend for MmapAnonBackend {
+    fn resize_impl(&mut self, new_len: usize, _: bool) -> crate::Result<()> {
+        unsafe {
+            self.storage_mut()
+                .map
+                .remap(new_len, RemapOptions::new().may_move(true))?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn growth_policy(&self) -> &dyn GrowthPolicy {
+        BaseBackend::growth_policy(self)
+    }
+}
+
+impl Deref for MmapAnon {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.map.deref()
+    }
+}
+
+impl DerefMut for MmapAnon {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.map.deref_mut()
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::super::test::*;
+    use super::*;
+    use crate::components::map::FMap;
+    use crate::traits::creatable::Creatable;
+
+    #[test]
+    fn mmap_anon_backend() {
+        let mut backend = MmapAnonBackend::new(100).unwrap();
+        assert!(backend.is_empty());
+        assert_eq!(backend.capacity(), 100);
+        be_clear(&mut backend);
+        be_push(&mut backend);
+        be_replace(&mut backend);
+        be_fill(&mut backend);
+
+        let mut big_backend = MmapAnonBackend::new(1024 * 1024).unwrap();
+        be_stresstest(&mut big_backend);
+    }
+
+    #[test]
+    fn test_grow() {
+        let mut backend = MmapAnonBackend::new(0).unwrap();
+        backend.grow(10).unwrap();
+        backend.push(&[1; 10]).unwrap();
+        backend.grow(1024 * 1024).unwrap();
+        backend.push(&[2; 1024]).unwrap();
+        assert_eq!(backend.get(0, 11), Ok(&[1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 2][..]));
+
+        let mut map: FMap<_, u32, u32> = FMap::create(MmapAnonBackend::new(0).unwrap()).unwrap();
+        for i in 0..1000 {
+            map.insert(&i, &(i * 2)).unwrap();
+        }
+        assert_eq!(map.get(&500), Some(1000));
+    }
+}
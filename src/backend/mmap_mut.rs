@@ -1,32 +1,41 @@
 use crate::backend::base::BaseBackend;
 use crate::backend::growable::GrowableBackend;
+use crate::backend::growth::GrowthPolicy;
 use crate::backend::Backend;
 use crate::error::Error;
 use crate::header::BaseHeader;
+use crate::utils::lock_file;
 use memmap2::{MmapMut, MmapOptions, RemapOptions};
 use std::fs::{File, OpenOptions};
 use std::ops::{Deref, DerefMut};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 pub type MmapBackendMut = BaseBackend<MmapFileMut>;
 
 pub struct MmapFileMut {
     file: File,
     map: MmapMut,
+    huge_pages: bool,
+    /// Path of a temporary file that couldn't be removed right after creating it.
+    temp_path: Option<PathBuf>,
 }
 
 impl MmapFileMut {
-    /// Creates a new file and truncase an existing one.
+    /// Creates a new file and truncase an existing one. The file gets locked exclusively, see `load`.
     pub fn create<P: AsRef<Path>>(path: P, size: usize) -> Result<Self, Error> {
         fn inner(path: &Path, size: usize) -> Result<MmapFileMut, Error> {
+            // Truncate after locking so a file that's in use doesn't get cleared.
             let file = OpenOptions::new()
                 .read(true)
                 .write(true)
                 .create(true)
-                .truncate(true)
+                .truncate(false)
                 .open(path)?;
+            lock_file(&file, true)?;
 
             let file_size = size + BaseHeader::len_bytes();
+            file.set_len(0)?;
             file.set_len(file_size as u64)?;
 
             MmapFileMut::from_file(file)
@@ -35,7 +44,8 @@ impl MmapFileMut {
         inner(path.as_ref(), size)
     }
 
-    /// Loads an existing mmap file from a path read only.
+    /// Loads an existing mmap file from a path read only. The file gets a shared lock, so other readers can open it too
+    /// but opening it for writing fails with `Error::Locked` until it gets dropped.
     pub fn load_ro<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
         fn inner(path: &Path) -> Result<MmapFileMut, Error> {
             let file = OpenOptions::new()
@@ -43,13 +53,16 @@ impl MmapFileMut {
                 .write(false)
                 .create(false)
                 .open(path)?;
+            lock_file(&file, false)?;
             MmapFileMut::from_file(file)
         }
 
         inner(path.as_ref())
     }
 
-    /// Loads an existing mmap file from a path.
+    /// Loads an existing mmap file from a path. The file gets locked exclusively until it gets dropped, so opening it a
+    /// second time, even within the same process, fails with `Error::Locked`. Locks are advisory and only available on
+    /// unix systems.
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
         fn inner(path: &Path) -> Result<MmapFileMut, Error> {
             let file = OpenOptions::new()
@@ -57,6 +70,7 @@ impl MmapFileMut {
                 .write(true)
                 .create(false)
                 .open(path)?;
+            lock_file(&file, true)?;
             MmapFileMut::from_file(file)
         }
 
@@ -66,14 +80,173 @@ impl MmapFileMut {
     /// Loads a MapStore directly from a `File`.
     pub fn from_file(file: File) -> crate::Result<Self> {
         let map = unsafe { MmapOptions::new().map_mut(&file)? };
-        Ok(MmapFileMut { file, map })
+        Ok(MmapFileMut {
+            file,
+            map,
+            huge_pages: false,
+            temp_path: None,
+        })
     }
 
+    /// Creates a temporary file in the systems temp directory which gets removed once it is dropped.
+    #[inline]
+    pub fn create_temp(size: usize) -> Result<Self, Error> {
+        Self::create_temp_in(std::env::temp_dir(), size)
+    }
+
+    /// Creates a temporary file in `dir` which gets removed once it is dropped. On unix the file gets unlinked right
+    /// away so it doesn't outlive the process even if it crashes.
+    pub fn create_temp_in<P: AsRef<Path>>(dir: P, size: usize) -> Result<Self, Error> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let (path, file) = loop {
+            let name = format!(
+                "bytestore-{}-{}.tmp",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            );
+            let path = dir.as_ref().join(name);
+            match OpenOptions::new().read(true).write(true).create_new(true).open(&path) {
+                Ok(file) => break (path, file),
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err.into()),
+            }
+        };
+
+        file.set_len((size + BaseHeader::len_bytes()) as u64)?;
+        let mut mmap = Self::from_file(file)?;
+
+        if cfg!(unix) {
+            std::fs::remove_file(&path)?;
+        } else {
+            mmap.temp_path = Some(path);
+        }
+
+        Ok(mmap)
+    }
+
+    /// Same as `create` but requests transparent huge pages for the mapping. This reduces TLB misses for random
+    /// accesses on large files, eg. lookups in big `FMap`s.
+    pub fn create_huge<P: AsRef<Path>>(path: P, size: usize) -> Result<Self, Error> {
+        let mut file = Self::create(path, size)?;
+        file.set_huge_pages(true);
+        Ok(file)
+    }
+
+    /// Enables or disables transparent huge pages for the mapping. The setting is kept when the file gets remapped.
+    /// This is only a hint and a no-op on systems other than linux or kernels without huge page support.
+    pub fn set_huge_pages(&mut self, enabled: bool) {
+        self.huge_pages = enabled;
+        self.apply_huge_pages();
+    }
+
+    /// Returns `true` if transparent huge pages have been requested for the mapping.
+    #[inline]
+    pub fn huge_pages(&self) -> bool {
+        self.huge_pages
+    }
+
+    #[cfg(target_os = "linux")]
+    fn apply_huge_pages(&self) {
+        use memmap2::Advice;
+        let advice = if self.huge_pages { Advice::HugePage } else { Advice::NoHugePage };
+        let _ = self.map.advise(advice);
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    #[inline]
+    fn apply_huge_pages(&self) {}
+
     #[inline]
     pub fn flush_range(&mut self, start: usize, len: usize) -> crate::Result<()> {
         self.map.flush_range(start, len)?;
         Ok(())
     }
+
+    /// Sets the files size to `new_len` bytes and remaps it.
+    pub(crate) fn resize(&mut self, new_len: usize) -> crate::Result<()> {
+        self.file.set_len(new_len as u64)?;
+        unsafe {
+            self.map
+                .remap(new_len, RemapOptions::new().may_move(true))?;
+        }
+        if self.huge_pages {
+            self.apply_huge_pages();
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub(crate) fn file(&self) -> &File {
+        &self.file
+    }
+
+    /// Reserves disk space for `bytes` bytes of data (excluding the header) so later writes into this area can't fail
+    /// due to a full disk. Grows the file if it's smaller than that.
+    pub fn preallocate(&mut self, bytes: usize) -> crate::Result<()> {
+        let file_size = bytes + BaseHeader::len_bytes();
+        allocate(&self.file, file_size)?;
+
+        if file_size > self.map.len() {
+            unsafe {
+                self.map
+                    .remap(file_size, RemapOptions::new().may_move(true))?;
+            }
+            if self.huge_pages {
+                self.apply_huge_pages();
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Allocates the first `len` bytes of `file` on disk.
+#[cfg(target_os = "linux")]
+fn allocate(file: &File, len: usize) -> crate::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let res = unsafe { libc::posix_fallocate(file.as_raw_fd(), 0, len as libc::off_t) };
+    if res != 0 {
+        return Err(std::io::Error::from_raw_os_error(res).into());
+    }
+    Ok(())
+}
+
+/// Allocates the first `len` bytes of `file` on disk. Without `fallocate` support this only grows the file.
+#[cfg(not(target_os = "linux"))]
+fn allocate(file: &File, len: usize) -> crate::Result<()> {
+    if file.metadata()?.len() < len as u64 {
+        file.set_len(len as u64)?;
+    }
+    Ok(())
+}
+
+impl MmapBackendMut {
+    /// Loads the file at `path` if it exists and creates a new one with a capacity of `initial_size` bytes otherwise.
+    /// Returns `Error::InvalidHeader` if an existing file doesn't contain a valid header.
+    pub fn open_or_create<P: AsRef<Path>>(path: P, initial_size: usize) -> crate::Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Self::from_storage(MmapFileMut::create(path, initial_size)?);
+        }
+
+        let storage = MmapFileMut::load(path)?;
+        if storage.len() < BaseHeader::len_bytes() {
+            return Err(Error::InvalidHeader);
+        }
+        let backend = Self::from_storage(storage)?;
+        if backend.len() > backend.data().len() - BaseHeader::len_bytes() {
+            return Err(Error::InvalidHeader);
+        }
+        Ok(backend)
+    }
+
+    /// Reserves disk space for `bytes` bytes of data. The capacity of the backend grows accordingly if it was smaller.
+    #[inline]
+    pub fn preallocate(&mut self, bytes: usize) -> crate::Result<()> {
+        self.storage_mut().preallocate(bytes)
+    }
 }
 
 impl Backend for MmapBackendMut {
@@ -112,15 +285,20 @@ impl Backend for MmapBackendMut {
 impl GrowableBackend for MmapBackendMut {
     fn resize_impl(&mut self, new_len: usize, _: bool) -> crate::Result<()> {
         // println!("Growing: {new_len}");
-        self.storage_mut().file.set_len(new_len as u64).unwrap();
-        unsafe {
-            self.storage_mut()
-                .map
-                .remap(new_len, RemapOptions::new().may_move(true))
-                .unwrap();
-        }
+        self.storage_mut().resize(new_len)
+    }
 
-        Ok(())
+    #[inline]
+    fn growth_policy(&self) -> &dyn GrowthPolicy {
+        BaseBackend::growth_policy(self)
+    }
+}
+
+impl Drop for MmapFileMut {
+    fn drop(&mut self) {
+        if let Some(path) = self.temp_path.take() {
+            let _ = std::fs::remove_file(path);
+        }
     }
 }
 
@@ -144,6 +322,9 @@ impl DerefMut for MmapFileMut {
 pub mod test {
     use super::super::test::*;
     use super::*;
+    use crate::backend::Advice;
+    use crate::components::map::FMap;
+    use crate::traits::creatable::OpenOrInit;
 
     pub fn make_mmap_backend(file: &str, len: usize) -> MmapBackendMut {
         let path = Path::new("./testfiles");
@@ -168,6 +349,127 @@ pub mod test {
         assert_eq!(loaded_backend.get(0, 4), Ok(&[10, 10, 9, 123][..]));
     }
 
+    #[test]
+    fn test_open_or_create() {
+        let path = Path::new("./testfiles/open_or_create");
+        let _ = std::fs::remove_file(path);
+        {
+            let backend = MmapBackendMut::open_or_create(path, 10).unwrap();
+            let mut map: FMap<_, u32, u32> = FMap::open_or_init(backend).unwrap();
+            assert!(map.is_empty());
+            map.insert(&1, &2).unwrap();
+            map.flush().unwrap();
+        }
+
+        let backend = MmapBackendMut::open_or_create(path, 10).unwrap();
+        let map: FMap<_, u32, u32> = FMap::open_or_init(backend).unwrap();
+        assert_eq!(map.get(&1), Some(2));
+
+        let broken = Path::new("./testfiles/open_or_create_broken");
+        std::fs::write(broken, [0xFF; 16]).unwrap();
+        assert_eq!(MmapBackendMut::open_or_create(broken, 10).err(), Some(Error::InvalidHeader));
+        std::fs::write(broken, [1; 4]).unwrap();
+        assert_eq!(MmapBackendMut::open_or_create(broken, 10).err(), Some(Error::InvalidHeader));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_lock() {
+        let path = "./testfiles/locked";
+        let mut writer = make_mmap_backend("./locked", 10);
+        writer.push(&[1, 2, 3]).unwrap();
+
+        assert_eq!(MmapFileMut::load(path).err(), Some(Error::Locked));
+        assert_eq!(MmapFileMut::load_ro(path).err(), Some(Error::Locked));
+        assert_eq!(MmapFileMut::create(path, 10).err(), Some(Error::Locked));
+        assert_eq!(writer.get(0, 3), Ok(&[1, 2, 3][..]));
+        drop(writer);
+
+        // Readers can share a lock
+        let reader = File::open(path).unwrap();
+        lock_file(&reader, false).unwrap();
+        let reader2 = File::open(path).unwrap();
+        lock_file(&reader2, false).unwrap();
+        assert_eq!(MmapFileMut::load(path).err(), Some(Error::Locked));
+        drop(reader);
+        drop(reader2);
+
+        let backend = MmapBackendMut::from_storage(MmapFileMut::load(path).unwrap()).unwrap();
+        assert_eq!(backend.get(0, 3), Ok(&[1, 2, 3][..]));
+    }
+
+    #[test]
+    fn test_temp() {
+        let storage = MmapFileMut::create_temp(10).unwrap();
+        let mut backend = MmapBackendMut::from_storage(storage).unwrap();
+        be_clear(&mut backend);
+        backend.grow(1024 * 1024).unwrap();
+        be_stresstest(&mut backend);
+        backend.flush().unwrap();
+
+        let tmp_files = || {
+            let prefix = format!("bytestore-{}-", std::process::id());
+            std::fs::read_dir(std::env::temp_dir())
+                .unwrap()
+                .filter(|i| i.as_ref().unwrap().file_name().to_string_lossy().starts_with(&prefix))
+                .count()
+        };
+        #[cfg(unix)]
+        assert_eq!(tmp_files(), 0);
+        drop(backend);
+        assert_eq!(tmp_files(), 0);
+    }
+
+    #[test]
+    fn test_advise() {
+        let mut backend = make_mmap_backend("./advise", 64 * 1024);
+        backend.push_fill(3, 64 * 1024).unwrap();
+
+        backend.advise_sequential();
+        backend.advise_random();
+        backend.advise_willneed(100..5000);
+        // Out of bounds ranges are ignored.
+        backend.advise_willneed(0..usize::MAX / 2);
+        backend.advise(Advice::Normal);
+        assert!(backend.content_data().iter().all(|i| *i == 3));
+    }
+
+    #[test]
+    fn test_preallocate() {
+        let mut backend = make_mmap_backend("./preallocate", 10);
+        backend.push(&[1, 2, 3]).unwrap();
+
+        backend.preallocate(4096).unwrap();
+        assert_eq!(backend.capacity(), 4096);
+        assert_eq!(backend.get(0, 3), Ok(&[1, 2, 3][..]));
+
+        // Never shrinks the backend.
+        backend.preallocate(100).unwrap();
+        assert_eq!(backend.capacity(), 4096);
+
+        backend.push_fill(9, 4093).unwrap();
+        assert!(backend.is_full());
+    }
+
+    #[test]
+    fn test_huge_pages() {
+        let path = Path::new("./testfiles");
+        if !path.is_dir() {
+            std::fs::create_dir_all(path).unwrap();
+        }
+
+        let file = MmapFileMut::create_huge(path.join("huge"), 4 * 1024 * 1024).unwrap();
+        assert!(file.huge_pages());
+        let mut backend = MmapBackendMut::from_storage(file).unwrap();
+        backend.push_fill(1, 1024).unwrap();
+        backend.grow(4 * 1024 * 1024).unwrap();
+        assert!(backend.storage().huge_pages());
+        assert_eq!(backend.get(1023, 1), Ok(&[1][..]));
+
+        backend.storage_mut().set_huge_pages(false);
+        assert!(!backend.storage().huge_pages());
+    }
+
     #[test]
     fn mmap_backend() {
         let small_backend = make_mmap_backend("./stest", 100);
@@ -186,6 +488,7 @@ pub mod test {
         be_push(&mut backend);
         be_replace(&mut backend);
         be_replace(&mut backend);
+        be_vectored(&mut backend);
         be_fill(&mut backend);
 
         if backend.capacity() > 61 * 1024 {
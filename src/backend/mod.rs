@@ -1,4 +1,4 @@
-use crate::deser::{deserialize_impl, serialize_impl};
+use crate::deser::{deserialize_impl, deserialize_into, serialize_impl, ScratchBuffer};
 use crate::error::Error;
 use crate::utils::ranges_overlap;
 use serde::de::DeserializeOwned;
@@ -6,11 +6,47 @@ use serde::Serialize;
 use std::ops::Range;
 
 pub mod base;
+pub mod bounded;
+#[cfg(feature = "lz4_flex")]
+pub mod compressed;
+#[cfg(unix)]
+pub mod direct;
+pub mod dirty;
+pub mod file;
+pub mod flusher;
 pub mod full;
 pub mod growable;
+pub mod growth;
+pub mod instrumented;
 pub mod memory;
+pub mod mirror;
+#[cfg(feature = "mmap")]
 pub mod mmap_mut;
+#[cfg(feature = "mmap")]
 pub mod mmap;
+#[cfg(feature = "mmap")]
+pub mod mmap_anon;
+pub mod paged;
+#[cfg(feature = "mmap")]
+pub mod pool;
+#[cfg(all(unix, feature = "mmap"))]
+pub mod secure;
+pub mod shared;
+#[cfg(all(unix, feature = "mmap"))]
+pub mod shm;
+#[cfg(feature = "mmap")]
+pub mod sparse;
+#[cfg(feature = "mmap")]
+pub mod tiered;
+
+/// Hints about how data of a backend is going to be accessed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Advice {
+    Normal,
+    Sequential,
+    Random,
+    WillNeed,
+}
 
 pub trait Backend {
     /// Should return the whole data in bytes.
@@ -18,6 +54,13 @@ pub trait Backend {
     /// Should return the whole data in bytes mutable.
     fn data_mut(&mut self) -> &mut [u8];
 
+    /// Returns the raw data within `range` mutable. Wrapping backends can override this to know which part of the
+    /// data gets written, so it should be preferred over indexing `data_mut()`.
+    #[inline]
+    fn data_mut_range(&mut self, range: Range<usize>) -> &mut [u8] {
+        &mut self.data_mut()[range]
+    }
+
     /// Should return the first writable index (eg without prepending headers that may be contained in `data()`.
     fn first_index(&self) -> usize;
 
@@ -69,7 +112,7 @@ pub trait Backend {
     fn content_data_mut(&mut self) -> &mut [u8] {
         let first = self.first_index();
         let end = self.last_index();
-        &mut self.data_mut()[first..end]
+        self.data_mut_range(first..end)
     }
 
     /// Pushes raw data
@@ -99,6 +142,24 @@ pub trait Backend {
         Ok(pos)
     }
 
+    /// Pushes multiple buffers at once, as if they were concatenated. Returns the position of the first byte.
+    fn push_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, Error> {
+        let pos = self.len();
+        let total: usize = bufs.iter().map(|i| i.len()).sum();
+        if total == 0 {
+            return Ok(pos);
+        }
+
+        let mut next_slice = self.next_free_slice(total)?;
+        for buf in bufs {
+            let (dst, rest) = next_slice.split_at_mut(buf.len());
+            dst.copy_from_slice(buf);
+            next_slice = rest;
+        }
+        self.set_len(self.len() + total)?;
+        Ok(pos)
+    }
+
     /// Pushes a typed value
     fn push_t<T: Serialize>(&mut self, data: &T) -> Result<(usize, usize), Error> {
         let data = serialize_impl(data)?;
@@ -142,7 +203,7 @@ pub trait Backend {
         let start = self.get_index(index);
         let end = start + len;
         self.check_len_oob(end)?;
-        Ok(&mut self.data_mut()[start..end])
+        Ok(self.data_mut_range(start..end))
     }
 
     /// Gets typed at a given position
@@ -151,6 +212,17 @@ pub trait Backend {
         deserialize_impl(self.get(index, len)?)
     }
 
+    /// Gets typed at a given position reusing the allocations of `scratch`.
+    #[inline]
+    fn get_t_into<T: DeserializeOwned>(
+        &self,
+        index: usize,
+        len: usize,
+        scratch: &mut ScratchBuffer,
+    ) -> Result<T, Error> {
+        deserialize_into(scratch, self.get(index, len)?)
+    }
+
     /*
     /// Swaps the data given by their ranges.
     fn swap(&mut self, a: Range<usize>, b: Range<usize>) -> Result<(), Error> {
@@ -192,8 +264,8 @@ pub trait Backend {
 
         let (a, b) = if a > b { (b, a) } else { (a, b) };
 
-        let (first, second) = self.data_mut().split_at_mut(b);
-        first[a..a + len].swap_with_slice(&mut second[..len]);
+        let (first, second) = self.data_mut_range(a..b + len).split_at_mut(b - a);
+        first[..len].swap_with_slice(&mut second[..len]);
         Ok(())
     }
 
@@ -220,8 +292,7 @@ pub trait Backend {
             let move_end = self.last_index();
             let new_start_index = start_index + fill_len;
 
-            self.data_mut()
-                .copy_within(move_start_index..move_end, new_start_index);
+            self.copy_raw_within(move_start_index..move_end, new_start_index);
         }
 
         if fill_len > 0 {
@@ -229,7 +300,7 @@ pub trait Backend {
             self.check_capacity_oob(end_index)?;
 
             // self.data_mut()[start_index..end_index].copy_from_slice(data);
-            self.data_mut()[start_index..end_index].fill(data);
+            self.data_mut_range(start_index..end_index).fill(data);
         }
 
         let len = len.min(self.len());
@@ -260,14 +331,13 @@ pub trait Backend {
             let move_end = self.last_index();
             let new_start_index = start_index + data.len();
 
-            self.data_mut()
-                .copy_within(move_start_index..move_end, new_start_index);
+            self.copy_raw_within(move_start_index..move_end, new_start_index);
         }
 
         if !data.is_empty() {
             let end_index = start_index + data.len();
             self.check_capacity_oob(end_index)?;
-            self.data_mut()[start_index..end_index].copy_from_slice(data);
+            self.data_mut_range(start_index..end_index).copy_from_slice(data);
         }
 
         let len = len.min(self.len());
@@ -281,6 +351,40 @@ pub trait Backend {
         Ok(diff)
     }
 
+    /// Replaces the data at index..index+len with the concatenation of `bufs`, moving the following data only once.
+    fn replace_vectored(&mut self, index: usize, len: usize, bufs: &[&[u8]]) -> Result<usize, Error> {
+        let total: usize = bufs.iter().map(|i| i.len()).sum();
+        let start_index = self.get_index(index);
+        self.check_len_oob(start_index)?;
+
+        if len != total {
+            let move_start_index = start_index + len;
+            if move_start_index < self.end_index() {
+                let move_end = self.last_index();
+                self.check_capacity_oob(move_end + total.saturating_sub(len))?;
+                self.copy_raw_within(move_start_index..move_end, start_index + total);
+            }
+        }
+
+        let end_index = start_index + total;
+        self.check_capacity_oob(end_index)?;
+        let mut dst = self.data_mut_range(start_index..end_index);
+        for buf in bufs {
+            let (part, rest) = dst.split_at_mut(buf.len());
+            part.copy_from_slice(buf);
+            dst = rest;
+        }
+
+        let len = len.min(self.len());
+        let diff = len.abs_diff(total);
+        if len > total {
+            self.set_len(self.len() - diff)?;
+        } else {
+            self.set_len(self.len() + diff)?;
+        }
+
+        Ok(diff)
+    }
 
     fn replace_t<T: Serialize>(
         &mut self,
@@ -335,7 +439,7 @@ pub trait Backend {
         let start = self.get_index(index);
         let end = start + fill_len;
         self.check_capacity_oob(end)?;
-        self.data_mut()[start..end].fill(data);
+        self.data_mut_range(start..end).fill(data);
 
         if end > self.last_index() {
             let diff = end - self.last_index();
@@ -356,7 +460,7 @@ pub trait Backend {
     fn replace_same_len_direct(&mut self, index: usize, data: &[u8]) -> Result<usize, Error> {
         let end = index + data.len();
         self.check_capacity_oob(end)?;
-        self.data_mut()[index..end].copy_from_slice(data);
+        self.data_mut_range(index..end).copy_from_slice(data);
 
         if end > self.last_index() {
             let diff = end - self.last_index();
@@ -372,7 +476,7 @@ pub trait Backend {
         let start = self.get_index(self.len());
         let end = start + len;
         self.check_capacity_oob(end)?;
-        Ok(&mut self.data_mut()[start..end])
+        Ok(self.data_mut_range(start..end))
     }
 
     /// Fills the given range with `val`. This range has to be within the length bounds, so only valid data can be filled.
@@ -384,7 +488,7 @@ pub trait Backend {
         let start = self.get_index(range.start);
         let end = self.get_index(range.end);
         self.check_len_oob(end)?;
-        self.data_mut()[start..end].fill(val);
+        self.data_mut_range(start..end).fill(val);
         Ok(())
     }
 
@@ -432,6 +536,39 @@ pub trait Backend {
         Ok(())
     }
 
+    /// Hints that the data at index..index+len will be read soon so it can be loaded ahead of time. Indices out of
+    /// bounds are ignored since this is only a hint.
+    #[inline]
+    fn prefetch(&self, index: usize, len: usize) {
+        let start = self.get_index(index).min(self.data().len());
+        let end = (start + len).min(self.data().len());
+        crate::utils::prefetch(&self.data()[start..end]);
+    }
+
+    /// Hints how the backends data is going to be accessed. This only has an effect for memory mapped data.
+    #[inline]
+    fn advise(&self, advice: Advice) {
+        crate::utils::advise(self.data(), advice);
+    }
+
+    /// Hints that the data will be read sequentially, so the OS can read ahead more aggressively.
+    #[inline]
+    fn advise_sequential(&self) {
+        self.advise(Advice::Sequential);
+    }
+
+    /// Hints that the data will be accessed in random order, so the OS doesn't read ahead.
+    #[inline]
+    fn advise_random(&self) {
+        self.advise(Advice::Random);
+    }
+
+    /// Hints that the data within `range` will be needed soon.
+    #[inline]
+    fn advise_willneed(&self, range: Range<usize>) {
+        self.prefetch(range.start, range.len());
+    }
+
     /// Moves all bytes in a given range to a new index. This works on the raw data indices, without taking first_index()
     /// into account!
     #[inline]
@@ -439,10 +576,19 @@ pub trait Backend {
         self.check_len_oob(index + len)?;
         self.check_len_oob(new_index + len)?;
         let move_end = index + len;
-        self.data_mut()
-            .copy_within(index..move_end, new_index);
+        self.copy_raw_within(index..move_end, new_index);
         Ok(())
     }
+
+    /// Copies the raw data within `src` to `dest`, like `slice::copy_within` does on `data_mut()` but only borrowing
+    /// the range that gets touched.
+    #[inline]
+    fn copy_raw_within(&mut self, src: Range<usize>, dest: usize) {
+        let start = src.start.min(dest);
+        let end = src.end.max(dest + src.len());
+        self.data_mut_range(start..end)
+            .copy_within(src.start - start..src.end - start, dest - start);
+    }
 }
 
 impl<B> Backend for &mut B
@@ -459,6 +605,11 @@ impl<B> Backend for &mut B
         (*self).data_mut()
     }
 
+    #[inline]
+    fn data_mut_range(&mut self, range: Range<usize>) -> &mut [u8] {
+        (**self).data_mut_range(range)
+    }
+
     #[inline]
     fn first_index(&self) -> usize {
         (**self).first_index()
@@ -473,6 +624,111 @@ impl<B> Backend for &mut B
     fn set_len(&mut self, len: usize) -> Result<(), Error> {
         (*self).set_len(len)
     }
+
+    #[inline]
+    fn push(&mut self, data: &[u8]) -> Result<usize, Error> {
+        (**self).push(data)
+    }
+
+    #[inline]
+    fn push_fill(&mut self, data: u8, len: usize) -> Result<usize, Error> {
+        (**self).push_fill(data, len)
+    }
+
+    #[inline]
+    fn push_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, Error> {
+        (**self).push_vectored(bufs)
+    }
+
+    #[inline]
+    fn get(&self, index: usize, len: usize) -> Result<&[u8], Error> {
+        (**self).get(index, len)
+    }
+
+    #[inline]
+    fn get_single(&self, index: usize) -> Result<u8, Error> {
+        (**self).get_single(index)
+    }
+
+    #[inline]
+    fn get_single_unchecked(&self, index: usize) -> u8 {
+        (**self).get_single_unchecked(index)
+    }
+
+    #[inline]
+    fn get_unchecked(&self, index: usize, len: usize) -> &[u8] {
+        (**self).get_unchecked(index, len)
+    }
+
+    #[inline]
+    fn get_mut(&mut self, index: usize, len: usize) -> Result<&mut [u8], Error> {
+        (**self).get_mut(index, len)
+    }
+
+    #[inline]
+    fn swap_same_len(&mut self, a: usize, b: usize, len: usize) -> Result<(), Error> {
+        (**self).swap_same_len(a, b, len)
+    }
+
+    #[inline]
+    fn replace_fill(&mut self, index: usize, len: usize, data: u8, fill_len: usize) -> Result<usize, Error> {
+        (**self).replace_fill(index, len, data, fill_len)
+    }
+
+    #[inline]
+    fn replace(&mut self, index: usize, len: usize, data: &[u8]) -> Result<usize, Error> {
+        (**self).replace(index, len, data)
+    }
+
+    #[inline]
+    fn replace_vectored(&mut self, index: usize, len: usize, bufs: &[&[u8]]) -> Result<usize, Error> {
+        (**self).replace_vectored(index, len, bufs)
+    }
+
+    #[inline]
+    fn replace_same_len_fill(&mut self, index: usize, data: u8, fill_len: usize) -> Result<usize, Error> {
+        (**self).replace_same_len_fill(index, data, fill_len)
+    }
+
+    #[inline]
+    fn replace_same_len(&mut self, index: usize, data: &[u8]) -> Result<usize, Error> {
+        (**self).replace_same_len(index, data)
+    }
+
+    #[inline]
+    fn replace_same_len_direct(&mut self, index: usize, data: &[u8]) -> Result<usize, Error> {
+        (**self).replace_same_len_direct(index, data)
+    }
+
+    #[inline]
+    fn fill(&mut self, range: Range<usize>, val: u8) -> Result<(), Error> {
+        (**self).fill(range, val)
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        (**self).clear()
+    }
+
+    #[inline]
+    fn flush_range_impl(&mut self, start: usize, len: usize) -> Result<(), Error> {
+        (**self).flush_range_impl(start, len)
+    }
+
+    #[inline]
+    fn prefetch(&self, index: usize, len: usize) {
+        (**self).prefetch(index, len)
+    }
+
+    #[inline]
+    fn advise(&self, advice: Advice) {
+        (**self).advise(advice)
+    }
+
+    #[inline]
+    fn move_range_to(&mut self, index: usize, len: usize, new_index: usize) -> Result<(), Error> {
+        (**self).move_range_to(index, len, new_index)
+    }
 }
 
 impl<'a> Backend for &'a mut [u8] {
@@ -634,6 +890,27 @@ pub mod test {
         assert_eq!(backend.len(), 1);
     }
 
+    pub fn be_vectored<B: Backend>(backend: &mut B) {
+        backend.clear();
+        assert_eq!(backend.push_vectored(&[]), Ok(0));
+        assert_eq!(backend.push_vectored(&[&[1, 2], &[], &[3]]), Ok(0));
+        assert_eq!(backend.push_vectored(&[&[4], &[5, 6]]), Ok(3));
+        assert_eq!(backend.get(0, 6), Ok(&[1, 2, 3, 4, 5, 6][..]));
+
+        // Grow, shrink and keep the length of a range
+        backend.replace_vectored(1, 1, &[&[7], &[8, 9]]).unwrap();
+        assert_eq!(backend.get(0, 8), Ok(&[1, 7, 8, 9, 3, 4, 5, 6][..]));
+        backend.replace_vectored(1, 3, &[&[2]]).unwrap();
+        assert_eq!(backend.get(0, 6), Ok(&[1, 2, 3, 4, 5, 6][..]));
+        backend.replace_vectored(4, 2, &[&[6], &[5]]).unwrap();
+        assert_eq!(backend.get(0, 6), Ok(&[1, 2, 3, 4, 6, 5][..]));
+        assert_eq!(backend.len(), 6);
+
+        let cap = backend.capacity();
+        assert_eq!(backend.push_vectored(&[&vec![0; cap]]), Err(Error::OutOfBounds));
+        assert_eq!(backend.len(), 6);
+    }
+
     pub fn be_fill<B: Backend>(backend: &mut B) {
         backend.clear();
         let len = backend.capacity();
@@ -0,0 +1,344 @@
+use crate::budget::{BudgetHandle, MemoryBudget};
+use crate::error::Error;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::path::Path;
+
+/// File accessed through a fixed amount of cached pages instead of mapping it. Pages are loaded on access, the least
+/// recently used page gets evicted once the cache is full and dirty pages are written back on eviction or flush. This
+/// allows working with files much larger than the available memory.
+///
+/// Unlike the other backends this doesn't implement `Backend` since its data is never available as a single slice.
+pub struct PagedFile {
+    file: File,
+    page_size: usize,
+    max_pages: usize,
+    pages: HashMap<usize, Page>,
+    clock: u64,
+    len: usize,
+    /// Amount of bytes actually written to the file.
+    file_len: usize,
+    /// Optional memory budget limiting the size of the cached pages.
+    budget: Option<BudgetHandle>,
+}
+
+struct Page {
+    data: Box<[u8]>,
+    dirty: bool,
+    last_use: u64,
+}
+
+impl PagedFile {
+    /// Creates a new file and truncates an existing one. At most `max_pages` pages of `page_size` bytes are cached.
+    pub fn create<P: AsRef<Path>>(path: P, page_size: usize, max_pages: usize) -> Result<Self, Error> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        Self::from_file(file, page_size, max_pages)
+    }
+
+    /// Loads an existing file. At most `max_pages` pages of `page_size` bytes are cached.
+    pub fn load<P: AsRef<Path>>(path: P, page_size: usize, max_pages: usize) -> Result<Self, Error> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        Self::from_file(file, page_size, max_pages)
+    }
+
+    /// Uses `file` as storage.
+    pub fn from_file(file: File, page_size: usize, max_pages: usize) -> Result<Self, Error> {
+        assert!(page_size > 0 && max_pages > 0);
+        let len = file.metadata()?.len() as usize;
+        Ok(Self {
+            file,
+            page_size,
+            max_pages,
+            pages: HashMap::with_capacity(max_pages),
+            clock: 0,
+            len,
+            file_len: len,
+            budget: None,
+        })
+    }
+
+    /// Returns the amount of bytes stored.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the amount of currently cached pages.
+    #[inline]
+    pub fn cached_pages(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Returns the total size of all cached pages.
+    #[inline]
+    pub fn cached_size(&self) -> usize {
+        self.pages.len() * self.page_size
+    }
+
+    /// Registers the page cache in a memory budget. If the budget gets exceeded, the least recently used pages get
+    /// evicted before `max_pages` is reached. The most recently used page is always kept cached.
+    pub fn set_budget(&mut self, budget: &MemoryBudget) -> Result<(), Error> {
+        self.budget = Some(budget.register());
+        self.apply_budget()
+    }
+
+    /// Reads `buf.len()` bytes starting at `offset` into `buf`.
+    pub fn read(&mut self, offset: usize, buf: &mut [u8]) -> Result<(), Error> {
+        if offset + buf.len() > self.len {
+            return Err(Error::OutOfBounds);
+        }
+
+        let mut done = 0;
+        while done < buf.len() {
+            let pos = offset + done;
+            let in_page = pos % self.page_size;
+            let count = (self.page_size - in_page).min(buf.len() - done);
+            let page = self.page(pos / self.page_size)?;
+            buf[done..done + count].copy_from_slice(&page.data[in_page..in_page + count]);
+            done += count;
+        }
+
+        Ok(())
+    }
+
+    /// Writes `data` at `offset`, growing the file if needed. New pages are only allocated once they get written.
+    pub fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), Error> {
+        // Update the length first so pages evicted while writing are stored completely.
+        self.len = self.len.max(offset + data.len());
+
+        let mut done = 0;
+        while done < data.len() {
+            let pos = offset + done;
+            let in_page = pos % self.page_size;
+            let count = (self.page_size - in_page).min(data.len() - done);
+            let page = self.page(pos / self.page_size)?;
+            page.data[in_page..in_page + count].copy_from_slice(&data[done..done + count]);
+            page.dirty = true;
+            done += count;
+        }
+
+        Ok(())
+    }
+
+    /// Appends `data` to the end and returns its offset.
+    #[inline]
+    pub fn push(&mut self, data: &[u8]) -> Result<usize, Error> {
+        let offset = self.len;
+        self.write(offset, data)?;
+        Ok(offset)
+    }
+
+    /// Writes all dirty pages back to the file.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        let ids: Vec<_> = self.pages.iter().filter(|i| i.1.dirty).map(|i| *i.0).collect();
+        for id in ids {
+            self.write_back(id)?;
+        }
+        self.file.set_len(self.len as u64)?;
+        self.file_len = self.len;
+        self.file.sync_data()?;
+        Ok(())
+    }
+
+    /// Returns the cached page with the given ID and loads it if necessary.
+    fn page(&mut self, id: usize) -> Result<&mut Page, Error> {
+        self.clock += 1;
+
+        if !self.pages.contains_key(&id) {
+            if self.pages.len() >= self.max_pages {
+                self.evict()?;
+            }
+
+            let mut data = vec![0u8; self.page_size].into_boxed_slice();
+            let start = id * self.page_size;
+            if start < self.file_len {
+                let end = (start + self.page_size).min(self.file_len);
+                read_at(&self.file, start as u64, &mut data[..end - start])?;
+            }
+
+            let page = Page {
+                data,
+                dirty: false,
+                last_use: self.clock,
+            };
+            self.pages.insert(id, page);
+            self.apply_budget()?;
+        }
+
+        let page = self.pages.get_mut(&id).unwrap();
+        page.last_use = self.clock;
+        Ok(page)
+    }
+
+    /// Removes the least recently used page from the cache.
+    fn evict(&mut self) -> Result<(), Error> {
+        let Some(id) = self.pages.iter().min_by_key(|i| i.1.last_use).map(|i| *i.0) else {
+            return Ok(());
+        };
+
+        if self.pages[&id].dirty {
+            self.write_back(id)?;
+        }
+        self.pages.remove(&id);
+        Ok(())
+    }
+
+    /// Evicts pages requested by the memory budget and reports the current size to it.
+    fn apply_budget(&mut self) -> Result<(), Error> {
+        let Some(budget) = self.budget.take() else {
+            return Ok(());
+        };
+
+        let res = budget.enforce(self, Self::evict_for_budget, Self::cached_size);
+        self.budget = Some(budget);
+        res
+    }
+
+    /// Evicts the least recently used page unless it's the only one left. Returns the amount of bytes freed.
+    fn evict_for_budget(&mut self) -> Result<Option<usize>, Error> {
+        if self.pages.len() <= 1 {
+            return Ok(None);
+        }
+        self.evict()?;
+        Ok(Some(self.page_size))
+    }
+
+    /// Writes the page with the given ID to the file.
+    fn write_back(&mut self, id: usize) -> Result<(), Error> {
+        let start = id * self.page_size;
+        let end = (start + self.page_size).min(self.len.max(start));
+        let page = self.pages.get_mut(&id).unwrap();
+        write_at(&self.file, start as u64, &page.data[..end - start])?;
+        page.dirty = false;
+        self.file_len = self.file_len.max(end);
+        Ok(())
+    }
+}
+
+impl Drop for PagedFile {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+#[cfg(unix)]
+#[inline]
+fn read_at(file: &File, offset: u64, buf: &mut [u8]) -> std::io::Result<()> {
+    use std::os::unix::fs::FileExt;
+    file.read_exact_at(buf, offset)
+}
+
+#[cfg(unix)]
+#[inline]
+fn write_at(file: &File, offset: u64, data: &[u8]) -> std::io::Result<()> {
+    use std::os::unix::fs::FileExt;
+    file.write_all_at(data, offset)
+}
+
+#[cfg(not(unix))]
+fn read_at(mut file: &File, offset: u64, buf: &mut [u8]) -> std::io::Result<()> {
+    use std::io::{Read, Seek, SeekFrom};
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(buf)
+}
+
+#[cfg(not(unix))]
+fn write_at(mut file: &File, offset: u64, data: &[u8]) -> std::io::Result<()> {
+    use std::io::{Seek, SeekFrom, Write};
+    file.seek(SeekFrom::Start(offset))?;
+    file.write_all(data)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn make_paged_file(file: &str, page_size: usize, max_pages: usize) -> PagedFile {
+        let path = Path::new("./testfiles");
+        if !path.is_dir() {
+            std::fs::create_dir_all(path).unwrap();
+        }
+        PagedFile::create(path.join(file), page_size, max_pages).unwrap()
+    }
+
+    #[test]
+    fn test_read_write() {
+        let mut file = make_paged_file("./paged", 16, 4);
+        assert!(file.is_empty());
+
+        let data: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+        for chunk in data.chunks(7) {
+            file.push(chunk).unwrap();
+        }
+        assert_eq!(file.len(), data.len());
+        assert!(file.cached_pages() <= 4);
+
+        let mut buf = vec![0u8; data.len()];
+        file.read(0, &mut buf).unwrap();
+        assert_eq!(buf, data);
+
+        let mut buf = vec![0u8; 40];
+        file.read(13, &mut buf).unwrap();
+        assert_eq!(buf, data[13..53]);
+        assert_eq!(file.read(990, &mut buf), Err(Error::OutOfBounds));
+
+        file.write(20, &[255; 30]).unwrap();
+        file.read(13, &mut buf).unwrap();
+        assert_eq!(buf[..7], data[13..20]);
+        assert_eq!(buf[7..37], [255; 30]);
+    }
+
+    #[test]
+    fn test_budget() {
+        let budget = MemoryBudget::new(64);
+        let other = budget.register();
+
+        let mut file = make_paged_file("./paged_budget", 16, 8);
+        file.set_budget(&budget).unwrap();
+        let data: Vec<u8> = (0..128u32).map(|i| i as u8).collect();
+        file.push(&data).unwrap();
+        assert_eq!(file.cached_pages(), 4);
+        assert_eq!(budget.usage(), 64);
+
+        // Another component needing more memory causes pages to be evicted.
+        other.report(32);
+        let mut buf = [0u8; 16];
+        file.read(0, &mut buf).unwrap();
+        assert_eq!(file.cached_pages(), 3);
+        assert_eq!(file.cached_size(), 48);
+
+        // Evicted pages have been written back.
+        let mut buf = vec![0u8; data.len()];
+        file.read(0, &mut buf).unwrap();
+        assert_eq!(buf, data);
+    }
+
+    #[test]
+    fn test_load() {
+        {
+            let mut file = make_paged_file("./paged_load", 8, 2);
+            file.write(100, &[1, 2, 3]).unwrap();
+            file.write(4, &[9; 10]).unwrap();
+        }
+
+        let mut file = PagedFile::load("./testfiles/paged_load", 8, 2).unwrap();
+        assert_eq!(file.len(), 103);
+        let mut buf = [0u8; 3];
+        file.read(100, &mut buf).unwrap();
+        assert_eq!(buf, [1, 2, 3]);
+        file.read(2, &mut buf).unwrap();
+        assert_eq!(buf, [0, 0, 9]);
+        file.read(50, &mut buf).unwrap();
+        assert_eq!(buf, [0, 0, 0]);
+    }
+}
@@ -0,0 +1,248 @@
+use crate::backend::mmap_mut::{MmapBackendMut, MmapFileMut};
+use crate::backend::Backend;
+use crate::budget::{BudgetHandle, MemoryBudget};
+use crate::{Error, Result};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+/// A pool of memory mapped file backends that keeps at most `max_open` files opened and mapped at the same time.
+/// Accessing a closed file transparently re-opens it and closes the least recently used one, so applications
+/// working with many small stores don't run out of file descriptors or address space.
+pub struct BackendPool {
+    paths: Vec<PathBuf>,
+    open: Vec<Option<MmapBackendMut>>,
+    /// IDs of the opened backends, the least recently used one first.
+    lru: VecDeque<usize>,
+    max_open: usize,
+    /// Optional memory budget limiting the total size of the opened backends.
+    budget: Option<BudgetHandle>,
+}
+
+impl BackendPool {
+    /// Creates a new pool keeping at most `max_open` backends opened at the same time.
+    pub fn new(max_open: usize) -> Result<Self> {
+        if max_open == 0 {
+            return Err(Error::UnexpectedValue);
+        }
+
+        Ok(Self {
+            paths: vec![],
+            open: vec![],
+            lru: VecDeque::new(),
+            max_open,
+            budget: None,
+        })
+    }
+
+    /// Creates a new file with the given capacity, adds it to the pool and returns its ID.
+    pub fn create<P: AsRef<Path>>(&mut self, path: P, size: usize) -> Result<usize> {
+        let path = path.as_ref().to_path_buf();
+        let backend = MmapBackendMut::from_storage(MmapFileMut::create(&path, size)?)?;
+
+        let id = self.paths.len();
+        self.paths.push(path);
+        self.open.push(None);
+        self.insert_open(id, backend)?;
+        self.apply_budget()?;
+        Ok(id)
+    }
+
+    /// Adds an existing file to the pool and returns its ID. The file gets opened on its first access.
+    pub fn register<P: AsRef<Path>>(&mut self, path: P) -> Result<usize> {
+        let path = path.as_ref();
+        if !path.is_file() {
+            return Err(Error::Initialization);
+        }
+
+        let id = self.paths.len();
+        self.paths.push(path.to_path_buf());
+        self.open.push(None);
+        Ok(id)
+    }
+
+    /// Returns the backend with the given ID, opening it if necessary.
+    pub fn get_mut(&mut self, id: usize) -> Result<&mut MmapBackendMut> {
+        if id >= self.paths.len() {
+            return Err(Error::OutOfBounds);
+        }
+
+        if self.open[id].is_some() {
+            let pos = self.lru.iter().position(|i| *i == id).unwrap();
+            self.lru.remove(pos);
+            self.lru.push_back(id);
+        } else {
+            let backend = MmapBackendMut::from_storage(MmapFileMut::load(&self.paths[id])?)?;
+            self.insert_open(id, backend)?;
+        }
+
+        self.apply_budget()?;
+        Ok(self.open[id].as_mut().unwrap())
+    }
+
+    /// Flushes and closes the backend with the given ID. It gets re-opened on the next access.
+    pub fn close(&mut self, id: usize) -> Result<()> {
+        let Some(mut backend) = self.open.get_mut(id).and_then(|i| i.take()) else {
+            return Ok(());
+        };
+
+        let pos = self.lru.iter().position(|i| *i == id).unwrap();
+        self.lru.remove(pos);
+        backend.flush()
+    }
+
+    /// Registers the pool in a memory budget. If the budget gets exceeded, the least recently used backends get closed.
+    /// The most recently used backend is always kept open.
+    pub fn set_budget(&mut self, budget: &MemoryBudget) -> Result<()> {
+        self.budget = Some(budget.register());
+        self.apply_budget()
+    }
+
+    /// Returns the total size of all opened backends.
+    #[inline]
+    pub fn mapped_size(&self) -> usize {
+        self.open.iter().flatten().map(|i| i.data().len()).sum()
+    }
+
+    /// Returns the path of the file with the given ID.
+    #[inline]
+    pub fn path(&self, id: usize) -> Option<&Path> {
+        self.paths.get(id).map(|i| i.as_path())
+    }
+
+    /// Returns `true` if the backend with the given ID is currently opened.
+    #[inline]
+    pub fn is_open(&self, id: usize) -> bool {
+        self.open.get(id).map(|i| i.is_some()).unwrap_or_default()
+    }
+
+    /// Returns the amount of currently opened backends.
+    #[inline]
+    pub fn open_count(&self) -> usize {
+        self.lru.len()
+    }
+
+    /// Returns the maximum amount of backends opened at the same time.
+    #[inline]
+    pub fn max_open(&self) -> usize {
+        self.max_open
+    }
+
+    /// Returns the amount of files in the pool.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Returns `true` if there are no files in the pool.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Flushes all opened backends.
+    pub fn flush(&mut self) -> Result<()> {
+        for backend in self.open.iter_mut().flatten() {
+            backend.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Closes backends requested by the memory budget and reports the current size to it.
+    fn apply_budget(&mut self) -> Result<()> {
+        let Some(budget) = self.budget.take() else {
+            return Ok(());
+        };
+
+        let res = budget.enforce(self, Self::close_lru, Self::mapped_size);
+        self.budget = Some(budget);
+        res
+    }
+
+    /// Closes the least recently used backend unless it's the only one opened. Returns the amount of bytes freed.
+    fn close_lru(&mut self) -> Result<Option<usize>> {
+        if self.lru.len() <= 1 {
+            return Ok(None);
+        }
+
+        let oldest = *self.lru.front().unwrap();
+        let size = self.open[oldest].as_ref().unwrap().data().len();
+        self.close(oldest)?;
+        Ok(Some(size))
+    }
+
+    /// Marks `backend` as opened, closing the least recently used backends if the limit is exceeded.
+    fn insert_open(&mut self, id: usize, backend: MmapBackendMut) -> Result<()> {
+        while self.lru.len() >= self.max_open {
+            let oldest = *self.lru.front().unwrap();
+            self.close(oldest)?;
+        }
+
+        self.open[id] = Some(backend);
+        self.lru.push_back(id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pool() {
+        std::fs::create_dir_all("./testfiles").unwrap();
+
+        let mut pool = BackendPool::new(2).unwrap();
+        let ids: Vec<_> = (0..5)
+            .map(|i| pool.create(format!("./testfiles/pool_{i}"), 100).unwrap())
+            .collect();
+        assert_eq!(pool.len(), 5);
+        assert_eq!(pool.open_count(), 2);
+        assert!(pool.is_open(3) && pool.is_open(4));
+
+        for id in ids.iter() {
+            pool.get_mut(*id).unwrap().push(&[*id as u8; 3]).unwrap();
+            assert!(pool.open_count() <= 2);
+        }
+
+        // Data survives closing and re-opening.
+        for id in ids.iter() {
+            assert_eq!(pool.get_mut(*id).unwrap().get(0, 3), Ok(&[*id as u8; 3][..]));
+        }
+
+        // Accessing a backend marks it as recently used.
+        pool.get_mut(3).unwrap();
+        pool.get_mut(0).unwrap();
+        assert!(pool.is_open(0) && pool.is_open(3) && !pool.is_open(4));
+
+        pool.close(0).unwrap();
+        assert_eq!(pool.open_count(), 1);
+        assert!(pool.get_mut(10).is_err());
+
+        let id = pool.register("./testfiles/pool_2").unwrap();
+        assert_eq!(pool.get_mut(id).unwrap().get(0, 3), Ok(&[2; 3][..]));
+        assert!(pool.register("./testfiles/pool_missing").is_err());
+    }
+
+    #[test]
+    fn test_pool_budget() {
+        std::fs::create_dir_all("./testfiles").unwrap();
+
+        let budget = MemoryBudget::new(2000);
+        let other = budget.register();
+
+        let mut pool = BackendPool::new(10).unwrap();
+        pool.set_budget(&budget).unwrap();
+        for i in 0..5 {
+            pool.create(format!("./testfiles/pool_budget_{i}"), 492).unwrap();
+        }
+        assert_eq!(pool.open_count(), 4);
+        assert_eq!(budget.usage(), 2000);
+
+        // Another component needing more memory causes the pool to close backends.
+        other.report(1000);
+        pool.get_mut(4).unwrap();
+        assert_eq!(pool.open_count(), 2);
+        assert!(pool.is_open(3) && pool.is_open(4));
+        assert_eq!(budget.usage(), 2000);
+    }
+}
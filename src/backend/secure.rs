@@ -0,0 +1,156 @@
+use crate::backend::base::BaseBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::growth::GrowthPolicy;
+use crate::backend::Backend;
+use crate::error::Error;
+use crate::header::BaseHeader;
+use memmap2::MmapMut;
+use std::ops::{Deref, DerefMut};
+
+pub type SecureMemoryBackend = BaseBackend<SecureMemory>;
+
+/// Anonymous memory whose pages are locked into RAM so they never get written to swap. The memory is zeroed before it
+/// gets released, both when dropping and when resizing, and excluded from core dumps on linux. Useful for storing
+/// secrets, eg. in an `FMap`.
+///
+/// Locking fails if the process exceeds its locked memory limit (`RLIMIT_MEMLOCK`).
+pub struct SecureMemory {
+    map: MmapMut,
+}
+
+impl SecureMemory {
+    /// Allocates and locks memory holding `size` bytes of data.
+    #[inline]
+    pub fn new(size: usize) -> Result<Self, Error> {
+        Ok(Self {
+            map: locked_map(size + BaseHeader::len_bytes())?,
+        })
+    }
+
+    /// Moves the data into a new locked map with the given length and wipes the old one.
+    fn resize(&mut self, new_len: usize) -> crate::Result<()> {
+        let mut map = locked_map(new_len)?;
+        let len = new_len.min(self.map.len());
+        map[..len].copy_from_slice(&self.map[..len]);
+
+        let mut old = std::mem::replace(&mut self.map, map);
+        zeroize(&mut old);
+        Ok(())
+    }
+}
+
+fn locked_map(len: usize) -> crate::Result<MmapMut> {
+    let map = MmapMut::map_anon(len)?;
+    map.lock()?;
+    #[cfg(target_os = "linux")]
+    let _ = map.advise(memmap2::Advice::DontDump);
+    Ok(map)
+}
+
+/// Overwrites `data` with zeros in a way the compiler can't optimize away.
+fn zeroize(data: &mut [u8]) {
+    for byte in data.iter_mut() {
+        unsafe { std::ptr::write_volatile(byte, 0) };
+    }
+    std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+}
+
+impl Drop for SecureMemory {
+    fn drop(&mut self) {
+        zeroize(&mut self.map);
+    }
+}
+
+impl SecureMemoryBackend {
+    /// Creates a new empty backend with a capacity of `size` bytes.
+    #[inline]
+    pub fn new(size: usize) -> Result<Self, Error> {
+        Self::from_storage(SecureMemory::new(size)?)
+    }
+}
+
+impl Backend for SecureMemoryBackend {
+    #[inline]
+    fn data(&self) -> &[u8] {
+        self.storage().deref()
+    }
+
+    #[inline]
+    fn data_mut(&mut self) -> &mut [u8] {
+        self.storage_mut().deref_mut()
+    }
+
+    #[inline]
+    fn first_index(&self) -> usize {
+        BaseHeader::len_bytes()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.header().data_len()
+    }
+
+    #[inline]
+    fn set_len(&mut self, len: usize) -> Result<(), Error> {
+        self.set_len(len)
+    }
+}
+
+impl GrowableBackend for SecureMemoryBackend {
+    #[inline]
+    fn resize_impl(&mut self, new_len: usize, _: bool) -> crate::Result<()> {
+        self.storage_mut().resize(new_len)
+    }
+
+    #[inline]
+    fn growth_policy(&self) -> &dyn GrowthPolicy {
+        BaseBackend::growth_policy(self)
+    }
+}
+
+impl Deref for SecureMemory {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.map.deref()
+    }
+}
+
+impl DerefMut for SecureMemory {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.map.deref_mut()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::*;
+    use super::*;
+    use crate::components::map::FMap;
+    use crate::traits::creatable::Creatable;
+
+    #[test]
+    fn secure_backend() {
+        let mut backend = SecureMemoryBackend::new(100).unwrap();
+        be_clear(&mut backend);
+        be_push(&mut backend);
+        be_replace(&mut backend);
+        be_fill(&mut backend);
+    }
+
+    #[test]
+    fn test_secrets() {
+        let backend = SecureMemoryBackend::new(10).unwrap();
+        let mut map: FMap<_, String, String> = FMap::create(backend).unwrap();
+        for i in 0..100 {
+            map.insert(&format!("key{i}"), &format!("secret{i}")).unwrap();
+        }
+        assert_eq!(map.get(&"key42".to_string()), Some("secret42".to_string()));
+
+        let mut data = vec![1u8; 64];
+        zeroize(&mut data);
+        assert!(data.iter().all(|i| *i == 0));
+    }
+}
@@ -0,0 +1,83 @@
+use crate::backend::{Advice, Backend};
+use crate::error::Error;
+use std::sync::Arc;
+
+/// Read only backend that can be shared between threads. Cloning it only clones the handle, not the data. Components
+/// on this backend are handed out wrapped in a `ReadOnly`, so mutations are rejected at compile time.
+#[derive(Debug)]
+pub struct SharedBackend<B>(Arc<B>);
+
+impl<B> SharedBackend<B> {
+    #[inline]
+    pub fn new(inner: B) -> Self {
+        Self(Arc::new(inner))
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &B {
+        &self.0
+    }
+}
+
+impl<B> Clone for SharedBackend<B> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<B> Backend for SharedBackend<B>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn data(&self) -> &[u8] {
+        self.0.data()
+    }
+
+    #[inline]
+    fn data_mut(&mut self) -> &mut [u8] {
+        panic!("Shared backend is read only")
+    }
+
+    #[inline]
+    fn first_index(&self) -> usize {
+        self.0.first_index()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[inline]
+    fn set_len(&mut self, _len: usize) -> Result<(), Error> {
+        Err(Error::UnsupportedOperation)
+    }
+
+    #[inline]
+    fn prefetch(&self, index: usize, len: usize) {
+        self.0.prefetch(index, len)
+    }
+
+    #[inline]
+    fn advise(&self, advice: Advice) {
+        self.0.advise(advice)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+
+    #[test]
+    fn test_set_len() {
+        let mut backend = make_mem_backend(100);
+        backend.push(&[1, 2, 3]).unwrap();
+
+        let mut shared = SharedBackend::new(backend);
+        assert_eq!(shared.set_len(0), Err(Error::UnsupportedOperation));
+        assert_eq!(shared.clone().content_data(), &[1, 2, 3]);
+    }
+}
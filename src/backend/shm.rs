@@ -0,0 +1,197 @@
+use crate::backend::base::BaseBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::growth::GrowthPolicy;
+use crate::backend::mmap::MmapFile;
+use crate::backend::mmap_mut::MmapFileMut;
+use crate::backend::Backend;
+use crate::error::Error;
+use crate::header::BaseHeader;
+use std::ffi::CString;
+use std::fs::File;
+use std::ops::{Deref, DerefMut};
+use std::os::unix::io::FromRawFd;
+
+pub type ShmBackend = BaseBackend<SharedMemory>;
+
+/// POSIX shared memory object mapped into memory. This allows multiple processes to access the same data without going
+/// through the filesystem: one process writes using a `ShmBackend` while others open it read only using
+/// `SharedMemory::open_ro`.
+///
+/// Readers only see the size and header at the time they were opened, so they need to reopen the object to see data
+/// that got added afterwards. The object is removed once the `SharedMemory` that created it gets dropped.
+pub struct SharedMemory {
+    inner: MmapFileMut,
+    name: CString,
+    owner: bool,
+}
+
+impl SharedMemory {
+    /// Creates a new shared memory object with the given name and truncates an existing one. Names have to start with
+    /// a `/` and can't contain any other slashes.
+    pub fn create(name: &str, size: usize) -> Result<Self, Error> {
+        let name = shm_name(name)?;
+        let file = shm_open(&name, libc::O_CREAT | libc::O_TRUNC | libc::O_RDWR)?;
+        file.set_len((size + BaseHeader::len_bytes()) as u64)?;
+        Ok(Self {
+            inner: MmapFileMut::from_file(file)?,
+            name,
+            owner: true,
+        })
+    }
+
+    /// Opens an existing shared memory object for writing.
+    pub fn open(name: &str) -> Result<Self, Error> {
+        let name = shm_name(name)?;
+        let file = shm_open(&name, libc::O_RDWR)?;
+        Ok(Self {
+            inner: MmapFileMut::from_file(file)?,
+            name,
+            owner: false,
+        })
+    }
+
+    /// Opens an existing shared memory object read only. Use it with a `MmapBackend`.
+    pub fn open_ro(name: &str) -> Result<MmapFile, Error> {
+        let file = shm_open(&shm_name(name)?, libc::O_RDONLY)?;
+        MmapFile::from_file(file)
+    }
+
+    /// Removes the shared memory object with the given name. Processes that already opened it can still use it.
+    pub fn unlink(name: &str) -> Result<(), Error> {
+        let name = shm_name(name)?;
+        if unsafe { libc::shm_unlink(name.as_ptr()) } != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for SharedMemory {
+    fn drop(&mut self) {
+        if self.owner {
+            unsafe { libc::shm_unlink(self.name.as_ptr()) };
+        }
+    }
+}
+
+#[inline]
+fn shm_name(name: &str) -> Result<CString, Error> {
+    if !name.starts_with('/') || name[1..].contains('/') {
+        return Err(Error::UnexpectedValue);
+    }
+    CString::new(name).map_err(|_| Error::UnexpectedValue)
+}
+
+fn shm_open(name: &CString, flags: libc::c_int) -> Result<File, Error> {
+    let fd = unsafe { libc::shm_open(name.as_ptr(), flags, 0o600 as libc::mode_t) };
+    if fd < 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(unsafe { File::from_raw_fd(fd) })
+}
+
+impl Backend for ShmBackend {
+    #[inline]
+    fn data(&self) -> &[u8] {
+        self.storage().deref()
+    }
+
+    #[inline]
+    fn data_mut(&mut self) -> &mut [u8] {
+        self.storage_mut().deref_mut()
+    }
+
+    #[inline]
+    fn first_index(&self) -> usize {
+        BaseHeader::len_bytes()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.header().data_len()
+    }
+
+    #[inline]
+    fn set_len(&mut self, len: usize) -> Result<(), Error> {
+        self.set_len(len)
+    }
+
+    #[inline]
+    fn flush_range_impl(&mut self, start: usize, len: usize) -> Result<(), Error> {
+        self.storage_mut().inner.flush_range(start, len)
+    }
+}
+
+impl GrowableBackend for ShmBackend {
+    #[inline]
+    fn resize_impl(&mut self, new_len: usize, _: bool) -> crate::Result<()> {
+        self.storage_mut().inner.resize(new_len)
+    }
+
+    #[inline]
+    fn growth_policy(&self) -> &dyn GrowthPolicy {
+        BaseBackend::growth_policy(self)
+    }
+}
+
+impl Deref for SharedMemory {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.inner.deref()
+    }
+}
+
+impl DerefMut for SharedMemory {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.inner.deref_mut()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::*;
+    use super::*;
+    use crate::backend::mmap::MmapBackend;
+    use crate::components::map::FMap;
+    use crate::traits::creatable::Creatable;
+    use crate::traits::initiable::Initiable;
+
+    fn test_name(name: &str) -> String {
+        format!("/bytestore-{}-{name}", std::process::id())
+    }
+
+    #[test]
+    fn shm_backend() {
+        let mut backend = ShmBackend::from_storage(SharedMemory::create(&test_name("be"), 100).unwrap()).unwrap();
+        be_clear(&mut backend);
+        be_push(&mut backend);
+        be_replace(&mut backend);
+        be_fill(&mut backend);
+    }
+
+    #[test]
+    fn test_shared_map() {
+        let name = test_name("map");
+        let mut writer = ShmBackend::from_storage(SharedMemory::create(&name, 10).unwrap()).unwrap();
+        {
+            let mut map: FMap<_, u32, u32> = FMap::create(&mut writer).unwrap();
+            for i in 0..1000 {
+                map.insert(&i, &(i + 1)).unwrap();
+            }
+        }
+
+        let reader = MmapBackend::from_storage(SharedMemory::open_ro(&name).unwrap()).unwrap();
+        let map: FMap<_, u32, u32> = FMap::init(reader).unwrap();
+        assert_eq!(map.len(), 1000);
+        assert_eq!(map.get(&999), Some(1000));
+
+        drop(writer);
+        assert!(SharedMemory::open(&name).is_err());
+        assert_eq!(map.get(&1), Some(2));
+
+        assert_eq!(SharedMemory::create("no_slash", 10).err(), Some(Error::UnexpectedValue));
+    }
+}
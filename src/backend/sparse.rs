@@ -0,0 +1,200 @@
+use crate::backend::base::BaseBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::growth::GrowthPolicy;
+use crate::backend::mmap_mut::MmapFileMut;
+use crate::backend::Backend;
+use crate::error::Error;
+use crate::header::BaseHeader;
+use std::fs::File;
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+
+pub type SparseBackend = BaseBackend<SparseFile>;
+
+/// Memory mapped sparse file. Space that gets released by shrinking the backend or by `release_unused()` gets punched
+/// out of the file, so its disk blocks are returned to the OS.
+pub struct SparseFile {
+    inner: MmapFileMut,
+}
+
+impl SparseFile {
+    /// Creates a new sparse file and truncates an existing one. The capacity doesn't occupy any disk blocks until it
+    /// gets written.
+    #[inline]
+    pub fn create<P: AsRef<Path>>(path: P, size: usize) -> Result<Self, Error> {
+        Ok(Self {
+            inner: MmapFileMut::create(path, size)?,
+        })
+    }
+
+    /// Loads an existing file.
+    #[inline]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Ok(Self {
+            inner: MmapFileMut::load(path)?,
+        })
+    }
+
+    /// Deallocates the disk blocks of the given byte range. The range reads as zeros afterwards.
+    pub fn punch_hole(&mut self, start: usize, len: usize) -> crate::Result<()> {
+        if start + len > self.inner.len() {
+            return Err(Error::OutOfBounds);
+        }
+        if len == 0 {
+            return Ok(());
+        }
+
+        if !punch_hole(self.inner.file(), start, len)? {
+            // Filesystem doesn't support hole punching.
+            self.inner[start..start + len].fill(0);
+        }
+        Ok(())
+    }
+
+    /// Returns the amount of bytes the file occupies on disk.
+    #[cfg(unix)]
+    pub fn disk_usage(&self) -> crate::Result<u64> {
+        use std::os::unix::fs::MetadataExt;
+        Ok(self.inner.file().metadata()?.blocks() * 512)
+    }
+}
+
+/// Punches a hole into `file`. Returns `false` if the filesystem doesn't support it.
+#[cfg(target_os = "linux")]
+fn punch_hole(file: &File, start: usize, len: usize) -> crate::Result<bool> {
+    use std::os::unix::io::AsRawFd;
+
+    let mode = libc::FALLOC_FL_PUNCH_HOLE | libc::FALLOC_FL_KEEP_SIZE;
+    let res = unsafe { libc::fallocate(file.as_raw_fd(), mode, start as libc::off_t, len as libc::off_t) };
+    if res != 0 {
+        let err = std::io::Error::last_os_error();
+        if err.raw_os_error() == Some(libc::EOPNOTSUPP) {
+            return Ok(false);
+        }
+        return Err(err.into());
+    }
+    Ok(true)
+}
+
+/// Hole punching is only supported on linux.
+#[cfg(not(target_os = "linux"))]
+#[inline]
+fn punch_hole(_file: &File, _start: usize, _len: usize) -> crate::Result<bool> {
+    Ok(false)
+}
+
+impl SparseBackend {
+    /// Releases the disk blocks of the unused capacity without shrinking the backend.
+    pub fn release_unused(&mut self) -> crate::Result<()> {
+        let start = self.last_index();
+        let len = self.data().len() - start;
+        self.storage_mut().punch_hole(start, len)
+    }
+}
+
+impl Backend for SparseBackend {
+    #[inline]
+    fn data(&self) -> &[u8] {
+        self.storage().deref()
+    }
+
+    #[inline]
+    fn data_mut(&mut self) -> &mut [u8] {
+        self.storage_mut().deref_mut()
+    }
+
+    #[inline]
+    fn first_index(&self) -> usize {
+        BaseHeader::len_bytes()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.header().data_len()
+    }
+
+    #[inline]
+    fn set_len(&mut self, len: usize) -> Result<(), Error> {
+        self.set_len(len)
+    }
+
+    #[inline]
+    fn flush_range_impl(&mut self, start: usize, len: usize) -> Result<(), Error> {
+        self.storage_mut().inner.flush_range(start, len)
+    }
+}
+
+impl GrowableBackend for SparseBackend {
+    fn resize_impl(&mut self, new_len: usize, growing: bool) -> crate::Result<()> {
+        if !growing {
+            let old_len = self.data().len();
+            self.storage_mut().punch_hole(new_len, old_len - new_len)?;
+        }
+        self.storage_mut().inner.resize(new_len)
+    }
+
+    #[inline]
+    fn growth_policy(&self) -> &dyn GrowthPolicy {
+        BaseBackend::growth_policy(self)
+    }
+}
+
+impl Deref for SparseFile {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.inner.deref()
+    }
+}
+
+impl DerefMut for SparseFile {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.inner.deref_mut()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::*;
+    use super::*;
+
+    fn make_sparse_backend(file: &str, len: usize) -> SparseBackend {
+        let path = Path::new("./testfiles");
+        if !path.is_dir() {
+            std::fs::create_dir_all(path).unwrap();
+        }
+        SparseBackend::from_storage(SparseFile::create(path.join(file), len).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn sparse_backend() {
+        let mut backend = make_sparse_backend("./sparse", 100);
+        be_clear(&mut backend);
+        be_push(&mut backend);
+        be_replace(&mut backend);
+        be_fill(&mut backend);
+    }
+
+    #[test]
+    fn test_release() {
+        let size = 1024 * 1024;
+        let mut backend = make_sparse_backend("./sparse_release", size);
+        backend.push_fill(7, size).unwrap();
+        backend.flush().unwrap();
+        let full_usage = backend.storage().disk_usage().unwrap();
+
+        backend.set_len(4096).unwrap();
+        backend.release_unused().unwrap();
+        assert_eq!(backend.capacity(), size);
+        assert!(backend.data()[backend.last_index()..].iter().all(|i| *i == 0));
+        assert_eq!(backend.get(0, 3), Ok(&[7, 7, 7][..]));
+        assert!(backend.storage().disk_usage().unwrap() <= full_usage);
+
+        backend.grow(100).unwrap();
+        backend.shrink_to_fit().unwrap();
+        assert_eq!(backend.capacity(), 4096);
+        assert_eq!(backend.get(4095, 1), Ok(&[7][..]));
+    }
+}
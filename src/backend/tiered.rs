@@ -0,0 +1,248 @@
+use crate::backend::base::BaseBackend;
+use crate::backend::dirty::DirtyPages;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::growth::GrowthPolicy;
+use crate::backend::mmap_mut::MmapBackendMut;
+use crate::backend::Backend;
+use crate::error::Error;
+use crate::header::BaseHeader;
+use memmap2::{MmapMut, MmapOptions, RemapOptions};
+use std::ops::{Deref, DerefMut, Range};
+
+pub type TieredBackend = BaseBackend<TieredData>;
+
+/// Storage keeping changes to a memory mapped file in memory. Reads and writes are served from a private copy-on-write
+/// mapping of the file, so pages get loaded on their first access and only written pages get copied into memory.
+/// Written pages are tracked and only those get written back to the mapped file when flushing. Unflushed changes are
+/// written back when the storage gets dropped.
+pub struct TieredData {
+    cold: MmapBackendMut,
+    hot: MmapMut,
+    /// Pages of `hot` that were written since they got written back the last time.
+    dirty: DirtyPages,
+}
+
+impl TieredData {
+    /// Maps the file of `cold` a second time as private copy-on-write mapping which all reads and writes go to.
+    pub fn new(cold: MmapBackendMut) -> crate::Result<Self> {
+        let hot = unsafe { MmapOptions::new().len(cold.data().len()).map_copy(cold.storage().file())? };
+        let dirty = DirtyPages::new(hot.len());
+        Ok(Self { cold, hot, dirty })
+    }
+
+    /// Writes the dirty pages within the given range back to the mapped file and flushes them.
+    pub fn flush_range(&mut self, start: usize, len: usize) -> crate::Result<()> {
+        let mut runs = vec![];
+        let mut from = start;
+        while let Some(run) = self.dirty.take_run(from..start + len, self.hot.len()) {
+            self.cold.data_mut_range(run.clone()).copy_from_slice(&self.hot[run.clone()]);
+            from = run.end;
+            runs.push(run);
+        }
+
+        // Keep the header of the mapped file in sync.
+        if runs.first().is_some_and(|i| i.start == 0) {
+            let header = BaseHeader::from_bytes(self.hot[..8].try_into().unwrap());
+            Backend::set_len(&mut self.cold, header.data_len())?;
+        }
+
+        for run in runs {
+            self.cold.flush_range(run.start, run.len())?;
+        }
+        Ok(())
+    }
+
+    /// Returns the mapped file. It only contains flushed changes.
+    #[inline]
+    pub fn cold(&self) -> &MmapBackendMut {
+        &self.cold
+    }
+
+    /// Returns `true` if there are unflushed writes.
+    #[inline]
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.is_dirty()
+    }
+
+    /// Returns the amount of bytes that will be written back by the next flush.
+    #[inline]
+    pub fn dirty_len(&self) -> usize {
+        self.dirty.dirty_len(self.hot.len())
+    }
+
+    /// Returns the given range mutable and marks its pages as dirty.
+    #[inline]
+    fn data_mut_range(&mut self, range: Range<usize>) -> &mut [u8] {
+        self.dirty.mark(range.clone(), self.hot.len());
+        &mut self.hot[range]
+    }
+
+    /// Resizes the mapped file and the private mapping to `new_len` bytes. The file gets resized first since pages of
+    /// the private mapping beyond the end of the file can't be accessed.
+    fn resize(&mut self, new_len: usize, growing: bool) -> crate::Result<()> {
+        self.cold.resize_impl(new_len, growing)?;
+        unsafe {
+            self.hot
+                .remap(new_len, RemapOptions::new().may_move(true))?;
+        }
+        self.dirty.resize(new_len);
+        Ok(())
+    }
+}
+
+impl Drop for TieredData {
+    fn drop(&mut self) {
+        if self.dirty.is_dirty() {
+            let _ = self.flush_range(0, self.hot.len());
+        }
+    }
+}
+
+impl Backend for TieredBackend {
+    #[inline]
+    fn data(&self) -> &[u8] {
+        self.storage().deref()
+    }
+
+    #[inline]
+    fn data_mut(&mut self) -> &mut [u8] {
+        self.storage_mut().deref_mut()
+    }
+
+    #[inline]
+    fn data_mut_range(&mut self, range: Range<usize>) -> &mut [u8] {
+        self.storage_mut().data_mut_range(range)
+    }
+
+    #[inline]
+    fn first_index(&self) -> usize {
+        BaseHeader::len_bytes()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.header().data_len()
+    }
+
+    /// Writes the header through `data_mut_range` so only the page of the header gets marked as dirty.
+    #[inline]
+    fn set_len(&mut self, len: usize) -> Result<(), Error> {
+        self.header_mut().set_data_len(len);
+        let header = self.header().bytes();
+        self.data_mut_range(Self::header_range()).copy_from_slice(&header);
+        Ok(())
+    }
+
+    #[inline]
+    fn flush_range_impl(&mut self, start: usize, len: usize) -> Result<(), Error> {
+        self.storage_mut().flush_range(start, len)
+    }
+}
+
+impl GrowableBackend for TieredBackend {
+    #[inline]
+    fn resize_impl(&mut self, new_len: usize, growing: bool) -> crate::Result<()> {
+        self.storage_mut().resize(new_len, growing)
+    }
+
+    #[inline]
+    fn growth_policy(&self) -> &dyn GrowthPolicy {
+        BaseBackend::growth_policy(self)
+    }
+}
+
+impl Deref for TieredData {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.hot
+    }
+}
+
+/// Writes through the returned slice can't be tracked and mark all pages as dirty.
+impl DerefMut for TieredData {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.dirty.mark_all(self.hot.len());
+        &mut self.hot
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::*;
+    use super::*;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+    use crate::backend::mmap_mut::MmapFileMut;
+    use crate::components::map::FMap;
+    use crate::traits::creatable::Creatable;
+    use crate::traits::initiable::Initiable;
+    use crate::utils::page_size;
+
+    fn make_tiered_backend(file: &str, len: usize) -> TieredBackend {
+        TieredBackend::from_storage(TieredData::new(make_mmap_backend(file, len)).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn tiered_backend() {
+        let mut backend = make_tiered_backend("./tiered", 100);
+        assert!(backend.is_empty());
+        be_clear(&mut backend);
+        be_push(&mut backend);
+        be_replace(&mut backend);
+        be_fill(&mut backend);
+    }
+
+    #[test]
+    fn test_write_back() {
+        let mut backend = make_tiered_backend("./tiered_wb", 10);
+        backend.push(&[1, 2, 3]).unwrap();
+        assert!(backend.storage().cold().is_empty());
+
+        backend.flush().unwrap();
+        assert_eq!(backend.storage().cold().get(0, 3), Ok(&[1, 2, 3][..]));
+
+        backend.clear();
+        {
+            let mut map: FMap<_, u32, u32> = FMap::create(&mut backend).unwrap();
+            for i in 0..1000 {
+                map.insert(&i, &(i + 1)).unwrap();
+            }
+        }
+        drop(backend);
+
+        let cold = MmapBackendMut::from_storage(MmapFileMut::load("./testfiles/tiered_wb").unwrap()).unwrap();
+        let backend = TieredBackend::from_storage(TieredData::new(cold).unwrap()).unwrap();
+        let map: FMap<_, u32, u32> = FMap::init(backend).unwrap();
+        assert_eq!(map.len(), 1000);
+        assert_eq!(map.get(&999), Some(1000));
+    }
+
+    #[test]
+    fn test_dirty_pages() {
+        let page = page_size();
+        let mut backend = make_tiered_backend("./tiered_dirty", 10);
+        backend.grow_to(page * 10).unwrap();
+        backend.push_fill(1, page * 10).unwrap();
+        assert!(backend.storage().dirty_len() >= page * 10);
+        backend.flush().unwrap();
+        assert!(!backend.storage().is_dirty());
+        assert_eq!(backend.storage().cold().len(), page * 10);
+
+        // Only the written page gets written back.
+        backend.replace_same_len(page * 5, &[2, 2]).unwrap();
+        assert_eq!(backend.storage().dirty_len(), page);
+        assert_eq!(backend.storage().cold().get(page * 5, 2), Ok(&[1, 1][..]));
+        backend.flush().unwrap();
+        assert_eq!(backend.storage().cold().get(page * 5, 2), Ok(&[2, 2][..]));
+        assert_eq!(backend.storage().dirty_len(), 0);
+
+        // Changing the length marks the page of the header.
+        Backend::set_len(&mut backend, page).unwrap();
+        assert_eq!(backend.storage().dirty_len(), page);
+        assert_eq!(backend.storage().cold().len(), page * 10);
+        backend.flush().unwrap();
+        assert_eq!(backend.storage().cold().len(), page);
+    }
+}
@@ -0,0 +1,220 @@
+use crate::Result;
+use std::sync::{Arc, Mutex};
+
+/// A memory budget shared by multiple cache-like components. Each component registers itself and reports its
+/// current memory usage. If the total usage exceeds the limit, the least recently active components are asked to
+/// evict data until the total fits into the budget again.
+#[derive(Clone)]
+pub struct MemoryBudget {
+    inner: Arc<Mutex<BudgetState>>,
+}
+
+struct BudgetState {
+    limit: usize,
+    clock: u64,
+    members: Vec<Option<Member>>,
+}
+
+#[derive(Default)]
+struct Member {
+    usage: usize,
+    last_use: u64,
+    evict: usize,
+}
+
+impl MemoryBudget {
+    /// Creates a new budget allowing `limit` bytes in total.
+    pub fn new(limit: usize) -> Self {
+        let state = BudgetState {
+            limit,
+            clock: 0,
+            members: vec![],
+        };
+        Self {
+            inner: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    /// Registers a new component. The component is removed from the budget once the handle gets dropped.
+    pub fn register(&self) -> BudgetHandle {
+        let mut state = self.inner.lock().unwrap();
+        let id = match state.members.iter().position(|i| i.is_none()) {
+            Some(id) => id,
+            None => {
+                state.members.push(None);
+                state.members.len() - 1
+            }
+        };
+        state.members[id] = Some(Member::default());
+
+        BudgetHandle {
+            budget: self.clone(),
+            id,
+        }
+    }
+
+    /// Returns the maximum amount of bytes.
+    #[inline]
+    pub fn limit(&self) -> usize {
+        self.inner.lock().unwrap().limit
+    }
+
+    /// Sets a new limit. Components are asked to evict on their next report if they exceed it.
+    pub fn set_limit(&self, limit: usize) {
+        let mut state = self.inner.lock().unwrap();
+        state.limit = limit;
+        state.assign_evictions();
+    }
+
+    /// Returns the total amount of bytes used by all registered components.
+    #[inline]
+    pub fn usage(&self) -> usize {
+        self.inner.lock().unwrap().usage()
+    }
+}
+
+impl BudgetState {
+    #[inline]
+    fn usage(&self) -> usize {
+        self.members.iter().flatten().map(|i| i.usage).sum()
+    }
+
+    /// Distributes the amount of bytes exceeding the limit over the least recently active members.
+    fn assign_evictions(&mut self) {
+        let mut excess = self.usage().saturating_sub(self.limit);
+
+        let mut members: Vec<_> = self.members.iter_mut().flatten().collect();
+        members.sort_by_key(|i| i.last_use);
+        for member in members {
+            member.evict = excess.min(member.usage);
+            excess -= member.evict;
+        }
+    }
+}
+
+/// A components registration in a `MemoryBudget`.
+pub struct BudgetHandle {
+    budget: MemoryBudget,
+    id: usize,
+}
+
+impl BudgetHandle {
+    /// Reports the current memory usage of the component and marks it as recently active. Returns the amount of
+    /// bytes the component should evict.
+    pub fn report(&self, usage: usize) -> usize {
+        let mut state = self.budget.inner.lock().unwrap();
+        state.clock += 1;
+        let clock = state.clock;
+
+        let member = state.members[self.id].as_mut().unwrap();
+        member.usage = usage;
+        member.last_use = clock;
+
+        state.assign_evictions();
+        state.members[self.id].as_ref().unwrap().evict
+    }
+
+    /// Returns the amount of bytes the component should evict without reporting any usage.
+    #[inline]
+    pub fn pending_eviction(&self) -> usize {
+        let state = self.budget.inner.lock().unwrap();
+        state.members[self.id].as_ref().unwrap().evict
+    }
+
+    /// Lets `component` evict until it fits into its share of the budget and reports its usage afterwards.
+    /// `evict_one` evicts a single item and returns the amount of bytes freed or `None` if nothing can be evicted
+    /// anymore. `usage` returns the current memory usage of the component.
+    pub fn enforce<C, E, U>(&self, component: &mut C, mut evict_one: E, usage: U) -> Result<()>
+        where
+            E: FnMut(&mut C) -> Result<Option<usize>>,
+            U: Fn(&C) -> usize,
+    {
+        let mut evict = self.pending_eviction();
+        loop {
+            let mut exhausted = false;
+            while evict > 0 {
+                let Some(freed) = evict_one(component)? else {
+                    exhausted = true;
+                    break;
+                };
+                evict = evict.saturating_sub(freed);
+            }
+
+            evict = self.report(usage(component));
+            if evict == 0 || exhausted {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Returns the budget this handle is registered in.
+    #[inline]
+    pub fn budget(&self) -> &MemoryBudget {
+        &self.budget
+    }
+}
+
+impl Drop for BudgetHandle {
+    fn drop(&mut self) {
+        let mut state = self.budget.inner.lock().unwrap();
+        state.members[self.id] = None;
+        state.assign_evictions();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_budget() {
+        let budget = MemoryBudget::new(100);
+        let a = budget.register();
+        let b = budget.register();
+
+        assert_eq!(a.report(60), 0);
+        assert_eq!(b.report(30), 0);
+        assert_eq!(budget.usage(), 90);
+
+        // `a` was active least recently so it has to evict.
+        assert_eq!(b.report(50), 0);
+        assert_eq!(a.pending_eviction(), 10);
+
+        // Exceeding more than the least recently active component uses.
+        assert_eq!(a.report(60), 0);
+        assert_eq!(b.report(120), 20);
+        assert_eq!(a.pending_eviction(), 60);
+
+        drop(a);
+        assert_eq!(b.pending_eviction(), 20);
+        assert_eq!(b.report(100), 0);
+
+        budget.set_limit(50);
+        assert_eq!(b.pending_eviction(), 50);
+
+        // Slots of dropped handles are reused.
+        let c = budget.register();
+        assert_eq!(c.id, 0);
+    }
+
+    #[test]
+    fn test_enforce() {
+        let budget = MemoryBudget::new(100);
+        let a = budget.register();
+        let b = budget.register();
+
+        let mut items = vec![30usize; 3];
+        let evict_one = |items: &mut Vec<usize>| -> Result<Option<usize>> {
+            Ok((items.len() > 1).then(|| items.remove(0)))
+        };
+        let usage = |items: &Vec<usize>| -> usize { items.iter().sum() };
+        a.enforce(&mut items, evict_one, usage).unwrap();
+        assert_eq!(items.len(), 3);
+
+        // `b` growing makes `a` evict, but never its last item.
+        b.report(80);
+        a.enforce(&mut items, evict_one, usage).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(budget.usage(), 110);
+    }
+}
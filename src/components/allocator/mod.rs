@@ -0,0 +1,313 @@
+use crate::backend::base::sub::BaseSubBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::list::List;
+use crate::components::multi_file::MultiFile;
+use crate::header::BaseHeader;
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+/// A region of space handed out by a `SpaceAllocator`. Regions are plain values and can be stored by components to
+/// find their data again after loading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Region {
+    offset: u64,
+    len: u64,
+}
+
+impl Region {
+    #[inline]
+    pub fn new(offset: usize, len: usize) -> Self {
+        Self {
+            offset: offset as u64,
+            len: len as u64,
+        }
+    }
+
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset as usize
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the offset right after the region.
+    #[inline]
+    pub fn end(&self) -> usize {
+        self.offset() + self.len()
+    }
+}
+
+type FreeList<B> = List<B, Region, 16>;
+
+/// Persistent allocator handing out regions of a single heap. Released regions are tracked in a free list and reused
+/// by later allocations, so components sharing the allocator turn deletions into reusable space instead of leaving
+/// unused bytes behind. Adjacent free regions get merged and free regions at the end of the heap are truncated.
+pub struct SpaceAllocator<B> {
+    backend: MultiFile<B>,
+}
+
+impl<B> SpaceAllocator<B>
+    where
+        B: Backend,
+{
+    /// Returns the data of `region`.
+    pub fn get(&self, region: Region) -> Result<&[u8]> {
+        let heap = self.backend.get_be_data(1)?;
+        self.check_region(region)?;
+        let start = BaseHeader::len_bytes() + region.offset();
+        Ok(&heap[start..start + region.len()])
+    }
+
+    /// Returns the amount of bytes in the heap, including released ones.
+    #[inline]
+    pub fn heap_len(&self) -> usize {
+        self.backend.get(1).map(|i| i.len()).unwrap_or(0)
+    }
+
+    /// Returns the amount of released bytes that can be reused by new allocations.
+    pub fn free_bytes(&self) -> usize {
+        self.free_list().iter().map(|i: Region| i.len()).sum()
+    }
+
+    /// Returns the amount of bytes currently allocated.
+    #[inline]
+    pub fn used_bytes(&self) -> usize {
+        self.heap_len() - self.free_bytes()
+    }
+
+    /// Returns all released regions ordered by their offset.
+    #[inline]
+    pub fn free_regions(&self) -> Vec<Region> {
+        self.free_list().iter().collect()
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.backend.flush()
+    }
+
+    /// Returns an error if `region` isn't fully located within the heap.
+    #[inline]
+    fn check_region(&self, region: Region) -> Result<()> {
+        if region.end() > self.heap_len() {
+            return Err(Error::OutOfBounds);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn free_list(&self) -> FreeList<BaseSubBackend<'_, &[u8]>> {
+        self.backend.get_backend(0).unwrap()
+    }
+}
+
+impl<B> SpaceAllocator<B>
+    where
+        B: GrowableBackend,
+{
+    /// Allocates a zeroed region of `len` bytes. Released space gets reused if a free region is large enough,
+    /// otherwise the heap grows.
+    pub fn allocate(&mut self, len: usize) -> Result<Region> {
+        if len == 0 {
+            return Ok(Region::new(0, 0));
+        }
+
+        let mut free_list: FreeList<_> = self.backend.get_backend_mut(0).unwrap();
+        let found = free_list.iter().enumerate().find(|(_, i): &(usize, Region)| i.len() >= len);
+
+        let region = match found {
+            Some((pos, free)) => {
+                if free.len() == len {
+                    free_list.remove(pos)?;
+                } else {
+                    free_list.set(pos, &Region::new(free.offset() + len, free.len() - len))?;
+                }
+                Region::new(free.offset(), len)
+            }
+            None => {
+                let mut heap = self.backend.entry_mut(1).unwrap();
+                if heap.free() < len {
+                    heap.grow(len.max(heap.capacity()))?;
+                }
+                let offset = heap.len();
+                heap.push_fill(0, len)?;
+                return Ok(Region::new(offset, len));
+            }
+        };
+
+        self.get_mut(region)?.fill(0);
+        Ok(region)
+    }
+
+    /// Releases `region` so its space can be reused. Returns an error if the region overlaps with released space.
+    pub fn release(&mut self, region: Region) -> Result<()> {
+        if region.is_empty() {
+            return Ok(());
+        }
+        self.check_region(region)?;
+
+        let heap_len = self.heap_len();
+        let mut free_list: FreeList<_> = self.backend.get_backend_mut(0).unwrap();
+        let free: Vec<Region> = free_list.iter().collect();
+
+        let pos = free.partition_point(|i| i.offset() < region.offset());
+        let prev = pos.checked_sub(1).map(|i| free[i]);
+        let next = free.get(pos).copied();
+
+        if prev.is_some_and(|p| p.end() > region.offset()) || next.is_some_and(|n| n.offset() < region.end()) {
+            return Err(Error::UnexpectedValue);
+        }
+
+        // Merge with adjacent free regions.
+        let mut merged = region;
+        let mut insert_pos = pos;
+        if let Some(n) = next.filter(|n| n.offset() == region.end()) {
+            merged = Region::new(merged.offset(), merged.len() + n.len());
+            free_list.remove(pos)?;
+        }
+        if let Some(p) = prev.filter(|p| p.end() == region.offset()) {
+            merged = Region::new(p.offset(), p.len() + merged.len());
+            free_list.remove(pos - 1)?;
+            insert_pos -= 1;
+        }
+
+        // Free space at the end of the heap gets truncated instead of tracked.
+        if merged.end() == heap_len {
+            let mut heap = self.backend.entry_mut(1).unwrap();
+            heap.set_len(merged.offset())?;
+            return Ok(());
+        }
+
+        free_list.insert(insert_pos, &merged)
+    }
+
+    /// Returns the data of `region` mutable.
+    pub fn get_mut(&mut self, region: Region) -> Result<&mut [u8]> {
+        self.check_region(region)?;
+        let heap = self.backend.get_be_data_mut(1)?;
+        let start = BaseHeader::len_bytes() + region.offset();
+        Ok(&mut heap[start..start + region.len()])
+    }
+
+    /// Allocates a region and writes `data` into it.
+    pub fn insert(&mut self, data: &[u8]) -> Result<Region> {
+        let region = self.allocate(data.len())?;
+        self.get_mut(region)?.copy_from_slice(data);
+        Ok(region)
+    }
+
+    /// Truncates unused capacity of the heap and the free list.
+    pub fn shrink_to_fit(&mut self) -> Result<()> {
+        self.backend.entry_mut(0).unwrap().shrink_to_fit()?;
+        self.backend.entry_mut(1).unwrap().shrink_to_fit()
+    }
+}
+
+impl<B: GrowableBackend> Creatable<B> for SpaceAllocator<B> {
+    fn with_capacity(backend: B, capacity: usize) -> Result<Self> {
+        let mut backend = MultiFile::create(backend)?;
+        backend.insert_new_backend::<FreeList<_>>()?;
+        let mut heap = backend.insert_empty()?;
+        heap.grow(capacity)?;
+        Ok(Self { backend })
+    }
+}
+
+impl<B: Backend> Initiable<B> for SpaceAllocator<B> {
+    fn init(backend: B) -> Result<Self> {
+        let backend = MultiFile::init(backend)?;
+        if backend.count() != 2 {
+            return Err(Error::Initialization);
+        }
+        backend.get_backend::<FreeList<_>>(0).ok_or(Error::Initialization)?;
+        Ok(Self { backend })
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_allocate(&mut mem_backend);
+        test_release(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./space_allocator", 10);
+        test_allocate(&mut mmap_backend);
+        test_release(&mut mmap_backend);
+    }
+
+    fn test_allocate<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+
+        let regions: Vec<_> = {
+            let mut alloc = SpaceAllocator::create(&mut backend).unwrap();
+            let regions: Vec<_> = (0..100u8)
+                .map(|i| alloc.insert(&vec![i; i as usize + 1]).unwrap())
+                .collect();
+            assert_eq!(alloc.heap_len(), (1..=100).sum::<usize>());
+
+            for (i, r) in regions.iter().enumerate() {
+                assert_eq!(alloc.get(*r).unwrap(), vec![i as u8; i + 1]);
+            }
+            alloc.flush().unwrap();
+            regions
+        };
+
+        let mut alloc = SpaceAllocator::init(backend).unwrap();
+        assert_eq!(alloc.get(regions[50]).unwrap(), vec![50; 51]);
+
+        // Released space gets reused and zeroed.
+        let heap_len = alloc.heap_len();
+        alloc.release(regions[50]).unwrap();
+        assert_eq!(alloc.free_bytes(), 51);
+        let new = alloc.allocate(20).unwrap();
+        assert_eq!(new.offset(), regions[50].offset());
+        assert_eq!(alloc.get(new).unwrap(), vec![0; 20]);
+        assert_eq!(alloc.free_bytes(), 31);
+        assert_eq!(alloc.heap_len(), heap_len);
+        assert_eq!(alloc.used_bytes(), heap_len - 31);
+    }
+
+    fn test_release<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut alloc = SpaceAllocator::create(&mut backend).unwrap();
+        let regions: Vec<_> = (0..5).map(|_| alloc.insert(&[1; 10]).unwrap()).collect();
+
+        alloc.release(regions[1]).unwrap();
+        alloc.release(regions[3]).unwrap();
+        assert_eq!(alloc.free_regions(), vec![regions[1], regions[3]]);
+
+        // Double releases are detected.
+        assert_eq!(alloc.release(regions[1]), Err(Error::UnexpectedValue));
+        assert_eq!(alloc.release(Region::new(15, 10)), Err(Error::UnexpectedValue));
+
+        // Adjacent regions get merged.
+        alloc.release(regions[2]).unwrap();
+        assert_eq!(alloc.free_regions(), vec![Region::new(10, 30)]);
+
+        // Releasing the end of the heap truncates it, including free regions before.
+        alloc.release(regions[4]).unwrap();
+        assert!(alloc.free_regions().is_empty());
+        assert_eq!(alloc.heap_len(), 10);
+
+        assert_eq!(alloc.get(Region::new(5, 10)), Err(Error::OutOfBounds));
+        assert_eq!(alloc.allocate(0).unwrap(), Region::new(0, 0));
+    }
+}
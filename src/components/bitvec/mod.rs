@@ -216,6 +216,13 @@ where
         Ok(())
     }
 
+    /// Returns the bits packed into bytes where bit `i` is stored in byte `i / 8` at bit `i % 8`, least significant
+    /// bit first. Unused bits of the last byte are undefined.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.backend.content_data()[FIRST_INDEX..]
+    }
+
     /// Returns an iterator over all items in the `BitVec`.
     #[inline]
     pub fn iter(&self) -> BitVecIter<B> {
@@ -231,7 +238,7 @@ where
     #[inline]
     fn set_byte_unchecked(&mut self, byte_index: usize, val: u8) {
         let index = self.backend.first_index() + byte_index;
-        self.backend.data_mut()[index] = val;
+        self.backend.data_mut_range(index..index + 1)[0] = val;
     }
 
     #[inline]
@@ -280,11 +287,11 @@ where
 
         // Grow backend if free space doesn't fit
         if be_free < bytes_needed {
-            let grow_len = bytes_needed - be_free;
-            // Grow by factor 2 to reduce reallocations.
-            let len = grow_len.max((self.backend.capacity() - 8) * 2);
-
-            self.backend.grow(len)?;
+            // Capacities without the bit count stored at the beginning.
+            let capacity = self.backend.capacity() - FIRST_INDEX;
+            let required = self.backend.len() - FIRST_INDEX + bytes_needed;
+            let new_capacity = self.backend.growth_policy().next_capacity(capacity, required);
+            self.backend.grow_to(new_capacity + FIRST_INDEX)?;
         }
 
         self.backend.set_len(self.backend.len() + bytes_needed)?;
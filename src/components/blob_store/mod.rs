@@ -0,0 +1,389 @@
+use crate::backend::base::sub::GeneralSubBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::indexed_file::IndexedFile;
+use crate::components::map::FMap;
+use crate::components::multi_file::entry_mut::MFileEntryMut;
+use crate::components::multi_file::MultiFile;
+use crate::deser::{deserialize_impl, serialize_impl};
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use siphasher::sip128::{Hasher128, SipHasher13};
+use std::hash::Hasher;
+
+/// ID of the entry holding the stores metadata.
+const METADATA_ID: usize = 0;
+
+/// ID of the entry holding the map of content hashes to blob IDs.
+const MAP_ID: usize = 1;
+
+/// ID of the entry holding the blobs.
+const BLOBS_ID: usize = 2;
+
+/// Length of the reference count at the start of each blob.
+const REFS_LEN: usize = 4;
+
+/// Hash identifying a blob by its content.
+pub type BlobHash = u128;
+
+#[derive(Serialize, Deserialize)]
+struct BlobMetadata {
+    len: u64,
+
+    /// Amount of blobs without references that still occupy space.
+    dead: u64,
+}
+
+/// Returns the content hash of `data`, which is the ID a `BlobStore` returns for it.
+pub fn content_hash(data: &[u8]) -> BlobHash {
+    let mut hasher = SipHasher13::new();
+    hasher.write(data);
+    hasher.finish128().as_u128()
+}
+
+/// Content addressed storage for byte blobs. Inserting returns the content hash of the blob, and inserting the same
+/// content again only increments a reference count instead of storing it twice. Blobs get dropped once all of their
+/// references are removed.
+///
+/// Dropped blobs keep their space until there are more dropped than stored blobs, in which case the store gets
+/// rebuilt with only the stored blobs.
+///
+/// The internal format: MultiFile(METADATA | FMap<BlobHash, BLOB ID> | IndexedFile<BLOB>)
+/// where each blob is (REFERENCES (4 bytes) | DATA)
+pub struct BlobStore<B> {
+    storage: MultiFile<B>,
+    len: usize,
+    dead: usize,
+}
+
+impl<B> BlobStore<B> {
+    /// Returns the amount of stored blobs.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if there are no blobs stored.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<B> BlobStore<B>
+    where
+        B: Backend,
+{
+    /// Returns the blob with the given content hash.
+    pub fn get(&self, hash: BlobHash) -> Option<Vec<u8>> {
+        let id = self.map().get(&hash)?;
+        let blobs = self.blobs();
+        let blob = blobs.get(id as usize).ok()?;
+        (refs_of(blob) > 0).then(|| blob[REFS_LEN..].to_vec())
+    }
+
+    /// Returns `true` if a blob with the given content hash is stored.
+    #[inline]
+    pub fn contains(&self, hash: BlobHash) -> bool {
+        self.ref_count(hash) > 0
+    }
+
+    /// Returns the amount of references to the blob with the given content hash.
+    pub fn ref_count(&self, hash: BlobHash) -> u32 {
+        self.map()
+            .get(&hash)
+            .and_then(|id| Some(refs_of(self.blobs().get(id as usize).ok()?)))
+            .unwrap_or(0)
+    }
+
+    /// Returns an iterator over all stored blobs with their content hashes in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (BlobHash, Vec<u8>)> + '_ {
+        let blobs = self.blobs();
+        self.map().iter().collect::<Vec<_>>().into_iter().filter_map(move |(hash, id)| {
+            let blob = blobs.get(id as usize).ok()?;
+            (refs_of(blob) > 0).then(|| (hash, blob[REFS_LEN..].to_vec()))
+        })
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.storage.flush()
+    }
+
+    #[inline]
+    fn map(&self) -> FMap<GeneralSubBackend<'_>, BlobHash, u32> {
+        self.storage.get_backend(MAP_ID).unwrap()
+    }
+
+    #[inline]
+    fn blobs(&self) -> IndexedFile<GeneralSubBackend<'_>> {
+        self.storage.get_backend(BLOBS_ID).unwrap()
+    }
+}
+
+impl<B> BlobStore<B>
+    where
+        B: GrowableBackend,
+{
+    /// Inserts a blob and returns its content hash. If the blob is already stored, its reference count gets
+    /// incremented instead. Returns `Error::UnexpectedValue` if a different blob with the same hash is stored.
+    pub fn insert(&mut self, data: &[u8]) -> Result<BlobHash> {
+        let hash = content_hash(data);
+
+        if let Some(id) = self.map().get(&hash) {
+            let mut blobs = self.blobs_mut();
+            let blob = blobs.get_mut(id as usize)?;
+            if &blob[REFS_LEN..] != data {
+                return Err(Error::UnexpectedValue);
+            }
+
+            let refs = refs_of(blob);
+            blob[..REFS_LEN].copy_from_slice(&(refs + 1).to_le_bytes());
+            if refs == 0 {
+                // Blob was dropped before but its data wasn't compacted away yet.
+                self.dead -= 1;
+                self.len += 1;
+                self.write_metadata()?;
+            }
+            return Ok(hash);
+        }
+
+        let mut blob = Vec::with_capacity(REFS_LEN + data.len());
+        blob.extend_from_slice(&1u32.to_le_bytes());
+        blob.extend_from_slice(data);
+        let id = self.blobs_mut().insert(&blob)?;
+        self.map_mut().insert(&hash, &(id as u32))?;
+        self.len += 1;
+        self.write_metadata()?;
+        Ok(hash)
+    }
+
+    /// Removes a reference to the blob with the given content hash and returns the amount of remaining references.
+    /// The blob gets dropped if there are no references left. Returns `None` if there is no such blob.
+    pub fn remove(&mut self, hash: BlobHash) -> Result<Option<u32>> {
+        let Some(id) = self.map().get(&hash) else {
+            return Ok(None);
+        };
+
+        let mut blobs = self.blobs_mut();
+        let blob = blobs.get_mut(id as usize)?;
+        let refs = refs_of(blob);
+        if refs == 0 {
+            return Ok(None);
+        }
+        blob[..REFS_LEN].copy_from_slice(&(refs - 1).to_le_bytes());
+
+        if refs == 1 {
+            self.len -= 1;
+            self.dead += 1;
+            if self.dead > self.len {
+                self.compact()?;
+            } else {
+                self.write_metadata()?;
+            }
+        }
+
+        Ok(Some(refs - 1))
+    }
+
+    /// Removes all blobs.
+    pub fn clear(&mut self) -> Result<()> {
+        self.storage.clear();
+        self.len = 0;
+        self.dead = 0;
+        self.init_storage()
+    }
+
+    /// Rebuilds the store with only the stored blobs to free the space of dropped ones.
+    pub fn compact(&mut self) -> Result<()> {
+        let blobs = self.blobs();
+        let live: Vec<_> = (0..blobs.count())
+            .filter_map(|id| blobs.get(id).ok())
+            .filter(|blob| refs_of(blob) > 0)
+            .map(|blob| blob.to_vec())
+            .collect();
+
+        self.clear()?;
+        for blob in live.iter() {
+            let id = self.blobs_mut().insert(blob)?;
+            self.map_mut().insert(&content_hash(&blob[REFS_LEN..]), &(id as u32))?;
+        }
+        self.len = live.len();
+        self.write_metadata()
+    }
+
+    /// Inserts the metadata, an empty map and empty blob storage into the empty storage.
+    fn init_storage(&mut self) -> Result<()> {
+        self.storage.insert_empty()?;
+        self.write_metadata()?;
+        FMap::<_, BlobHash, u32>::create(self.storage.insert_empty()?)?;
+        IndexedFile::create(self.storage.insert_empty()?)?;
+        Ok(())
+    }
+
+    #[inline]
+    fn map_mut(&mut self) -> FMap<MFileEntryMut<'_, B>, BlobHash, u32> {
+        self.storage.get_backend_mut(MAP_ID).unwrap()
+    }
+
+    #[inline]
+    fn blobs_mut(&mut self) -> IndexedFile<MFileEntryMut<'_, B>> {
+        self.storage.get_backend_mut(BLOBS_ID).unwrap()
+    }
+
+    fn write_metadata(&mut self) -> Result<()> {
+        let metadata = BlobMetadata {
+            len: self.len as u64,
+            dead: self.dead as u64,
+        };
+        self.storage.set_entry(METADATA_ID, &serialize_impl(&metadata)?)
+    }
+}
+
+/// Returns the reference count of an encoded blob.
+#[inline]
+fn refs_of(blob: &[u8]) -> u32 {
+    u32::from_le_bytes(blob[..REFS_LEN].try_into().unwrap())
+}
+
+impl<B> Creatable<B> for BlobStore<B>
+    where
+        B: GrowableBackend,
+{
+    fn with_capacity(backend: B, _: usize) -> Result<Self> {
+        let mut store = Self {
+            storage: MultiFile::with_capacity(backend, 3)?,
+            len: 0,
+            dead: 0,
+        };
+        store.init_storage()?;
+        Ok(store)
+    }
+}
+
+impl<B> Initiable<B> for BlobStore<B>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let storage = MultiFile::init(backend)?;
+        let metadata: BlobMetadata = storage
+            .get(METADATA_ID)
+            .ok_or(Error::Initialization)
+            .and_then(|i| deserialize_impl(i.content_data()))?;
+
+        if !storage.has_id(MAP_ID) || !storage.has_id(BLOBS_ID) {
+            return Err(Error::Initialization);
+        }
+
+        Ok(Self {
+            storage,
+            len: metadata.len as usize,
+            dead: metadata.dead as usize,
+        })
+    }
+}
+
+impl<B> MType for BlobStore<B>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.storage.raw_data()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_dedup(&mut mem_backend);
+        test_remove(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./blob_store", 10);
+        test_dedup(&mut mmap_backend);
+        test_remove(&mut mmap_backend);
+    }
+
+    fn blob(i: usize) -> Vec<u8> {
+        (0..i * 7 % 300).map(|j| (i + j) as u8).collect()
+    }
+
+    fn test_dedup<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut hashes = vec![];
+        let size;
+        {
+            let mut store = BlobStore::create(&mut backend).unwrap();
+            assert_eq!(store.get(content_hash(b"")), None);
+            for i in 0..200 {
+                hashes.push(store.insert(&blob(i)).unwrap());
+            }
+            size = store.raw_data().len();
+
+            // Storing the same blobs again only increments their reference count.
+            for (i, hash) in hashes.iter().enumerate() {
+                assert_eq!(store.insert(&blob(i)), Ok(*hash));
+            }
+            assert_eq!(store.raw_data().len(), size);
+        }
+
+        let store = BlobStore::init(&mut backend).unwrap();
+        assert_eq!(store.len(), 200);
+        for (i, hash) in hashes.iter().enumerate() {
+            assert_eq!(*hash, content_hash(&blob(i)));
+            assert_eq!(store.get(*hash), Some(blob(i)));
+            assert_eq!(store.ref_count(*hash), 2);
+        }
+        assert!(!store.contains(content_hash(b"not stored")));
+
+        let items: HashMap<_, _> = store.iter().collect();
+        assert_eq!(items.len(), 200);
+        assert_eq!(items[&hashes[5]], blob(5));
+    }
+
+    fn test_remove<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut store = BlobStore::create(&mut backend).unwrap();
+        let hashes: Vec<_> = (0..100).map(|i| store.insert(&blob(i)).unwrap()).collect();
+        store.insert(&blob(3)).unwrap();
+
+        assert_eq!(store.remove(hashes[3]), Ok(Some(1)));
+        assert!(store.contains(hashes[3]));
+        assert_eq!(store.remove(hashes[3]), Ok(Some(0)));
+        assert!(!store.contains(hashes[3]));
+        assert_eq!(store.get(hashes[3]), None);
+        assert_eq!(store.remove(hashes[3]), Ok(None));
+        assert_eq!(store.len(), 99);
+
+        // Dropped blobs can be inserted again.
+        assert_eq!(store.insert(&blob(3)), Ok(hashes[3]));
+        assert_eq!(store.get(hashes[3]), Some(blob(3)));
+        assert_eq!(store.ref_count(hashes[3]), 1);
+
+        // Dropping more than half of the blobs compacts the store.
+        for hash in &hashes[..60] {
+            assert_eq!(store.remove(*hash), Ok(Some(0)));
+        }
+        assert!(store.blobs().count() < 60);
+        assert_eq!(store.len(), 40);
+        for (i, hash) in hashes.iter().enumerate() {
+            assert_eq!(store.get(*hash), (i >= 60).then(|| blob(i)));
+        }
+
+        let store = BlobStore::init(&mut backend).unwrap();
+        assert_eq!(store.len(), 40);
+        assert_eq!(store.iter().count(), 40);
+    }
+}
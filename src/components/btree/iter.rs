@@ -0,0 +1,119 @@
+use crate::backend::Backend;
+use crate::components::btree::BTree;
+use serde::de::DeserializeOwned;
+use std::ops::{Bound, RangeBounds};
+use std::vec::IntoIter;
+
+/// Iterator over the items of a `BTree` within a range of keys.
+pub struct Range<'a, B, K, V, R> {
+    tree: &'a BTree<B, K, V>,
+    range: R,
+
+    /// Nodes on the path to the next item.
+    stack: Vec<Frame<K, V>>,
+}
+
+/// A node whose items left of `pos` have already been visited.
+struct Frame<K, V> {
+    keys: IntoIter<K>,
+    values: IntoIter<V>,
+    children: Vec<u64>,
+    pos: usize,
+}
+
+impl<'a, B, K, V, R> Range<'a, B, K, V, R>
+    where
+        B: Backend,
+        K: DeserializeOwned + Ord,
+        V: DeserializeOwned,
+        R: RangeBounds<K>,
+{
+    pub(super) fn new(tree: &'a BTree<B, K, V>, range: R) -> Self {
+        let mut iter = Self {
+            tree,
+            range,
+            stack: vec![],
+        };
+        iter.seek(tree.root());
+        iter
+    }
+
+    /// Descends from the node with the given ID to the first key within the range.
+    fn seek(&mut self, mut id: usize) {
+        loop {
+            let mut node = self.tree.load_node(id).unwrap();
+            let pos = match self.range.start_bound() {
+                Bound::Included(start) => node.keys.partition_point(|k| k < start),
+                Bound::Excluded(start) => node.keys.partition_point(|k| k <= start),
+                Bound::Unbounded => 0,
+            };
+
+            node.keys.drain(..pos);
+            node.values.drain(..pos);
+            let child = node.children.get(pos).copied();
+            self.stack.push(Frame {
+                keys: node.keys.into_iter(),
+                values: node.values.into_iter(),
+                children: node.children,
+                pos,
+            });
+
+            match child {
+                Some(child) => id = child as usize,
+                None => return,
+            }
+        }
+    }
+
+    /// Descends from the node with the given ID to its smallest key.
+    fn seek_first(&mut self, mut id: usize) {
+        loop {
+            let node = self.tree.load_node(id).unwrap();
+            let child = node.children.first().copied();
+            self.stack.push(Frame {
+                keys: node.keys.into_iter(),
+                values: node.values.into_iter(),
+                children: node.children,
+                pos: 0,
+            });
+
+            match child {
+                Some(child) => id = child as usize,
+                None => return,
+            }
+        }
+    }
+}
+
+impl<'a, B, K, V, R> Iterator for Range<'a, B, K, V, R>
+    where
+        B: Backend,
+        K: DeserializeOwned + Ord,
+        V: DeserializeOwned,
+        R: RangeBounds<K>,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let frame = self.stack.last_mut()?;
+            let Some(key) = frame.keys.next() else {
+                self.stack.pop();
+                continue;
+            };
+            let value = frame.values.next().unwrap();
+            frame.pos += 1;
+
+            // Keys are visited in order so the first key outside of the range ends the iteration.
+            if !self.range.contains(&key) {
+                self.stack.clear();
+                return None;
+            }
+
+            if let Some(child) = frame.children.get(frame.pos).copied() {
+                self.seek_first(child as usize);
+            }
+            return Some((key, value));
+        }
+    }
+}
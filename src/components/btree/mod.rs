@@ -0,0 +1,673 @@
+pub mod iter;
+
+use crate::backend::base::sub::GeneralSubBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::btree::iter::Range;
+use crate::components::list::List;
+use crate::components::multi_file::entry_mut::MFileEntryMut;
+use crate::components::multi_file::MultiFile;
+use crate::deser::{deserialize_impl, serialize_impl};
+use crate::traits::creatable::Creatable;
+use crate::traits::deser::Deser;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::{Error, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+use std::ops::RangeBounds;
+
+/// Default minimum degree. Each node except the root holds between `degree - 1` and `2 * degree - 1` keys.
+const DEFAULT_DEGREE: usize = 16;
+
+/// ID of the entry holding the trees metadata.
+const METADATA_ID: usize = 0;
+
+/// ID of the entry holding the IDs of entries of removed nodes which can be reused.
+const FREE_ID: usize = 1;
+
+/// ID of the entry holding the root of an empty tree.
+const INITIAL_ROOT_ID: usize = 2;
+
+#[derive(Serialize, Deserialize)]
+struct BTreeMetadata {
+    degree: u32,
+    len: u64,
+    root: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Node<K, V> {
+    keys: Vec<K>,
+    values: Vec<V>,
+
+    /// Entry IDs of the children. Empty for leaves.
+    children: Vec<u64>,
+}
+
+impl<K, V> Node<K, V> {
+    #[inline]
+    fn leaf() -> Self {
+        Self {
+            keys: vec![],
+            values: vec![],
+            children: vec![],
+        }
+    }
+
+    #[inline]
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+/// Ordered map stored as a B-tree. Unlike `FMap` it supports range scans and prefix queries.
+///
+/// The internal format: MultiFile(METADATA | FREE | NODE | NODE | ...)
+/// where FREE is a `List<u64>` of the entry IDs of removed nodes and each node is a serialized list of keys, values and
+/// the entry IDs of its children.
+pub struct BTree<B, K, V> {
+    storage: MultiFile<B>,
+    degree: usize,
+    len: usize,
+    root: usize,
+    p: PhantomData<(K, V)>,
+}
+
+impl<B, K, V> BTree<B, K, V>
+    where
+        B: GrowableBackend,
+{
+    /// Creates a new tree with the given minimum degree which has to be at least 2.
+    pub fn with_degree(backend: B, degree: usize) -> Result<Self> {
+        if degree < 2 {
+            return Err(Error::UnexpectedValue);
+        }
+
+        let mut tree = Self {
+            storage: MultiFile::with_capacity(backend, 3)?,
+            degree,
+            len: 0,
+            root: INITIAL_ROOT_ID,
+            p: PhantomData,
+        };
+        tree.init_storage()?;
+        Ok(tree)
+    }
+
+    /// Removes all items.
+    pub fn clear(&mut self) -> Result<()> {
+        self.storage.clear();
+        self.len = 0;
+        self.root = INITIAL_ROOT_ID;
+        self.init_storage()
+    }
+
+    /// Flushes the tree to the backend.
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.storage.flush()
+    }
+
+    /// Inserts the metadata, an empty free list and an empty root into the empty storage.
+    fn init_storage(&mut self) -> Result<()> {
+        self.storage.insert_empty()?;
+        self.storage.insert_new_backend::<List<_, u64, 8>>()?;
+        self.storage.insert_empty()?;
+        self.write_metadata()?;
+        self.storage.set_entry(self.root, &serialize_impl(&Node::<(), ()>::leaf())?)
+    }
+
+    fn write_metadata(&mut self) -> Result<()> {
+        let metadata = BTreeMetadata {
+            degree: self.degree as u32,
+            len: self.len as u64,
+            root: self.root as u64,
+        };
+        self.storage.set_entry(METADATA_ID, &serialize_impl(&metadata)?)
+    }
+
+    /// Returns the ID of an unused entry for a new node.
+    fn alloc_node(&mut self) -> Result<usize> {
+        let mut free = self.free_mut();
+        if !free.is_empty() {
+            let id = free.get(free.len() - 1)?;
+            free.pop()?;
+            return Ok(id as usize);
+        }
+        let id = self.storage.count();
+        self.storage.insert_empty()?;
+        Ok(id)
+    }
+
+    #[inline]
+    fn free_node(&mut self, id: usize) -> Result<()> {
+        self.storage.entry_mut(id).ok_or(Error::OutOfBounds)?.clear();
+        self.free_mut().push(&(id as u64))
+    }
+
+    #[inline]
+    fn free_mut(&mut self) -> List<MFileEntryMut<'_, B>, u64, 8> {
+        self.storage.get_backend_mut(FREE_ID).unwrap()
+    }
+}
+
+impl<B, K, V> BTree<B, K, V>
+    where
+        B: GrowableBackend,
+        K: Deser + Ord,
+        V: Deser,
+{
+    /// Inserts a key with its value and returns the previous value of the key.
+    pub fn insert(&mut self, k: K, v: V) -> Result<Option<V>> {
+        let mut id = self.root;
+        let mut node = self.load_node(id)?;
+
+        // Nodes get split on the way down so there is always room for the median of a full child.
+        if node.keys.len() == self.max_keys() {
+            let new_root = self.alloc_node()?;
+            let mut root = Node::leaf();
+            root.children.push(id as u64);
+            let (left, right) = self.split_child(&mut root, 0, node)?;
+            self.write_node(new_root, &root)?;
+            self.root = new_root;
+
+            (id, node) = match k.cmp(&root.keys[0]) {
+                Ordering::Less => (id, left),
+                Ordering::Greater => (root.children[1] as usize, right),
+                Ordering::Equal => (new_root, root),
+            };
+        }
+
+        let res = loop {
+            let i = match node.keys.binary_search(&k) {
+                Ok(i) => break Some(std::mem::replace(&mut node.values[i], v)),
+                Err(i) => i,
+            };
+
+            if node.is_leaf() {
+                node.keys.insert(i, k);
+                node.values.insert(i, v);
+                self.len += 1;
+                break None;
+            }
+
+            let mut child_id = node.children[i] as usize;
+            let mut child = self.load_node(child_id)?;
+            if child.keys.len() == self.max_keys() {
+                let (left, right) = self.split_child(&mut node, i, child)?;
+                self.write_node(id, &node)?;
+                child = match k.cmp(&node.keys[i]) {
+                    Ordering::Less => left,
+                    Ordering::Greater => {
+                        child_id = node.children[i + 1] as usize;
+                        right
+                    }
+                    // The key got moved into `node`.
+                    Ordering::Equal => continue,
+                };
+            }
+
+            id = child_id;
+            node = child;
+        };
+
+        self.write_node(id, &node)?;
+        self.write_metadata()?;
+        Ok(res)
+    }
+
+    /// Removes a key from the tree and returns its value.
+    pub fn remove(&mut self, k: &K) -> Result<Option<V>> {
+        let res = self.remove_from(self.root, k)?;
+
+        let root = self.load_node(self.root)?;
+        if root.keys.is_empty() && !root.is_leaf() {
+            let old_root = self.root;
+            self.root = root.children[0] as usize;
+            self.free_node(old_root)?;
+        }
+
+        if res.is_some() {
+            self.len -= 1;
+        }
+        self.write_metadata()?;
+        Ok(res)
+    }
+
+    /// Splits the full child `i` of `parent` into two nodes and moves its median into `parent`. Returns both halves.
+    fn split_child(&mut self, parent: &mut Node<K, V>, i: usize, mut child: Node<K, V>) -> Result<(Node<K, V>, Node<K, V>)> {
+        let t = self.degree;
+        let right = Node {
+            keys: child.keys.split_off(t),
+            values: child.values.split_off(t),
+            children: if child.is_leaf() { vec![] } else { child.children.split_off(t) },
+        };
+        let median_key = child.keys.pop().unwrap();
+        let median_value = child.values.pop().unwrap();
+
+        let right_id = self.alloc_node()?;
+        parent.keys.insert(i, median_key);
+        parent.values.insert(i, median_value);
+        parent.children.insert(i + 1, right_id as u64);
+
+        self.write_node(parent.children[i] as usize, &child)?;
+        self.write_node(right_id, &right)?;
+        Ok((child, right))
+    }
+
+    fn remove_from(&mut self, id: usize, k: &K) -> Result<Option<V>> {
+        let mut node = self.load_node(id)?;
+        match node.keys.binary_search(k) {
+            Ok(i) if node.is_leaf() => {
+                node.keys.remove(i);
+                let value = node.values.remove(i);
+                self.write_node(id, &node)?;
+                Ok(Some(value))
+            }
+            Ok(i) => {
+                let t = self.degree;
+                let left_id = node.children[i] as usize;
+                let right_id = node.children[i + 1] as usize;
+
+                // Replace the key with its predecessor or successor if the respective child can lose a key.
+                let (key, value) = if self.load_node(left_id)?.keys.len() >= t {
+                    self.remove_edge(left_id, true)?
+                } else if self.load_node(right_id)?.keys.len() >= t {
+                    self.remove_edge(right_id, false)?
+                } else {
+                    self.merge_children(&mut node, i)?;
+                    self.write_node(id, &node)?;
+                    return self.remove_from(left_id, k);
+                };
+
+                node.keys[i] = key;
+                let value = std::mem::replace(&mut node.values[i], value);
+                self.write_node(id, &node)?;
+                Ok(Some(value))
+            }
+            Err(_) if node.is_leaf() => Ok(None),
+            Err(i) => {
+                let i = self.ensure_child(&mut node, id, i)?;
+                self.remove_from(node.children[i] as usize, k)
+            }
+        }
+    }
+
+    /// Removes the largest (`last == true`) or smallest key of the subtree.
+    fn remove_edge(&mut self, id: usize, last: bool) -> Result<(K, V)> {
+        let mut node = self.load_node(id)?;
+        if node.is_leaf() {
+            let (key, value) = if last {
+                (node.keys.pop().unwrap(), node.values.pop().unwrap())
+            } else {
+                (node.keys.remove(0), node.values.remove(0))
+            };
+            self.write_node(id, &node)?;
+            return Ok((key, value));
+        }
+
+        let i = if last { node.keys.len() } else { 0 };
+        let i = self.ensure_child(&mut node, id, i)?;
+        self.remove_edge(node.children[i] as usize, last)
+    }
+
+    /// Makes sure child `i` of `node` has at least `degree` keys so a key can be removed from it, by borrowing a key
+    /// from a sibling or merging it with one. Returns the index of the child afterwards.
+    fn ensure_child(&mut self, node: &mut Node<K, V>, id: usize, i: usize) -> Result<usize> {
+        let t = self.degree;
+        let child_id = node.children[i] as usize;
+        let mut child = self.load_node(child_id)?;
+        if child.keys.len() >= t {
+            return Ok(i);
+        }
+
+        if i > 0 {
+            let left_id = node.children[i - 1] as usize;
+            let mut left = self.load_node(left_id)?;
+            if left.keys.len() >= t {
+                let key = std::mem::replace(&mut node.keys[i - 1], left.keys.pop().unwrap());
+                let value = std::mem::replace(&mut node.values[i - 1], left.values.pop().unwrap());
+                child.keys.insert(0, key);
+                child.values.insert(0, value);
+                if let Some(c) = left.children.pop() {
+                    child.children.insert(0, c);
+                }
+                self.write_node(left_id, &left)?;
+                self.write_node(child_id, &child)?;
+                self.write_node(id, node)?;
+                return Ok(i);
+            }
+        }
+
+        if i < node.keys.len() {
+            let right_id = node.children[i + 1] as usize;
+            let mut right = self.load_node(right_id)?;
+            if right.keys.len() >= t {
+                let key = std::mem::replace(&mut node.keys[i], right.keys.remove(0));
+                let value = std::mem::replace(&mut node.values[i], right.values.remove(0));
+                child.keys.push(key);
+                child.values.push(value);
+                if !right.is_leaf() {
+                    child.children.push(right.children.remove(0));
+                }
+                self.write_node(right_id, &right)?;
+                self.write_node(child_id, &child)?;
+                self.write_node(id, node)?;
+                return Ok(i);
+            }
+        }
+
+        let i = if i < node.keys.len() { i } else { i - 1 };
+        self.merge_children(node, i)?;
+        self.write_node(id, node)?;
+        Ok(i)
+    }
+
+    /// Merges child `i + 1` and the key `i` of `node` into child `i`. The caller has to write `node`.
+    fn merge_children(&mut self, node: &mut Node<K, V>, i: usize) -> Result<()> {
+        let left_id = node.children[i] as usize;
+        let right_id = node.children.remove(i + 1) as usize;
+        let mut left = self.load_node(left_id)?;
+        let right = self.load_node(right_id)?;
+
+        left.keys.push(node.keys.remove(i));
+        left.values.push(node.values.remove(i));
+        left.keys.extend(right.keys);
+        left.values.extend(right.values);
+        left.children.extend(right.children);
+
+        self.write_node(left_id, &left)?;
+        self.free_node(right_id)
+    }
+
+    #[inline]
+    fn write_node(&mut self, id: usize, node: &Node<K, V>) -> Result<()> {
+        self.storage.set_entry(id, &serialize_impl(node)?)
+    }
+
+    #[inline]
+    fn max_keys(&self) -> usize {
+        2 * self.degree - 1
+    }
+}
+
+impl<B, K, V> BTree<B, K, V>
+    where
+        B: Backend,
+        K: DeserializeOwned + Ord,
+        V: DeserializeOwned,
+{
+    /// Returns the value of a key.
+    pub fn get(&self, k: &K) -> Option<V> {
+        let mut node = self.load_node(self.root).ok()?;
+        loop {
+            match node.keys.binary_search(k) {
+                Ok(i) => return Some(node.values.swap_remove(i)),
+                Err(_) if node.is_leaf() => return None,
+                Err(i) => node = self.load_node(node.children[i] as usize).ok()?,
+            }
+        }
+    }
+
+    /// Returns `true` if the tree contains the given key.
+    #[inline]
+    pub fn contains_key(&self, k: &K) -> bool {
+        self.get(k).is_some()
+    }
+
+    /// Returns an iterator over all items ordered by their keys.
+    #[inline]
+    pub fn iter(&self) -> Range<'_, B, K, V, std::ops::RangeFull> {
+        self.range(..)
+    }
+
+    /// Returns an iterator over the items whose keys are within `range`, ordered by their keys.
+    #[inline]
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, B, K, V, R> {
+        Range::new(self, range)
+    }
+
+    /// Returns an iterator over the items whose keys start with `prefix`, ordered by their keys.
+    pub fn prefix<'a>(&'a self, prefix: &'a K) -> impl Iterator<Item = (K, V)> + 'a
+        where
+            K: Prefix,
+    {
+        self.range((std::ops::Bound::Included(prefix), std::ops::Bound::Unbounded))
+            .take_while(move |(k, _)| k.starts_with(prefix))
+    }
+
+    pub(crate) fn load_node(&self, id: usize) -> Result<Node<K, V>> {
+        let entry = self.storage.get(id).ok_or(Error::OutOfBounds)?;
+        deserialize_impl(entry.content_data())
+    }
+
+    #[inline]
+    pub(crate) fn root(&self) -> usize {
+        self.root
+    }
+}
+
+impl<B, K, V> BTree<B, K, V> {
+    /// Returns the amount of items in the tree.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the tree doesn't contain any items.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the minimum degree of the tree.
+    #[inline]
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+}
+
+/// Keys that can be searched by their prefix using `BTree::prefix`.
+pub trait Prefix: Ord {
+    fn starts_with(&self, prefix: &Self) -> bool;
+}
+
+impl Prefix for String {
+    #[inline]
+    fn starts_with(&self, prefix: &Self) -> bool {
+        self.as_str().starts_with(prefix.as_str())
+    }
+}
+
+impl Prefix for Vec<u8> {
+    #[inline]
+    fn starts_with(&self, prefix: &Self) -> bool {
+        self.as_slice().starts_with(prefix)
+    }
+}
+
+impl<B, K, V> Creatable<B> for BTree<B, K, V>
+    where
+        B: GrowableBackend,
+{
+    #[inline]
+    fn with_capacity(backend: B, _: usize) -> Result<Self> {
+        Self::with_degree(backend, DEFAULT_DEGREE)
+    }
+}
+
+impl<B, K, V> Initiable<B> for BTree<B, K, V>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let storage = MultiFile::init(backend)?;
+        let metadata: BTreeMetadata = storage
+            .get(METADATA_ID)
+            .ok_or(Error::Initialization)
+            .and_then(|i| deserialize_impl(i.content_data()))?;
+
+        let root = metadata.root as usize;
+        if metadata.degree < 2 || root < INITIAL_ROOT_ID || !storage.has_id(root) {
+            return Err(Error::Initialization);
+        }
+        let _: List<GeneralSubBackend<'_>, u64, 8> = storage.get_backend(FREE_ID).ok_or(Error::Initialization)?;
+
+        Ok(Self {
+            storage,
+            degree: metadata.degree as usize,
+            len: metadata.len as usize,
+            root,
+            p: PhantomData,
+        })
+    }
+}
+
+impl<B, K, V> MType for BTree<B, K, V>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.storage.raw_data()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_insert(&mut mem_backend);
+        test_remove(&mut mem_backend);
+        test_range(&mut mem_backend);
+        test_prefix(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./btree", 10);
+        test_insert(&mut mmap_backend);
+        test_remove(&mut mmap_backend);
+        test_range(&mut mmap_backend);
+        test_prefix(&mut mmap_backend);
+    }
+
+    /// Deterministic permutation of 0..count.
+    fn shuffled(count: u32) -> Vec<u32> {
+        (0..count).map(|i| (i * 7919) % count).collect()
+    }
+
+    fn test_insert<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        {
+            let mut tree = BTree::with_degree(&mut backend, 2).unwrap();
+            for i in shuffled(1000) {
+                assert_eq!(tree.insert(i, i * 2), Ok(None));
+            }
+            assert_eq!(tree.insert(10, 11), Ok(Some(20)));
+            assert_eq!(tree.len(), 1000);
+        }
+
+        let tree: BTree<_, u32, u32> = BTree::init(&mut backend).unwrap();
+        assert_eq!(tree.len(), 1000);
+        assert_eq!(tree.degree(), 2);
+        assert_eq!(tree.get(&10), Some(11));
+        for i in 0..1000 {
+            assert_eq!(tree.get(&i), Some(if i == 10 { 11 } else { i * 2 }));
+        }
+        assert_eq!(tree.get(&1000), None);
+        assert!(!tree.contains_key(&1001));
+        assert!(tree.iter().map(|i| i.0).eq(0..1000));
+    }
+
+    fn test_remove<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        for degree in [2, 3, 16] {
+            let mut tree = BTree::with_degree(&mut backend, degree).unwrap();
+            let mut expected = BTreeMap::new();
+            for i in shuffled(500) {
+                tree.insert(i, i.to_string()).unwrap();
+                expected.insert(i, i.to_string());
+            }
+
+            for (n, i) in shuffled(500).into_iter().rev().enumerate() {
+                if n % 3 == 0 {
+                    continue;
+                }
+                assert_eq!(tree.remove(&i), Ok(expected.remove(&i)));
+                assert_eq!(tree.remove(&i), Ok(None));
+            }
+            assert_eq!(tree.len(), expected.len());
+            assert!(tree.iter().eq(expected.clone()));
+
+            // Removed nodes get reused, also after reloading the tree.
+            let nodes = tree.storage.count();
+            let mut tree: BTree<_, u32, String> = BTree::init(&mut backend).unwrap();
+            for i in 500..700 {
+                tree.insert(i, i.to_string()).unwrap();
+                expected.insert(i, i.to_string());
+                tree.remove(&i).unwrap();
+                expected.remove(&i);
+            }
+            assert!(tree.storage.count() <= nodes + 2);
+
+            let keys: Vec<_> = expected.keys().copied().collect();
+            for i in keys {
+                assert_eq!(tree.remove(&i), Ok(expected.remove(&i)));
+            }
+            assert!(tree.is_empty());
+            assert_eq!(tree.iter().count(), 0);
+            assert!(tree.load_node(tree.root()).unwrap().is_leaf());
+            tree.clear().unwrap();
+        }
+    }
+
+    fn test_range<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut tree = BTree::with_degree(&mut backend, 3).unwrap();
+        for i in shuffled(300) {
+            tree.insert(i * 2, i).unwrap();
+        }
+
+        let keys = |r: Vec<(u32, u32)>| r.into_iter().map(|i| i.0).collect::<Vec<_>>();
+        assert_eq!(keys(tree.range(10..20).collect()), vec![10, 12, 14, 16, 18]);
+        assert_eq!(keys(tree.range(11..=20).collect()), vec![12, 14, 16, 18, 20]);
+        assert_eq!(keys(tree.range(..6).collect()), vec![0, 2, 4]);
+        assert_eq!(keys(tree.range(594..).collect()), vec![594, 596, 598]);
+        assert_eq!(tree.range(599..).count(), 0);
+        assert_eq!(tree.range(20..20).count(), 0);
+        assert_eq!(
+            tree.range((std::ops::Bound::Excluded(10), std::ops::Bound::Included(14))).collect::<Vec<_>>(),
+            vec![(12, 6), (14, 7)]
+        );
+        assert_eq!(tree.iter().count(), 300);
+    }
+
+    fn test_prefix<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut tree = BTree::create(&mut backend).unwrap();
+        for word in ["apple", "app", "application", "banana", "band", "bandana", "can", "ap"] {
+            tree.insert(word.to_string(), word.len() as u32).unwrap();
+        }
+
+        let found: Vec<_> = tree.prefix(&"app".to_string()).map(|i| i.0).collect();
+        assert_eq!(found, vec!["app", "apple", "application"]);
+        let found: Vec<_> = tree.prefix(&"band".to_string()).collect();
+        assert_eq!(found, vec![("band".to_string(), 4), ("bandana".to_string(), 7)]);
+        assert_eq!(tree.prefix(&"x".to_string()).count(), 0);
+        assert_eq!(tree.prefix(&String::new()).count(), 8);
+
+        backend.clear();
+        let mut tree: BTree<_, Vec<u8>, u32> = BTree::create(&mut backend).unwrap();
+        tree.insert(vec![1, 2, 3], 1).unwrap();
+        tree.insert(vec![1, 3], 2).unwrap();
+        assert_eq!(tree.prefix(&vec![1, 2]).count(), 1);
+    }
+}
@@ -0,0 +1,285 @@
+/// Declares a plain struct together with a columnar (struct of arrays) storage for it. Each field gets stored in its
+/// own `NumberSequence` within a single `MultiFile`, so scanning a single field only touches that fields data.
+///
+/// This is a `macro_rules` stand-in for a `#[derive(Columns)]` attribute. A derive macro would need its own proc-macro
+/// crate, so the struct gets declared within the macro instead, followed by the name of the storage type to generate.
+///
+/// Fields must be fixed-width types whose `SizedDeser` width equals their in-memory size, which holds for all numeric
+/// types and `bool`. Besides `new`, `push`, `get`, `len`, `is_empty` and `flush`, the storage gets a method for each
+/// field returning the column of that field, so field names must not collide with those.
+///
+/// ```
+/// bytestore::columns! {
+///     #[derive(Debug, PartialEq)]
+///     pub struct Point {
+///         pub x: f32,
+///         pub y: f32,
+///     }
+///     store PointColumns;
+/// }
+/// use bytestore::backend::memory::{MemoryBackend, MemoryData};
+///
+/// let backend = MemoryBackend::from_storage(MemoryData::new(vec![0u8; 8])).unwrap();
+/// let mut points = PointColumns::new(backend).unwrap();
+/// points.push(&Point { x: 1.0, y: 2.0 }).unwrap();
+/// assert_eq!(points.get(0).unwrap(), Point { x: 1.0, y: 2.0 });
+/// assert_eq!(points.y().iter().collect::<Vec<_>>(), vec![2.0]);
+/// ```
+#[macro_export]
+macro_rules! columns {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($fvis:vis $field:ident : $ty:ty),+ $(,)?
+        }
+        store $store:ident;
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $($fvis $field: $ty),+
+        }
+
+        /// Columnar storage holding one column per field.
+        $vis struct $store<B> {
+            backend: $crate::components::multi_file::MultiFile<B>,
+            len: usize,
+        }
+
+        impl<B> $store<B>
+            where
+                B: $crate::backend::growable::GrowableBackend,
+        {
+            /// Creates a new empty columnar storage on `backend`.
+            pub fn new(backend: B) -> $crate::Result<Self> {
+                let mut backend: $crate::components::multi_file::MultiFile<B> =
+                    $crate::traits::creatable::Creatable::create(backend)?;
+                $(
+                    backend.insert_new_backend::<$crate::components::number_seq::NumberSequence<
+                        _,
+                        $ty,
+                        { ::std::mem::size_of::<$ty>() },
+                    >>()?;
+                )+
+                Ok(Self { backend, len: 0 })
+            }
+
+            /// Appends `item` by pushing each field to its column. If pushing to a column fails, the columns pushed to
+            /// already get truncated again, so all columns keep the same length.
+            pub fn push(&mut self, item: &$name) -> $crate::Result<()> {
+                if let Err(err) = self.push_fields(item) {
+                    self.truncate_columns(self.len)?;
+                    return Err(err);
+                }
+                self.len += 1;
+                Ok(())
+            }
+
+            fn push_fields(&mut self, item: &$name) -> $crate::Result<()> {
+                let mut id = 0;
+                $(
+                    let mut column: $crate::components::number_seq::NumberSequence<
+                        _,
+                        $ty,
+                        { ::std::mem::size_of::<$ty>() },
+                    > = self.backend.get_backend_mut(id).ok_or($crate::Error::Initialization)?;
+                    column.append(&[item.$field])?;
+                    id += 1;
+                )+
+                debug_assert_eq!(id, Self::FIELDS.len());
+                Ok(())
+            }
+
+            /// Truncates all columns to `len` items.
+            fn truncate_columns(&mut self, len: usize) -> $crate::Result<()> {
+                let mut id = 0;
+                $(
+                    let mut column: $crate::components::number_seq::NumberSequence<
+                        _,
+                        $ty,
+                        { ::std::mem::size_of::<$ty>() },
+                    > = self.backend.get_backend_mut(id).ok_or($crate::Error::Initialization)?;
+                    column.truncate(len)?;
+                    id += 1;
+                )+
+                debug_assert_eq!(id, Self::FIELDS.len());
+                Ok(())
+            }
+        }
+
+        impl<B> $store<B>
+            where
+                B: $crate::backend::Backend,
+        {
+            const FIELDS: &'static [&'static str] = &[$(stringify!($field)),+];
+
+            /// Assembles the item at `index` from all columns.
+            pub fn get(&self, index: usize) -> $crate::Result<$name> {
+                if index >= self.len {
+                    return Err($crate::Error::OutOfBounds);
+                }
+
+                let mut ids = 0..;
+                Ok($name {
+                    $($field: {
+                        let column: $crate::components::number_seq::NumberSequence<
+                            _,
+                            $ty,
+                            { ::std::mem::size_of::<$ty>() },
+                        > = self.backend.get_backend(ids.next().unwrap()).ok_or($crate::Error::Initialization)?;
+                        column.get(index)?
+                    }),+
+                })
+            }
+
+            /// Returns the amount of stored items.
+            #[inline]
+            pub fn len(&self) -> usize {
+                self.len
+            }
+
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                self.len == 0
+            }
+
+            #[inline]
+            pub fn flush(&mut self) -> $crate::Result<()> {
+                self.backend.flush()
+            }
+
+            $(
+                /// Returns the column of this field.
+                #[allow(dead_code)]
+                pub fn $field(&self) -> $crate::components::number_seq::NumberSequence<
+                    $crate::backend::base::sub::BaseSubBackend<'_, &[u8]>,
+                    $ty,
+                    { ::std::mem::size_of::<$ty>() },
+                > {
+                    let id = Self::FIELDS
+                        .iter()
+                        .position(|i| *i == stringify!($field))
+                        .unwrap();
+                    self.backend.get_backend(id).unwrap()
+                }
+            )+
+        }
+
+        impl<B> $crate::traits::initiable::Initiable<B> for $store<B>
+            where
+                B: $crate::backend::Backend,
+        {
+            fn init(backend: B) -> $crate::Result<Self> {
+                let backend: $crate::components::multi_file::MultiFile<B> =
+                    $crate::traits::initiable::Initiable::init(backend)?;
+
+                let mut lens = vec![];
+                let mut id = 0;
+                $(
+                    let column: $crate::components::number_seq::NumberSequence<
+                        _,
+                        $ty,
+                        { ::std::mem::size_of::<$ty>() },
+                    > = backend.get_backend(id).ok_or($crate::Error::Initialization)?;
+                    lens.push(column.len());
+                    id += 1;
+                )+
+
+                if backend.count() != id || lens.iter().any(|i| *i != lens[0]) {
+                    return Err($crate::Error::Initialization);
+                }
+
+                let len = lens[0];
+                Ok(Self { backend, len })
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use crate::backend::bounded::BoundedBackend;
+    use crate::backend::growable::GrowableBackend;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+    use crate::traits::initiable::Initiable;
+    use crate::Error;
+
+    crate::columns! {
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        struct Measurement {
+            id: u64,
+            value: f32,
+            valid: bool,
+            delta: i16,
+        }
+        store Measurements;
+    }
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_columns(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./columns", 10);
+        test_columns(&mut mmap_backend);
+    }
+
+    #[test]
+    fn test_push_rollback() {
+        let mut store = Measurements::new(BoundedBackend::new(make_mem_backend(10), 4096)).unwrap();
+        let item = Measurement { id: 1, value: 0.5, valid: true, delta: -1 };
+        let err = loop {
+            if let Err(err) = store.push(&item) {
+                break err;
+            }
+        };
+        assert_eq!(err, Error::QuotaExceeded);
+
+        // Columns pushed to before the failing one have been truncated again.
+        let len = store.len();
+        assert_eq!(store.id().len(), len);
+        assert_eq!(store.value().len(), len);
+        assert_eq!(store.valid().len(), len);
+        assert_eq!(store.delta().len(), len);
+        assert_eq!(store.get(len - 1).unwrap(), item);
+    }
+
+    fn test_columns<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+
+        let items: Vec<_> = (0..500u64)
+            .map(|i| Measurement {
+                id: i,
+                value: i as f32 / 2.0,
+                valid: i % 3 == 0,
+                delta: -(i as i16),
+            })
+            .collect();
+
+        {
+            let mut store = Measurements::new(&mut backend).unwrap();
+            assert!(store.is_empty());
+            for item in items.iter() {
+                store.push(item).unwrap();
+            }
+            assert_eq!(store.len(), items.len());
+
+            for (i, item) in items.iter().enumerate() {
+                assert_eq!(store.get(i).unwrap(), *item);
+            }
+            assert_eq!(store.get(items.len()), Err(Error::OutOfBounds));
+
+            let ids: Vec<_> = store.id().iter().collect();
+            assert_eq!(ids, items.iter().map(|i| i.id).collect::<Vec<_>>());
+            let valid = store.valid().iter().filter(|i| *i).count();
+            assert_eq!(valid, items.iter().filter(|i| i.valid).count());
+            store.flush().unwrap();
+        }
+
+        let store = Measurements::init(backend).unwrap();
+        assert_eq!(store.len(), items.len());
+        assert_eq!(store.get(42).unwrap(), items[42]);
+        assert_eq!(store.delta().get(7).unwrap(), -7);
+    }
+}
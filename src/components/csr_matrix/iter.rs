@@ -0,0 +1,59 @@
+use crate::backend::base::sub::GeneralSubBackend;
+use crate::components::number_seq::NumberSequence;
+use crate::traits::sized_deser::SizedDeser;
+
+/// Iterator over the (column, value) pairs of a single row of a `CsrMatrix`.
+pub struct RowIter<'a, V, const N: usize> {
+    columns: NumberSequence<GeneralSubBackend<'a>, u32, 4>,
+    values: NumberSequence<GeneralSubBackend<'a>, V, N>,
+    pos: usize,
+    end: usize,
+}
+
+impl<'a, V, const N: usize> RowIter<'a, V, N> {
+    #[inline]
+    pub(super) fn new(
+        columns: NumberSequence<GeneralSubBackend<'a>, u32, 4>,
+        values: NumberSequence<GeneralSubBackend<'a>, V, N>,
+        pos: usize,
+        end: usize,
+    ) -> Self {
+        Self {
+            columns,
+            values,
+            pos,
+            end,
+        }
+    }
+
+    /// Returns the range of the remaining items within the column and value sequences.
+    #[inline]
+    pub(super) fn range(&self) -> (usize, usize) {
+        (self.pos, self.end)
+    }
+}
+
+impl<'a, V, const N: usize> Iterator for RowIter<'a, V, N>
+    where
+        V: SizedDeser<N>,
+{
+    type Item = (u32, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.end {
+            return None;
+        }
+
+        let column = self.columns.get(self.pos).ok()?;
+        let value = self.values.get(self.pos).ok()?;
+        self.pos += 1;
+        Some((column, value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.pos;
+        (len, Some(len))
+    }
+}
@@ -0,0 +1,337 @@
+pub mod iter;
+
+use crate::backend::base::sub::GeneralSubBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::csr_matrix::iter::RowIter;
+use crate::components::multi_file::MultiFile;
+use crate::components::number_seq::NumberSequence;
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::traits::sized_deser::SizedDeser;
+use crate::{Error, Result};
+use num_traits::Num;
+use std::marker::PhantomData;
+
+pub type CsrMatrixF32<B> = CsrMatrix<B, f32, 4>;
+pub type CsrMatrixF64<B> = CsrMatrix<B, f64, 8>;
+
+/// ID of the MultiFile entry holding the metadata (rows, columns).
+const METADATA_ID: usize = 0;
+
+/// ID of the MultiFile entry holding the offsets of each row into the column and value sequences.
+const OFFSETS_ID: usize = 1;
+
+/// ID of the MultiFile entry holding the column index of each value.
+const COLUMNS_ID: usize = 2;
+
+/// ID of the MultiFile entry holding the values.
+const VALUES_ID: usize = 3;
+
+type Offsets<'a> = NumberSequence<GeneralSubBackend<'a>, u64, 8>;
+type Columns<'a> = NumberSequence<GeneralSubBackend<'a>, u32, 4>;
+type Values<'a, V, const N: usize> = NumberSequence<GeneralSubBackend<'a>, V, N>;
+
+/// A static sparse matrix in compressed sparse row (CSR) format. Only non zero values are stored along with their
+/// column index. Rows are stored consecutively and referenced by offsets.
+///
+/// The internal format: MultiFile(METADATA | ROW OFFSETS | COLUMN INDICES | VALUES)
+pub struct CsrMatrix<B, V, const N: usize> {
+    backend: MultiFile<B>,
+    rows: usize,
+    cols: usize,
+    p: PhantomData<V>,
+}
+
+impl<B, V, const N: usize> CsrMatrix<B, V, N>
+    where
+        B: GrowableBackend,
+        V: SizedDeser<N> + Num + Copy,
+{
+    /// Builds a new `rows` x `cols` matrix from (row, column, value) triples. Values of duplicate positions get summed
+    /// up and zero values are not stored.
+    pub fn build<I>(backend: B, rows: usize, cols: usize, entries: I) -> Result<Self>
+        where
+            I: IntoIterator<Item=(u32, u32, V)>,
+    {
+        let mut entries: Vec<_> = entries.into_iter().collect();
+        if entries
+            .iter()
+            .any(|(r, c, _)| *r as usize >= rows || *c as usize >= cols)
+        {
+            return Err(Error::OutOfBounds);
+        }
+        entries.sort_by_key(|(r, c, _)| (*r, *c));
+
+        // Merge duplicates and drop zeros.
+        let mut merged: Vec<(u32, u32, V)> = Vec::with_capacity(entries.len());
+        for (r, c, v) in entries {
+            match merged.last_mut() {
+                Some(last) if last.0 == r && last.1 == c => last.2 = last.2 + v,
+                _ => merged.push((r, c, v)),
+            }
+        }
+        merged.retain(|(_, _, v)| !v.is_zero());
+
+        let mut offsets = Vec::with_capacity(rows + 1);
+        offsets.push(0u64);
+        let mut pos = 0;
+        for row in 0..rows as u32 {
+            while pos < merged.len() && merged[pos].0 == row {
+                pos += 1;
+            }
+            offsets.push(pos as u64);
+        }
+
+        let mut backend = MultiFile::create(backend)?;
+
+        let mut metadata = backend.insert_empty()?;
+        metadata.grow_to(16)?;
+        metadata.push(&(rows as u64).to_le_bytes())?;
+        metadata.push(&(cols as u64).to_le_bytes())?;
+
+        backend
+            .insert_new_backend::<NumberSequence<_, u64, 8>>()?
+            .append(&offsets)?;
+
+        let columns: Vec<u32> = merged.iter().map(|i| i.1).collect();
+        backend
+            .insert_new_backend::<NumberSequence<_, u32, 4>>()?
+            .append(&columns)?;
+
+        let values: Vec<V> = merged.iter().map(|i| i.2).collect();
+        backend
+            .insert_new_backend::<NumberSequence<_, V, N>>()?
+            .append(&values)?;
+
+        Ok(Self {
+            backend,
+            rows,
+            cols,
+            p: PhantomData,
+        })
+    }
+}
+
+impl<B, V, const N: usize> CsrMatrix<B, V, N>
+    where
+        B: Backend,
+        V: SizedDeser<N> + Num + Copy,
+{
+    /// Returns the value at the given position. Positions without a stored value are zero.
+    pub fn get(&self, row: usize, col: usize) -> Result<V> {
+        if col >= self.cols {
+            return Err(Error::OutOfBounds);
+        }
+
+        let iter = self.row(row)?;
+        let (mut start, mut end) = iter.range();
+        let columns = self.columns();
+        while start < end {
+            let mid = (start + end) / 2;
+            let c = columns.get(mid)? as usize;
+            match c.cmp(&col) {
+                std::cmp::Ordering::Less => start = mid + 1,
+                std::cmp::Ordering::Greater => end = mid,
+                std::cmp::Ordering::Equal => return self.values().get(mid),
+            }
+        }
+
+        Ok(V::zero())
+    }
+
+    /// Returns an iterator over all stored (column, value) pairs of the given row, ordered by column.
+    pub fn row(&self, row: usize) -> Result<RowIter<'_, V, N>> {
+        if row >= self.rows {
+            return Err(Error::OutOfBounds);
+        }
+
+        let offsets = self.offsets();
+        let start = offsets.get(row)? as usize;
+        let end = offsets.get(row + 1)? as usize;
+        Ok(RowIter::new(self.columns(), self.values(), start, end))
+    }
+
+    /// Multiplies the matrix with the vector `x` and returns the resulting vector. `x` must have a length equal to
+    /// the amount of columns.
+    pub fn mul_vec(&self, x: &[V]) -> Result<Vec<V>> {
+        if x.len() != self.cols {
+            return Err(Error::UnexpectedValue);
+        }
+
+        let offsets = self.offsets();
+        let columns = self.columns();
+        let values = self.values();
+
+        let mut out = Vec::with_capacity(self.rows);
+        let mut start = offsets.get(0)? as usize;
+        for row in 0..self.rows {
+            let end = offsets.get(row + 1)? as usize;
+            let mut sum = V::zero();
+            for i in start..end {
+                sum = sum + values.get(i)? * x[columns.get(i)? as usize];
+            }
+            out.push(sum);
+            start = end;
+        }
+
+        Ok(out)
+    }
+
+    /// Returns the amount of rows.
+    #[inline]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the amount of columns.
+    #[inline]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns the amount of stored (non zero) values.
+    #[inline]
+    pub fn nnz(&self) -> usize {
+        self.values().len()
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.backend.flush()
+    }
+
+    #[inline]
+    fn offsets(&self) -> Offsets<'_> {
+        self.backend.get_backend(OFFSETS_ID).unwrap()
+    }
+
+    #[inline]
+    fn columns(&self) -> Columns<'_> {
+        self.backend.get_backend(COLUMNS_ID).unwrap()
+    }
+
+    #[inline]
+    fn values(&self) -> Values<'_, V, N> {
+        self.backend.get_backend(VALUES_ID).unwrap()
+    }
+}
+
+impl<B, V, const N: usize> Initiable<B> for CsrMatrix<B, V, N>
+    where
+        B: Backend,
+        V: SizedDeser<N>,
+{
+    fn init(backend: B) -> Result<Self> {
+        let backend = MultiFile::init(backend)?;
+
+        let metadata = backend.get(METADATA_ID).ok_or(Error::Initialization)?;
+        let metadata = metadata.get(0, 16)?;
+        let rows = u64::from_le_bytes(metadata[..8].try_into().unwrap()) as usize;
+        let cols = u64::from_le_bytes(metadata[8..].try_into().unwrap()) as usize;
+
+        let offsets = backend.get(OFFSETS_ID).ok_or(Error::Initialization)?;
+        let columns = backend.get(COLUMNS_ID).ok_or(Error::Initialization)?;
+        let values = backend.get(VALUES_ID).ok_or(Error::Initialization)?;
+        if offsets.len() != (rows + 1) * 8 || columns.len() / 4 != values.len() / N {
+            return Err(Error::Initialization);
+        }
+
+        Ok(Self {
+            backend,
+            rows,
+            cols,
+            p: PhantomData,
+        })
+    }
+}
+
+impl<B, V, const N: usize> MType for CsrMatrix<B, V, N>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.backend.raw_data()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_build(&mut mem_backend);
+        test_mul_vec(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./csr_matrix", 10);
+        test_build(&mut mmap_backend);
+        test_mul_vec(&mut mmap_backend);
+    }
+
+    fn test_build<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        {
+            let entries = [(0, 1, 2.0), (2, 0, 1.0), (0, 1, 1.0), (2, 3, 4.0), (1, 2, 0.0)];
+            let matrix: CsrMatrixF64<_> = CsrMatrix::build(&mut backend, 3, 4, entries).unwrap();
+            assert_eq!(matrix.rows(), 3);
+            assert_eq!(matrix.cols(), 4);
+            assert_eq!(matrix.nnz(), 3);
+
+            assert_eq!(matrix.get(0, 1), Ok(3.0));
+            assert_eq!(matrix.get(0, 0), Ok(0.0));
+            assert_eq!(matrix.get(1, 2), Ok(0.0));
+            assert_eq!(matrix.get(3, 0), Err(Error::OutOfBounds));
+            assert_eq!(matrix.get(0, 4), Err(Error::OutOfBounds));
+        }
+
+        let matrix: CsrMatrixF64<_> = CsrMatrix::init(&mut backend).unwrap();
+        assert_eq!(matrix.row(0).unwrap().collect::<Vec<_>>(), vec![(1, 3.0)]);
+        assert_eq!(matrix.row(1).unwrap().count(), 0);
+        assert_eq!(
+            matrix.row(2).unwrap().collect::<Vec<_>>(),
+            vec![(0, 1.0), (3, 4.0)]
+        );
+        assert_eq!(matrix.get(2, 3), Ok(4.0));
+
+        let res = CsrMatrixF64::build(&mut backend, 2, 2, [(2, 0, 1.0)]);
+        assert!(matches!(res, Err(Error::OutOfBounds)));
+    }
+
+    fn test_mul_vec<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let (rows, cols) = (50, 80);
+
+        let mut dense = vec![vec![0i64; cols]; rows];
+        let mut entries = vec![];
+        for i in 0..400 {
+            let r = (i * 7) % rows;
+            let c = (i * 13 + i / 3) % cols;
+            let v = (i as i64 * 31) % 200 - 100;
+            dense[r][c] += v;
+            entries.push((r as u32, c as u32, v));
+        }
+
+        let matrix: CsrMatrix<_, i64, 8> = CsrMatrix::build(&mut backend, rows, cols, entries).unwrap();
+
+        let x: Vec<i64> = (0..cols as i64).map(|i| (i * 3) % 20 - 10).collect();
+        let expected: Vec<i64> = dense
+            .iter()
+            .map(|row| row.iter().zip(x.iter()).map(|(a, b)| a * b).sum())
+            .collect();
+        assert_eq!(matrix.mul_vec(&x), Ok(expected));
+        assert_eq!(matrix.mul_vec(&x[1..]), Err(Error::UnexpectedValue));
+
+        for (r, row) in dense.iter().enumerate() {
+            for (c, v) in row.iter().enumerate() {
+                assert_eq!(matrix.get(r, c), Ok(*v));
+            }
+        }
+    }
+}
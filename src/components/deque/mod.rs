@@ -0,0 +1,361 @@
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::header_file::CustomHeaderFile;
+use crate::deser::{deserialize_impl, serialize_impl};
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::{Error, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+/// A double-ended queue (ring buffer) where each item `T` de/serializes with the same amount of bytes `N` > 0.
+/// Items can be pushed and popped on both ends in O(1). The position of the first item and the amount of items are
+/// stored in the header, so no items need to be moved.
+///
+/// The internal format: (H_LEN (4 bytes) | HEADER (head, len) | SLOTS (capacity * N bytes))
+pub struct Deque<B, T, const N: usize> {
+    backend: CustomHeaderFile<B, DequeHeader>,
+    head: usize,
+    len: usize,
+    _p: PhantomData<T>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy)]
+pub(crate) struct DequeHeader {
+    /// Slot of the first item.
+    head: u64,
+    len: u64,
+}
+
+impl<B, T, const N: usize> Deque<B, T, N> {
+    /// Returns the amount of items in the deque.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the deque is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the slot of the item at `index`.
+    #[inline]
+    fn slot(&self, index: usize, capacity: usize) -> usize {
+        (self.head + index) % capacity
+    }
+}
+
+impl<B, T, const N: usize> Deque<B, T, N>
+    where
+        B: Backend,
+{
+    /// Returns the amount of items the deque can hold without growing.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.backend.len() / N
+    }
+
+    /// Returns `true` if pushing another item requires growing the deque.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity()
+    }
+
+    /// Gets the item at `index` as bytes, counting from the front.
+    pub fn get_raw(&self, index: usize) -> Result<&[u8]> {
+        if index >= self.len {
+            return Err(Error::OutOfBounds);
+        }
+        let slot = self.slot(index, self.capacity());
+        self.backend.get(slot * N, N)
+    }
+
+    /// Removes all items from the deque, preserving the allocated space.
+    pub fn clear(&mut self) -> Result<()> {
+        self.head = 0;
+        self.len = 0;
+        self.write_header()
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.backend.flush()
+    }
+
+    /// Writes `data` into the given slot.
+    #[inline]
+    fn write_slot(&mut self, slot: usize, data: &[u8]) -> Result<()> {
+        if data.len() != N {
+            return Err(Error::UnexpectedValue);
+        }
+        self.backend.replace_same_len(slot * N, data)?;
+        Ok(())
+    }
+
+    #[inline]
+    fn write_header(&mut self) -> Result<()> {
+        self.backend.update_header(DequeHeader {
+            head: self.head as u64,
+            len: self.len as u64,
+        })
+    }
+}
+
+impl<B, T, const N: usize> Deque<B, T, N>
+    where
+        B: Backend,
+        T: DeserializeOwned,
+{
+    /// Gets the item at `index`, counting from the front.
+    #[inline]
+    pub fn get(&self, index: usize) -> Result<T> {
+        deserialize_impl(self.get_raw(index)?)
+    }
+
+    /// Returns the first item.
+    #[inline]
+    pub fn front(&self) -> Option<T> {
+        self.get(0).ok()
+    }
+
+    /// Returns the last item.
+    #[inline]
+    pub fn back(&self) -> Option<T> {
+        self.get(self.len.checked_sub(1)?).ok()
+    }
+
+    /// Returns an iterator over all items from front to back.
+    #[inline]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = T> + ExactSizeIterator + '_ {
+        (0..self.len).map(|i| self.get(i).expect("Failed to load item"))
+    }
+
+    /// Removes the first item and returns it.
+    pub fn pop_front(&mut self) -> Result<Option<T>> {
+        let Some(item) = self.front() else {
+            return Ok(None);
+        };
+        self.head = self.slot(1, self.capacity());
+        self.len -= 1;
+        self.write_header()?;
+        Ok(Some(item))
+    }
+
+    /// Removes the last item and returns it.
+    pub fn pop_back(&mut self) -> Result<Option<T>> {
+        let Some(item) = self.back() else {
+            return Ok(None);
+        };
+        self.len -= 1;
+        self.write_header()?;
+        Ok(Some(item))
+    }
+}
+
+impl<B, T, const N: usize> Deque<B, T, N>
+    where
+        B: GrowableBackend,
+        T: Serialize,
+{
+    /// Appends an item to the back of the deque.
+    #[inline]
+    pub fn push_back(&mut self, item: &T) -> Result<()> {
+        self.push_back_raw(&serialize_impl(item)?)
+    }
+
+    /// Prepends an item to the front of the deque.
+    #[inline]
+    pub fn push_front(&mut self, item: &T) -> Result<()> {
+        self.push_front_raw(&serialize_impl(item)?)
+    }
+}
+
+impl<B, T, const N: usize> Deque<B, T, N>
+    where
+        B: GrowableBackend,
+{
+    /// Appends an item in form of raw data to the back of the deque.
+    pub fn push_back_raw(&mut self, data: &[u8]) -> Result<()> {
+        if self.is_full() {
+            self.grow()?;
+        }
+        let slot = self.slot(self.len, self.capacity());
+        self.write_slot(slot, data)?;
+        self.len += 1;
+        self.write_header()
+    }
+
+    /// Prepends an item in form of raw data to the front of the deque.
+    pub fn push_front_raw(&mut self, data: &[u8]) -> Result<()> {
+        if self.is_full() {
+            self.grow()?;
+        }
+        let capacity = self.capacity();
+        let slot = self.slot(capacity - 1, capacity);
+        self.write_slot(slot, data)?;
+        self.head = slot;
+        self.len += 1;
+        self.write_header()
+    }
+
+    /// Grows the deque for more items. How much it grows is decided by the backends `GrowthPolicy`.
+    pub fn grow(&mut self) -> Result<()> {
+        let capacity = self.capacity();
+        let required = (capacity + 1) * N;
+        let new_capacity = self.backend.growth_policy().next_capacity(capacity * N, required) / N;
+        self.backend.grow_to(new_capacity * N)?;
+        self.backend.push_fill(0, (new_capacity - capacity) * N)?;
+
+        // Items wrapping around the end of the old slots would be split by the new ones, so the part before the
+        // wrap gets moved to the end of the new slots.
+        if self.head + self.len > capacity {
+            let moved = capacity - self.head;
+            let new_head = new_capacity - moved;
+            let data = self.backend.get(self.head * N, moved * N)?.to_vec();
+            self.backend.replace_same_len(new_head * N, &data)?;
+            self.head = new_head;
+            self.write_header()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<B, T, const N: usize> Creatable<B> for Deque<B, T, N>
+    where
+        B: GrowableBackend,
+{
+    fn with_capacity(backend: B, capacity: usize) -> Result<Self> {
+        assert!(N > 0);
+        let mut backend = CustomHeaderFile::with_capacity(backend, 0)?;
+        backend.grow_to(capacity * N)?;
+        backend.push_fill(0, capacity * N)?;
+        Ok(Self {
+            backend,
+            head: 0,
+            len: 0,
+            _p: PhantomData,
+        })
+    }
+}
+
+impl<B, T, const N: usize> Initiable<B> for Deque<B, T, N>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        assert!(N > 0);
+        let backend: CustomHeaderFile<B, DequeHeader> = CustomHeaderFile::init(backend)?;
+        let header = *backend.header();
+        let (head, len) = (header.head as usize, header.len as usize);
+
+        let capacity = backend.len() / N;
+        if !backend.len().is_multiple_of(N) || len > capacity || (capacity > 0 && head >= capacity) {
+            return Err(Error::InvalidHeader);
+        }
+
+        Ok(Self {
+            backend,
+            head,
+            len,
+            _p: PhantomData,
+        })
+    }
+}
+
+impl<B, T, const N: usize> MType for Deque<B, T, N>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.backend.raw_data()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_all() {
+        let mut backend = make_mem_backend(1);
+        test_push_pop(&mut backend);
+        test_wrap(&mut backend);
+
+        let mut backend = make_mmap_backend("deque", 1);
+        test_push_pop(&mut backend);
+        test_wrap(&mut backend);
+    }
+
+    fn test_push_pop<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        {
+            let mut deque: Deque<_, u32, 4> = Deque::create(&mut backend).unwrap();
+            assert_eq!(deque.pop_front(), Ok(None));
+            assert_eq!(deque.pop_back(), Ok(None));
+
+            for i in 0..100 {
+                deque.push_back(&i).unwrap();
+                deque.push_front(&(1000 + i)).unwrap();
+            }
+            assert_eq!(deque.len(), 200);
+            assert_eq!(deque.front(), Some(1099));
+            assert_eq!(deque.back(), Some(99));
+        }
+
+        let mut deque: Deque<_, u32, 4> = Deque::init(&mut backend).unwrap();
+        assert_eq!(deque.len(), 200);
+        let expected: Vec<u32> = (1000..1100).rev().chain(0..100).collect();
+        assert_eq!(deque.iter().collect::<Vec<_>>(), expected);
+        assert_eq!(deque.get(200), Err(Error::OutOfBounds));
+
+        assert_eq!(deque.pop_front(), Ok(Some(1099)));
+        assert_eq!(deque.pop_back(), Ok(Some(99)));
+        assert_eq!(deque.len(), 198);
+        assert_eq!(deque.push_back_raw(&[1]), Err(Error::UnexpectedValue));
+
+        deque.clear().unwrap();
+        assert!(deque.is_empty());
+        assert_eq!(deque.front(), None);
+    }
+
+    /// Compares random operations against `VecDeque` to cover the ring wrapping around while growing.
+    fn test_wrap<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut deque: Deque<_, u64, 8> = Deque::with_capacity(&mut backend, 3).unwrap();
+        let mut expected = VecDeque::new();
+
+        let mut seed = 7u64;
+        for i in 0..3000 {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            match (seed >> 33) % 5 {
+                0 | 1 => {
+                    deque.push_back(&i).unwrap();
+                    expected.push_back(i);
+                }
+                2 => {
+                    deque.push_front(&i).unwrap();
+                    expected.push_front(i);
+                }
+                3 => assert_eq!(deque.pop_front(), Ok(expected.pop_front())),
+                _ => assert_eq!(deque.pop_back(), Ok(expected.pop_back())),
+            }
+            assert_eq!(deque.len(), expected.len());
+        }
+
+        assert!(deque.iter().eq(expected.iter().copied()));
+        assert!(deque.iter().rev().eq(expected.iter().rev().copied()));
+
+        let deque: Deque<_, u64, 8> = Deque::init(&mut backend).unwrap();
+        assert!(deque.iter().eq(expected.into_iter()));
+    }
+}
@@ -0,0 +1,192 @@
+use crate::backend::base::sub::GeneralSubBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::components::multi_file::MultiFile;
+use crate::components::number_seq::NumberSequence;
+use crate::traits::creatable::Creatable;
+use crate::traits::sized_deser::SizedDeser;
+use crate::{Error, Result};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Sorts more items than fit into memory. Pushed items get buffered until `run_len` items are collected, which then get
+/// sorted and written as a run into the scratch backend. Finishing merges all runs into a sorted `NumberSequence`, so
+/// at most `run_len` items plus a single item of each run are held in memory at once.
+///
+/// The scratch format: MultiFile(RUN | RUN | ...)
+/// where each run is a sorted `NumberSequence`.
+pub struct ExternalSorter<B, T, const N: usize> {
+    scratch: MultiFile<B>,
+    buffer: Vec<T>,
+    run_len: usize,
+    len: usize,
+}
+
+impl<B, T, const N: usize> ExternalSorter<B, T, N>
+    where
+        B: GrowableBackend,
+        T: SizedDeser<N> + Ord + Copy,
+{
+    /// Creates a new sorter writing its runs into `scratch` and holding up to `run_len` items in memory. Existing data
+    /// in `scratch` gets overwritten. Returns `Error::UnexpectedValue` if `run_len` is 0.
+    pub fn new(scratch: B, run_len: usize) -> Result<Self> {
+        if run_len == 0 {
+            return Err(Error::UnexpectedValue);
+        }
+        Ok(Self {
+            scratch: MultiFile::create(scratch)?,
+            buffer: Vec::with_capacity(run_len),
+            run_len,
+            len: 0,
+        })
+    }
+
+    /// Adds an item to be sorted.
+    pub fn push(&mut self, item: T) -> Result<()> {
+        self.buffer.push(item);
+        self.len += 1;
+        if self.buffer.len() == self.run_len {
+            self.write_run()?;
+        }
+        Ok(())
+    }
+
+    /// Returns the amount of pushed items.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no items were pushed.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the amount of runs written into the scratch backend so far.
+    #[inline]
+    pub fn runs(&self) -> usize {
+        self.scratch.count()
+    }
+
+    /// Merges all pushed items into a new sorted `NumberSequence` in `out` and clears the scratch backend.
+    pub fn finish<O: GrowableBackend>(mut self, out: O) -> Result<NumberSequence<O, T, N>> {
+        let mut output = NumberSequence::with_capacity(out, self.len)?;
+
+        if self.runs() == 0 {
+            // Everything fits into memory.
+            self.buffer.sort_unstable();
+            output.append(&self.buffer)?;
+            return Ok(output);
+        }
+
+        if !self.buffer.is_empty() {
+            self.write_run()?;
+        }
+
+        let runs: Vec<NumberSequence<GeneralSubBackend<'_>, T, N>> = (0..self.runs())
+            .map(|i| self.scratch.get_backend(i).ok_or(Error::Initialization))
+            .collect::<Result<_>>()?;
+
+        // Heap of the smallest not yet merged item of each run along with the run and the items position in it.
+        let mut heap = BinaryHeap::with_capacity(runs.len());
+        for (run, seq) in runs.iter().enumerate() {
+            heap.push(Reverse((seq.get(0)?, run, 0)));
+        }
+
+        let mut chunk = Vec::with_capacity(self.run_len);
+        while let Some(Reverse((item, run, pos))) = heap.pop() {
+            chunk.push(item);
+            if chunk.len() == self.run_len {
+                output.append(&chunk)?;
+                chunk.clear();
+            }
+
+            if pos + 1 < runs[run].len() {
+                heap.push(Reverse((runs[run].get(pos + 1)?, run, pos + 1)));
+            }
+        }
+        output.append(&chunk)?;
+
+        self.scratch.clear();
+        Ok(output)
+    }
+
+    /// Sorts the buffered items and writes them as a new run into the scratch backend.
+    fn write_run(&mut self) -> Result<()> {
+        self.buffer.sort_unstable();
+        let mut run = self.scratch.insert_new_backend::<NumberSequence<_, T, N>>()?;
+        run.append(&self.buffer)?;
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+impl<B, T, const N: usize> Extend<T> for ExternalSorter<B, T, N>
+    where
+        B: GrowableBackend,
+        T: SizedDeser<N> + Ord + Copy,
+{
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for i in iter {
+            self.push(i).expect("Failed to push");
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_sort(&mut mem_backend);
+        test_small(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./external_sort", 10);
+        test_sort(&mut mmap_backend);
+        test_small(&mut mmap_backend);
+    }
+
+    fn items(count: u64) -> Vec<i64> {
+        (0..count).map(|i| ((i * 7919) % 4999) as i64 - 2500).collect()
+    }
+
+    fn test_sort<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let items = items(10_000);
+        let mut sorter: ExternalSorter<_, i64, 8> = ExternalSorter::new(&mut backend, 300).unwrap();
+        sorter.extend(items.iter().copied());
+        assert_eq!(sorter.len(), items.len());
+        assert_eq!(sorter.runs(), 33);
+
+        let sorted = sorter.finish(make_mem_backend(10)).unwrap();
+
+        let mut expected = items;
+        expected.sort_unstable();
+        assert_eq!(sorted.iter().collect::<Vec<_>>(), expected);
+    }
+
+    fn test_small<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        assert!(ExternalSorter::<_, u32, 4>::new(&mut backend, 0).is_err());
+
+        let mut sorter: ExternalSorter<_, u32, 4> = ExternalSorter::new(&mut backend, 100).unwrap();
+        sorter.extend([5, 3, 9, 1]);
+        assert_eq!(sorter.runs(), 0);
+        let sorted = sorter.finish(make_mem_backend(10)).unwrap();
+        assert_eq!(sorted.iter().collect::<Vec<_>>(), vec![1, 3, 5, 9]);
+
+        // A run length of 1 merges each item as its own run.
+        backend.clear();
+        let mut sorter: ExternalSorter<_, u32, 4> = ExternalSorter::new(&mut backend, 1).unwrap();
+        sorter.extend([5, 3, 5, 1]);
+        assert_eq!(sorter.runs(), 4);
+        let sorted = sorter.finish(make_mem_backend(10)).unwrap();
+        assert_eq!(sorted.iter().collect::<Vec<_>>(), vec![1, 3, 5, 5]);
+    }
+}
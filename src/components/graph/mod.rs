@@ -0,0 +1,260 @@
+use crate::backend::base::sub::GeneralSubBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::list::ListU32;
+use crate::components::multi_file::entry_mut::MFileEntryMut;
+use crate::components::multi_file::MultiFile;
+use crate::components::number_seq::compressed::iter::OwnedCompressedNumSeqIterator;
+use crate::components::number_seq::compressed::CompressedNumberSequence;
+use crate::traits::collection::Collection;
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::{Error, Result};
+
+/// ID of the entry holding the out-degree of each node.
+const DEGREES_ID: usize = 0;
+
+/// ID of the entry holding the neighbors of the first node.
+const NODES_START: usize = 1;
+
+/// A directed graph storing the outgoing edges of each node as adjacency list. Node IDs are handed out sequentially
+/// starting at 0. Neighbor IDs get compressed so sparse graphs with many nodes take little space.
+///
+/// The internal format: MultiFile(DEGREES | NEIGHBORS | NEIGHBORS | ...)
+/// where the neighbors of each node are a `CompressedNumberSequence` of node IDs in the order the edges were added.
+pub struct Graph<B> {
+    storage: MultiFile<B>,
+    edges: usize,
+}
+
+impl<B> Graph<B> {
+    /// Returns the amount of edges in the graph.
+    #[inline]
+    pub fn edge_count(&self) -> usize {
+        self.edges
+    }
+}
+
+impl<B> Graph<B>
+    where
+        B: Backend,
+{
+    /// Returns the amount of nodes in the graph.
+    #[inline]
+    pub fn node_count(&self) -> usize {
+        self.degrees().len()
+    }
+
+    /// Returns `true` if the graph has no nodes.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.node_count() == 0
+    }
+
+    /// Returns `true` if `node` exists in the graph.
+    #[inline]
+    pub fn contains_node(&self, node: u32) -> bool {
+        (node as usize) < self.node_count()
+    }
+
+    /// Returns the amount of outgoing edges of `node`.
+    #[inline]
+    pub fn degree(&self, node: u32) -> Result<u32> {
+        self.degrees().get(node as usize)
+    }
+
+    /// Returns an iterator over the targets of all outgoing edges of `node` in the order the edges were added.
+    pub fn neighbors(&self, node: u32) -> Result<OwnedCompressedNumSeqIterator<GeneralSubBackend<'_>, u32>> {
+        let neighbors: CompressedNumberSequence<GeneralSubBackend<'_>, u32> = self
+            .storage
+            .get_backend(NODES_START + node as usize)
+            .ok_or(Error::OutOfBounds)?;
+        Ok(neighbors.into_iter())
+    }
+
+    /// Returns `true` if there is an edge from `from` to `to`.
+    #[inline]
+    pub fn has_edge(&self, from: u32, to: u32) -> bool {
+        self.neighbors(from).is_ok_and(|mut i| i.any(|n| n == to))
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.storage.flush()
+    }
+
+    #[inline]
+    fn degrees(&self) -> ListU32<GeneralSubBackend<'_>> {
+        self.storage.get_backend(DEGREES_ID).unwrap()
+    }
+}
+
+impl<B> Graph<B>
+    where
+        B: GrowableBackend,
+{
+    /// Adds a new node without edges and returns its ID.
+    pub fn add_node(&mut self) -> Result<u32> {
+        let node = self.node_count() as u32;
+        CompressedNumberSequence::<_, u32>::create(self.storage.insert_empty()?)?;
+        self.degrees_mut().push(&0)?;
+        Ok(node)
+    }
+
+    /// Adds a directed edge from `from` to `to`. Returns `Error::OutOfBounds` if one of the nodes doesn't exist.
+    pub fn add_edge(&mut self, from: u32, to: u32) -> Result<()> {
+        if !self.contains_node(to) {
+            return Err(Error::OutOfBounds);
+        }
+        let degree = self.degree(from)?;
+
+        let mut neighbors: CompressedNumberSequence<MFileEntryMut<'_, B>, u32> = self
+            .storage
+            .get_backend_mut(NODES_START + from as usize)
+            .ok_or(Error::OutOfBounds)?;
+        neighbors.push(to)?;
+
+        self.degrees_mut().set(from as usize, &(degree + 1))?;
+        self.edges += 1;
+        Ok(())
+    }
+
+    /// Adds an edge from `a` to `b` and from `b` to `a`.
+    #[inline]
+    pub fn add_undirected_edge(&mut self, a: u32, b: u32) -> Result<()> {
+        self.add_edge(a, b)?;
+        self.add_edge(b, a)
+    }
+
+    /// Removes all nodes and edges.
+    pub fn clear(&mut self) -> Result<()> {
+        self.storage.clear();
+        self.edges = 0;
+        self.storage.insert_new_backend::<ListU32<_>>()?;
+        Ok(())
+    }
+
+    #[inline]
+    fn degrees_mut(&mut self) -> ListU32<MFileEntryMut<'_, B>> {
+        self.storage.get_backend_mut(DEGREES_ID).unwrap()
+    }
+}
+
+impl<B> Creatable<B> for Graph<B>
+    where
+        B: GrowableBackend,
+{
+    fn with_capacity(backend: B, capacity: usize) -> Result<Self> {
+        let mut graph = Self {
+            storage: MultiFile::with_capacity(backend, capacity + 1)?,
+            edges: 0,
+        };
+        graph.storage.insert_new_backend::<ListU32<_>>()?;
+        Ok(graph)
+    }
+}
+
+impl<B> Initiable<B> for Graph<B>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let storage = MultiFile::init(backend)?;
+        let degrees: ListU32<GeneralSubBackend<'_>> =
+            storage.get_backend(DEGREES_ID).ok_or(Error::Initialization)?;
+        if degrees.len() + NODES_START != storage.count() {
+            return Err(Error::Initialization);
+        }
+        let edges = degrees.iter().map(|i| i as usize).sum();
+
+        Ok(Self { storage, edges })
+    }
+}
+
+impl<B> MType for Graph<B>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.storage.raw_data()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_graph(&mut mem_backend);
+        test_undirected(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./graph", 10);
+        test_graph(&mut mmap_backend);
+        test_undirected(&mut mmap_backend);
+    }
+
+    fn edges(nodes: u32) -> Vec<(u32, u32)> {
+        (0..nodes)
+            .flat_map(|i| (1..=i % 7).map(move |j| (i, (i * 31 + j * 1_009) % nodes)))
+            .collect()
+    }
+
+    fn test_graph<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let nodes = 300;
+        {
+            let mut graph = Graph::create(&mut backend).unwrap();
+            assert!(graph.is_empty());
+            for i in 0..nodes {
+                assert_eq!(graph.add_node(), Ok(i));
+            }
+            for (from, to) in edges(nodes) {
+                graph.add_edge(from, to).unwrap();
+            }
+            assert_eq!(graph.add_edge(0, nodes), Err(Error::OutOfBounds));
+            assert_eq!(graph.add_edge(nodes, 0), Err(Error::OutOfBounds));
+        }
+
+        let graph = Graph::init(&mut backend).unwrap();
+        let edges = edges(nodes);
+        assert_eq!(graph.node_count(), nodes as usize);
+        assert_eq!(graph.edge_count(), edges.len());
+        for node in 0..nodes {
+            let expected: Vec<_> = edges.iter().filter(|i| i.0 == node).map(|i| i.1).collect();
+            assert_eq!(graph.degree(node), Ok(expected.len() as u32));
+            assert_eq!(graph.neighbors(node).unwrap().collect::<Vec<_>>(), expected);
+        }
+        assert!(graph.has_edge(edges[10].0, edges[10].1));
+        assert!(!graph.has_edge(0, 1));
+        assert!(graph.neighbors(nodes).is_err());
+        assert!(graph.degree(nodes).is_err());
+    }
+
+    fn test_undirected<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut graph = Graph::create(&mut backend).unwrap();
+        let a = graph.add_node().unwrap();
+        let b = graph.add_node().unwrap();
+        let c = graph.add_node().unwrap();
+        graph.add_undirected_edge(a, b).unwrap();
+        graph.add_undirected_edge(a, c).unwrap();
+        graph.add_edge(c, c).unwrap();
+
+        assert_eq!(graph.neighbors(a).unwrap().collect::<Vec<_>>(), vec![b, c]);
+        assert_eq!(graph.neighbors(c).unwrap().collect::<Vec<_>>(), vec![a, c]);
+        assert_eq!(graph.degree(b), Ok(1));
+        assert_eq!(graph.edge_count(), 5);
+
+        graph.clear().unwrap();
+        assert!(graph.is_empty());
+        assert_eq!(graph.edge_count(), 0);
+        assert_eq!(graph.add_node(), Ok(0));
+    }
+}
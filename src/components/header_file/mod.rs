@@ -1,15 +1,21 @@
 use crate::backend::growable::GrowableBackend;
+use crate::backend::growth::GrowthPolicy;
 use crate::backend::Backend;
+use crate::deser::serialize_impl;
 use crate::error::Error;
+use crate::traits::batch::UndoLog;
 use crate::traits::creatable::Creatable;
 use crate::traits::deser::Deser;
 use crate::traits::initiable::Initiable;
 use crate::traits::mtype::MType;
+use std::ops::Range;
 
 pub struct CustomHeaderFile<B, H> {
     pub(crate) backend: B,
     header: H,
     header_len: u32,
+    /// Records writes to the backend while a batch is running.
+    undo: UndoLog,
 }
 
 impl<B, H> Initiable<B> for CustomHeaderFile<B, H>
@@ -18,17 +24,12 @@ where
     H: Deser,
 {
     fn init(backend: B) -> crate::Result<Self> {
-        let header_len: u32 = backend.get_t(0, 4).map_err(|_| Error::InvalidHeader)?;
-
-        let header_data = backend
-            .get(4, header_len as usize - 4)
-            .map_err(|_| Error::InvalidHeader)?;
-
-        let header = bitcode::deserialize(header_data)?;
+        let (header, header_len) = Self::read_header(&backend)?;
         Ok(Self {
             backend,
             header,
             header_len,
+            undo: UndoLog::default(),
         })
     }
 }
@@ -56,6 +57,7 @@ where
             backend,
             header,
             header_len,
+            undo: UndoLog::default(),
         })
     }
 }
@@ -80,16 +82,33 @@ where
             backend,
             header,
             header_len,
+            undo: UndoLog::default(),
         })
     }
 
     pub fn set_header(&mut self, new_header: H) -> Result<(), Error> {
-        let new_header_len =
-            self.backend
-                .replace_t(4, self.header_len as usize - 4, &new_header)? as u32
-                + 4;
-        self.backend.replace_same_len_t(0, &new_header_len)?;
+        self.update_header(new_header)?;
         self.backend.flush_range(0, self.header_len as usize)?;
+        Ok(())
+    }
+
+    /// Same as `set_header` but doesn't flush the header, for headers that change on every write.
+    pub(crate) fn update_header(&mut self, new_header: H) -> Result<(), Error> {
+        let header_data = serialize_impl(&new_header)?;
+        let new_header_len = header_data.len() as u32 + 4;
+
+        // A header with a different length moves all data following it.
+        let start = self.backend.first_index();
+        let end = if new_header_len == self.header_len {
+            start + self.header_len()
+        } else {
+            self.backend.data().len()
+        };
+        self.undo.record(&self.backend, start..end);
+
+        self.backend
+            .replace(4, self.header_len as usize - 4, &header_data)?;
+        self.backend.replace_same_len_t(0, &new_header_len)?;
         self.header_len = new_header_len;
         self.header = new_header;
         Ok(())
@@ -99,6 +118,43 @@ where
     pub fn backend(&self) -> &B {
         &self.backend
     }
+
+    /// Starts recording all writes so they can be rolled back with `rollback_undo()`.
+    #[inline]
+    pub(crate) fn begin_undo(&mut self) {
+        self.undo.begin(&self.backend);
+    }
+
+    /// Keeps all writes done since the last call of `begin_undo()`.
+    #[inline]
+    pub(crate) fn commit_undo(&mut self) {
+        self.undo.commit();
+    }
+
+    /// Rolls back all writes done since the last call of `begin_undo()`. Call `reload()` afterwards.
+    #[inline]
+    pub(crate) fn rollback_undo(&mut self) -> Result<(), Error>
+    where
+        B: GrowableBackend,
+    {
+        self.undo.rollback(&mut self.backend)
+    }
+
+    /// Reads the header again from the backend.
+    pub(crate) fn reload(&mut self) -> Result<(), Error> {
+        (self.header, self.header_len) = Self::read_header(&self.backend)?;
+        Ok(())
+    }
+
+    fn read_header(backend: &B) -> Result<(H, u32), Error> {
+        let header_len: u32 = backend.get_t(0, 4).map_err(|_| Error::InvalidHeader)?;
+
+        let header_data = backend
+            .get(4, header_len as usize - 4)
+            .map_err(|_| Error::InvalidHeader)?;
+
+        Ok((bitcode::deserialize(header_data)?, header_len))
+    }
 }
 
 impl<B, H> MType for CustomHeaderFile<B, H>
@@ -131,9 +187,16 @@ impl<B: Backend, H> Backend for CustomHeaderFile<B, H> {
 
     #[inline]
     fn data_mut(&mut self) -> &mut [u8] {
+        self.undo.record(&self.backend, 0..self.backend.data().len());
         self.backend.data_mut()
     }
 
+    #[inline]
+    fn data_mut_range(&mut self, range: Range<usize>) -> &mut [u8] {
+        self.undo.record(&self.backend, range.clone());
+        self.backend.data_mut_range(range)
+    }
+
     #[inline]
     fn first_index(&self) -> usize {
         self.backend.first_index() + self.header_len as usize
@@ -156,6 +219,15 @@ impl<B: Backend, H> Backend for CustomHeaderFile<B, H> {
     }
 }
 
+impl<B: Backend, H> CustomHeaderFile<B, H> {
+    /// Records the data that gets cut off by shrinking the backend by `size` bytes.
+    #[inline]
+    fn record_truncation(&mut self, size: usize) {
+        let end = self.backend.data().len();
+        self.undo.record(&self.backend, end.saturating_sub(size)..end);
+    }
+}
+
 impl<B, H> GrowableBackend for CustomHeaderFile<B, H>
 where
     B: GrowableBackend,
@@ -171,16 +243,26 @@ where
 
     #[inline]
     fn shrink(&mut self, size: usize) -> crate::Result<()> {
+        self.record_truncation(size);
         self.backend.shrink(size)
     }
 
     #[inline]
     fn resize(&mut self, delta: isize) -> crate::Result<()> {
+        if delta < 0 {
+            self.record_truncation(delta.unsigned_abs());
+        }
         self.backend.resize(delta)
     }
+
+    #[inline]
+    fn growth_policy(&self) -> &dyn GrowthPolicy {
+        self.backend.growth_policy()
+    }
 }
 
 #[cfg(test)]
+#[cfg(feature = "mmap")]
 mod test {
     use super::*;
     use crate::backend::base::BaseBackend;
@@ -1,4 +1,5 @@
 use crate::backend::growable::GrowableBackend;
+use crate::backend::growth::GrowthPolicy;
 use crate::backend::Backend;
 use crate::components::indexed_file::IndexedFile;
 use crate::components::split_file::backend_index::BackendIndex;
@@ -112,6 +113,11 @@ impl<'d, B> GrowableBackend for Entry<'d, B>
         self.index.end -= size;
         Ok(())
     }
+
+    #[inline]
+    fn growth_policy(&self) -> &dyn GrowthPolicy {
+        self.ifile.backend.growth_policy()
+    }
 }
 
 impl<'d, B> Backend for Entry<'d, B>
@@ -125,7 +131,14 @@ impl<'d, B> Backend for Entry<'d, B>
 
     #[inline]
     fn data_mut(&mut self) -> &mut [u8] {
-        &mut self.ifile.backend.backend_data_mut(BackendIndex::Second)[self.index.clone()]
+        self.ifile.backend.backend_data_mut_range(BackendIndex::Second, self.index.clone())
+    }
+
+    #[inline]
+    fn data_mut_range(&mut self, range: Range<usize>) -> &mut [u8] {
+        assert!(range.start <= range.end && range.end <= self.index.len(), "range out of bounds");
+        let start = self.index.start + range.start;
+        self.ifile.backend.backend_data_mut_range(BackendIndex::Second, start..start + range.len())
     }
 
     #[inline]
@@ -194,6 +207,7 @@ impl<'d, B> MType for Entry<'d, B>
 }
 
 #[cfg(test)]
+#[cfg(feature = "mmap")]
 pub mod test {
     use super::*;
     use crate::backend::memory::test::make_mem_backend;
@@ -1,10 +1,12 @@
 use crate::backend::Backend;
 use crate::components::indexed_file::IndexedFile;
+use crate::utils::ReadAhead;
 
 pub struct IndexedFileIter<'i, B> {
     ifile: &'i IndexedFile<B>,
     pos: usize,
     pos_end: usize,
+    read_ahead: ReadAhead,
 }
 
 impl<'i, B> IndexedFileIter<'i, B> {
@@ -14,10 +16,28 @@ impl<'i, B> IndexedFileIter<'i, B> {
             ifile,
             pos: 0,
             pos_end: 0,
+            read_ahead: ReadAhead::default(),
         }
     }
 }
 
+impl<'i, B> IndexedFileIter<'i, B>
+where
+    B: Backend,
+{
+    /// Hints the backend to load entries `distance` items ahead of the current position while iterating forward,
+    /// which speeds up scans over files that are not in the page cache yet. The backend is also advised about the
+    /// sequential access. A distance of 0 disables prefetching.
+    #[inline]
+    pub fn prefetch(mut self, distance: usize) -> Self {
+        if distance > 0 {
+            self.ifile.inner().advise_sequential();
+        }
+        self.read_ahead = ReadAhead::new(distance);
+        self
+    }
+}
+
 impl<'i, B> Iterator for IndexedFileIter<'i, B>
 where
     B: Backend,
@@ -26,6 +46,9 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(window) = self.read_ahead.window(self.pos, self.ifile.count) {
+            self.ifile.prefetch_range(window);
+        }
         let item = self.ifile.get(self.pos).ok()?;
         self.pos += 1;
         Some(item)
@@ -77,6 +100,7 @@ where
 }
 
 #[cfg(test)]
+#[cfg(feature = "mmap")]
 mod test {
     use super::*;
     use crate::backend::growable::GrowableBackend;
@@ -108,6 +132,9 @@ mod test {
         exp.reverse();
         assert_eq!(iter.rev().collect::<Vec<_>>(), exp);
 
+        let iter = IndexedFileIter::new(ifile).prefetch(2);
+        assert_eq!(iter.collect::<Vec<_>>(), ALL_TEST_DATA);
+
         let iter = IndexedFileIter::new(ifile);
         assert_eq!(iter.last(), Some(ALL_TEST_DATA[ALL_TEST_DATA.len() - 1]));
     }
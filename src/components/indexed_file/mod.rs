@@ -4,18 +4,23 @@ pub mod iter;
 use crate::backend::base::sub::BaseSubBackend;
 use crate::backend::full::FullBackend;
 use crate::backend::growable::GrowableBackend;
+use crate::backend::growth::GrowthPolicy;
+use crate::backend::shared::SharedBackend;
 use crate::backend::Backend;
 use crate::components::indexed_file::entry::Entry;
 use crate::components::indexed_file::iter::IndexedFileIter;
 use crate::components::split_file;
 use crate::components::split_file::backend_index::BackendIndex;
 use crate::components::split_file::SplitFile;
-use crate::deser::{deserialize_impl, serialize_impl};
+use crate::deser::{deserialize_impl, deserialize_into, serialize_impl, ScratchBuffer};
 use crate::error::Error;
 use crate::header::BaseHeader;
 use crate::traits::creatable::Creatable;
 use crate::traits::initiable::Initiable;
 use crate::traits::mtype::MType;
+use crate::traits::read_only::ReadOnly;
+use crate::traits::batch::Batch;
+use crate::traits::shrink::{AutoShrink, ShrinkPolicy, ShrinkTracker};
 use crate::Result;
 use mult_split::MultiSplit;
 use serde::de::DeserializeOwned;
@@ -28,24 +33,103 @@ use std::ops::Range;
 pub struct IndexedFile<B> {
     backend: SplitFile<B>,
     count: usize,
+    shrink: Option<ShrinkTracker>,
 }
 
 impl<'b, B> IndexedFile<B>
     where
         B: Backend + 'b,
 {
+    /// Returns the underlying backend.
+    #[inline]
+    pub(crate) fn inner(&self) -> &B {
+        self.backend.inner()
+    }
+
+    /// Starts recording all writes so they can be rolled back with `rollback_undo()`.
+    #[inline]
+    pub(crate) fn begin_undo(&mut self) {
+        self.backend.begin_undo();
+    }
+
+    /// Keeps all writes done since the last call of `begin_undo()`.
+    #[inline]
+    pub(crate) fn commit_undo(&mut self) {
+        self.backend.commit_undo();
+    }
+
+    /// Reads the state again from the backend.
+    pub(crate) fn reload(&mut self) -> Result<()> {
+        self.backend.reload()?;
+        self.count = self.backend.first().len() / 8;
+        Ok(())
+    }
+
     /// Gets a deserializeable value from the IndexedFile by its ID.
     #[inline]
     pub fn get_t<T: DeserializeOwned>(&self, index: usize) -> Result<T> {
         deserialize_impl(self.get(index)?)
     }
 
+    /// Same as `get_t` but reuses the allocations of `scratch`, which avoids allocating for each call in hot loops.
+    #[inline]
+    pub fn get_t_into<T: DeserializeOwned>(&self, index: usize, scratch: &mut ScratchBuffer) -> Result<T> {
+        deserialize_into(scratch, self.get(index)?)
+    }
+
+    /// Hints that the entry with the given ID will be read soon. Unknown IDs are ignored.
+    #[inline]
+    pub fn prefetch(&self, id: usize) {
+        if let Ok(data) = self.get(id) {
+            crate::utils::prefetch(data);
+        }
+    }
+
+    /// Hints that all entries with an ID within `ids` will be read soon. IDs out of bounds are ignored.
+    pub fn prefetch_range(&self, ids: Range<usize>) {
+        let end = ids.end.min(self.count);
+        if ids.start >= end {
+            return;
+        }
+
+        // Offsets are always 8 bytes.
+        self.first().prefetch(ids.start * 8, (end - ids.start + 1) * 8);
+
+        let (Ok(first), Ok(last)) = (self.entry_index(ids.start), self.entry_index(end - 1)) else {
+            return;
+        };
+        crate::utils::prefetch(&self.backend.backend_data(BackendIndex::Second)[first.start..last.end]);
+    }
+
     /// Gets the data of an entry by its ID.
     pub fn get(&self, id: usize) -> Result<&[u8]> {
         let index = self.entry_index(id)?;
         Ok(&self.backend.backend_data(BackendIndex::Second)[index])
     }
 
+    /// Gets the data of all entries with an ID within `ids` as a single slice, along with the range of each entry
+    /// within this slice.
+    pub fn get_range(&self, ids: Range<usize>) -> Result<(&[u8], Vec<Range<usize>>)> {
+        if ids.is_empty() {
+            return Ok((&[], vec![]));
+        }
+
+        if ids.end > self.count {
+            return Err(Error::OutOfBounds);
+        }
+
+        let start = self.entry_index(ids.start)?.start;
+        let mut end = start;
+        let mut ranges = Vec::with_capacity(ids.len());
+        for id in ids {
+            let index = self.entry_index(id)?;
+            ranges.push(index.start - start..index.end - start);
+            end = index.end;
+        }
+
+        Ok((&self.backend.backend_data(BackendIndex::Second)[start..end], ranges))
+    }
+
     /// Gets the entry by its ID as `FullBackend`.
     pub fn get_backend(&self, id: usize) -> Result<FullBackend<&[u8]>> {
         let index = self.entry_index(id)?;
@@ -56,7 +140,17 @@ impl<'b, B> IndexedFile<B>
     /// Gets the mutable data of an entry by its ID.
     pub fn get_mut(&mut self, id: usize) -> Result<&mut [u8]> {
         let index = self.entry_index(id)?;
-        Ok(&mut self.backend.backend_data_mut(BackendIndex::Second)[index])
+        Ok(self.backend.backend_data_mut_range(BackendIndex::Second, index))
+    }
+
+    /// Gets the bytes within `range` of an entry by its ID mutable.
+    pub(crate) fn get_mut_range(&mut self, id: usize, range: Range<usize>) -> Result<&mut [u8]> {
+        let index = self.entry_index(id)?;
+        if range.start > range.end || range.end > index.len() {
+            return Err(Error::OutOfBounds);
+        }
+        let start = index.start + range.start;
+        Ok(self.backend.backend_data_mut_range(BackendIndex::Second, start..start + range.len()))
     }
 
     /// Gets the entry by its ID as `FullBackend` mutable.
@@ -96,8 +190,12 @@ impl<'b, B> IndexedFile<B>
         &mut self,
         ranges: [Range<usize>; N],
     ) -> [&mut [u8]; N] {
-        let mut mult_split = MultiSplit::new(self.backend.backend_data_mut(BackendIndex::Second));
-        ranges.map(|range| mult_split.borrow_mut(range).unwrap())
+        // Only borrow the span covering all ranges so wrapping backends know which data may get written.
+        let start = ranges.iter().map(|i| i.start).min().unwrap_or(0);
+        let end = ranges.iter().map(|i| i.end).max().unwrap_or(0);
+        let data = self.backend.backend_data_mut_range(BackendIndex::Second, start..end);
+        let mut mult_split = MultiSplit::new(data);
+        ranges.map(|range| mult_split.borrow_mut(range.start - start..range.end - start).unwrap())
     }
 
     /// Gets two items in the `IndexedFile` mutable.
@@ -197,7 +295,7 @@ impl<'b, B> IndexedFile<B>
         let data = new_val.to_le_bytes();
         let mut fm = self.first_mut();
         let index = fm.get_index(id * 8);
-        fm.data_mut()[index..index + 8].copy_from_slice(&data);
+        fm.data_mut_range(index..index + 8).copy_from_slice(&data);
         Ok(())
     }
 
@@ -224,7 +322,7 @@ impl<'b, B> IndexedFile<B>
 
     /// Returns `true` if the second list can fit the given amount of bytes.
     #[inline]
-    fn second_can_fit(&self, bytes: usize) -> bool {
+    pub(crate) fn second_can_fit(&self, bytes: usize) -> bool {
         self.second().free() >= bytes
     }
 
@@ -234,6 +332,13 @@ impl<'b, B> IndexedFile<B>
         self.first().free() >= item_count * 8
     }
 
+    /// Returns the free bytes and the total capacity in bytes of both, the index and the data.
+    #[inline]
+    pub(crate) fn space(&self) -> (usize, usize) {
+        let (first, second) = (self.first(), self.second());
+        (first.free() + second.free(), first.capacity() + second.capacity())
+    }
+
     /// Shifts all offsets by a given offset `by` after a given id. `after_id` itself
     /// doesn't get updated.
     pub(crate) fn shift_offsets(&mut self, after_id: usize, by: isize) -> Result<bool> {
@@ -269,6 +374,16 @@ impl<'b, B> IndexedFile<B>
     }
 
 
+    /// Shifts the offsets of all entries in `ids` by `by`.
+    fn shift_offset_range(&mut self, ids: Range<usize>, by: isize) -> Result<()> {
+        for id in ids {
+            let pos = self.id_to_storage_offset(id)?;
+            let res = pos.checked_add_signed(by).ok_or(Error::InvalidShift)?;
+            self.set_id_to_storage_offset(id, res)?;
+        }
+        Ok(())
+    }
+
     /// Shifts all offsets by a given offset `by` after a given id. `after_id` itself
     /// doesn't get updated.
     #[allow(dead_code)]
@@ -362,7 +477,11 @@ impl<'b, B> IndexedFile<B>
     #[inline]
     fn from_split_file(backend: SplitFile<B>) -> Result<Self> {
         let count = backend.first().len() / 8;
-        Ok(Self { backend, count })
+        Ok(Self {
+            backend,
+            count,
+            shrink: None,
+        })
     }
 
     /*    fn header(&self) -> Vec<usize> {
@@ -393,10 +512,67 @@ impl<B> Initiable<B> for IndexedFile<B>
     }
 }
 
+impl<B> Batch for IndexedFile<B>
+    where
+        B: GrowableBackend,
+{
+    fn batch<F, R>(&mut self, f: F) -> Result<R>
+        where
+            F: FnOnce(&mut Self) -> Result<R>,
+    {
+        self.begin_undo();
+        match f(self) {
+            Ok(res) => {
+                self.commit_undo();
+                self.flush()?;
+                Ok(res)
+            }
+            Err(err) => {
+                self.rollback_undo()?;
+                Err(err)
+            }
+        }
+    }
+}
+
+impl<B> IndexedFile<SharedBackend<B>>
+    where
+        B: Backend,
+{
+    /// Creates a new, independent read handle sharing the same backend. Handles can be moved to other threads to
+    /// serve reads concurrently.
+    #[inline]
+    pub fn reader(&self) -> Result<ReadOnly<Self>> {
+        Self::init_readonly(self.inner().clone())
+    }
+}
+
+impl<'a> IndexedFile<BaseSubBackend<'a, &'a [u8]>> {
+    /// Same as `get` but the returned data borrows the storage of the underlying sub-backend instead of `self`, so it
+    /// can outlive the `IndexedFile`.
+    #[inline]
+    pub fn get_borrowed(&self, id: usize) -> Result<&'a [u8]> {
+        let index = self.entry_index(id)?;
+        Ok(&self.backend.backend_data_borrowed(BackendIndex::Second)[index])
+    }
+}
+
 impl<B> IndexedFile<B>
     where
         B: GrowableBackend,
 {
+    /// Returns the growth policy of the underlying backend.
+    #[inline]
+    pub fn growth_policy(&self) -> &dyn GrowthPolicy {
+        self.backend.growth_policy()
+    }
+
+    /// Rolls back all writes done since the last call of `begin_undo()`.
+    pub(crate) fn rollback_undo(&mut self) -> Result<()> {
+        self.backend.rollback_undo()?;
+        self.reload()
+    }
+
     /// Inserts a serializeable value into the IndexedFile and returns its ID.
     pub fn insert_t<T: Serialize>(&mut self, item: &T) -> Result<usize> {
         let data = serialize_impl(item)?;
@@ -406,14 +582,16 @@ impl<B> IndexedFile<B>
 
     /// Inserts raw data into the IndexedFile and returns its ID.
     pub fn insert(&mut self, data: &[u8]) -> Result<usize> {
+        self.auto_shrink()?;
         let id = self.count();
 
+        // Grows data if nedeed. This happens before adding the index so a failed grow doesn't leave an entry without
+        // data behind.
+        self.grow_data_for(data.len())?;
+
         let pos = self.second().len();
         self.add_index(pos)?;
 
-        // Grows data if nedeed.
-        self.grow_data_for(data.len())?;
-
         self.second_mut().push(data)?;
         Ok(id)
     }
@@ -454,13 +632,25 @@ impl<B> IndexedFile<B>
             self.grow_data_by(data_len)?;
         }
 
-        let first_id = self.insert(items[0].as_ref())?;
+        self.auto_shrink()?;
+        let first_id = self.count();
 
-        // Insert data
-        for i in &items[1..] {
-            let i = i.as_ref();
-            self.insert(i)?;
-        }
+        // Write all indices and all data with one push each
+        let mut pos = self.second().len();
+        let indices: Vec<[u8; 8]> = items
+            .iter()
+            .map(|i| {
+                let index = pos;
+                pos += i.as_ref().len();
+                index.to_le_bytes()
+            })
+            .collect();
+        let index_bufs: Vec<&[u8]> = indices.iter().map(|i| &i[..]).collect();
+        self.first_mut().push_vectored(&index_bufs)?;
+        self.count += items.len();
+
+        let data_bufs: Vec<&[u8]> = items.iter().map(|i| i.as_ref()).collect();
+        self.second_mut().push_vectored(&data_bufs)?;
 
         Ok(Some(first_id))
     }
@@ -475,6 +665,7 @@ impl<B> IndexedFile<B>
             return Ok(());
         }
 
+        self.auto_shrink()?;
         self.grow_data_for(data.len())?;
 
         let insert_index = self.id_to_storage_offset(pos)?;
@@ -490,7 +681,9 @@ impl<B> IndexedFile<B>
             return Ok(());
         }
 
-        self.backend.grow(BackendIndex::Second, size)?;
+        if !self.second_can_fit(size) {
+            self.backend.grow(BackendIndex::Second, size)?;
+        }
 
         let index = self.entry_index(id)?.end - self.second().first_index();
         self.second_mut().replace_fill(index, 0, value, size)?;
@@ -506,7 +699,9 @@ impl<B> IndexedFile<B>
             return Ok(());
         }
 
-        self.backend.grow(BackendIndex::Second, size)?;
+        if !self.second_can_fit(size) {
+            self.backend.grow(BackendIndex::Second, size)?;
+        }
 
         let index = self.entry_index(id)?.end - self.second().first_index();
         self.second_mut().replace(index, 0, data)?;
@@ -603,6 +798,50 @@ impl<B> IndexedFile<B>
         self.backend.shrink_to_fit()
     }
 
+    /// Shrinks the file if required by its shrink policy.
+    fn auto_shrink(&mut self) -> Result<()> {
+        let (free, capacity) = self.space();
+        if let Some(tracker) = self.shrink.as_mut() {
+            if tracker.record(free, capacity) {
+                self.shrink_to_fit()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves `size` bytes of capacity from the end of the entry `from` to the end of the entry `to` without growing
+    /// the file. Only the entries between both and the first `from_used`/`to_used` bytes of `from` and `to` get moved,
+    /// the remaining bytes of the entries are treated as unused. The bytes gained by `to` are zeroed.
+    pub(crate) fn move_entry_capacity(
+        &mut self,
+        from: usize,
+        from_used: usize,
+        to: usize,
+        to_used: usize,
+        size: usize,
+    ) -> Result<()> {
+        let from_index = self.entry_index(from)?;
+        let to_index = self.entry_index(to)?;
+        if from == to || from_index.len() < from_used + size || to_index.len() < to_used {
+            return Err(Error::OutOfBounds);
+        }
+
+        let mut storage = self.second_mut();
+        if from > to {
+            // Everything between the end of `to` and the used part of `from` moves towards the end.
+            let len = from_index.start + from_used - to_index.end;
+            storage.move_range_to(to_index.end, len, to_index.end + size)?;
+            storage.data_mut_range(to_index.end..to_index.end + size).fill(0);
+            self.shift_offset_range(to + 1..from + 1, size as isize)
+        } else {
+            // Everything between the end of `from` and the used part of `to` moves towards the start.
+            let used_end = to_index.start + to_used;
+            storage.move_range_to(from_index.end, used_end - from_index.end, from_index.end - size)?;
+            storage.data_mut_range(used_end - size..used_end).fill(0);
+            self.shift_offset_range(from + 1..to + 1, -(size as isize))
+        }
+    }
+
     /// Shrinks the entries size by `delta` without checking the entries 'len' bounds which means that meaningful data
     /// in the entry might be truncated.
     pub(crate) fn shrink_entry_unchecked(&mut self, id: usize, delta: usize) -> Result<()> {
@@ -616,7 +855,7 @@ impl<B> IndexedFile<B>
         let mut storage = self.second_mut();
         storage.replace(end_index, delta, &[])?;
         self.shift_offsets(id, -(delta as isize))?;
-        self.backend.shrink(BackendIndex::Second, delta)?;
+        self.backend.truncate_second(delta)?;
         Ok(())
     }
 
@@ -628,6 +867,18 @@ impl<B> IndexedFile<B>
         Ok(())
     }
 
+    /// Same as `grow` but only grows by the space that isn't free already, so inserting `add_entries` more entries with
+    /// `total_entry_len` of total entry data won't regrow the file.
+    pub fn reserve(&mut self, add_entries: usize, total_entry_len: usize) -> Result<()> {
+        let entry_size = (add_entries * 8).saturating_sub(self.first().free());
+        let data_size = total_entry_len.saturating_sub(self.second().free());
+        if entry_size == 0 && data_size == 0 {
+            return Ok(());
+        }
+        self.backend.grow_both(entry_size, data_size)?;
+        Ok(())
+    }
+
     /// Adds a new entry to the index, providing its position in self.second(). Returns the ID of the new entry.
     fn add_index_at(&mut self, pos: usize, data_pos: usize) -> Result<()> {
         if self.first().free() < 8 {
@@ -655,11 +906,10 @@ impl<B> IndexedFile<B>
 
     /// Grows the ID-Index mapping area of the IndexedFile.
     fn grow_list(&mut self) -> Result<()> {
-        // println!("Growing list");
-
-        // Duplicate current size
-        let first_cap = self.first().capacity().max(8);
-        self.grow_list_by(first_cap)?;
+        let capacity = self.first().capacity();
+        let required = self.first().len() + 8;
+        let new_capacity = self.growth_policy().next_capacity(capacity, required);
+        self.grow_list_by(new_capacity - capacity)?;
 
         Ok(())
     }
@@ -677,8 +927,9 @@ impl<B> IndexedFile<B>
             return Ok(0);
         }
 
-        // println!("Growing data");
-        let size = len.max(self.second().capacity());
+        let capacity = self.second().capacity();
+        let required = self.second().len() + len;
+        let size = self.growth_policy().next_capacity(capacity, required) - capacity;
         self.grow_data_by(size)?;
         Ok(size)
     }
@@ -709,6 +960,18 @@ impl<B, T> Extend<T> for IndexedFile<B>
     }
 }
 
+impl<B> AutoShrink for IndexedFile<B> {
+    #[inline]
+    fn set_shrink_policy(&mut self, policy: Option<ShrinkPolicy>) {
+        self.shrink = policy.map(ShrinkTracker::new);
+    }
+
+    #[inline]
+    fn shrink_policy(&self) -> Option<ShrinkPolicy> {
+        self.shrink.map(|i| i.policy())
+    }
+}
+
 impl<B> MType for IndexedFile<B>
     where
         B: Backend,
@@ -720,6 +983,7 @@ impl<B> MType for IndexedFile<B>
 }
 
 #[cfg(test)]
+#[cfg(feature = "mmap")]
 mod test {
     use super::*;
     use crate::backend::memory::test::{make_deeta, make_mem_backend};
@@ -738,8 +1002,12 @@ mod test {
         test_push(&mut indf);
         test_grow(&mut indf);
         test_insert_at(&mut indf);
+        test_insert_n(&mut indf);
         test_two_mut(&mut indf);
         test_full_be(&mut indf);
+        test_auto_shrink(&mut indf);
+        test_batch(&mut indf);
+        test_get_range(&mut indf);
         test_init(&mut backend);
 
         let mut backend = make_mmap_backend("stest2", 100);
@@ -749,11 +1017,82 @@ mod test {
         test_push(&mut indf);
         test_grow(&mut indf);
         test_insert_at(&mut indf);
+        test_insert_n(&mut indf);
         test_two_mut(&mut indf);
         test_full_be(&mut indf);
+        test_auto_shrink(&mut indf);
+        test_batch(&mut indf);
+        test_get_range(&mut indf);
         test_init(&mut backend);
     }
 
+    #[test]
+    fn test_reader() {
+        let mut backend = make_mem_backend(100);
+        {
+            let mut indf = IndexedFile::create(&mut backend).unwrap();
+            for i in 0..100u32 {
+                indf.insert(&i.to_le_bytes()).unwrap();
+            }
+        }
+
+        let indf = IndexedFile::init_readonly(SharedBackend::new(backend)).unwrap();
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                let reader = indf.reader().unwrap();
+                s.spawn(move || {
+                    assert_eq!(reader.count(), 100);
+                    for i in 0..100u32 {
+                        assert_eq!(reader.get(i as usize), Ok(&i.to_le_bytes()[..]));
+                    }
+                });
+            }
+        });
+    }
+
+    fn test_batch<B: GrowableBackend>(backend: &mut IndexedFile<B>) {
+        backend.clear();
+        backend.insert(&[1, 2, 3]).unwrap();
+
+        let res = backend.batch(|b| b.insert(&[4, 5]));
+        assert_eq!(res, Ok(1));
+        assert_eq!(backend.count(), 2);
+
+        let size = backend.raw_data().len();
+        let res = backend.batch(|b| {
+            b.get_mut(0)?.copy_from_slice(&[9, 9, 9]);
+            for i in 0..1000u32 {
+                b.insert(&i.to_le_bytes())?;
+            }
+            b.get_mut(5000).map(|_| ())
+        });
+        assert_eq!(res, Err(Error::OutOfBounds));
+        assert_eq!(backend.raw_data().len(), size);
+        assert_eq!(backend.count(), 2);
+        assert_eq!(backend.get(0), Ok(&[1, 2, 3][..]));
+        assert_eq!(backend.get(1), Ok(&[4, 5][..]));
+
+        backend.insert(&[6]).unwrap();
+        assert_eq!(backend.get(2), Ok(&[6][..]));
+    }
+
+    fn test_auto_shrink<B: GrowableBackend>(backend: &mut IndexedFile<B>) {
+        backend.clear();
+        backend.grow(100, 10_000).unwrap();
+        backend.set_shrink_policy(Some(ShrinkPolicy::new(0.5, 2)));
+
+        let size = backend.raw_data().len();
+        backend.insert(&[1, 2, 3]).unwrap();
+        assert_eq!(backend.raw_data().len(), size);
+
+        backend.insert(&[4, 5]).unwrap();
+        assert!(backend.raw_data().len() < size - 10_000);
+        assert_eq!(backend.get(0), Ok(&[1, 2, 3][..]));
+        assert_eq!(backend.get(1), Ok(&[4, 5][..]));
+
+        backend.set_shrink_policy(None);
+    }
+
     fn test_init<B: GrowableBackend>(mut backend: &mut B) {
         backend.clear();
 
@@ -767,6 +1106,11 @@ mod test {
 
         let indf = IndexedFile::init(&mut backend).unwrap();
         assert_eq!(indf.get_t(0), Ok("hallo".to_string()));
+
+        let mut scratch = ScratchBuffer::new();
+        for _ in 0..3 {
+            assert_eq!(indf.get_t_into(0, &mut scratch), Ok("hallo".to_string()));
+        }
     }
 
     fn test_push<B: GrowableBackend>(backend: &mut IndexedFile<B>) {
@@ -784,6 +1128,26 @@ mod test {
         assert_eq!(backend.get_t(2), Ok("fill me".to_string()));
     }
 
+    fn test_get_range<B: GrowableBackend>(backend: &mut IndexedFile<B>) {
+        backend.clear();
+        backend.insert(&[1, 2, 3]).unwrap();
+        backend.insert(&[]).unwrap();
+        backend.insert(&[4, 5]).unwrap();
+        backend.insert(&[6]).unwrap();
+
+        let (data, ranges) = backend.get_range(1..4).unwrap();
+        assert_eq!(data, &[4, 5, 6]);
+        assert_eq!(ranges, vec![0..0, 0..2, 2..3]);
+
+        let (data, ranges) = backend.get_range(0..4).unwrap();
+        for (id, range) in ranges.into_iter().enumerate() {
+            assert_eq!(&data[range], backend.get(id).unwrap());
+        }
+
+        assert_eq!(backend.get_range(2..2), Ok((&[][..], vec![])));
+        assert_eq!(backend.get_range(2..5), Err(Error::OutOfBounds));
+    }
+
     fn test_clear<B: GrowableBackend>(backend: &mut IndexedFile<B>) {
         assert!(backend.is_empty());
 
@@ -939,6 +1303,20 @@ mod test {
         assert_eq!(r, Err(Error::OutOfBounds));
     }
 
+    fn test_insert_n<B: GrowableBackend>(backend: &mut IndexedFile<B>) {
+        backend.clear();
+        assert_eq!(backend.insert_n::<_, &[u8]>([]), Ok(None));
+        backend.insert(TEST_DATA_1).unwrap();
+        assert_eq!(backend.insert_n([TEST_DATA_2, &[], TEST_DATA_3]), Ok(Some(1)));
+        assert_eq!(backend.count(), 4);
+        assert_eq!(backend.get(1), Ok(TEST_DATA_2));
+        assert_eq!(backend.get(2), Ok(&[][..]));
+        assert_eq!(backend.get(3), Ok(TEST_DATA_3));
+
+        backend.insert(TEST_DATA_4).unwrap();
+        assert_eq!(backend.get(4), Ok(TEST_DATA_4));
+    }
+
     fn test_insert_at<B: GrowableBackend>(backend: &mut IndexedFile<B>) {
         backend.clear();
         backend.insert(&[0u8; 40]).unwrap();
@@ -0,0 +1,282 @@
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::indexed_file::IndexedFile;
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::{Error, Result};
+use std::ops::Range;
+
+/// Size of a single encoded entry: START (8 bytes) + END (8 bytes) + MAX END (8 bytes) + PAYLOAD (8 bytes)
+const ENTRY_SIZE: usize = 4 * 8;
+
+/// ID of the entry holding all intervals.
+const NODES_ID: usize = 0;
+
+/// A static interval tree storing closed intervals [start, end] along with a payload. The tree gets bulk loaded once
+/// and can't be modified afterwards.
+///
+/// Intervals are stored sorted by their start. The middle of each range of intervals acts as node of an implicit
+/// balanced search tree and additionally stores the biggest end of all intervals in its range, which allows skipping
+/// whole subtrees that end before the queried position.
+///
+/// The internal format: IndexedFile(NODES)
+/// where each node is encoded as (START | END | MAX END | PAYLOAD).
+pub struct IntervalTree<B> {
+    storage: IndexedFile<B>,
+    len: usize,
+}
+
+#[derive(Clone, Copy)]
+struct Node {
+    start: u64,
+    end: u64,
+    max_end: u64,
+    payload: u64,
+}
+
+impl<B> IntervalTree<B>
+    where
+        B: GrowableBackend,
+{
+    /// Builds a new interval tree from (start, end, payload) triples where both, start and end are inclusive. Returns
+    /// `Error::UnexpectedValue` if an interval ends before it starts.
+    pub fn build<I>(backend: B, items: I) -> Result<Self>
+        where
+            I: IntoIterator<Item=(u64, u64, u64)>,
+    {
+        let mut nodes: Vec<Node> = items
+            .into_iter()
+            .map(|(start, end, payload)| Node {
+                start,
+                end,
+                max_end: end,
+                payload,
+            })
+            .collect();
+        if nodes.iter().any(|i| i.end < i.start) {
+            return Err(Error::UnexpectedValue);
+        }
+        nodes.sort_by_key(|i| (i.start, i.end));
+        let len = nodes.len();
+        fill_max_end(&mut nodes, 0..len);
+
+        let mut data = Vec::with_capacity(nodes.len() * ENTRY_SIZE);
+        for node in nodes.iter() {
+            for i in [node.start, node.end, node.max_end, node.payload] {
+                data.extend_from_slice(&i.to_le_bytes());
+            }
+        }
+
+        let mut storage = IndexedFile::with_capacity(backend, 1)?;
+        storage.insert(&data)?;
+
+        Ok(Self { storage, len })
+    }
+}
+
+impl<B> IntervalTree<B>
+    where
+        B: Backend,
+{
+    /// Returns the amount of intervals stored in the tree.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the tree doesn't contain any intervals.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the payloads of all intervals containing `x`, ordered by the start of their intervals.
+    #[inline]
+    pub fn stab(&self, x: u64) -> Vec<u64> {
+        self.overlapping(x, x)
+    }
+
+    /// Returns the payloads of all intervals overlapping the closed interval [`start`, `end`], ordered by the start of
+    /// their intervals.
+    pub fn overlapping(&self, start: u64, end: u64) -> Vec<u64> {
+        let mut out = vec![];
+        if start <= end {
+            self.collect(0..self.len, start, end, &mut out);
+        }
+        out
+    }
+
+    /// Returns the (start, end, payload) triple of the interval at `index` in order of their starts.
+    pub fn get(&self, index: usize) -> Result<(u64, u64, u64)> {
+        if index >= self.len {
+            return Err(Error::OutOfBounds);
+        }
+        let node = self.node(index);
+        Ok((node.start, node.end, node.payload))
+    }
+
+    /// Returns an iterator over all intervals as (start, end, payload) triples in order of their starts.
+    #[inline]
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (u64, u64, u64)> + '_ {
+        (0..self.len).map(|i| self.get(i).unwrap())
+    }
+
+    /// Collects the payloads of all intervals within `range` that overlap [`start`, `end`] in order.
+    fn collect(&self, range: Range<usize>, start: u64, end: u64, out: &mut Vec<u64>) {
+        if range.is_empty() {
+            return;
+        }
+
+        let mid = middle(&range);
+        let node = self.node(mid);
+        if node.max_end < start {
+            // All intervals in this subtree end before the queried interval starts.
+            return;
+        }
+
+        self.collect(range.start..mid, start, end, out);
+        if node.start > end {
+            // All following intervals start after the queried interval ends.
+            return;
+        }
+        if node.end >= start {
+            out.push(node.payload);
+        }
+        self.collect(mid + 1..range.end, start, end, out);
+    }
+
+    #[inline]
+    fn node(&self, index: usize) -> Node {
+        let nodes = self.storage.get(NODES_ID).unwrap();
+        let data = &nodes[index * ENTRY_SIZE..(index + 1) * ENTRY_SIZE];
+        let field = |i: usize| u64::from_le_bytes(data[i * 8..(i + 1) * 8].try_into().unwrap());
+        Node {
+            start: field(0),
+            end: field(1),
+            max_end: field(2),
+            payload: field(3),
+        }
+    }
+}
+
+impl<B> Initiable<B> for IntervalTree<B>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let storage = IndexedFile::init(backend)?;
+        let size = storage.get(NODES_ID).map_err(|_| Error::Initialization)?.len();
+        if !size.is_multiple_of(ENTRY_SIZE) {
+            return Err(Error::Initialization);
+        }
+
+        Ok(Self {
+            storage,
+            len: size / ENTRY_SIZE,
+        })
+    }
+}
+
+impl<B> MType for IntervalTree<B>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.storage.raw_data()
+    }
+}
+
+/// Returns the index of the node representing the given range of nodes.
+#[inline]
+fn middle(range: &Range<usize>) -> usize {
+    range.start + range.len() / 2
+}
+
+/// Sets the max end of the node representing `range` to the biggest end within the range and returns it.
+fn fill_max_end(nodes: &mut [Node], range: Range<usize>) -> u64 {
+    if range.is_empty() {
+        return 0;
+    }
+    let mid = middle(&range);
+    let left = fill_max_end(nodes, range.start..mid);
+    let right = fill_max_end(nodes, mid + 1..range.end);
+    let max_end = nodes[mid].end.max(left).max(right);
+    nodes[mid].max_end = max_end;
+    max_end
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_query(&mut mem_backend);
+        test_empty(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./interval_tree", 10);
+        test_query(&mut mmap_backend);
+        test_empty(&mut mmap_backend);
+    }
+
+    fn make_intervals(count: u64) -> Vec<(u64, u64, u64)> {
+        (0..count)
+            .map(|i| {
+                let start = (i * 7919) % 1000;
+                let len = (i * 104729) % 50;
+                (start, start + len, i)
+            })
+            .collect()
+    }
+
+    /// Returns the payloads of all intervals overlapping [start, end] ordered like the trees results.
+    fn expected(intervals: &[(u64, u64, u64)], start: u64, end: u64) -> Vec<u64> {
+        let mut found: Vec<_> = intervals.iter().filter(|i| i.0 <= end && i.1 >= start).collect();
+        found.sort_by_key(|i| (i.0, i.1));
+        found.into_iter().map(|i| i.2).collect()
+    }
+
+    fn test_query<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let intervals = make_intervals(2000);
+        {
+            let tree = IntervalTree::build(&mut backend, intervals.clone()).unwrap();
+            assert_eq!(tree.len(), 2000);
+        }
+
+        let tree = IntervalTree::init(&mut backend).unwrap();
+        for x in [0, 1, 37, 500, 999, 1020, 1048, 1049, 5000] {
+            assert_eq!(tree.stab(x), expected(&intervals, x, x), "{x}");
+        }
+        for (start, end) in [(0, 0), (10, 20), (990, 2000), (400, 401), (0, u64::MAX)] {
+            assert_eq!(tree.overlapping(start, end), expected(&intervals, start, end));
+        }
+        assert!(tree.overlapping(20, 10).is_empty());
+
+        let mut sorted = intervals.clone();
+        sorted.sort_by_key(|i| (i.0, i.1));
+        let ranges: Vec<_> = tree.iter().map(|i| (i.0, i.1)).collect();
+        assert_eq!(ranges, sorted.iter().map(|i| (i.0, i.1)).collect::<Vec<_>>());
+        assert_eq!(tree.get(2000), Err(Error::OutOfBounds));
+    }
+
+    fn test_empty<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        assert!(IntervalTree::build(&mut backend, vec![(5, 4, 0)]).is_err());
+
+        backend.clear();
+        {
+            let tree = IntervalTree::build(&mut backend, vec![]).unwrap();
+            assert!(tree.is_empty());
+            assert!(tree.stab(0).is_empty());
+        }
+        let tree = IntervalTree::init(&mut backend).unwrap();
+        assert!(tree.is_empty());
+    }
+}
@@ -11,6 +11,7 @@ use crate::components::split_file::entry::Entry;
 use crate::components::split_file::SplitFile;
 use crate::traits::creatable::Creatable;
 use crate::traits::initiable::Initiable;
+use crate::traits::varint;
 use crate::Result;
 
 type CompressionRatioWidth = u32;
@@ -32,7 +33,7 @@ where
         P: Into<u64>,
     {
         let number = number.into();
-        let (encoded, enc_len) = varint_simd::encode(number);
+        let (encoded, enc_len) = varint::encode(number);
         let encoded = &encoded[..enc_len as usize];
 
         if self.need_indexing() {
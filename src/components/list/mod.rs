@@ -14,12 +14,13 @@ use crate::components::list::iter::ListIter;
 use crate::deser::{deserialize_impl, serialize_impl};
 use crate::traits::creatable::{Creatable, MemCreatable};
 use crate::traits::initiable::Initiable;
-use crate::utils::smallest_two_power_for;
+use crate::traits::batch::{Batch, UndoLog};
+use crate::traits::shrink::{AutoShrink, ShrinkPolicy, ShrinkTracker};
 use crate::{Error, Result};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::marker::PhantomData;
-use std::ops::RangeBounds;
+use std::ops::{Range, RangeBounds};
 
 /// A Vec<T> like type where each entry `T` de/serializes with the same amount of bytes `N` > 0.
 /// This allows us to store the items storage efficiently, without the need of an index that holds
@@ -30,6 +31,9 @@ use std::ops::RangeBounds;
 pub struct List<B, T, const N: usize> {
     backend: B,
     len: usize,
+    shrink: Option<ShrinkTracker>,
+    /// Records the items overwritten while a batch is running.
+    undo: UndoLog,
     _p1: PhantomData<T>,
 }
 
@@ -71,6 +75,19 @@ impl<B, T, const N: usize> List<B, T, N>
 where
     B: Backend,
 {
+    /// Hints that the entry at `index` will be read soon.
+    #[inline]
+    pub fn prefetch(&self, index: usize) {
+        self.backend.prefetch(Self::byte_index(index), N);
+    }
+
+    /// Hints that all entries within `range` will be read soon.
+    #[inline]
+    pub fn prefetch_range(&self, range: Range<usize>) {
+        let len = range.end.saturating_sub(range.start);
+        self.backend.prefetch(Self::byte_index(range.start), len * N);
+    }
+
     /// Gets an entry at `index` as bytes.
     #[inline]
     pub fn get_raw(&self, index: usize) -> Result<&[u8]> {
@@ -82,6 +99,7 @@ where
     #[inline]
     pub fn get_raw_mut(&mut self, index: usize) -> Result<&mut [u8]> {
         let index = Self::byte_index(index);
+        self.record(index..index + N);
         self.backend.get_mut(index, N)
     }
 
@@ -93,6 +111,25 @@ where
         }
 
         let index = Self::byte_index(index);
+        self.record(index..index + data.len());
+        self.backend.replace_same_len(index, data)?;
+        Ok(())
+    }
+
+    /// Overwrites consecutive elements starting at `index` with the raw bytes in `data`.
+    pub fn set_raw_n(&mut self, index: usize, data: &[u8]) -> Result<()> {
+        let count = data.len() / N;
+        if count * N != data.len() {
+            return Err(Error::UnexpectedValue);
+        }
+
+        if count == 0 {
+            return Ok(());
+        }
+        self.check_oob(index + count - 1)?;
+
+        let index = Self::byte_index(index);
+        self.record(index..index + data.len());
         self.backend.replace_same_len(index, data)?;
         Ok(())
     }
@@ -119,6 +156,7 @@ where
     pub fn remove(&mut self, index: usize) -> Result<()> {
         self.check_oob(index)?;
         let byte_offset = Self::byte_index(index);
+        self.record(byte_offset..Self::byte_index(self.len));
         self.backend.replace(byte_offset, N, &[])?;
         self.len -= 1;
         Ok(())
@@ -149,6 +187,7 @@ where
         };
 
         let range = start..end;
+        self.record(range.clone());
         self.backend.fill(range, val)?;
         Ok(())
     }
@@ -165,6 +204,14 @@ where
         Ok(())
     }
 
+    /// Records the bytes within `range` before they get written, so a failing batch can restore them. Bytes after the
+    /// last item don't need to be recorded as the length gets restored as well.
+    #[inline]
+    fn record(&mut self, range: Range<usize>) {
+        let start = self.backend.get_index(range.start);
+        self.undo.record(&self.backend, start..start + range.len());
+    }
+
     pub(crate) fn set_len(&mut self, len: usize) -> Result<()> {
         let blen = Self::byte_index(len);
         if blen > self.backend.capacity() {
@@ -241,6 +288,8 @@ where
             return Err(Error::UnexpectedValue);
         }
 
+        self.auto_shrink()?;
+
         // Grow when necessary.
         if self.free() == 0 {
             self.grow()?;
@@ -253,6 +302,8 @@ where
 
     /// Inserts data at the given `index` in the list.
     pub fn insert_raw(&mut self, index: usize, data: &[u8]) -> Result<()> {
+        self.auto_shrink()?;
+
         if self.free() == 0 {
             self.grow_for(1)?;
         }
@@ -262,6 +313,7 @@ where
         }
 
         let byte_offset = Self::byte_index(index);
+        self.record(byte_offset..Self::byte_index(self.len));
         self.backend.replace(byte_offset, 0, data)?;
         self.len += 1;
         Ok(())
@@ -272,7 +324,7 @@ impl<B, T, const N: usize> List<B, T, N>
 where
     B: GrowableBackend,
 {
-    /// Grows the lists backend for more items. This means growing to twice the size of the current capacity.
+    /// Grows the lists backend for more items. How much it grows is decided by the backends `GrowthPolicy`.
     #[inline]
     pub fn grow(&mut self) -> Result<()> {
         // Make sure to actually grow the list for at least one element as self.len() can be 0.
@@ -295,14 +347,77 @@ where
             return Ok(());
         }
 
-        // List needs to hold `items` additional items
-        let need_hold = items + self.len();
-        let pow = smallest_two_power_for(need_hold);
-        self.backend.grow_to(Self::byte_index(2usize.pow(pow)))?;
+        // List needs to hold `items` additional items but always grows for at least two.
+        let required = Self::byte_index((items + self.len()).max(2));
+        let capacity = self.backend.capacity();
+        if required <= capacity {
+            return Ok(());
+        }
+
+        let new_capacity = self.backend.growth_policy().next_capacity(capacity, required);
+        self.backend.grow_to(new_capacity)?;
+        Ok(())
+    }
+
+    /// Shrinks the lists backend so it doesn't hold any free space anymore.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) -> Result<()> {
+        self.backend.shrink_to_fit()
+    }
+}
+
+impl<B, T, const N: usize> List<B, T, N>
+where
+    B: GrowableBackend,
+{
+    /// Shrinks the list if required by its shrink policy.
+    fn auto_shrink(&mut self) -> Result<()> {
+        let (free, capacity) = (self.free(), self.capacity());
+        if let Some(tracker) = self.shrink.as_mut() {
+            if tracker.record(free, capacity) {
+                self.shrink_to_fit()?;
+            }
+        }
         Ok(())
     }
 }
 
+impl<B, T, const N: usize> AutoShrink for List<B, T, N> {
+    #[inline]
+    fn set_shrink_policy(&mut self, policy: Option<ShrinkPolicy>) {
+        self.shrink = policy.map(ShrinkTracker::new);
+    }
+
+    #[inline]
+    fn shrink_policy(&self) -> Option<ShrinkPolicy> {
+        self.shrink.map(|i| i.policy())
+    }
+}
+
+impl<B, T, const N: usize> Batch for List<B, T, N>
+where
+    B: GrowableBackend,
+{
+    fn batch<F, R>(&mut self, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut Self) -> Result<R>,
+    {
+        self.undo.begin(&self.backend);
+        match f(self) {
+            Ok(res) => {
+                self.undo.commit();
+                self.flush()?;
+                Ok(res)
+            }
+            Err(err) => {
+                self.undo.rollback(&mut self.backend)?;
+                self.len = self.backend.len() / N;
+                Err(err)
+            }
+        }
+    }
+}
+
 impl<B, T, const N: usize> Creatable<B> for List<B, T, N>
 where
     B: GrowableBackend,
@@ -314,6 +429,8 @@ where
         Ok(Self {
             len: 0,
             backend,
+            shrink: None,
+            undo: UndoLog::default(),
             _p1: PhantomData,
         })
     }
@@ -334,6 +451,8 @@ where
         Ok(Self {
             len,
             backend,
+            shrink: None,
+            undo: UndoLog::default(),
             _p1: PhantomData,
         })
     }
@@ -372,6 +491,7 @@ where
 }
 
 #[cfg(test)]
+#[cfg(feature = "mmap")]
 mod test {
     use super::*;
     use crate::backend::memory::test::make_mem_backend;
@@ -401,12 +521,16 @@ mod test {
         grow(&mut backend);
         insert_at(&mut backend);
         mem_set(&mut backend);
+        auto_shrink(&mut backend);
+        batch(&mut backend);
 
         let mut backend = make_mmap_backend("list_test", 1);
         insert_get(&mut backend);
         grow(&mut backend);
         insert_at(&mut backend);
         mem_set(&mut backend);
+        auto_shrink(&mut backend);
+        batch(&mut backend);
     }
 
     fn insert_get<B: GrowableBackend>(backend: &mut B) {
@@ -499,4 +623,56 @@ mod test {
             assert_eq!(list.capacity(), cap);
         }
     }
+
+    fn auto_shrink<B: GrowableBackend>(backend: &mut B) {
+        backend.clear();
+        let mut list = ListU32::create(backend).unwrap();
+        list.grow_for_exact(1000).unwrap();
+        list.set_shrink_policy(Some(ShrinkPolicy::new(0.5, 3)));
+        assert_eq!(list.shrink_policy(), Some(ShrinkPolicy::new(0.5, 3)));
+
+        let capacity = list.capacity();
+        assert!(capacity >= 1000);
+        list.push(&1).unwrap();
+        list.push(&2).unwrap();
+        assert_eq!(list.capacity(), capacity);
+
+        // The third operation with too much free space shrinks the list before pushing.
+        list.push(&3).unwrap();
+        assert_eq!(list.capacity(), 4);
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+
+        list.set_shrink_policy(None);
+        list.grow_for_exact(1000).unwrap();
+        for i in 0..10 {
+            list.push(&i).unwrap();
+        }
+        assert!(list.capacity() >= 1000);
+    }
+
+    fn batch<B: GrowableBackend>(backend: &mut B) {
+        backend.clear();
+        let mut list = ListU32::create(backend).unwrap();
+        list.push(&1).unwrap();
+
+        list.batch(|list| {
+            list.push(&2)?;
+            list.set(0, &5)
+        })
+        .unwrap();
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![5, 2]);
+
+        let capacity = list.capacity();
+        let res = list.batch(|list| {
+            list.set(1, &7)?;
+            list.extend(0..1000);
+            list.remove(5000)
+        });
+        assert_eq!(res, Err(Error::OutOfBounds));
+        assert_eq!(list.capacity(), capacity);
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![5, 2]);
+
+        list.push(&3).unwrap();
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![5, 2, 3]);
+    }
 }
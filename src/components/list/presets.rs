@@ -7,6 +7,8 @@ pub type ListI16<B> = List<B, i16, 2>;
 pub type ListU32<B> = List<B, u32, 4>;
 pub type ListI32<B> = List<B, i32, 4>;
 pub type ListU32Opt<B> = List<B, Option<u32>, 5>;
+pub type ListU64<B> = List<B, u64, 8>;
+pub type ListI64<B> = List<B, i64, 8>;
 pub type ListUsize<B> = List<B, usize, 8>;
 pub type ListIsize<B> = List<B, isize, 8>;
 pub type ListF32<B> = List<B, f32, 4>;
@@ -0,0 +1,39 @@
+use crate::backend::Backend;
+use crate::components::lru::{LruCache, NONE};
+use crate::components::map::hashing;
+use crate::traits::deser::Deser;
+use serde::de::DeserializeOwned;
+
+/// Iterator over the items of an `LruCache`, from the most to the least recently used one.
+pub struct LruIter<'a, B, K, V> {
+    cache: &'a LruCache<B, K, V>,
+    slot: u32,
+}
+
+impl<'a, B, K, V> LruIter<'a, B, K, V> {
+    #[inline]
+    pub(super) fn new(cache: &'a LruCache<B, K, V>) -> Self {
+        Self {
+            cache,
+            slot: cache.head(),
+        }
+    }
+}
+
+impl<B, K, V> Iterator for LruIter<'_, B, K, V>
+    where
+        B: Backend,
+        K: hashing::Hash + Eq + Deser,
+        V: DeserializeOwned,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.slot == NONE {
+            return None;
+        }
+        let item = self.cache.item(self.slot).unwrap();
+        self.slot = self.cache.next_slot(self.slot).unwrap();
+        Some(item)
+    }
+}
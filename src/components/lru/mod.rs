@@ -0,0 +1,485 @@
+pub mod iter;
+
+use crate::backend::base::sub::GeneralSubBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::lru::iter::LruIter;
+use crate::components::map::hashing;
+use crate::components::map::FMap;
+use crate::components::multi_file::entry_mut::MFileEntryMut;
+use crate::components::multi_file::MultiFile;
+use crate::deser::{deserialize_impl, serialize_impl};
+use crate::traits::creatable::Creatable;
+use crate::traits::deser::Deser;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::{Error, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+/// ID of the entry holding the caches metadata.
+const METADATA_ID: usize = 0;
+
+/// ID of the entry holding the map of keys to their slot.
+const MAP_ID: usize = 1;
+
+/// ID of the entry holding the first slot.
+const SLOTS_START: usize = 2;
+
+/// Marks the end of the list.
+const NONE: u32 = u32::MAX;
+
+/// Length of the links at the start of each slot: present flag, previous and next slot.
+const LINKS_LEN: usize = 9;
+
+#[derive(Serialize, Deserialize)]
+struct LruMetadata {
+    capacity: u64,
+    len: u64,
+
+    /// Slot of the most recently used item.
+    head: u32,
+    /// Slot of the least recently used item.
+    tail: u32,
+}
+
+/// Links of a slot in the recency list.
+#[derive(Clone, Copy)]
+struct Links {
+    present: bool,
+    prev: u32,
+    next: u32,
+}
+
+/// A cache holding up to `capacity` items. Inserting into a full cache evicts the least recently used item.
+///
+/// Each key gets a fixed slot on its first insertion, which is stored in an `FMap`. The slots hold the items along with
+/// the links of a doubly linked list ordered by recency, so reading or writing an item moves it to the front of the list
+/// without touching other items. Slots of evicted keys are kept until the amount of slots exceeds twice the capacity, in
+/// which case the cache gets rebuilt with only the cached items.
+///
+/// The internal format: MultiFile(METADATA | FMap<K, SLOT> | SLOT | SLOT | ...)
+/// where each slot is (PRESENT (1 byte) | PREV (4 bytes) | NEXT (4 bytes) | (K, V))
+pub struct LruCache<B, K, V> {
+    storage: MultiFile<B>,
+    capacity: usize,
+    len: usize,
+    head: u32,
+    tail: u32,
+    p: PhantomData<(K, V)>,
+}
+
+impl<B, K, V> LruCache<B, K, V> {
+    /// Returns the amount of cached items.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the cache is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the maximum amount of cached items.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    #[inline]
+    pub(crate) fn head(&self) -> u32 {
+        self.head
+    }
+}
+
+impl<B, K, V> LruCache<B, K, V>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn map(&self) -> FMap<GeneralSubBackend<'_>, K, u32> {
+        self.storage.get_backend(MAP_ID).unwrap()
+    }
+
+    fn links(&self, slot: u32) -> Result<Links> {
+        let entry = self.storage.get(SLOTS_START + slot as usize).ok_or(Error::OutOfBounds)?;
+        let data = entry.get(0, LINKS_LEN)?;
+        Ok(Links {
+            present: data[0] != 0,
+            prev: u32::from_le_bytes(data[1..5].try_into().unwrap()),
+            next: u32::from_le_bytes(data[5..9].try_into().unwrap()),
+        })
+    }
+}
+
+impl<B, K, V> LruCache<B, K, V>
+    where
+        B: Backend,
+        K: hashing::Hash + Eq + Deser,
+{
+    /// Returns the slot of `k`, if it has one.
+    #[inline]
+    fn slot(&self, k: &K) -> Option<u32> {
+        self.map().get(k)
+    }
+}
+
+impl<B, K, V> LruCache<B, K, V>
+    where
+        B: Backend,
+        K: hashing::Hash + Eq + Deser,
+        V: DeserializeOwned,
+{
+    /// Returns the value of `k` without marking it as recently used.
+    pub fn peek(&self, k: &K) -> Option<V> {
+        let slot = self.slot(k)?;
+        if !self.links(slot).ok()?.present {
+            return None;
+        }
+        self.item(slot).ok().map(|i| i.1)
+    }
+
+    /// Returns `true` if `k` is cached.
+    #[inline]
+    pub fn contains_key(&self, k: &K) -> bool {
+        self.slot(k).is_some_and(|i| self.links(i).is_ok_and(|i| i.present))
+    }
+
+    /// Returns an iterator over all cached items, from the most to the least recently used one.
+    #[inline]
+    pub fn iter(&self) -> LruIter<'_, B, K, V> {
+        LruIter::new(self)
+    }
+
+    pub(crate) fn item(&self, slot: u32) -> Result<(K, V)> {
+        let entry = self.storage.get(SLOTS_START + slot as usize).ok_or(Error::OutOfBounds)?;
+        deserialize_impl(&entry.content_data()[LINKS_LEN..])
+    }
+
+    #[inline]
+    pub(crate) fn next_slot(&self, slot: u32) -> Result<u32> {
+        Ok(self.links(slot)?.next)
+    }
+}
+
+impl<B, K, V> LruCache<B, K, V>
+    where
+        B: GrowableBackend,
+        K: hashing::Hash + Eq + Deser,
+        V: Deser,
+{
+    /// Creates a new cache holding up to `capacity` items.
+    pub fn new(backend: B, capacity: usize) -> Result<Self> {
+        if capacity == 0 {
+            return Err(Error::UnexpectedValue);
+        }
+
+        let mut cache = Self {
+            storage: MultiFile::with_capacity(backend, 2)?,
+            capacity,
+            len: 0,
+            head: NONE,
+            tail: NONE,
+            p: PhantomData,
+        };
+        cache.init_storage()?;
+        Ok(cache)
+    }
+
+    /// Returns the value of `k` and marks it as the most recently used item.
+    pub fn get(&mut self, k: &K) -> Result<Option<V>> {
+        let Some(slot) = self.slot(k) else {
+            return Ok(None);
+        };
+        if !self.links(slot)?.present {
+            return Ok(None);
+        }
+
+        self.unlink(slot)?;
+        self.push_front(slot)?;
+        self.write_metadata()?;
+        Ok(Some(self.item(slot)?.1))
+    }
+
+    /// Inserts or updates an item and marks it as the most recently used one. If the cache is full, the least
+    /// recently used item gets evicted and returned.
+    pub fn insert(&mut self, k: &K, v: &V) -> Result<Option<(K, V)>> {
+        let slot = match self.slot(k) {
+            Some(slot) => slot,
+            None => {
+                let mut map = self.map_mut();
+                let slot = map.len() as u32;
+                map.insert(k, &slot)?;
+                self.storage.insert_empty()?;
+                slot
+            }
+        };
+
+        let links = self.links_or_empty(slot)?;
+        if links.present {
+            self.unlink(slot)?;
+        } else {
+            self.len += 1;
+        }
+
+        let mut data = vec![0; LINKS_LEN];
+        data.extend(serialize_impl(&(k, v))?);
+        self.storage.set_entry(SLOTS_START + slot as usize, &data)?;
+        self.push_front(slot)?;
+
+        let mut evicted = None;
+        if self.len > self.capacity {
+            let tail = self.tail;
+            evicted = Some(self.item(tail)?);
+            self.evict(tail)?;
+        }
+        self.write_metadata()?;
+
+        if self.map().len() > self.capacity * 2 {
+            self.rebuild()?;
+        }
+
+        Ok(evicted)
+    }
+
+    /// Removes `k` from the cache and returns its value.
+    pub fn remove(&mut self, k: &K) -> Result<Option<V>> {
+        let Some(slot) = self.slot(k) else {
+            return Ok(None);
+        };
+        if !self.links(slot)?.present {
+            return Ok(None);
+        }
+
+        let (_, v) = self.item(slot)?;
+        self.evict(slot)?;
+        self.write_metadata()?;
+        Ok(Some(v))
+    }
+
+    /// Removes all items.
+    pub fn clear(&mut self) -> Result<()> {
+        self.storage.clear();
+        self.len = 0;
+        self.head = NONE;
+        self.tail = NONE;
+        self.init_storage()
+    }
+
+    /// Flushes the cache to the backend.
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.storage.flush()
+    }
+
+    /// Inserts the metadata and an empty map into the empty storage.
+    fn init_storage(&mut self) -> Result<()> {
+        self.storage.insert_empty()?;
+        self.write_metadata()?;
+        FMap::<_, K, u32>::with_capacity(self.storage.insert_empty()?, self.capacity)?;
+        Ok(())
+    }
+
+    /// Rebuilds the cache with only the cached items to drop the slots of evicted keys.
+    fn rebuild(&mut self) -> Result<()> {
+        let items: Vec<(K, V)> = self.iter().collect();
+        self.clear()?;
+        for (k, v) in items.iter().rev() {
+            self.insert(k, v)?;
+        }
+        Ok(())
+    }
+
+    /// Removes the item of `slot` from the list and drops its data.
+    fn evict(&mut self, slot: u32) -> Result<()> {
+        self.unlink(slot)?;
+        self.storage.set_entry(SLOTS_START + slot as usize, &[0; LINKS_LEN])?;
+        self.len -= 1;
+        Ok(())
+    }
+
+    /// Returns the links of `slot` or empty links if the slot was just created.
+    fn links_or_empty(&self, slot: u32) -> Result<Links> {
+        let entry = self.storage.get(SLOTS_START + slot as usize).ok_or(Error::OutOfBounds)?;
+        if entry.is_empty() {
+            return Ok(Links {
+                present: false,
+                prev: NONE,
+                next: NONE,
+            });
+        }
+        self.links(slot)
+    }
+
+    /// Removes `slot` from the recency list.
+    fn unlink(&mut self, slot: u32) -> Result<()> {
+        let links = self.links(slot)?;
+        match links.prev {
+            NONE => self.head = links.next,
+            prev => self.set_links(prev, None, Some(links.next))?,
+        }
+        match links.next {
+            NONE => self.tail = links.prev,
+            next => self.set_links(next, Some(links.prev), None)?,
+        }
+        Ok(())
+    }
+
+    /// Inserts `slot` at the front of the recency list.
+    fn push_front(&mut self, slot: u32) -> Result<()> {
+        let mut entry = self.storage.entry_mut(SLOTS_START + slot as usize).ok_or(Error::OutOfBounds)?;
+        entry.replace_same_len(0, &[1])?;
+        self.set_links(slot, Some(NONE), Some(self.head))?;
+
+        match self.head {
+            NONE => self.tail = slot,
+            head => self.set_links(head, Some(slot), None)?,
+        }
+        self.head = slot;
+        Ok(())
+    }
+
+    fn set_links(&mut self, slot: u32, prev: Option<u32>, next: Option<u32>) -> Result<()> {
+        let mut entry = self.storage.entry_mut(SLOTS_START + slot as usize).ok_or(Error::OutOfBounds)?;
+        if let Some(prev) = prev {
+            entry.replace_same_len(1, &prev.to_le_bytes())?;
+        }
+        if let Some(next) = next {
+            entry.replace_same_len(5, &next.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn map_mut(&mut self) -> FMap<MFileEntryMut<'_, B>, K, u32> {
+        self.storage.get_backend_mut(MAP_ID).unwrap()
+    }
+
+    fn write_metadata(&mut self) -> Result<()> {
+        let metadata = LruMetadata {
+            capacity: self.capacity as u64,
+            len: self.len as u64,
+            head: self.head,
+            tail: self.tail,
+        };
+        self.storage.set_entry(METADATA_ID, &serialize_impl(&metadata)?)
+    }
+}
+
+impl<B, K, V> Initiable<B> for LruCache<B, K, V>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let storage = MultiFile::init(backend)?;
+        let metadata: LruMetadata = storage
+            .get(METADATA_ID)
+            .ok_or(Error::Initialization)
+            .and_then(|i| deserialize_impl(i.content_data()))?;
+
+        if metadata.capacity == 0 || !storage.has_id(MAP_ID) {
+            return Err(Error::Initialization);
+        }
+
+        Ok(Self {
+            storage,
+            capacity: metadata.capacity as usize,
+            len: metadata.len as usize,
+            head: metadata.head,
+            tail: metadata.tail,
+            p: PhantomData,
+        })
+    }
+}
+
+impl<B, K, V> MType for LruCache<B, K, V>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.storage.raw_data()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_eviction(&mut mem_backend);
+        test_rebuild(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./lru", 10);
+        test_eviction(&mut mmap_backend);
+        test_rebuild(&mut mmap_backend);
+    }
+
+    fn test_eviction<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        assert!(LruCache::<_, u32, String>::new(&mut *backend, 0).is_err());
+        backend.clear();
+        {
+            let mut cache: LruCache<_, u32, String> = LruCache::new(&mut backend, 3).unwrap();
+            assert_eq!(cache.get(&1), Ok(None));
+            for i in 0..3 {
+                assert_eq!(cache.insert(&i, &i.to_string()), Ok(None));
+            }
+            assert_eq!(cache.get(&0), Ok(Some("0".to_string())));
+            assert_eq!(cache.insert(&3, &"3".to_string()), Ok(Some((1, "1".to_string()))));
+            assert!(!cache.contains_key(&1));
+            assert_eq!(cache.len(), 3);
+        }
+
+        let mut cache: LruCache<_, u32, String> = LruCache::init(&mut backend).unwrap();
+        assert_eq!(cache.capacity(), 3);
+        let keys: Vec<_> = cache.iter().map(|i| i.0).collect();
+        assert_eq!(keys, vec![3, 0, 2]);
+
+        // Peeking doesn't change the order but updating does.
+        assert_eq!(cache.peek(&2), Some("2".to_string()));
+        assert_eq!(cache.insert(&2, &"two".to_string()), Ok(None));
+        assert_eq!(cache.insert(&1, &"1".to_string()), Ok(Some((0, "0".to_string()))));
+        let items: Vec<_> = cache.iter().collect();
+        assert_eq!(items, vec![(1, "1".to_string()), (2, "two".to_string()), (3, "3".to_string())]);
+
+        assert_eq!(cache.remove(&2), Ok(Some("two".to_string())));
+        assert_eq!(cache.remove(&2), Ok(None));
+        assert_eq!(cache.peek(&2), None);
+        assert_eq!(cache.iter().map(|i| i.0).collect::<Vec<_>>(), vec![1, 3]);
+        assert_eq!(cache.insert(&4, &"4".to_string()), Ok(None));
+        assert_eq!(cache.iter().map(|i| i.0).collect::<Vec<_>>(), vec![4, 1, 3]);
+
+        cache.clear().unwrap();
+        assert!(cache.is_empty());
+        assert_eq!(cache.iter().next(), None);
+    }
+
+    fn test_rebuild<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut cache: LruCache<_, u64, Vec<u64>> = LruCache::new(&mut backend, 50).unwrap();
+        for i in 0..1000u64 {
+            cache.insert(&i, &vec![i; (i % 7) as usize]).unwrap();
+            if i % 3 == 0 {
+                cache.get(&(i / 2)).unwrap();
+            }
+        }
+
+        assert_eq!(cache.len(), 50);
+        assert!(cache.map().len() <= 100);
+        let keys: Vec<_> = cache.iter().map(|i| i.0).collect();
+        assert_eq!(keys.len(), 50);
+        assert_eq!(keys[0], 999);
+        for (k, v) in cache.iter() {
+            assert_eq!(v, vec![k; (k % 7) as usize]);
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use crate::backend::growable::GrowableBackend;
+use crate::components::map::hashing::hashfn::HashFn;
+use crate::components::map::hashing::Hash;
+use crate::components::map::kvpair::KVPair;
+use crate::components::map::FMap;
+use crate::traits::deser::Deser;
+use crate::{Error, Result};
+
+/// A view into a single key of an `FMap`, which is either occupied or vacant. Created by `FMap::entry`.
+pub enum Entry<'a, B, K, V, H> {
+    Occupied(OccupiedEntry<'a, B, K, V, H>),
+    Vacant(VacantEntry<'a, B, K, V, H>),
+}
+
+/// An entry of a key that is stored in the map.
+pub struct OccupiedEntry<'a, B, K, V, H> {
+    map: &'a mut FMap<B, K, V, H>,
+    key: &'a K,
+    kv_id: u32,
+    pos: usize,
+}
+
+/// An entry of a key that isn't stored in the map.
+pub struct VacantEntry<'a, B, K, V, H> {
+    map: &'a mut FMap<B, K, V, H>,
+    key: &'a K,
+    key_hash: u64,
+    pos: usize,
+    reuse: bool,
+}
+
+impl<'a, B, K, V, H> Entry<'a, B, K, V, H>
+    where
+        H: HashFn,
+        B: GrowableBackend,
+        K: Hash + Eq + Deser,
+        V: Deser,
+{
+    /// Returns the key of the entry.
+    #[inline]
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(e) => e.key(),
+            Entry::Vacant(e) => e.key(),
+        }
+    }
+
+    /// Returns the value of the entry, inserting `value` if the entry is vacant.
+    #[inline]
+    pub fn or_insert(self, value: V) -> Result<V> {
+        self.or_insert_with(|| value)
+    }
+
+    /// Returns the value of the entry, inserting the value returned by `f` if the entry is vacant.
+    pub fn or_insert_with<F>(self, f: F) -> Result<V>
+        where
+            F: FnOnce() -> V,
+    {
+        match self {
+            Entry::Occupied(e) => e.get(),
+            Entry::Vacant(e) => {
+                let value = f();
+                e.insert(&value)?;
+                Ok(value)
+            }
+        }
+    }
+
+    /// Modifies the value of an occupied entry in place using `f`. Vacant entries are left untouched.
+    pub fn and_modify<F>(mut self, f: F) -> Result<Self>
+        where
+            F: FnOnce(&mut V),
+    {
+        if let Entry::Occupied(e) = &mut self {
+            e.map.modify_entry(e.kv_id, f)?;
+        }
+        Ok(self)
+    }
+}
+
+impl<'a, B, K, V, H> OccupiedEntry<'a, B, K, V, H>
+    where
+        H: HashFn,
+        B: GrowableBackend,
+        K: Hash + Eq + Deser,
+        V: Deser,
+{
+    #[inline]
+    pub(super) fn new(map: &'a mut FMap<B, K, V, H>, key: &'a K, kv_id: u32, pos: usize) -> Self {
+        Self { map, key, kv_id, pos }
+    }
+
+    /// Returns the key of the entry.
+    #[inline]
+    pub fn key(&self) -> &K {
+        self.key
+    }
+
+    /// Returns the unique ID of the entries KV pair.
+    #[inline]
+    pub fn kv_id(&self) -> u32 {
+        self.kv_id
+    }
+
+    /// Returns the value of the entry.
+    #[inline]
+    pub fn get(&self) -> Result<V> {
+        FMap::<B, K, V, H>::entry_by_id(self.kv_id, &self.map.entry_storage())
+            .map(|i| i.into_value())
+            .ok_or(Error::UnexpectedValue)
+    }
+
+    /// Replaces the value of the entry, returning the old one.
+    pub fn insert(&mut self, value: &V) -> Result<V> {
+        let old = self.get()?;
+        self.map.set_entry(self.kv_id, &KVPair::new(self.key, value))?;
+        Ok(old)
+    }
+
+    /// Removes the entry from the map, returning its value.
+    #[inline]
+    pub fn remove(self) -> Result<V> {
+        self.map.remove_at(self.kv_id, self.pos)
+    }
+}
+
+impl<'a, B, K, V, H> VacantEntry<'a, B, K, V, H>
+    where
+        H: HashFn,
+        B: GrowableBackend,
+        K: Hash + Eq + Deser,
+        V: Deser,
+{
+    #[inline]
+    pub(super) fn new(map: &'a mut FMap<B, K, V, H>, key: &'a K, key_hash: u64, pos: usize, reuse: bool) -> Self {
+        Self { map, key, key_hash, pos, reuse }
+    }
+
+    /// Returns the key of the entry.
+    #[inline]
+    pub fn key(&self) -> &K {
+        self.key
+    }
+
+    /// Inserts `value` for the entries key, returning the unique ID of the new KV pair.
+    pub fn insert(self, value: &V) -> Result<u32> {
+        let (kv_id, _) = self.map.insert_vacant(self.key, value, self.key_hash, self.pos, self.reuse)?;
+        self.map.inc_len(1)?;
+        Ok(kv_id)
+    }
+}
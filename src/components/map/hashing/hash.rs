@@ -1,7 +1,17 @@
 use crate::traits::sized_deser::SizedDeser;
+use std::hash::Hasher;
 
 pub trait Hash {
+    /// Returns the FNV hash of the value.
     fn hash(&self) -> u64;
+
+    /// Feeds the value into the given hasher. Used for seeded hashing. By default the result of `hash` gets fed, which
+    /// keeps values colliding in `hash` colliding for every seed. Types hashing more than 64 bits should feed their
+    /// data instead.
+    #[inline]
+    fn hash_into<S: Hasher>(&self, state: &mut S) {
+        state.write_u64(self.hash());
+    }
 }
 
 impl<'a, const N: usize> Hash for &'a [char; N] {
@@ -9,6 +19,11 @@ impl<'a, const N: usize> Hash for &'a [char; N] {
     fn hash(&self) -> u64 {
         (&self[..]).hash()
     }
+
+    #[inline]
+    fn hash_into<S: Hasher>(&self, state: &mut S) {
+        (&self[..]).hash_into(state)
+    }
 }
 
 impl<const N: usize> Hash for [char; N] {
@@ -16,6 +31,11 @@ impl<const N: usize> Hash for [char; N] {
     fn hash(&self) -> u64 {
         (&self[..]).hash()
     }
+
+    #[inline]
+    fn hash_into<S: Hasher>(&self, state: &mut S) {
+        (&self[..]).hash_into(state)
+    }
 }
 
 impl Hash for &[char] {
@@ -23,6 +43,13 @@ impl Hash for &[char] {
     fn hash(&self) -> u64 {
         fnv_hash_it(self.iter().flat_map(|i| i.to_bytes()))
     }
+
+    #[inline]
+    fn hash_into<S: Hasher>(&self, state: &mut S) {
+        for c in self.iter() {
+            state.write(&c.to_bytes());
+        }
+    }
 }
 
 impl Hash for &[u8] {
@@ -30,6 +57,23 @@ impl Hash for &[u8] {
     fn hash(&self) -> u64 {
         fnv_hash(self)
     }
+
+    #[inline]
+    fn hash_into<S: Hasher>(&self, state: &mut S) {
+        state.write(self);
+    }
+}
+
+impl Hash for [u8] {
+    #[inline]
+    fn hash(&self) -> u64 {
+        fnv_hash(self)
+    }
+
+    #[inline]
+    fn hash_into<S: Hasher>(&self, state: &mut S) {
+        state.write(self);
+    }
 }
 
 impl Hash for Vec<u8> {
@@ -37,6 +81,11 @@ impl Hash for Vec<u8> {
     fn hash(&self) -> u64 {
         fnv_hash(self)
     }
+
+    #[inline]
+    fn hash_into<S: Hasher>(&self, state: &mut S) {
+        state.write(self);
+    }
 }
 
 impl Hash for String {
@@ -44,6 +93,11 @@ impl Hash for String {
     fn hash(&self) -> u64 {
         fnv_hash(self.as_bytes())
     }
+
+    #[inline]
+    fn hash_into<S: Hasher>(&self, state: &mut S) {
+        state.write(self.as_bytes());
+    }
 }
 
 impl Hash for &String {
@@ -51,6 +105,23 @@ impl Hash for &String {
     fn hash(&self) -> u64 {
         fnv_hash(self.as_bytes())
     }
+
+    #[inline]
+    fn hash_into<S: Hasher>(&self, state: &mut S) {
+        state.write(self.as_bytes());
+    }
+}
+
+impl Hash for str {
+    #[inline]
+    fn hash(&self) -> u64 {
+        fnv_hash(self.as_bytes())
+    }
+
+    #[inline]
+    fn hash_into<S: Hasher>(&self, state: &mut S) {
+        state.write(self.as_bytes());
+    }
 }
 
 impl Hash for &str {
@@ -58,6 +129,11 @@ impl Hash for &str {
     fn hash(&self) -> u64 {
         fnv_hash(self.as_bytes())
     }
+
+    #[inline]
+    fn hash_into<S: Hasher>(&self, state: &mut S) {
+        state.write(self.as_bytes());
+    }
 }
 
 impl Hash for u8 {
@@ -88,6 +164,18 @@ impl Hash for u64 {
     }
 }
 
+impl Hash for u128 {
+    #[inline]
+    fn hash(&self) -> u64 {
+        fnv_hash(&self.to_le_bytes())
+    }
+
+    #[inline]
+    fn hash_into<S: Hasher>(&self, state: &mut S) {
+        state.write_u128(*self);
+    }
+}
+
 impl Hash for char {
     #[inline]
     fn hash(&self) -> u64 {
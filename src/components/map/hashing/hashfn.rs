@@ -1,7 +1,44 @@
 use std::marker::PhantomData;
 
 pub trait HashFn {
+    /// Whether the hash function is chosen at runtime using a `ProbeStrategy`.
+    const DYNAMIC: bool = false;
+
+    /// `distance` of hash functions placing entries using Robin Hood hashing or `None` for regular placement. Hash
+    /// functions implementing `RobinHood` enable it by setting this to `Some(Self::distance)`.
+    const ROBIN_HOOD: Option<fn(u64, usize, usize) -> usize> = None;
+
     fn f(hash: u64, pos: usize, max: usize) -> u64;
+
+    /// Hash function that can be overwritten by hash functions that support choosing the strategy at runtime.
+    #[inline]
+    fn f_dyn(_strategy: Option<ProbeStrategy>, hash: u64, pos: usize, max: usize) -> u64 {
+        Self::f(hash, pos, max)
+    }
+
+    /// Returns the slot probed at step `pos` of a sequence that visits every slot of the table within `probe_len(max)`
+    /// steps. Functions like quadratic probing don't cover the whole table within their first `max` steps, so the
+    /// sequence continues with a linear scan afterwards.
+    #[inline]
+    fn probe(strategy: Option<ProbeStrategy>, hash: u64, pos: usize, max: usize) -> u64 {
+        if pos < max {
+            return Self::f_dyn(strategy, hash, pos, max);
+        }
+        LinearProbing::f(hash, pos - max, max)
+    }
+
+    /// Returns the amount of steps of a sequence produced by `probe`.
+    #[inline]
+    fn probe_len(max: usize) -> usize {
+        max * 2
+    }
+}
+
+/// Hash functions placing entries using Robin Hood hashing.
+pub trait RobinHood: HashFn {
+    /// Returns the probe position an entry with the given `hash` has been placed at when stored in `slot`, so the
+    /// inverse of `f`.
+    fn distance(hash: u64, slot: usize, max: usize) -> usize;
 }
 
 pub struct LinearProbing;
@@ -26,6 +63,27 @@ impl HashFn for PALinearProbing {
     }
 }
 
+/// Linear probing with Robin Hood placement. Inserting a key displaces entries being closer to their home slot than
+/// the key itself, which keeps the probe lengths of all entries close to the average even under high load.
+pub struct RobinHoodProbing;
+
+impl HashFn for RobinHoodProbing {
+    const ROBIN_HOOD: Option<fn(u64, usize, usize) -> usize> = Some(Self::distance);
+
+    #[inline]
+    fn f(hash: u64, pos: usize, max: usize) -> u64 {
+        LinearProbing::f(hash, pos, max)
+    }
+}
+
+impl RobinHood for RobinHoodProbing {
+    #[inline]
+    fn distance(hash: u64, slot: usize, max: usize) -> usize {
+        let home = (hash % max as u64) as usize;
+        (slot + max - home) % max
+    }
+}
+
 pub struct QuadraticProbing;
 
 impl HashFn for QuadraticProbing {
@@ -59,3 +117,145 @@ impl<F, S> HashFn for DoubleHashing<F, S>
         (first + second) % max
     }
 }
+
+/// Probing that uses the `ProbeStrategy` stored in a maps metadata and falls back to the default
+/// (`DoubleHashing<QuadraticProbing, LinearProbing>`) if there is none.
+pub struct DynamicProbing;
+
+impl HashFn for DynamicProbing {
+    const DYNAMIC: bool = true;
+
+    #[inline]
+    fn f(hash: u64, pos: usize, max: usize) -> u64 {
+        DoubleHashing::<QuadraticProbing, LinearProbing>::f(hash, pos, max)
+    }
+
+    #[inline]
+    fn f_dyn(strategy: Option<ProbeStrategy>, hash: u64, pos: usize, max: usize) -> u64 {
+        match strategy {
+            Some(strategy) => strategy.f(hash, pos, max),
+            None => Self::f(hash, pos, max),
+        }
+    }
+}
+
+/// A single probing function that can be used within a `ProbeStrategy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Probe {
+    Linear,
+    Quadratic,
+}
+
+impl Probe {
+    #[inline]
+    pub fn f(&self, hash: u64, pos: usize, max: usize) -> u64 {
+        match self {
+            Probe::Linear => LinearProbing::f(hash, pos, max),
+            Probe::Quadratic => QuadraticProbing::f(hash, pos, max),
+        }
+    }
+
+    #[inline]
+    fn to_byte(self) -> u8 {
+        match self {
+            Probe::Linear => 1,
+            Probe::Quadratic => 2,
+        }
+    }
+
+    #[inline]
+    fn from_byte(b: u8) -> Option<Self> {
+        match b {
+            1 => Some(Probe::Linear),
+            2 => Some(Probe::Quadratic),
+            _ => None,
+        }
+    }
+}
+
+/// Probing strategy of a map that can be chosen at runtime and gets stored along with the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbeStrategy {
+    Linear,
+    Quadratic,
+
+    /// Double hashing using the first probe for the initial position and the second one for the probing sequence.
+    DoubleHashing(Probe, Probe),
+}
+
+impl ProbeStrategy {
+    #[inline]
+    pub fn f(&self, hash: u64, pos: usize, max: usize) -> u64 {
+        match self {
+            ProbeStrategy::Linear => LinearProbing::f(hash, pos, max),
+            ProbeStrategy::Quadratic => QuadraticProbing::f(hash, pos, max),
+            ProbeStrategy::DoubleHashing(first, second) => {
+                let first = first.f(hash, 1, max);
+                let second = second.f(hash, pos, max);
+                (first + second) % max as u64
+            }
+        }
+    }
+
+    /// Encodes an optional `ProbeStrategy` into bytes. `None` is encoded as all zeroes.
+    pub fn to_bytes(strategy: Option<Self>) -> [u8; 4] {
+        match strategy {
+            None => [0; 4],
+            Some(ProbeStrategy::Linear) => [1, 0, 0, 0],
+            Some(ProbeStrategy::Quadratic) => [2, 0, 0, 0],
+            Some(ProbeStrategy::DoubleHashing(first, second)) => {
+                [3, first.to_byte(), second.to_byte(), 0]
+            }
+        }
+    }
+
+    /// Decodes an optional `ProbeStrategy` from bytes encoded with `to_bytes`. Returns `None` if the bytes don't
+    /// encode a strategy, eg. because they are corrupted.
+    pub fn from_bytes(bytes: [u8; 4]) -> Option<Option<Self>> {
+        let strategy = match bytes {
+            [0, 0, 0, 0] => None,
+            [1, 0, 0, 0] => Some(ProbeStrategy::Linear),
+            [2, 0, 0, 0] => Some(ProbeStrategy::Quadratic),
+            [3, first, second, 0] => Some(ProbeStrategy::DoubleHashing(
+                Probe::from_byte(first)?,
+                Probe::from_byte(second)?,
+            )),
+            _ => return None,
+        };
+        Some(strategy)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_probe_strategy() {
+        let strategies = [
+            ProbeStrategy::Linear,
+            ProbeStrategy::Quadratic,
+            ProbeStrategy::DoubleHashing(Probe::Quadratic, Probe::Linear),
+            ProbeStrategy::DoubleHashing(Probe::Linear, Probe::Quadratic),
+        ];
+        for strategy in strategies {
+            let bytes = ProbeStrategy::to_bytes(Some(strategy));
+            assert_eq!(ProbeStrategy::from_bytes(bytes), Some(Some(strategy)));
+        }
+        assert_eq!(ProbeStrategy::from_bytes(ProbeStrategy::to_bytes(None)), Some(None));
+        assert_eq!(ProbeStrategy::from_bytes([4, 0, 0, 0]), None);
+        assert_eq!(ProbeStrategy::from_bytes([3, 1, 9, 0]), None);
+        assert_eq!(ProbeStrategy::from_bytes([1, 0, 0, 1]), None);
+
+        // Must match the static probing functions.
+        for pos in 0..100 {
+            let double = ProbeStrategy::DoubleHashing(Probe::Quadratic, Probe::Linear);
+            assert_eq!(
+                double.f(1234, pos, 1031),
+                DoubleHashing::<QuadraticProbing, LinearProbing>::f(1234, pos, 1031)
+            );
+            assert_eq!(ProbeStrategy::Linear.f(99, pos, 17), LinearProbing::f(99, pos, 17));
+            assert_eq!(DynamicProbing::f_dyn(None, 99, pos, 17), DynamicProbing::f(99, pos, 17));
+        }
+    }
+}
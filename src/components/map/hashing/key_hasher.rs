@@ -0,0 +1,98 @@
+use crate::components::map::hashing::Hash;
+use siphasher::sip::SipHasher13;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+/// Hash function used to hash the keys of a map. The used hasher (including its seed) gets stored along with the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyHasher {
+    /// Fast FNV hashing without seed. Should only be used for trusted keys as it is trivially collidable.
+    Fnv,
+
+    /// Seeded SipHash-1-3 which is resistant against HashDoS attacks.
+    SipHash13 { k0: u64, k1: u64 },
+}
+
+impl KeyHasher {
+    /// Returns a SipHash-1-3 hasher with a random seed.
+    pub fn random_sip() -> Self {
+        let k0 = RandomState::new().build_hasher().finish();
+        let k1 = RandomState::new().build_hasher().finish();
+        Self::SipHash13 { k0, k1 }
+    }
+
+    /// Hashes the given key.
+    #[inline]
+    pub fn hash<K: Hash + ?Sized>(&self, key: &K) -> u64 {
+        match self {
+            KeyHasher::Fnv => key.hash(),
+            KeyHasher::SipHash13 { k0, k1 } => {
+                let mut hasher = SipHasher13::new_with_keys(*k0, *k1);
+                key.hash_into(&mut hasher);
+                hasher.finish()
+            }
+        }
+    }
+
+    #[inline]
+    pub const fn byte_len() -> usize {
+        4 + 8 + 8
+    }
+
+    /// Converts the hasher into bytes.
+    pub fn to_bytes(self) -> [u8; 20] {
+        let mut bytes = [0u8; 20];
+        match self {
+            KeyHasher::Fnv => bytes[..4].copy_from_slice(&0u32.to_le_bytes()),
+            KeyHasher::SipHash13 { k0, k1 } => {
+                bytes[..4].copy_from_slice(&1u32.to_le_bytes());
+                bytes[4..12].copy_from_slice(&k0.to_le_bytes());
+                bytes[12..].copy_from_slice(&k1.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// Parses a hasher from bytes. Returns `None` if the bytes don't represent a valid hasher.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        assert_eq!(bytes.len(), Self::byte_len());
+        let kind = u32::from_le_bytes(bytes[..4].try_into().unwrap());
+        match kind {
+            0 => Some(KeyHasher::Fnv),
+            1 => {
+                let k0 = u64::from_le_bytes(bytes[4..12].try_into().unwrap());
+                let k1 = u64::from_le_bytes(bytes[12..].try_into().unwrap());
+                Some(KeyHasher::SipHash13 { k0, k1 })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Default for KeyHasher {
+    #[inline]
+    fn default() -> Self {
+        Self::random_sip()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_key_hasher() {
+        let fnv = KeyHasher::Fnv;
+        assert_eq!(fnv.hash(&"hello".to_string()), "hello".to_string().hash());
+        assert_eq!(KeyHasher::from_bytes(&fnv.to_bytes()), Some(fnv));
+
+        let sip = KeyHasher::SipHash13 { k0: 1, k1: 2 };
+        assert_eq!(KeyHasher::from_bytes(&sip.to_bytes()), Some(sip));
+        assert_eq!(sip.hash(&"hello".to_string()), sip.hash(&"hello"));
+        assert_ne!(sip.hash(&"hello"), KeyHasher::SipHash13 { k0: 2, k1: 1 }.hash(&"hello"));
+
+        let a = KeyHasher::random_sip();
+        let b = KeyHasher::random_sip();
+        assert_ne!(a, b);
+    }
+}
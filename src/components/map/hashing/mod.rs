@@ -1,4 +1,5 @@
 pub mod hash;
 pub mod hashfn;
+pub mod key_hasher;
 
 pub use hash::Hash;
@@ -0,0 +1,503 @@
+use crate::backend::base::sub::BaseSubBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::map::hashing::hashfn::HashFn;
+use crate::components::map::hashing::key_hasher::KeyHasher;
+use crate::components::map::hashing::Hash;
+use crate::components::map::metadata::{MapMetadata, NO_RELEVANCE};
+use crate::components::map::{primes, DefaultHasher, FMap, Search, MAX_LOAD};
+use crate::components::multi_file::entry_mut::MFileEntryMut;
+use crate::components::multi_file::MultiFile;
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::traits::sized_deser::SizedDeser;
+use crate::{Error, Result};
+use std::marker::PhantomData;
+
+/// ID of the entry holding the maps metadata.
+const METADATA_ID: usize = 0;
+
+/// ID of the entry holding the slots of the hash table.
+const SLOTS_ID: usize = 1;
+
+/// Size of a slots header: STATE (1 byte) | KEY HASH (u64 LE)
+const SLOT_HEADER_LEN: usize = 9;
+
+/// States of a slot, stored in its first byte.
+const EMPTY: u8 = 0;
+const OCCUPIED: u8 = 1;
+const DELETED: u8 = 2;
+
+/// A hash map storing fixed-size keys and values directly in the slots of its hash table. Unlike `FMap`, neither keys
+/// nor values live behind the KV storage, so a lookup only reads the slots along the probe sequence. Values can be
+/// updated in place.
+///
+/// The internal format: MultiFile(METADATA | SLOTS)
+/// where each slot is encoded as (STATE (1 byte) | KEY HASH (u64 LE) | KEY | VALUE) and STATE is one of `EMPTY`,
+/// `OCCUPIED` and `DELETED`.
+pub struct InlineMap<B, K, V, const KN: usize, const N: usize, H = DefaultHasher> {
+    backend: MultiFile<B>,
+    len: usize,
+    capacity: usize,
+
+    /// The amount of slots marked as deleted. They count towards the load factor until the map grows.
+    deleted: usize,
+
+    hasher: KeyHasher,
+    p: PhantomData<(K, V, H)>,
+}
+
+impl<B, K, V, const KN: usize, const N: usize, H> InlineMap<B, K, V, KN, N, H> {
+    /// Size of a single slot.
+    const SLOT_LEN: usize = SLOT_HEADER_LEN + KN + N;
+
+    /// Offset of the value within a slot.
+    const VALUE_OFFSET: usize = SLOT_HEADER_LEN + KN;
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the hasher used to hash the maps keys.
+    #[inline]
+    pub fn hasher(&self) -> KeyHasher {
+        self.hasher
+    }
+
+    #[inline]
+    fn metadata(&self) -> MapMetadata {
+        MapMetadata::new(self.len, self.capacity, NO_RELEVANCE, None, self.hasher, self.deleted, false, false, None)
+    }
+
+    /// Returns `true` if the map needs to grow for a new item.
+    #[inline]
+    fn need_grow(&self) -> bool {
+        (self.len + self.deleted + 1) as f32 / self.capacity as f32 >= MAX_LOAD
+    }
+
+    /// Returns the state of the slot at `pos`.
+    #[inline]
+    fn slot_state<E: Backend>(slots: &E, pos: usize) -> u8 {
+        slots.get(pos * Self::SLOT_LEN, 1).unwrap()[0]
+    }
+
+    /// Returns the key hash stored in the slot at `pos`.
+    #[inline]
+    fn slot_hash<E: Backend>(slots: &E, pos: usize) -> u64 {
+        let raw = slots.get(pos * Self::SLOT_LEN + 4, 8).unwrap();
+        u64::from_le_bytes(raw.try_into().unwrap())
+    }
+}
+
+impl<B, K, V, const KN: usize, const N: usize, H> InlineMap<B, K, V, KN, N, H>
+    where
+        B: GrowableBackend,
+{
+    /// Creates a new map using the given hasher to hash the keys.
+    pub fn with_hasher(backend: B, capacity: usize, hasher: KeyHasher) -> Result<Self> {
+        let capacity = primes::next_bigger_than(capacity) as usize;
+        let mut backend = MultiFile::with_capacity(backend, 2)?;
+
+        let mut metadata = backend.insert_empty()?;
+        metadata.grow_to(MapMetadata::byte_len())?;
+        metadata.push(&MapMetadata::new(0, capacity, NO_RELEVANCE, None, hasher, 0, false, false, None).to_bytes())?;
+
+        let mut slots = backend.insert_empty()?;
+        slots.grow_to(capacity * Self::SLOT_LEN)?;
+        slots.push_fill(0, capacity * Self::SLOT_LEN)?;
+
+        Ok(Self {
+            backend,
+            len: 0,
+            capacity,
+            deleted: 0,
+            hasher,
+            p: PhantomData,
+        })
+    }
+
+    /// Removes all items from the map.
+    pub fn clear(&mut self) -> Result<()> {
+        let size = self.capacity * Self::SLOT_LEN;
+        self.slots_mut().replace_same_len_fill(0, 0, size)?;
+        self.len = 0;
+        self.deleted = 0;
+        self.write_metadata()
+    }
+
+    /// Writes the slot at `pos`.
+    fn write_slot(&mut self, pos: usize, key_hash: u64, key: &[u8; KN], value: &[u8; N]) -> Result<()> {
+        let mut data = Vec::with_capacity(Self::SLOT_LEN);
+        data.push(OCCUPIED);
+        data.extend_from_slice(&key_hash.to_le_bytes());
+        data.extend_from_slice(key);
+        data.extend_from_slice(value);
+        self.slots_mut().replace_same_len(pos * Self::SLOT_LEN, &data)?;
+        Ok(())
+    }
+
+    /// Writes the value of the slot at `pos`.
+    #[inline]
+    fn write_value(&mut self, pos: usize, value: &[u8; N]) -> Result<()> {
+        self.slots_mut().replace_same_len(pos * Self::SLOT_LEN + Self::VALUE_OFFSET, value)?;
+        Ok(())
+    }
+
+    fn write_metadata(&mut self) -> Result<()> {
+        let metadata = self.metadata();
+        let mut entry = self.backend.get_mut(METADATA_ID).unwrap();
+        entry.replace_same_len(0, &metadata.to_bytes())?;
+        Ok(())
+    }
+
+    #[inline]
+    fn slots_mut(&mut self) -> MFileEntryMut<'_, B> {
+        self.backend.entry_mut(SLOTS_ID).unwrap()
+    }
+}
+
+impl<B, K, V, const KN: usize, const N: usize, H> InlineMap<B, K, V, KN, N, H>
+    where
+        H: HashFn,
+        B: GrowableBackend,
+        K: Hash + Eq + SizedDeser<KN>,
+        V: SizedDeser<N>,
+{
+    /// Inserts a new key value pair into the map. Returns `false` if the key already existed, in which case its value
+    /// *DOES NOT* get updated. Use `insert_or_update` to replace existing values.
+    pub fn insert(&mut self, k: &K, v: &V) -> Result<bool> {
+        let key_hash = self.hasher.hash(k);
+        match self.search_grow(k, key_hash)? {
+            Search::Found { .. } => Ok(false),
+            Search::Vacant { pos, reuse, .. } => {
+                self.insert_vacant(k, v, key_hash, pos, reuse)?;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Inserts the key value pair into the map. If the key already existed, its value gets replaced and the old value
+    /// is returned.
+    pub fn insert_or_update(&mut self, k: &K, v: &V) -> Result<Option<V>> {
+        let key_hash = self.hasher.hash(k);
+        match self.search_grow(k, key_hash)? {
+            Search::Found { pos, .. } => {
+                let old = Self::slot_value(&self.slots(), pos);
+                self.write_value(pos, &v.to_bytes())?;
+                Ok(Some(old))
+            }
+            Search::Vacant { pos, reuse, .. } => {
+                self.insert_vacant(k, v, key_hash, pos, reuse)?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Modifies the value of `k` in place using `f`. Returns `false` if the key doesn't exist.
+    pub fn update<F>(&mut self, k: &K, f: F) -> Result<bool>
+        where
+            F: FnOnce(&mut V),
+    {
+        let Search::Found { pos, .. } = self.search(k, self.hasher.hash(k))? else {
+            return Ok(false);
+        };
+        let mut value = Self::slot_value(&self.slots(), pos);
+        f(&mut value);
+        self.write_value(pos, &value.to_bytes())?;
+        Ok(true)
+    }
+
+    /// Removes `k` from the map and returns its value. The keys slot gets marked as deleted.
+    pub fn remove(&mut self, k: &K) -> Result<Option<V>> {
+        let Search::Found { pos, .. } = self.search(k, self.hasher.hash(k))? else {
+            return Ok(None);
+        };
+        let value = Self::slot_value(&self.slots(), pos);
+        self.slots_mut().replace_same_len(pos * Self::SLOT_LEN, &[DELETED])?;
+
+        self.len -= 1;
+        self.deleted += 1;
+        self.write_metadata()?;
+        Ok(Some(value))
+    }
+
+    /// Grows the map if required and searches the slot of `k` afterwards.
+    #[inline]
+    fn search_grow(&mut self, k: &K, key_hash: u64) -> Result<Search> {
+        if self.need_grow() {
+            self.grow()?;
+        }
+        self.search(k, key_hash)
+    }
+
+    fn insert_vacant(&mut self, k: &K, v: &V, key_hash: u64, pos: usize, reuse: bool) -> Result<()> {
+        self.write_slot(pos, key_hash, &k.to_bytes(), &v.to_bytes())?;
+
+        self.len += 1;
+        if reuse {
+            self.deleted -= 1;
+        }
+        self.write_metadata()
+    }
+
+    /// Moves all slots into a table with the smallest capacity fitting a new item, which drops slots marked as
+    /// deleted. Keys don't need to be hashed again as their hashes are stored in the slots.
+    fn grow(&mut self) -> Result<()> {
+        let capacity = FMap::<B, K, V, H>::capacity_for(self.len + 1);
+        let old = self.slots();
+
+        let mut data = vec![0u8; capacity * Self::SLOT_LEN];
+        for pos in 0..self.capacity {
+            if Self::slot_state(&old, pos) != OCCUPIED {
+                continue;
+            }
+            let key_hash = Self::slot_hash(&old, pos);
+            let new_pos = (0..H::probe_len(capacity))
+                .map(|i| H::probe(None, key_hash, i, capacity) as usize)
+                .find(|i| data[i * Self::SLOT_LEN] == EMPTY)
+                .ok_or(Error::OutOfBounds)?;
+            let slot = old.get(pos * Self::SLOT_LEN, Self::SLOT_LEN)?;
+            data[new_pos * Self::SLOT_LEN..(new_pos + 1) * Self::SLOT_LEN].copy_from_slice(slot);
+        }
+
+        let mut slots = self.slots_mut();
+        slots.clear();
+        if slots.capacity() < data.len() {
+            slots.grow_to(data.len())?;
+        }
+        slots.push(&data)?;
+
+        self.capacity = capacity;
+        self.deleted = 0;
+        self.write_metadata()
+    }
+}
+
+impl<B, K, V, const KN: usize, const N: usize, H> InlineMap<B, K, V, KN, N, H>
+    where
+        H: HashFn,
+        B: Backend,
+        K: Hash + Eq + SizedDeser<KN>,
+        V: SizedDeser<N>,
+{
+    /// Returns the value of `k`.
+    #[inline]
+    pub fn get(&self, k: &K) -> Option<V> {
+        match self.search(k, self.hasher.hash(k)).ok()? {
+            Search::Found { pos, .. } => Some(Self::slot_value(&self.slots(), pos)),
+            Search::Vacant { .. } => None,
+        }
+    }
+
+    /// Returns `true` if the map contains a value for the given key.
+    #[inline]
+    pub fn contains_key(&self, k: &K) -> bool {
+        matches!(self.search(k, self.hasher.hash(k)), Ok(Search::Found { .. }))
+    }
+
+    /// Returns an iterator over all items in the map in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (K, V)> + '_ {
+        let slots = self.slots();
+        (0..self.capacity)
+            .filter(move |pos| Self::slot_state(&slots, *pos) == OCCUPIED)
+            .map(move |pos| (Self::slot_key(&slots, pos), Self::slot_value(&slots, pos)))
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.backend.flush()
+    }
+
+    /// Searches the slot of `k`. Only keys of slots with a matching key hash get decoded. As there is no KV storage,
+    /// `kv_id` of a found key is always 0.
+    fn search(&self, k: &K, key_hash: u64) -> Result<Search> {
+        let slots = self.slots();
+
+        let mut deleted = None;
+        for i in 0..H::probe_len(self.capacity) {
+            let pos = H::probe(None, key_hash, i, self.capacity) as usize;
+            match Self::slot_state(&slots, pos) {
+                EMPTY => {
+                    let (probes, pos) = deleted.unwrap_or((i, pos));
+                    return Ok(Search::Vacant { probes, pos, reuse: deleted.is_some() });
+                }
+                DELETED => {
+                    deleted.get_or_insert((i, pos));
+                }
+                _ if Self::slot_hash(&slots, pos) == key_hash && Self::slot_key(&slots, pos) == *k => {
+                    return Ok(Search::Found { kv_id: 0, probes: i, pos });
+                }
+                _ => {}
+            }
+        }
+
+        let (probes, pos) = deleted.ok_or(Error::OutOfBounds)?;
+        Ok(Search::Vacant { probes, pos, reuse: true })
+    }
+
+    /// Returns the key stored in the slot at `pos`.
+    #[inline]
+    fn slot_key<E: Backend>(slots: &E, pos: usize) -> K {
+        let raw = slots.get(pos * Self::SLOT_LEN + SLOT_HEADER_LEN, KN).unwrap();
+        K::from_bytes(raw.try_into().unwrap())
+    }
+
+    /// Returns the value stored in the slot at `pos`.
+    #[inline]
+    fn slot_value<E: Backend>(slots: &E, pos: usize) -> V {
+        let raw = slots.get(pos * Self::SLOT_LEN + Self::VALUE_OFFSET, N).unwrap();
+        V::from_bytes(raw.try_into().unwrap())
+    }
+}
+
+impl<B, K, V, const KN: usize, const N: usize, H> InlineMap<B, K, V, KN, N, H>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn slots(&self) -> BaseSubBackend<'_, &[u8]> {
+        self.backend.get(SLOTS_ID).unwrap()
+    }
+}
+
+impl<B, K, V, const KN: usize, const N: usize, H> Creatable<B> for InlineMap<B, K, V, KN, N, H>
+    where
+        B: GrowableBackend,
+{
+    /// Creates a new map using seeded SipHash-1-3 with a random seed to hash the keys.
+    #[inline]
+    fn with_capacity(backend: B, capacity: usize) -> Result<Self> {
+        Self::with_hasher(backend, capacity, KeyHasher::random_sip())
+    }
+}
+
+impl<B, K, V, const KN: usize, const N: usize, H> Initiable<B> for InlineMap<B, K, V, KN, N, H>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let backend = MultiFile::init(backend)?;
+
+        let metadata = backend.get(METADATA_ID).ok_or(Error::Initialization)?;
+        let metadata = MapMetadata::read(metadata.content_data())?;
+
+        let slots = backend.get(SLOTS_ID).ok_or(Error::Initialization)?;
+        if slots.len() != metadata.capacity() * Self::SLOT_LEN {
+            return Err(Error::Initialization);
+        }
+
+        Ok(Self {
+            backend,
+            len: metadata.len(),
+            capacity: metadata.capacity(),
+            deleted: metadata.deleted(),
+            hasher: metadata.hasher(),
+            p: PhantomData,
+        })
+    }
+}
+
+impl<B, K, V, const KN: usize, const N: usize, H> MType for InlineMap<B, K, V, KN, N, H>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.backend.raw_data()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_inline(&mut mem_backend);
+        test_remove(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./inline_map", 10);
+        test_inline(&mut mmap_backend);
+        test_remove(&mut mmap_backend);
+    }
+
+    fn test_inline<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        {
+            let mut map: InlineMap<_, u64, u64, 8, 8> = InlineMap::create(&mut backend).unwrap();
+            assert!(map.is_empty());
+            for i in 0..5000u64 {
+                assert_eq!(map.insert(&(i + 1000), &(i * 3)), Ok(true));
+            }
+            assert_eq!(map.insert(&1007, &0), Ok(false));
+            assert_eq!(map.get(&1007), Some(21));
+        }
+
+        let mut map: InlineMap<_, u64, u64, 8, 8> = InlineMap::init(&mut backend).unwrap();
+        assert_eq!(map.len(), 5000);
+        assert!((0..5000u64).all(|i| map.get(&(i + 1000)) == Some(i * 3)));
+        assert!(map.contains_key(&5999));
+        assert!(!map.contains_key(&6000));
+        assert_eq!(map.get(&0), None);
+
+        let mut all: Vec<_> = map.iter().collect();
+        all.sort_unstable_by_key(|i| i.1);
+        assert_eq!(all, (0..5000u64).map(|i| (i + 1000, i * 3)).collect::<Vec<_>>());
+
+        assert_eq!(map.insert_or_update(&1001, &100), Ok(Some(3)));
+        assert_eq!(map.insert_or_update(&1, &1), Ok(None));
+        assert_eq!(map.update(&1002, |v| *v += 1), Ok(true));
+        assert_eq!(map.update(&2, |v| *v += 1), Ok(false));
+        assert_eq!(map.get(&1001), Some(100));
+        assert_eq!(map.get(&1002), Some(7));
+        assert_eq!(map.len(), 5001);
+
+        map.clear().unwrap();
+        assert!(map.is_empty());
+        assert_eq!(map.get(&1001), None);
+        assert_eq!(map.iter().count(), 0);
+    }
+
+    fn test_remove<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut map: InlineMap<_, u32, f32, 4, 4> = InlineMap::with_capacity(&mut backend, 100).unwrap();
+        for i in 0..1000u32 {
+            map.insert(&i, &(i as f32 / 2.0)).unwrap();
+        }
+        for i in (0..1000u32).step_by(3) {
+            assert_eq!(map.remove(&i), Ok(Some(i as f32 / 2.0)));
+        }
+        assert_eq!(map.remove(&0), Ok(None));
+        assert_eq!(map.len(), 666);
+        assert!((0..1000u32).all(|i| map.contains_key(&i) == (i % 3 != 0)));
+
+        // Slots of removed keys get reused.
+        let capacity = map.capacity();
+        assert_eq!(map.insert(&3, &1.5), Ok(true));
+        assert_eq!(map.capacity(), capacity);
+        assert_eq!(map.get(&3), Some(1.5));
+
+        for i in 1000..3000u32 {
+            map.insert(&i, &1.0).unwrap();
+        }
+        assert!(map.capacity() > capacity);
+        assert!((0..1000u32).all(|i| map.contains_key(&i) == (i % 3 != 0 || i == 3)));
+        assert_eq!(map.iter().count(), map.len());
+    }
+}
@@ -3,7 +3,10 @@ use crate::backend::Backend;
 use crate::backend::base::sub::BaseSubBackend;
 use crate::components::indexed_file::IndexedFile;
 use crate::components::list::ListU32;
-use crate::components::map::FMap;
+use crate::components::map::kvpair::KVPair;
+use crate::components::map::{kvpair, FMap, TOMBSTONE};
+use crate::utils::ReadAhead;
+use std::marker::PhantomData;
 
 pub struct MapIter<'a, B, K, V, H> {
     // TODO: remove unused parameter and use phantom data!
@@ -11,6 +14,8 @@ pub struct MapIter<'a, B, K, V, H> {
     table: ListU32<BaseSubBackend<'a, &'a [u8]>>,
     storage: IndexedFile<BaseSubBackend<'a, &'a [u8]>>,
     pos: usize,
+    yielded: usize,
+    read_ahead: ReadAhead,
 }
 
 impl<'a, B, K, V, H> MapIter<'a, B, K, V, H> where B: Backend {
@@ -18,17 +23,42 @@ impl<'a, B, K, V, H> MapIter<'a, B, K, V, H> where B: Backend {
     pub(super) fn new(map: &'a FMap<B, K, V, H>) -> Self {
         let table = map.hash_table();
         let storage = map.entry_storage();
-        Self { map, pos: 0, table, storage }
+        Self { map, pos: 0, yielded: 0, table, storage, read_ahead: ReadAhead::default() }
+    }
+
+    /// Hints the backend to load table slots and the entries they point to `distance` slots ahead of the current
+    /// position, which speeds up scans over maps that are not in the page cache yet. A distance of 0 disables
+    /// prefetching.
+    #[inline]
+    pub fn prefetch(mut self, distance: usize) -> Self {
+        self.read_ahead = ReadAhead::new(distance);
+        self
     }
 }
 
 impl<'a, B, K, V, H> MapIter<'a, B, K, V, H> where B: Backend {
+    /// Returns the amount of entries that haven't been yielded yet.
+    #[inline]
+    fn remaining(&self) -> usize {
+        self.map.len() - self.yielded
+    }
+
     #[inline]
     fn find_next_occupied(&mut self) -> Option<(usize, usize)> {
+        if let Some(window) = self.read_ahead.window(self.pos, self.table.len()) {
+            self.table.prefetch_range(window.clone());
+            for index in window {
+                let item = self.table.get(index).unwrap_or(0);
+                if item > 0 && item != TOMBSTONE {
+                    self.storage.prefetch(item as usize - 1);
+                }
+            }
+        }
+
         (self.pos..self.table.len()).find_map(|index| {
-            let item = self.table.get(index).ok().unwrap() as usize;
-            if item > 0 {
-                Some((item - 1, index))
+            let item = self.table.get(index).ok().unwrap();
+            if item > 0 && item != TOMBSTONE {
+                Some((item as usize - 1, index))
             } else {
                 None
             }
@@ -36,6 +66,17 @@ impl<'a, B, K, V, H> MapIter<'a, B, K, V, H> where B: Backend {
     }
 }
 
+impl<'a, B, K, V, H> MapIter<'a, B, K, V, H> where B: Backend {
+    /// Returns the encoded KV pair of the next occupied slot.
+    #[inline]
+    fn next_raw(&mut self) -> Option<&[u8]> {
+        let (kv_id, pos) = self.find_next_occupied()?;
+        self.pos = pos + 1;
+        self.yielded += 1;
+        Some(self.storage.get(kv_id).unwrap())
+    }
+}
+
 impl<'a, B, K, V, H> Iterator for MapIter<'a, B, K, V, H>
     where B: Backend,
           K: DeserializeOwned,
@@ -46,6 +87,141 @@ impl<'a, B, K, V, H> Iterator for MapIter<'a, B, K, V, H>
     fn next(&mut self) -> Option<Self::Item> {
         let (kv_id, pos) = self.find_next_occupied()?;
         self.pos = pos + 1;
+        self.yielded += 1;
         Some(FMap::<B, K, V>::entry_by_id(kv_id as u32, &self.storage).unwrap().into())
     }
-}
\ No newline at end of file
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining(), Some(self.remaining()))
+    }
+}
+
+impl<'a, B, K, V, H> ExactSizeIterator for MapIter<'a, B, K, V, H>
+    where B: Backend,
+          K: DeserializeOwned,
+          V: DeserializeOwned,
+{
+}
+
+/// Iterator over the keys of a map. Only the keys get decoded.
+pub struct Keys<'a, B, K, V, H> {
+    inner: MapIter<'a, B, K, V, H>,
+}
+
+impl<'a, B, K, V, H> Keys<'a, B, K, V, H> where B: Backend {
+    #[inline]
+    pub(super) fn new(map: &'a FMap<B, K, V, H>) -> Self {
+        Self { inner: MapIter::new(map) }
+    }
+
+    /// Same as `MapIter::prefetch`.
+    #[inline]
+    pub fn prefetch(self, distance: usize) -> Self {
+        Self { inner: self.inner.prefetch(distance) }
+    }
+}
+
+impl<'a, B, K, V, H> Iterator for Keys<'a, B, K, V, H>
+    where B: Backend,
+          K: DeserializeOwned,
+{
+    type Item = K;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(kvpair::decode_key(self.inner.next_raw()?).unwrap())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.inner.remaining(), Some(self.inner.remaining()))
+    }
+}
+
+/// Iterator over the values of a map. Keys get decoded into the same place for each pair, so their allocations are
+/// reused instead of being made for every item.
+pub struct Values<'a, B, K, V, H> {
+    inner: MapIter<'a, B, K, V, H>,
+    key: Option<K>,
+}
+
+impl<'a, B, K, V, H> Values<'a, B, K, V, H> where B: Backend {
+    #[inline]
+    pub(super) fn new(map: &'a FMap<B, K, V, H>) -> Self {
+        Self { inner: MapIter::new(map), key: None }
+    }
+
+    /// Same as `MapIter::prefetch`.
+    #[inline]
+    pub fn prefetch(self, distance: usize) -> Self {
+        Self { inner: self.inner.prefetch(distance), key: self.key }
+    }
+}
+
+impl<'a, B, K, V, H> Iterator for Values<'a, B, K, V, H>
+    where B: Backend,
+          K: DeserializeOwned,
+          V: DeserializeOwned,
+{
+    type Item = V;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let raw = self.inner.next_raw()?;
+        Some(kvpair::decode_value(raw, &mut self.key).unwrap())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.inner.remaining(), Some(self.inner.remaining()))
+    }
+}
+/// Iterator over the pairs of a map in the order they are stored in the KV storage, which is the order they got
+/// inserted in as long as no ID of a removed pair got reused.
+pub struct InsertionOrderIter<'a, K, V> {
+    storage: IndexedFile<BaseSubBackend<'a, &'a [u8]>>,
+    removed: Vec<bool>,
+    pos: usize,
+    remaining: usize,
+    p: PhantomData<(K, V)>,
+}
+
+impl<'a, K, V> InsertionOrderIter<'a, K, V> {
+    #[inline]
+    pub(super) fn new<B: Backend, H>(map: &'a FMap<B, K, V, H>) -> Self {
+        Self {
+            storage: map.entry_storage(),
+            removed: map.removed_ids(),
+            pos: 0,
+            remaining: map.len(),
+            p: PhantomData,
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for InsertionOrderIter<'a, K, V>
+    where K: DeserializeOwned,
+          V: DeserializeOwned,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let kv_id = (self.pos..self.storage.count()).find(|i| !self.removed[*i])?;
+        self.pos = kv_id + 1;
+        self.remaining -= 1;
+        let pair: KVPair<K, V> = bincode::deserialize(self.storage.get(kv_id).unwrap()).unwrap();
+        Some(pair.into())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for InsertionOrderIter<'a, K, V>
+    where K: DeserializeOwned,
+          V: DeserializeOwned,
+{
+}
@@ -1,3 +1,4 @@
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
@@ -22,6 +23,11 @@ impl<K, V> KVPair<K, V> {
         &self.value
     }
 
+    #[inline]
+    pub fn value_mut(&mut self) -> &mut V {
+        &mut self.value
+    }
+
     #[inline]
     pub fn into_value(self) -> V {
         self.value
@@ -34,4 +40,59 @@ impl<K, V> Into<(K, V)> for KVPair<K, V> {
     fn into(self) -> (K, V) {
         (self.key, self.value)
     }
-}
\ No newline at end of file
+}
+/// Deserializes an encoded `KVPair` into `place`, reusing the allocations `place` already holds. A `KVPair` is encoded
+/// the same way as a tuple of its key and value.
+pub(crate) fn decode_into<'de, K, V>(data: &'de [u8], place: &mut (K, V)) -> crate::Result<()>
+    where
+        K: Deserialize<'de>,
+        V: Deserialize<'de>,
+{
+    let mut deserializer = bincode::Deserializer::from_slice(data, options());
+    Deserialize::deserialize_in_place(&mut deserializer, place)?;
+    Ok(())
+}
+
+/// Deserializes only the key of an encoded `KVPair`.
+pub(crate) fn decode_key<'de, K>(data: &'de [u8]) -> crate::Result<K>
+    where
+        K: Deserialize<'de>,
+{
+    let mut deserializer = bincode::Deserializer::from_slice(data, options());
+    Ok(K::deserialize(&mut deserializer)?)
+}
+
+/// Deserializes the value of an encoded `KVPair`. As the encoding isn't self-describing the key has to be decoded as
+/// well, which happens in place of `key` to reuse its allocations.
+pub(crate) fn decode_value<'de, K, V>(data: &'de [u8], key: &mut Option<K>) -> crate::Result<V>
+    where
+        K: Deserialize<'de>,
+        V: Deserialize<'de>,
+{
+    let mut deserializer = bincode::Deserializer::from_slice(data, options());
+    match key {
+        Some(key) => K::deserialize_in_place(&mut deserializer, key)?,
+        None => *key = Some(K::deserialize(&mut deserializer)?),
+    }
+    Ok(V::deserialize(&mut deserializer)?)
+}
+
+/// Returns the offset of the encoded value within an encoded `KVPair`, which is the encoded length of its key.
+pub(crate) fn value_offset<K>(data: &[u8]) -> crate::Result<usize>
+    where
+        K: DeserializeOwned,
+{
+    let mut reader = data;
+    let mut deserializer = bincode::Deserializer::with_reader(&mut reader, options());
+    K::deserialize(&mut deserializer)?;
+    Ok(data.len() - reader.len())
+}
+
+#[inline]
+fn options() -> impl bincode::Options {
+    use bincode::Options;
+
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+}
@@ -1,14 +1,84 @@
+use crate::components::map::hashing::hashfn::ProbeStrategy;
+use crate::components::map::hashing::key_hasher::KeyHasher;
+use crate::error::Error;
+use crate::Result;
+
+/// Version of the map format, stored in front of the metadata. Maps written with another version can't be opened.
+/// Maps created before the version got stored only have 16 bytes of metadata holding their length and capacity.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Relevance ID for maps that haven't been rehashed with a relevance function.
+pub const NO_RELEVANCE: u32 = 0;
+
+/// Relevance ID for maps that have been rehashed with a relevance function that wasn't given an explicit ID.
+pub const UNNAMED_RELEVANCE: u32 = u32::MAX;
+
+/// State of an incremental rehash into a bigger table.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Migration {
+    /// Capacity of the new table.
+    pub capacity: usize,
+
+    /// Slots of the current table before `pos` have already been migrated to the new table.
+    pub pos: usize,
+
+    /// Amount of slots in the new table marked as deleted.
+    pub deleted: usize,
+}
+
 /// Metadata for a Hashmap.
 #[derive(Default, Copy, Clone)]
 pub struct MapMetadata {
     len: usize,
     capacity: usize,
+
+    /// ID of the relevance function the map has been rehashed with the last time or `NO_RELEVANCE`.
+    relevance: u32,
+
+    /// Probing strategy chosen at runtime. `None` if the probing is defined by the maps type.
+    probe: Option<ProbeStrategy>,
+
+    /// Hasher used to hash the maps keys.
+    hasher: KeyHasher,
+
+    /// Amount of table slots marked as deleted.
+    deleted: usize,
+
+    /// `true` if the table is stored in the alternative table entries.
+    alt_table: bool,
+
+    /// `true` once the ID of a removed KV pair got reused, so the KV storage isn't in insertion order anymore.
+    ids_reused: bool,
+
+    /// Incremental rehash in progress.
+    migration: Option<Migration>,
 }
 
 impl MapMetadata {
     #[inline]
-    pub fn new(len: usize, capacity: usize) -> Self {
-        Self { len, capacity }
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        len: usize,
+        capacity: usize,
+        relevance: u32,
+        probe: Option<ProbeStrategy>,
+        hasher: KeyHasher,
+        deleted: usize,
+        alt_table: bool,
+        ids_reused: bool,
+        migration: Option<Migration>,
+    ) -> Self {
+        Self {
+            len,
+            capacity,
+            relevance,
+            probe,
+            hasher,
+            deleted,
+            alt_table,
+            ids_reused,
+            migration,
+        }
     }
 
     #[inline]
@@ -21,28 +91,109 @@ impl MapMetadata {
         self.capacity
     }
 
+    #[inline]
+    pub fn relevance(&self) -> u32 {
+        self.relevance
+    }
+
+    #[inline]
+    pub fn probe(&self) -> Option<ProbeStrategy> {
+        self.probe
+    }
+
+    #[inline]
+    pub fn hasher(&self) -> KeyHasher {
+        self.hasher
+    }
+
+    #[inline]
+    pub fn deleted(&self) -> usize {
+        self.deleted
+    }
+
+    #[inline]
+    pub fn alt_table(&self) -> bool {
+        self.alt_table
+    }
+
+    #[inline]
+    pub fn ids_reused(&self) -> bool {
+        self.ids_reused
+    }
+
+    #[inline]
+    pub fn migration(&self) -> Option<Migration> {
+        self.migration
+    }
+
     #[inline]
     pub const fn byte_len() -> usize {
-        8 + 8
+        4 + 8 + 8 + 4 + 4 + KeyHasher::byte_len() + 8 + 4 + 8 + 8 + 8
     }
 
     /// Converts the Maps metadata to a byte array.
     #[inline]
-    pub fn to_bytes(self) -> [u8; 16] {
-        let mut bytes = [0u8; 16];
-        bytes[..8].copy_from_slice(&self.len.to_le_bytes());
-        bytes[8..].copy_from_slice(&self.capacity.to_le_bytes());
+    pub fn to_bytes(self) -> [u8; Self::byte_len()] {
+        let mut bytes = [0u8; Self::byte_len()];
+        bytes[..4].copy_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes[4..12].copy_from_slice(&self.len.to_le_bytes());
+        bytes[12..20].copy_from_slice(&self.capacity.to_le_bytes());
+        bytes[20..24].copy_from_slice(&self.relevance.to_le_bytes());
+        bytes[24..28].copy_from_slice(&ProbeStrategy::to_bytes(self.probe));
+        bytes[28..48].copy_from_slice(&self.hasher.to_bytes());
+        bytes[48..56].copy_from_slice(&self.deleted.to_le_bytes());
+        // Both flags share a single word, the alternative table using the lowest bit.
+        let flags = self.alt_table as u32 | (self.ids_reused as u32) << 1;
+        bytes[56..60].copy_from_slice(&flags.to_le_bytes());
+        let migration = self.migration.unwrap_or(Migration { capacity: 0, pos: 0, deleted: 0 });
+        bytes[60..68].copy_from_slice(&migration.capacity.to_le_bytes());
+        bytes[68..76].copy_from_slice(&migration.pos.to_le_bytes());
+        bytes[76..].copy_from_slice(&migration.deleted.to_le_bytes());
         bytes
     }
 
-    /// Parses bytes to a MapMetadat
+    /// Parses the content of a maps metadata entry. Returns `Error::UnsupportedVersion` if the map has been written
+    /// with another format version and `Error::Initialization` if the metadata is invalid.
+    pub fn read(data: &[u8]) -> Result<Self> {
+        if data.len() != Self::byte_len() || data[..4] != FORMAT_VERSION.to_le_bytes() {
+            return Err(Error::UnsupportedVersion);
+        }
+        Self::from_bytes(data).ok_or(Error::Initialization)
+    }
+
+    /// Parses bytes to a MapMetadat. Returns `None` if the stored hasher or probing strategy is invalid.
     #[inline]
-    pub fn from_bytes(bytes: &[u8]) -> Self {
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
         assert_eq!(bytes.len(), Self::byte_len());
-        let blen: [u8; 8] = unsafe { bytes[..8].try_into().unwrap_unchecked() };
-        let bcap: [u8; 8] = unsafe { bytes[8..].try_into().unwrap_unchecked() };
-        let len = usize::from_le_bytes(blen);
-        let capacity = usize::from_le_bytes(bcap);
-        Self { len, capacity }
+        let read_u32 = |start: usize| u32::from_le_bytes(bytes[start..start + 4].try_into().unwrap());
+        let read_usize = |start: usize| usize::from_le_bytes(bytes[start..start + 8].try_into().unwrap());
+        let bprobe: [u8; 4] = unsafe { bytes[24..28].try_into().unwrap_unchecked() };
+        let len = read_usize(4);
+        let capacity = read_usize(12);
+        let relevance = read_u32(20);
+        let probe = ProbeStrategy::from_bytes(bprobe)?;
+        let hasher = KeyHasher::from_bytes(&bytes[28..48])?;
+        let deleted = read_usize(48);
+        let flags = read_u32(56);
+        let alt_table = flags & 1 != 0;
+        let ids_reused = flags & 2 != 0;
+        // A capacity of 0 means there is no rehash in progress.
+        let migration = Some(Migration {
+            capacity: read_usize(60),
+            pos: read_usize(68),
+            deleted: read_usize(76),
+        })
+        .filter(|i| i.capacity > 0);
+        Some(Self {
+            len,
+            capacity,
+            relevance,
+            probe,
+            hasher,
+            deleted,
+            alt_table,
+            ids_reused,
+            migration,
+        })
     }
 }
@@ -1,20 +1,36 @@
+pub mod entry;
 pub mod hashing;
+pub mod inline;
 pub mod insertion;
 mod kvpair;
 mod metadata;
+pub mod mph;
 mod primes;
+pub mod set;
+pub mod stats;
 pub mod iter;
+#[cfg(feature = "rayon")]
+mod par_build;
+#[cfg(feature = "rayon")]
+mod par_iter;
 
 use crate::backend::base::sub::BaseSubBackend;
 use crate::backend::base::sub_mut::GeneralSubMutBackend;
+use crate::backend::full::FullBackend;
 use crate::backend::growable::GrowableBackend;
+#[cfg(feature = "mmap")]
+use crate::backend::mmap::MmapBackend;
+use crate::backend::shared::SharedBackend;
 use crate::backend::Backend;
 use crate::components::indexed_file::IndexedFile;
-use crate::components::list::ListU32;
-use crate::components::map::hashing::hashfn::{DoubleHashing, HashFn, LinearProbing, QuadraticProbing};
+use crate::components::list::{ListU32, ListU64};
+use crate::components::map::hashing::hashfn::{DoubleHashing, DynamicProbing, HashFn, LinearProbing, ProbeStrategy, QuadraticProbing};
+use crate::components::map::entry::{Entry, OccupiedEntry, VacantEntry};
+use crate::components::map::hashing::key_hasher::KeyHasher;
 use crate::components::map::insertion::Insertion;
 use crate::components::map::kvpair::KVPair;
-use crate::components::map::metadata::MapMetadata;
+use crate::components::map::stats::MapStats;
+use crate::components::map::metadata::{MapMetadata, Migration, NO_RELEVANCE, UNNAMED_RELEVANCE};
 use crate::components::multi_file::entry_mut::MFileEntryMut;
 use crate::components::multi_file::MultiFile;
 use crate::traits::creatable::Creatable;
@@ -24,10 +40,14 @@ use crate::utils::smallest_two_power_for;
 use crate::{Error, Result};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use std::borrow::Borrow;
 use std::cmp::Ordering;
 use std::marker::PhantomData;
-use crate::components::map::iter::MapIter;
+use crate::components::map::iter::{InsertionOrderIter, Keys, MapIter, Values};
 use crate::traits::mtype::MType;
+use crate::traits::read_only::ReadOnly;
+use crate::traits::batch::Batch;
+use crate::traits::shrink::{AutoShrink, ShrinkPolicy, ShrinkTracker};
 
 // TODO: [maybe] Convert to sorted list and allow binary search. Simple since the array used currently as hashtable can be used as sorted position, allowing binary search.
 
@@ -37,9 +57,27 @@ const MAX_LOAD: f32 = 0.75;
 // Max amount of entries that can be inserted until the map regrows in percent.
 // const MIN_LOAD: f32 = 0.3;
 
+/// Value of a table slot whose KV pair has been removed. Lookups have to probe past such slots.
+const TOMBSTONE: u32 = u32::MAX;
+
+/// ID of the MultiFile entry holding the IDs of removed KV pairs, whose storage gets reused by new pairs.
+const FREE_ID: usize = 3;
+
+/// ID of the MultiFile entry holding the key hash of each occupied table slot. Probing compares those before decoding
+/// the stored keys.
+const HASHES_ID: usize = 4;
+
+/// IDs of the MultiFile entries holding the alternative hash table and its key hashes. Incremental rehashing builds the
+/// new table in the entries not in use and switches to them once all slots have been migrated.
+const ALT_TABLE_ID: usize = 5;
+const ALT_HASHES_ID: usize = 6;
+
+/// Load factor at which maps rehashing incrementally start migrating to a bigger table.
+const INCREMENTAL_LOAD: f32 = 0.5;
+
 /// The default hashing algorithm
 // type DefaultHasher = QuadraticProbing;
-type DefaultHasher = DoubleHashing<QuadraticProbing, LinearProbing>;
+pub(crate) type DefaultHasher = DoubleHashing<QuadraticProbing, LinearProbing>;
 
 /// A HashMap similar data structure working entirely stored in the given backend. Supports growing if it gets too full
 pub struct FMap<B, K, V, H = DefaultHasher> {
@@ -51,9 +89,63 @@ pub struct FMap<B, K, V, H = DefaultHasher> {
     /// The amount of items that can be stored in the map without rehashing.
     capacity: usize,
 
+    /// ID of the relevance function used in the last `rehash_with_relevance` call.
+    relevance: u32,
+
+    /// Probing strategy chosen at runtime. Only used for maps with `DynamicProbing`.
+    probe: Option<ProbeStrategy>,
+
+    /// Hasher used to hash the keys.
+    hasher: KeyHasher,
+
+    /// Tracks the free space for the automatic shrink policy.
+    shrink: Option<ShrinkTracker>,
+
+    /// The amount of table slots marked as deleted. They count towards the load factor until the next rehash.
+    deleted: usize,
+
+    /// `true` if the table is stored in the alternative table entries.
+    alt_table: bool,
+
+    /// `true` once the ID of a removed KV pair got reused, so the KV storage isn't in insertion order anymore.
+    ids_reused: bool,
+
+    /// Incremental rehash in progress.
+    migration: Option<Migration>,
+
+    /// Amount of slots migrated per insertion for incremental rehashing or `None` to rehash all at once.
+    rehash_steps: Option<usize>,
+
     p: PhantomData<(K, V, H)>,
 }
 
+/// State of a single slot in the hash table.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Slot {
+    Empty,
+    Deleted,
+    Occupied(u32),
+}
+
+/// Defines how `FMap::merge_from` handles keys that already exist in the map.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Keeps the existing value.
+    KeepExisting,
+
+    /// Replaces the existing value with the merged one.
+    Overwrite,
+}
+
+/// Result of searching the slot of a key in the hash table.
+enum Search {
+    /// The key is stored in the KV pair `kv_id` referenced by the slot at `pos`, found after `probes` probes.
+    Found { kv_id: u32, probes: usize, pos: usize },
+
+    /// The key isn't stored and can be inserted at `pos`. `reuse` is `true` if the slot is marked as deleted.
+    Vacant { probes: usize, pos: usize, reuse: bool },
+}
+
 impl<B, K, V, H> FMap<B, K, V, H>
     where
         H: HashFn,
@@ -62,7 +154,7 @@ impl<B, K, V, H> FMap<B, K, V, H>
         V: Deser,
 {
     /// Inserts a new key value pair into the map returning its unique, non changing ID. If the key already existed, the
-    /// value *DOES NOT* get updated.
+    /// value *DOES NOT* get updated. Use `insert_or_update` or `update` to change existing values.
     #[inline]
     pub fn insert(&mut self, k: &K, v: &V) -> Result<u32> {
         self.insert_debug(k, v).map(|i| i.kv_id())
@@ -71,6 +163,9 @@ impl<B, K, V, H> FMap<B, K, V, H>
     /// Inserts a new key value pair into the map returning its unique, non changing ID. If the key already existed, the
     /// value *DOES NOT* get updated.
     pub fn insert_debug(&mut self, k: &K, v: &V) -> Result<Insertion> {
+        self.auto_shrink()?;
+        self.rehash_step()?;
+
         if self.need_grow() {
             self.grow()?;
         }
@@ -85,63 +180,282 @@ impl<B, K, V, H> FMap<B, K, V, H>
         Ok(res)
     }
 
+    /// Inserts all pairs of `iter` which can be owned or borrowed. Like `insert`, values of existing keys don't get
+    /// updated. If the exact amount of pairs is known, the map grows only once up front. Unlike `extend`, errors like
+    /// `Error::RelevanceLost` get returned instead of panicking, keeping the pairs inserted so far.
+    pub fn try_extend<I, Q, R>(&mut self, iter: I) -> Result<()>
+        where
+            I: IntoIterator<Item=(Q, R)>,
+            Q: Borrow<K>,
+            R: Borrow<V>,
+    {
+        let iter = iter.into_iter();
+        let (lower, upper) = iter.size_hint();
+
+        if lower == 0 || Some(lower) != upper || !self.need_grow_for(lower) {
+            for (k, v) in iter {
+                self.insert(k.borrow(), v.borrow())?;
+            }
+            return Ok(());
+        }
+
+        self.grow_to(self.len() + lower)?;
+        let mut insert_count = 0;
+        let mut res = Ok(());
+        for (k, v) in iter {
+            match self.raw_insert(k.borrow(), v.borrow()) {
+                Ok(insertion) if insertion.inserted() => insert_count += 1,
+                Ok(_) => (),
+                Err(err) => {
+                    res = Err(err);
+                    break;
+                }
+            }
+        }
+
+        self.inc_len(insert_count)?;
+        res
+    }
+
+    /// Inserts the key value pair into the map. If the key already existed, its value gets replaced and the old value
+    /// is returned.
+    pub fn insert_or_update(&mut self, k: &K, v: &V) -> Result<Option<V>> {
+        let res = self.insert_debug(k, v)?;
+        if res.inserted() {
+            return Ok(None);
+        }
+
+        let old = Self::entry_by_id(res.kv_id(), &self.entry_storage()).ok_or(Error::UnexpectedValue)?;
+        self.set_entry(res.kv_id(), &KVPair::new(k, v))?;
+        Ok(Some(old.into_value()))
+    }
+
+    /// Modifies the value of `k` in place using `f`. Since values are stored encoded in the backend, the value gets
+    /// decoded, passed to `f` and written back afterwards. Returns `false` if the key doesn't exist.
+    pub fn update<F>(&mut self, k: &K, f: F) -> Result<bool>
+        where
+            F: FnOnce(&mut V),
+    {
+        let key_hash = self.hasher.hash(k);
+        let Search::Found { kv_id, .. } = self.search(k, key_hash)? else {
+            return Ok(false);
+        };
+        self.modify_entry(kv_id, f)?;
+        Ok(true)
+    }
+
+    /// Modifies the value of the KV pair `kv_id` in place using `f`.
+    fn modify_entry<F>(&mut self, kv_id: u32, f: F) -> Result<()>
+        where
+            F: FnOnce(&mut V),
+    {
+        let mut pair = Self::entry_by_id(kv_id, &self.entry_storage()).ok_or(Error::UnexpectedValue)?;
+        f(pair.value_mut());
+        self.set_entry(kv_id, &KVPair::new(pair.key(), pair.value()))
+    }
+
+    /// Inserts all `items`, for example the pairs of another map, growing the map only once beforehand if the amount of
+    /// items is known. Keys that already exist are handled according to `policy`. Returns the amount of new keys.
+    pub fn merge_from<I>(&mut self, items: I, policy: MergePolicy) -> Result<usize>
+        where
+            I: IntoIterator<Item = (K, V)>,
+    {
+        self.merge_impl(items, |map, kv_id, k, v| match policy {
+            MergePolicy::KeepExisting => Ok(()),
+            MergePolicy::Overwrite => map.set_entry(kv_id, &KVPair::new(k, &v)),
+        })
+    }
+
+    /// Same as `merge_from` but merges the values of keys that already exist using `f`, which gets the key, the
+    /// existing value and the new value passed.
+    pub fn merge_from_with<I, F>(&mut self, items: I, mut f: F) -> Result<usize>
+        where
+            I: IntoIterator<Item = (K, V)>,
+            F: FnMut(&K, &mut V, V),
+    {
+        self.merge_impl(items, |map, kv_id, k, v| map.modify_entry(kv_id, |old| f(k, old, v)))
+    }
+
+    fn merge_impl<I, C>(&mut self, items: I, mut on_conflict: C) -> Result<usize>
+        where
+            I: IntoIterator<Item = (K, V)>,
+            C: FnMut(&mut Self, u32, &K, V) -> Result<()>,
+    {
+        let items = items.into_iter();
+        let (lower, _) = items.size_hint();
+        if self.need_grow_for(lower) {
+            self.grow_to(self.len() + lower)?;
+        }
+
+        let mut inserted = 0;
+        for (k, v) in items {
+            let res = self.insert_debug(&k, &v)?;
+            if res.inserted() {
+                inserted += 1;
+            } else {
+                on_conflict(self, res.kv_id(), &k, v)?;
+            }
+        }
+        Ok(inserted)
+    }
+
+    /// Returns the value for `k` or inserts the value returned by `f` if the key doesn't exist yet. Since values are
+    /// stored encoded in the backend, the value gets returned owned instead of as reference like `HashMap` does.
+    #[inline]
+    pub fn get_or_insert_with<F>(&mut self, k: &K, f: F) -> Result<V>
+        where
+            F: FnOnce() -> V,
+    {
+        self.entry(k)?.or_insert_with(f)
+    }
+
     /// Inserts the key value pair into the map assuming there is enough space and the insertion won't break the
     /// load factor invariant! It also won't increase the maps length counter.
     fn raw_insert(&mut self, k: &K, v: &V) -> Result<Insertion> {
-        let pair = KVPair::new(k, v);
-        let pair_id = self.insert_entry(&pair)?;
-        self.map_kv_pair_s(k, pair_id)
+        let key_hash = self.hasher.hash(k);
+        let search = self.search(k, key_hash)?;
+        match search {
+            Search::Found { kv_id, probes, pos } => Ok(Insertion::new(kv_id, probes, false, pos)),
+            Search::Vacant { probes, pos, reuse } => {
+                let (kv_id, pos) = self.insert_vacant(k, v, key_hash, pos, reuse)?;
+                let probes = H::ROBIN_HOOD.map_or(probes, |distance| distance(key_hash, pos, self.capacity));
+                Ok(Insertion::new(kv_id, probes, true, pos))
+            }
+        }
     }
 
-    fn map_kv_pair_s(&mut self, key: &K, kv_id: u32) -> Result<Insertion> {
-        let capacity = self.capacity();
-        let (mut table, mut kv_storage) = self.kv_and_table_mut()?;
-        Self::map_kv_pair(key, kv_id, capacity, &mut table, &mut kv_storage)
+    /// Stores the key value pair and maps it to the free table slot `pos` found by a previous search. It won't increase
+    /// the maps length counter. Returns the ID of the KV pair and the slot it has been placed at, which only differs
+    /// from `pos` for Robin Hood hashing.
+    fn insert_vacant(&mut self, k: &K, v: &V, key_hash: u64, pos: usize, reuse: bool) -> Result<(u32, usize)> {
+        // Only store the pair once we know the key is new, so no orphaned pairs are left behind.
+        let kv_id = self.insert_entry(&KVPair::new(k, v))?;
+        let capacity = self.capacity;
+
+        let Some(distance) = H::ROBIN_HOOD else {
+            // Write the slot through the entries of the table and the hashes so only the touched bytes get written.
+            Self::set_table_kvid(pos, kv_id, &mut self.table_list_mut())?;
+            self.key_hashes_mut().set_raw(pos, &key_hash.to_le_bytes())?;
+            if reuse {
+                self.deleted -= 1;
+            }
+            self.insert_migrated(kv_id, key_hash, pos)?;
+            return Ok((kv_id, pos));
+        };
+
+        let (mut table, mut hashes, _) = self.table_hashes_kv_mut()?;
+        let (pos, reuse) = Self::robin_hood_place(distance, kv_id, key_hash, capacity, &mut table, &mut hashes)?;
+        if reuse {
+            self.deleted -= 1;
+        }
+        Ok((kv_id, pos))
     }
 
-    /// Maps a KV pair to its hash.
-    fn map_kv_pair<E1: Backend, E2: Backend>(
-        key: &K,
-        kv_id: u32,
-        capacity: usize,
-        table: &mut ListU32<E1>,
-        kv_storage: &mut IndexedFile<E2>,
-    ) -> Result<Insertion> {
-        let key_hash = key.hash();
+    /// Returns the entry of `k` for in-place manipulation. The map grows beforehand if required, so the key gets
+    /// hashed and searched only once, regardless of the operations performed on the entry.
+    pub fn entry<'a>(&'a mut self, k: &'a K) -> Result<Entry<'a, B, K, V, H>> {
+        self.auto_shrink()?;
+        self.rehash_step()?;
+
+        if self.need_grow() {
+            self.grow()?;
+        }
+
+        let key_hash = self.hasher.hash(k);
+        Ok(match self.search(k, key_hash)? {
+            Search::Found { kv_id, pos, .. } => Entry::Occupied(OccupiedEntry::new(self, k, kv_id, pos)),
+            Search::Vacant { pos, reuse, .. } => Entry::Vacant(VacantEntry::new(self, k, key_hash, pos, reuse)),
+        })
+    }
 
-        for i in 0..capacity {
-            let hash = H::f(key_hash, i, capacity);
+    /// Removes `k` from the map and returns its value. The keys table slot gets marked as deleted and the storage of
+    /// its KV pair gets reused by following insertions.
+    pub fn remove(&mut self, k: &K) -> Result<Option<V>> {
+        let key_hash = self.hasher.hash(k);
+        let Search::Found { kv_id, pos, .. } = self.search(k, key_hash)? else {
+            return Ok(None);
+        };
+        self.remove_at(kv_id, pos).map(Some)
+    }
 
-            if let Some(pair_id) = Self::resolve_hash(hash, table) {
-                let data = kv_storage.get(pair_id as usize)?;
-                let kv_pair: KVPair<K, V> = bincode::deserialize(data)?;
+    /// Removes the KV pair `kv_id` referenced by the table slot at `pos` and returns its value.
+    fn remove_at(&mut self, kv_id: u32, pos: usize) -> Result<V> {
+        let pair = Self::entry_by_id(kv_id, &self.entry_storage()).ok_or(Error::UnexpectedValue)?;
+        self.remove_migrated(kv_id, pos)?;
+        self.table_list_mut().set_raw(pos, &TOMBSTONE.to_le_bytes())?;
+        self.kv_storage_mut().entry(kv_id as usize)?.set(&[])?;
+        self.free_list_mut().push(&kv_id)?;
+
+        self.len -= 1;
+        self.deleted += 1;
+        self.set_metadata(self.metadata())?;
+        Ok(pair.into_value())
+    }
 
-                if kv_pair.key() == key {
-                    // KV pair already exists in the map and key is the same (so not just a collision)
-                    return Ok(Insertion::new(pair_id, i, false, hash as usize));
-                }
+    /// Retains only the entries for which `f` returns `true` and compacts the KV storage afterwards. This changes the
+    /// IDs of the remaining KV pairs. Slots of removed entries get dropped as well, unless the map has been rehashed
+    /// with relevance, as rehashing would destroy the order.
+    pub fn retain<F>(&mut self, mut f: F) -> Result<()>
+        where
+            F: FnMut(&K, &V) -> bool,
+    {
+        let mut removed = self.removed_ids();
+        let mut count = 0;
 
-                // Continue as we haven't found the key yet.
+        let (mut table, kv_storage) = self.kv_and_table_mut()?;
+        for pos in 0..table.len() {
+            let Slot::Occupied(kv_id) = Self::read_slot(pos as u64, &table) else {
                 continue;
-            }
+            };
 
-            // Insert the key into the current free slot
-            Self::set_table_kvid(hash as usize, kv_id, table)?;
-            return Ok(Insertion::new(kv_id, i, true, hash as usize));
+            let pair = Self::entry_by_id(kv_id, &kv_storage).ok_or(Error::UnexpectedValue)?;
+            if !f(pair.key(), pair.value()) {
+                table.set_raw(pos, &TOMBSTONE.to_le_bytes())?;
+                removed[kv_id as usize] = true;
+                count += 1;
+            }
         }
 
-        // The hash function must be implemented in a way that it doesn't need more than `capacity` iterations to find a free slot.
-        unreachable!()
+        self.len -= count;
+        self.deleted += count;
+        self.compact(&removed)?;
+
+        if self.deleted > 0 && self.relevance_id().is_none() {
+            return self.rehash();
+        }
+        self.set_metadata(self.metadata())
     }
 
     /// Grows the Map to the next prime of nth power of 2 so that the load factor for the given length is <= `MAX_LOAD`.
     /// This rehashes all entries and should only be called if the load factor is getting bigger than the
     /// given limit (but hasn't exceeded this limit yet).
+    /// Returns `Error::RelevanceLost` if the map has been rehashed with relevance as growing would destroy the order.
+    /// Use `grow_to_with_relevance` or `clear_relevance` for those maps.
     pub fn grow_to(&mut self, len: usize) -> Result<usize> {
+        if self.relevance_id().is_some() {
+            return Err(Error::RelevanceLost);
+        }
+
+        self.grow_to_unordered(len)
+    }
+
+    /// Same as `grow_to` but re-applies the relevance order using `compare` afterwards. The relevance ID of the map
+    /// remains unchanged.
+    pub fn grow_to_with_relevance<R>(&mut self, len: usize, compare: R) -> Result<usize>
+        where
+            R: FnMut(&KVPair<K, V>, &KVPair<K, V>) -> Ordering,
+    {
+        let id = self.relevance;
+        let regrowth_size = self.grow_to_unordered(len)?;
+        if regrowth_size > 0 && id != NO_RELEVANCE {
+            self.rehash_with_relevance_id(id, compare)?;
+        }
+        Ok(regrowth_size)
+    }
+
+    fn grow_to_unordered(&mut self, len: usize) -> Result<usize> {
         // Increase capacity to the next prime of amount of new items we want to add.
-        let need_cap = (len as f32 / MAX_LOAD).ceil() as usize;
-        let pow = smallest_two_power_for(need_cap) as usize;
-        let regrowth_size = primes::NEXT_PRIMES_OF_TWO[pow] as usize;
+        let regrowth_size = Self::capacity_for(len);
         if self.capacity() == regrowth_size {
             return Ok(0);
         }
@@ -154,11 +468,70 @@ impl<B, K, V, H> FMap<B, K, V, H>
         Ok(regrowth_size)
     }
 
+    /// Reserves space for `additional_items` new items, whose encoded key value pairs take `total_value_bytes` in total.
+    /// Inserting them afterwards neither grows the backend nor rehashes the map, unless a shrink policy releases the
+    /// space again. The hash table grows like `grow_to` and the KV-storage like `preallocate_entries`, but only by the
+    /// space that isn't available yet. Returns `Error::RelevanceLost` if the hash table of a map that has been rehashed
+    /// with relevance would have to grow.
+    pub fn reserve(&mut self, additional_items: usize, total_value_bytes: usize) -> Result<()> {
+        self.finish_rehash()?;
+
+        let max_load = self.rehash_load();
+        if self.load_factor_for(self.len + self.deleted + additional_items) >= max_load {
+            if self.relevance_id().is_some() {
+                return Err(Error::RelevanceLost);
+            }
+            let len = ((self.len + additional_items + 1) as f32 * MAX_LOAD / max_load).ceil() as usize;
+            self.increase_capacity(Self::capacity_for(len))?;
+            self.rehash()?;
+        }
+
+        self.kv_storage_mut().reserve(additional_items, total_value_bytes)
+    }
+
     pub fn reserve_storage(&mut self, items: usize, bytes: usize) -> Result<()> {
         self.kv_storage_mut().grow(items, bytes)?;
         Ok(())
     }
 
+    /// Frees all unused space of the map. The KV-storage gets compacted and the hash table rebuilt with the smallest
+    /// capacity satisfying `MAX_LOAD`, which changes the IDs of KV pairs if entries have been removed. Maps rehashed with
+    /// relevance keep their capacity as rehashing would destroy the order. Afterwards the free space left in the
+    /// KV-storage and hash table gets truncated and the whole backend shrunk.
+    pub fn shrink_to_fit(&mut self) -> Result<()> {
+        let removed = self.removed_ids();
+        if removed.contains(&true) {
+            self.compact(&removed)?;
+        }
+
+        if self.relevance_id().is_none() {
+            let capacity = Self::capacity_for(self.len);
+            if capacity < self.capacity {
+                self.decrease_capacity(capacity)?;
+                self.rehash()?;
+            } else if self.deleted > 0 {
+                self.rehash()?;
+            }
+        }
+
+        self.truncate_free_space()
+    }
+
+    /// Decreases the capacity, leaving the hash table in an invalid state as the hashing function isn't valid anymore.
+    /// It also clears the hash table. This means you have to rehash all entries again!
+    fn decrease_capacity(&mut self, new_capacity: usize) -> Result<()> {
+        if new_capacity >= self.capacity {
+            return Ok(());
+        }
+
+        let mut table = self.table_list_mut();
+        table.set_len(new_capacity)?;
+        table.mem_set(0)?;
+        self.key_hashes_mut().set_len(new_capacity)?;
+
+        self.set_capacity(new_capacity)
+    }
+
     /// Increases the capacity, leaving the hash table in an invalid state as the hashing function isn't valid anymore.
     /// It also clears the hash table. This means you have to rehash all entries again!
     fn increase_capacity(&mut self, new_capacity: usize) -> Result<()> {
@@ -172,30 +545,209 @@ impl<B, K, V, H> FMap<B, K, V, H>
         table.set_len(new_capacity)?;
         table.mem_set(0)?;
 
+        let mut hashes = self.key_hashes_mut();
+        hashes.grow_for_exact(diff)?;
+        hashes.set_len(new_capacity)?;
+
         self.set_capacity(new_capacity)?;
         Ok(())
     }
 
-    /// Hashes all elements in the KV store again.
+    /// Hashes all elements in the KV store again. This also drops all slots marked as deleted.
     pub fn rehash(&mut self) -> Result<()> {
-        let len = self.len();
+        self.clear_table()?;
+
         let capacity = self.capacity();
+        let probe = self.probe;
+        let hasher = self.hasher;
+        let removed = self.removed_ids();
 
-        let (mut table, mut kv_storage) = self.kv_and_table_mut()?;
+        let (mut table, mut hashes, kv_storage) = self.table_hashes_kv_mut()?;
 
-        for i in 0..len as u32 {
-            let kv = Self::entry_by_id(i, &kv_storage).unwrap();
-            Self::map_kv_pair(kv.key(), i, capacity, &mut table, &mut kv_storage)?;
+        for i in 0..kv_storage.count() as u32 {
+            if removed[i as usize] {
+                continue;
+            }
+            let key: K = kvpair::decode_key(kv_storage.get(i as usize)?)?;
+            let key_hash = hasher.hash(&key);
+            if let Some(distance) = H::ROBIN_HOOD {
+                Self::robin_hood_place(distance, i, key_hash, capacity, &mut table, &mut hashes)?;
+            } else if let Search::Vacant { pos, .. } =
+                Self::find_slot(&key, key_hash, capacity, probe, &table, &hashes, &kv_storage)?
+            {
+                Self::set_slot(pos, i, key_hash, &mut table, &mut hashes)?;
+            }
         }
 
-        Ok(())
+        self.deleted = 0;
+        self.set_metadata(self.metadata())
     }
 
     /// Grows the Map to the next prime of nth power of 2 so that the load factor is <= `MAX_LOAD` for a single new entry.
     /// This rehashes all entries and should only be called if the load factor is getting bigger than the
-    /// given limit (but hasn't exceeded this limit yet).
+    /// given limit (but hasn't exceeded this limit yet). If the map doesn't need to grow because the load is caused by
+    /// slots marked as deleted, the map gets rehashed in place instead.
     fn grow(&mut self) -> Result<usize> {
-        self.grow_to(self.len() + 1)
+        let grown = self.grow_to(self.len() + 1)?;
+        if grown == 0 && self.deleted > 0 {
+            self.rehash()?;
+        }
+        Ok(grown)
+    }
+
+    /// Enables incremental rehashing, which migrates `steps` slots of the table per insertion into a bigger table
+    /// instead of rehashing all entries at once when the map grows. Lookups keep using the current table until all of
+    /// its slots have been migrated. If the current table gets full before, which can happen for less than 2 steps,
+    /// the remaining slots get migrated at once. Maps using Robin Hood hashing or having a relevance order always
+    /// rehash at once. The setting isn't stored in the backend.
+    #[inline]
+    pub fn set_incremental_rehash(&mut self, steps: Option<usize>) {
+        self.rehash_steps = steps.map(|i| i.max(1));
+    }
+
+    /// Returns the amount of slots migrated per insertion for incremental rehashing or `None` if it is disabled.
+    #[inline]
+    pub fn incremental_rehash(&self) -> Option<usize> {
+        self.rehash_steps
+    }
+
+    /// Returns `true` if an incremental rehash is in progress.
+    #[inline]
+    pub fn is_rehashing(&self) -> bool {
+        self.migration.is_some()
+    }
+
+    /// Migrates all remaining slots of an incremental rehash in progress.
+    #[inline]
+    pub fn finish_rehash(&mut self) -> Result<()> {
+        self.migrate(self.capacity)
+    }
+
+    /// Returns the load factor at which inserting starts to rehash the map.
+    #[inline]
+    fn rehash_load(&self) -> f32 {
+        if self.rehash_steps.is_some() && H::ROBIN_HOOD.is_none() && self.relevance_id().is_none() {
+            INCREMENTAL_LOAD
+        } else {
+            MAX_LOAD
+        }
+    }
+
+    /// Advances an incremental rehash, starting a new one if the map is loaded enough. Rehashes in progress get
+    /// finished at once if incremental rehashing is disabled, eg. for maps initialized again while rehashing.
+    fn rehash_step(&mut self) -> Result<()> {
+        let Some(steps) = self.rehash_steps.filter(|_| !self.need_grow()) else {
+            return self.finish_rehash();
+        };
+
+        if self.migration.is_none() {
+            if H::ROBIN_HOOD.is_some()
+                || self.relevance_id().is_some()
+                || self.load_factor_for(self.len + self.deleted + 1) < INCREMENTAL_LOAD
+            {
+                return Ok(());
+            }
+            self.start_migration()?;
+        }
+
+        self.migrate(steps)
+    }
+
+    /// Prepares the unused table entries for an incremental rehash.
+    fn start_migration(&mut self) -> Result<()> {
+        // Until the current table gets full there can't be more than 3/4 of its capacity entries to migrate, so the
+        // new table never runs out of slots. Maps loaded mostly by deleted slots keep their capacity.
+        let capacity = Self::capacity_for(((self.len + 1) * 2).max(self.capacity * 3 / 4));
+        let (table_id, hashes_id) = self.next_table_ids();
+
+        let mut table: ListU32<_> = self.backend.get_backend_mut(table_id).ok_or(Error::Initialization)?;
+        table.clear();
+        table.grow_for_exact(capacity)?;
+        table.set_len(capacity)?;
+        table.mem_set(0)?;
+
+        let mut hashes: ListU64<_> = self.backend.get_backend_mut(hashes_id).ok_or(Error::Initialization)?;
+        hashes.clear();
+        hashes.grow_for_exact(capacity)?;
+        hashes.set_len(capacity)?;
+
+        self.migration = Some(Migration { capacity, pos: 0, deleted: 0 });
+        self.set_metadata(self.metadata())
+    }
+
+    /// Migrates the next `slots` slots of an incremental rehash in progress and switches to the new table once all
+    /// slots have been migrated. Keys get placed using their cached hashes, so no KV pairs need to be decoded.
+    fn migrate(&mut self, slots: usize) -> Result<()> {
+        let Some(mut migration) = self.migration else {
+            return Ok(());
+        };
+        let end = (migration.pos + slots).min(self.capacity);
+        let probe = self.probe;
+
+        let (table_id, hashes_id) = self.table_ids();
+        let (next_table_id, next_hashes_id) = self.next_table_ids();
+        let [table, hashes, next_table, next_hashes] = self
+            .backend
+            .get_n_by_index_mut([table_id, hashes_id, next_table_id, next_hashes_id])
+            .ok_or(Error::Initialization)?;
+        let (table, hashes) = (ListU32::init(table)?, ListU64::init(hashes)?);
+        let (mut next_table, mut next_hashes) = (ListU32::init(next_table)?, ListU64::init(next_hashes)?);
+
+        for pos in migration.pos..end {
+            if let Slot::Occupied(kv_id) = Self::read_slot(pos as u64, &table) {
+                let key_hash = Self::slot_hash(pos, &hashes);
+                Self::place_new(kv_id, key_hash, migration.capacity, probe, &mut next_table, &mut next_hashes)?;
+            }
+        }
+
+        migration.pos = end;
+        if end < self.capacity {
+            self.migration = Some(migration);
+            return self.set_metadata(self.metadata());
+        }
+
+        self.alt_table = !self.alt_table;
+        self.capacity = migration.capacity;
+        self.deleted = migration.deleted;
+        self.migration = None;
+        self.clear_next_table()?;
+        self.shrink_next_table()?;
+        self.set_metadata(self.metadata())
+    }
+
+    /// Places a newly inserted KV pair into the new table of an incremental rehash in progress if its slot at `pos`
+    /// has already been migrated. Slots after the migrated ones get migrated later on.
+    fn insert_migrated(&mut self, kv_id: u32, key_hash: u64, pos: usize) -> Result<()> {
+        let Some(migration) = self.migration.filter(|i| pos < i.pos) else {
+            return Ok(());
+        };
+        let probe = self.probe;
+        let (table_id, hashes_id) = self.next_table_ids();
+        let [table, hashes] = self.backend.get_n_by_index_mut([table_id, hashes_id]).ok_or(Error::Initialization)?;
+        let (mut table, mut hashes) = (ListU32::init(table)?, ListU64::init(hashes)?);
+        Self::place_new(kv_id, key_hash, migration.capacity, probe, &mut table, &mut hashes)
+    }
+
+    /// Marks the slot of a removed KV pair as deleted in the new table of an incremental rehash in progress if its slot
+    /// at `pos` has already been migrated.
+    fn remove_migrated(&mut self, kv_id: u32, pos: usize) -> Result<()> {
+        let Some(mut migration) = self.migration.filter(|i| pos < i.pos) else {
+            return Ok(());
+        };
+        let key_hash = Self::slot_hash(pos, &self.key_hashes());
+        let probe = self.probe;
+        let (table_id, _) = self.next_table_ids();
+        let mut table: ListU32<_> = self.backend.get_backend_mut(table_id).ok_or(Error::Initialization)?;
+
+        let new_pos = (0..H::probe_len(migration.capacity))
+            .map(|i| H::probe(probe, key_hash, i, migration.capacity))
+            .find(|i| Self::read_slot(*i, &table) == Slot::Occupied(kv_id))
+            .ok_or(Error::UnexpectedValue)?;
+        table.set_raw(new_pos as usize, &TOMBSTONE.to_le_bytes())?;
+
+        migration.deleted += 1;
+        self.migration = Some(migration);
+        Ok(())
     }
 }
 
@@ -206,23 +758,176 @@ impl<B, K, V, H> FMap<B, K, V, H>
         K: hashing::Hash + Eq + Deser,
         V: DeserializeOwned,
 {
+    /// Returns the value of `k`. The key may be any borrowed form of the maps key type, like `&str` for `String` keys,
+    /// as long as both hash the same way.
     #[inline]
-    pub fn get(&self, k: &K) -> Option<V> {
+    pub fn get<Q>(&self, k: &Q) -> Option<V>
+        where
+            K: Borrow<Q>,
+            Q: hashing::Hash + Eq + ?Sized,
+    {
         self.get_debug(k).map(|i| i.0)
     }
 
-    pub(crate) fn get_debug(&self, k: &K) -> Option<(V, usize)> {
-        let key_hash = k.hash();
+    /// Looks up `k` and decodes its entry into `pair`, reusing the memory `pair` already holds. This avoids allocating
+    /// for each lookup if the same `pair` is passed to many calls. Returns `true` if the key has been found, in which
+    /// case the value is stored in `pair.1`. Otherwise the content of `pair` is unspecified.
+    pub fn get_into(&self, k: &K, pair: &mut (K, V)) -> Result<bool> {
+        let key_hash = self.hasher.hash(k);
+
+        let table_list = self.hash_table();
+        let hashes = self.key_hashes();
+        let kv_storage = self.entry_storage();
+
+        for i in 0..H::probe_len(self.capacity) {
+            let hash = H::probe(self.probe, key_hash, i, self.capacity);
+            let kv_pair_id = match Self::read_slot(hash, &table_list) {
+                Slot::Empty => return Ok(false),
+                Slot::Deleted => continue,
+                Slot::Occupied(id) => id,
+            };
+            if Self::slot_hash(hash as usize, &hashes) != key_hash {
+                continue;
+            }
+
+            kvpair::decode_into(kv_storage.get(kv_pair_id as usize)?, pair)?;
+            if pair.0 == *k {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
 
+    /// Looks up all `keys` at once. All keys get hashed first and the table slots as well as the entries they point to
+    /// are prefetched before resolving the lookups, so page faults of a cold map are amortized over the whole batch.
+    pub fn get_many(&self, keys: &[&K]) -> Vec<Option<V>> {
         let table_list = self.hash_table();
+        let key_hashes = self.key_hashes();
+        let kv_storage = self.entry_storage();
+
+        let hashes: Vec<u64> = keys.iter().map(|k| self.hasher.hash(*k)).collect();
+
+        let slots: Vec<u64> = hashes
+            .iter()
+            .map(|h| H::f_dyn(self.probe, *h, 0, self.capacity))
+            .collect();
+        for slot in slots.iter() {
+            table_list.prefetch(*slot as usize);
+            key_hashes.prefetch(*slot as usize);
+        }
+
+        for slot in slots {
+            if let Some(kv_id) = Self::resolve_hash(slot, &table_list) {
+                kv_storage.prefetch(kv_id as usize);
+            }
+        }
+
+        keys.iter()
+            .zip(hashes)
+            .map(|(k, h)| self.lookup(k, h, &table_list, &key_hashes, &kv_storage).map(|i| i.0))
+            .collect()
+    }
+
+    /// Same as `get_many` but for a fixed amount of keys, returning the values as array.
+    #[inline]
+    pub fn get_many_n<const N: usize>(&self, keys: &[&K; N]) -> [Option<V>; N] {
+        let mut values = self.get_many(keys).into_iter();
+        std::array::from_fn(|_| values.next().unwrap())
+    }
+
+    /// Returns `true` if the map contains a value for the given key. Only keys get decoded, values are never touched.
+    /// Same as for `get`, the key may be any borrowed form of the maps key type.
+    #[inline]
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+        where
+            K: Borrow<Q>,
+            Q: hashing::Hash + Eq + ?Sized,
+    {
+        let key_hash = self.hasher.hash(k);
+        self.find_key(k, key_hash, &self.hash_table(), &self.key_hashes(), &self.entry_storage()).is_some()
+    }
+
+    /// Returns the encoded bytes of the value of `k` without copying or deserializing them. Values are encoded using
+    /// bincode with fixed size integers, so for example a `Vec<u8>` value starts with its length as u64 LE.
+    pub fn get_raw<Q>(&self, k: &Q) -> Option<&[u8]>
+        where
+            K: Borrow<Q>,
+            Q: hashing::Hash + Eq + ?Sized,
+    {
+        let key_hash = self.hasher.hash(k);
         let kv_storage = self.entry_storage();
+        let (kv_id, _) = self.find_key(k, key_hash, &self.hash_table(), &self.key_hashes(), &kv_storage)?;
+        let raw = kv_storage.get_borrowed(kv_id as usize).ok()?;
+        let value_start = kvpair::value_offset::<K>(raw).ok()?;
+        Some(&raw[value_start..])
+    }
+
+    /// Same as `get_raw` but returns the encoded value as `FullBackend`, which allows opening nested components that
+    /// are stored inside the value.
+    #[inline]
+    pub fn get_backend<Q>(&self, k: &Q) -> Option<FullBackend<&[u8]>>
+        where
+            K: Borrow<Q>,
+            Q: hashing::Hash + Eq + ?Sized,
+    {
+        self.get_raw(k).map(FullBackend::new)
+    }
+
+    pub(crate) fn get_debug<Q>(&self, k: &Q) -> Option<(V, usize)>
+        where
+            K: Borrow<Q>,
+            Q: hashing::Hash + Eq + ?Sized,
+    {
+        let key_hash = self.hasher.hash(k);
+        self.lookup(k, key_hash, &self.hash_table(), &self.key_hashes(), &self.entry_storage())
+    }
+
+    /// Looks up `k` using its precomputed `key_hash` and returns the value along with the amount of probes needed.
+    fn lookup<Q, E: Backend>(
+        &self,
+        k: &Q,
+        key_hash: u64,
+        table_list: &ListU32<E>,
+        hashes: &ListU64<E>,
+        kv_storage: &IndexedFile<E>,
+    ) -> Option<(V, usize)>
+        where
+            K: Borrow<Q>,
+            Q: Eq + ?Sized,
+    {
+        let (kv_pair_id, probes) = self.find_key(k, key_hash, table_list, hashes, kv_storage)?;
+        let kv_item: KVPair<K, V> = Self::entry_by_id(kv_pair_id, kv_storage).unwrap();
+        Some((kv_item.into_value(), probes))
+    }
 
-        for i in 0..self.capacity {
-            let hash = H::f(key_hash, i, self.capacity);
-            let kv_pair_id = Self::resolve_hash(hash, &table_list)?;
-            let kv_item: KVPair<K, V> = Self::entry_by_id(kv_pair_id, &kv_storage).unwrap();
-            if kv_item.key() == k {
-                return Some((kv_item.into_value(), i));
+    /// Looks up `k` using its precomputed `key_hash` and returns the ID of its KV pair along with the amount of probes
+    /// needed. Only the keys of probed KV pairs with a matching key hash get decoded.
+    fn find_key<Q, E: Backend>(
+        &self,
+        k: &Q,
+        key_hash: u64,
+        table_list: &ListU32<E>,
+        hashes: &ListU64<E>,
+        kv_storage: &IndexedFile<E>,
+    ) -> Option<(u32, usize)>
+        where
+            K: Borrow<Q>,
+            Q: Eq + ?Sized,
+    {
+        for i in 0..H::probe_len(self.capacity) {
+            let hash = H::probe(self.probe, key_hash, i, self.capacity);
+            let kv_pair_id = match Self::read_slot(hash, table_list) {
+                Slot::Empty => return None,
+                Slot::Deleted => continue,
+                Slot::Occupied(id) => id,
+            };
+            if Self::slot_hash(hash as usize, hashes) != key_hash {
+                continue;
+            }
+            let key: K = kvpair::decode_key(kv_storage.get(kv_pair_id as usize).ok()?).unwrap();
+            if key.borrow() == k {
+                return Some((kv_pair_id, i));
             }
         }
 
@@ -232,29 +937,50 @@ impl<B, K, V, H> FMap<B, K, V, H>
     /// Hashes all elements in the map using a comparing function that prefers some items over other ones when a collision occurs.
     /// This means that, in average, the items that have a higher order (defined by the `compare` function) will have less to zero
     /// collisions making lookup for those faster and potentially other items with less relevance slower.
-    pub fn rehash_with_relevance<R>(&mut self, mut compare: R) -> Result<()>
+    #[inline]
+    pub fn rehash_with_relevance<R>(&mut self, compare: R) -> Result<()>
+        where
+            R: FnMut(&KVPair<K, V>, &KVPair<K, V>) -> Ordering,
+    {
+        self.rehash_with_relevance_id(UNNAMED_RELEVANCE, compare)
+    }
+
+    /// Same as `rehash_with_relevance` but stores the given `id` in the maps metadata so the used relevance function
+    /// can be identified later on, eg. after loading the map again. `id` must not be 0.
+    pub fn rehash_with_relevance_id<R>(&mut self, id: u32, mut compare: R) -> Result<()>
         where
             R: FnMut(&KVPair<K, V>, &KVPair<K, V>) -> Ordering,
     {
+        if id == NO_RELEVANCE {
+            return Err(Error::UnexpectedValue);
+        }
+
         self.clear_table()?;
 
         // We don't change the amount of entries or capacity here so they don't need to be reevaluated in the algorithm.
         let len = self.len();
         let capacity = self.capacity();
+        let probe = self.probe;
+        let hasher = self.hasher;
+        let removed = self.removed_ids();
 
-        let (mut table, kv_storage) = self.kv_and_table_mut()?;
+        let (mut table, mut hashes, kv_storage) = self.table_hashes_kv_mut()?;
 
         let mut mapped_entries = 0;
 
         // Iterate over all entries
-        for mut entry_id in 0..len as u32 {
+        for mut entry_id in 0..kv_storage.count() as u32 {
+            if removed[entry_id as usize] {
+                continue;
+            }
+
             // Current entry we want to find a position in the table for.
             let mut entry = Self::entry_by_id(entry_id, &kv_storage).unwrap();
-            let mut key_hash = entry.key().hash();
+            let mut key_hash = hasher.hash(entry.key());
 
             let mut i = 0;
             loop {
-                let hash = H::f(key_hash, i, capacity);
+                let hash = H::probe(probe, key_hash, i, capacity);
 
                 // Hashed position already occupied
                 if let Some(pair_id) = Self::resolve_hash(hash, &table) {
@@ -270,14 +996,14 @@ impl<B, K, V, H> FMap<B, K, V, H>
                     }
 
                     // Swap occupied entry with current entry if it has a higher relevance.
-                    Self::set_table_kvid(hash as usize, entry_id, &mut table)?;
+                    Self::set_slot(hash as usize, entry_id, key_hash, &mut table, &mut hashes)?;
                     entry_id = pair_id;
-                    key_hash = occupied.key().hash();
+                    key_hash = hasher.hash(occupied.key());
                     entry = occupied;
 
                     i = 0;
                     loop {
-                        if H::f(key_hash, i, capacity) == hash {
+                        if H::probe(probe, key_hash, i, capacity) == hash {
                             break;
                         }
 
@@ -285,7 +1011,7 @@ impl<B, K, V, H> FMap<B, K, V, H>
                     }
                 } else {
                     mapped_entries += 1;
-                    Self::set_table_kvid(hash as usize, entry_id, &mut table)?;
+                    Self::set_slot(hash as usize, entry_id, key_hash, &mut table, &mut hashes)?;
                     break;
                 }
             }
@@ -293,7 +1019,8 @@ impl<B, K, V, H> FMap<B, K, V, H>
 
         assert_eq!(mapped_entries, len);
 
-        Ok(())
+        self.deleted = 0;
+        self.set_relevance(id)
     }
 }
 
@@ -311,18 +1038,199 @@ impl<B, K, V, H> FMap<B, K, V, H>
     }
 }
 
+impl<B, K, V, H> FMap<B, K, V, H>
+    where
+        H: HashFn,
+        B: Backend,
+        K: Eq + DeserializeOwned,
+        V: DeserializeOwned,
+{
+    /// Searches the slot of `key` in the table.
+    #[inline]
+    fn search(&self, key: &K, key_hash: u64) -> Result<Search> {
+        Self::find_slot(
+            key,
+            key_hash,
+            self.capacity,
+            self.probe,
+            &self.hash_table(),
+            &self.key_hashes(),
+            &self.entry_storage(),
+        )
+    }
+
+    /// Searches the slot of `key` in `table`. Returns the ID of its KV pair if the key is stored, otherwise the first
+    /// slot the key can be inserted into, preferring slots marked as deleted.
+    fn find_slot<E1: Backend, E2: Backend>(
+        key: &K,
+        key_hash: u64,
+        capacity: usize,
+        probe: Option<ProbeStrategy>,
+        table: &ListU32<E1>,
+        hashes: &ListU64<E1>,
+        kv_storage: &IndexedFile<E2>,
+    ) -> Result<Search> {
+        let mut deleted = None;
+        for i in 0..H::probe_len(capacity) {
+            let hash = H::probe(probe, key_hash, i, capacity) as usize;
+
+            match Self::read_slot(hash as u64, table) {
+                Slot::Empty => {
+                    let (probes, pos) = deleted.unwrap_or((i, hash));
+                    return Ok(Search::Vacant { probes, pos, reuse: deleted.is_some() });
+                }
+                Slot::Deleted => {
+                    deleted.get_or_insert((i, hash));
+                }
+                Slot::Occupied(kv_id) if Self::slot_hash(hash, hashes) == key_hash => {
+                    let stored: K = kvpair::decode_key(kv_storage.get(kv_id as usize)?)?;
+                    if stored == *key {
+                        return Ok(Search::Found { kv_id, probes: i, pos: hash });
+                    }
+                }
+                Slot::Occupied(_) => {}
+            }
+        }
+
+        // The probe sequence visits every slot, so this only happens for tables without any free slot.
+        let (probes, pos) = deleted.ok_or(Error::OutOfBounds)?;
+        Ok(Search::Vacant { probes, pos, reuse: true })
+    }
+
+    /// Maps `kv_id` into the table using Robin Hood hashing: Walking along the probe sequence, each entry being closer to
+    /// its home slot than the entry to place gets displaced by it and is placed further along its own sequence
+    /// instead. `distance` is the one of the `RobinHood` hash function. Returns the slot `kv_id` has been placed at
+    /// and whether a slot marked as deleted has been reused.
+    fn robin_hood_place<E: Backend>(
+        distance: fn(u64, usize, usize) -> usize,
+        mut kv_id: u32,
+        mut key_hash: u64,
+        capacity: usize,
+        table: &mut ListU32<E>,
+        hashes: &mut ListU64<E>,
+    ) -> Result<(usize, bool)> {
+        let mut placed_at = None;
+        let mut dist = 0;
+        loop {
+            let pos = H::f(key_hash, dist, capacity) as usize;
+            match Self::read_slot(pos as u64, table) {
+                Slot::Empty | Slot::Deleted => {
+                    let reuse = Self::read_slot(pos as u64, table) == Slot::Deleted;
+                    Self::set_slot(pos, kv_id, key_hash, table, hashes)?;
+                    return Ok((placed_at.unwrap_or(pos), reuse));
+                }
+                Slot::Occupied(other) => {
+                    let other_hash = Self::slot_hash(pos, hashes);
+                    let other_dist = distance(other_hash, pos, capacity);
+                    if other_dist < dist {
+                        Self::set_slot(pos, kv_id, key_hash, table, hashes)?;
+                        placed_at.get_or_insert(pos);
+                        (kv_id, key_hash, dist) = (other, other_hash, other_dist);
+                    }
+                }
+            }
+            dist += 1;
+        }
+    }
+
+    /// Maps `kv_id` into the first empty slot along its probe sequence, assuming its key isn't stored in `table` yet.
+    fn place_new<E: Backend>(
+        kv_id: u32,
+        key_hash: u64,
+        capacity: usize,
+        probe: Option<ProbeStrategy>,
+        table: &mut ListU32<E>,
+        hashes: &mut ListU64<E>,
+    ) -> Result<()> {
+        let pos = (0..H::probe_len(capacity))
+            .map(|i| H::probe(probe, key_hash, i, capacity) as usize)
+            .find(|i| Self::read_slot(*i as u64, table) == Slot::Empty)
+            .ok_or(Error::OutOfBounds)?;
+        Self::set_slot(pos, kv_id, key_hash, table, hashes)
+    }
+}
+
 impl<B, K, V, H> FMap<B, K, V, H>
     where
         B: GrowableBackend,
         V: Serialize,
         K: Serialize,
 {
-    /// Inserts a key with its value into the KV pair storage returning its ID.
-    #[inline]
+    /// Inserts a key with its value into the KV pair storage returning its ID. The storage of removed pairs gets
+    /// reused first, which gets recorded in the metadata written by the caller along with the new length.
     fn insert_entry(&mut self, pair: &KVPair<&K, &V>) -> Result<u32> {
         let enc = bincode::serialize(pair)?;
+
+        let mut free = self.free_list_mut();
+        if let Some(last) = free.len().checked_sub(1) {
+            let id = free.get(last)?;
+            free.pop()?;
+            self.kv_storage_mut().entry(id as usize)?.set(&enc)?;
+            self.ids_reused = true;
+            return Ok(id);
+        }
+
         Ok(self.kv_storage_mut().insert(&enc)? as u32)
     }
+
+    /// Replaces the KV pair with the given ID.
+    #[inline]
+    fn set_entry(&mut self, kv_id: u32, pair: &KVPair<&K, &V>) -> Result<()> {
+        let enc = bincode::serialize(pair)?;
+        self.kv_storage_mut().entry(kv_id as usize)?.set(&enc)?;
+        Ok(())
+    }
+}
+
+impl<B, K, V, H> FMap<B, K, V, H>
+    where
+        H: HashFn,
+        B: Backend,
+{
+    /// Returns statistics about the probe lengths, load and wasted space of the map. This scans the whole hash table
+    /// but doesn't decode any KV pairs.
+    pub fn stats(&self) -> MapStats {
+        let table = self.hash_table();
+        let hashes = self.key_hashes();
+
+        let mut probe_histogram = vec![];
+        let mut total_probes = 0;
+        for pos in 0..self.capacity {
+            if !matches!(Self::read_slot(pos as u64, &table), Slot::Occupied(_)) {
+                continue;
+            }
+            let key_hash = Self::slot_hash(pos, &hashes);
+            let probes = (0..H::probe_len(self.capacity))
+                .find(|i| H::probe(self.probe, key_hash, *i, self.capacity) as usize == pos)
+                .unwrap_or_default();
+            if probe_histogram.len() <= probes {
+                probe_histogram.resize(probes + 1, 0);
+            }
+            probe_histogram[probes] += 1;
+            total_probes += probes;
+        }
+
+        // Removed KV pairs keep their offset in the KV storage's index and an entry in the free list.
+        let kv_storage = self.entry_storage();
+        let removed_bytes: usize = self
+            .removed_ids()
+            .iter()
+            .enumerate()
+            .filter(|i| *i.1)
+            .map(|i| kv_storage.entry_index(i.0).map(|i| i.len()).unwrap_or_default() + 8 + 4)
+            .sum();
+
+        MapStats {
+            len: self.len,
+            capacity: self.capacity,
+            load_factor: self.load_factor(),
+            deleted: self.deleted,
+            max_probes: probe_histogram.len().saturating_sub(1),
+            avg_probes: if self.len > 0 { total_probes as f32 / self.len as f32 } else { 0.0 },
+            probe_histogram,
+            wasted_bytes: removed_bytes + self.deleted * (4 + 8),
+        }
+    }
 }
 
 impl<B, K, V, H> FMap<B, K, V, H>
@@ -334,6 +1242,26 @@ impl<B, K, V, H> FMap<B, K, V, H>
         MapIter::new(self)
     }
 
+    /// Returns an iterator over all keys of the map. Values don't get decoded.
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, B, K, V, H> {
+        Keys::new(self)
+    }
+
+    /// Returns an iterator over all values of the map.
+    #[inline]
+    pub fn values(&self) -> Values<'_, B, K, V, H> {
+        Values::new(self)
+    }
+
+    /// Returns an iterator over all pairs in the order they got inserted. This walks the KV storage instead of the
+    /// hash table, which is a lot faster for sparse tables. Returns `None` once an insert reused the ID of a removed
+    /// pair, as the KV storage doesn't hold the pairs in insertion order anymore. Only clearing the map restores it.
+    #[inline]
+    pub fn iter_insertion_order(&self) -> Option<InsertionOrderIter<'_, K, V>> {
+        (!self.ids_reused).then(|| InsertionOrderIter::new(self))
+    }
+
     /// Returns the HashTable and KVStorage both mutable.
     fn kv_and_table_mut(
         &mut self,
@@ -341,14 +1269,41 @@ impl<B, K, V, H> FMap<B, K, V, H>
         ListU32<GeneralSubMutBackend>,
         IndexedFile<GeneralSubMutBackend>,
     )> {
-        let (header_be, kv_be) = self.backend.get_two_mut(1, 2)?;
+        let (header_be, kv_be) = self.backend.get_two_mut(self.table_ids().0, 2)?;
         Ok((ListU32::init(header_be)?, IndexedFile::init(kv_be)?))
     }
 
+    /// Returns the HashTable, the key hashes of its slots and the KVStorage, all mutable.
+    #[allow(clippy::type_complexity)]
+    fn table_hashes_kv_mut(
+        &mut self,
+    ) -> Result<(
+        ListU32<GeneralSubMutBackend<'_>>,
+        ListU64<GeneralSubMutBackend<'_>>,
+        IndexedFile<GeneralSubMutBackend<'_>>,
+    )> {
+        let (table_id, hashes_id) = self.table_ids();
+        let [table_be, kv_be, hashes_be] =
+            self.backend.get_n_by_index_mut([table_id, 2, hashes_id]).ok_or(Error::Initialization)?;
+        Ok((ListU32::init(table_be)?, ListU64::init(hashes_be)?, IndexedFile::init(kv_be)?))
+    }
+
     /// Returns the table list.
     #[inline]
     fn hash_table(&self) -> ListU32<BaseSubBackend<&[u8]>> {
-        self.backend.get_backend(1).unwrap()
+        self.backend.get_backend(self.table_ids().0).unwrap()
+    }
+
+    /// Returns the key hashes of the table slots.
+    #[inline]
+    fn key_hashes(&self) -> ListU64<BaseSubBackend<'_, &[u8]>> {
+        self.backend.get_backend(self.table_ids().1).unwrap()
+    }
+
+    /// Returns the key hash stored for the table slot at `pos`.
+    #[inline]
+    fn slot_hash<E: Backend>(pos: usize, hashes: &ListU64<E>) -> u64 {
+        u64::from_le_bytes(hashes.get_raw(pos).unwrap().try_into().unwrap())
     }
 
     /// Returns the Key-Value-pair storage.
@@ -357,17 +1312,70 @@ impl<B, K, V, H> FMap<B, K, V, H>
         self.backend.get_backend(2).unwrap()
     }
 
-    /// Returns the KV storage ID for a given hash value.
+    /// Drops an incremental rehash in progress. The current table always holds all entries, so this is done whenever
+    /// the table gets rebuilt anyway.
+    fn abort_rehash(&mut self) -> Result<()> {
+        if self.migration.take().is_none() {
+            return Ok(());
+        }
+        self.clear_next_table()?;
+        self.set_metadata(self.metadata())
+    }
+
+    /// Clears the table entries not in use by the hash table.
+    fn clear_next_table(&mut self) -> Result<()> {
+        let (table_id, hashes_id) = self.next_table_ids();
+        let mut table: ListU32<_> = self.backend.get_backend_mut(table_id).ok_or(Error::Initialization)?;
+        table.clear();
+        let mut hashes: ListU64<_> = self.backend.get_backend_mut(hashes_id).ok_or(Error::Initialization)?;
+        hashes.clear();
+        Ok(())
+    }
+
+    /// Returns a flag for each KV pair ID telling whether the pair has been removed.
+    fn removed_ids(&self) -> Vec<bool> {
+        let mut removed = vec![false; self.entry_storage().count()];
+        let free: ListU32<BaseSubBackend<'_, &[u8]>> = self.backend.get_backend(FREE_ID).unwrap();
+        for i in 0..free.len() {
+            removed[free.get(i).unwrap() as usize] = true;
+        }
+        removed
+    }
+
+    /// Returns the KV storage ID for a given hash value or `None` if the slot isn't occupied.
+    #[inline]
     fn resolve_hash<E: Backend>(hash: u64, table_list: &ListU32<E>) -> Option<u32> {
-        let e: [u8; 4] = table_list.get_raw(hash as usize).ok()?.try_into().unwrap();
-        let e = u32::from_le_bytes(e);
-        if e > 0 {
-            Some(e - 1)
-        } else {
-            None
+        match Self::read_slot(hash, table_list) {
+            Slot::Occupied(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Returns the state of the table slot at `hash`.
+    fn read_slot<E: Backend>(hash: u64, table_list: &ListU32<E>) -> Slot {
+        let Ok(raw) = table_list.get_raw(hash as usize) else {
+            return Slot::Empty;
+        };
+        match u32::from_le_bytes(raw.try_into().unwrap()) {
+            0 => Slot::Empty,
+            TOMBSTONE => Slot::Deleted,
+            e => Slot::Occupied(e - 1),
         }
     }
 
+    /// Inserts the given `kv_id` along with its `key_hash` into the hash table at the given position.
+    #[inline]
+    fn set_slot<E: Backend>(
+        pos: usize,
+        kv_id: u32,
+        key_hash: u64,
+        table: &mut ListU32<E>,
+        hashes: &mut ListU64<E>,
+    ) -> Result<()> {
+        Self::set_table_kvid(pos, kv_id, table)?;
+        hashes.set_raw(pos, &key_hash.to_le_bytes())
+    }
+
     /// Inserts the given `kv_id` into the hash table at the given position.
     #[inline]
     fn set_table_kvid<E: Backend>(pos: usize, kv_id: u32, table: &mut ListU32<E>) -> Result<()> {
@@ -378,13 +1386,25 @@ impl<B, K, V, H> FMap<B, K, V, H>
 
     /// Clears all entries from the map.
     pub fn clear(&mut self) -> Result<()> {
+        self.abort_rehash()?;
         let (mut table, mut kv_storage) = self.kv_and_table_mut()?;
 
         Self::table_clear(&mut table)?;
         kv_storage.clear();
+        let mut free: ListU32<_> = self.backend.get_backend_mut(FREE_ID).ok_or(Error::Initialization)?;
+        free.clear();
 
         self.len = 0;
-        Ok(())
+        self.deleted = 0;
+        self.relevance = NO_RELEVANCE;
+        self.ids_reused = false;
+        self.set_metadata(self.metadata())
+    }
+
+    /// Removes the relevance marker of the map, allowing it to grow again without re-applying the relevance order.
+    #[inline]
+    pub fn clear_relevance(&mut self) -> Result<()> {
+        self.set_relevance(NO_RELEVANCE)
     }
 
     /// Flushes the whole map.
@@ -404,20 +1424,27 @@ impl<B, K, V, H> FMap<B, K, V, H>
     /// Increments the maps length by `amount`.
     #[inline]
     fn inc_len(&mut self, amount: usize) -> Result<()> {
-        self.set_metadata(MapMetadata::new(self.len + amount, self.capacity))?;
         self.len += amount;
-        Ok(())
+        self.set_metadata(self.metadata())
     }
 
     /// Sets the maps capacity to `capacity`
     #[inline]
     fn set_capacity(&mut self, capacity: usize) -> Result<()> {
         self.capacity = capacity;
-        self.set_metadata(MapMetadata::new(self.len, capacity))
+        self.set_metadata(self.metadata())
+    }
+
+    /// Sets the maps relevance ID to `relevance`
+    #[inline]
+    fn set_relevance(&mut self, relevance: u32) -> Result<()> {
+        self.relevance = relevance;
+        self.set_metadata(self.metadata())
     }
 
     /// Clears the hash table.
     fn clear_table(&mut self) -> Result<()> {
+        self.abort_rehash()?;
         let (mut table, _) = self.kv_and_table_mut()?;
         Self::table_clear(&mut table)?;
         Ok(())
@@ -438,7 +1465,7 @@ impl<B, K, V, H> FMap<B, K, V, H>
     /// Returns the table list mutable.
     #[inline]
     fn table_list_mut(&mut self) -> ListU32<MFileEntryMut<B>> {
-        self.backend.get_backend_mut(1).unwrap()
+        self.backend.get_backend_mut(self.table_ids().0).unwrap()
     }
 
     /// Returns the Key-Value-pair storage mutable.
@@ -447,11 +1474,98 @@ impl<B, K, V, H> FMap<B, K, V, H>
         self.backend.get_backend_mut(2).unwrap()
     }
 
+    /// Returns the key hashes of the table slots mutable.
+    #[inline]
+    fn key_hashes_mut(&mut self) -> ListU64<MFileEntryMut<'_, B>> {
+        self.backend.get_backend_mut(self.table_ids().1).unwrap()
+    }
+
+    /// Returns the list of IDs of removed KV pairs mutable.
+    #[inline]
+    fn free_list_mut(&mut self) -> ListU32<MFileEntryMut<'_, B>> {
+        self.backend.get_backend_mut(FREE_ID).unwrap()
+    }
+
     /// Allocates more space to store n new entries with a total encoded size
     #[inline]
     pub fn preallocate_entries(&mut self, entry_count: usize, data_len: usize) -> Result<()> {
         self.kv_storage_mut().grow(entry_count, data_len)
     }
+
+    /// Rewrites the KV storage without the pairs flagged in `removed` and updates the hash table to the new IDs of the
+    /// remaining pairs.
+    fn compact(&mut self, removed: &[bool]) -> Result<()> {
+        self.abort_rehash()?;
+        let kv_storage = self.entry_storage();
+        let mut new_ids = vec![0u32; removed.len()];
+        let mut pairs = Vec::with_capacity(self.len);
+        for (id, _) in removed.iter().enumerate().filter(|i| !i.1) {
+            new_ids[id] = pairs.len() as u32;
+            pairs.push(kv_storage.get(id)?.to_vec());
+        }
+
+        let mut kv_storage = self.kv_storage_mut();
+        kv_storage.clear();
+        kv_storage.insert_n(&pairs)?;
+
+        let mut table = self.table_list_mut();
+        for pos in 0..table.len() {
+            if let Slot::Occupied(id) = Self::read_slot(pos as u64, &table) {
+                Self::set_table_kvid(pos, new_ids[id as usize], &mut table)?;
+            }
+        }
+
+        self.free_list_mut().clear();
+        Ok(())
+    }
+
+    /// Truncates the free space left in the KV-storage, the hash table, its key hashes, the list of removed IDs and the
+    /// unused table entries which usually remains from growing and shrinks the whole backend afterwards.
+    fn truncate_free_space(&mut self) -> Result<()> {
+        self.kv_storage_mut().shrink_to_fit()?;
+        self.table_list_mut().shrink_to_fit()?;
+        self.key_hashes_mut().shrink_to_fit()?;
+        self.free_list_mut().shrink_to_fit()?;
+        self.shrink_next_table()?;
+        self.backend.shrink_to_fit()
+    }
+
+    /// Truncates the free space of the table entries not in use by the hash table. Those only hold data while an
+    /// incremental rehash is in progress, so they don't keep a copy of the previous table once it finished.
+    fn shrink_next_table(&mut self) -> Result<()> {
+        let (table_id, hashes_id) = self.next_table_ids();
+        let mut table: ListU32<_> = self.backend.get_backend_mut(table_id).ok_or(Error::Initialization)?;
+        table.shrink_to_fit()?;
+        let mut hashes: ListU64<_> = self.backend.get_backend_mut(hashes_id).ok_or(Error::Initialization)?;
+        hashes.shrink_to_fit()
+    }
+
+    /// Shrinks the map if required by its shrink policy. Only the free space of the KV-storage is taken into account
+    /// and truncated, so the IDs of KV pairs and the hash table stay the same.
+    fn auto_shrink(&mut self) -> Result<()> {
+        let Some(tracker) = self.shrink.as_mut() else {
+            return Ok(());
+        };
+
+        let kv_storage: IndexedFile<_> = self.backend.get_backend(2).ok_or(Error::Initialization)?;
+        let (free, capacity) = kv_storage.space();
+        if tracker.record(free, capacity) {
+            self.truncate_free_space()?;
+        }
+        Ok(())
+    }
+}
+
+impl<B, K, V, H> AutoShrink for FMap<B, K, V, H> {
+    #[inline]
+    fn set_shrink_policy(&mut self, policy: Option<ShrinkPolicy>) {
+        self.shrink = policy.map(ShrinkTracker::new);
+    }
+
+    #[inline]
+    fn shrink_policy(&self) -> Option<ShrinkPolicy> {
+        self.shrink.map(|i| i.policy())
+    }
 }
 
 impl<B, K, V, H> FMap<B, K, V, H> {
@@ -470,10 +1584,66 @@ impl<B, K, V, H> FMap<B, K, V, H> {
         self.len == 0
     }
 
+    /// Returns the ID of the relevance function the map has been rehashed with or `None` if the map doesn't have
+    /// a relevance order.
+    #[inline]
+    pub fn relevance_id(&self) -> Option<u32> {
+        (self.relevance != NO_RELEVANCE).then_some(self.relevance)
+    }
+
+    /// Returns the probing strategy stored in the map or `None` if probing is defined by the maps type.
+    #[inline]
+    pub fn probe_strategy(&self) -> Option<ProbeStrategy> {
+        self.probe
+    }
+
+    /// Returns the hasher used to hash the maps keys.
+    #[inline]
+    pub fn hasher(&self) -> KeyHasher {
+        self.hasher
+    }
+
+    /// Returns the maps metadata.
+    #[inline]
+    fn metadata(&self) -> MapMetadata {
+        MapMetadata::new(
+            self.len,
+            self.capacity,
+            self.relevance,
+            self.probe,
+            self.hasher,
+            self.deleted,
+            self.alt_table,
+            self.ids_reused,
+            self.migration,
+        )
+    }
+
+    /// Returns the IDs of the MultiFile entries holding the hash table and its key hashes.
+    #[inline]
+    fn table_ids(&self) -> (usize, usize) {
+        if self.alt_table {
+            (ALT_TABLE_ID, ALT_HASHES_ID)
+        } else {
+            (1, HASHES_ID)
+        }
+    }
+
+    /// Returns the IDs of the MultiFile entries not in use by the hash table, which hold the new table while rehashing
+    /// incrementally.
+    #[inline]
+    fn next_table_ids(&self) -> (usize, usize) {
+        if self.alt_table {
+            (1, HASHES_ID)
+        } else {
+            (ALT_TABLE_ID, ALT_HASHES_ID)
+        }
+    }
+
     /// Returns `true` if the hashmap can fit additional new items without needing to grow.
     #[inline]
     pub fn can_fit(&self, additional: usize) -> bool {
-        self.load_factor_for(self.len() + additional) < MAX_LOAD
+        self.load_factor_for(self.len() + self.deleted + additional) < MAX_LOAD
     }
 
     /// Returns the Load factor of the Map.
@@ -482,6 +1652,14 @@ impl<B, K, V, H> FMap<B, K, V, H> {
         self.load_factor_for(self.len())
     }
 
+    /// Returns the smallest capacity holding `len` entries without exceeding `MAX_LOAD`. This is the next prime of the
+    /// nth power of 2.
+    fn capacity_for(len: usize) -> usize {
+        let need_cap = (len as f32 / MAX_LOAD).ceil() as usize;
+        let pow = smallest_two_power_for(need_cap) as usize;
+        primes::NEXT_PRIMES_OF_TWO[pow] as usize
+    }
+
     /// Returns the Load factor for a given length in the Map.
     #[inline]
     fn load_factor_for(&self, len: usize) -> f32 {
@@ -501,7 +1679,7 @@ impl<B, K, V, H> FMap<B, K, V, H> {
     /// Returns `true` if the map needs to grow and rehash for n new elements.
     #[inline]
     fn need_grow_for(&self, new_elements: usize) -> bool {
-        self.load_factor_for(self.len() + new_elements) >= MAX_LOAD
+        self.load_factor_for(self.len() + self.deleted + new_elements) >= MAX_LOAD
     }
 }
 
@@ -509,7 +1687,20 @@ impl<B, K, V, H> Creatable<B> for FMap<B, K, V, H>
     where
         B: GrowableBackend,
 {
+    /// Creates a new map using seeded SipHash-1-3 with a random seed to hash the keys.
+    #[inline]
     fn with_capacity(backend: B, capacity: usize) -> Result<Self> {
+        Self::with_hasher(backend, capacity, KeyHasher::random_sip())
+    }
+}
+
+impl<B, K, V, H> FMap<B, K, V, H>
+    where
+        B: GrowableBackend,
+{
+    /// Creates a new map using the given hasher to hash the keys. `KeyHasher::Fnv` is faster than the default
+    /// but should only be used if the keys are trusted.
+    pub fn with_hasher(backend: B, capacity: usize, hasher: KeyHasher) -> Result<Self> {
         let cap = primes::next_bigger_than(capacity) as usize;
 
         let mut backend = MultiFile::with_capacity(backend, cap)?;
@@ -517,7 +1708,7 @@ impl<B, K, V, H> Creatable<B> for FMap<B, K, V, H>
         let mut capacity_metadata = backend.insert_empty()?;
         capacity_metadata.grow_to(MapMetadata::byte_len())?;
         capacity_metadata
-            .push(&MapMetadata::new(0, cap).to_bytes())
+            .push(&MapMetadata::new(0, cap, NO_RELEVANCE, None, hasher, 0, false, false, None).to_bytes())
             .unwrap();
 
         let mut table: ListU32<_> = backend.insert_new_backend()?;
@@ -528,120 +1719,233 @@ impl<B, K, V, H> Creatable<B> for FMap<B, K, V, H>
         let mut kv_storage: IndexedFile<_> = backend.insert_new_backend()?;
         kv_storage.grow(cap, cap)?;
 
+        backend.insert_new_backend::<ListU32<_>>()?;
+
+        let mut hashes: ListU64<_> = backend.insert_new_backend()?;
+        hashes.grow_for_exact(cap)?;
+        hashes.set_len(cap)?;
+
+        backend.insert_new_backend::<ListU32<_>>()?;
+        backend.insert_new_backend::<ListU64<_>>()?;
+
         Ok(Self {
             backend,
             len: 0,
             capacity: cap,
+            relevance: NO_RELEVANCE,
+            probe: None,
+            hasher,
+            shrink: None,
+            deleted: 0,
+            alt_table: false,
+            ids_reused: false,
+            migration: None,
+            rehash_steps: None,
             p: PhantomData,
         })
     }
 }
 
+impl<B, K, V> FMap<B, K, V, DynamicProbing>
+    where
+        B: GrowableBackend,
+        K: DeserializeOwned,
+        V: DeserializeOwned,
+{
+    /// Creates a new map using the given probing strategy. The strategy gets stored along with the map and is
+    /// used automatically when initializing the map again.
+    pub fn with_probe_strategy(backend: B, capacity: usize, strategy: ProbeStrategy) -> Result<Self> {
+        let mut map = Self::with_capacity(backend, capacity)?;
+        map.probe = Some(strategy);
+        map.set_metadata(map.metadata())?;
+        Ok(map)
+    }
+}
+
 impl<B, K, V, H> Initiable<B> for FMap<B, K, V, H>
     where
+        H: HashFn,
         B: Backend,
 {
     fn init(backend: B) -> Result<Self> {
         let backend = MultiFile::init(backend)?;
 
-        let metadata_be = backend.get(0).ok_or(Error::Initialization)?;
-        let metadata = MapMetadata::from_bytes(metadata_be.get(0, MapMetadata::byte_len())?);
+        let metadata = Self::read_metadata(&backend)?;
+        if [FREE_ID, HASHES_ID, ALT_TABLE_ID, ALT_HASHES_ID].iter().any(|i| !backend.has_id(*i)) {
+            return Err(Error::Initialization);
+        }
 
         Ok(Self {
             len: metadata.len(),
             capacity: metadata.capacity(),
+            relevance: metadata.relevance(),
+            probe: metadata.probe(),
+            hasher: metadata.hasher(),
+            shrink: None,
+            deleted: metadata.deleted(),
+            alt_table: metadata.alt_table(),
+            ids_reused: metadata.ids_reused(),
+            migration: metadata.migration(),
+            rehash_steps: None,
             backend,
             p: PhantomData,
         })
     }
 }
 
-impl<B, K, V, H> Extend<(K, V)> for FMap<B, K, V, H>
+impl<B, K, V, H> FMap<B, K, V, H>
     where
         H: HashFn,
-        B: GrowableBackend,
-        K: hashing::Hash + Eq + Deser,
-        V: Deser,
+        B: Backend,
 {
-    fn extend<T: IntoIterator<Item=(K, V)>>(&mut self, iter: T) {
-        let iter = iter.into_iter();
-        let (lower, upper) = iter.size_hint();
+    /// Reads the state again from the backend.
+    fn reload(&mut self) -> Result<()> {
+        self.backend.reload()?;
+        let metadata = Self::read_metadata(&self.backend)?;
+        self.len = metadata.len();
+        self.capacity = metadata.capacity();
+        self.relevance = metadata.relevance();
+        self.probe = metadata.probe();
+        self.hasher = metadata.hasher();
+        self.deleted = metadata.deleted();
+        self.alt_table = metadata.alt_table();
+        self.ids_reused = metadata.ids_reused();
+        self.migration = metadata.migration();
+        Ok(())
+    }
+
+    fn read_metadata(backend: &MultiFile<B>) -> Result<MapMetadata> {
+        let metadata_be = backend.get(0).ok_or(Error::Initialization)?;
+        let metadata = MapMetadata::read(metadata_be.content_data())?;
 
-        let mut did_pregrow = false;
-        if lower > 0 && Some(lower) == upper && self.need_grow_for(lower) {
-            self.grow_to(self.len() + lower).expect("Failed to grow");
-            did_pregrow = true;
+        // Maps with a stored probing strategy can only be used with dynamic probing.
+        if metadata.probe().is_some() && !H::DYNAMIC {
+            return Err(Error::Initialization);
         }
 
-        if did_pregrow {
-            let mut insert_count = 0;
-            for (k, v) in iter {
-                let res = self.raw_insert(&k, &v).expect("Failed to insert");
-                if res.inserted() {
-                    insert_count += 1;
-                }
-            }
+        Ok(metadata)
+    }
+}
 
-            self.inc_len(insert_count)
-                .expect("Failed to set maps length");
-        } else {
-            for (k, v) in iter {
-                self.insert(&k, &v).expect("Failed to insert");
+impl<B, K, V, H> Batch for FMap<B, K, V, H>
+    where
+        H: HashFn,
+        B: GrowableBackend,
+{
+    fn batch<F, R>(&mut self, f: F) -> Result<R>
+        where
+            F: FnOnce(&mut Self) -> Result<R>,
+    {
+        self.backend.begin_undo();
+        match f(self) {
+            Ok(res) => {
+                self.backend.commit_undo();
+                self.flush()?;
+                Ok(res)
+            }
+            Err(err) => {
+                self.backend.rollback_undo()?;
+                self.reload()?;
+                Err(err)
             }
         }
     }
 }
 
-impl<'a, B, K, V, H> Extend<(&'a K, &'a V)> for FMap<B, K, V, H>
+impl<B, K, V, H> FMap<SharedBackend<B>, K, V, H>
+    where
+        H: HashFn,
+        B: Backend,
+{
+    /// Creates a new, independent read handle sharing the same backend. Handles can be moved to other threads to
+    /// serve queries concurrently.
+    #[inline]
+    pub fn reader(&self) -> Result<ReadOnly<Self>> {
+        Self::init_readonly(self.backend.inner().clone())
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl<B, K, V, H> FMap<B, K, V, H>
+    where
+        H: HashFn,
+        B: Backend,
+{
+    /// Returns a read only snapshot of the map that is `Send + Sync`. More handles for other threads can be created
+    /// using `reader`. The snapshot is a copy in a temporary memory mapped file, so changes made to this map afterwards
+    /// don't affect it.
+    #[inline]
+    pub fn freeze(&self) -> Result<ReadOnly<FMap<SharedBackend<MmapBackend>, K, V, H>>> {
+        let backend = MmapBackend::from_content(self.backend.inner().content_data())?;
+        FMap::init_readonly(SharedBackend::new(backend))
+    }
+}
+
+impl<B, K, V, H> Extend<(K, V)> for FMap<B, K, V, H>
     where
         H: HashFn,
         B: GrowableBackend,
         K: hashing::Hash + Eq + Deser,
         V: Deser,
 {
-    fn extend<T: IntoIterator<Item=(&'a K, &'a V)>>(&mut self, iter: T) {
-        let iter = iter.into_iter();
-        let (lower, upper) = iter.size_hint();
-
-        let mut did_pregrow = false;
-        if lower > 0 && Some(lower) == upper && self.need_grow_for(lower) {
-            self.grow_to(self.len() + lower).expect("Failed to grow");
-            did_pregrow = true;
-        }
-
-        if did_pregrow {
-            let mut insert_count = 0;
-            for (k, v) in iter {
-                let res = self.raw_insert(k, v).expect("Failed to insert");
-                if res.inserted() {
-                    insert_count += 1;
-                }
-            }
-
-            self.inc_len(insert_count)
-                .expect("Failed to set maps length");
-        } else {
-            for (k, v) in iter {
-                self.insert(k, v).expect("Failed to insert");
-            }
-        }
+    /// Inserts all pairs of `iter`.
+    ///
+    /// # Panics
+    /// Panics if inserting fails, eg. with `Error::RelevanceLost` for maps rehashed with relevance that would have to
+    /// grow. Use `try_extend` to handle those errors.
+    #[inline]
+    fn extend<T: IntoIterator<Item=(K, V)>>(&mut self, iter: T) {
+        self.try_extend(iter).expect("Failed to extend map");
     }
 }
 
-impl<'a, B, K, V, H> Extend<&'a (K, V)> for FMap<B, K, V, H>
+impl<'a, B, K, V, H> Extend<(&'a K, &'a V)> for FMap<B, K, V, H>
     where
         H: HashFn,
         B: GrowableBackend,
         K: hashing::Hash + Eq + Deser,
         V: Deser,
 {
+    /// Inserts all pairs of `iter`.
+    ///
+    /// # Panics
+    /// Panics if inserting fails, eg. with `Error::RelevanceLost` for maps rehashed with relevance that would have to
+    /// grow. Use `try_extend` to handle those errors.
     #[inline]
-    fn extend<T: IntoIterator<Item=&'a (K, V)>>(&mut self, iter: T) {
-        self.extend(iter.into_iter().map(|i: &(K, V)| (&i.0, &i.1)));
+    fn extend<T: IntoIterator<Item=(&'a K, &'a V)>>(&mut self, iter: T) {
+        self.try_extend(iter).expect("Failed to extend map");
     }
 }
 
-impl<B, K, V, H> MType for FMap<B, K, V, H> where B: Backend {
+impl<'a, B, K, V, H> Extend<&'a (K, V)> for FMap<B, K, V, H>
+    where
+        H: HashFn,
+        B: GrowableBackend,
+        K: hashing::Hash + Eq + Deser,
+        V: Deser,
+{
+    #[inline]
+    fn extend<T: IntoIterator<Item=&'a (K, V)>>(&mut self, iter: T) {
+        self.extend(iter.into_iter().map(|i: &(K, V)| (&i.0, &i.1)));
+    }
+}
+
+impl<'a, B, K, V, H> IntoIterator for &'a FMap<B, K, V, H>
+    where
+        B: Backend,
+        K: DeserializeOwned,
+        V: DeserializeOwned,
+{
+    type Item = (K, V);
+    type IntoIter = MapIter<'a, B, K, V, H>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<B, K, V, H> MType for FMap<B, K, V, H> where B: Backend {
     #[inline]
     fn raw_data(&self) -> &[u8] {
         self.backend.raw_data()
@@ -649,11 +1953,13 @@ impl<B, K, V, H> MType for FMap<B, K, V, H> where B: Backend {
 }
 
 #[cfg(test)]
+#[cfg(feature = "mmap")]
 mod test {
     use super::*;
     use crate::backend::memory::test::{make_deeta, make_mem_backend};
     use crate::backend::memory::{MemoryBackend, MemoryData};
     use crate::backend::mmap_mut::test::make_mmap_backend;
+    use crate::components::map::hashing::hashfn::{Probe, RobinHoodProbing};
     use std::time::Instant;
 
     // #[test]
@@ -673,6 +1979,31 @@ mod test {
         test_load(&mut mem_backend);
         test_not_contained(&mut mem_backend);
         test_rehash_with_relevance(&mut mem_backend);
+        test_relevance_persistence(&mut mem_backend);
+        test_std_api(&mut mem_backend);
+        test_probe_strategy(&mut mem_backend);
+        test_remove_probe_strategies(&mut mem_backend);
+        test_incremental_probe_strategies(&mut mem_backend);
+        test_hasher(&mut mem_backend);
+        test_shrink_to_fit(&mut mem_backend);
+        test_shrink_to_fit_size(&mut mem_backend);
+        test_auto_shrink(&mut mem_backend);
+        test_batch(&mut mem_backend);
+        test_get_into(&mut mem_backend);
+        test_get_many(&mut mem_backend);
+        test_remove(&mut mem_backend);
+        test_update(&mut mem_backend);
+        test_entry(&mut mem_backend);
+        test_keys_values(&mut mem_backend);
+        test_retain(&mut mem_backend);
+        test_robin_hood(&mut mem_backend);
+        test_merge(&mut mem_backend);
+        test_insertion_order(&mut mem_backend);
+        test_get_raw(&mut mem_backend);
+        test_stats(&mut mem_backend);
+        test_incremental_rehash(&mut mem_backend);
+        test_freeze(&mut mem_backend);
+        test_reserve(&mut mem_backend);
 
         let mut mmap_backend = make_mmap_backend("./fmap", 100);
         test_grow(&mut mmap_backend);
@@ -680,6 +2011,31 @@ mod test {
         test_insert(&mut mmap_backend);
         test_load(&mut mmap_backend);
         test_not_contained(&mut mmap_backend);
+        test_relevance_persistence(&mut mmap_backend);
+        test_std_api(&mut mmap_backend);
+        test_probe_strategy(&mut mmap_backend);
+        test_remove_probe_strategies(&mut mmap_backend);
+        test_incremental_probe_strategies(&mut mmap_backend);
+        test_hasher(&mut mmap_backend);
+        test_shrink_to_fit(&mut mmap_backend);
+        test_shrink_to_fit_size(&mut mmap_backend);
+        test_auto_shrink(&mut mmap_backend);
+        test_batch(&mut mmap_backend);
+        test_get_into(&mut mmap_backend);
+        test_get_many(&mut mmap_backend);
+        test_remove(&mut mmap_backend);
+        test_update(&mut mmap_backend);
+        test_entry(&mut mmap_backend);
+        test_keys_values(&mut mmap_backend);
+        test_retain(&mut mmap_backend);
+        test_robin_hood(&mut mmap_backend);
+        test_merge(&mut mmap_backend);
+        test_insertion_order(&mut mmap_backend);
+        test_get_raw(&mut mmap_backend);
+        test_stats(&mut mmap_backend);
+        test_incremental_rehash(&mut mmap_backend);
+        test_freeze(&mut mmap_backend);
+        test_reserve(&mut mmap_backend);
     }
 
     fn test_grow<B: GrowableBackend>(mut backend: &mut B) {
@@ -720,6 +2076,7 @@ mod test {
         }
 
         assert_eq!(map.len, map.iter().count());
+        assert_eq!(map.len, map.iter().prefetch(16).count());
         let mut got: Vec<_> = map.iter().map(|i| i.0).collect();
         got.sort_unstable();
         let mut expect = terms.clone();
@@ -764,12 +2121,932 @@ mod test {
 
         for i in make_deeta().skip(100).take(1000) {
             assert_eq!(map.get(&i), None);
+            assert!(!map.contains_key(&i));
+        }
+        for (k, _) in data.iter() {
+            assert!(map.contains_key(k));
+        }
+    }
+
+    /// Asserts that each occupied table slot caches the hash of the key it points to.
+    fn assert_slot_hashes<B: Backend, K: hashing::Hash + Deser, H>(map: &FMap<B, K, u32, H>) {
+        let table = map.hash_table();
+        let hashes = map.key_hashes();
+        let kv_storage = map.entry_storage();
+        let mut occupied = 0;
+        for pos in 0..map.capacity() {
+            if let Slot::Occupied(id) = FMap::<B, K, u32, H>::read_slot(pos as u64, &table) {
+                let key: K = kvpair::decode_key(kv_storage.get(id as usize).unwrap()).unwrap();
+                assert_eq!(FMap::<B, K, u32, H>::slot_hash(pos, &hashes), map.hasher().hash(&key));
+                occupied += 1;
+            }
+        }
+        assert_eq!(occupied, map.len());
+    }
+
+    #[test]
+    fn test_slot_hashes() {
+        let mut map: FMap<_, String, u32> = FMap::create(make_mem_backend(10)).unwrap();
+        for i in 0..3000u32 {
+            map.insert(&i.to_string(), &i).unwrap();
+        }
+        assert_slot_hashes(&map);
+
+        map.retain(|_, v| v % 4 != 0).unwrap();
+        map.remove(&"1".to_string()).unwrap();
+        assert_slot_hashes(&map);
+
+        map.rehash_with_relevance(|a, b| b.value().cmp(a.value())).unwrap();
+        assert_slot_hashes(&map);
+
+        map.clear_relevance().unwrap();
+        map.shrink_to_fit().unwrap();
+        assert_slot_hashes(&map);
+        for i in 0..3000u32 {
+            assert_eq!(map.get(&i.to_string()), (i % 4 != 0 && i != 1).then_some(i));
+        }
+    }
+
+    /// Value type which fails the test if it gets decoded.
+    #[derive(Serialize)]
+    struct NoDecode(u32);
+
+    impl<'de> serde::Deserialize<'de> for NoDecode {
+        fn deserialize<D: serde::Deserializer<'de>>(_: D) -> std::result::Result<Self, D::Error> {
+            panic!("value must not be decoded")
+        }
+    }
+
+    #[test]
+    fn test_contains_key_no_decode() {
+        let mut map: FMap<_, u32, NoDecode> = FMap::create(make_mem_backend(10)).unwrap();
+        for i in 0..1000u32 {
+            map.insert(&i, &NoDecode(i)).unwrap();
+        }
+        for i in 0..1000u32 {
+            assert!(map.contains_key(&i));
+        }
+        assert!(!map.contains_key(&1000));
+    }
+
+    #[test]
+    fn test_borrowed_lookup() {
+        let mut map: FMap<_, String, u32> = FMap::create(make_mem_backend(10)).unwrap();
+        for i in 0..500u32 {
+            map.insert(&format!("key{i}"), &i).unwrap();
+        }
+        assert_eq!(map.get("key42"), Some(42));
+        assert_eq!(map.get(&"key42".to_string()), Some(42));
+        assert!(map.contains_key("key499"));
+        assert!(!map.contains_key("key500"));
+
+        let mut map: FMap<_, Vec<u8>, u32> = FMap::with_hasher(make_mem_backend(10), 0, KeyHasher::Fnv).unwrap();
+        map.insert(&b"abc".to_vec(), &1).unwrap();
+        assert_eq!(map.get(&b"abc"[..]), Some(1));
+        assert!(!map.contains_key(&b"ab"[..]));
+    }
+
+    fn test_std_api<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut map: FMap<_, String, u32> = FMap::with_capacity(&mut backend, 0).unwrap();
+        assert!(map.is_empty());
+        assert!(!map.contains_key(&"a".to_string()));
+
+        assert_eq!(map.get_or_insert_with(&"a".to_string(), || 1), Ok(1));
+        assert_eq!(map.get_or_insert_with(&"a".to_string(), || 2), Ok(1));
+        assert!(map.contains_key(&"a".to_string()));
+        assert_eq!(map.len(), 1);
+        assert!(!map.is_empty());
+
+        map.insert(&"b".to_string(), &3).unwrap();
+        let mut got: Vec<_> = (&map).into_iter().collect();
+        got.sort_unstable();
+        assert_eq!(got, vec![("a".to_string(), 1), ("b".to_string(), 3)]);
+    }
+
+    fn test_shrink_to_fit<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut map: FMap<_, String, u32> = FMap::with_capacity(&mut backend, 1000).unwrap();
+        let data: Vec<_> = make_deeta().take(20).map(|i| (i, 42)).collect();
+        map.extend(data.iter());
+
+        let old_size = map.raw_data().len();
+        map.shrink_to_fit().unwrap();
+        assert!(map.raw_data().len() < old_size);
+        for (k, v) in data.iter() {
+            assert_eq!(map.get(k), Some(*v));
+        }
+
+        // Still usable after shrinking
+        map.insert(&"new".to_string(), &1).unwrap();
+        assert_eq!(map.get(&"new".to_string()), Some(1));
+        drop(map);
+
+        let mut map: FMap<_, String, u32> = FMap::init(&mut backend).unwrap();
+        assert_eq!(map.len(), 21);
+        for (k, v) in data.iter() {
+            assert_eq!(map.get(k), Some(*v));
+        }
+
+        // Shrinking after removing most entries rebuilds the table with a smaller capacity.
+        for i in 0..2000u32 {
+            map.insert(&i.to_string(), &i).unwrap();
+        }
+        let capacity = map.capacity();
+        for i in 10..2000u32 {
+            map.remove(&i.to_string()).unwrap();
+        }
+        let old_size = map.raw_data().len();
+        map.shrink_to_fit().unwrap();
+        assert!(map.capacity() < capacity);
+        assert!(map.raw_data().len() < old_size);
+        assert_eq!(map.len(), 31);
+        assert_eq!(map.entry_storage().count(), 31);
+        assert!(map.load_factor() < MAX_LOAD);
+        for i in 0..2000u32 {
+            assert_eq!(map.get(&i.to_string()), (i < 10).then_some(i));
+        }
+        drop(map);
+
+        let mut map: FMap<_, String, u32> = FMap::init(&mut backend).unwrap();
+        assert_eq!(map.len(), 31);
+        for i in 10..100u32 {
+            map.insert(&i.to_string(), &i).unwrap();
+        }
+        assert_eq!(map.get(&"99".to_string()), Some(99));
+        assert_eq!(map.iter().count(), 121);
+    }
+
+    fn test_shrink_to_fit_size<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut map: FMap<_, String, u32> = FMap::with_capacity(&mut backend, 1000).unwrap();
+        for i in 0..5000u32 {
+            map.insert(&i.to_string(), &i).unwrap();
+        }
+        for i in 100..5000u32 {
+            map.remove(&i.to_string()).unwrap();
+        }
+        map.shrink_to_fit().unwrap();
+        let capacity = map.capacity();
+        let size = map.raw_data().len();
+        drop(map);
+
+        // A map that never held the removed entries can't be smaller after shrinking.
+        backend.clear();
+        let mut map: FMap<_, String, u32> = FMap::with_capacity(&mut backend, 1000).unwrap();
+        for i in 0..100u32 {
+            map.insert(&i.to_string(), &i).unwrap();
+        }
+        map.shrink_to_fit().unwrap();
+        assert_eq!(map.capacity(), capacity);
+        assert!(size <= map.raw_data().len());
+    }
+
+    #[test]
+    fn test_reader() {
+        let mut backend = make_mem_backend(100);
+        let mut map: FMap<_, String, u32> = FMap::create(&mut backend).unwrap();
+        let data: Vec<_> = make_deeta().take(100).collect();
+        for (i, k) in data.iter().enumerate() {
+            map.insert(k, &(i as u32)).unwrap();
+        }
+        drop(map);
+
+        let map: ReadOnly<FMap<_, String, u32>> = FMap::init_readonly(SharedBackend::new(backend)).unwrap();
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                let reader = map.reader().unwrap();
+                let data = &data;
+                s.spawn(move || {
+                    assert_eq!(reader.len(), 100);
+                    for (i, k) in data.iter().enumerate() {
+                        assert_eq!(reader.get(k), Some(i as u32));
+                    }
+                });
+            }
+        });
+    }
+
+    fn test_freeze<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut map: FMap<_, String, u32> = FMap::create(&mut backend).unwrap();
+        let data: Vec<_> = make_deeta().take(300).collect();
+        for (i, k) in data.iter().enumerate() {
+            map.insert(k, &(i as u32)).unwrap();
+        }
+
+        let frozen = map.freeze().unwrap();
+        assert_eq!(frozen.len(), 300);
+
+        // The writer keeps going while readers only see the snapshot.
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                let reader = frozen.reader().unwrap();
+                let data = &data;
+                s.spawn(move || {
+                    for (i, k) in data.iter().enumerate() {
+                        assert_eq!(reader.get(k), Some(i as u32));
+                    }
+                    assert_eq!(reader.get(&"new0".to_string()), None);
+                });
+            }
+
+            for i in 0..1000u32 {
+                map.insert(&format!("new{i}"), &i).unwrap();
+            }
+            map.remove(&data[0]).unwrap();
+        });
+
+        assert_eq!(map.len(), 1299);
+        assert_eq!(frozen.len(), 300);
+        assert_eq!(frozen.get(&data[0]), Some(0));
+        assert_eq!(frozen.iter().count(), 300);
+    }
+
+    fn test_reserve<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut map: FMap<_, String, u32> = FMap::create(&mut backend).unwrap();
+        for i in 0..100u32 {
+            map.insert(&i.to_string(), &i).unwrap();
+        }
+        for i in 0..50u32 {
+            map.remove(&i.to_string()).unwrap();
+        }
+
+        let pair_len = |k: &String, v: &u32| bincode::serialize(&KVPair::new(k, v)).unwrap().len();
+        let data: Vec<_> = make_deeta().take(2000).collect();
+        let bytes: usize = data.iter().enumerate().map(|(i, k)| pair_len(k, &(i as u32))).sum();
+        map.reserve(data.len(), bytes).unwrap();
+
+        let capacity = map.capacity();
+        let size = map.raw_data().len();
+        for (i, k) in data.iter().enumerate() {
+            map.insert(k, &(i as u32)).unwrap();
+        }
+        assert_eq!(map.capacity(), capacity);
+        assert_eq!(map.raw_data().len(), size);
+        assert_eq!(map.len(), 2050);
+        assert!(data.iter().enumerate().all(|(i, k)| map.get(k) == Some(i as u32)));
+
+        // Reserving space that is available already doesn't grow the map.
+        map.reserve(10, 0).unwrap();
+        assert_eq!(map.capacity(), capacity);
+        assert_eq!(map.raw_data().len(), size);
+
+        // Incremental rehashing doesn't start for reserved items either.
+        map.set_incremental_rehash(Some(4));
+        let data: Vec<_> = (0..3000u32).map(|i| format!("reserved_{i}")).collect();
+        let bytes: usize = data.iter().map(|k| pair_len(k, &1)).sum();
+        map.reserve(data.len(), bytes).unwrap();
+
+        let capacity = map.capacity();
+        let size = map.raw_data().len();
+        for k in data.iter() {
+            map.insert(k, &1).unwrap();
+            assert!(!map.is_rehashing());
+        }
+        assert_eq!(map.capacity(), capacity);
+        assert_eq!(map.raw_data().len(), size);
+        assert_eq!(map.len(), 5050);
+    }
+
+    fn test_get_into<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut map: FMap<_, String, String> = FMap::create(&mut backend).unwrap();
+        let data: Vec<_> = (0..100).map(|i| (format!("key_{i}"), format!("value_{i}"))).collect();
+        map.extend(data.iter().cloned());
+
+        let mut pair = (String::new(), String::new());
+        for (k, v) in data.iter() {
+            assert_eq!(map.get_into(k, &mut pair), Ok(true));
+            assert_eq!(&pair.1, v);
+        }
+        assert_eq!(map.get_into(&"missing".to_string(), &mut pair), Ok(false));
+    }
+
+    fn test_get_many<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut map: FMap<_, String, u32> = FMap::create(&mut backend).unwrap();
+        map.extend((0..1000).map(|i| (format!("key_{i}"), i)));
+
+        let keys: Vec<_> = (0..1100).map(|i| format!("key_{i}")).collect();
+        let key_refs: Vec<_> = keys.iter().collect();
+        let res = map.get_many(&key_refs);
+        assert_eq!(res.len(), keys.len());
+        for (i, v) in res.into_iter().enumerate() {
+            assert_eq!(v, (i < 1000).then_some(i as u32));
+        }
+        assert!(map.get_many(&[]).is_empty());
+
+        let (a, b, c) = ("key_3".to_string(), "key_2000".to_string(), "key_999".to_string());
+        assert_eq!(map.get_many_n(&[&a, &b, &c]), [Some(3), None, Some(999)]);
+        assert_eq!(map.get_many_n::<0>(&[]), []);
+    }
+
+    fn test_remove<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        {
+            let mut map: FMap<_, u32, String> = FMap::create(&mut backend).unwrap();
+            for i in 0..1000u32 {
+                map.insert(&i, &i.to_string()).unwrap();
+            }
+            for i in (0..1000u32).step_by(2) {
+                assert_eq!(map.remove(&i).unwrap(), Some(i.to_string()));
+            }
+            assert_eq!(map.remove(&0).unwrap(), None);
+            assert_eq!(map.remove(&5000).unwrap(), None);
+            assert_eq!(map.len(), 500);
+        }
+
+        let mut map: FMap<_, u32, String> = FMap::init(&mut backend).unwrap();
+        assert_eq!(map.len(), 500);
+        for i in 0..1000u32 {
+            assert_eq!(map.get(&i), (i % 2 == 1).then(|| i.to_string()));
+        }
+        assert_eq!(map.iter().count(), 500);
+
+        // Storage of removed pairs gets reused.
+        for i in (0..1000u32).step_by(2) {
+            map.insert(&i, &format!("new{i}")).unwrap();
+        }
+        assert_eq!(map.len(), 1000);
+        assert_eq!(map.entry_storage().count(), 1000);
+        assert_eq!(map.get(&10), Some("new10".to_string()));
+
+        // Removing and inserting many times must not grow the table forever.
+        let capacity = map.capacity();
+        for round in 0..20u32 {
+            for i in 0..1000u32 {
+                map.remove(&i).unwrap();
+                map.insert(&(i + round * 1000), &i.to_string()).unwrap();
+            }
+            for i in 0..1000u32 {
+                map.remove(&(i + round * 1000)).unwrap();
+                map.insert(&i, &i.to_string()).unwrap();
+            }
+        }
+        assert_eq!(map.len(), 1000);
+        assert!(map.capacity() <= capacity * 2);
+        for i in 0..1000u32 {
+            assert_eq!(map.get(&i), Some(i.to_string()));
+        }
+
+        // Inserting existing keys doesn't leave unused pairs behind.
+        map.clear().unwrap();
+        for _ in 0..3 {
+            for i in 0..100u32 {
+                map.insert(&i, &i.to_string()).unwrap();
+            }
+        }
+        assert_eq!(map.entry_storage().count(), 100);
+    }
+
+    fn test_update<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        {
+            let mut map: FMap<_, u32, String> = FMap::create(&mut backend).unwrap();
+            for i in 0..500u32 {
+                map.insert(&i, &i.to_string()).unwrap();
+            }
+
+            assert_eq!(map.insert_or_update(&3, &"three".to_string()).unwrap(), Some("3".to_string()));
+            assert_eq!(map.insert_or_update(&1000, &"1000".to_string()).unwrap(), None);
+            assert_eq!(map.len(), 501);
+
+            // Values growing and shrinking in size.
+            assert!(map.update(&7, |v| v.push_str(&"x".repeat(100))).unwrap());
+            assert!(map.update(&8, |v| v.clear()).unwrap());
+            assert!(!map.update(&2000, |v| v.clear()).unwrap());
+        }
+
+        let map: FMap<_, u32, String> = FMap::init(&mut backend).unwrap();
+        assert_eq!(map.len(), 501);
+        assert_eq!(map.get(&3), Some("three".to_string()));
+        assert_eq!(map.get(&7), Some(format!("7{}", "x".repeat(100))));
+        assert_eq!(map.get(&8), Some(String::new()));
+        assert_eq!(map.get(&2000), None);
+        for i in (9..500u32).chain(0..3) {
+            assert_eq!(map.get(&i), Some(i.to_string()));
+        }
+    }
+
+    fn test_entry<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut map: FMap<_, String, u32> = FMap::create(&mut backend).unwrap();
+        let words = "a b c a b a d a".split(' ').map(|i| i.to_string());
+        for word in words {
+            map.entry(&word).unwrap().and_modify(|v| *v += 1).unwrap().or_insert(1).unwrap();
+        }
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.get(&"a".to_string()), Some(4));
+        assert_eq!(map.get(&"b".to_string()), Some(2));
+        assert_eq!(map.get(&"d".to_string()), Some(1));
+
+        let e = "e".to_string();
+        assert_eq!(map.entry(&e).unwrap().or_insert_with(|| 9).unwrap(), 9);
+        assert_eq!(map.entry(&e).unwrap().or_insert_with(|| 1).unwrap(), 9);
+        assert_eq!(map.get_or_insert_with(&e, || 1).unwrap(), 9);
+
+        match map.entry(&e).unwrap() {
+            Entry::Occupied(mut o) => {
+                assert_eq!(o.insert(&10).unwrap(), 9);
+                assert_eq!(o.get().unwrap(), 10);
+                assert_eq!(o.remove().unwrap(), 10);
+            }
+            Entry::Vacant(_) => panic!("entry must be occupied"),
+        }
+        assert_eq!(map.len(), 4);
+        assert!(matches!(map.entry(&e).unwrap(), Entry::Vacant(_)));
+
+        // Entries keep working while the map grows.
+        for i in 0..1000u32 {
+            let kv_id = match map.entry(&i.to_string()).unwrap() {
+                Entry::Vacant(v) => v.insert(&i).unwrap(),
+                Entry::Occupied(_) => panic!("entry must be vacant"),
+            };
+            assert_eq!(map.get(&i.to_string()), Some(i));
+            assert_eq!(map.insert(&i.to_string(), &0).unwrap(), kv_id);
+        }
+        assert_eq!(map.len(), 1004);
+    }
+
+    fn test_keys_values<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut map: FMap<_, String, Vec<u32>> = FMap::create(&mut backend).unwrap();
+        for i in 0..300u32 {
+            map.insert(&format!("key{i}"), &vec![i; i as usize % 5]).unwrap();
+        }
+        map.remove(&"key7".to_string()).unwrap();
+
+        let (mut keys, mut values): (Vec<_>, Vec<_>) = map.iter().unzip();
+        let mut only_keys: Vec<_> = map.keys().collect();
+        let mut only_values: Vec<_> = map.values().prefetch(8).collect();
+        keys.sort();
+        values.sort();
+        only_keys.sort();
+        only_values.sort();
+        assert_eq!(only_keys.len(), 299);
+        assert_eq!(only_keys, keys);
+        assert_eq!(only_values, values);
+    }
+
+    fn test_insertion_order<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut map: FMap<_, String, u32> = FMap::create(&mut backend).unwrap();
+        let items: Vec<_> = (0..1000u32).rev().map(|i| (format!("key{i}"), i)).collect();
+        for (k, v) in items.iter() {
+            map.insert(k, v).unwrap();
+        }
+        assert_eq!(map.iter_insertion_order().unwrap().len(), 1000);
+        assert_eq!(map.iter_insertion_order().unwrap().collect::<Vec<_>>(), items);
+
+        map.remove(&"key500".to_string()).unwrap();
+        map.remove(&"key3".to_string()).unwrap();
+        let expected: Vec<_> = items.iter().filter(|i| i.1 != 500 && i.1 != 3).cloned().collect();
+        assert_eq!(map.iter_insertion_order().unwrap().len(), 998);
+        assert_eq!(map.iter_insertion_order().unwrap().collect::<Vec<_>>(), expected);
+
+        // Reusing the ID of a removed pair loses the order, even after loading the map again.
+        map.insert(&"new".to_string(), &1).unwrap();
+        assert!(map.iter_insertion_order().is_none());
+        drop(map);
+        let mut map: FMap<_, String, u32> = FMap::init(&mut backend).unwrap();
+        assert!(map.iter_insertion_order().is_none());
+
+        map.clear().unwrap();
+        map.insert(&"b".to_string(), &2).unwrap();
+        map.insert(&"a".to_string(), &1).unwrap();
+        let order: Vec<_> = map.iter_insertion_order().unwrap().collect();
+        assert_eq!(order, vec![("b".to_string(), 2), ("a".to_string(), 1)]);
+    }
+
+    fn test_get_raw<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut nested_backend = make_mem_backend(10);
+        let mut nested: FMap<_, u32, u64> = FMap::create(&mut nested_backend).unwrap();
+        for i in 0..100u32 {
+            nested.insert(&i, &(i as u64 * 3)).unwrap();
+        }
+        drop(nested);
+
+        let mut map: FMap<_, String, Vec<u8>> = FMap::create(&mut backend).unwrap();
+        for i in 0..100u32 {
+            map.insert(&format!("key{i}"), &vec![i as u8; i as usize]).unwrap();
+        }
+        map.insert(&"nested".to_string(), &nested_backend.content_data().to_vec()).unwrap();
+
+        let raw = map.get_raw("key5").unwrap();
+        assert_eq!(raw.len(), 8 + 5);
+        assert_eq!(&raw[..8], &5u64.to_le_bytes());
+        assert_eq!(&raw[8..], &[5; 5]);
+        assert_eq!(map.get_raw("key0").unwrap(), &0u64.to_le_bytes());
+        assert_eq!(map.get_raw("key100"), None);
+
+        let backend = map.get_backend("nested").unwrap();
+        let nested: FMap<_, u32, u64> = FMap::init(FullBackend::new(&backend.data()[8..])).unwrap();
+        assert_eq!(nested.len(), 100);
+        assert_eq!(nested.get(&33), Some(99));
+    }
+
+    fn test_stats<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut map: FMap<_, u32, String> = FMap::create(&mut backend).unwrap();
+        let stats = map.stats();
+        assert_eq!(stats.len, 0);
+        assert_eq!(stats.max_probes, 0);
+        assert_eq!(stats.collisions(), 0);
+
+        let mut probes = vec![];
+        for i in 0..3000u32 {
+            map.insert(&i, &"value".to_string()).unwrap();
+        }
+        for i in 0..3000u32 {
+            probes.push(map.get_debug(&i).unwrap().1);
+        }
+
+        let stats = map.stats();
+        assert_eq!(stats.len, 3000);
+        assert_eq!(stats.capacity, map.capacity());
+        assert_eq!(stats.load_factor, map.load_factor());
+        assert_eq!(stats.probe_histogram.iter().sum::<usize>(), 3000);
+        assert_eq!(stats.max_probes, *probes.iter().max().unwrap());
+        assert_eq!(stats.avg_probes, probes.iter().sum::<usize>() as f32 / 3000.0);
+        for (i, count) in stats.probe_histogram.iter().enumerate() {
+            assert_eq!(*count, probes.iter().filter(|p| **p == i).count());
+        }
+        assert_eq!(stats.wasted_bytes, 0);
+
+        map.remove(&10).unwrap();
+        map.remove(&11).unwrap();
+        let stats = map.stats();
+        assert_eq!(stats.deleted, 2);
+        assert_eq!(stats.probe_histogram.iter().sum::<usize>(), 2998);
+        assert_eq!(stats.wasted_bytes, 2 * 12 + 2 * 12);
+
+        map.shrink_to_fit().unwrap();
+        assert_eq!(map.stats().wasted_bytes, 0);
+    }
+
+    fn test_incremental_rehash<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let key = |i: u32| format!("key{i}");
+        {
+            let mut map: FMap<_, String, u32> = FMap::create(&mut backend).unwrap();
+            map.set_incremental_rehash(Some(4));
+            assert_eq!(map.incremental_rehash(), Some(4));
+
+            let mut rehashes = 0;
+            let mut was_rehashing = false;
+            for i in 0..20_000u32 {
+                let capacity = map.capacity();
+                map.insert(&key(i), &i).unwrap();
+                if map.capacity() != capacity {
+                    // Capacity only changes once all slots have been migrated, which takes more than one insertion
+                    // for tables bigger than the amount of steps.
+                    assert!(was_rehashing || capacity <= 4);
+                    rehashes += 1;
+                }
+                was_rehashing = map.is_rehashing();
+
+                if was_rehashing && i % 7 == 0 {
+                    assert_eq!(map.remove(&key(i / 2)).unwrap(), Some(i / 2));
+                    map.insert(&key(i / 2), &(i / 2)).unwrap();
+                }
+                if i % 1000 == 0 {
+                    assert!((0..=i).all(|j| map.get(&key(j)) == Some(j)));
+                    assert_eq!(map.iter().count(), map.len());
+                }
+            }
+            assert!(rehashes > 5);
+            assert_eq!(map.len(), 20_000);
+            assert_slot_hashes(&map);
+
+            while !map.is_rehashing() {
+                let i = map.len() as u32;
+                map.insert(&key(i), &i).unwrap();
+            }
+        }
+
+        // Rehashes in progress get finished once the map isn't rehashing incrementally anymore.
+        let mut map: FMap<_, String, u32> = FMap::init(&mut backend).unwrap();
+        assert!(map.is_rehashing());
+        let len = map.len() as u32;
+        assert!((0..len).all(|j| map.get(&key(j)) == Some(j)));
+        map.insert(&key(len), &len).unwrap();
+        assert!(!map.is_rehashing());
+        assert!((0..=len).all(|j| map.get(&key(j)) == Some(j)));
+        assert_slot_hashes(&map);
+
+        // The previous table doesn't take up any space once all of its slots have been migrated.
+        let (table_id, hashes_id) = map.next_table_ids();
+        for id in [table_id, hashes_id] {
+            let entry = map.backend.get(id).unwrap();
+            assert_eq!(entry.len(), 0);
+            assert_eq!(entry.free(), 0);
+        }
+
+        // Rebuilding the table drops the rehash in progress.
+        map.set_incremental_rehash(Some(1));
+        while !map.is_rehashing() {
+            let i = map.len() as u32;
+            map.insert(&key(i), &i).unwrap();
+        }
+        map.remove(&key(0)).unwrap();
+        map.rehash().unwrap();
+        assert!(!map.is_rehashing());
+        assert_eq!(map.get(&key(1)), Some(1));
+        assert_eq!(map.iter().count(), map.len());
+    }
+
+    fn test_retain<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        {
+            let mut map: FMap<_, u32, String> = FMap::create(&mut backend).unwrap();
+            for i in 0..2000u32 {
+                map.insert(&i, &i.to_string()).unwrap();
+            }
+            map.remove(&1).unwrap();
+
+            map.retain(|k, v| k % 3 == 0 && v.len() < 4).unwrap();
+            assert_eq!(map.len(), 334);
+            assert_eq!(map.entry_storage().count(), 334);
+        }
+
+        let mut map: FMap<_, u32, String> = FMap::init(&mut backend).unwrap();
+        assert_eq!(map.len(), 334);
+        for i in 0..2000u32 {
+            assert_eq!(map.get(&i), (i % 3 == 0 && i < 1000).then(|| i.to_string()));
+        }
+        map.insert(&1, &"1".to_string()).unwrap();
+        assert_eq!(map.get(&1), Some("1".to_string()));
+        assert_eq!(map.iter().count(), 335);
+
+        // The relevance order is kept.
+        map.rehash_with_relevance_id(1, |a, b| a.key().cmp(b.key())).unwrap();
+        map.retain(|k, _| *k < 500).unwrap();
+        assert_eq!(map.len(), 168);
+        assert_eq!(map.relevance_id(), Some(1));
+        for i in 0..1000u32 {
+            assert_eq!(map.get(&i), ((i % 3 == 0 || i == 1) && i < 500).then(|| i.to_string()));
+        }
+
+        map.retain(|_, _| false).unwrap();
+        assert!(map.is_empty());
+        assert_eq!(map.iter().count(), 0);
+    }
+
+    fn test_robin_hood<B: GrowableBackend>(mut backend: &mut B) {
+        fn max_probes<B: Backend, H: HashFn>(map: &FMap<B, String, u32, H>) -> usize {
+            (0..6000u32).filter_map(|i| map.get_debug(&i.to_string())).map(|i| i.1).max().unwrap()
+        }
+
+        backend.clear();
+        let mut linear: FMap<_, String, u32, LinearProbing> =
+            FMap::with_hasher(make_mem_backend(10), 0, KeyHasher::Fnv).unwrap();
+        let mut map: FMap<_, String, u32, RobinHoodProbing> =
+            FMap::with_hasher(&mut backend, 0, KeyHasher::Fnv).unwrap();
+        for i in 0..6000u32 {
+            linear.insert(&i.to_string(), &i).unwrap();
+            let insertion = map.insert_debug(&i.to_string(), &i).unwrap();
+            assert_eq!(map.get_debug(&i.to_string()), Some((i, insertion.collisions())));
+        }
+        for i in 0..6000u32 {
+            assert_eq!(map.get(&i.to_string()), Some(i));
+        }
+        assert!(max_probes(&map) <= max_probes(&linear));
+
+        for i in (0..6000u32).step_by(3) {
+            assert_eq!(map.remove(&i.to_string()).unwrap(), Some(i));
+        }
+        for i in (0..6000u32).step_by(6) {
+            map.insert(&i.to_string(), &(i + 1)).unwrap();
+        }
+        drop(map);
+
+        let map: FMap<_, String, u32, RobinHoodProbing> = FMap::init(&mut backend).unwrap();
+        for i in 0..6000u32 {
+            let expected = match i {
+                i if i % 6 == 0 => Some(i + 1),
+                i if i % 3 == 0 => None,
+                i => Some(i),
+            };
+            assert_eq!(map.get(&i.to_string()), expected);
+        }
+    }
+
+    fn test_merge<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut other: FMap<_, String, u32> = FMap::create(make_mem_backend(10)).unwrap();
+        for i in 500..2000u32 {
+            other.insert(&i.to_string(), &(i * 2)).unwrap();
+        }
+        assert_eq!(other.iter().len(), 1500);
+
+        let mut map: FMap<_, String, u32> = FMap::create(&mut backend).unwrap();
+        for i in 0..1000u32 {
+            map.insert(&i.to_string(), &i).unwrap();
+        }
+
+        assert_eq!(map.merge_from(&other, MergePolicy::KeepExisting).unwrap(), 1000);
+        assert_eq!(map.len(), 2000);
+        assert_eq!(map.get("600"), Some(600));
+        assert_eq!(map.get("1600"), Some(3200));
+
+        assert_eq!(map.merge_from(&other, MergePolicy::Overwrite).unwrap(), 0);
+        assert_eq!(map.get("600"), Some(1200));
+        assert_eq!(map.get("100"), Some(100));
+
+        let items = (1990..2010u32).map(|i| (i.to_string(), 1));
+        assert_eq!(map.merge_from_with(items, |_, old, new| *old += new).unwrap(), 10);
+        assert_eq!(map.get("1995"), Some(3991));
+        assert_eq!(map.get("2005"), Some(1));
+        assert_eq!(map.len(), 2010);
+    }
+
+    fn test_batch<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut map: FMap<_, String, u32> = FMap::create(&mut backend).unwrap();
+        map.insert(&"a".to_string(), &1).unwrap();
+
+        let res = map.batch(|map| {
+            map.insert(&"b".to_string(), &2)?;
+            map.insert(&"c".to_string(), &3)?;
+            Ok(map.len())
+        });
+        assert_eq!(res, Ok(3));
+        assert_eq!(map.get(&"c".to_string()), Some(3));
+
+        // Failing batches including growing the map get rolled back completely.
+        let size = map.raw_data().len();
+        let res = map.batch(|map| {
+            map.insert(&"a".to_string(), &10)?;
+            for (i, k) in make_deeta().take(500).enumerate() {
+                map.insert(&k, &(i as u32))?;
+            }
+            Err::<(), _>(Error::UnexpectedValue)
+        });
+        assert_eq!(res, Err(Error::UnexpectedValue));
+        assert_eq!(map.raw_data().len(), size);
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.get(&"a".to_string()), Some(1));
+        assert_eq!(map.get(&"b".to_string()), Some(2));
+        assert_eq!(map.get(&"c".to_string()), Some(3));
+        assert_eq!(map.get(&make_deeta().next().unwrap()), None);
+
+        // Still usable after a rollback
+        map.insert(&"d".to_string(), &4).unwrap();
+        assert_eq!(map.get(&"d".to_string()), Some(4));
+        drop(map);
+
+        let map: FMap<_, String, u32> = FMap::init(backend).unwrap();
+        assert_eq!(map.len(), 4);
+    }
+
+    fn test_auto_shrink<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut map: FMap<_, String, u32> = FMap::with_capacity(&mut backend, 1000).unwrap();
+        map.set_shrink_policy(Some(ShrinkPolicy::new(0.5, 2)));
+        assert_eq!(map.shrink_policy(), Some(ShrinkPolicy::new(0.5, 2)));
+
+        let size = map.raw_data().len();
+        map.insert(&"a".to_string(), &1).unwrap();
+        assert_eq!(map.raw_data().len(), size);
+
+        map.insert(&"b".to_string(), &2).unwrap();
+        assert!(map.raw_data().len() < size);
+        assert_eq!(map.get(&"a".to_string()), Some(1));
+        assert_eq!(map.get(&"b".to_string()), Some(2));
+    }
+
+    fn test_probe_strategy<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let strategy = ProbeStrategy::DoubleHashing(Probe::Linear, Probe::Quadratic);
+        let mut map: FMap<_, String, u32, DynamicProbing> =
+            FMap::with_probe_strategy(&mut backend, 10, strategy).unwrap();
+        let data: Vec<_> = make_deeta().take(300).map(|i| (i, 7)).collect();
+        map.extend(data.iter());
+        assert_eq!(map.probe_strategy(), Some(strategy));
+        drop(map);
+
+        let map: FMap<_, String, u32, DynamicProbing> = FMap::init(&mut backend).unwrap();
+        assert_eq!(map.probe_strategy(), Some(strategy));
+        for (k, v) in data.iter() {
+            assert_eq!(map.get(k), Some(*v));
+        }
+        drop(map);
+
+        // A statically typed probing function can't be used for maps with a stored strategy.
+        let map: Result<FMap<_, String, u32>> = FMap::init(&mut backend);
+        assert!(map.is_err());
+
+        // Corrupted strategies get rejected instead of probing with another strategy.
+        let mut map: FMap<_, String, u32, DynamicProbing> = FMap::init(&mut backend).unwrap();
+        map.backend.get_mut(0).unwrap().replace_same_len(24, &[3, 1, 9, 0]).unwrap();
+        drop(map);
+        let map: Result<FMap<_, String, u32, DynamicProbing>> = FMap::init(&mut backend);
+        assert_eq!(map.err(), Some(Error::Initialization));
+    }
+
+    /// All probe strategies a map can be created with.
+    const PROBE_STRATEGIES: [ProbeStrategy; 6] = [
+        ProbeStrategy::Linear,
+        ProbeStrategy::Quadratic,
+        ProbeStrategy::DoubleHashing(Probe::Linear, Probe::Linear),
+        ProbeStrategy::DoubleHashing(Probe::Linear, Probe::Quadratic),
+        ProbeStrategy::DoubleHashing(Probe::Quadratic, Probe::Linear),
+        ProbeStrategy::DoubleHashing(Probe::Quadratic, Probe::Quadratic),
+    ];
+
+    fn test_remove_probe_strategies<B: GrowableBackend>(mut backend: &mut B) {
+        for strategy in PROBE_STRATEGIES {
+            for mut seed in 0..20u64 {
+                backend.clear();
+                let mut map: FMap<_, u32, u32, DynamicProbing> =
+                    FMap::with_probe_strategy(&mut backend, 10, strategy).unwrap();
+                let mut expected = std::collections::HashMap::new();
+
+                // Removing many keys leaves lots of slots marked as deleted, which the probe sequences have to skip.
+                for i in 0..3000u32 {
+                    seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                    let key = (seed >> 33) as u32 % 700;
+                    if seed >> 63 == 0 {
+                        assert_eq!(map.insert_or_update(&key, &i).unwrap(), expected.insert(key, i));
+                    } else {
+                        assert_eq!(map.remove(&key).unwrap(), expected.remove(&key));
+                    }
+                }
+
+                assert_eq!(map.len(), expected.len());
+                for key in 0..700u32 {
+                    assert_eq!(map.get(&key), expected.get(&key).copied());
+                }
+            }
+        }
+    }
+
+    fn test_incremental_probe_strategies<B: GrowableBackend>(mut backend: &mut B) {
+        for strategy in PROBE_STRATEGIES {
+            for mut seed in 0..20u64 {
+                backend.clear();
+                let mut map: FMap<_, u32, u32, DynamicProbing> =
+                    FMap::with_probe_strategy(&mut backend, 10, strategy).unwrap();
+                map.set_incremental_rehash(Some(3));
+                let mut expected = std::collections::HashMap::new();
+
+                // Insert only first, then mixed with removes while the map keeps rehashing.
+                for i in 0..4000u32 {
+                    seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                    let key = (seed >> 33) as u32 % 3000;
+                    if i < 2000 || seed >> 63 == 0 {
+                        assert_eq!(map.insert_or_update(&key, &i).unwrap(), expected.insert(key, i));
+                    } else {
+                        assert_eq!(map.remove(&key).unwrap(), expected.remove(&key));
+                    }
+                }
+
+                assert_eq!(map.len(), expected.len());
+                for key in 0..3000u32 {
+                    assert_eq!(map.get(&key), expected.get(&key).copied());
+                }
+                map.finish_rehash().unwrap();
+                assert_slot_hashes(&map);
+            }
+        }
+    }
+
+    fn test_hasher<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut map: FMap<_, String, u32> = FMap::with_capacity(&mut backend, 10).unwrap();
+        assert!(matches!(map.hasher(), KeyHasher::SipHash13 { .. }));
+        let data: Vec<_> = make_deeta().take(100).map(|i| (i, 3)).collect();
+        map.extend(data.iter());
+        let hasher = map.hasher();
+        drop(map);
+
+        let map: FMap<_, String, u32> = FMap::init(&mut backend).unwrap();
+        assert_eq!(map.hasher(), hasher);
+        for (k, v) in data.iter() {
+            assert_eq!(map.get(k), Some(*v));
+        }
+        drop(map);
+
+        backend.clear();
+        let mut map: FMap<_, String, u32> = FMap::with_hasher(&mut backend, 10, KeyHasher::Fnv).unwrap();
+        map.extend(data.iter());
+        drop(map);
+
+        let map: FMap<_, String, u32> = FMap::init(&mut backend).unwrap();
+        assert_eq!(map.hasher(), KeyHasher::Fnv);
+        for (k, v) in data.iter() {
+            assert_eq!(map.get(k), Some(*v));
         }
     }
 
     fn test_rehash_with_relevance<B: GrowableBackend>(mut backend: &mut B) {
         backend.clear();
-        let mut map: FMap<_, String, u32> = FMap::with_capacity(&mut backend, 1031).unwrap();
+        // Use a fixed hasher as the test relies on the exact collisions happening.
+        let mut map: FMap<_, String, u32> =
+            FMap::with_hasher(&mut backend, 1031, KeyHasher::Fnv).unwrap();
         let end = 773;
 
         let data: Vec<_> = make_deeta()
@@ -814,5 +3091,83 @@ mod test {
 
         assert_eq!(rehash_collisions, 0);
         assert_eq!(rehash_collisions_tot, 0);
+        assert!(map.relevance_id().is_some());
+    }
+
+    #[test]
+    fn test_legacy_format() {
+        // Maps created before the format version got stored only hold their length and capacity as metadata.
+        let mut backend = make_mem_backend(100);
+        {
+            let mut mfile = MultiFile::create(&mut backend).unwrap();
+            let mut metadata = mfile.insert_empty().unwrap();
+            metadata.grow_to(16).unwrap();
+            metadata.push(&[0u8; 16]).unwrap();
+            mfile.insert_new_backend::<ListU32<_>>().unwrap();
+            mfile.insert_new_backend::<IndexedFile<_>>().unwrap();
+        }
+
+        let map: Result<FMap<_, String, u32>> = FMap::init(&mut backend);
+        assert_eq!(map.err(), Some(Error::UnsupportedVersion));
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to extend map")]
+    fn test_extend_relevance_lost() {
+        let mut backend = make_mem_backend(100);
+        let mut map: FMap<_, String, u32> = FMap::with_capacity(&mut backend, 10).unwrap();
+        let mut i = 0;
+        while map.can_fit(1) {
+            map.insert(&i.to_string(), &i).unwrap();
+            i += 1;
+        }
+        map.rehash_with_relevance(|a, b| a.value().cmp(b.value())).unwrap();
+
+        map.extend([(i.to_string(), i)]);
+    }
+
+    fn test_relevance_persistence<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut map: FMap<_, String, u32> = FMap::with_capacity(&mut backend, 10).unwrap();
+
+        // Fill the map so that the next insertion needs to grow the map.
+        let mut data = vec![];
+        for (i, k) in make_deeta().enumerate() {
+            if !map.can_fit(1) {
+                break;
+            }
+            map.insert(&k, &(i as u32)).unwrap();
+            data.push((k, i as u32));
+        }
+        assert_eq!(map.relevance_id(), None);
+
+        let compare = |a: &KVPair<String, u32>, b: &KVPair<String, u32>| a.value().cmp(b.value());
+        assert_eq!(map.rehash_with_relevance_id(0, compare), Err(Error::UnexpectedValue));
+        map.rehash_with_relevance_id(3, compare).unwrap();
+        drop(map);
+
+        let mut map: FMap<_, String, u32> = FMap::init(&mut backend).unwrap();
+        assert_eq!(map.relevance_id(), Some(3));
+
+        // Growing would destroy the order
+        let cap = map.capacity();
+        assert_eq!(map.grow_to(cap * 2), Err(Error::RelevanceLost));
+        assert_eq!(map.insert(&"new".to_string(), &7), Err(Error::RelevanceLost));
+        assert_eq!(map.try_extend([("new".to_string(), 7u32)]), Err(Error::RelevanceLost));
+        assert_eq!(map.len(), data.len());
+
+        map.grow_to_with_relevance(cap * 2, compare).unwrap();
+        assert!(map.capacity() > cap);
+        assert_eq!(map.relevance_id(), Some(3));
+        for (k, v) in data.iter() {
+            assert_eq!(map.get(k), Some(*v));
+        }
+
+        map.clear_relevance().unwrap();
+        assert_eq!(map.relevance_id(), None);
+        map.grow_to(map.capacity() * 2).unwrap();
+        for (k, v) in data.iter() {
+            assert_eq!(map.get(k), Some(*v));
+        }
     }
 }
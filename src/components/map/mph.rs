@@ -0,0 +1,304 @@
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::indexed_file::IndexedFile;
+use crate::components::map::hashing::Hash;
+use crate::components::map::FMap;
+use crate::components::sketch::mix;
+use crate::deser::{deserialize_impl, serialize_impl};
+use crate::traits::creatable::Creatable;
+use crate::traits::deser::Deser;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::{Error, Result};
+use std::marker::PhantomData;
+
+/// ID of the entry holding the metadata (len, bucket count).
+const METADATA_ID: usize = 0;
+
+/// ID of the entry holding the pilot of each bucket.
+const PILOTS_ID: usize = 1;
+
+/// ID of the entry holding the first slot.
+const SLOTS_START: usize = 2;
+
+/// Size of the metadata: len u64 | buckets u64
+const METADATA_LEN: usize = 16;
+
+/// Average amount of keys per bucket.
+const KEYS_PER_BUCKET: usize = 4;
+
+/// A read-only map using a minimal perfect hash function. Each key maps to its own slot without any collisions, so
+/// the table has exactly as many slots as there are keys and a lookup reads a single slot only.
+///
+/// Keys get distributed into buckets and each bucket stores a pilot which, combined with the keys hash, places all of
+/// its keys into free slots. Pilots get searched once while building the map.
+///
+/// The internal format: IndexedFile(METADATA | PILOTS | SLOT | SLOT | ...)
+/// where each slot holds the serialized (KEY, VALUE) pair and pilots are u32 LE.
+pub struct MphMap<B, K, V> {
+    storage: IndexedFile<B>,
+    len: usize,
+    buckets: usize,
+    p: PhantomData<(K, V)>,
+}
+
+impl<B, K, V> MphMap<B, K, V>
+    where
+        B: GrowableBackend,
+        K: Hash + Eq + Deser,
+        V: Deser,
+{
+    /// Builds a new map containing the given items. Returns `Error::UnexpectedValue` if a key occurs more than once.
+    pub fn build<I>(backend: B, items: I) -> Result<Self>
+        where
+            I: IntoIterator<Item = (K, V)>,
+    {
+        let items: Vec<(u64, K, V)> = items.into_iter().map(|(k, v)| (k.hash(), k, v)).collect();
+        let len = items.len();
+        let buckets = len.div_ceil(KEYS_PER_BUCKET).max(1);
+
+        // Keys with equal hashes can't be placed into different slots.
+        let mut hashes: Vec<_> = items.iter().map(|i| i.0).collect();
+        hashes.sort_unstable();
+        if hashes.windows(2).any(|i| i[0] == i[1]) {
+            return Err(Error::UnexpectedValue);
+        }
+
+        let mut bucket_items = vec![vec![]; buckets];
+        for (i, item) in items.iter().enumerate() {
+            bucket_items[bucket_of(item.0, buckets)].push(i);
+        }
+
+        // Place big buckets first while there are still many free slots.
+        let mut order: Vec<_> = (0..buckets).collect();
+        order.sort_unstable_by_key(|i| std::cmp::Reverse(bucket_items[*i].len()));
+
+        let mut pilots = vec![0u32; buckets];
+        let mut slots: Vec<Option<usize>> = vec![None; len];
+        let mut placed = Vec::with_capacity(KEYS_PER_BUCKET);
+        for bucket in order {
+            let keys = &bucket_items[bucket];
+            if keys.is_empty() {
+                break;
+            }
+
+            let pilot = (0..=u32::MAX)
+                .find(|pilot| {
+                    placed.clear();
+                    keys.iter().all(|i| {
+                        let slot = slot_of(items[*i].0, *pilot, len);
+                        if slots[slot].is_some() || placed.contains(&slot) {
+                            return false;
+                        }
+                        placed.push(slot);
+                        true
+                    })
+                })
+                .ok_or(Error::UnexpectedValue)?;
+
+            pilots[bucket] = pilot;
+            for (i, slot) in keys.iter().zip(placed.iter()) {
+                slots[*slot] = Some(*i);
+            }
+        }
+
+        let mut metadata = Vec::with_capacity(METADATA_LEN);
+        metadata.extend_from_slice(&(len as u64).to_le_bytes());
+        metadata.extend_from_slice(&(buckets as u64).to_le_bytes());
+        let pilots: Vec<u8> = pilots.iter().flat_map(|i| i.to_le_bytes()).collect();
+
+        let mut storage = IndexedFile::with_capacity(backend, SLOTS_START + len)?;
+        storage.insert(&metadata)?;
+        storage.insert(&pilots)?;
+        for slot in slots {
+            let (_, k, v) = &items[slot.expect("Unplaced key")];
+            storage.insert(&serialize_impl(&(k, v))?)?;
+        }
+
+        Ok(Self {
+            storage,
+            len,
+            buckets,
+            p: PhantomData,
+        })
+    }
+
+    /// Builds a new map containing all items of `map`.
+    #[inline]
+    pub fn from_map<MB, H>(backend: B, map: &FMap<MB, K, V, H>) -> Result<Self>
+        where
+            MB: Backend,
+    {
+        Self::build(backend, map.iter())
+    }
+}
+
+impl<B, K, V> MphMap<B, K, V>
+    where
+        B: Backend,
+        K: Hash + Eq + Deser,
+        V: Deser,
+{
+    /// Returns the amount of items in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the map is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the value of `k`.
+    pub fn get(&self, k: &K) -> Option<V> {
+        if self.is_empty() {
+            return None;
+        }
+        let hash = k.hash();
+        let slot = slot_of(hash, self.pilot(bucket_of(hash, self.buckets)), self.len);
+        let (key, value) = self.slot(slot)?;
+        (key == *k).then_some(value)
+    }
+
+    /// Returns `true` if the map contains `k`.
+    #[inline]
+    pub fn contains_key(&self, k: &K) -> bool {
+        self.get(k).is_some()
+    }
+
+    /// Returns an iterator over all items in the map in arbitrary order.
+    #[inline]
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (K, V)> + '_ {
+        (0..self.len).map(|i| self.slot(i).expect("Failed to load slot"))
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.storage.flush()
+    }
+
+    #[inline]
+    fn pilot(&self, bucket: usize) -> u32 {
+        let pilots = self.storage.get(PILOTS_ID).unwrap();
+        u32::from_le_bytes(pilots[bucket * 4..bucket * 4 + 4].try_into().unwrap())
+    }
+
+    #[inline]
+    fn slot(&self, slot: usize) -> Option<(K, V)> {
+        deserialize_impl(self.storage.get(SLOTS_START + slot).ok()?).ok()
+    }
+}
+
+impl<B, K, V> Initiable<B> for MphMap<B, K, V>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let storage = IndexedFile::init(backend)?;
+        let metadata = storage.get(METADATA_ID).map_err(|_| Error::Initialization)?;
+        if metadata.len() != METADATA_LEN {
+            return Err(Error::Initialization);
+        }
+        let len = u64::from_le_bytes(metadata[..8].try_into().unwrap()) as usize;
+        let buckets = u64::from_le_bytes(metadata[8..].try_into().unwrap()) as usize;
+
+        let pilots = storage.get(PILOTS_ID).map_err(|_| Error::Initialization)?;
+        if pilots.len() != buckets * 4 || storage.count() != SLOTS_START + len {
+            return Err(Error::Initialization);
+        }
+
+        Ok(Self {
+            storage,
+            len,
+            buckets,
+            p: PhantomData,
+        })
+    }
+}
+
+impl<B, K, V> MType for MphMap<B, K, V>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.storage.raw_data()
+    }
+}
+
+/// Returns the bucket of a key with the given hash.
+#[inline]
+fn bucket_of(hash: u64, buckets: usize) -> usize {
+    (mix(hash) % buckets as u64) as usize
+}
+
+/// Returns the slot of a key with the given hash when placed using `pilot`.
+#[inline]
+fn slot_of(hash: u64, pilot: u32, len: usize) -> usize {
+    (mix(hash ^ mix(pilot as u64 + 1)) % len as u64) as usize
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_build(&mut mem_backend);
+        test_from_map(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./mph", 10);
+        test_build(&mut mmap_backend);
+        test_from_map(&mut mmap_backend);
+    }
+
+    fn test_build<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let items: Vec<_> = (0..5000u32).map(|i| (format!("key{i}"), i * 3)).collect();
+        {
+            let map = MphMap::build(&mut backend, items.clone()).unwrap();
+            assert_eq!(map.len(), 5000);
+        }
+
+        let map = MphMap::<_, String, u32>::init(&mut backend).unwrap();
+        for (k, v) in items.iter() {
+            assert_eq!(map.get(k), Some(*v));
+        }
+        assert_eq!(map.get(&"key5000".to_string()), None);
+        assert!(!map.contains_key(&"foo".to_string()));
+
+        let mut all: Vec<_> = map.iter().collect();
+        all.sort_unstable_by_key(|i| i.1);
+        assert_eq!(all, items);
+
+        backend.clear();
+        let dup = vec![(1u32, 1u32), (2, 2), (1, 3)];
+        assert!(MphMap::build(&mut backend, dup).is_err());
+
+        backend.clear();
+        let empty = MphMap::<_, u32, u32>::build(&mut backend, vec![]).unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(empty.get(&1), None);
+    }
+
+    fn test_from_map<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut map: FMap<_, u64, String> = FMap::create(make_mem_backend(10)).unwrap();
+        for i in 0..300u64 {
+            map.insert(&(i * 7), &i.to_string()).unwrap();
+        }
+
+        let mph = MphMap::from_map(&mut backend, &map).unwrap();
+        assert_eq!(mph.len(), map.len());
+        for i in 0..300u64 {
+            assert_eq!(mph.get(&(i * 7)), Some(i.to_string()));
+        }
+        assert_eq!(mph.get(&1), None);
+    }
+}
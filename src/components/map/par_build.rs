@@ -0,0 +1,202 @@
+use crate::backend::growable::GrowableBackend;
+use crate::components::indexed_file::IndexedFile;
+use crate::components::list::{ListU32, ListU64};
+use crate::components::map::hashing::hashfn::HashFn;
+use crate::components::map::hashing::key_hasher::KeyHasher;
+use crate::components::map::hashing::Hash;
+use crate::components::map::kvpair::KVPair;
+use crate::components::map::{primes, FMap, HASHES_ID, MAX_LOAD};
+use crate::traits::deser::Deser;
+use crate::utils::smallest_two_power_for;
+use crate::Result;
+use rayon::prelude::*;
+
+impl<B, K, V, H> FMap<B, K, V, H>
+    where
+        H: HashFn,
+        B: GrowableBackend,
+        K: Hash + Eq + Deser + Sync,
+        V: Deser + Sync,
+{
+    /// Builds a new map from `pairs` using multiple threads. Keys get hashed, pairs encoded and the hash table filled
+    /// in parallel in memory. Both, the table and KV-storage are written at once afterwards. Maps using Robin Hood
+    /// hashing place their pairs one after another instead. If a key exists multiple times only its first pair gets
+    /// inserted, just like `insert` does.
+    #[inline]
+    pub fn par_build(backend: B, pairs: &[(K, V)]) -> Result<Self> {
+        Self::par_build_with_hasher(backend, pairs, KeyHasher::random_sip())
+    }
+
+    /// Same as `par_build` but uses the given hasher to hash the keys.
+    pub fn par_build_with_hasher(backend: B, pairs: &[(K, V)], hasher: KeyHasher) -> Result<Self> {
+        let encoded: Vec<(u64, Vec<u8>)> = pairs
+            .par_iter()
+            .map(|(k, v)| Ok((hasher.hash(k), bincode::serialize(&KVPair::new(k, v))?)))
+            .collect::<Result<_>>()?;
+
+        let need_cap = (pairs.len() as f32 / MAX_LOAD).ceil() as usize;
+        let capacity = primes::NEXT_PRIMES_OF_TWO[smallest_two_power_for(need_cap) as usize] as usize;
+
+        // Build the hash table in memory. Slots store the index of the pair + 1 and 0 for empty ones. The table gets
+        // split into ranges of slots, each filled by its own thread with the pairs whose home slot lies within it.
+        let mut table = vec![0u32; capacity];
+        let mut hashes = vec![0u64; capacity];
+        let range_len = capacity.div_ceil(rayon::current_num_threads() * 4);
+        let mut ranges = vec![vec![]; capacity.div_ceil(range_len)];
+        for (i, (key_hash, _)) in encoded.iter().enumerate() {
+            ranges[H::probe(None, *key_hash, 0, capacity) as usize / range_len].push(i);
+        }
+
+        let overflow: Vec<usize> = table
+            .par_chunks_mut(range_len)
+            .zip(hashes.par_chunks_mut(range_len))
+            .zip(ranges.par_iter())
+            .enumerate()
+            .flat_map_iter(|(range, ((table, hashes), range_pairs))| {
+                let offset = range * range_len;
+                range_pairs
+                    .iter()
+                    .copied()
+                    .filter(|i| !Self::place_in_range(*i, pairs, &encoded, capacity, offset, table, hashes))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        // Pairs probing past the end of their range get placed afterwards. Equal keys share their probe sequence and
+        // thus their range, so they are still placed in input order and only the first pair of a key gets inserted.
+        for i in overflow {
+            if !Self::place_in_range(i, pairs, &encoded, capacity, 0, &mut table, &mut hashes) {
+                unreachable!()
+            }
+        }
+
+        // KV pairs get stored in input order, so the table has to map to their IDs instead of the indices of the pairs.
+        let mut inserted: Vec<usize> = table.par_iter().filter(|i| **i > 0).map(|i| *i as usize - 1).collect();
+        inserted.par_sort_unstable();
+        let mut kv_ids = vec![0u32; pairs.len()];
+        for (kv_id, i) in inserted.iter().enumerate() {
+            kv_ids[*i] = kv_id as u32;
+        }
+        table.par_iter_mut().filter(|i| **i > 0).for_each(|i| *i = kv_ids[*i as usize - 1] + 1);
+
+        let mut map = Self::with_hasher(backend, 0, hasher)?;
+        map.increase_capacity(capacity)?;
+
+        let items: Vec<&[u8]> = inserted.iter().map(|i| encoded[*i].1.as_slice()).collect();
+        let mut kv_storage: IndexedFile<_> = map.backend.get_backend_mut(2).unwrap();
+        kv_storage.insert_n(&items)?;
+
+        if let Some(distance) = H::ROBIN_HOOD {
+            // Where Robin Hood hashing places a pair depends on the pairs placed before, so the table built above only
+            // drops duplicate keys and the pairs get placed one after another.
+            let (mut table_list, mut hash_list, _) = map.table_hashes_kv_mut()?;
+            for (kv_id, i) in inserted.iter().enumerate() {
+                let key_hash = encoded[*i].0;
+                Self::robin_hood_place(distance, kv_id as u32, key_hash, capacity, &mut table_list, &mut hash_list)?;
+            }
+        } else {
+            let raw_table: Vec<u8> = table.iter().flat_map(|i| i.to_le_bytes()).collect();
+            let mut table_list: ListU32<_> = map.backend.get_backend_mut(1).unwrap();
+            table_list.set_raw_n(0, &raw_table)?;
+
+            let raw_hashes: Vec<u8> = hashes.iter().flat_map(|i| i.to_le_bytes()).collect();
+            let mut hash_list: ListU64<_> = map.backend.get_backend_mut(HASHES_ID).unwrap();
+            hash_list.set_raw_n(0, &raw_hashes)?;
+        }
+
+        map.inc_len(inserted.len())?;
+        Ok(map)
+    }
+
+    /// Places pair `i` into the first empty slot along its probe sequence, unless a pair with the same key has been
+    /// placed already. `table` and `hashes` hold the slots of the table starting at `offset`. Returns `false` without
+    /// placing the pair if its probe sequence leaves those slots before.
+    fn place_in_range(
+        i: usize,
+        pairs: &[(K, V)],
+        encoded: &[(u64, Vec<u8>)],
+        capacity: usize,
+        offset: usize,
+        table: &mut [u32],
+        hashes: &mut [u64],
+    ) -> bool {
+        let key_hash = encoded[i].0;
+        for probe in 0..H::probe_len(capacity) {
+            let slot = (H::probe(None, key_hash, probe, capacity) as usize).wrapping_sub(offset);
+            if slot >= table.len() {
+                return false;
+            }
+
+            match table[slot] {
+                0 => {
+                    table[slot] = i as u32 + 1;
+                    hashes[slot] = key_hash;
+                    return true;
+                }
+                other if pairs[other as usize - 1].0 == pairs[i].0 => return true,
+                _ => {}
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::components::map::hashing::hashfn::RobinHoodProbing;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+    use crate::traits::initiable::Initiable;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_build(&mut mem_backend);
+        test_build_robin_hood(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./fmap_par_build", 10);
+        test_build(&mut mmap_backend);
+        test_build_robin_hood(&mut mmap_backend);
+    }
+
+    fn test_build<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut pairs: Vec<_> = (0..5000u32).map(|i| (format!("key_{i}"), i)).collect();
+        pairs.push((pairs[10].0.clone(), 42));
+
+        {
+            let mut map: FMap<_, String, u32> = FMap::par_build(&mut backend, &pairs).unwrap();
+            assert_eq!(map.len(), 5000);
+            for (k, v) in pairs[..5000].iter() {
+                assert_eq!(map.get(k), Some(*v));
+            }
+            let values: Vec<_> = map.iter_insertion_order().unwrap().map(|i| i.1).collect();
+            assert_eq!(values, (0..5000).collect::<Vec<_>>());
+
+            map.insert(&"new".to_string(), &1).unwrap();
+            assert_eq!(map.get(&"new".to_string()), Some(1));
+        }
+
+        let map: FMap<_, String, u32> = FMap::init(backend).unwrap();
+        assert_eq!(map.len(), 5001);
+        assert_eq!(map.get(&pairs[10].0), Some(10));
+    }
+
+    fn test_build_robin_hood<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut pairs: Vec<_> = (0..5000u32).map(|i| (format!("key_{i}"), i)).collect();
+        pairs.push((pairs[10].0.clone(), 42));
+
+        let mut map: FMap<_, String, u32, RobinHoodProbing> = FMap::par_build(&mut backend, &pairs).unwrap();
+        assert_eq!(map.len(), 5000);
+        for (k, v) in pairs[..5000].iter() {
+            assert_eq!(map.get(k), Some(*v));
+        }
+
+        map.insert(&"new".to_string(), &1).unwrap();
+        assert_eq!(map.get(&"new".to_string()), Some(1));
+        assert_eq!(map.get(&pairs[10].0), Some(10));
+    }
+}
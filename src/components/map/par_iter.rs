@@ -0,0 +1,68 @@
+use crate::backend::Backend;
+use crate::components::map::kvpair::KVPair;
+use crate::components::map::FMap;
+use crate::traits::deser::Deser;
+use rayon::prelude::*;
+
+impl<B, K, V, H> FMap<B, K, V, H>
+    where
+        B: Backend,
+        K: Deser + Send,
+        V: Deser + Send,
+{
+    /// Returns a parallel iterator over all items in the map. The ID range of the KV storage gets split across
+    /// threads, which avoids scanning the hash table. Items are yielded in arbitrary order.
+    pub fn par_iter(&self) -> impl ParallelIterator<Item = (K, V)> + '_ {
+        let storage = self.entry_storage();
+        let removed = self.removed_ids();
+        (0..storage.count()).into_par_iter().filter_map(move |kv_id| {
+            if removed[kv_id] {
+                return None;
+            }
+            let pair: KVPair<K, V> = bincode::deserialize(storage.get(kv_id).unwrap()).unwrap();
+            Some(pair.into())
+        })
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::growable::GrowableBackend;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+    use crate::traits::creatable::Creatable;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_par_iter(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./fmap_par_iter", 10);
+        test_par_iter(&mut mmap_backend);
+    }
+
+    fn test_par_iter<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut map: FMap<_, String, u64> = FMap::create(&mut backend).unwrap();
+        assert_eq!(map.par_iter().count(), 0);
+
+        for i in 0..10_000u64 {
+            map.insert(&format!("key_{i}"), &i).unwrap();
+        }
+        for i in (0..10_000u64).step_by(7) {
+            map.remove(&format!("key_{i}")).unwrap();
+        }
+
+        let mut items: Vec<_> = map.par_iter().collect();
+        let mut expected: Vec<_> = map.iter().collect();
+        items.sort_unstable_by_key(|i| i.1);
+        expected.sort_unstable_by_key(|i| i.1);
+        assert_eq!(items.len(), map.len());
+        assert_eq!(items, expected);
+
+        let sum: u64 = map.par_iter().map(|(_, v)| v).sum();
+        assert_eq!(sum, (0..10_000u64).filter(|i| i % 7 != 0).sum());
+    }
+}
@@ -0,0 +1,222 @@
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::map::hashing::hashfn::HashFn;
+use crate::components::map::{hashing, DefaultHasher, FMap};
+use crate::traits::creatable::Creatable;
+use crate::traits::deser::Deser;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::Result;
+use serde::de::DeserializeOwned;
+
+/// A HashSet similar data structure stored in the given backend. Works the same as `FMap` but only stores keys, so
+/// no bytes are spent on values.
+pub struct FSet<B, K, H = DefaultHasher> {
+    map: FMap<B, K, (), H>,
+}
+
+impl<B, K, H> FSet<B, K, H>
+    where
+        H: HashFn,
+        B: GrowableBackend,
+        K: hashing::Hash + Eq + Deser,
+{
+    /// Adds a key to the set. Returns `false` if the set already contained the key.
+    pub fn insert(&mut self, k: &K) -> Result<bool> {
+        // `FMap::insert` stores the entry before looking the key up, so check first to not leave unused entries.
+        if self.contains(k) {
+            return Ok(false);
+        }
+        self.map.insert(k, &())?;
+        Ok(true)
+    }
+
+    /// Adds all keys of `other` to the set.
+    pub fn union<O: Backend, H2: HashFn>(&mut self, other: &FSet<O, K, H2>) -> Result<()> {
+        for k in other.iter() {
+            self.insert(&k)?;
+        }
+        Ok(())
+    }
+
+    /// Removes all keys from the set.
+    #[inline]
+    pub fn clear(&mut self) -> Result<()> {
+        self.map.clear()
+    }
+}
+
+impl<B, K, H> FSet<B, K, H>
+    where
+        H: HashFn,
+        B: Backend,
+        K: hashing::Hash + Eq + Deser,
+{
+    /// Returns `true` if the set contains the key.
+    #[inline]
+    pub fn contains(&self, k: &K) -> bool {
+        self.map.contains_key(k)
+    }
+}
+
+impl<B, K, H> FSet<B, K, H>
+    where
+        B: Backend,
+        K: DeserializeOwned,
+{
+    /// Returns an iterator over all keys of the set in arbitrary order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = K> + '_ {
+        self.map.iter().map(|i| i.0)
+    }
+}
+
+impl<B, K, H> FSet<B, K, H>
+    where
+        B: Backend,
+{
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.map.flush()
+    }
+}
+
+impl<B, K, H> FSet<B, K, H> {
+    /// Returns the amount of keys in the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the set is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the map holding the keys.
+    #[inline]
+    pub fn as_map(&self) -> &FMap<B, K, (), H> {
+        &self.map
+    }
+}
+
+impl<B, K, H> Creatable<B> for FSet<B, K, H>
+    where
+        B: GrowableBackend,
+{
+    #[inline]
+    fn with_capacity(backend: B, capacity: usize) -> Result<Self> {
+        Ok(Self {
+            map: FMap::with_capacity(backend, capacity)?,
+        })
+    }
+}
+
+impl<B, K, H> Initiable<B> for FSet<B, K, H>
+    where
+        H: HashFn,
+        B: Backend,
+{
+    #[inline]
+    fn init(backend: B) -> Result<Self> {
+        Ok(Self {
+            map: FMap::init(backend)?,
+        })
+    }
+}
+
+impl<B, K, H> Extend<K> for FSet<B, K, H>
+    where
+        H: HashFn,
+        B: GrowableBackend,
+        K: hashing::Hash + Eq + Deser,
+{
+    #[inline]
+    fn extend<T: IntoIterator<Item = K>>(&mut self, iter: T) {
+        for k in iter {
+            self.insert(&k).expect("Failed to insert");
+        }
+    }
+}
+
+impl<B, K, H> MType for FSet<B, K, H>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.map.raw_data()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::memory::MemoryBackend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+    use crate::traits::creatable::MemCreatable;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_set(&mut mem_backend);
+        test_union(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./fset", 10);
+        test_set(&mut mmap_backend);
+        test_union(&mut mmap_backend);
+    }
+
+    fn test_set<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        {
+            let mut set: FSet<_, String> = FSet::create(&mut backend).unwrap();
+            for i in 0..1000 {
+                assert_eq!(set.insert(&i.to_string()), Ok(true));
+            }
+            assert_eq!(set.insert(&"5".to_string()), Ok(false));
+            assert_eq!(set.len(), 1000);
+        }
+
+        let set: FSet<_, String> = FSet::init(&mut backend).unwrap();
+        assert!(set.contains(&"999".to_string()));
+        assert!(!set.contains(&"1000".to_string()));
+        let keys: HashSet<_> = set.iter().collect();
+        assert_eq!(keys, (0..1000).map(|i| i.to_string()).collect());
+
+        // Storing no values needs less space than a map storing a value for each key.
+        let mut map: FMap<MemoryBackend, String, u64> = FMap::create_mem_with_capacity(10).unwrap();
+        for i in 0..1000u64 {
+            map.insert(&i.to_string(), &i).unwrap();
+        }
+        let set_storage = set.as_map().entry_storage();
+        let map_storage = map.entry_storage();
+        assert_eq!(set_storage.count(), map_storage.count());
+        for (i, key) in set_storage.iter().enumerate() {
+            assert_eq!(key.len() + 8, map_storage.get(i).unwrap().len());
+        }
+    }
+
+    fn test_union<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut a: FSet<_, u32> = FSet::create(&mut backend).unwrap();
+        a.extend(0..100);
+
+        let mut b: FSet<MemoryBackend, u32> = FSet::create_mem_with_capacity(10).unwrap();
+        b.extend(50..200);
+
+        a.union(&b).unwrap();
+        assert_eq!(a.len(), 200);
+        let mut keys: Vec<_> = a.iter().collect();
+        keys.sort_unstable();
+        assert_eq!(keys, (0..200).collect::<Vec<_>>());
+
+        a.clear().unwrap();
+        assert!(a.is_empty());
+        assert!(!a.contains(&1));
+    }
+}
@@ -0,0 +1,23 @@
+/// Statistics about the hash table of an `FMap`, returned by `FMap::stats`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapStats {
+    pub len: usize,
+    pub capacity: usize,
+    pub load_factor: f32,
+    /// Amount of table slots holding a tombstone of a removed key.
+    pub deleted: usize,
+    /// Amount of keys for each probe length, where the keys at index 0 are the ones placed in their home slot.
+    pub probe_histogram: Vec<usize>,
+    pub avg_probes: f32,
+    pub max_probes: usize,
+    /// Amount of bytes used by removed KV pairs and tombstones which can be reclaimed by `shrink_to_fit`.
+    pub wasted_bytes: usize,
+}
+
+impl MapStats {
+    /// Returns the amount of keys that collided with at least one other key while being inserted.
+    #[inline]
+    pub fn collisions(&self) -> usize {
+        self.probe_histogram.iter().skip(1).sum()
+    }
+}
@@ -0,0 +1,303 @@
+use crate::backend::base::sub::GeneralSubBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::multi_file::entry_mut::MFileEntryMut;
+use crate::components::multi_file::MultiFile;
+use crate::components::number_seq::NumberSequence;
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::traits::sized_deser::SizedDeser;
+use crate::{Error, Result};
+use std::marker::PhantomData;
+
+pub type MatrixF32<B> = Matrix<B, f32, 4>;
+pub type MatrixF64<B> = Matrix<B, f64, 8>;
+
+/// ID of the MultiFile entry holding the metadata (rows, columns).
+const METADATA_ID: usize = 0;
+
+/// ID of the MultiFile entry holding the values.
+const VALUES_ID: usize = 1;
+
+/// Size of the metadata: rows u64 | cols u64
+const METADATA_LEN: usize = 16;
+
+/// A dense `rows` x `cols` matrix of fixed-size values. Values are stored row by row, so reading a whole row is a
+/// single contiguous read.
+///
+/// The internal format: MultiFile(METADATA | VALUES)
+/// where VALUES is a `NumberSequence` of `rows * cols` values.
+pub struct Matrix<B, T, const N: usize> {
+    backend: MultiFile<B>,
+    rows: usize,
+    cols: usize,
+    p: PhantomData<T>,
+}
+
+impl<B, T, const N: usize> Matrix<B, T, N> {
+    /// Returns the amount of rows.
+    #[inline]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the amount of columns.
+    #[inline]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns `true` if the matrix doesn't have any values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0 || self.cols == 0
+    }
+
+    /// Returns the index of the value at the given position in the value sequence.
+    #[inline]
+    fn index(&self, row: usize, col: usize) -> Result<usize> {
+        if row >= self.rows || col >= self.cols {
+            return Err(Error::OutOfBounds);
+        }
+        Ok(row * self.cols + col)
+    }
+}
+
+impl<B, T, const N: usize> Matrix<B, T, N>
+    where
+        B: GrowableBackend,
+        T: SizedDeser<N>,
+{
+    /// Creates a new `rows` x `cols` matrix with all values zeroed.
+    pub fn new(backend: B, rows: usize, cols: usize) -> Result<Self> {
+        let mut backend = MultiFile::with_capacity(backend, 2)?;
+
+        let mut metadata = backend.insert_empty()?;
+        metadata.grow_to(METADATA_LEN)?;
+        metadata.push(&(rows as u64).to_le_bytes())?;
+        metadata.push(&(cols as u64).to_le_bytes())?;
+
+        let mut values = backend.insert_empty()?;
+        let size = rows * cols * N;
+        if size > 0 {
+            values.grow_to(size)?;
+            values.push_fill(0, size)?;
+        }
+
+        Ok(Self {
+            backend,
+            rows,
+            cols,
+            p: PhantomData,
+        })
+    }
+
+    /// Changes the dimensions of the matrix. Values within both, the old and new dimensions are kept and new values
+    /// are zeroed.
+    pub fn resize(&mut self, rows: usize, cols: usize) -> Result<()> {
+        let values = self.backend.get(VALUES_ID).unwrap();
+        let mut data = vec![0; rows * cols * N];
+        let keep_cols = self.cols.min(cols) * N;
+        for row in 0..self.rows.min(rows) {
+            let old = values.get(row * self.cols * N, keep_cols)?;
+            data[row * cols * N..row * cols * N + keep_cols].copy_from_slice(old);
+        }
+
+        let mut entry = self.backend.entry_mut(VALUES_ID).ok_or(Error::OutOfBounds)?;
+        entry.clear();
+        if entry.capacity() < data.len() {
+            entry.grow_to(data.len())?;
+        }
+        entry.push(&data)?;
+
+        let mut metadata = self.backend.get_mut(METADATA_ID).unwrap();
+        metadata.replace_same_len(0, &(rows as u64).to_le_bytes())?;
+        metadata.replace_same_len(8, &(cols as u64).to_le_bytes())?;
+        self.rows = rows;
+        self.cols = cols;
+        Ok(())
+    }
+}
+
+impl<B, T, const N: usize> Matrix<B, T, N>
+    where
+        B: Backend,
+        T: SizedDeser<N>,
+{
+    /// Returns the value at the given position.
+    #[inline]
+    pub fn get(&self, row: usize, col: usize) -> Result<T> {
+        self.values().get(self.index(row, col)?)
+    }
+
+    /// Sets the value at the given position.
+    #[inline]
+    pub fn set(&mut self, row: usize, col: usize, value: T) -> Result<()> {
+        let index = self.index(row, col)?;
+        self.values_mut().set(index, value)
+    }
+
+    /// Returns all values of the given row.
+    pub fn row(&self, row: usize) -> Result<Vec<T>> {
+        if row >= self.rows {
+            return Err(Error::OutOfBounds);
+        }
+        let values = self.backend.get(VALUES_ID).unwrap();
+        let data = values.get(row * self.cols * N, self.cols * N)?;
+        Ok(data
+            .chunks_exact(N)
+            .map(|i| T::from_bytes(i.try_into().unwrap()))
+            .collect())
+    }
+
+    /// Replaces all values of the given row. The length of `values` has to match the amount of columns.
+    pub fn set_row(&mut self, row: usize, values: &[T]) -> Result<()> {
+        if values.len() != self.cols {
+            return Err(Error::UnexpectedValue);
+        }
+        let start = self.index(row, 0)? * N;
+        let data: Vec<u8> = values.iter().flat_map(|i| i.to_bytes()).collect();
+        self.backend.get_mut(VALUES_ID).unwrap().replace_same_len(start, &data)?;
+        Ok(())
+    }
+
+    /// Returns all values of the given column.
+    pub fn column(&self, col: usize) -> Result<Vec<T>> {
+        if col >= self.cols {
+            return Err(Error::OutOfBounds);
+        }
+        let values = self.values();
+        (0..self.rows).map(|row| values.get(row * self.cols + col)).collect()
+    }
+
+    /// Returns an iterator over all rows.
+    #[inline]
+    pub fn iter_rows(&self) -> impl ExactSizeIterator<Item = Vec<T>> + '_ {
+        (0..self.rows).map(|i| self.row(i).expect("Failed to load row"))
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.backend.flush()
+    }
+
+    #[inline]
+    fn values(&self) -> NumberSequence<GeneralSubBackend<'_>, T, N> {
+        self.backend.get_backend(VALUES_ID).unwrap()
+    }
+
+    #[inline]
+    fn values_mut(&mut self) -> NumberSequence<MFileEntryMut<'_, B>, T, N> {
+        self.backend.get_backend_mut(VALUES_ID).unwrap()
+    }
+}
+
+impl<B, T, const N: usize> Initiable<B> for Matrix<B, T, N>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let backend = MultiFile::init(backend)?;
+
+        let metadata = backend.get(METADATA_ID).ok_or(Error::Initialization)?;
+        let metadata = metadata.get(0, METADATA_LEN)?;
+        let rows = u64::from_le_bytes(metadata[..8].try_into().unwrap()) as usize;
+        let cols = u64::from_le_bytes(metadata[8..].try_into().unwrap()) as usize;
+
+        let values = backend.get(VALUES_ID).ok_or(Error::Initialization)?;
+        if values.len() != rows * cols * N {
+            return Err(Error::Initialization);
+        }
+
+        Ok(Self {
+            backend,
+            rows,
+            cols,
+            p: PhantomData,
+        })
+    }
+}
+
+impl<B, T, const N: usize> MType for Matrix<B, T, N>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.backend.raw_data()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_matrix(&mut mem_backend);
+        test_resize(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./matrix", 10);
+        test_matrix(&mut mmap_backend);
+        test_resize(&mut mmap_backend);
+    }
+
+    fn test_matrix<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        {
+            let mut matrix: Matrix<_, u32, 4> = Matrix::new(&mut backend, 40, 30).unwrap();
+            assert_eq!(matrix.get(39, 29), Ok(0));
+            for r in 0..40 {
+                for c in 0..30 {
+                    matrix.set(r, c, (r * 100 + c) as u32).unwrap();
+                }
+            }
+            assert_eq!(matrix.set(40, 0, 1), Err(Error::OutOfBounds));
+            assert_eq!(matrix.set(0, 30, 1), Err(Error::OutOfBounds));
+        }
+
+        let mut matrix: Matrix<_, u32, 4> = Matrix::init(&mut backend).unwrap();
+        assert_eq!((matrix.rows(), matrix.cols()), (40, 30));
+        assert_eq!(matrix.get(12, 7), Ok(1207));
+        assert_eq!(matrix.row(3).unwrap(), (0..30).map(|c| 300 + c).collect::<Vec<_>>());
+        assert_eq!(matrix.column(5).unwrap(), (0..40).map(|r| r * 100 + 5).collect::<Vec<_>>());
+        assert!(matrix.row(40).is_err());
+        assert_eq!(matrix.iter_rows().len(), 40);
+
+        matrix.set_row(3, &[7; 30]).unwrap();
+        assert_eq!(matrix.row(3).unwrap(), vec![7; 30]);
+        assert_eq!(matrix.get(4, 0), Ok(400));
+        assert_eq!(matrix.set_row(3, &[7; 29]), Err(Error::UnexpectedValue));
+    }
+
+    fn test_resize<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut matrix: MatrixF64<_> = Matrix::new(&mut backend, 3, 3).unwrap();
+        for r in 0..3 {
+            matrix.set_row(r, &[r as f64, r as f64 + 0.5, -1.0]).unwrap();
+        }
+
+        matrix.resize(4, 2).unwrap();
+        assert_eq!((matrix.rows(), matrix.cols()), (4, 2));
+        assert_eq!(matrix.iter_rows().collect::<Vec<_>>(), vec![
+            vec![0.0, 0.5],
+            vec![1.0, 1.5],
+            vec![2.0, 2.5],
+            vec![0.0, 0.0],
+        ]);
+
+        matrix.resize(2, 4).unwrap();
+        assert_eq!(matrix.row(1).unwrap(), vec![1.0, 1.5, 0.0, 0.0]);
+
+        matrix.resize(0, 4).unwrap();
+        assert!(matrix.is_empty());
+
+        let matrix: MatrixF64<_> = Matrix::init(&mut backend).unwrap();
+        assert_eq!((matrix.rows(), matrix.cols()), (0, 4));
+    }
+}
@@ -1,9 +1,37 @@
+pub mod allocator;
 pub mod bitvec;
+pub mod blob_store;
+pub mod btree;
+pub mod columns;
+pub mod csr_matrix;
+pub mod deque;
+pub mod external_sort;
+pub mod graph;
 pub mod header_file;
 pub mod indexed_file;
+pub mod interval_tree;
 pub mod list;
+pub mod lru;
 pub mod map;
+pub mod matrix;
 pub mod metadata;
 pub mod multi_file;
+pub mod ngram;
 pub mod number_seq;
+pub mod queue;
+pub mod rank_select;
+pub mod roaring;
+pub mod rtree;
+pub mod sequence;
+pub mod sketch;
+pub mod slab;
+pub mod sorted_list;
+pub mod sparse_vec;
 pub mod split_file;
+pub mod string_list;
+pub mod suffix_array;
+pub mod table;
+pub mod trie;
+pub mod union_find;
+pub mod vector_store;
+pub mod versioned;
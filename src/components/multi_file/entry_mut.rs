@@ -1,8 +1,10 @@
 use crate::backend::growable::GrowableBackend;
+use crate::backend::growth::GrowthPolicy;
 use crate::backend::Backend;
 use crate::components::multi_file::MultiFile;
 use crate::header::BaseHeader;
 use crate::Result;
+use std::ops::Range;
 
 /// A single entry in a `MultiFile` implementing `GrowableBackend`.
 pub struct MFileEntryMut<'a, B> {
@@ -31,6 +33,11 @@ where
         self.file.get_be_data_mut(self.id).unwrap()
     }
 
+    #[inline]
+    fn data_mut_range(&mut self, range: Range<usize>) -> &mut [u8] {
+        self.file.get_be_data_mut_range(self.id, range).unwrap()
+    }
+
     #[inline]
     fn first_index(&self) -> usize {
         BaseHeader::len_bytes()
@@ -43,7 +50,7 @@ where
 
     #[inline]
     fn set_len(&mut self, len: usize) -> Result<()> {
-        self.file.get_mut(self.id).unwrap().set_len(len)
+        self.file.set_be_len(self.id, len)
     }
 }
 
@@ -72,4 +79,9 @@ where
             self.shrink(delta.unsigned_abs())
         }
     }
+
+    #[inline]
+    fn growth_policy(&self) -> &dyn GrowthPolicy {
+        self.file.growth_policy()
+    }
 }
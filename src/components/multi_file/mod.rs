@@ -4,6 +4,7 @@ use crate::backend::base::sub::BaseSubBackend;
 use crate::backend::base::sub_mut::{BaseSubMutBackend, GeneralSubMutBackend};
 use crate::backend::base::BaseBackend;
 use crate::backend::growable::GrowableBackend;
+use crate::backend::growth::GrowthPolicy;
 use crate::backend::Backend;
 use crate::components::indexed_file::IndexedFile;
 use crate::components::multi_file::entry_mut::MFileEntryMut;
@@ -13,6 +14,7 @@ use crate::traits::initiable::Initiable;
 use crate::traits::mtype::MType;
 use crate::{Error, Result};
 use mult_split::MultiSplit;
+use std::ops::Range;
 
 /// Similar to `SplitFile<B>` but supports n different sub-backends encoded in a single file.
 pub struct MultiFile<B> {
@@ -24,6 +26,43 @@ impl<B> MultiFile<B>
     where
         B: Backend,
 {
+    /// Returns the underlying backend.
+    #[inline]
+    pub(crate) fn inner(&self) -> &B {
+        self.backend.inner()
+    }
+
+    /// Starts recording all writes so they can be rolled back with `rollback_undo()`.
+    #[inline]
+    pub(crate) fn begin_undo(&mut self) {
+        self.backend.begin_undo();
+    }
+
+    /// Keeps all writes done since the last call of `begin_undo()`.
+    #[inline]
+    pub(crate) fn commit_undo(&mut self) {
+        self.backend.commit_undo();
+    }
+
+    /// Reads the state again from the backend.
+    pub(crate) fn reload(&mut self) -> Result<()> {
+        self.backend.reload()?;
+        self.headers = Self::read_headers(&mut self.backend)?;
+        Ok(())
+    }
+
+    fn read_headers(backend: &mut IndexedFile<B>) -> Result<Vec<BaseHeader>> {
+        let mut headers: Vec<BaseHeader> = Vec::with_capacity(backend.count());
+
+        for i in 0..backend.count() {
+            let entry = backend.entry(i)?;
+            let entry_be = BaseBackend::from_storage(entry)?;
+            headers.push(*entry_be.header());
+        }
+
+        Ok(headers)
+    }
+
     /// Gets a Backend by its ID.
     pub fn get(&self, id: usize) -> Option<BaseSubBackend<&[u8]>> {
         let data = self.backend.get(id).ok()?;
@@ -125,16 +164,46 @@ impl<B> MultiFile<B>
     }
 
     #[inline]
-    pub(super) fn get_be_data_mut(&mut self, id: usize) -> Result<&mut [u8]> {
+    pub(crate) fn get_be_data_mut(&mut self, id: usize) -> Result<&mut [u8]> {
         self.backend.get_mut(id)
     }
+
+    #[inline]
+    pub(crate) fn get_be_data_mut_range(&mut self, id: usize, range: Range<usize>) -> Result<&mut [u8]> {
+        self.backend.get_mut_range(id, range)
+    }
+
+    /// Sets the length of the backend with the given ID by only writing its header.
+    pub(crate) fn set_be_len(&mut self, id: usize, len: usize) -> Result<()> {
+        if len > self.get_be_data(id)?.len() {
+            return Err(Error::OutOfBounds);
+        }
+
+        let header = self.headers.get_mut(id).ok_or(Error::OutOfBounds)?;
+        header.set_data_len(len);
+        let bytes = header.bytes();
+        self.backend.get_mut_range(id, 0..BaseHeader::len_bytes())?.copy_from_slice(&bytes);
+        Ok(())
+    }
     #[inline]
-    pub(super) fn get_be_data(&self, id: usize) -> Result<&[u8]> {
+    pub(crate) fn get_be_data(&self, id: usize) -> Result<&[u8]> {
         self.backend.get(id)
     }
 }
 
 impl<B: GrowableBackend> MultiFile<B> {
+    /// Returns the growth policy of the underlying backend.
+    #[inline]
+    pub fn growth_policy(&self) -> &dyn GrowthPolicy {
+        self.backend.growth_policy()
+    }
+
+    /// Rolls back all writes done since the last call of `begin_undo()`.
+    pub(crate) fn rollback_undo(&mut self) -> Result<()> {
+        self.backend.rollback_undo()?;
+        self.reload()
+    }
+
     /// Inserts a new backend type into the MultiFile. This must start with a BaseHeader.
     pub fn insert<T>(&mut self, item: T) -> Result<usize>
         where
@@ -156,6 +225,29 @@ impl<B: GrowableBackend> MultiFile<B> {
         E::create(self.insert_empty()?)
     }
 
+    /// Duplicates the entry with the given ID including its unused capacity and returns the ID of the copy. Both
+    /// entries are independent afterwards.
+    pub fn clone_entry(&mut self, id: usize) -> Result<usize> {
+        let header = *self.header(id).ok_or(Error::OutOfBounds)?;
+        let data = self.backend.get(id)?.to_vec();
+        let new_id = self.backend.insert(&data)?;
+        self.headers.push(header);
+        Ok(new_id)
+    }
+
+    /// Replaces the content of the entry with the given ID by `data`, growing the entry if it doesn't fit.
+    pub fn set_entry(&mut self, id: usize, data: &[u8]) -> Result<()> {
+        let mut entry = self.entry_mut(id).ok_or(Error::OutOfBounds)?;
+        let capacity = entry.capacity();
+        if capacity < data.len() {
+            let new_capacity = entry.growth_policy().next_capacity(capacity, data.len());
+            entry.grow(new_capacity - capacity)?;
+        }
+        entry.clear();
+        entry.push(data)?;
+        Ok(())
+    }
+
     /// Inserts a new item containing a default base header.
     pub fn insert_empty(&mut self) -> Result<MFileEntryMut<B>> {
         let empty = &mut BaseHeader::new(0).bytes()[..];
@@ -165,9 +257,38 @@ impl<B: GrowableBackend> MultiFile<B> {
         Ok(MFileEntryMut::new(self, id))
     }
 
-    #[inline]
+    /// Grows the entry with the given ID by `size` bytes. The capacity gets taken from the nearest entry having enough
+    /// unused capacity, so space freed by removing data from any entry gets reused instead of growing the file. Only
+    /// the entries in between get moved. The file grows if no entry has enough unused capacity or if the last entry
+    /// fits into the unused space at the end of the file.
     pub(super) fn grow(&mut self, id: usize, size: usize) -> Result<()> {
-        self.backend.grow_entry(id, size, 0)
+        if size == 0 {
+            return Ok(());
+        }
+
+        let fits_at_end = id + 1 == self.count() && self.backend.second_can_fit(size);
+        match self.nearest_free(id, size) {
+            Some(from) if !fits_at_end => {
+                let from_used = self.used_bytes(from)?;
+                let to_used = self.used_bytes(id)?;
+                self.backend.move_entry_capacity(from, from_used, id, to_used, size)
+            }
+            _ => self.backend.grow_entry(id, size, 0),
+        }
+    }
+
+    /// Returns the ID of the entry closest to `id` having at least `size` bytes of unused capacity.
+    fn nearest_free(&self, id: usize, size: usize) -> Option<usize> {
+        (0..self.count())
+            .filter(|i| *i != id && self.get(*i).is_some_and(|e| e.free() >= size))
+            .min_by_key(|i| i.abs_diff(id))
+    }
+
+    /// Returns the amount of bytes in use by the entry with the given ID, including its header.
+    #[inline]
+    fn used_bytes(&self, id: usize) -> Result<usize> {
+        let header = self.header(id).ok_or(Error::OutOfBounds)?;
+        Ok(BaseHeader::len_bytes() + header.data_len())
     }
 
     #[inline]
@@ -175,6 +296,22 @@ impl<B: GrowableBackend> MultiFile<B> {
         self.backend.shrink_to_fit()
     }
 
+    /// Removes the unused capacity of all entries, packing them contiguously, and shrinks the underlying backend.
+    /// Returns the amount of bytes that were freed within the entries.
+    pub fn defragment(&mut self) -> Result<usize> {
+        let mut freed = 0;
+        for id in 0..self.count() {
+            let free = self.get(id).ok_or(Error::OutOfBounds)?.free();
+            if free > 0 {
+                self.shrink(id, free)?;
+                freed += free;
+            }
+        }
+
+        self.shrink_to_fit()?;
+        Ok(freed)
+    }
+
     #[inline]
     pub(super) fn shrink(&mut self, id: usize, size: usize) -> Result<()> {
         self.backend.shrink_entry_unchecked(id, size)
@@ -213,14 +350,7 @@ impl<B: GrowableBackend> Creatable<B> for MultiFile<B> {
 impl<B: Backend> Initiable<B> for MultiFile<B> {
     fn init(backend: B) -> Result<Self> {
         let mut backend = IndexedFile::init(backend)?;
-        let mut headers: Vec<BaseHeader> = Vec::with_capacity(backend.count());
-
-        for i in 0..backend.count() {
-            let entry = backend.entry(i)?;
-            let entry_be = BaseBackend::from_storage(entry)?;
-            headers.push(*entry_be.header());
-        }
-
+        let headers = Self::read_headers(&mut backend)?;
         Ok(Self { backend, headers })
     }
 }
@@ -233,6 +363,7 @@ impl<B: Backend> MType for MultiFile<B> {
 }
 
 #[cfg(test)]
+#[cfg(feature = "mmap")]
 mod test {
     use super::*;
     use crate::backend::memory::test::make_mem_backend;
@@ -249,11 +380,158 @@ mod test {
         let mut mfile = MultiFile::create(backend).unwrap();
         test_simple(&mut mfile);
         test(&mut mfile);
+        mfile.clear();
+        test_defragment(&mut mfile);
+        mfile.clear();
+        test_clone_entry(&mut mfile);
+        mfile.clear();
+        test_set_entry(&mut mfile);
+        mfile.clear();
+        test_get_borrowed(&mut mfile);
+        mfile.clear();
+        test_grow_reuses_free_space(&mut mfile);
 
         let backend = make_mmap_backend("./mfile", 10);
         let mut mfile = MultiFile::create(backend).unwrap();
         test_simple(&mut mfile);
         test(&mut mfile);
+        mfile.clear();
+        test_defragment(&mut mfile);
+        mfile.clear();
+        test_clone_entry(&mut mfile);
+        mfile.clear();
+        test_set_entry(&mut mfile);
+        mfile.clear();
+        test_get_borrowed(&mut mfile);
+        mfile.clear();
+        test_grow_reuses_free_space(&mut mfile);
+    }
+
+    fn test_defragment<B: GrowableBackend>(mfile: &mut MultiFile<B>) {
+        for i in 0..5 {
+            let mut list: IndexedFile<_> = mfile.insert_new_backend().unwrap();
+            for j in 0..=i {
+                list.insert(&[j as u8; 10]).unwrap();
+            }
+        }
+        for i in 0..5 {
+            mfile.grow(i, 100 * (i + 1)).unwrap();
+        }
+
+        let slack: usize = (0..5).map(|i| mfile.get(i).unwrap().free()).sum();
+        assert!(slack >= 1500);
+        let size_before = mfile.raw_data().len();
+
+        assert_eq!(mfile.defragment(), Ok(slack));
+        assert!(mfile.raw_data().len() <= size_before - slack);
+        assert_eq!(mfile.defragment(), Ok(0));
+
+        for i in 0..5 {
+            assert_eq!(mfile.get(i).unwrap().free(), 0);
+            let list: IndexedFile<_> = mfile.get_backend(i).unwrap();
+            assert_eq!(list.count(), i + 1);
+            for j in 0..=i {
+                assert_eq!(list.get(j), Ok(&[j as u8; 10][..]));
+            }
+        }
+
+        // Entries can still grow after being defragmented.
+        let mut list: IndexedFile<_> = mfile.get_backend_mut(2).unwrap();
+        list.insert(&[42; 50]).unwrap();
+        let list: IndexedFile<_> = mfile.get_backend(3).unwrap();
+        assert_eq!(list.get(3), Ok(&[3; 10][..]));
+    }
+
+    fn test_grow_reuses_free_space<B: GrowableBackend>(mfile: &mut MultiFile<B>) {
+        for i in 0..4 {
+            mfile.insert_empty().unwrap();
+            mfile.set_entry(i, &[i as u8; 10]).unwrap();
+        }
+
+        // Replacing the content with less data leaves unused capacity behind.
+        mfile.set_entry(1, &[1; 500]).unwrap();
+        mfile.set_entry(1, &[1; 5]).unwrap();
+        let free = mfile.get(1).unwrap().free();
+        let size = mfile.raw_data().len();
+
+        // Entries before and after it grow into the unused capacity instead of growing the file.
+        mfile.grow(0, 100).unwrap();
+        mfile.grow(2, 200).unwrap();
+        assert_eq!(mfile.get(1).unwrap().free(), free - 300);
+        assert_eq!(mfile.get(0).unwrap().free(), 100);
+        assert_eq!(mfile.get(2).unwrap().free(), 200);
+        assert_eq!(mfile.raw_data().len(), size);
+
+        mfile.reload().unwrap();
+        for (i, len) in [(0, 10), (1, 5), (2, 10), (3, 10)] {
+            assert_eq!(mfile.get(i).unwrap().content_data(), vec![i as u8; len]);
+        }
+
+        // Without enough unused capacity the entries keep theirs.
+        mfile.grow(0, free).unwrap();
+        assert_eq!(mfile.get(0).unwrap().free(), 100 + free);
+        assert_eq!(mfile.get(1).unwrap().free(), free - 300);
+        assert_eq!(mfile.get(3).unwrap().content_data(), vec![3; 10]);
+    }
+
+    fn test_set_entry<B: GrowableBackend>(mfile: &mut MultiFile<B>) {
+        mfile.insert_empty().unwrap();
+        mfile.insert_empty().unwrap();
+        assert_eq!(mfile.set_entry(2, &[1]), Err(Error::OutOfBounds));
+
+        mfile.set_entry(0, &[1, 2, 3]).unwrap();
+        assert_eq!(mfile.get(0).unwrap().content_data(), &[1, 2, 3]);
+
+        // Growing an entry keeps the following one intact.
+        mfile.set_entry(1, &[9; 4]).unwrap();
+        mfile.set_entry(0, &[7; 100]).unwrap();
+        assert_eq!(mfile.get(0).unwrap().content_data(), &[7; 100]);
+        assert_eq!(mfile.get(1).unwrap().content_data(), &[9; 4]);
+
+        // Shorter content replaces the previous one completely.
+        mfile.set_entry(0, &[5]).unwrap();
+        assert_eq!(mfile.get(0).unwrap().content_data(), &[5]);
+    }
+
+    fn test_get_borrowed<B: GrowableBackend>(mfile: &mut MultiFile<B>) {
+        let mut list: IndexedFile<_> = mfile.insert_new_backend().unwrap();
+        list.insert(&[1, 2, 3]).unwrap();
+        list.insert(&[4]).unwrap();
+
+        // The data stays accessible after the sub-backends are gone.
+        let (entry, content) = {
+            let list: IndexedFile<BaseSubBackend<&[u8]>> = mfile.get_backend(0).unwrap();
+            let content = mfile.get(0).unwrap().content_data_borrowed();
+            (list.get_borrowed(1).unwrap(), content)
+        };
+        assert_eq!(entry, &[4]);
+        assert_eq!(content, mfile.get(0).unwrap().content_data());
+    }
+
+    fn test_clone_entry<B: GrowableBackend>(mfile: &mut MultiFile<B>) {
+        let mut list: IndexedFile<_> = mfile.insert_new_backend().unwrap();
+        list.insert(&[1, 2, 3]).unwrap();
+        list.insert(&[4]).unwrap();
+        mfile.insert_empty().unwrap();
+
+        let id = mfile.clone_entry(0).unwrap();
+        assert_eq!(id, 2);
+        assert_eq!(mfile.get(0).unwrap().len(), mfile.get(id).unwrap().len());
+
+        // Modifying the clone doesn't change the original.
+        let mut clone: IndexedFile<_> = mfile.get_backend_mut(id).unwrap();
+        assert_eq!(clone.get(1), Ok(&[4][..]));
+        clone.insert(&[5, 6]).unwrap();
+        assert_eq!(clone.count(), 3);
+
+        let list: IndexedFile<_> = mfile.get_backend(0).unwrap();
+        assert_eq!(list.count(), 2);
+        assert_eq!(list.get(0), Ok(&[1, 2, 3][..]));
+
+        let clone: IndexedFile<_> = mfile.get_backend(id).unwrap();
+        assert_eq!(clone.get(2), Ok(&[5, 6][..]));
+
+        assert!(mfile.clone_entry(10).is_err());
     }
 
     fn test_simple<B: GrowableBackend>(mfile: &mut MultiFile<B>) {
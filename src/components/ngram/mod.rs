@@ -0,0 +1,256 @@
+mod postings;
+
+use crate::backend::base::sub::GeneralSubBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::indexed_file::IndexedFile;
+use crate::components::map::FMap;
+use crate::components::multi_file::MultiFile;
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::{Error, Result};
+use std::collections::{BTreeMap, HashSet};
+
+/// ID of the MultiFile entry holding the metadata (n, document count).
+const METADATA_ID: usize = 0;
+
+/// ID of the MultiFile entry holding the map of n-grams to their encoded posting lists.
+const POSTINGS_ID: usize = 1;
+
+/// Map type used to store the posting lists.
+type PostingsMap<B> = FMap<B, Vec<u8>, Vec<u8>>;
+
+/// An n-gram index mapping all byte n-grams of a set of documents to the (compressed) list of document IDs they
+/// occur in. Queries return candidate IDs that contain all n-grams of the query and therefore *might* contain the
+/// query, so the candidates have to be verified by the caller.
+///
+/// The internal format: MultiFile(METADATA | FMap<GRAM, POSTINGS>)
+pub struct NGramIndex<B> {
+    backend: MultiFile<B>,
+    n: usize,
+    doc_count: usize,
+}
+
+impl<B> NGramIndex<B>
+    where
+        B: GrowableBackend,
+{
+    /// Builds an n-gram index over all documents of `docs`. The document IDs returned by queries are the IDs of the
+    /// documents in `docs`.
+    pub fn build<D: Backend>(backend: B, n: usize, docs: &IndexedFile<D>) -> Result<Self> {
+        Self::build_from_iter(backend, n, (0..docs.count()).map(|i| docs.get(i).unwrap()))
+    }
+
+    /// Builds an n-gram index over all documents yielded by `docs`. The n-th document gets the ID n.
+    pub fn build_from_iter<I, D>(backend: B, n: usize, docs: I) -> Result<Self>
+        where
+            I: IntoIterator<Item=D>,
+            D: AsRef<[u8]>,
+    {
+        if n == 0 {
+            return Err(Error::UnexpectedValue);
+        }
+
+        let mut grams: BTreeMap<&[u8], Vec<u32>> = BTreeMap::new();
+        let docs: Vec<D> = docs.into_iter().collect();
+        for (id, doc) in docs.iter().enumerate() {
+            let doc_grams: HashSet<&[u8]> = doc.as_ref().windows(n).collect();
+            for gram in doc_grams {
+                grams.entry(gram).or_default().push(id as u32);
+            }
+        }
+
+        let mut backend = MultiFile::create(backend)?;
+
+        let mut metadata = backend.insert_empty()?;
+        metadata.grow_to(8)?;
+        metadata.push(&(n as u32).to_le_bytes())?;
+        metadata.push(&(docs.len() as u32).to_le_bytes())?;
+
+        let mut map: PostingsMap<_> = FMap::with_capacity(backend.insert_empty()?, grams.len())?;
+        for (gram, ids) in grams {
+            map.insert(&gram.to_vec(), &postings::encode(&ids))?;
+        }
+
+        Ok(Self {
+            backend,
+            n,
+            doc_count: docs.len(),
+        })
+    }
+}
+
+impl<B> NGramIndex<B>
+    where
+        B: Backend,
+{
+    /// Returns the sorted IDs of all documents containing `gram`. `gram` must have a length of `n`.
+    pub fn postings(&self, gram: &[u8]) -> Vec<u32> {
+        if gram.len() != self.n {
+            return vec![];
+        }
+
+        self.postings_map()
+            .get(&gram.to_vec())
+            .map(|i| postings::decode(&i))
+            .unwrap_or_default()
+    }
+
+    /// Returns the sorted IDs of all documents that contain all n-grams of `query`. If the query is shorter than `n`,
+    /// all documents are returned as candidates.
+    pub fn query(&self, query: &[u8]) -> Vec<u32> {
+        if query.len() < self.n {
+            return (0..self.doc_count as u32).collect();
+        }
+
+        let map = self.postings_map();
+
+        let grams: HashSet<&[u8]> = query.windows(self.n).collect();
+        let mut lists = Vec::with_capacity(grams.len());
+        for gram in grams {
+            match map.get(&gram.to_vec()) {
+                Some(list) => lists.push(postings::decode(&list)),
+                // A gram without postings means that no document can match.
+                None => return vec![],
+            }
+        }
+
+        // Intersect shortest lists first to keep the candidate set small.
+        lists.sort_unstable_by_key(|i| i.len());
+        let mut lists = lists.into_iter();
+        let mut candidates = lists.next().unwrap_or_default();
+        for list in lists {
+            if candidates.is_empty() {
+                break;
+            }
+            candidates = postings::intersect(&candidates, &list);
+        }
+
+        candidates
+    }
+
+    /// Returns the length of the n-grams.
+    #[inline]
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Returns the amount of indexed documents.
+    #[inline]
+    pub fn doc_count(&self) -> usize {
+        self.doc_count
+    }
+
+    /// Returns the amount of distinct n-grams in the index.
+    #[inline]
+    pub fn gram_count(&self) -> usize {
+        self.postings_map().len()
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.backend.flush()
+    }
+
+    #[inline]
+    fn postings_map(&self) -> PostingsMap<GeneralSubBackend<'_>> {
+        self.backend.get_backend(POSTINGS_ID).unwrap()
+    }
+}
+
+impl<B> Initiable<B> for NGramIndex<B>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let backend = MultiFile::init(backend)?;
+
+        let metadata = backend.get(METADATA_ID).ok_or(Error::Initialization)?;
+        let metadata = metadata.get(0, 8)?;
+        let n = u32::from_le_bytes(metadata[..4].try_into().unwrap()) as usize;
+        let doc_count = u32::from_le_bytes(metadata[4..].try_into().unwrap()) as usize;
+
+        if !backend.has_id(POSTINGS_ID) {
+            return Err(Error::Initialization);
+        }
+
+        Ok(Self {
+            backend,
+            n,
+            doc_count,
+        })
+    }
+}
+
+impl<B> MType for NGramIndex<B>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.backend.raw_data()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::{make_deeta, make_mem_backend};
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+    use crate::traits::creatable::MemCreatable;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_query(&mut mem_backend);
+        test_candidates(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./ngram_index", 10);
+        test_query(&mut mmap_backend);
+        test_candidates(&mut mmap_backend);
+    }
+
+    fn test_query<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let docs = ["hello world", "yellow", "world wide web", "hallo welt"];
+
+        let mut doc_file = IndexedFile::create_mem_with_capacity(100).unwrap();
+        for doc in docs {
+            doc_file.insert(doc.as_bytes()).unwrap();
+        }
+
+        {
+            let index = NGramIndex::build(&mut backend, 3, &doc_file).unwrap();
+            assert_eq!(index.n(), 3);
+            assert_eq!(index.doc_count(), 4);
+            assert_eq!(index.postings(b"ell"), vec![0, 1]);
+            assert_eq!(index.postings(b"el"), vec![]);
+            assert_eq!(index.query(b"world"), vec![0, 2]);
+            assert_eq!(index.query(b"llo"), vec![0, 1, 3]);
+            assert_eq!(index.query(b"xyz"), vec![]);
+        }
+
+        let index = NGramIndex::init(&mut backend).unwrap();
+        assert_eq!(index.query(b"w"), vec![0, 1, 2, 3]);
+        assert_eq!(index.query(b"welt"), vec![3]);
+        assert_eq!(index.query(b"hello world"), vec![0]);
+    }
+
+    fn test_candidates<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let docs: Vec<_> = make_deeta().take(200).collect();
+        let index = NGramIndex::build_from_iter(&mut backend, 2, docs.iter()).unwrap();
+
+        for query in docs.iter().take(50) {
+            let candidates = index.query(query.as_bytes());
+            for (id, doc) in docs.iter().enumerate() {
+                // All documents containing the query must be candidates.
+                if doc.contains(query.as_str()) {
+                    assert!(candidates.contains(&(id as u32)));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use crate::traits::varint;
+
+/// Encodes a sorted list of IDs by storing the varint encoded deltas.
+pub(super) fn encode(ids: &[u32]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(ids.len());
+    let mut last = 0;
+    for id in ids {
+        let (data, len) = varint::encode(*id - last);
+        out.extend_from_slice(&data[..len as usize]);
+        last = *id;
+    }
+    out
+}
+
+/// Decodes a posting list encoded with `encode`.
+pub(super) fn decode(mut data: &[u8]) -> Vec<u32> {
+    let mut out = vec![];
+    let mut last = 0;
+    while !data.is_empty() {
+        let (delta, len) = varint::decode::<u32>(data).expect("invalid posting list");
+        last += delta;
+        out.push(last);
+        data = &data[len..];
+    }
+    out
+}
+
+/// Returns the intersection of two sorted lists.
+pub(super) fn intersect(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut out = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                out.push(a[i]);
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    out
+}
@@ -1,7 +1,7 @@
 use crate::backend::Backend;
 use crate::components::number_seq::compressed::CompressedNumberSequence;
+use crate::traits::varint::{self, VarInt};
 use std::marker::PhantomData;
-use varint_simd::VarIntTarget;
 
 pub struct CompressedNumSeqIter<'a, T> {
     slice: &'a [u8],
@@ -12,7 +12,7 @@ pub struct CompressedNumSeqIter<'a, T> {
 
 impl<'a, T> CompressedNumSeqIter<'a, T>
 where
-    T: VarIntTarget,
+    T: VarInt,
 {
     #[inline]
     pub fn new<B>(num_seq: &'a CompressedNumberSequence<B, T>) -> Self
@@ -41,14 +41,14 @@ where
 
 impl<'a, T> Iterator for CompressedNumSeqIter<'a, T>
 where
-    T: VarIntTarget,
+    T: VarInt,
 {
     type Item = T;
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
         let slice = self.remaining_slice()?;
-        let (num, len) = varint_simd::decode(slice).expect("Decoding failed");
+        let (num, len) = varint::decode(slice).expect("Decoding failed");
         self.start_index += len;
         Some(num)
     }
@@ -73,7 +73,7 @@ pub struct OwnedCompressedNumSeqIterator<B, T> {
 
 impl<B, T> OwnedCompressedNumSeqIterator<B, T>
 where
-    T: VarIntTarget,
+    T: VarInt,
 {
     #[inline]
     pub fn new(num_seq: CompressedNumberSequence<B, T>) -> Self {
@@ -87,7 +87,7 @@ where
 impl<B, T> OwnedCompressedNumSeqIterator<B, T>
 where
     B: Backend,
-    T: VarIntTarget,
+    T: VarInt,
 {
     /// Returns the remaining slice if not empty.
     #[inline]
@@ -103,14 +103,14 @@ where
 impl<B, T> Iterator for OwnedCompressedNumSeqIterator<B, T>
 where
     B: Backend,
-    T: VarIntTarget,
+    T: VarInt,
 {
     type Item = T;
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
         let slice = self.remaining_slice()?;
-        let (num, len) = varint_simd::decode(slice).expect("Decoding failed");
+        let (num, len) = varint::decode(slice).expect("Decoding failed");
         self.start_index += len;
         Some(num)
     }
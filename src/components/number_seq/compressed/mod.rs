@@ -8,9 +8,9 @@ use crate::components::number_seq::compressed::iter::{
 use crate::traits::collection::{Collection, GrowableCollection};
 use crate::traits::creatable::Creatable;
 use crate::traits::initiable::Initiable;
+use crate::traits::varint::{self, VarInt};
 use crate::{Error, Result};
 use std::marker::PhantomData;
-use varint_simd::VarIntTarget;
 
 /// Similar to `super::NumberSequence` but compresses integerss. This is still quite fast as SIMD instructions are used
 /// but is definitely slower as using a NumberSequence and get(x) is O(n) in worst case. Iterating over the values
@@ -56,7 +56,7 @@ impl<B, T> CompressedNumberSequence<B, T> {
 impl<B, T> CompressedNumberSequence<B, T>
 where
     B: Backend,
-    T: VarIntTarget,
+    T: VarInt,
 {
     /// Returns the number at `index` or `None` if the index is out of the number sequences bonuds.
     pub fn get(&self, index: usize) -> Option<T> {
@@ -70,11 +70,11 @@ where
         let mut len = 0;
         while !slice.is_empty() {
             if len == index {
-                let (val, _) = varint_simd::decode::<T>(&slice).expect("bug");
+                let (val, _) = varint::decode::<T>(&slice).expect("bug");
                 return Some(val);
             }
 
-            let varint_width = varint_simd::decode_len::<T>(&slice).expect("unexpected");
+            let varint_width = varint::decode_len::<T>(&slice).expect("unexpected");
             slice = &slice[varint_width..];
             len += 1;
         }
@@ -122,7 +122,7 @@ where
         let mut len = 0;
 
         while !slice.is_empty() {
-            let varint_width = varint_simd::decode_len::<T>(&slice).expect("unexpected");
+            let varint_width = varint::decode_len::<T>(&slice).expect("unexpected");
             slice = &slice[varint_width..];
             len += 1;
         }
@@ -134,11 +134,11 @@ where
 impl<B, T> CompressedNumberSequence<B, T>
 where
     B: GrowableBackend,
-    T: VarIntTarget,
+    T: VarInt,
 {
     /// Pushes the given number into the number sequence, automatically compressing it.
     pub fn push(&mut self, number: T) -> Result<()> {
-        let (datavec, len) = varint_simd::encode(number);
+        let (datavec, len) = varint::encode(number);
         let data = &datavec[..len as usize];
         if self.backend.free() < data.len() + 1 {
             self.backend.grow(data.len() + 1)?;
@@ -152,7 +152,7 @@ where
 impl<B, T> Extend<T> for CompressedNumberSequence<B, T>
 where
     B: GrowableBackend,
-    T: VarIntTarget,
+    T: VarInt,
 {
     #[inline]
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
@@ -165,7 +165,7 @@ where
 impl<B, T> Collection<T> for CompressedNumberSequence<B, T>
 where
     B: Backend,
-    T: VarIntTarget,
+    T: VarInt,
 {
     type Iter<'a> = CompressedNumSeqIter<'a, T> where Self: 'a;
     type Iterator = OwnedCompressedNumSeqIterator<B, T>;
@@ -189,7 +189,7 @@ where
 impl<B, T> GrowableCollection<T> for CompressedNumberSequence<B, T>
 where
     B: GrowableBackend,
-    T: VarIntTarget,
+    T: VarInt,
 {
     #[inline]
     fn push(&mut self, item: T) -> Result<()> {
@@ -295,7 +295,7 @@ mod test {
 
         let mut entry = ifile.get_backend_mut(0).unwrap();
 
-        let enc = varint_simd::encode(10u32);
+        let enc = varint::encode(10u32);
         let end = entry.len();
         entry.grow(enc.1 as usize).unwrap();
         let new_data = &enc.0[..enc.1 as usize];
@@ -45,6 +45,15 @@ impl<B, T, const N: usize> NumberSequence<B, T, N>
         self.backend.is_empty()
     }
 
+    /// Shortens the sequence to `len` items. Does nothing if the sequence isn't longer than that.
+    #[inline]
+    pub fn truncate(&mut self, len: usize) -> Result<()> {
+        if len < self.len() {
+            self.backend.set_len(len * N)?;
+        }
+        Ok(())
+    }
+
     /// Checks the bounds of `index` and returns an Error if the index is not within the bounds of the NumberSequence.
     #[inline]
     fn check_bounds(&self, index: usize) -> Result<()> {
@@ -354,6 +363,20 @@ mod test {
         assert_eq!(num_seq.get(1), Err(Error::OutOfBounds));
     }
 
+    #[test]
+    fn truncate() {
+        let backend = make_mem_backend(0);
+        let mut num_seq: NumberSequence<_, u32, 4> = NumberSequence::create(backend).unwrap();
+        num_seq.append(&[1, 2, 3]).unwrap();
+
+        num_seq.truncate(5).unwrap();
+        assert_eq!(num_seq.len(), 3);
+        num_seq.truncate(1).unwrap();
+        assert_eq!(num_seq.len(), 1);
+        assert_eq!(num_seq.get(0), Ok(1));
+        assert_eq!(num_seq.get(1), Err(Error::OutOfBounds));
+    }
+
     #[test]
     fn append() {
         let backend = make_mem_backend(1);
@@ -0,0 +1,339 @@
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::header_file::CustomHeaderFile;
+use crate::deser::{deserialize_impl, serialize_impl};
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::{Error, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+/// Length prefix of each item.
+const LEN_BYTES: usize = 4;
+
+/// A persistent FIFO queue of variable sized items. The position of the next item to consume is stored in the header
+/// alongside the items, so after loading the queue again consuming continues exactly where it left off.
+///
+/// Consumed items stay in the backend until all items are consumed or `compact()` gets called.
+///
+/// The internal format: (H_LEN (4 bytes) | HEADER (cursor, len, consumed) | (ITEM_LEN (4 bytes) | ITEM)*)
+pub struct Queue<B, T> {
+    backend: CustomHeaderFile<B, QueueHeader>,
+    header: QueueHeader,
+    _p: PhantomData<T>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy)]
+pub(crate) struct QueueHeader {
+    /// Byte position of the next item to consume.
+    cursor: u64,
+    /// Amount of items not consumed yet.
+    len: u64,
+    /// Amount of items consumed since the queue got created.
+    consumed: u64,
+}
+
+impl<B, T> Queue<B, T> {
+    /// Returns the amount of items that haven't been consumed yet.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.header.len as usize
+    }
+
+    /// Returns `true` if there are no items left to consume.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.header.len == 0
+    }
+
+    /// Returns the amount of items consumed since the queue got created. This is the offset of the next item.
+    #[inline]
+    pub fn consumed(&self) -> u64 {
+        self.header.consumed
+    }
+}
+
+impl<B, T> Queue<B, T>
+    where
+        B: Backend,
+{
+    /// Returns the raw data of the next item.
+    pub fn peek_raw(&self) -> Result<Option<&[u8]>> {
+        if self.is_empty() {
+            return Ok(None);
+        }
+        let (data, _) = self.read_item(self.header.cursor as usize)?;
+        Ok(Some(data))
+    }
+
+    /// Removes the next item and returns its raw data.
+    pub fn pop_raw(&mut self) -> Result<Option<Vec<u8>>> {
+        if self.is_empty() {
+            return Ok(None);
+        }
+        let (data, next) = self.read_item(self.header.cursor as usize)?;
+        let data = data.to_vec();
+
+        self.header.len -= 1;
+        self.header.consumed += 1;
+        self.header.cursor = next as u64;
+
+        if self.is_empty() {
+            // All items are consumed so their space can be reused.
+            self.backend.set_len(0)?;
+            self.header.cursor = 0;
+        }
+
+        self.write_header()?;
+        Ok(Some(data))
+    }
+
+    /// Removes all items, including the ones not consumed yet.
+    pub fn clear(&mut self) -> Result<()> {
+        self.header.consumed += self.header.len;
+        self.header.len = 0;
+        self.header.cursor = 0;
+        self.backend.set_len(0)?;
+        self.write_header()
+    }
+
+    /// Removes all consumed items from the backend, moving the remaining items to the front.
+    pub fn compact(&mut self) -> Result<()> {
+        if self.header.cursor == 0 {
+            return Ok(());
+        }
+        self.backend.replace(0, self.header.cursor as usize, &[])?;
+        self.header.cursor = 0;
+        self.write_header()
+    }
+
+    /// Writes the items and the consumer position to the underlying medium.
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.backend.flush()
+    }
+
+    /// Reads the item at the byte position `pos`. Returns its data and the position of the following item.
+    fn read_item(&self, pos: usize) -> Result<(&[u8], usize)> {
+        let len = self.backend.get(pos, LEN_BYTES)?;
+        let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+        let data = self.backend.get(pos + LEN_BYTES, len)?;
+        Ok((data, pos + LEN_BYTES + len))
+    }
+
+    #[inline]
+    fn write_header(&mut self) -> Result<()> {
+        self.backend.update_header(self.header)
+    }
+}
+
+impl<B, T> Queue<B, T>
+    where
+        B: Backend,
+        T: DeserializeOwned,
+{
+    /// Returns the next item without consuming it.
+    #[inline]
+    pub fn peek(&self) -> Result<Option<T>> {
+        self.peek_raw()?.map(deserialize_impl).transpose()
+    }
+
+    /// Removes the next item and returns it.
+    #[inline]
+    pub fn pop(&mut self) -> Result<Option<T>> {
+        self.pop_raw()?.map(|i| deserialize_impl(&i)).transpose()
+    }
+
+    /// Returns an iterator over all items not consumed yet, without consuming them.
+    #[inline]
+    pub fn iter(&self) -> QueueIter<'_, B, T> {
+        QueueIter {
+            queue: self,
+            pos: self.header.cursor as usize,
+            left: self.len(),
+        }
+    }
+}
+
+impl<B, T> Queue<B, T>
+    where
+        B: GrowableBackend,
+        T: Serialize,
+{
+    /// Appends an item to the end of the queue.
+    #[inline]
+    pub fn push(&mut self, item: &T) -> Result<()> {
+        self.push_raw(&serialize_impl(item)?)
+    }
+}
+
+impl<B, T> Queue<B, T>
+    where
+        B: GrowableBackend,
+{
+    /// Appends an item in form of raw data to the end of the queue.
+    pub fn push_raw(&mut self, data: &[u8]) -> Result<()> {
+        let len: u32 = data.len().try_into().map_err(|_| Error::UnexpectedValue)?;
+
+        let required = self.backend.len() + LEN_BYTES + data.len();
+        let capacity = self.backend.capacity();
+        if required > capacity {
+            let new_capacity = self.backend.growth_policy().next_capacity(capacity, required);
+            self.backend.grow_to(new_capacity)?;
+        }
+
+        self.backend.push_vectored(&[&len.to_le_bytes(), data])?;
+        self.header.len += 1;
+        self.write_header()
+    }
+}
+
+impl<B, T> Creatable<B> for Queue<B, T>
+    where
+        B: GrowableBackend,
+{
+    fn with_capacity(backend: B, capacity: usize) -> Result<Self> {
+        let mut backend = CustomHeaderFile::with_capacity(backend, 0)?;
+        backend.grow_to(capacity)?;
+        Ok(Self {
+            backend,
+            header: QueueHeader::default(),
+            _p: PhantomData,
+        })
+    }
+}
+
+impl<B, T> Initiable<B> for Queue<B, T>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let backend: CustomHeaderFile<B, QueueHeader> = CustomHeaderFile::init(backend)?;
+        let header = *backend.header();
+        if header.cursor as usize > backend.len() {
+            return Err(Error::InvalidHeader);
+        }
+
+        Ok(Self {
+            backend,
+            header,
+            _p: PhantomData,
+        })
+    }
+}
+
+impl<B, T> MType for Queue<B, T>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.backend.raw_data()
+    }
+}
+
+/// Iterator over the items of a `Queue` that haven't been consumed yet.
+pub struct QueueIter<'a, B, T> {
+    queue: &'a Queue<B, T>,
+    pos: usize,
+    left: usize,
+}
+
+impl<B, T> Iterator for QueueIter<'_, B, T>
+    where
+        B: Backend,
+        T: DeserializeOwned,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.left == 0 {
+            return None;
+        }
+        let (data, next) = self.queue.read_item(self.pos).expect("Failed to load item");
+        self.pos = next;
+        self.left -= 1;
+        Some(deserialize_impl(data).expect("Failed to deserialize item"))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.left, Some(self.left))
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut backend = make_mem_backend(1);
+        test_resume(&mut backend);
+        test_compact(&mut backend);
+
+        let mut backend = make_mmap_backend("queue", 1);
+        test_resume(&mut backend);
+        test_compact(&mut backend);
+    }
+
+    fn test_resume<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        {
+            let mut queue: Queue<_, String> = Queue::create(&mut backend).unwrap();
+            assert_eq!(queue.pop(), Ok(None));
+            for i in 0..100 {
+                queue.push(&"item".repeat(i)).unwrap();
+            }
+            for i in 0..40 {
+                assert_eq!(queue.pop(), Ok(Some("item".repeat(i))));
+            }
+            queue.flush().unwrap();
+        }
+
+        let mut queue: Queue<_, String> = Queue::init(&mut backend).unwrap();
+        assert_eq!(queue.len(), 60);
+        assert_eq!(queue.consumed(), 40);
+        assert_eq!(queue.peek(), Ok(Some("item".repeat(40))));
+        assert!(queue.iter().eq((40..100).map(|i| "item".repeat(i))));
+
+        queue.push(&"last".to_string()).unwrap();
+        for i in 40..100 {
+            assert_eq!(queue.pop(), Ok(Some("item".repeat(i))));
+        }
+        assert_eq!(queue.pop(), Ok(Some("last".to_string())));
+        assert_eq!(queue.pop(), Ok(None));
+        assert_eq!(queue.consumed(), 101);
+
+        // Space of consumed items gets reused once the queue is empty.
+        assert_eq!(queue.backend.len(), 0);
+    }
+
+    fn test_compact<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut queue: Queue<_, u64> = Queue::create(&mut backend).unwrap();
+        for i in 0..1000 {
+            queue.push(&i).unwrap();
+        }
+        for i in 0..500 {
+            assert_eq!(queue.pop(), Ok(Some(i)));
+        }
+
+        let len = queue.backend.len();
+        queue.compact().unwrap();
+        assert!(queue.backend.len() < len);
+        assert!(queue.iter().eq(500..1000));
+
+        let mut queue: Queue<_, u64> = Queue::init(&mut backend).unwrap();
+        assert_eq!(queue.pop(), Ok(Some(500)));
+        queue.clear().unwrap();
+        assert!(queue.is_empty());
+        assert_eq!(queue.consumed(), 1000);
+        assert_eq!(queue.peek(), Ok(None));
+    }
+}
@@ -0,0 +1,353 @@
+use crate::backend::base::sub::GeneralSubBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::bitvec::BitVec;
+use crate::components::multi_file::MultiFile;
+use crate::components::number_seq::NumberSequence;
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::{Error, Result};
+
+/// ID of the MultiFile entry holding the metadata (bits, ones).
+const METADATA_ID: usize = 0;
+
+/// ID of the MultiFile entry holding the bits as words.
+const WORDS_ID: usize = 1;
+
+/// ID of the MultiFile entry holding the amount of ones before each superblock.
+const SUPERBLOCKS_ID: usize = 2;
+
+/// ID of the MultiFile entry holding the amount of ones before each word within its superblock.
+const BLOCKS_ID: usize = 3;
+
+/// ID of the MultiFile entry holding the superblock of every `SELECT_SAMPLE`th one.
+const SAMPLES_ID: usize = 4;
+
+/// Amount of words (blocks of 64 bits) per superblock.
+const WORDS_PER_SUPERBLOCK: usize = 8;
+
+/// Every n-th one gets its superblock sampled to speed up `select1`.
+const SELECT_SAMPLE: usize = 512;
+
+type Words<'a> = NumberSequence<GeneralSubBackend<'a>, u64, 8>;
+type Superblocks<'a> = NumberSequence<GeneralSubBackend<'a>, u64, 8>;
+type Blocks<'a> = NumberSequence<GeneralSubBackend<'a>, u16, 2>;
+type Samples<'a> = NumberSequence<GeneralSubBackend<'a>, u32, 4>;
+
+/// A static bit sequence supporting rank and select queries in constant time. The amount of ones before every
+/// superblock of 512 bits and before every word of 64 bits within its superblock are stored along with the bits, so a
+/// rank query needs a single popcount. Select queries use sampled superblocks to narrow down the search.
+///
+/// The internal format: MultiFile(METADATA | WORDS | SUPERBLOCKS | BLOCKS | SAMPLES)
+pub struct RankSelect<B> {
+    backend: MultiFile<B>,
+    len: usize,
+    ones: usize,
+}
+
+impl<B> RankSelect<B>
+    where
+        B: GrowableBackend,
+{
+    /// Builds the rank/select structure for the bits of `bits`.
+    pub fn build<O: Backend>(backend: B, bits: &BitVec<O>) -> Result<Self> {
+        let len = bits.len();
+        let mut words: Vec<u64> = bits
+            .as_bytes()
+            .chunks(8)
+            .take(len.div_ceil(64))
+            .map(|chunk| {
+                let mut word = [0u8; 8];
+                word[..chunk.len()].copy_from_slice(chunk);
+                u64::from_le_bytes(word)
+            })
+            .collect();
+        if !len.is_multiple_of(64) {
+            // Clear the unused bits of the last word.
+            *words.last_mut().unwrap() &= (1 << (len % 64)) - 1;
+        }
+
+        let mut superblocks = Vec::with_capacity(words.len() / WORDS_PER_SUPERBLOCK + 2);
+        let mut blocks = Vec::with_capacity(words.len());
+        let mut samples = vec![];
+        let mut ones = 0u64;
+        for (i, word) in words.iter().enumerate() {
+            if i.is_multiple_of(WORDS_PER_SUPERBLOCK) {
+                superblocks.push(ones);
+            }
+            blocks.push((ones - superblocks.last().unwrap()) as u16);
+
+            let count = word.count_ones() as u64;
+            // Sample each superblock containing a multiple of `SELECT_SAMPLE`.
+            let next_sample = (samples.len() * SELECT_SAMPLE) as u64;
+            if ones <= next_sample && next_sample < ones + count {
+                let superblock = (superblocks.len() - 1) as u32;
+                let sampled = (ones + count - 1) as usize / SELECT_SAMPLE - samples.len() + 1;
+                samples.extend(std::iter::repeat_n(superblock, sampled));
+            }
+            ones += count;
+        }
+        superblocks.push(ones);
+
+        let mut backend = MultiFile::create(backend)?;
+
+        let mut metadata = backend.insert_empty()?;
+        metadata.grow_to(16)?;
+        metadata.push(&(len as u64).to_le_bytes())?;
+        metadata.push(&ones.to_le_bytes())?;
+
+        backend.insert_new_backend::<NumberSequence<_, u64, 8>>()?.append(&words)?;
+        backend.insert_new_backend::<NumberSequence<_, u64, 8>>()?.append(&superblocks)?;
+        backend.insert_new_backend::<NumberSequence<_, u16, 2>>()?.append(&blocks)?;
+        backend.insert_new_backend::<NumberSequence<_, u32, 4>>()?.append(&samples)?;
+
+        Ok(Self {
+            backend,
+            len,
+            ones: ones as usize,
+        })
+    }
+}
+
+impl<B> RankSelect<B>
+    where
+        B: Backend,
+{
+    /// Returns the amount of bits.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if there are no bits.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the total amount of set bits.
+    #[inline]
+    pub fn count_ones(&self) -> usize {
+        self.ones
+    }
+
+    /// Returns the bit at `index`.
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index >= self.len {
+            return None;
+        }
+        let word = self.words().get(index / 64).ok()?;
+        Some(word & (1 << (index % 64)) != 0)
+    }
+
+    /// Returns the amount of set bits before `index`. Returns `Error::OutOfBounds` if `index` is bigger than the
+    /// amount of bits.
+    pub fn rank1(&self, index: usize) -> Result<usize> {
+        if index > self.len {
+            return Err(Error::OutOfBounds);
+        }
+        if index == self.len {
+            return Ok(self.ones);
+        }
+
+        let word_index = index / 64;
+        let superblock = self.superblocks().get(word_index / WORDS_PER_SUPERBLOCK)?;
+        let block = self.blocks().get(word_index)?;
+        let word = self.words().get(word_index)?;
+        let below = word & ((1 << (index % 64)) - 1);
+        Ok(superblock as usize + block as usize + below.count_ones() as usize)
+    }
+
+    /// Returns the amount of unset bits before `index`. Returns `Error::OutOfBounds` if `index` is bigger than the
+    /// amount of bits.
+    #[inline]
+    pub fn rank0(&self, index: usize) -> Result<usize> {
+        Ok(index - self.rank1(index)?)
+    }
+
+    /// Returns the position of the `k`th set bit, counting from 0. Returns `None` if there are not more than `k` set
+    /// bits.
+    pub fn select1(&self, k: usize) -> Option<usize> {
+        if k >= self.ones {
+            return None;
+        }
+
+        // The sampled superblocks of the surrounding samples bound the superblock containing the `k`th one.
+        let samples = self.samples();
+        let sample = k / SELECT_SAMPLE;
+        let superblocks = self.superblocks();
+        let mut lo = samples.get(sample).ok()? as usize;
+        let mut hi = match samples.get(sample + 1) {
+            Ok(s) => s as usize + 1,
+            Err(_) => superblocks.len() - 1,
+        };
+
+        // Find the last superblock with at most `k` ones before it.
+        while hi - lo > 1 {
+            let mid = (lo + hi) / 2;
+            if superblocks.get(mid).ok()? as usize <= k {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        let mut rest = k - superblocks.get(lo).ok()? as usize;
+
+        // Find the word within the superblock.
+        let blocks = self.blocks();
+        let first = lo * WORDS_PER_SUPERBLOCK;
+        let last = (first + WORDS_PER_SUPERBLOCK).min(blocks.len());
+        let mut word_index = first;
+        for i in first + 1..last {
+            if blocks.get(i).ok()? as usize > rest {
+                break;
+            }
+            word_index = i;
+        }
+        rest -= blocks.get(word_index).ok()? as usize;
+
+        let mut word = self.words().get(word_index).ok()?;
+        for _ in 0..rest {
+            // Clear the lowest set bit.
+            word &= word - 1;
+        }
+        Some(word_index * 64 + word.trailing_zeros() as usize)
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.backend.flush()
+    }
+
+    #[inline]
+    fn words(&self) -> Words<'_> {
+        self.backend.get_backend(WORDS_ID).unwrap()
+    }
+
+    #[inline]
+    fn superblocks(&self) -> Superblocks<'_> {
+        self.backend.get_backend(SUPERBLOCKS_ID).unwrap()
+    }
+
+    #[inline]
+    fn blocks(&self) -> Blocks<'_> {
+        self.backend.get_backend(BLOCKS_ID).unwrap()
+    }
+
+    #[inline]
+    fn samples(&self) -> Samples<'_> {
+        self.backend.get_backend(SAMPLES_ID).unwrap()
+    }
+}
+
+impl<B> Initiable<B> for RankSelect<B>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let backend = MultiFile::init(backend)?;
+
+        let metadata = backend.get(METADATA_ID).ok_or(Error::Initialization)?;
+        let metadata = metadata.get(0, 16)?;
+        let len = u64::from_le_bytes(metadata[..8].try_into().unwrap()) as usize;
+        let ones = u64::from_le_bytes(metadata[8..].try_into().unwrap()) as usize;
+
+        if !backend.has_id(SAMPLES_ID) {
+            return Err(Error::Initialization);
+        }
+
+        let rs = Self { backend, len, ones };
+        if rs.words().len() != len.div_ceil(64) || rs.blocks().len() != rs.words().len() {
+            return Err(Error::Initialization);
+        }
+        Ok(rs)
+    }
+}
+
+impl<B> MType for RankSelect<B>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.backend.raw_data()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::memory::MemoryBackend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+    use crate::traits::creatable::MemCreatable;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_rank_select(&mut mem_backend);
+        test_edge_cases(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./rank_select", 10);
+        test_rank_select(&mut mmap_backend);
+        test_edge_cases(&mut mmap_backend);
+    }
+
+    fn make_bits(bits: &[bool]) -> BitVec<MemoryBackend> {
+        let mut bitvec = BitVec::create_mem_with_capacity(0).unwrap();
+        bitvec.extend(bits.iter().copied());
+        bitvec
+    }
+
+    fn check<B: Backend>(rs: &RankSelect<B>, bits: &[bool]) {
+        assert_eq!(rs.len(), bits.len());
+        let positions: Vec<_> = (0..bits.len()).filter(|i| bits[*i]).collect();
+        assert_eq!(rs.count_ones(), positions.len());
+
+        let mut rank = 0;
+        for (i, bit) in bits.iter().enumerate() {
+            assert_eq!(rs.rank1(i), Ok(rank), "{i}");
+            assert_eq!(rs.get(i), Some(*bit));
+            rank += *bit as usize;
+        }
+        assert_eq!(rs.rank1(bits.len()), Ok(rank));
+        assert!(rs.rank1(bits.len() + 1).is_err());
+
+        for (k, pos) in positions.iter().enumerate() {
+            assert_eq!(rs.select1(k), Some(*pos), "{k}");
+        }
+        assert_eq!(rs.select1(positions.len()), None);
+    }
+
+    fn test_rank_select<B: GrowableBackend>(mut backend: &mut B) {
+        let patterns: [fn(usize) -> bool; 4] = [
+            |i| (i * 7919) % 13 < 5,
+            |i| i % 3000 < 10,
+            |i| i % 100 != 0,
+            |i| (i / 700) % 2 == 0,
+        ];
+
+        for pattern in patterns {
+            backend.clear();
+            let bits: Vec<bool> = (0..20_000).map(pattern).collect();
+            {
+                let rs = RankSelect::build(&mut backend, &make_bits(&bits)).unwrap();
+                check(&rs, &bits);
+            }
+
+            let rs = RankSelect::init(&mut backend).unwrap();
+            check(&rs, &bits);
+            assert_eq!(rs.rank0(1000), Ok(1000 - rs.rank1(1000).unwrap()));
+        }
+    }
+
+    fn test_edge_cases<B: GrowableBackend>(mut backend: &mut B) {
+        for bits in [vec![], vec![false; 1000], vec![true; 1025], vec![true], vec![false, true, false]] {
+            backend.clear();
+            let rs = RankSelect::build(&mut backend, &make_bits(&bits)).unwrap();
+            check(&rs, &bits);
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use serde::{Deserialize, Serialize};
+
+/// Maximum amount of values an array container holds before it gets converted into a bitmap container, which from
+/// then on needs less space.
+const MAX_ARRAY_LEN: usize = 4096;
+
+/// Amount of words of a bitmap container.
+const BITMAP_WORDS: usize = 1 << 16 >> 6;
+
+/// The lower 16 bits of all values sharing the same upper 16 bits.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub(crate) enum Container {
+    /// Sorted values, for up to `MAX_ARRAY_LEN` values.
+    Array(Vec<u16>),
+    /// One bit for each possible value.
+    Bitmap(Vec<u64>),
+    /// Sorted, non overlapping runs of values as `(start, len - 1)`. Only created by `run_optimize`.
+    Run(Vec<(u16, u16)>),
+}
+
+impl Container {
+    #[inline]
+    pub(crate) fn new() -> Self {
+        Self::Array(vec![])
+    }
+
+    /// Returns the amount of values in the container.
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Self::Array(values) => values.len(),
+            Self::Bitmap(words) => words.iter().map(|i| i.count_ones() as usize).sum(),
+            Self::Run(runs) => runs.iter().map(|i| i.1 as usize + 1).sum(),
+        }
+    }
+
+    pub(crate) fn contains(&self, value: u16) -> bool {
+        match self {
+            Self::Array(values) => values.binary_search(&value).is_ok(),
+            Self::Bitmap(words) => words[value as usize >> 6] & (1 << (value & 63)) != 0,
+            Self::Run(runs) => {
+                let i = runs.partition_point(|i| i.0 <= value);
+                i > 0 && value - runs[i - 1].0 <= runs[i - 1].1
+            }
+        }
+    }
+
+    /// Adds a value. Returns `false` if it was already present.
+    pub(crate) fn insert(&mut self, value: u16) -> bool {
+        if let Self::Run(_) = self {
+            *self = self.to_normal();
+        }
+
+        let inserted = match self {
+            Self::Array(values) => match values.binary_search(&value) {
+                Ok(_) => false,
+                Err(i) => {
+                    values.insert(i, value);
+                    true
+                }
+            },
+            Self::Bitmap(words) => {
+                let word = &mut words[value as usize >> 6];
+                let bit = 1 << (value & 63);
+                let inserted = *word & bit == 0;
+                *word |= bit;
+                inserted
+            }
+            Self::Run(_) => unreachable!(),
+        };
+
+        if matches!(self, Self::Array(values) if values.len() > MAX_ARRAY_LEN) {
+            *self = self.to_bitmap();
+        }
+        inserted
+    }
+
+    /// Removes a value. Returns `false` if it wasn't present.
+    pub(crate) fn remove(&mut self, value: u16) -> bool {
+        if !self.contains(value) {
+            return false;
+        }
+
+        if let Self::Run(_) = self {
+            *self = self.to_normal();
+        }
+
+        match self {
+            Self::Array(values) => {
+                values.retain(|i| *i != value);
+            }
+            Self::Bitmap(words) => {
+                words[value as usize >> 6] &= !(1 << (value & 63));
+            }
+            Self::Run(_) => unreachable!(),
+        }
+
+        if matches!(self, Self::Bitmap(_)) && self.len() <= MAX_ARRAY_LEN {
+            *self = self.to_array();
+        }
+        true
+    }
+
+    /// Returns all values in ascending order.
+    pub(crate) fn values(&self) -> Vec<u16> {
+        match self {
+            Self::Array(values) => values.clone(),
+            Self::Bitmap(words) => {
+                let mut values = Vec::with_capacity(self.len());
+                for (i, word) in words.iter().enumerate() {
+                    let mut word = *word;
+                    while word != 0 {
+                        values.push((i * 64) as u16 + word.trailing_zeros() as u16);
+                        word &= word - 1;
+                    }
+                }
+                values
+            }
+            Self::Run(runs) => runs.iter().flat_map(|&(start, len)| start..=start + len).collect(),
+        }
+    }
+
+    /// Returns the union of both containers.
+    pub(crate) fn union(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::Array(a), Self::Array(b)) if a.len() + b.len() <= MAX_ARRAY_LEN => {
+                let mut values = Vec::with_capacity(a.len() + b.len());
+                let (mut i, mut j) = (0, 0);
+                while i < a.len() && j < b.len() {
+                    values.push(a[i].min(b[j]));
+                    let (x, y) = (a[i], b[j]);
+                    i += (x <= y) as usize;
+                    j += (y <= x) as usize;
+                }
+                values.extend_from_slice(&a[i..]);
+                values.extend_from_slice(&b[j..]);
+                Self::Array(values)
+            }
+            _ => {
+                let mut words = self.words();
+                for (word, other) in words.iter_mut().zip(other.words()) {
+                    *word |= other;
+                }
+                Self::Bitmap(words).to_normal()
+            }
+        }
+    }
+
+    /// Returns the intersection of both containers.
+    pub(crate) fn intersection(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::Bitmap(a), Self::Bitmap(b)) => {
+                let words = a.iter().zip(b).map(|(a, b)| a & b).collect();
+                Self::Bitmap(words).to_normal()
+            }
+            (Self::Array(a), b) | (b, Self::Array(a)) => {
+                Self::Array(a.iter().copied().filter(|i| b.contains(*i)).collect())
+            }
+            _ => Self::Array(self.values().into_iter().filter(|i| other.contains(*i)).collect()),
+        }
+    }
+
+    /// Returns the amount of values present in both containers.
+    pub(crate) fn intersection_len(&self, other: &Self) -> usize {
+        match (self, other) {
+            (Self::Bitmap(a), Self::Bitmap(b)) => a.iter().zip(b).map(|(a, b)| (a & b).count_ones() as usize).sum(),
+            (Self::Array(a), b) | (b, Self::Array(a)) => a.iter().filter(|i| b.contains(**i)).count(),
+            _ => self.values().into_iter().filter(|i| other.contains(*i)).count(),
+        }
+    }
+
+    /// Converts the container into the representation needing the least space, including run containers.
+    pub(crate) fn run_optimize(&self) -> Self {
+        let normal = self.to_normal();
+        let runs = self.runs();
+        let normal_size = match &normal {
+            Self::Array(values) => values.len() * 2,
+            _ => BITMAP_WORDS * 8,
+        };
+        if runs.len() * 4 < normal_size {
+            return Self::Run(runs);
+        }
+        normal
+    }
+
+    /// Converts the container into an array or bitmap container, depending on its amount of values.
+    fn to_normal(&self) -> Self {
+        if self.len() <= MAX_ARRAY_LEN {
+            self.to_array()
+        } else {
+            self.to_bitmap()
+        }
+    }
+
+    #[inline]
+    fn to_array(&self) -> Self {
+        Self::Array(self.values())
+    }
+
+    #[inline]
+    fn to_bitmap(&self) -> Self {
+        Self::Bitmap(self.words())
+    }
+
+    fn words(&self) -> Vec<u64> {
+        if let Self::Bitmap(words) = self {
+            return words.clone();
+        }
+        let mut words = vec![0u64; BITMAP_WORDS];
+        for value in self.values() {
+            words[value as usize >> 6] |= 1 << (value & 63);
+        }
+        words
+    }
+
+    fn runs(&self) -> Vec<(u16, u16)> {
+        if let Self::Run(runs) = self {
+            return runs.clone();
+        }
+        let mut runs: Vec<(u16, u16)> = vec![];
+        for value in self.values() {
+            match runs.last_mut() {
+                Some((start, len)) if *start as u32 + *len as u32 + 1 == value as u32 => *len += 1,
+                _ => runs.push((value, 0)),
+            }
+        }
+        runs
+    }
+}
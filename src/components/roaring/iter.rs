@@ -0,0 +1,46 @@
+use crate::backend::Backend;
+use crate::components::roaring::RoaringBitmap;
+use std::vec::IntoIter;
+
+/// Iterator over the values of a `RoaringBitmap` in ascending order.
+pub struct RoaringIter<'a, B> {
+    bitmap: &'a RoaringBitmap<B>,
+
+    /// Index of the next container to load.
+    next: usize,
+    /// Upper 16 bits and remaining lower 16 bits of the current container.
+    current: Option<(u32, IntoIter<u16>)>,
+}
+
+impl<'a, B> RoaringIter<'a, B> {
+    #[inline]
+    pub(super) fn new(bitmap: &'a RoaringBitmap<B>) -> Self {
+        Self {
+            bitmap,
+            next: 0,
+            current: None,
+        }
+    }
+}
+
+impl<B> Iterator for RoaringIter<'_, B>
+    where
+        B: Backend,
+{
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((high, values)) = self.current.as_mut() {
+                if let Some(low) = values.next() {
+                    return Some(*high << 16 | low as u32);
+                }
+            }
+
+            let key = *self.bitmap.keys().get(self.next)?;
+            let container = self.bitmap.load_container(self.next).unwrap();
+            self.current = Some((key as u32, container.values().into_iter()));
+            self.next += 1;
+        }
+    }
+}
@@ -0,0 +1,434 @@
+mod container;
+pub mod iter;
+
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::multi_file::MultiFile;
+use crate::components::roaring::container::Container;
+use crate::components::roaring::iter::RoaringIter;
+use crate::deser::{deserialize_impl, serialize_impl};
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+/// ID of the entry holding the bitmaps metadata.
+const METADATA_ID: usize = 0;
+
+#[derive(Serialize, Deserialize)]
+struct RoaringMetadata {
+    keys: Vec<u16>,
+    ids: Vec<u64>,
+
+    /// IDs of entries of removed containers which can be reused.
+    free: Vec<u64>,
+}
+
+/// Compressed bitmap of `u32` values, for large sparse sets of IDs where a dense `BitVec` would waste too much space.
+///
+/// Values are grouped by their upper 16 bits into containers, each storing the lower 16 bits of its values either as
+/// a sorted array, a bitmap or, after `run_optimize()`, as runs of consecutive values. Unions and intersections work
+/// on whole containers at once.
+///
+/// The internal format: MultiFile(METADATA | CONTAINER | CONTAINER | ...)
+/// where the metadata holds the upper 16 bits of each container with the ID of its entry. The metadata only gets
+/// written when containers are added or removed.
+pub struct RoaringBitmap<B> {
+    storage: MultiFile<B>,
+    len: usize,
+
+    /// Upper 16 bits of the values of each container in ascending order.
+    keys: Vec<u16>,
+    /// Entry IDs of the containers of `keys`.
+    ids: Vec<usize>,
+    free: Vec<usize>,
+}
+
+impl<B> RoaringBitmap<B> {
+    /// Returns the amount of values in the bitmap.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the bitmap is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the index of the container holding `value`.
+    #[inline]
+    fn find(&self, value: u32) -> std::result::Result<usize, usize> {
+        self.keys.binary_search(&((value >> 16) as u16))
+    }
+}
+
+impl<B> RoaringBitmap<B>
+    where
+        B: Backend,
+{
+    /// Returns `true` if the bitmap contains `value`.
+    pub fn contains(&self, value: u32) -> bool {
+        let Ok(i) = self.find(value) else {
+            return false;
+        };
+        self.load_container(i).is_ok_and(|i| i.contains(value as u16))
+    }
+
+    /// Returns an iterator over all values in ascending order.
+    #[inline]
+    pub fn iter(&self) -> RoaringIter<'_, B> {
+        RoaringIter::new(self)
+    }
+
+    /// Returns the amount of values present in both bitmaps without building their intersection.
+    pub fn intersection_len<O: Backend>(&self, other: &RoaringBitmap<O>) -> usize {
+        self.common_containers(other)
+            .map(|(a, b)| self.load_container(a).unwrap().intersection_len(&other.load_container(b).unwrap()))
+            .sum()
+    }
+
+    /// Returns the indices of all containers of both bitmaps with the same key.
+    fn common_containers<'a, O>(&'a self, other: &'a RoaringBitmap<O>) -> impl Iterator<Item = (usize, usize)> + 'a {
+        self.keys
+            .iter()
+            .enumerate()
+            .filter_map(|(i, key)| Some((i, other.keys.binary_search(key).ok()?)))
+    }
+
+    pub(crate) fn load_container(&self, i: usize) -> Result<Container> {
+        let entry = self.storage.get(self.ids[i]).ok_or(Error::OutOfBounds)?;
+        deserialize_impl(entry.content_data())
+    }
+
+    #[inline]
+    pub(crate) fn keys(&self) -> &[u16] {
+        &self.keys
+    }
+}
+
+impl<B> RoaringBitmap<B>
+    where
+        B: GrowableBackend,
+{
+    /// Adds a value. Returns `false` if it was already present.
+    pub fn insert(&mut self, value: u32) -> Result<bool> {
+        let (i, mut container, new) = match self.find(value) {
+            Ok(i) => (i, self.load_container(i)?, false),
+            Err(i) => {
+                self.insert_container(i, (value >> 16) as u16)?;
+                (i, Container::new(), true)
+            }
+        };
+
+        if !container.insert(value as u16) {
+            return Ok(false);
+        }
+        self.write_container(i, &container)?;
+        if new {
+            self.write_metadata()?;
+        }
+        self.len += 1;
+        Ok(true)
+    }
+
+    /// Removes a value. Returns `false` if it wasn't present.
+    pub fn remove(&mut self, value: u32) -> Result<bool> {
+        let Ok(i) = self.find(value) else {
+            return Ok(false);
+        };
+        let mut container = self.load_container(i)?;
+        if !container.remove(value as u16) {
+            return Ok(false);
+        }
+
+        if container.len() == 0 {
+            self.remove_container(i)?;
+            self.write_metadata()?;
+        } else {
+            self.write_container(i, &container)?;
+        }
+        self.len -= 1;
+        Ok(true)
+    }
+
+    /// Adds all values of `other` to the bitmap.
+    pub fn union_with<O: Backend>(&mut self, other: &RoaringBitmap<O>) -> Result<()> {
+        for (j, key) in other.keys.iter().enumerate() {
+            let other_container = other.load_container(j)?;
+            let i = match self.keys.binary_search(key) {
+                Ok(i) => {
+                    let container = self.load_container(i)?;
+                    self.len -= container.len();
+                    let union = container.union(&other_container);
+                    self.len += union.len();
+                    self.write_container(i, &union)?;
+                    continue;
+                }
+                Err(i) => i,
+            };
+
+            self.insert_container(i, *key)?;
+            self.write_container(i, &other_container)?;
+            self.len += other_container.len();
+        }
+        self.write_metadata()
+    }
+
+    /// Removes all values from the bitmap which are not in `other`.
+    pub fn intersect_with<O: Backend>(&mut self, other: &RoaringBitmap<O>) -> Result<()> {
+        let mut i = 0;
+        while i < self.keys.len() {
+            let container = self.load_container(i)?;
+            self.len -= container.len();
+
+            let intersection = match other.keys.binary_search(&self.keys[i]) {
+                Ok(j) => container.intersection(&other.load_container(j)?),
+                Err(_) => Container::new(),
+            };
+
+            if intersection.len() == 0 {
+                self.remove_container(i)?;
+                continue;
+            }
+            self.len += intersection.len();
+            self.write_container(i, &intersection)?;
+            i += 1;
+        }
+        self.write_metadata()
+    }
+
+    /// Converts each container into its smallest representation. Ranges of consecutive values get stored as runs,
+    /// which are converted back on the next write to their container.
+    pub fn run_optimize(&mut self) -> Result<()> {
+        for i in 0..self.keys.len() {
+            let container = self.load_container(i)?.run_optimize();
+            self.write_container(i, &container)?;
+        }
+        Ok(())
+    }
+
+    /// Removes all values.
+    pub fn clear(&mut self) -> Result<()> {
+        self.storage.clear();
+        self.len = 0;
+        self.keys.clear();
+        self.ids.clear();
+        self.free.clear();
+        self.storage.insert_empty()?;
+        self.write_metadata()
+    }
+
+    /// Flushes the bitmap to the backend.
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.storage.flush()
+    }
+
+    /// Adds an empty container for `key` at index `i`.
+    fn insert_container(&mut self, i: usize, key: u16) -> Result<()> {
+        let id = match self.free.pop() {
+            Some(id) => id,
+            None => {
+                let id = self.storage.count();
+                self.storage.insert_empty()?;
+                id
+            }
+        };
+        self.keys.insert(i, key);
+        self.ids.insert(i, id);
+        Ok(())
+    }
+
+    fn remove_container(&mut self, i: usize) -> Result<()> {
+        self.keys.remove(i);
+        let id = self.ids.remove(i);
+        self.storage.entry_mut(id).ok_or(Error::OutOfBounds)?.clear();
+        self.free.push(id);
+        Ok(())
+    }
+
+    #[inline]
+    fn write_container(&mut self, i: usize, container: &Container) -> Result<()> {
+        self.storage.set_entry(self.ids[i], &serialize_impl(container)?)
+    }
+
+    fn write_metadata(&mut self) -> Result<()> {
+        let metadata = RoaringMetadata {
+            keys: self.keys.clone(),
+            ids: self.ids.iter().map(|i| *i as u64).collect(),
+            free: self.free.iter().map(|i| *i as u64).collect(),
+        };
+        self.storage.set_entry(METADATA_ID, &serialize_impl(&metadata)?)
+    }
+}
+
+impl<B> Creatable<B> for RoaringBitmap<B>
+    where
+        B: GrowableBackend,
+{
+    fn with_capacity(backend: B, _: usize) -> Result<Self> {
+        let mut bitmap = Self {
+            storage: MultiFile::with_capacity(backend, 1)?,
+            len: 0,
+            keys: vec![],
+            ids: vec![],
+            free: vec![],
+        };
+        bitmap.storage.insert_empty()?;
+        bitmap.write_metadata()?;
+        Ok(bitmap)
+    }
+}
+
+impl<B> Initiable<B> for RoaringBitmap<B>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let storage = MultiFile::init(backend)?;
+        let metadata: RoaringMetadata = storage
+            .get(METADATA_ID)
+            .ok_or(Error::Initialization)
+            .and_then(|i| deserialize_impl(i.content_data()))?;
+
+        if metadata.keys.len() != metadata.ids.len() || !metadata.ids.iter().all(|i| storage.has_id(*i as usize)) {
+            return Err(Error::Initialization);
+        }
+
+        let mut bitmap = Self {
+            storage,
+            len: 0,
+            keys: metadata.keys,
+            ids: metadata.ids.iter().map(|i| *i as usize).collect(),
+            free: metadata.free.iter().map(|i| *i as usize).collect(),
+        };
+        bitmap.len = (0..bitmap.keys.len())
+            .map(|i| bitmap.load_container(i).map(|i| i.len()))
+            .sum::<Result<usize>>()?;
+        Ok(bitmap)
+    }
+}
+
+impl<B> MType for RoaringBitmap<B>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.storage.raw_data()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::memory::MemoryBackend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+    use crate::traits::creatable::MemCreatable;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_insert(&mut mem_backend);
+        test_set_ops(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./roaring", 10);
+        test_insert(&mut mmap_backend);
+        test_set_ops(&mut mmap_backend);
+    }
+
+    /// Values in a few dense and many sparse containers.
+    fn values(seed: u64, count: usize) -> Vec<u32> {
+        let mut seed = seed;
+        (0..count)
+            .map(|i| {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                match i % 3 {
+                    0 => (seed >> 48) as u32,
+                    1 => 0x0003_0000 + (seed >> 52) as u32,
+                    _ => (seed >> 40) as u32,
+                }
+            })
+            .collect()
+    }
+
+    fn test_insert<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut expected = BTreeSet::new();
+        {
+            let mut bitmap = RoaringBitmap::create(&mut backend).unwrap();
+            assert!(!bitmap.contains(0));
+            for value in values(1, 6_000) {
+                assert_eq!(bitmap.insert(value), Ok(expected.insert(value)));
+            }
+        }
+
+        let mut bitmap = RoaringBitmap::init(&mut backend).unwrap();
+        assert_eq!(bitmap.len(), expected.len());
+        assert!(bitmap.iter().eq(expected.iter().copied()));
+        assert!(bitmap.contains(*expected.last().unwrap()));
+        assert!(!bitmap.contains(u32::MAX));
+
+        for value in values(1, 6_000).into_iter().step_by(2) {
+            assert_eq!(bitmap.remove(value), Ok(expected.remove(&value)));
+        }
+        assert_eq!(bitmap.remove(u32::MAX), Ok(false));
+        assert_eq!(bitmap.len(), expected.len());
+        assert!(bitmap.iter().eq(expected.iter().copied()));
+
+        // Ranges get compressed into runs.
+        for value in 0x0010_0000..0x0010_1400 {
+            bitmap.insert(value).unwrap();
+            expected.insert(value);
+        }
+        bitmap.run_optimize().unwrap();
+        assert!(bitmap.storage.get(*bitmap.ids.last().unwrap()).unwrap().content_data().len() < 100);
+        assert!(bitmap.iter().eq(expected.iter().copied()));
+
+        bitmap.remove(0x0010_0010).unwrap();
+        assert!(!bitmap.contains(0x0010_0010));
+        assert!(bitmap.contains(0x0010_0011));
+
+        bitmap.clear().unwrap();
+        assert!(bitmap.is_empty());
+        assert_eq!(bitmap.iter().next(), None);
+    }
+
+    fn test_set_ops<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let a_values: BTreeSet<_> = values(2, 8_000).into_iter().collect();
+        let b_values: BTreeSet<_> = values(3, 8_000).into_iter().chain(0x0003_0000..0x0003_1000).collect();
+
+        let mut a = RoaringBitmap::create(&mut backend).unwrap();
+        let mut b: RoaringBitmap<MemoryBackend> = RoaringBitmap::create_mem_with_capacity(10).unwrap();
+        for value in a_values.iter() {
+            a.insert(*value).unwrap();
+        }
+        for value in b_values.iter() {
+            b.insert(*value).unwrap();
+        }
+        b.run_optimize().unwrap();
+
+        let intersection: Vec<_> = a_values.intersection(&b_values).copied().collect();
+        assert_eq!(a.intersection_len(&b), intersection.len());
+
+        a.union_with(&b).unwrap();
+        let union: Vec<_> = a_values.union(&b_values).copied().collect();
+        assert_eq!(a.len(), union.len());
+        assert!(a.iter().eq(union.iter().copied()));
+
+        let mut c: RoaringBitmap<MemoryBackend> = RoaringBitmap::create_mem_with_capacity(10).unwrap();
+        for value in a_values.iter() {
+            c.insert(*value).unwrap();
+        }
+        c.intersect_with(&b).unwrap();
+        assert_eq!(c.len(), intersection.len());
+        assert!(c.iter().eq(intersection.iter().copied()));
+    }
+}
@@ -0,0 +1,389 @@
+pub mod rect;
+
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::indexed_file::IndexedFile;
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::{Error, Result};
+use rect::Rect;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::ops::Range;
+
+/// Default amount of children per node.
+const DEFAULT_NODE_SIZE: usize = 16;
+
+/// Size of a single encoded entry: Rect (32 bytes) + ID (8 bytes)
+const ENTRY_SIZE: usize = 32 + 8;
+
+/// ID of the entry holding the trees metadata.
+const METADATA_ID: usize = 0;
+
+/// ID of the entry holding all nodes.
+const NODES_ID: usize = 1;
+
+#[derive(Serialize, Deserialize, Default)]
+struct RTreeMetadata {
+    node_size: u32,
+
+    /// Index ranges of the entries of each level, starting with the leaves.
+    levels: Vec<(u64, u64)>,
+}
+
+/// A static, packed R-tree (Sort-Tile-Recursive) storing rectangles or points along with an ID. The tree gets bulk
+/// loaded once and can't be modified afterwards.
+///
+/// The internal format: IndexedFile(METADATA | NODES)
+/// where NODES contains all levels (leaves first) and each node is encoded as (RECT | ID).
+pub struct RTree<B> {
+    storage: IndexedFile<B>,
+    node_size: usize,
+    levels: Vec<Range<usize>>,
+}
+
+impl<B> RTree<B>
+    where
+        B: GrowableBackend,
+{
+    /// Builds a new R-tree containing all `items` using the default node size.
+    #[inline]
+    pub fn build<I>(backend: B, items: I) -> Result<Self>
+        where
+            I: IntoIterator<Item=(Rect, u64)>,
+    {
+        Self::build_with_node_size(backend, items, DEFAULT_NODE_SIZE)
+    }
+
+    /// Builds a new R-tree containing all `items` where each node has up to `node_size` children.
+    pub fn build_with_node_size<I>(backend: B, items: I, node_size: usize) -> Result<Self>
+        where
+            I: IntoIterator<Item=(Rect, u64)>,
+    {
+        if node_size < 2 {
+            return Err(Error::UnexpectedValue);
+        }
+
+        let mut leaves: Vec<(Rect, u64)> = items.into_iter().collect();
+        str_sort(&mut leaves, node_size);
+
+        let mut levels: Vec<Range<usize>> = Vec::new();
+        levels.push(0..leaves.len());
+        let mut nodes = leaves;
+
+        // Build parent levels until there is only a single root node left.
+        while levels.last().unwrap().len() > 1 {
+            let level = levels.last().unwrap().clone();
+            let parents: Vec<(Rect, u64)> = nodes[level]
+                .chunks(node_size)
+                .map(|children| {
+                    let bbox = children.iter().skip(1).fold(children[0].0, |a, b| a.union(&b.0));
+                    (bbox, 0)
+                })
+                .collect();
+            levels.push(nodes.len()..nodes.len() + parents.len());
+            nodes.extend(parents);
+        }
+
+        let mut data = Vec::with_capacity(nodes.len() * ENTRY_SIZE);
+        for (rect, id) in nodes.iter() {
+            data.extend_from_slice(&rect.to_bytes());
+            data.extend_from_slice(&id.to_le_bytes());
+        }
+
+        let metadata = RTreeMetadata {
+            node_size: node_size as u32,
+            levels: levels.iter().map(|i| (i.start as u64, i.end as u64)).collect(),
+        };
+
+        let mut storage = IndexedFile::with_capacity(backend, 2)?;
+        storage.insert_t(&metadata)?;
+        storage.insert(&data)?;
+
+        Ok(Self {
+            storage,
+            node_size,
+            levels,
+        })
+    }
+}
+
+impl<B> RTree<B>
+    where
+        B: Backend,
+{
+    /// Returns the amount of items stored in the tree.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// Returns `true` if the tree doesn't contain any items.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the bounding box of all items or `None` if the tree is empty.
+    pub fn bounds(&self) -> Option<Rect> {
+        if self.is_empty() {
+            return None;
+        }
+        let root = self.levels.last().unwrap().start;
+        Some(self.entry(root).0)
+    }
+
+    /// Returns the IDs of all items intersecting `bbox`.
+    pub fn query(&self, bbox: &Rect) -> Vec<u64> {
+        let mut out = vec![];
+        if self.is_empty() {
+            return out;
+        }
+
+        let mut stack = vec![(self.levels.len() - 1, 0)];
+        while let Some((level, index)) = stack.pop() {
+            let (rect, id) = self.entry(self.levels[level].start + index);
+            if !rect.intersects(bbox) {
+                continue;
+            }
+
+            if level == 0 {
+                out.push(id);
+            } else {
+                stack.extend(self.children(level, index).map(|i| (level - 1, i)));
+            }
+        }
+
+        out
+    }
+
+    /// Returns the IDs and distances of the `k` items closest to the point (`x`, `y`), ordered by their distance.
+    pub fn nearest(&self, x: f64, y: f64, k: usize) -> Vec<(u64, f64)> {
+        let mut out = Vec::with_capacity(k);
+        if self.is_empty() || k == 0 {
+            return out;
+        }
+
+        let mut heap = BinaryHeap::new();
+        let root_level = self.levels.len() - 1;
+        let (rect, _) = self.entry(self.levels[root_level].start);
+        heap.push(HeapItem {
+            dist2: rect.dist2(x, y),
+            level: root_level,
+            index: 0,
+        });
+
+        while let Some(item) = heap.pop() {
+            if item.level == 0 {
+                let (_, id) = self.entry(self.levels[0].start + item.index);
+                out.push((id, item.dist2.sqrt()));
+                if out.len() == k {
+                    break;
+                }
+                continue;
+            }
+
+            for child in self.children(item.level, item.index) {
+                let (child_rect, _) = self.entry(self.levels[item.level - 1].start + child);
+                heap.push(HeapItem {
+                    dist2: child_rect.dist2(x, y),
+                    level: item.level - 1,
+                    index: child,
+                });
+            }
+        }
+
+        out
+    }
+
+    /// Returns the index range (within the level below) of the children of a node.
+    #[inline]
+    fn children(&self, level: usize, index: usize) -> Range<usize> {
+        let start = index * self.node_size;
+        let end = (start + self.node_size).min(self.levels[level - 1].len());
+        start..end
+    }
+
+    /// Returns the entry with the given (global) index.
+    #[inline]
+    fn entry(&self, index: usize) -> (Rect, u64) {
+        let nodes = self.storage.get(NODES_ID).unwrap();
+        let data = &nodes[index * ENTRY_SIZE..(index + 1) * ENTRY_SIZE];
+        let rect = Rect::from_bytes(&data[..32]);
+        let id = u64::from_le_bytes(data[32..].try_into().unwrap());
+        (rect, id)
+    }
+}
+
+impl<B> Initiable<B> for RTree<B>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let storage = IndexedFile::init(backend)?;
+        let metadata: RTreeMetadata = storage.get_t(METADATA_ID)?;
+
+        let levels: Vec<_> = metadata
+            .levels
+            .iter()
+            .map(|(start, end)| *start as usize..*end as usize)
+            .collect();
+
+        let node_count = levels.last().map(|i| i.end).unwrap_or(0);
+        if levels.is_empty() || storage.get(NODES_ID)?.len() != node_count * ENTRY_SIZE {
+            return Err(Error::Initialization);
+        }
+
+        Ok(Self {
+            storage,
+            node_size: metadata.node_size as usize,
+            levels,
+        })
+    }
+}
+
+impl<B> MType for RTree<B>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.storage.raw_data()
+    }
+}
+
+/// Sorts the items using the Sort-Tile-Recursive algorithm so that consecutive chunks of `node_size` items are
+/// spatially close to each other.
+fn str_sort(items: &mut [(Rect, u64)], node_size: usize) {
+    let leaf_count = items.len().div_ceil(node_size);
+    let slice_count = (leaf_count as f64).sqrt().ceil() as usize;
+    let slice_size = slice_count.max(1) * node_size;
+
+    items.sort_unstable_by(|a, b| a.0.center()[0].total_cmp(&b.0.center()[0]));
+    for slice in items.chunks_mut(slice_size) {
+        slice.sort_unstable_by(|a, b| a.0.center()[1].total_cmp(&b.0.center()[1]));
+    }
+}
+
+/// Item of the priority queue used for nearest neighbor search. Ordered by smallest distance first.
+struct HeapItem {
+    dist2: f64,
+    level: usize,
+    index: usize,
+}
+
+impl PartialEq for HeapItem {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for HeapItem {}
+
+impl PartialOrd for HeapItem {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapItem {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed as `BinaryHeap` is a max heap. Leaves first on equal distance to return items early.
+        other
+            .dist2
+            .total_cmp(&self.dist2)
+            .then_with(|| other.level.cmp(&self.level))
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_query(&mut mem_backend);
+        test_nearest(&mut mem_backend);
+        test_empty(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./rtree", 10);
+        test_query(&mut mmap_backend);
+        test_nearest(&mut mmap_backend);
+        test_empty(&mut mmap_backend);
+    }
+
+    /// Generates a deterministic grid-like set of points with some noise.
+    fn make_points(count: usize) -> Vec<(Rect, u64)> {
+        (0..count)
+            .map(|i| {
+                let x = ((i * 7919) % 1000) as f64 / 10.0;
+                let y = ((i * 104729) % 997) as f64 / 10.0;
+                (Rect::point(x, y), i as u64)
+            })
+            .collect()
+    }
+
+    fn test_query<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let points = make_points(1000);
+        {
+            let tree = RTree::build_with_node_size(&mut backend, points.clone(), 8).unwrap();
+            assert_eq!(tree.len(), 1000);
+        }
+
+        let tree = RTree::init(&mut backend).unwrap();
+        let bounds = tree.bounds().unwrap();
+        assert!(points.iter().all(|(r, _)| bounds.contains(r)));
+
+        let boxes = [
+            Rect::new(10.0, 10.0, 20.0, 30.0),
+            Rect::new(0.0, 0.0, 100.0, 100.0),
+            Rect::new(50.0, 50.0, 50.5, 50.5),
+            Rect::new(200.0, 200.0, 300.0, 300.0),
+        ];
+        for bbox in boxes {
+            let mut found = tree.query(&bbox);
+            found.sort_unstable();
+            let expected: Vec<_> = points
+                .iter()
+                .filter(|(r, _)| r.intersects(&bbox))
+                .map(|(_, id)| *id)
+                .collect();
+            assert_eq!(found, expected);
+        }
+    }
+
+    fn test_nearest<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let points = make_points(500);
+        let tree = RTree::build(&mut backend, points.clone()).unwrap();
+
+        for (x, y) in [(0.0, 0.0), (50.0, 50.0), (99.0, 12.5), (-10.0, 200.0)] {
+            let found = tree.nearest(x, y, 5);
+            assert_eq!(found.len(), 5);
+
+            let mut expected: Vec<_> = points.iter().map(|(r, _)| r.dist2(x, y).sqrt()).collect();
+            expected.sort_unstable_by(|a, b| a.total_cmp(b));
+            let dists: Vec<_> = found.iter().map(|i| i.1).collect();
+            assert_eq!(dists, expected[..5]);
+        }
+    }
+
+    fn test_empty<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let tree = RTree::build(&mut backend, vec![]).unwrap();
+        assert!(tree.is_empty());
+        assert_eq!(tree.bounds(), None);
+        assert!(tree.query(&Rect::new(0.0, 0.0, 1.0, 1.0)).is_empty());
+        assert!(tree.nearest(0.0, 0.0, 3).is_empty());
+    }
+}
@@ -0,0 +1,84 @@
+/// An axis aligned rectangle. Points are represented as rectangles with `min == max`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub min: [f64; 2],
+    pub max: [f64; 2],
+}
+
+impl Rect {
+    #[inline]
+    pub fn new(min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> Self {
+        Self {
+            min: [min_x, min_y],
+            max: [max_x, max_y],
+        }
+    }
+
+    /// Creates a rectangle representing a single point.
+    #[inline]
+    pub fn point(x: f64, y: f64) -> Self {
+        Self::new(x, y, x, y)
+    }
+
+    /// Returns `true` if both rectangles overlap or touch.
+    #[inline]
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.min[0] <= other.max[0]
+            && self.max[0] >= other.min[0]
+            && self.min[1] <= other.max[1]
+            && self.max[1] >= other.min[1]
+    }
+
+    /// Returns `true` if `other` is entirely within `self`.
+    #[inline]
+    pub fn contains(&self, other: &Rect) -> bool {
+        self.min[0] <= other.min[0]
+            && self.min[1] <= other.min[1]
+            && self.max[0] >= other.max[0]
+            && self.max[1] >= other.max[1]
+    }
+
+    /// Returns the smallest rectangle containing both rectangles.
+    #[inline]
+    pub fn union(&self, other: &Rect) -> Rect {
+        Rect::new(
+            self.min[0].min(other.min[0]),
+            self.min[1].min(other.min[1]),
+            self.max[0].max(other.max[0]),
+            self.max[1].max(other.max[1]),
+        )
+    }
+
+    /// Returns the center of the rectangle.
+    #[inline]
+    pub fn center(&self) -> [f64; 2] {
+        [
+            (self.min[0] + self.max[0]) / 2.0,
+            (self.min[1] + self.max[1]) / 2.0,
+        ]
+    }
+
+    /// Returns the squared distance of the point (`x`, `y`) to the closest point of the rectangle.
+    #[inline]
+    pub fn dist2(&self, x: f64, y: f64) -> f64 {
+        let dx = (self.min[0] - x).max(0.0).max(x - self.max[0]);
+        let dy = (self.min[1] - y).max(0.0).max(y - self.max[1]);
+        dx * dx + dy * dy
+    }
+
+    #[inline]
+    pub(super) fn to_bytes(self) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        let values = [self.min[0], self.min[1], self.max[0], self.max[1]];
+        for (i, v) in values.iter().enumerate() {
+            out[i * 8..(i + 1) * 8].copy_from_slice(&v.to_le_bytes());
+        }
+        out
+    }
+
+    #[inline]
+    pub(super) fn from_bytes(data: &[u8]) -> Self {
+        let v = |i: usize| f64::from_le_bytes(data[i * 8..(i + 1) * 8].try_into().unwrap());
+        Rect::new(v(0), v(1), v(2), v(3))
+    }
+}
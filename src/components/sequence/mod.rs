@@ -0,0 +1,208 @@
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::{Error, Result};
+use std::ops::Range;
+
+/// Amount of IDs reserved at once if no batch size is given.
+pub const DEFAULT_BATCH_SIZE: u64 = 128;
+
+/// Size of the stored data: reserved u64 | batch size u64
+const DATA_LEN: usize = 16;
+
+/// A persistent counter handing out unique, increasing IDs.
+///
+/// IDs are reserved in batches: before an ID of a new batch is returned, the end of the batch gets written and
+/// flushed to the backend. After a crash or reopening, counting continues at the end of the last reserved batch so an
+/// ID is never returned twice. IDs that were reserved but not handed out get skipped.
+///
+/// The internal format: RESERVED | BATCH SIZE
+pub struct Sequence<B> {
+    backend: B,
+    next: u64,
+    reserved: u64,
+    batch_size: u64,
+}
+
+impl<B> Sequence<B>
+    where
+        B: GrowableBackend,
+{
+    /// Creates a new sequence starting at 0 which reserves `batch_size` IDs at once. A batch size of 1 persists each
+    /// returned ID.
+    pub fn with_batch_size(backend: B, batch_size: u64) -> Result<Self> {
+        Self::starting_at(backend, 0, batch_size)
+    }
+
+    /// Creates a new sequence starting at `start` which reserves `batch_size` IDs at once.
+    pub fn starting_at(mut backend: B, start: u64, batch_size: u64) -> Result<Self> {
+        if batch_size == 0 {
+            return Err(Error::UnexpectedValue);
+        }
+
+        if backend.capacity() < DATA_LEN {
+            backend.grow_to(DATA_LEN)?;
+        }
+        backend.push(&start.to_le_bytes())?;
+        backend.push(&batch_size.to_le_bytes())?;
+
+        Ok(Self {
+            backend,
+            next: start,
+            reserved: start,
+            batch_size,
+        })
+    }
+}
+
+impl<B> Sequence<B>
+    where
+        B: Backend,
+{
+    /// Returns the next ID.
+    #[inline]
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<u64> {
+        Ok(self.next_n(1)?.start)
+    }
+
+    /// Returns a range of `n` consecutive IDs.
+    pub fn next_n(&mut self, n: u64) -> Result<Range<u64>> {
+        let end = self.next.checked_add(n).ok_or(Error::OutOfBounds)?;
+        if end > self.reserved {
+            // Reserve enough batches to cover all requested IDs.
+            let missing = end - self.reserved;
+            let batches = missing.div_ceil(self.batch_size);
+            let reserved = batches
+                .checked_mul(self.batch_size)
+                .and_then(|i| self.reserved.checked_add(i))
+                .unwrap_or(u64::MAX);
+            self.persist(reserved)?;
+        }
+
+        let range = self.next..end;
+        self.next = end;
+        Ok(range)
+    }
+
+    /// Returns the ID that gets returned by the next call of `next` without consuming it.
+    #[inline]
+    pub fn peek(&self) -> u64 {
+        self.next
+    }
+
+    /// Returns the amount of IDs that get reserved at once.
+    #[inline]
+    pub fn batch_size(&self) -> u64 {
+        self.batch_size
+    }
+
+    /// Writes and flushes the new end of the reserved IDs.
+    fn persist(&mut self, reserved: u64) -> Result<()> {
+        self.backend.replace_same_len(0, &reserved.to_le_bytes())?;
+        self.backend.flush()?;
+        self.reserved = reserved;
+        Ok(())
+    }
+}
+
+impl<B> Creatable<B> for Sequence<B>
+    where
+        B: GrowableBackend,
+{
+    /// Creates a new sequence starting at 0 using the default batch size. The capacity is ignored.
+    #[inline]
+    fn with_capacity(backend: B, _: usize) -> Result<Self> {
+        Self::with_batch_size(backend, DEFAULT_BATCH_SIZE)
+    }
+}
+
+impl<B> Initiable<B> for Sequence<B>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let data = backend
+            .get(0, DATA_LEN)
+            .map_err(|_| Error::Initialization)?;
+        let reserved = u64::from_le_bytes(data[..8].try_into().unwrap());
+        let batch_size = u64::from_le_bytes(data[8..].try_into().unwrap());
+        if batch_size == 0 {
+            return Err(Error::Initialization);
+        }
+
+        // IDs of the last batch might have been handed out already so continue after it.
+        Ok(Self {
+            backend,
+            next: reserved,
+            reserved,
+            batch_size,
+        })
+    }
+}
+
+impl<B> MType for Sequence<B>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.backend.data()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_next(&mut mem_backend);
+        test_reopen(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./sequence", 10);
+        test_next(&mut mmap_backend);
+        test_reopen(&mut mmap_backend);
+    }
+
+    fn test_next<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut seq = Sequence::create(&mut backend).unwrap();
+        assert_eq!(seq.batch_size(), DEFAULT_BATCH_SIZE);
+        for i in 0..1000 {
+            assert_eq!(seq.peek(), i);
+            assert_eq!(seq.next(), Ok(i));
+        }
+        assert_eq!(seq.next_n(10), Ok(1000..1010));
+        assert_eq!(seq.next_n(0), Ok(1010..1010));
+
+        backend.clear();
+        assert!(Sequence::with_batch_size(&mut backend, 0).is_err());
+    }
+
+    fn test_reopen<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        {
+            let mut seq = Sequence::starting_at(&mut backend, 5, 10).unwrap();
+            assert_eq!(seq.next(), Ok(5));
+            assert_eq!(seq.next(), Ok(6));
+        }
+
+        // The rest of the first batch gets skipped.
+        {
+            let mut seq = Sequence::init(&mut backend).unwrap();
+            assert_eq!(seq.batch_size(), 10);
+            assert_eq!(seq.next(), Ok(15));
+            assert_eq!(seq.next_n(25), Ok(16..41));
+        }
+
+        let mut seq = Sequence::init(&mut backend).unwrap();
+        assert_eq!(seq.next(), Ok(45));
+    }
+}
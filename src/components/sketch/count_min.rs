@@ -0,0 +1,304 @@
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::map::hashing::Hash;
+use crate::components::multi_file::MultiFile;
+use crate::components::number_seq::NumberSequence;
+use crate::components::sketch::mix;
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::{Error, Result};
+
+/// ID of the MultiFile entry holding the metadata (width, depth, total).
+const METADATA_ID: usize = 0;
+
+/// ID of the MultiFile entry holding the counters.
+const COUNTERS_ID: usize = 1;
+
+/// Size of the metadata: width u32 | depth u32 | total u64
+const METADATA_LEN: usize = 16;
+
+/// A count-min sketch estimating the frequency of items in a stream using a fixed amount of memory. Estimations are
+/// never lower than the real count but might be higher due to hash collisions.
+///
+/// Keys are hashed using FNV so the sketch can be reopened and merged with other sketches.
+///
+/// The internal format: MultiFile(METADATA | COUNTERS)
+pub struct CountMinSketch<B> {
+    backend: MultiFile<B>,
+    width: usize,
+    depth: usize,
+    total: u64,
+}
+
+impl<B> CountMinSketch<B>
+    where
+        B: GrowableBackend,
+{
+    /// Creates a new sketch with `depth` rows of `width` counters each.
+    pub fn new(backend: B, width: usize, depth: usize) -> Result<Self> {
+        if width == 0 || depth == 0 || width > u32::MAX as usize || depth > u32::MAX as usize {
+            return Err(Error::UnexpectedValue);
+        }
+
+        let mut backend = MultiFile::create(backend)?;
+
+        let mut metadata = backend.insert_empty()?;
+        metadata.grow_to(METADATA_LEN)?;
+        metadata.push(&(width as u32).to_le_bytes())?;
+        metadata.push(&(depth as u32).to_le_bytes())?;
+        metadata.push(&0u64.to_le_bytes())?;
+
+        let mut counters = backend.insert_empty()?;
+        counters.grow_to(width * depth * 8)?;
+        counters.push_fill(0, width * depth * 8)?;
+
+        Ok(Self {
+            backend,
+            width,
+            depth,
+            total: 0,
+        })
+    }
+
+    /// Creates a new sketch whose estimations exceed the real count by at most `epsilon * total` with a probability
+    /// of `1 - delta`.
+    pub fn with_error(backend: B, epsilon: f64, delta: f64) -> Result<Self> {
+        if epsilon <= 0.0 || delta <= 0.0 || delta >= 1.0 {
+            return Err(Error::UnexpectedValue);
+        }
+
+        let width = (std::f64::consts::E / epsilon).ceil() as usize;
+        let depth = (1.0 / delta).ln().ceil().max(1.0) as usize;
+        Self::new(backend, width, depth)
+    }
+}
+
+impl<B> CountMinSketch<B>
+    where
+        B: Backend,
+{
+    /// Adds `count` occurrences of `key` and returns the new estimated count of it.
+    #[inline]
+    pub fn add<K: Hash>(&mut self, key: &K, count: u64) -> Result<u64> {
+        self.add_hash(key.hash(), count)
+    }
+
+    /// Adds `count` occurrences of an item with the given hash and returns the new estimated count of it.
+    pub fn add_hash(&mut self, hash: u64, count: u64) -> Result<u64> {
+        let indices: Vec<_> = self.indices(hash).collect();
+
+        let mut counters: NumberSequence<_, u64, 8> =
+            NumberSequence::init(self.backend.get_mut(COUNTERS_ID).unwrap())?;
+
+        let mut estimate = u64::MAX;
+        for index in indices {
+            let new = counters.get(index)?.saturating_add(count);
+            counters.set(index, new)?;
+            estimate = estimate.min(new);
+        }
+
+        self.set_total(self.total.saturating_add(count))?;
+        Ok(estimate)
+    }
+
+    /// Returns the estimated count of `key`.
+    #[inline]
+    pub fn estimate<K: Hash>(&self, key: &K) -> Result<u64> {
+        self.estimate_hash(key.hash())
+    }
+
+    /// Returns the estimated count of an item with the given hash.
+    pub fn estimate_hash(&self, hash: u64) -> Result<u64> {
+        let counters: NumberSequence<_, u64, 8> =
+            NumberSequence::init(self.backend.get(COUNTERS_ID).unwrap())?;
+
+        let mut estimate = u64::MAX;
+        for index in self.indices(hash) {
+            estimate = estimate.min(counters.get(index)?);
+        }
+        Ok(estimate)
+    }
+
+    /// Merges `other` into this sketch so it estimates the counts of the items added to both. Both must have the same
+    /// width and depth.
+    pub fn merge<O: Backend>(&mut self, other: &CountMinSketch<O>) -> Result<()> {
+        if other.width != self.width || other.depth != self.depth {
+            return Err(Error::UnexpectedValue);
+        }
+
+        let other_counters: NumberSequence<_, u64, 8> =
+            NumberSequence::init(other.backend.get(COUNTERS_ID).unwrap())?;
+        let mut counters: NumberSequence<_, u64, 8> =
+            NumberSequence::init(self.backend.get_mut(COUNTERS_ID).unwrap())?;
+        for (i, other) in other_counters.iter().enumerate() {
+            if other > 0 {
+                counters.set(i, counters.get(i)?.saturating_add(other))?;
+            }
+        }
+
+        self.set_total(self.total.saturating_add(other.total))
+    }
+
+    /// Returns the amount of counters per row.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the amount of rows.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the sum of all added counts.
+    #[inline]
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.backend.flush()
+    }
+
+    /// Returns the counter index of `hash` in each row.
+    #[inline]
+    fn indices(&self, hash: u64) -> impl Iterator<Item=usize> {
+        let h1 = mix(hash);
+        let h2 = mix(h1) | 1;
+        let width = self.width as u64;
+        (0..self.depth as u64).map(move |row| {
+            let col = h1.wrapping_add(row.wrapping_mul(h2)) % width;
+            (row * width + col) as usize
+        })
+    }
+
+    fn set_total(&mut self, total: u64) -> Result<()> {
+        let mut metadata = self.backend.get_mut(METADATA_ID).unwrap();
+        metadata.replace_same_len(8, &total.to_le_bytes())?;
+        self.total = total;
+        Ok(())
+    }
+}
+
+impl<B> Initiable<B> for CountMinSketch<B>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let backend = MultiFile::init(backend)?;
+
+        let metadata = backend.get(METADATA_ID).ok_or(Error::Initialization)?;
+        let metadata = metadata.get(0, METADATA_LEN)?;
+        let width = u32::from_le_bytes(metadata[..4].try_into().unwrap()) as usize;
+        let depth = u32::from_le_bytes(metadata[4..8].try_into().unwrap()) as usize;
+        let total = u64::from_le_bytes(metadata[8..].try_into().unwrap());
+
+        let counters = backend.get(COUNTERS_ID).ok_or(Error::Initialization)?;
+        if width == 0 || depth == 0 || counters.len() != width * depth * 8 {
+            return Err(Error::Initialization);
+        }
+
+        Ok(Self {
+            backend,
+            width,
+            depth,
+            total,
+        })
+    }
+}
+
+impl<B> MType for CountMinSketch<B>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.backend.raw_data()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_estimate(&mut mem_backend);
+        test_error_bound(&mut mem_backend);
+        test_merge(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./count_min", 10);
+        test_estimate(&mut mmap_backend);
+        test_error_bound(&mut mmap_backend);
+        test_merge(&mut mmap_backend);
+    }
+
+    fn test_estimate<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        {
+            let mut sketch = CountMinSketch::new(&mut backend, 64, 4).unwrap();
+            assert_eq!(sketch.estimate(&"a"), Ok(0));
+            assert_eq!(sketch.add(&"a", 1), Ok(1));
+            assert_eq!(sketch.add(&"a", 2), Ok(3));
+            assert_eq!(sketch.add(&"b", 5), Ok(5));
+            assert_eq!(sketch.total(), 8);
+        }
+
+        let sketch = CountMinSketch::init(&mut backend).unwrap();
+        assert_eq!(sketch.width(), 64);
+        assert_eq!(sketch.depth(), 4);
+        assert_eq!(sketch.total(), 8);
+        assert_eq!(sketch.estimate(&"a"), Ok(3));
+        assert_eq!(sketch.estimate(&"b"), Ok(5));
+
+        backend.clear();
+        assert!(CountMinSketch::new(&mut backend, 0, 4).is_err());
+    }
+
+    fn test_error_bound<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut sketch = CountMinSketch::with_error(&mut backend, 0.01, 0.01).unwrap();
+
+        let mut counts = vec![0u64; 500];
+        for i in 0..5000u64 {
+            let key = (i * i) % 500;
+            sketch.add(&key, 1).unwrap();
+            counts[key as usize] += 1;
+        }
+
+        let bound = (0.01 * sketch.total() as f64) as u64;
+        for (key, count) in counts.iter().enumerate() {
+            let estimate = sketch.estimate(&(key as u64)).unwrap();
+            assert!(estimate >= *count);
+            assert!(estimate <= count + bound);
+        }
+    }
+
+    fn test_merge<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut sketch = CountMinSketch::new(&mut backend, 64, 4).unwrap();
+        sketch.add(&"a", 3).unwrap();
+        sketch.add(&"b", 1).unwrap();
+
+        let mut other = CountMinSketch::new(make_mem_backend(10), 64, 4).unwrap();
+        other.add(&"a", 2).unwrap();
+        other.add(&"c", 7).unwrap();
+
+        sketch.merge(&other).unwrap();
+        assert_eq!(sketch.total(), 13);
+        assert_eq!(sketch.estimate(&"a"), Ok(5));
+        assert_eq!(sketch.estimate(&"b"), Ok(1));
+        assert_eq!(sketch.estimate(&"c"), Ok(7));
+        assert_eq!(other.estimate(&"b"), Ok(0));
+
+        let other = CountMinSketch::new(make_mem_backend(10), 32, 4).unwrap();
+        assert_eq!(sketch.merge(&other), Err(Error::UnexpectedValue));
+    }
+}
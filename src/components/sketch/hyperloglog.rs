@@ -0,0 +1,269 @@
+use crate::backend::base::sub_mut::GeneralSubMutBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::map::hashing::Hash;
+use crate::components::multi_file::MultiFile;
+use crate::components::number_seq::NumberSequence;
+use crate::components::sketch::mix;
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::{Error, Result};
+
+/// ID of the MultiFile entry holding the precision.
+const METADATA_ID: usize = 0;
+
+/// ID of the MultiFile entry holding the registers.
+const REGISTERS_ID: usize = 1;
+
+/// Smallest allowed precision.
+pub const MIN_PRECISION: u8 = 4;
+
+/// Largest allowed precision.
+pub const MAX_PRECISION: u8 = 18;
+
+/// A HyperLogLog estimating the amount of distinct items using `2^precision` one byte registers. The standard error
+/// of the estimation is about `1.04 / sqrt(2^precision)`.
+///
+/// The internal format: MultiFile(PRECISION | REGISTERS)
+pub struct HyperLogLog<B> {
+    backend: MultiFile<B>,
+    precision: u8,
+}
+
+impl<B> HyperLogLog<B>
+    where
+        B: GrowableBackend,
+{
+    /// Creates a new, empty HyperLogLog using `2^precision` registers.
+    pub fn new(backend: B, precision: u8) -> Result<Self> {
+        if !(MIN_PRECISION..=MAX_PRECISION).contains(&precision) {
+            return Err(Error::UnexpectedValue);
+        }
+
+        let mut backend = MultiFile::create(backend)?;
+
+        let mut metadata = backend.insert_empty()?;
+        metadata.grow_to(1)?;
+        metadata.push(&[precision])?;
+
+        let register_count = 1 << precision;
+        let mut registers = backend.insert_empty()?;
+        registers.grow_to(register_count)?;
+        registers.push_fill(0, register_count)?;
+
+        Ok(Self { backend, precision })
+    }
+}
+
+impl<B> HyperLogLog<B>
+    where
+        B: Backend,
+{
+    /// Adds an item with the given hash. Returns `true` if the estimation might have changed.
+    pub fn insert(&mut self, hash: u64) -> Result<bool> {
+        let hash = mix(hash);
+        let index = (hash >> (64 - self.precision)) as usize;
+        let rank = ((hash << self.precision).leading_zeros() + 1).min(64 - self.precision as u32 + 1) as u8;
+
+        let mut registers = self.registers_mut()?;
+        if registers.get(index)? >= rank {
+            return Ok(false);
+        }
+        registers.set(index, rank)?;
+        Ok(true)
+    }
+
+    /// Adds `key`. Returns `true` if the estimation might have changed.
+    #[inline]
+    pub fn insert_key<K: Hash>(&mut self, key: &K) -> Result<bool> {
+        self.insert(key.hash())
+    }
+
+    /// Returns the estimated amount of distinct items.
+    pub fn estimate(&self) -> f64 {
+        let m = self.register_count() as f64;
+        let registers: NumberSequence<_, u8, 1> =
+            NumberSequence::init(self.backend.get(REGISTERS_ID).unwrap()).unwrap();
+
+        let mut sum = 0.0;
+        let mut zeros = 0;
+        for register in registers.iter() {
+            sum += 1.0 / (1u64 << register) as f64;
+            if register == 0 {
+                zeros += 1;
+            }
+        }
+
+        let alpha = match self.precision {
+            4 => 0.673,
+            5 => 0.697,
+            6 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+        let estimate = alpha * m * m / sum;
+
+        // Use linear counting for small cardinalities.
+        if estimate <= 2.5 * m && zeros > 0 {
+            return m * (m / zeros as f64).ln();
+        }
+
+        estimate
+    }
+
+    /// Merges `other` into this HyperLogLog so it estimates the amount of distinct items of both. Both must have the
+    /// same precision.
+    pub fn merge<O: Backend>(&mut self, other: &HyperLogLog<O>) -> Result<()> {
+        if other.precision != self.precision {
+            return Err(Error::UnexpectedValue);
+        }
+
+        let other_registers: NumberSequence<_, u8, 1> =
+            NumberSequence::init(other.backend.get(REGISTERS_ID).unwrap())?;
+        let mut registers = self.registers_mut()?;
+        for (i, other) in other_registers.iter().enumerate() {
+            if other > registers.get(i)? {
+                registers.set(i, other)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Resets all registers.
+    pub fn clear(&mut self) -> Result<()> {
+        let mut registers = self.backend.get_mut(REGISTERS_ID).unwrap();
+        let len = registers.len();
+        registers.replace_same_len_fill(0, 0, len)?;
+        Ok(())
+    }
+
+    /// Returns the precision.
+    #[inline]
+    pub fn precision(&self) -> u8 {
+        self.precision
+    }
+
+    /// Returns the amount of registers.
+    #[inline]
+    pub fn register_count(&self) -> usize {
+        1 << self.precision
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.backend.flush()
+    }
+
+    #[inline]
+    fn registers_mut(&mut self) -> Result<NumberSequence<GeneralSubMutBackend<'_>, u8, 1>> {
+        NumberSequence::init(self.backend.get_mut(REGISTERS_ID).unwrap())
+    }
+}
+
+impl<B> Initiable<B> for HyperLogLog<B>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let backend = MultiFile::init(backend)?;
+
+        let metadata = backend.get(METADATA_ID).ok_or(Error::Initialization)?;
+        let precision = metadata.get_single(0)?;
+        if !(MIN_PRECISION..=MAX_PRECISION).contains(&precision) {
+            return Err(Error::Initialization);
+        }
+
+        let registers = backend.get(REGISTERS_ID).ok_or(Error::Initialization)?;
+        if registers.len() != 1 << precision {
+            return Err(Error::Initialization);
+        }
+
+        Ok(Self { backend, precision })
+    }
+}
+
+impl<B> MType for HyperLogLog<B>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.backend.raw_data()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_estimate(&mut mem_backend);
+        test_merge(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./hyperloglog", 10);
+        test_estimate(&mut mmap_backend);
+        test_merge(&mut mmap_backend);
+    }
+
+    fn assert_close(estimate: f64, real: f64) {
+        let error = (estimate - real).abs() / real;
+        assert!(error < 0.05, "estimate {estimate} too far off {real}");
+    }
+
+    fn test_estimate<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        {
+            let mut hll = HyperLogLog::new(&mut backend, 12).unwrap();
+            assert_eq!(hll.estimate(), 0.0);
+            assert_eq!(hll.insert_key(&1u64), Ok(true));
+            assert_eq!(hll.insert_key(&1u64), Ok(false));
+
+            for i in 0..50_000u64 {
+                hll.insert_key(&i).unwrap();
+            }
+            assert_close(hll.estimate(), 50_000.0);
+        }
+
+        let mut hll = HyperLogLog::init(&mut backend).unwrap();
+        assert_eq!(hll.precision(), 12);
+        assert_eq!(hll.register_count(), 4096);
+        assert_close(hll.estimate(), 50_000.0);
+
+        // Duplicates don't change the estimation.
+        let before = hll.estimate();
+        for i in 0..1000u64 {
+            hll.insert_key(&i).unwrap();
+        }
+        assert_eq!(hll.estimate(), before);
+
+        hll.clear().unwrap();
+        assert_eq!(hll.estimate(), 0.0);
+
+        backend.clear();
+        assert!(HyperLogLog::new(&mut backend, 3).is_err());
+    }
+
+    fn test_merge<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut a = HyperLogLog::new(&mut backend, 12).unwrap();
+        let mut b = HyperLogLog::new(make_mem_backend(10), 12).unwrap();
+
+        for i in 0..20_000u64 {
+            a.insert_key(&i).unwrap();
+        }
+        for i in 10_000..40_000u64 {
+            b.insert_key(&i).unwrap();
+        }
+
+        a.merge(&b).unwrap();
+        assert_close(a.estimate(), 40_000.0);
+
+        let other = HyperLogLog::new(make_mem_backend(10), 10).unwrap();
+        assert_eq!(a.merge(&other), Err(Error::UnexpectedValue));
+    }
+}
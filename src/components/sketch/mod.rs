@@ -0,0 +1,11 @@
+pub mod count_min;
+pub mod hyperloglog;
+pub mod top_k;
+
+/// Finalizer of SplitMix64. Used to spread weak (eg. FNV) hashes over all bits.
+#[inline]
+pub(crate) fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
@@ -0,0 +1,230 @@
+use crate::backend::base::sub::GeneralSubBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::map::hashing::Hash;
+use crate::components::multi_file::entry_mut::MFileEntryMut;
+use crate::components::multi_file::MultiFile;
+use crate::components::sketch::count_min::CountMinSketch;
+use crate::deser::{deserialize_impl, serialize_impl};
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::{Error, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::cmp::Reverse;
+
+/// ID of the MultiFile entry holding the amount of tracked heavy hitters.
+const METADATA_ID: usize = 0;
+
+/// ID of the MultiFile entry holding the count-min sketch.
+const SKETCH_ID: usize = 1;
+
+/// ID of the MultiFile entry holding the encoded list of heavy hitters.
+const TOP_ID: usize = 2;
+
+/// Tracks the (approximately) `k` most frequent items of a stream. Frequencies are estimated using a count-min
+/// sketch and only the current heavy hitters are stored along with their estimated count.
+///
+/// The internal format: MultiFile(METADATA | COUNT-MIN SKETCH | TOP ITEMS)
+pub struct TopK<B, K> {
+    backend: MultiFile<B>,
+    k: usize,
+    /// Current heavy hitters ordered by their count descending.
+    top: Vec<(K, u64)>,
+}
+
+impl<B, K> TopK<B, K>
+    where
+        B: GrowableBackend,
+        K: Hash + Serialize + DeserializeOwned + PartialEq + Clone,
+{
+    /// Creates a new top-K list tracking the `k` most frequent items using a count-min sketch with the given
+    /// dimensions.
+    pub fn new(backend: B, k: usize, width: usize, depth: usize) -> Result<Self> {
+        if k == 0 || k > u32::MAX as usize {
+            return Err(Error::UnexpectedValue);
+        }
+
+        let mut backend = MultiFile::create(backend)?;
+
+        let mut metadata = backend.insert_empty()?;
+        metadata.grow_to(4)?;
+        metadata.push(&(k as u32).to_le_bytes())?;
+
+        CountMinSketch::new(backend.insert_empty()?, width, depth)?;
+        backend.insert_empty()?;
+
+        let mut top_k = Self {
+            backend,
+            k,
+            top: vec![],
+        };
+        top_k.write_top()?;
+        Ok(top_k)
+    }
+
+    /// Adds a single occurrence of `key`.
+    #[inline]
+    pub fn insert(&mut self, key: &K) -> Result<()> {
+        self.add(key, 1)
+    }
+
+    /// Adds `count` occurrences of `key` and updates the heavy hitters.
+    pub fn add(&mut self, key: &K, count: u64) -> Result<()> {
+        let estimate = self.sketch_mut().add(key, count)?;
+
+        if let Some(pos) = self.top.iter().position(|i| i.0 == *key) {
+            self.top[pos].1 = estimate;
+        } else if self.top.len() < self.k {
+            self.top.push((key.clone(), estimate));
+        } else if self.top.last().map(|i| i.1 < estimate).unwrap_or_default() {
+            *self.top.last_mut().unwrap() = (key.clone(), estimate);
+        } else {
+            return Ok(());
+        }
+
+        self.top.sort_by_key(|i| Reverse(i.1));
+        self.write_top()
+    }
+
+    /// Writes the current heavy hitters into the backend.
+    fn write_top(&mut self) -> Result<()> {
+        let data = serialize_impl(&self.top)?;
+        let mut entry = self.backend.entry_mut(TOP_ID).unwrap();
+        entry.clear();
+        entry.grow_to(data.len())?;
+        entry.push(&data)?;
+        Ok(())
+    }
+
+    #[inline]
+    fn sketch_mut(&mut self) -> CountMinSketch<MFileEntryMut<'_, B>> {
+        self.backend.get_backend_mut(SKETCH_ID).unwrap()
+    }
+}
+
+impl<B, K> TopK<B, K>
+    where
+        B: Backend,
+{
+    /// Returns the heavy hitters along with their estimated count, ordered by the count descending.
+    #[inline]
+    pub fn top(&self) -> &[(K, u64)] {
+        &self.top
+    }
+
+    /// Returns the estimated count of `key`.
+    #[inline]
+    pub fn estimate(&self, key: &K) -> Result<u64>
+        where
+            K: Hash,
+    {
+        self.sketch().estimate(key)
+    }
+
+    /// Returns the maximum amount of tracked heavy hitters.
+    #[inline]
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.backend.flush()
+    }
+
+    #[inline]
+    fn sketch(&self) -> CountMinSketch<GeneralSubBackend<'_>> {
+        self.backend.get_backend(SKETCH_ID).unwrap()
+    }
+}
+
+impl<B, K> Initiable<B> for TopK<B, K>
+    where
+        B: Backend,
+        K: DeserializeOwned,
+{
+    fn init(backend: B) -> Result<Self> {
+        let backend = MultiFile::init(backend)?;
+
+        let metadata = backend.get(METADATA_ID).ok_or(Error::Initialization)?;
+        let k = u32::from_le_bytes(metadata.get(0, 4)?.try_into().unwrap()) as usize;
+
+        let _: CountMinSketch<_> = backend
+            .get_backend(SKETCH_ID)
+            .ok_or(Error::Initialization)?;
+
+        let top_entry = backend.get(TOP_ID).ok_or(Error::Initialization)?;
+        let top: Vec<(K, u64)> = deserialize_impl(top_entry.get(0, top_entry.len())?)?;
+
+        Ok(Self { backend, k, top })
+    }
+}
+
+impl<B, K> MType for TopK<B, K>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.backend.raw_data()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_top(&mut mem_backend);
+        test_heavy_hitters(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./top_k", 10);
+        test_top(&mut mmap_backend);
+        test_heavy_hitters(&mut mmap_backend);
+    }
+
+    fn test_top<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        {
+            let mut top_k: TopK<_, String> = TopK::new(&mut backend, 2, 128, 4).unwrap();
+            assert!(top_k.top().is_empty());
+
+            top_k.insert(&"a".to_string()).unwrap();
+            top_k.add(&"b".to_string(), 3).unwrap();
+            assert_eq!(top_k.top(), &[("b".to_string(), 3), ("a".to_string(), 1)]);
+
+            top_k.add(&"c".to_string(), 2).unwrap();
+            assert_eq!(top_k.top(), &[("b".to_string(), 3), ("c".to_string(), 2)]);
+        }
+
+        let mut top_k: TopK<_, String> = TopK::init(&mut backend).unwrap();
+        assert_eq!(top_k.k(), 2);
+        assert_eq!(top_k.top(), &[("b".to_string(), 3), ("c".to_string(), 2)]);
+        assert_eq!(top_k.estimate(&"a".to_string()), Ok(1));
+
+        top_k.add(&"a".to_string(), 4).unwrap();
+        assert_eq!(top_k.top(), &[("a".to_string(), 5), ("b".to_string(), 3)]);
+    }
+
+    fn test_heavy_hitters<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut top_k: TopK<_, u64> = TopK::new(&mut backend, 5, 512, 4).unwrap();
+
+        // Items 0..5 occur far more often than all others.
+        for i in 0..10_000u64 {
+            let key = if i % 2 == 0 { i % 5 } else { 5 + i % 997 };
+            top_k.insert(&key).unwrap();
+        }
+
+        let mut keys: Vec<_> = top_k.top().iter().map(|i| i.0).collect();
+        keys.sort_unstable();
+        assert_eq!(keys, vec![0, 1, 2, 3, 4]);
+    }
+}
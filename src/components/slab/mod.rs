@@ -0,0 +1,359 @@
+use crate::backend::base::sub::GeneralSubBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::list::List;
+use crate::components::multi_file::entry_mut::MFileEntryMut;
+use crate::components::multi_file::MultiFile;
+use crate::deser::{deserialize_impl, serialize_impl};
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::{Error, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+/// ID of the entry holding the slabs metadata.
+const METADATA_ID: usize = 0;
+
+/// ID of the entry holding the IDs of vacant slots which get reused by the next insertions.
+const FREE_ID: usize = 1;
+
+/// ID of the entry holding the first slot.
+const SLOTS_START: usize = 2;
+
+/// First byte of an occupied slot. Vacant slots are empty, which allows items that encode to zero bytes.
+const OCCUPIED: u8 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct SlabMetadata {
+    len: u64,
+}
+
+/// Arena handing out stable IDs for its items. Unlike `IndexedFile`, IDs of removed items get reused by later
+/// insertions instead of leaving an unused entry behind. An ID stays valid until its item gets removed.
+///
+/// The internal format: MultiFile(METADATA | FREE | SLOT | SLOT | ...)
+/// where FREE is a `List<u64>` of the IDs of vacant slots, an occupied slot is (OCCUPIED (1 byte) | T) and a vacant
+/// slot is empty.
+pub struct Slab<B, T> {
+    storage: MultiFile<B>,
+    len: usize,
+    p: PhantomData<T>,
+}
+
+impl<B, T> Slab<B, T> {
+    /// Returns the amount of items in the slab.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the slab holds no items.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the amount of slots, including vacant ones. All IDs are smaller than this.
+    #[inline]
+    pub fn slots(&self) -> usize {
+        self.storage.count() - SLOTS_START
+    }
+}
+
+impl<B, T> Slab<B, T>
+    where
+        B: Backend,
+{
+    /// Returns the encoded item with the given ID.
+    pub fn get_raw(&self, id: usize) -> Option<&[u8]> {
+        let data = self.storage.get(SLOTS_START + id)?.content_data_borrowed();
+        (data.first() == Some(&OCCUPIED)).then(|| &data[1..])
+    }
+
+    /// Returns `true` if there is an item with the given ID.
+    #[inline]
+    pub fn contains(&self, id: usize) -> bool {
+        self.get_raw(id).is_some()
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.storage.flush()
+    }
+}
+
+impl<B, T> Slab<B, T>
+    where
+        B: Backend,
+        T: DeserializeOwned,
+{
+    /// Returns the item with the given ID.
+    #[inline]
+    pub fn get(&self, id: usize) -> Option<T> {
+        deserialize_impl(self.get_raw(id)?).ok()
+    }
+
+    /// Returns an iterator over all items along with their IDs in ascending order of the IDs.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, T)> + '_ {
+        (0..self.slots()).filter_map(|id| Some((id, self.get(id)?)))
+    }
+}
+
+impl<B, T> Slab<B, T>
+    where
+        B: GrowableBackend,
+{
+    /// Removes all items. IDs handed out before are invalid afterwards.
+    pub fn clear(&mut self) -> Result<()> {
+        self.storage.clear();
+        self.len = 0;
+        self.init_storage()
+    }
+
+    /// Inserts an already encoded item and returns its ID.
+    pub fn insert_raw(&mut self, data: &[u8]) -> Result<usize> {
+        let id = match self.pop_free()? {
+            Some(id) => id,
+            None => {
+                self.storage.insert_empty()?;
+                self.storage.count() - 1 - SLOTS_START
+            }
+        };
+        self.write_slot(id, data)?;
+        self.len += 1;
+        self.write_metadata()?;
+        Ok(id)
+    }
+
+    /// Replaces the encoded item with the given ID. Returns `Error::OutOfBounds` if there is no such item.
+    pub fn set_raw(&mut self, id: usize, data: &[u8]) -> Result<()> {
+        if !self.contains(id) {
+            return Err(Error::OutOfBounds);
+        }
+        self.write_slot(id, data)
+    }
+
+    /// Removes the item with the given ID, making the ID available for new items. Returns `false` if there was no such
+    /// item.
+    pub fn remove_raw(&mut self, id: usize) -> Result<bool> {
+        if !self.contains(id) {
+            return Ok(false);
+        }
+        self.storage.entry_mut(SLOTS_START + id).ok_or(Error::OutOfBounds)?.clear();
+        self.len -= 1;
+        self.free_mut().push(&(id as u64))?;
+        self.write_metadata()?;
+        Ok(true)
+    }
+
+    /// Inserts the metadata and an empty free list into the empty storage.
+    fn init_storage(&mut self) -> Result<()> {
+        self.storage.insert_empty()?;
+        self.storage.insert_new_backend::<List<_, u64, 8>>()?;
+        self.write_metadata()
+    }
+
+    /// Takes the most recently freed ID out of the free list.
+    fn pop_free(&mut self) -> Result<Option<usize>> {
+        let mut free = self.free_mut();
+        if free.is_empty() {
+            return Ok(None);
+        }
+        let id = free.get(free.len() - 1)?;
+        free.pop()?;
+        Ok(Some(id as usize))
+    }
+
+    #[inline]
+    fn free_mut(&mut self) -> List<MFileEntryMut<'_, B>, u64, 8> {
+        self.storage.get_backend_mut(FREE_ID).unwrap()
+    }
+
+    fn write_slot(&mut self, id: usize, data: &[u8]) -> Result<()> {
+        let mut slot = Vec::with_capacity(data.len() + 1);
+        slot.push(OCCUPIED);
+        slot.extend_from_slice(data);
+        self.storage.set_entry(SLOTS_START + id, &slot)
+    }
+
+    fn write_metadata(&mut self) -> Result<()> {
+        let metadata = SlabMetadata { len: self.len as u64 };
+        self.storage.set_entry(METADATA_ID, &serialize_impl(&metadata)?)
+    }
+}
+
+impl<B, T> Slab<B, T>
+    where
+        B: GrowableBackend,
+        T: Serialize,
+{
+    /// Inserts an item and returns its ID.
+    #[inline]
+    pub fn insert(&mut self, item: &T) -> Result<usize> {
+        self.insert_raw(&serialize_impl(item)?)
+    }
+
+    /// Replaces the item with the given ID. Returns `Error::OutOfBounds` if there is no such item.
+    #[inline]
+    pub fn set(&mut self, id: usize, item: &T) -> Result<()> {
+        self.set_raw(id, &serialize_impl(item)?)
+    }
+}
+
+impl<B, T> Slab<B, T>
+    where
+        B: GrowableBackend,
+        T: DeserializeOwned,
+{
+    /// Removes the item with the given ID and returns it, making the ID available for new items.
+    pub fn remove(&mut self, id: usize) -> Result<Option<T>> {
+        let Some(item) = self.get(id) else {
+            return Ok(None);
+        };
+        self.remove_raw(id)?;
+        Ok(Some(item))
+    }
+}
+
+impl<B, T> Creatable<B> for Slab<B, T>
+    where
+        B: GrowableBackend,
+{
+    fn with_capacity(backend: B, capacity: usize) -> Result<Self> {
+        let mut slab = Self {
+            storage: MultiFile::with_capacity(backend, capacity + SLOTS_START)?,
+            len: 0,
+            p: PhantomData,
+        };
+        slab.init_storage()?;
+        Ok(slab)
+    }
+}
+
+impl<B, T> Initiable<B> for Slab<B, T>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let storage = MultiFile::init(backend)?;
+        let metadata: SlabMetadata = storage
+            .get(METADATA_ID)
+            .ok_or(Error::Initialization)
+            .and_then(|i| deserialize_impl(i.content_data()))?;
+
+        let free: List<GeneralSubBackend<'_>, u64, 8> =
+            storage.get_backend(FREE_ID).ok_or(Error::Initialization)?;
+        let len = metadata.len as usize;
+        if len + free.len() != storage.count() - SLOTS_START {
+            return Err(Error::Initialization);
+        }
+
+        Ok(Self {
+            storage,
+            len,
+            p: PhantomData,
+        })
+    }
+}
+
+impl<B, T> MType for Slab<B, T>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.storage.raw_data()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_insert(&mut mem_backend);
+        test_remove(&mut mem_backend);
+        test_unit(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./slab", 10);
+        test_insert(&mut mmap_backend);
+        test_remove(&mut mmap_backend);
+        test_unit(&mut mmap_backend);
+    }
+
+    fn test_insert<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        {
+            let mut slab: Slab<_, String> = Slab::create(&mut backend).unwrap();
+            assert!(slab.is_empty());
+            assert_eq!(slab.get(0), None);
+            for i in 0..500 {
+                assert_eq!(slab.insert(&format!("item {i}")), Ok(i));
+            }
+            slab.set(7, &"seven".to_string()).unwrap();
+            assert_eq!(slab.set(500, &String::new()), Err(Error::OutOfBounds));
+        }
+
+        let slab: Slab<_, String> = Slab::init(&mut backend).unwrap();
+        assert_eq!(slab.len(), 500);
+        assert_eq!(slab.get(7).as_deref(), Some("seven"));
+        assert_eq!(slab.get(499), Some("item 499".to_string()));
+        assert_eq!(slab.get(500), None);
+        assert_eq!(slab.iter().count(), 500);
+    }
+
+    fn test_remove<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut expected = BTreeMap::new();
+        {
+            let mut slab: Slab<_, u64> = Slab::create(&mut backend).unwrap();
+            for i in 0..300u64 {
+                expected.insert(slab.insert(&(i * 3)).unwrap(), i * 3);
+            }
+            for id in (0..300).filter(|i| i % 4 != 0) {
+                assert_eq!(slab.remove(id), Ok(expected.remove(&id)));
+            }
+            assert_eq!(slab.remove(1), Ok(None));
+            assert!(!slab.contains(1));
+            assert_eq!(slab.len(), expected.len());
+        }
+
+        let mut slab: Slab<_, u64> = Slab::init(&mut backend).unwrap();
+        assert_eq!(slab.iter().collect::<BTreeMap<_, _>>(), expected);
+
+        // Vacant IDs get reused before new slots are added.
+        for i in 0..225u64 {
+            let id = slab.insert(&i).unwrap();
+            assert!(id < 300 && id % 4 != 0);
+            expected.insert(id, i);
+        }
+        assert_eq!(slab.insert(&1), Ok(300));
+        expected.insert(300, 1);
+        assert_eq!(slab.slots(), 301);
+        assert_eq!(slab.iter().collect::<BTreeMap<_, _>>(), expected);
+
+        slab.clear().unwrap();
+        assert!(slab.is_empty());
+        assert_eq!(slab.get(0), None);
+        assert_eq!(slab.insert(&5), Ok(0));
+    }
+
+    fn test_unit<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut slab: Slab<_, ()> = Slab::create(&mut backend).unwrap();
+        assert_eq!(slab.insert(&()), Ok(0));
+        assert_eq!(slab.insert(&()), Ok(1));
+        assert_eq!(slab.get(1), Some(()));
+        assert_eq!(slab.remove(0), Ok(Some(())));
+        assert_eq!(slab.get(0), None);
+        assert_eq!(slab.iter().collect::<Vec<_>>(), vec![(1, ())]);
+    }
+}
@@ -0,0 +1,255 @@
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::list::iter::ListIter;
+use crate::components::list::List;
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::Result;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::ops::{Bound, Range, RangeBounds};
+
+/// A `List` whose items are kept in ascending order, which allows looking items up using binary search. Can be used as
+/// a lightweight ordered index. Equal items are kept in insertion order.
+pub struct SortedList<B, T, const N: usize> {
+    list: List<B, T, N>,
+}
+
+impl<B, T, const N: usize> SortedList<B, T, N> {
+    /// Returns the amount of items in the list.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns `true` if the list is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Returns an iterator over all items in ascending order.
+    #[inline]
+    pub fn iter(&self) -> ListIter<'_, B, T, N> {
+        self.list.iter()
+    }
+
+    /// Returns the underlying list.
+    #[inline]
+    pub fn inner(&self) -> &List<B, T, N> {
+        &self.list
+    }
+}
+
+impl<B, T, const N: usize> SortedList<B, T, N>
+    where
+        B: Backend,
+{
+    /// Removes all items from the list, preserving the allocated space.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.list.clear();
+    }
+
+    /// Removes the item at `index`.
+    #[inline]
+    pub fn remove_at(&mut self, index: usize) -> Result<()> {
+        self.list.remove(index)
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.list.flush()
+    }
+}
+
+impl<B, T, const N: usize> SortedList<B, T, N>
+    where
+        B: Backend,
+        T: DeserializeOwned + Ord,
+{
+    /// Gets the item at `index`.
+    #[inline]
+    pub fn get(&self, index: usize) -> Result<T> {
+        self.list.get(index)
+    }
+
+    /// Searches `item` in the list. Returns the index of a matching item or the index where `item` could be inserted
+    /// while keeping the list sorted, same as `slice::binary_search`.
+    pub fn binary_search(&self, item: &T) -> std::result::Result<usize, usize> {
+        let index = self.partition_point(|i| i < item);
+        if index < self.len() && self.item(index) == *item {
+            return Ok(index);
+        }
+        Err(index)
+    }
+
+    /// Returns `true` if the list contains `item`.
+    #[inline]
+    pub fn contains(&self, item: &T) -> bool {
+        self.binary_search(item).is_ok()
+    }
+
+    /// Returns the range of indices of items within `range`.
+    pub fn range_indices<R: RangeBounds<T>>(&self, range: R) -> Range<usize> {
+        let start = match range.start_bound() {
+            Bound::Included(start) => self.partition_point(|i| i < start),
+            Bound::Excluded(start) => self.partition_point(|i| i <= start),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => self.partition_point(|i| i <= end),
+            Bound::Excluded(end) => self.partition_point(|i| i < end),
+            Bound::Unbounded => self.len(),
+        };
+        start..end.max(start)
+    }
+
+    /// Returns an iterator over all items within `range` in ascending order.
+    #[inline]
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> impl Iterator<Item = T> + '_ {
+        self.range_indices(range).map(|i| self.item(i))
+    }
+
+    /// Binary searches the first item for which `pred` returns `false`.
+    fn partition_point<P>(&self, mut pred: P) -> usize
+        where
+            P: FnMut(&T) -> bool,
+    {
+        let mut left = 0;
+        let mut right = self.len();
+        while left < right {
+            let mid = left + (right - left) / 2;
+            if pred(&self.item(mid)) {
+                left = mid + 1;
+            } else {
+                right = mid;
+            }
+        }
+
+        left
+    }
+
+    #[inline]
+    fn item(&self, index: usize) -> T {
+        self.list.get(index).expect("Failed to load item")
+    }
+}
+
+impl<B, T, const N: usize> SortedList<B, T, N>
+    where
+        B: GrowableBackend,
+        T: Serialize + DeserializeOwned + Ord,
+{
+    /// Inserts `item` at its sorted position, after all equal items, and returns its index.
+    pub fn insert(&mut self, item: &T) -> Result<usize> {
+        let index = self.partition_point(|i| i <= item);
+        self.list.insert(index, item)?;
+        Ok(index)
+    }
+
+    /// Removes one item equal to `item`. Returns `false` if there was no such item.
+    pub fn remove(&mut self, item: &T) -> Result<bool> {
+        match self.binary_search(item) {
+            Ok(index) => {
+                self.list.remove(index)?;
+                Ok(true)
+            }
+            Err(_) => Ok(false),
+        }
+    }
+}
+
+impl<B, T, const N: usize> Creatable<B> for SortedList<B, T, N>
+    where
+        B: GrowableBackend,
+{
+    #[inline]
+    fn with_capacity(backend: B, capacity: usize) -> Result<Self> {
+        Ok(Self {
+            list: List::with_capacity(backend, capacity)?,
+        })
+    }
+}
+
+impl<B, T, const N: usize> Initiable<B> for SortedList<B, T, N>
+    where
+        B: Backend,
+{
+    /// Loads a list from a backend. The items have to be sorted already.
+    #[inline]
+    fn init(backend: B) -> Result<Self> {
+        Ok(Self {
+            list: List::init(backend)?,
+        })
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut backend = make_mem_backend(1);
+        test_insert(&mut backend);
+        test_range(&mut backend);
+
+        let mut backend = make_mmap_backend("sorted_list", 1);
+        test_insert(&mut backend);
+        test_range(&mut backend);
+    }
+
+    fn test_insert<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        {
+            let mut list: SortedList<_, u32, 4> = SortedList::create(&mut backend).unwrap();
+            for i in (0..500).map(|i| (i * 7919) % 500) {
+                list.insert(&(i * 2)).unwrap();
+            }
+            assert_eq!(list.insert(&4), Ok(3));
+            assert_eq!(list.len(), 501);
+        }
+
+        let mut list: SortedList<_, u32, 4> = SortedList::init(&mut backend).unwrap();
+        let items: Vec<_> = list.iter().collect();
+        assert!(items.windows(2).all(|i| i[0] <= i[1]));
+
+        assert_eq!(list.binary_search(&0), Ok(0));
+        assert_eq!(list.binary_search(&10), Ok(6));
+        assert_eq!(list.binary_search(&11), Err(7));
+        assert_eq!(list.binary_search(&2000), Err(501));
+        assert!(list.contains(&998));
+        assert!(!list.contains(&999));
+
+        assert_eq!(list.remove(&4), Ok(true));
+        assert_eq!(list.remove(&4), Ok(true));
+        assert_eq!(list.remove(&4), Ok(false));
+        assert_eq!(list.get(2), Ok(6));
+        list.remove_at(0).unwrap();
+        assert_eq!(list.get(0), Ok(2));
+        assert_eq!(list.len(), 498);
+    }
+
+    fn test_range<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut list: SortedList<_, i32, 4> = SortedList::create(&mut backend).unwrap();
+        assert_eq!(list.binary_search(&1), Err(0));
+        assert_eq!(list.range(..).count(), 0);
+
+        for i in [5, -3, 8, 1, 5, 12, 0] {
+            list.insert(&i).unwrap();
+        }
+
+        assert_eq!(list.range(1..8).collect::<Vec<_>>(), vec![1, 5, 5]);
+        assert_eq!(list.range(1..=8).collect::<Vec<_>>(), vec![1, 5, 5, 8]);
+        assert_eq!(list.range(..0).collect::<Vec<_>>(), vec![-3]);
+        assert_eq!(list.range(6..).collect::<Vec<_>>(), vec![8, 12]);
+        assert_eq!(list.range(20..).count(), 0);
+        assert_eq!(list.range_indices(5..=5), 3..5);
+        assert_eq!(list.range_indices((Bound::Included(9), Bound::Excluded(2))), 6..6);
+    }
+}
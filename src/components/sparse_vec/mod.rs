@@ -0,0 +1,323 @@
+use crate::backend::base::sub::GeneralSubBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::list::List;
+use crate::components::multi_file::entry_mut::MFileEntryMut;
+use crate::components::multi_file::MultiFile;
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::{Error, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::marker::PhantomData;
+
+/// ID of the entry holding the logical length.
+const LEN_ID: usize = 0;
+
+/// ID of the entry holding the sorted indices of all stored items.
+const INDICES_ID: usize = 1;
+
+/// ID of the entry holding the values of all stored items.
+const VALUES_ID: usize = 2;
+
+/// A vector of `len` items where only items that differ from `T::default()` are stored. Stored items are kept sorted
+/// by their index, so lookups are a binary search over the stored indices.
+///
+/// The internal format: MultiFile(LEN (u64 LE) | INDICES | VALUES)
+/// where INDICES is a `List<u64>` and VALUES a `List<T>` holding the value of the index at the same position.
+pub struct SparseVec<B, T, const N: usize> {
+    storage: MultiFile<B>,
+    len: usize,
+    p: PhantomData<T>,
+}
+
+impl<B, T, const N: usize> SparseVec<B, T, N> {
+    /// Returns the logical length of the vector.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the logical length of the vector is 0.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<B, T, const N: usize> SparseVec<B, T, N>
+    where
+        B: GrowableBackend,
+        T: Serialize + DeserializeOwned + Default + PartialEq,
+{
+    /// Creates a new vector of `len` default items.
+    pub fn new(backend: B, len: usize) -> Result<Self> {
+        let mut storage = MultiFile::with_capacity(backend, 3)?;
+        let mut len_entry = storage.insert_empty()?;
+        len_entry.grow_to(8)?;
+        len_entry.push(&(len as u64).to_le_bytes())?;
+        storage.insert_new_backend::<List<_, u64, 8>>()?;
+        storage.insert_new_backend::<List<_, T, N>>()?;
+
+        Ok(Self {
+            storage,
+            len,
+            p: PhantomData,
+        })
+    }
+
+    /// Sets the item at `index`. Setting an item to `T::default()` removes it from the storage.
+    pub fn set(&mut self, index: usize, item: &T) -> Result<()> {
+        if index >= self.len {
+            return Err(Error::OutOfBounds);
+        }
+
+        if *item == T::default() {
+            self.remove(index)?;
+            return Ok(());
+        }
+
+        match self.position(index) {
+            Ok(pos) => self.values_mut().set(pos, item),
+            Err(pos) => {
+                self.indices_mut().insert(pos, &(index as u64))?;
+                self.values_mut().insert(pos, item)
+            }
+        }
+    }
+
+    /// Resets the item at `index` to `T::default()` and returns its previous value if it was stored.
+    pub fn remove(&mut self, index: usize) -> Result<Option<T>> {
+        let Ok(pos) = self.position(index) else {
+            return Ok(None);
+        };
+        let item = self.values().get(pos)?;
+        self.indices_mut().remove(pos)?;
+        self.values_mut().remove(pos)?;
+        Ok(Some(item))
+    }
+
+    /// Changes the logical length of the vector. Stored items beyond the new length get removed.
+    pub fn resize(&mut self, len: usize) -> Result<()> {
+        let keep = self.position(len).unwrap_or_else(|i| i);
+        let stored = self.stored();
+        for _ in keep..stored {
+            self.indices_mut().pop()?;
+            self.values_mut().pop()?;
+        }
+        self.set_len(len)
+    }
+
+    /// Removes all stored items and sets the logical length to 0.
+    pub fn clear(&mut self) -> Result<()> {
+        self.indices_mut().clear();
+        self.values_mut().clear();
+        self.set_len(0)
+    }
+
+    fn set_len(&mut self, len: usize) -> Result<()> {
+        let mut entry = self.storage.get_mut(LEN_ID).unwrap();
+        entry.replace_same_len(0, &(len as u64).to_le_bytes())?;
+        self.len = len;
+        Ok(())
+    }
+
+    #[inline]
+    fn indices_mut(&mut self) -> List<MFileEntryMut<'_, B>, u64, 8> {
+        self.storage.get_backend_mut(INDICES_ID).unwrap()
+    }
+
+    #[inline]
+    fn values_mut(&mut self) -> List<MFileEntryMut<'_, B>, T, N> {
+        self.storage.get_backend_mut(VALUES_ID).unwrap()
+    }
+}
+
+impl<B, T, const N: usize> SparseVec<B, T, N>
+    where
+        B: Backend,
+        T: DeserializeOwned + Default,
+{
+    /// Returns the item at `index`, which is `T::default()` if it isn't stored.
+    pub fn get(&self, index: usize) -> Result<T> {
+        if index >= self.len {
+            return Err(Error::OutOfBounds);
+        }
+        match self.position(index) {
+            Ok(pos) => self.values().get(pos),
+            Err(_) => Ok(T::default()),
+        }
+    }
+
+    /// Returns `true` if the item at `index` is stored, which means it differs from `T::default()`.
+    #[inline]
+    pub fn is_set(&self, index: usize) -> bool {
+        self.position(index).is_ok()
+    }
+
+    /// Returns the amount of stored items.
+    #[inline]
+    pub fn stored(&self) -> usize {
+        self.indices().len()
+    }
+
+    /// Returns an iterator over all stored items along with their index in ascending order of their index.
+    pub fn iter_stored(&self) -> impl Iterator<Item = (usize, T)> + '_ {
+        let indices = self.indices();
+        let values = self.values();
+        (0..indices.len()).map(move |i| {
+            let index = indices.get(i).expect("Failed to load index") as usize;
+            (index, values.get(i).expect("Failed to load value"))
+        })
+    }
+
+    /// Returns an iterator over all items including the default ones.
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        let mut stored = self.iter_stored().peekable();
+        (0..self.len).map(move |i| match stored.next_if(|s| s.0 == i) {
+            Some((_, item)) => item,
+            None => T::default(),
+        })
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.storage.flush()
+    }
+
+    /// Binary searches the position of `index` within the stored items. Returns the position where it could be
+    /// inserted if it isn't stored, same as `slice::binary_search`.
+    fn position(&self, index: usize) -> std::result::Result<usize, usize> {
+        let indices = self.indices();
+        let index = index as u64;
+        let (mut left, mut right) = (0, indices.len());
+        while left < right {
+            let mid = left + (right - left) / 2;
+            let current = indices.get(mid).expect("Failed to load index");
+            match current.cmp(&index) {
+                std::cmp::Ordering::Less => left = mid + 1,
+                std::cmp::Ordering::Greater => right = mid,
+                std::cmp::Ordering::Equal => return Ok(mid),
+            }
+        }
+        Err(left)
+    }
+
+    #[inline]
+    fn indices(&self) -> List<GeneralSubBackend<'_>, u64, 8> {
+        self.storage.get_backend(INDICES_ID).unwrap()
+    }
+
+    #[inline]
+    fn values(&self) -> List<GeneralSubBackend<'_>, T, N> {
+        self.storage.get_backend(VALUES_ID).unwrap()
+    }
+}
+
+impl<B, T, const N: usize> Initiable<B> for SparseVec<B, T, N>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let storage = MultiFile::init(backend)?;
+        let len_entry = storage.get(LEN_ID).ok_or(Error::Initialization)?;
+        let len = u64::from_le_bytes(len_entry.get(0, 8)?.try_into().unwrap()) as usize;
+
+        let indices: List<GeneralSubBackend<'_>, u64, 8> =
+            storage.get_backend(INDICES_ID).ok_or(Error::Initialization)?;
+        let values: List<GeneralSubBackend<'_>, T, N> =
+            storage.get_backend(VALUES_ID).ok_or(Error::Initialization)?;
+        if indices.len() != values.len() {
+            return Err(Error::Initialization);
+        }
+
+        Ok(Self {
+            storage,
+            len,
+            p: PhantomData,
+        })
+    }
+}
+
+impl<B, T, const N: usize> MType for SparseVec<B, T, N>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.storage.raw_data()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_sparse(&mut mem_backend);
+        test_resize(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./sparse_vec", 10);
+        test_sparse(&mut mmap_backend);
+        test_resize(&mut mmap_backend);
+    }
+
+    fn test_sparse<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let len = 1_000_000_000;
+        {
+            let mut vec: SparseVec<_, u32, 4> = SparseVec::new(&mut backend, len).unwrap();
+            for i in (0..500u32).rev() {
+                vec.set(i as usize * 1_000_003, &(i + 1)).unwrap();
+            }
+            assert_eq!(vec.set(len, &1), Err(Error::OutOfBounds));
+        }
+
+        let mut vec: SparseVec<_, u32, 4> = SparseVec::init(&mut backend).unwrap();
+        assert_eq!(vec.len(), len);
+        assert_eq!(vec.stored(), 500);
+        assert_eq!(vec.get(1_000_003 * 7), Ok(8));
+        assert_eq!(vec.get(1), Ok(0));
+        assert!(vec.is_set(0));
+        assert!(!vec.is_set(1));
+        assert!(vec.get(len).is_err());
+
+        let stored: Vec<_> = vec.iter_stored().collect();
+        let expected: Vec<_> = (0..500u32).map(|i| (i as usize * 1_000_003, i + 1)).collect();
+        assert_eq!(stored, expected);
+
+        // Setting the default value removes the item.
+        vec.set(0, &0).unwrap();
+        assert_eq!(vec.remove(1_000_003), Ok(Some(2)));
+        assert_eq!(vec.remove(1_000_003), Ok(None));
+        assert_eq!(vec.stored(), 498);
+        vec.set(5, &9).unwrap();
+        vec.set(5, &10).unwrap();
+        assert_eq!(vec.get(5), Ok(10));
+        assert_eq!(vec.stored(), 499);
+    }
+
+    fn test_resize<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut vec: SparseVec<_, u32, 4> = SparseVec::new(&mut backend, 10).unwrap();
+        vec.set(2, &1).unwrap();
+        vec.set(8, &2).unwrap();
+        assert_eq!(vec.iter().collect::<Vec<_>>(), vec![0, 0, 1, 0, 0, 0, 0, 0, 2, 0]);
+
+        vec.resize(5).unwrap();
+        assert_eq!(vec.len(), 5);
+        assert_eq!(vec.stored(), 1);
+        vec.resize(20).unwrap();
+        assert_eq!(vec.get(8), Ok(0));
+
+        vec.clear().unwrap();
+        assert!(vec.is_empty());
+        assert_eq!(vec.stored(), 0);
+    }
+}
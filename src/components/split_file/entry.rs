@@ -1,9 +1,11 @@
 use crate::backend::growable::GrowableBackend;
+use crate::backend::growth::GrowthPolicy;
 use crate::backend::Backend;
 use crate::components::split_file::backend_index::BackendIndex;
 use crate::components::split_file::SplitFile;
 use crate::header::BaseHeader;
 use crate::Error;
+use std::ops::Range;
 
 /// A backend in a `SplitFile` (either the first or the second one)
 pub struct Entry<'a, B> {
@@ -32,6 +34,11 @@ impl<'a, B> Backend for Entry<'a, B>
         self.sfile.backend_data_mut(self.index)
     }
 
+    #[inline]
+    fn data_mut_range(&mut self, range: Range<usize>) -> &mut [u8] {
+        self.sfile.backend_data_mut_range(self.index, range)
+    }
+
     fn first_index(&self) -> usize {
         BaseHeader::len_bytes()
     }
@@ -43,7 +50,7 @@ impl<'a, B> Backend for Entry<'a, B>
 
     #[inline]
     fn set_len(&mut self, len: usize) -> Result<(), Error> {
-        self.sfile.get_backend_mut(self.index).set_len(len)
+        self.sfile.set_backend_len(self.index, len)
     }
 
     #[inline]
@@ -69,4 +76,9 @@ impl<'a, B> GrowableBackend for Entry<'a, B>
     fn resize(&mut self, delta: isize) -> crate::Result<()> {
         self.sfile.resize(self.index, delta)
     }
+
+    #[inline]
+    fn growth_policy(&self) -> &dyn GrowthPolicy {
+        self.sfile.growth_policy()
+    }
 }
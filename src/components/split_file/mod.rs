@@ -5,6 +5,7 @@ mod header;
 use crate::backend::base::sub::BaseSubBackend;
 use crate::backend::base::sub_mut::{BaseSubMutBackend, GeneralSubMutBackend};
 use crate::backend::growable::GrowableBackend;
+use crate::backend::growth::GrowthPolicy;
 use crate::backend::Backend;
 use crate::components::header_file::CustomHeaderFile;
 use crate::components::split_file::entry::Entry;
@@ -47,6 +48,12 @@ impl<B> SplitFile<B>
     where
         B: GrowableBackend,
 {
+    /// Returns the growth policy of the underlying backend.
+    #[inline]
+    pub fn growth_policy(&self) -> &dyn GrowthPolicy {
+        self.backend.growth_policy()
+    }
+
     pub fn create_with_init_cap(mut backend: B, init_cap: usize) -> Result<Self> {
         let need_total_capacity = 4 + 8 + (init_cap + BaseHeader::len_bytes()) * 2;
         if backend.capacity() < need_total_capacity {
@@ -134,10 +141,40 @@ impl<B> SplitFile<B>
         Ok(())
     }
 
+    /// Rolls back all writes done since the last call of `begin_undo()`.
+    pub(crate) fn rollback_undo(&mut self) -> Result<()> {
+        self.backend.rollback_undo()?;
+        self.reload()
+    }
+
+    /// Shrinks both backends so that they don't have any free space left and truncates the remaining free bytes
+    /// of the underlying backend.
     pub fn shrink_to_fit(&mut self) -> Result<()> {
+        let first_free = self.first().free();
+        if first_free > 0 {
+            self.shrink(BackendIndex::First, first_free)?;
+        }
+
+        let second_free = self.second().free();
+        if second_free > 0 {
+            self.truncate_second(second_free)?;
+        }
+
         self.backend.shrink_to_fit()
     }
 
+    /// Shrinks the second backend by `delta` bytes of its free space. The second backend always spans until the end
+    /// of the wrapping backend so the free space has to be cut off from the wrapping backends length before it can
+    /// be shrunk.
+    pub(crate) fn truncate_second(&mut self, delta: usize) -> Result<()> {
+        if delta > self.second().free() {
+            return Err(Error::OutOfBounds);
+        }
+
+        self.backend.set_len(self.backend.len() - delta)?;
+        self.backend.shrink(delta)
+    }
+
     pub fn grow_both(&mut self, first_size: usize, second_size: usize) -> Result<()> {
         // println!("Grow both");
 
@@ -165,9 +202,42 @@ impl<'b, B> SplitFile<B>
     where
         B: Backend + 'b,
 {
+    /// Returns the underlying backend.
+    #[inline]
+    pub(crate) fn inner(&self) -> &B {
+        self.backend.backend()
+    }
+
+    /// Starts recording all writes so they can be rolled back with `rollback_undo()`.
+    #[inline]
+    pub(crate) fn begin_undo(&mut self) {
+        self.backend.begin_undo();
+    }
+
+    /// Keeps all writes done since the last call of `begin_undo()`.
+    #[inline]
+    pub(crate) fn commit_undo(&mut self) {
+        self.backend.commit_undo();
+    }
+
     /// Initializes a `SplitFile` for a given backend that already contains a created SplitFile.
     pub fn init(backend: B) -> Result<Self> {
         let chf: CustomHeaderFile<B, SplitFileHeader> = CustomHeaderFile::init(backend)?;
+        let header = Self::read_headers(&chf)?;
+        Ok(Self {
+            backend: chf,
+            header,
+        })
+    }
+
+    /// Reads all headers again from the backend.
+    pub(crate) fn reload(&mut self) -> Result<()> {
+        self.backend.reload()?;
+        self.header = Self::read_headers(&self.backend)?;
+        Ok(())
+    }
+
+    fn read_headers(chf: &CustomHeaderFile<B, SplitFileHeader>) -> Result<[BaseHeader; 2]> {
         let split_pos = chf.header().split_pos();
 
         let first_header = chf.get(0, BaseHeader::len_bytes())?;
@@ -176,10 +246,7 @@ impl<'b, B> SplitFile<B>
         let second_header = chf.get(split_pos, BaseHeader::len_bytes())?;
         let second_header = BaseHeader::from_bytes(second_header.try_into().unwrap());
 
-        Ok(Self {
-            backend: chf,
-            header: [first_header, second_header],
-        })
+        Ok([first_header, second_header])
     }
 
     /// Returns the first backend.
@@ -220,7 +287,7 @@ impl<'b, B> SplitFile<B>
 
         let (hf, hs) = self.header.split_at_mut(1);
 
-        let (fd, sd) = self.backend.data_mut()[fstart..send].split_at_mut(fend - fstart);
+        let (fd, sd) = self.backend.data_mut_range(fstart..send).split_at_mut(fend - fstart);
 
         let first = BaseSubMutBackend::new(fd, &mut hf[0]);
         let second = BaseSubMutBackend::new(sd, &mut hs[0]);
@@ -232,7 +299,7 @@ impl<'b, B> SplitFile<B>
     pub fn get_backend_mut(&mut self, index: BackendIndex) -> GeneralSubMutBackend {
         let be_range = self.backend_range(index);
         let header = &mut self.header[index as usize - 1];
-        let data = &mut self.backend.data_mut()[be_range];
+        let data = self.backend.data_mut_range(be_range);
         GeneralSubMutBackend::new(data, header)
     }
 
@@ -260,7 +327,29 @@ impl<'b, B> SplitFile<B>
     #[inline]
     pub fn backend_data_mut(&mut self, index: BackendIndex) -> &mut [u8] {
         let be_range = self.backend_range(index);
-        &mut self.backend.data_mut()[be_range]
+        self.backend.data_mut_range(be_range)
+    }
+
+    /// Returns the raw backend data within `range` for the given Backend mutable.
+    #[inline]
+    pub fn backend_data_mut_range(&mut self, index: BackendIndex, range: Range<usize>) -> &mut [u8] {
+        let be_range = self.backend_range(index);
+        assert!(range.start <= range.end && range.end <= be_range.len(), "range out of bounds");
+        let start = be_range.start + range.start;
+        self.backend.data_mut_range(start..start + range.len())
+    }
+
+    /// Sets the length of the given backend by only writing its header.
+    pub(crate) fn set_backend_len(&mut self, index: BackendIndex, len: usize) -> Result<()> {
+        if len > self.backend_range(index).len() {
+            return Err(Error::OutOfBounds);
+        }
+
+        let header = &mut self.header[index as usize - 1];
+        header.set_data_len(len);
+        let bytes = header.bytes();
+        self.backend_data_mut_range(index, 0..BaseHeader::len_bytes()).copy_from_slice(&bytes);
+        Ok(())
     }
 
     /// Flushes the given backend.
@@ -322,6 +411,15 @@ impl<B> SplitFile<B> {
     }
 }
 
+impl<'a> SplitFile<BaseSubBackend<'a, &'a [u8]>> {
+    /// Same as `backend_data` but the returned data borrows the storage of the underlying sub-backend instead of
+    /// `self`.
+    #[inline]
+    pub fn backend_data_borrowed(&self, index: BackendIndex) -> &'a [u8] {
+        &self.backend.backend.data_borrowed()[self.backend_range(index)]
+    }
+}
+
 impl<B> MType for SplitFile<B>
     where
         B: Backend,
@@ -333,6 +431,7 @@ impl<B> MType for SplitFile<B>
 }
 
 #[cfg(test)]
+#[cfg(feature = "mmap")]
 mod test {
     use super::*;
     use crate::backend::memory::test::make_mem_backend;
@@ -367,8 +466,12 @@ mod test {
         test_init(&mut mem);
         test_both_mut(&mut mem);
 
+        let mut mem = make_mem_backend(8);
+        test_shrink_to_fit(&mut mem);
+
         let mut file = make_mmap_backend("./growme", 8);
         test_shrink(&mut file);
+        test_shrink_to_fit(&mut file);
 
         let mut file = make_mmap_backend("./growme2", 8);
         test_small_grow(&mut file);
@@ -556,4 +659,22 @@ mod test {
         assert_eq!(sf.shrink(BackendIndex::First, 1), Err(Error::OutOfBounds));
         assert_eq!(sf.shrink(BackendIndex::Second, 1), Err(Error::OutOfBounds));
     }
+
+    fn test_shrink_to_fit<B: GrowableBackend>(be: &mut B) {
+        be.clear();
+        let mut sf = SplitFile::create_with_init_cap(be, 100).unwrap();
+        sf.first_mut().push(&[1, 2, 3]).unwrap();
+        sf.second_mut().push(&[9, 8]).unwrap();
+
+        sf.shrink_to_fit().unwrap();
+        assert_eq!(sf.first().capacity(), 3);
+        assert_eq!(sf.second().capacity(), 2);
+        assert_eq!(sf.first().get(0, 3), Ok(&[1, 2, 3][..]));
+        assert_eq!(sf.second().get(0, 2), Ok(&[9, 8][..]));
+
+        sf.grow(BackendIndex::Second, 1).unwrap();
+        sf.second_mut().push(&[7]).unwrap();
+        assert_eq!(sf.second().get(0, 3), Ok(&[9, 8, 7][..]));
+        assert_eq!(sf.first().get(0, 3), Ok(&[1, 2, 3][..]));
+    }
 }
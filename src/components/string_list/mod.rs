@@ -0,0 +1,399 @@
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::indexed_file::IndexedFile;
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::traits::varint;
+use crate::{Error, Result};
+use std::cmp::Ordering;
+
+/// ID of the entry holding the metadata (len, bucket size).
+const METADATA_ID: usize = 0;
+
+/// ID of the entry holding the first bucket.
+const BUCKETS_START: usize = 1;
+
+/// Size of the metadata: len u64 | bucket size u32
+const METADATA_LEN: usize = 12;
+
+/// Default amount of strings stored in a single bucket.
+pub const DEFAULT_BUCKET_SIZE: usize = 16;
+
+/// A list of sorted strings using front coding. Strings are grouped into buckets where the first string of each bucket
+/// is stored in full and every following string only stores the suffix that differs from its predecessor. Lookups
+/// binary search over the first strings of the buckets and decode a single bucket only.
+///
+/// The internal format: IndexedFile(METADATA | BUCKET | BUCKET | ...)
+/// where each bucket is encoded as (LEN | STRING | PREFIX LEN | SUFFIX LEN | SUFFIX | ...) using varints.
+pub struct StringList<B> {
+    storage: IndexedFile<B>,
+    len: usize,
+    bucket_size: usize,
+    last: Vec<u8>,
+}
+
+impl<B> StringList<B>
+    where
+        B: GrowableBackend,
+{
+    /// Creates a new empty string list using the default bucket size.
+    #[inline]
+    pub fn new(backend: B) -> Result<Self> {
+        Self::with_bucket_size(backend, DEFAULT_BUCKET_SIZE)
+    }
+
+    /// Creates a new empty string list storing `bucket_size` strings per bucket. Bigger buckets compress better but
+    /// make lookups slower. Returns `Error::UnexpectedValue` if `bucket_size` is 0.
+    pub fn with_bucket_size(backend: B, bucket_size: usize) -> Result<Self> {
+        if bucket_size == 0 || bucket_size > u32::MAX as usize {
+            return Err(Error::UnexpectedValue);
+        }
+
+        let mut storage = IndexedFile::create(backend)?;
+        storage.insert(&encode_metadata(0, bucket_size))?;
+
+        Ok(Self {
+            storage,
+            len: 0,
+            bucket_size,
+            last: vec![],
+        })
+    }
+
+    /// Appends a string and returns its index. Strings have to be pushed in ascending order, otherwise
+    /// `Error::UnexpectedValue` gets returned.
+    pub fn push(&mut self, s: &str) -> Result<usize> {
+        let s = s.as_bytes();
+        if !self.is_empty() && s < self.last.as_slice() {
+            return Err(Error::UnexpectedValue);
+        }
+
+        let mut data = Vec::with_capacity(s.len() + 10);
+        if self.len.is_multiple_of(self.bucket_size) {
+            push_varint(&mut data, s.len());
+            data.extend_from_slice(s);
+            self.storage.insert(&data)?;
+        } else {
+            let prefix = common_prefix(&self.last, s);
+            push_varint(&mut data, prefix);
+            push_varint(&mut data, s.len() - prefix);
+            data.extend_from_slice(&s[prefix..]);
+            let bucket = self.storage.count() - 1;
+            self.storage.grow_entry_with_data(bucket, &data)?;
+        }
+
+        self.len += 1;
+        let metadata = encode_metadata(self.len, self.bucket_size);
+        self.storage.get_mut(METADATA_ID)?.copy_from_slice(&metadata);
+        self.last.clear();
+        self.last.extend_from_slice(s);
+        Ok(self.len - 1)
+    }
+
+    /// Removes all strings.
+    pub fn clear(&mut self) -> Result<()> {
+        self.storage.clear();
+        self.storage.insert(&encode_metadata(0, self.bucket_size))?;
+        self.len = 0;
+        self.last.clear();
+        Ok(())
+    }
+}
+
+impl<B> StringList<B>
+    where
+        B: Backend,
+{
+    /// Returns the amount of strings.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the list doesn't contain any strings.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the amount of strings stored per bucket.
+    #[inline]
+    pub fn bucket_size(&self) -> usize {
+        self.bucket_size
+    }
+
+    /// Returns the string at `index`.
+    pub fn get(&self, index: usize) -> Result<String> {
+        if index >= self.len {
+            return Err(Error::OutOfBounds);
+        }
+        let s = self
+            .bucket(index / self.bucket_size)
+            .nth(index % self.bucket_size)
+            .ok_or(Error::OutOfBounds)?;
+        String::from_utf8(s).map_err(|_| Error::UnexpectedValue)
+    }
+
+    /// Returns the index of `s` or `None` if the list doesn't contain it.
+    pub fn find(&self, s: &str) -> Option<usize> {
+        let s = s.as_bytes();
+        let buckets = self.storage.count() - BUCKETS_START;
+
+        // Find the last bucket whose first string isn't bigger than `s`.
+        let (mut lo, mut hi) = (0, buckets);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if self.bucket_head(mid) <= s {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        let bucket = lo.checked_sub(1)?;
+
+        for (i, item) in self.bucket(bucket).enumerate() {
+            match item.as_slice().cmp(s) {
+                Ordering::Less => continue,
+                Ordering::Equal => return Some(bucket * self.bucket_size + i),
+                Ordering::Greater => return None,
+            }
+        }
+        None
+    }
+
+    /// Returns `true` if the list contains `s`.
+    #[inline]
+    pub fn contains(&self, s: &str) -> bool {
+        self.find(s).is_some()
+    }
+
+    /// Returns an iterator over all strings in ascending order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = String> + '_ {
+        (0..self.storage.count() - BUCKETS_START)
+            .flat_map(|i| self.bucket(i))
+            .map(|i| String::from_utf8(i).expect("Invalid string"))
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.storage.flush()
+    }
+
+    /// Returns the first string of a bucket without decoding the rest of it.
+    #[inline]
+    fn bucket_head(&self, bucket: usize) -> &[u8] {
+        let data = self.storage.get(BUCKETS_START + bucket).unwrap();
+        let (len, width) = read_varint(data);
+        &data[width..width + len]
+    }
+
+    /// Returns an iterator decoding the strings of a bucket.
+    #[inline]
+    fn bucket(&self, bucket: usize) -> BucketIter<'_> {
+        BucketIter {
+            data: self.storage.get(BUCKETS_START + bucket).unwrap(),
+            current: vec![],
+            first: true,
+        }
+    }
+}
+
+impl<B> Extend<String> for StringList<B>
+    where
+        B: GrowableBackend,
+{
+    #[inline]
+    fn extend<I: IntoIterator<Item = String>>(&mut self, iter: I) {
+        for i in iter {
+            self.push(&i).expect("Failed to push");
+        }
+    }
+}
+
+impl<'a, B> Extend<&'a str> for StringList<B>
+    where
+        B: GrowableBackend,
+{
+    #[inline]
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        for i in iter {
+            self.push(i).expect("Failed to push");
+        }
+    }
+}
+
+impl<B> Initiable<B> for StringList<B>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let storage = IndexedFile::init(backend)?;
+        let metadata = storage.get(METADATA_ID).map_err(|_| Error::Initialization)?;
+        if metadata.len() != METADATA_LEN {
+            return Err(Error::Initialization);
+        }
+        let len = u64::from_le_bytes(metadata[..8].try_into().unwrap()) as usize;
+        let bucket_size = u32::from_le_bytes(metadata[8..].try_into().unwrap()) as usize;
+        if bucket_size == 0 || storage.count() - BUCKETS_START != len.div_ceil(bucket_size) {
+            return Err(Error::Initialization);
+        }
+
+        let mut list = Self {
+            storage,
+            len,
+            bucket_size,
+            last: vec![],
+        };
+        if len > 0 {
+            let buckets = list.storage.count() - BUCKETS_START;
+            list.last = list.bucket(buckets - 1).last().ok_or(Error::Initialization)?;
+        }
+        Ok(list)
+    }
+}
+
+impl<B> MType for StringList<B>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.storage.raw_data()
+    }
+}
+
+/// Iterator decoding the front coded strings of a single bucket.
+struct BucketIter<'a> {
+    data: &'a [u8],
+    current: Vec<u8>,
+    first: bool,
+}
+
+impl Iterator for BucketIter<'_> {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+
+        let prefix = if self.first {
+            self.first = false;
+            0
+        } else {
+            let (prefix, width) = read_varint(self.data);
+            self.data = &self.data[width..];
+            prefix
+        };
+        let (suffix, width) = read_varint(self.data);
+        self.current.truncate(prefix);
+        self.current.extend_from_slice(&self.data[width..width + suffix]);
+        self.data = &self.data[width + suffix..];
+        Some(self.current.clone())
+    }
+}
+
+#[inline]
+fn encode_metadata(len: usize, bucket_size: usize) -> [u8; METADATA_LEN] {
+    let mut metadata = [0; METADATA_LEN];
+    metadata[..8].copy_from_slice(&(len as u64).to_le_bytes());
+    metadata[8..].copy_from_slice(&(bucket_size as u32).to_le_bytes());
+    metadata
+}
+
+/// Returns the length of the common prefix of `a` and `b`.
+#[inline]
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+#[inline]
+fn push_varint(out: &mut Vec<u8>, value: usize) {
+    let (data, len) = varint::encode(value as u64);
+    out.extend_from_slice(&data[..len as usize]);
+}
+
+/// Decodes a varint at the beginning of `data` and returns it along with its encoded width.
+#[inline]
+fn read_varint(data: &[u8]) -> (usize, usize) {
+    let (value, width) = varint::decode::<u64>(data).expect("Invalid varint");
+    (value as usize, width)
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_list(&mut mem_backend);
+        test_edge_cases(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./string_list", 10);
+        test_list(&mut mmap_backend);
+        test_edge_cases(&mut mmap_backend);
+    }
+
+    fn words(count: usize) -> Vec<String> {
+        let mut words: Vec<_> = (0..count).map(|i| format!("word_{}_{}", i % 97, i * 31)).collect();
+        words.sort_unstable();
+        words.dedup();
+        words
+    }
+
+    fn test_list<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let words = words(3000);
+        {
+            let mut list = StringList::new(&mut backend).unwrap();
+            list.extend(words.iter().take(1000).cloned());
+        }
+
+        // Keep appending after reopening.
+        let mut list = StringList::init(&mut backend).unwrap();
+        assert_eq!(list.len(), 1000);
+        list.extend(words.iter().skip(1000).cloned());
+        assert_eq!(list.push("a"), Err(Error::UnexpectedValue));
+
+        let raw_len: usize = words.iter().map(|i| i.len()).sum();
+        assert!(list.storage.get_range(0..list.storage.count()).unwrap().0.len() < raw_len);
+
+        assert_eq!(list.len(), words.len());
+        for (i, word) in words.iter().enumerate() {
+            assert_eq!(list.get(i).as_ref(), Ok(word));
+            assert_eq!(list.find(word), Some(i));
+        }
+        assert_eq!(list.iter().collect::<Vec<_>>(), words);
+        assert_eq!(list.get(words.len()), Err(Error::OutOfBounds));
+        assert!(!list.contains("a"));
+        assert!(!list.contains("word_1_"));
+        assert!(!list.contains("zzz"));
+    }
+
+    fn test_edge_cases<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        assert!(StringList::with_bucket_size(&mut backend, 0).is_err());
+
+        backend.clear();
+        let mut list = StringList::with_bucket_size(&mut backend, 2).unwrap();
+        assert!(list.is_empty());
+        assert_eq!(list.find(""), None);
+        list.extend(["", "", "a", "ab", "abc", "b"]);
+        assert_eq!(list.get(1), Ok(String::new()));
+        assert_eq!(list.get(4), Ok("abc".to_string()));
+        assert_eq!(list.find("ab"), Some(3));
+        assert_eq!(list.find("b"), Some(5));
+        assert!(list.find("").is_some());
+
+        list.clear().unwrap();
+        assert!(list.is_empty());
+        assert_eq!(list.push("z"), Ok(0));
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec!["z"]);
+    }
+}
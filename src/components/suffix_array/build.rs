@@ -0,0 +1,68 @@
+/// Builds the suffix array of `text` using prefix doubling.
+pub(super) fn suffix_array(text: &[u8]) -> Vec<u32> {
+    let n = text.len();
+    let mut sa: Vec<u32> = (0..n as u32).collect();
+    if n <= 1 {
+        return sa;
+    }
+
+    let mut rank: Vec<usize> = text.iter().map(|i| *i as usize).collect();
+    let mut tmp = vec![0usize; n];
+
+    let mut k = 1;
+    loop {
+        // Sort by the rank of the first k characters and the rank of the following k characters.
+        let key = |i: u32| {
+            let i = i as usize;
+            let second = if i + k < n { rank[i + k] + 1 } else { 0 };
+            (rank[i], second)
+        };
+        sa.sort_unstable_by_key(|i| key(*i));
+
+        tmp[sa[0] as usize] = 0;
+        for i in 1..n {
+            let is_new = key(sa[i - 1]) != key(sa[i]);
+            tmp[sa[i] as usize] = tmp[sa[i - 1] as usize] + is_new as usize;
+        }
+        std::mem::swap(&mut rank, &mut tmp);
+
+        // All suffixes have a distinct rank
+        if rank[sa[n - 1] as usize] == n - 1 {
+            break;
+        }
+
+        k *= 2;
+    }
+
+    sa
+}
+
+/// Builds the LCP array for the given text and its suffix array using Kasai's algorithm. The first value is
+/// always 0 and each other value is the length of the longest common prefix of a suffix and its predecessor.
+pub(super) fn lcp_array(text: &[u8], sa: &[u32]) -> Vec<u32> {
+    let n = text.len();
+    let mut lcp = vec![0u32; n];
+
+    let mut rank = vec![0usize; n];
+    for (i, pos) in sa.iter().enumerate() {
+        rank[*pos as usize] = i;
+    }
+
+    let mut h = 0;
+    for i in 0..n {
+        if rank[i] == 0 {
+            h = 0;
+            continue;
+        }
+
+        let j = sa[rank[i] - 1] as usize;
+        while i + h < n && j + h < n && text[i + h] == text[j + h] {
+            h += 1;
+        }
+
+        lcp[rank[i]] = h as u32;
+        h = h.saturating_sub(1);
+    }
+
+    lcp
+}
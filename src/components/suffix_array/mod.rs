@@ -0,0 +1,300 @@
+mod build;
+
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::indexed_file::IndexedFile;
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::{Error, Result};
+use std::cmp::Ordering;
+use std::ops::Range;
+
+/// ID of the entry holding the text.
+const TEXT_ID: usize = 0;
+
+/// ID of the entry holding the suffix array.
+const SA_ID: usize = 1;
+
+/// ID of the entry holding the LCP array. Empty if no LCP array has been built.
+const LCP_ID: usize = 2;
+
+/// A suffix array over a byte payload allowing fast substring search. Optionally stores the LCP array
+/// (longest common prefix of neighbouring suffixes) too.
+///
+/// The internal format: IndexedFile(TEXT | SA (u32 LE) | LCP (u32 LE))
+pub struct SuffixArray<B> {
+    storage: IndexedFile<B>,
+}
+
+impl<B> SuffixArray<B>
+    where
+        B: GrowableBackend,
+{
+    /// Builds a suffix array over `text` and stores it in the given backend. If `with_lcp` is `true` the LCP array
+    /// gets built and stored as well.
+    pub fn build(backend: B, text: &[u8], with_lcp: bool) -> Result<Self> {
+        if text.len() >= u32::MAX as usize {
+            return Err(Error::OutOfBounds);
+        }
+
+        let sa = build::suffix_array(text);
+        let lcp = if with_lcp {
+            build::lcp_array(text, &sa)
+        } else {
+            vec![]
+        };
+
+        let mut storage = IndexedFile::with_capacity(backend, 3)?;
+        storage.grow(3, text.len() + (sa.len() + lcp.len()) * 4)?;
+        storage.insert(text)?;
+        storage.insert(&to_bytes(&sa))?;
+        storage.insert(&to_bytes(&lcp))?;
+
+        Ok(Self { storage })
+    }
+}
+
+impl<B> SuffixArray<B>
+    where
+        B: Backend,
+{
+    /// Returns the text the suffix array has been built over.
+    #[inline]
+    pub fn text(&self) -> &[u8] {
+        self.storage.get(TEXT_ID).unwrap()
+    }
+
+    /// Returns the length of the text which is also the amount of suffixes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.text().len()
+    }
+
+    /// Returns `true` if the text is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the LCP array has been built.
+    #[inline]
+    pub fn has_lcp(&self) -> bool {
+        self.storage.get(LCP_ID).unwrap().len() == self.len() * 4
+    }
+
+    /// Returns the text position of the `index`-th smallest suffix.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<usize> {
+        read_u32(self.storage.get(SA_ID).unwrap(), index)
+    }
+
+    /// Returns the length of the longest common prefix of the suffix at `index` and its predecessor. Returns `None`
+    /// if `index` is out of bounds or the LCP array hasn't been built.
+    #[inline]
+    pub fn lcp(&self, index: usize) -> Option<usize> {
+        read_u32(self.storage.get(LCP_ID).unwrap(), index)
+    }
+
+    /// Returns the range of suffix array indices whose suffixes start with `pattern`.
+    pub fn find_range(&self, pattern: &[u8]) -> Range<usize> {
+        let start = self.partition_point(|suffix| prefix_cmp(suffix, pattern) == Ordering::Less);
+        let end = self.partition_point(|suffix| prefix_cmp(suffix, pattern) != Ordering::Greater);
+        start..end
+    }
+
+    /// Returns the positions of all occurrences of `pattern` in the text. The positions are not sorted.
+    pub fn find(&self, pattern: &[u8]) -> Vec<usize> {
+        self.find_range(pattern)
+            .map(|i| self.get(i).unwrap())
+            .collect()
+    }
+
+    /// Returns the amount of occurrences of `pattern` in the text.
+    #[inline]
+    pub fn count(&self, pattern: &[u8]) -> usize {
+        self.find_range(pattern).len()
+    }
+
+    /// Returns `true` if the text contains `pattern`.
+    #[inline]
+    pub fn contains(&self, pattern: &[u8]) -> bool {
+        self.count(pattern) > 0
+    }
+
+    /// Flushes the suffix array.
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.storage.flush()
+    }
+
+    /// Binary searches the first suffix for which `pred` returns `false`. Suffixes must be partitioned by `pred`.
+    fn partition_point<P>(&self, mut pred: P) -> usize
+        where
+            P: FnMut(&[u8]) -> bool,
+    {
+        let text = self.text();
+        let sa = self.storage.get(SA_ID).unwrap();
+
+        let mut left = 0;
+        let mut right = self.len();
+        while left < right {
+            let mid = left + (right - left) / 2;
+            let suffix = &text[read_u32(sa, mid).unwrap()..];
+            if pred(suffix) {
+                left = mid + 1;
+            } else {
+                right = mid;
+            }
+        }
+
+        left
+    }
+}
+
+impl<B> Initiable<B> for SuffixArray<B>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let storage = IndexedFile::init(backend)?;
+        if storage.count() != 3 {
+            return Err(Error::Initialization);
+        }
+
+        let sa = Self { storage };
+        if sa.storage.get(SA_ID)?.len() != sa.len() * 4 {
+            return Err(Error::Initialization);
+        }
+
+        Ok(sa)
+    }
+}
+
+impl<B> MType for SuffixArray<B>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.storage.raw_data()
+    }
+}
+
+/// Compares the prefix of `suffix` with the length of `pattern` to `pattern`.
+#[inline]
+fn prefix_cmp(suffix: &[u8], pattern: &[u8]) -> Ordering {
+    let len = suffix.len().min(pattern.len());
+    suffix[..len].cmp(pattern)
+}
+
+/// Reads the `index`-th u32 of an encoded array.
+#[inline]
+fn read_u32(data: &[u8], index: usize) -> Option<usize> {
+    let start = index * 4;
+    let bytes: [u8; 4] = data.get(start..start + 4)?.try_into().unwrap();
+    Some(u32::from_le_bytes(bytes) as usize)
+}
+
+/// Encodes an array of u32 values into little endian bytes.
+fn to_bytes(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|i| i.to_le_bytes()).collect()
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_find(&mut mem_backend);
+        test_lcp(&mut mem_backend);
+        test_empty(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./suffix_array", 10);
+        test_find(&mut mmap_backend);
+        test_lcp(&mut mmap_backend);
+        test_empty(&mut mmap_backend);
+    }
+
+    /// Naive substring search for comparison.
+    fn find_naive(text: &[u8], pattern: &[u8]) -> Vec<usize> {
+        if pattern.is_empty() {
+            return (0..text.len()).collect();
+        }
+        text.windows(pattern.len())
+            .enumerate()
+            .filter(|(_, w)| *w == pattern)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    fn test_find<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let text = b"abracadabra, banana and a bandana";
+        {
+            let sa = SuffixArray::build(&mut backend, text, false).unwrap();
+            assert_eq!(sa.len(), text.len());
+            assert!(!sa.has_lcp());
+            assert_eq!(sa.lcp(0), None);
+
+            // Suffixes must be sorted
+            for i in 1..sa.len() {
+                let a = &text[sa.get(i - 1).unwrap()..];
+                let b = &text[sa.get(i).unwrap()..];
+                assert!(a < b);
+            }
+
+            let patterns: [&[u8]; 8] = [
+                b"a",
+                b"abra",
+                b"ana",
+                b"ban",
+                b"xyz",
+                b"a bandana",
+                b"",
+                b"abracadabra, banana and a bandana!",
+            ];
+            for pattern in patterns {
+                let mut found = sa.find(pattern);
+                found.sort_unstable();
+                assert_eq!(found, find_naive(text, pattern));
+                assert_eq!(sa.count(pattern), found.len());
+            }
+        }
+
+        let sa = SuffixArray::init(&mut backend).unwrap();
+        assert_eq!(sa.text(), text);
+        assert!(sa.contains(b"bandana"));
+        assert!(!sa.contains(b"bandanas"));
+    }
+
+    fn test_lcp<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let text = b"mississippi";
+        let sa = SuffixArray::build(&mut backend, text, true).unwrap();
+        assert!(sa.has_lcp());
+
+        let positions: Vec<_> = (0..sa.len()).map(|i| sa.get(i).unwrap()).collect();
+        assert_eq!(positions, vec![10, 7, 4, 1, 0, 9, 8, 6, 3, 5, 2]);
+
+        let lcp: Vec<_> = (0..sa.len()).map(|i| sa.lcp(i).unwrap()).collect();
+        assert_eq!(lcp, vec![0, 1, 1, 4, 0, 0, 1, 0, 2, 1, 3]);
+
+        let mut found = sa.find(b"ssi");
+        found.sort_unstable();
+        assert_eq!(found, vec![2, 5]);
+    }
+
+    fn test_empty<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let sa = SuffixArray::build(&mut backend, b"", true).unwrap();
+        assert!(sa.is_empty());
+        assert!(sa.find(b"a").is_empty());
+        assert_eq!(sa.get(0), None);
+    }
+}
@@ -0,0 +1,281 @@
+pub mod row;
+pub mod schema;
+
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::header_file::CustomHeaderFile;
+use crate::components::table::row::Row;
+use crate::components::table::schema::{Column, Schema};
+use crate::deser::serialize_impl;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::traits::sized_deser::SizedDeser;
+use crate::{Error, Result};
+
+/// A table of fixed-size records. The layout of the records is described by a `Schema` which is stored in the header,
+/// so columns can be accessed by their name after loading the table.
+///
+/// The internal format: (H_LEN (4 bytes) | SCHEMA | ROW | ROW | ...)
+pub struct Table<B> {
+    backend: CustomHeaderFile<B, Schema>,
+    row_width: usize,
+}
+
+impl<B> Table<B>
+    where
+        B: GrowableBackend,
+{
+    /// Creates a new empty table with the given schema. Returns `Error::UnexpectedValue` if the schema has no columns.
+    pub fn new(mut backend: B, schema: Schema) -> Result<Self> {
+        let row_width = schema.row_width();
+        if row_width == 0 {
+            return Err(Error::UnexpectedValue);
+        }
+
+        // `CustomHeaderFile::create` doesn't grow the backend, so make room for the header and its length first.
+        let header_len = serialize_impl(&schema)?.len() + 4;
+        if backend.capacity() < header_len {
+            backend.grow(header_len)?;
+        }
+
+        let backend = CustomHeaderFile::create(backend, schema)?;
+        Ok(Self { backend, row_width })
+    }
+
+    /// Appends a record given as raw bytes and returns its index. The length of `data` has to match the schemas row
+    /// width.
+    pub fn push_row(&mut self, data: &[u8]) -> Result<usize> {
+        if data.len() != self.row_width {
+            return Err(Error::UnexpectedValue);
+        }
+        self.reserve(1)?;
+        self.backend.push(data)?;
+        Ok(self.len() - 1)
+    }
+
+    /// Appends a record with all columns set to zero and returns its index.
+    pub fn push_zeroed(&mut self) -> Result<usize> {
+        self.reserve(1)?;
+        self.backend.push_fill(0, self.row_width)?;
+        Ok(self.len() - 1)
+    }
+
+    /// Grows the table so that `additional` more records fit without growing again.
+    pub fn reserve(&mut self, additional: usize) -> Result<()> {
+        let required = self.backend.len() + additional * self.row_width;
+        let capacity = self.backend.capacity();
+        if required > capacity {
+            let new_capacity = self.backend.growth_policy().next_capacity(capacity, required);
+            self.backend.grow_to(new_capacity)?;
+        }
+        Ok(())
+    }
+}
+
+impl<B> Table<B>
+    where
+        B: Backend,
+{
+    /// Returns the amount of records.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.backend.len() / self.row_width
+    }
+
+    /// Returns `true` if the table has no records.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    pub fn schema(&self) -> &Schema {
+        self.backend.header()
+    }
+
+    /// Returns the record at `index`.
+    pub fn row(&self, index: usize) -> Result<Row<'_>> {
+        if index >= self.len() {
+            return Err(Error::OutOfBounds);
+        }
+        let data = self.backend.get(index * self.row_width, self.row_width)?;
+        Ok(Row::new(self.schema(), data))
+    }
+
+    /// Returns an iterator over all records.
+    #[inline]
+    pub fn rows(&self) -> impl ExactSizeIterator<Item = Row<'_>> + DoubleEndedIterator + '_ {
+        (0..self.len()).map(|i| self.row(i).expect("Failed to load row"))
+    }
+
+    /// Returns the raw bytes of a column of the record at `index`.
+    #[inline]
+    pub fn get_raw(&self, index: usize, column: &str) -> Result<&[u8]> {
+        self.row(index)?.get_raw(column)
+    }
+
+    /// Returns the value of a column of the record at `index`.
+    #[inline]
+    pub fn get<T: SizedDeser<N>, const N: usize>(&self, index: usize, column: &str) -> Result<T> {
+        self.row(index)?.get(column)
+    }
+
+    /// Returns an iterator over the values of a single column of all records, without reading the other columns.
+    pub fn column_iter<T: SizedDeser<N>, const N: usize>(
+        &self,
+        column: &str,
+    ) -> Result<impl ExactSizeIterator<Item = T> + '_> {
+        let offset = self.schema().typed_column(column, N)?.offset();
+        Ok((0..self.len()).map(move |i| {
+            let bytes = self.backend.get(i * self.row_width + offset, N).expect("Failed to load column");
+            T::from_bytes(bytes.try_into().unwrap())
+        }))
+    }
+
+    /// Sets the raw bytes of a column of the record at `index`. The length of `data` has to match the columns width.
+    pub fn set_raw(&mut self, index: usize, column: &str, data: &[u8]) -> Result<()> {
+        let column = self.schema().column(column).ok_or(Error::OutOfBounds)?;
+        if data.len() != column.width() {
+            return Err(Error::UnexpectedValue);
+        }
+        let pos = self.position(index, column)?;
+        self.backend.replace_same_len(pos, data)?;
+        Ok(())
+    }
+
+    /// Sets the value of a column of the record at `index`.
+    pub fn set<T: SizedDeser<N>, const N: usize>(&mut self, index: usize, column: &str, value: &T) -> Result<()> {
+        let column = self.schema().typed_column(column, N)?;
+        let pos = self.position(index, column)?;
+        self.backend.replace_same_len(pos, &value.to_bytes())?;
+        Ok(())
+    }
+
+    /// Removes all records, keeping the schema.
+    #[inline]
+    pub fn clear(&mut self) -> Result<()> {
+        self.backend.set_len(0)
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.backend.flush()
+    }
+
+    /// Returns the position of a column of the record at `index` in the backend.
+    fn position(&self, index: usize, column: &Column) -> Result<usize> {
+        if index >= self.len() {
+            return Err(Error::OutOfBounds);
+        }
+        Ok(index * self.row_width + column.offset())
+    }
+}
+
+impl<B> Initiable<B> for Table<B>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let backend: CustomHeaderFile<B, Schema> = CustomHeaderFile::init(backend)?;
+        let row_width = backend.header().row_width();
+        if row_width == 0 || !backend.len().is_multiple_of(row_width) {
+            return Err(Error::InvalidHeader);
+        }
+        Ok(Self { backend, row_width })
+    }
+}
+
+impl<B> MType for Table<B>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.backend.raw_data()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_table(&mut mem_backend);
+        test_raw(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./table", 10);
+        test_table(&mut mmap_backend);
+        test_raw(&mut mmap_backend);
+    }
+
+    fn schema() -> Schema {
+        Schema::new()
+            .with_column("id", 4)
+            .and_then(|i| i.with_column("score", 8))
+            .and_then(|i| i.with_column("flag", 1))
+            .unwrap()
+    }
+
+    fn test_table<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        {
+            let mut table = Table::new(&mut backend, schema()).unwrap();
+            assert!(table.is_empty());
+            for i in 0..500u32 {
+                let row = table.push_zeroed().unwrap();
+                assert_eq!(row, i as usize);
+                table.set(row, "id", &i).unwrap();
+                table.set(row, "score", &(i as f64 * 1.5)).unwrap();
+                table.set(row, "flag", &(i % 3 == 0)).unwrap();
+            }
+            assert_eq!(table.set(0, "id", &0u64), Err(Error::UnexpectedValue));
+            assert_eq!(table.set(0, "name", &0u32), Err(Error::OutOfBounds));
+            assert_eq!(table.set(500, "id", &0u32), Err(Error::OutOfBounds));
+        }
+
+        let table = Table::init(&mut backend).unwrap();
+        assert_eq!(table.schema(), &schema());
+        assert_eq!(table.len(), 500);
+        assert_eq!(table.get::<u32, 4>(7, "id"), Ok(7));
+        assert_eq!(table.get::<f64, 8>(7, "score"), Ok(10.5));
+        assert_eq!(table.get::<u32, 4>(7, "score"), Err(Error::UnexpectedValue));
+        assert!(table.get::<u32, 4>(500, "id").is_err());
+
+        for (i, row) in table.rows().enumerate() {
+            assert_eq!(row.get::<u32, 4>("id"), Ok(i as u32));
+            assert_eq!(row.get::<bool, 1>("flag"), Ok(i % 3 == 0));
+        }
+
+        let flagged = table.column_iter::<bool, 1>("flag").unwrap().filter(|i| *i).count();
+        assert_eq!(flagged, 167);
+        let ids: Vec<u32> = table.column_iter("id").unwrap().collect();
+        assert_eq!(ids, (0..500).collect::<Vec<_>>());
+    }
+
+    fn test_raw<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        assert!(Table::new(&mut backend, Schema::new()).is_err());
+        assert!(schema().with_column("id", 2).is_err());
+        assert!(schema().with_column("empty", 0).is_err());
+
+        let schema = Schema::new().with_column("a", 2).and_then(|i| i.with_column("b", 3)).unwrap();
+        assert_eq!(schema.column("b").map(|i| (i.offset(), i.width())), Some((2, 3)));
+        assert_eq!(schema.row_width(), 5);
+
+        let mut table = Table::new(&mut backend, schema).unwrap();
+        assert_eq!(table.push_row(&[1, 2, 3, 4, 5]), Ok(0));
+        assert_eq!(table.push_row(&[1, 2, 3]), Err(Error::UnexpectedValue));
+        table.set_raw(0, "b", &[9, 9, 9]).unwrap();
+        assert_eq!(table.get_raw(0, "a"), Ok(&[1, 2][..]));
+        assert_eq!(table.row(0).map(|i| i.data()), Ok(&[1, 2, 9, 9, 9][..]));
+
+        table.clear().unwrap();
+        assert!(table.is_empty());
+        assert_eq!(table.push_row(&[0; 5]), Ok(0));
+    }
+}
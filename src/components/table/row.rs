@@ -0,0 +1,36 @@
+use crate::components::table::schema::Schema;
+use crate::traits::sized_deser::SizedDeser;
+use crate::{Error, Result};
+
+/// A single record of a `Table`.
+#[derive(Clone, Copy)]
+pub struct Row<'a> {
+    schema: &'a Schema,
+    data: &'a [u8],
+}
+
+impl<'a> Row<'a> {
+    #[inline]
+    pub(super) fn new(schema: &'a Schema, data: &'a [u8]) -> Self {
+        Self { schema, data }
+    }
+
+    /// Returns the raw bytes of the whole record.
+    #[inline]
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Returns the raw bytes of the given column.
+    pub fn get_raw(&self, column: &str) -> Result<&'a [u8]> {
+        let column = self.schema.column(column).ok_or(Error::OutOfBounds)?;
+        Ok(&self.data[column.offset()..column.offset() + column.width()])
+    }
+
+    /// Returns the value of the given column. Returns `Error::UnexpectedValue` if the columns width isn't `N`.
+    pub fn get<T: SizedDeser<N>, const N: usize>(&self, column: &str) -> Result<T> {
+        let column = self.schema.typed_column(column, N)?;
+        let bytes = &self.data[column.offset()..column.offset() + N];
+        Ok(T::from_bytes(bytes.try_into().unwrap()))
+    }
+}
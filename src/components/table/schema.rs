@@ -0,0 +1,85 @@
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+/// A named column of fixed width within the records of a `Table`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Column {
+    name: String,
+    offset: u32,
+    width: u32,
+}
+
+impl Column {
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the position of the columns first byte within a record.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset as usize
+    }
+
+    /// Returns the amount of bytes the column takes in each record.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width as usize
+    }
+}
+
+/// Describes the layout of the records of a `Table`. Columns are laid out one after another in the order they were
+/// added.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Schema {
+    columns: Vec<Column>,
+}
+
+impl Schema {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a column with the given width after all existing columns. Returns `Error::UnexpectedValue` if the width
+    /// is zero or a column with the same name already exists.
+    pub fn with_column(mut self, name: impl Into<String>, width: usize) -> Result<Self> {
+        let name = name.into();
+        if width == 0 || self.column(&name).is_some() {
+            return Err(Error::UnexpectedValue);
+        }
+        let offset = self.row_width() as u32;
+        self.columns.push(Column {
+            name,
+            offset,
+            width: width as u32,
+        });
+        Ok(self)
+    }
+
+    /// Returns the column with the given name.
+    #[inline]
+    pub fn column(&self, name: &str) -> Option<&Column> {
+        self.columns.iter().find(|i| i.name == name)
+    }
+
+    #[inline]
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// Returns the amount of bytes of a single record.
+    #[inline]
+    pub fn row_width(&self) -> usize {
+        self.columns.last().map(|i| i.offset() + i.width()).unwrap_or(0)
+    }
+
+    /// Returns the column with the given name and checks that its width is `n`.
+    pub(crate) fn typed_column(&self, name: &str, n: usize) -> Result<&Column> {
+        let column = self.column(name).ok_or(Error::OutOfBounds)?;
+        if column.width() != n {
+            return Err(Error::UnexpectedValue);
+        }
+        Ok(column)
+    }
+}
@@ -0,0 +1,44 @@
+/// Levenshtein automaton accepting all byte strings within `max_edits` insertions, deletions or substitutions of a
+/// key. A state is the row of edit distances between the bytes consumed so far and each prefix of the key, capped at
+/// `max_edits + 1` as larger distances can't lead to a match anymore.
+pub(super) struct LevenshteinAutomaton<'a> {
+    key: &'a [u8],
+    max_edits: usize,
+}
+
+impl<'a> LevenshteinAutomaton<'a> {
+    #[inline]
+    pub fn new(key: &'a [u8], max_edits: usize) -> Self {
+        Self { key, max_edits }
+    }
+
+    /// Returns the state before consuming any byte.
+    #[inline]
+    pub fn start(&self) -> Vec<usize> {
+        (0..=self.key.len()).map(|i| i.min(self.max_edits + 1)).collect()
+    }
+
+    /// Returns the state after consuming `b` in `state`.
+    pub fn step(&self, state: &[usize], b: u8) -> Vec<usize> {
+        let mut next = Vec::with_capacity(state.len());
+        next.push((state[0] + 1).min(self.max_edits + 1));
+        for (i, k) in self.key.iter().enumerate() {
+            let substitution = state[i] + (*k != b) as usize;
+            let dist = substitution.min(state[i + 1] + 1).min(next[i] + 1);
+            next.push(dist.min(self.max_edits + 1));
+        }
+        next
+    }
+
+    /// Returns `true` if the bytes consumed to reach `state` match the key.
+    #[inline]
+    pub fn is_match(&self, state: &[usize]) -> bool {
+        state[self.key.len()] <= self.max_edits
+    }
+
+    /// Returns `true` if appending more bytes can still lead to a match.
+    #[inline]
+    pub fn can_match(&self, state: &[usize]) -> bool {
+        state.iter().any(|i| *i <= self.max_edits)
+    }
+}
@@ -0,0 +1,51 @@
+use crate::backend::Backend;
+use crate::components::trie::Trie;
+use serde::de::DeserializeOwned;
+
+/// Iterator over the items of a `Trie` whose keys start with a given prefix.
+pub struct PrefixIter<'a, B, V> {
+    trie: &'a Trie<B, V>,
+
+    /// Nodes left to visit with the part of the key leading to them, excluding their label.
+    stack: Vec<(usize, Vec<u8>)>,
+}
+
+impl<'a, B, V> PrefixIter<'a, B, V> {
+    #[inline]
+    pub(super) fn new(trie: &'a Trie<B, V>, id: usize, key: Vec<u8>) -> Self {
+        Self {
+            trie,
+            stack: vec![(id, key)],
+        }
+    }
+
+    #[inline]
+    pub(super) fn empty(trie: &'a Trie<B, V>) -> Self {
+        Self { trie, stack: vec![] }
+    }
+}
+
+impl<B, V> Iterator for PrefixIter<'_, B, V>
+    where
+        B: Backend,
+        V: DeserializeOwned,
+{
+    type Item = (Vec<u8>, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (id, mut key) = self.stack.pop()?;
+            let node = self.trie.load_node(id).unwrap();
+            key.extend_from_slice(&node.label);
+
+            // Children are sorted by their first byte, so visiting a node before its children yields sorted keys.
+            for (_, child) in node.children.iter().rev() {
+                self.stack.push((*child as usize, key.clone()));
+            }
+
+            if let Some(value) = node.value {
+                return Some((key, value));
+            }
+        }
+    }
+}
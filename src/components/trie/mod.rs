@@ -0,0 +1,590 @@
+mod fuzzy;
+pub mod iter;
+
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::multi_file::MultiFile;
+use crate::components::trie::fuzzy::LevenshteinAutomaton;
+use crate::components::trie::iter::PrefixIter;
+use crate::deser::{deserialize_impl, serialize_impl};
+use crate::traits::creatable::Creatable;
+use crate::traits::deser::Deser;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::{Error, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+/// ID of the entry holding the tries metadata.
+const METADATA_ID: usize = 0;
+
+/// ID of the entry holding the root node.
+const ROOT_ID: usize = 1;
+
+#[derive(Serialize, Deserialize)]
+struct TrieMetadata {
+    len: u64,
+
+    /// IDs of entries of removed nodes which can be reused.
+    free: Vec<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Node<V> {
+    /// Bytes of the edge leading to this node. Empty for the root.
+    label: Vec<u8>,
+    value: Option<V>,
+
+    /// First byte of each childs label with the childs entry ID, sorted by the byte.
+    children: Vec<(u8, u64)>,
+}
+
+impl<V> Node<V> {
+    #[inline]
+    fn new(label: Vec<u8>, value: Option<V>) -> Self {
+        Self {
+            label,
+            value,
+            children: vec![],
+        }
+    }
+
+    #[inline]
+    fn child(&self, b: u8) -> std::result::Result<usize, usize> {
+        self.children.binary_search_by_key(&b, |i| i.0)
+    }
+}
+
+/// Radix trie mapping byte string keys to values. Unlike `FMap` it can look up all keys starting with a given prefix.
+/// Chains of nodes with a single child are merged into one node so each node holds a part of the key.
+///
+/// The internal format: MultiFile(METADATA | ROOT | NODE | ...)
+/// where each node is a serialized edge label, optional value and the entry IDs of its children.
+pub struct Trie<B, V> {
+    storage: MultiFile<B>,
+    len: usize,
+    free: Vec<usize>,
+    p: PhantomData<V>,
+}
+
+impl<B, V> Trie<B, V>
+    where
+        B: GrowableBackend,
+{
+    /// Removes all items.
+    pub fn clear(&mut self) -> Result<()> {
+        self.storage.clear();
+        self.len = 0;
+        self.free.clear();
+        self.init_storage()
+    }
+
+    /// Flushes the trie to the backend.
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.storage.flush()
+    }
+
+    /// Inserts the metadata and an empty root into the empty storage.
+    fn init_storage(&mut self) -> Result<()> {
+        self.storage.insert_empty()?;
+        self.storage.insert_empty()?;
+        self.write_metadata()?;
+        self.storage.set_entry(ROOT_ID, &serialize_impl(&Node::<()>::new(vec![], None))?)
+    }
+
+    fn write_metadata(&mut self) -> Result<()> {
+        let metadata = TrieMetadata {
+            len: self.len as u64,
+            free: self.free.iter().map(|i| *i as u64).collect(),
+        };
+        self.storage.set_entry(METADATA_ID, &serialize_impl(&metadata)?)
+    }
+
+    /// Returns the ID of an unused entry for a new node.
+    fn alloc_node(&mut self) -> Result<usize> {
+        if let Some(id) = self.free.pop() {
+            return Ok(id);
+        }
+        let id = self.storage.count();
+        self.storage.insert_empty()?;
+        Ok(id)
+    }
+
+    #[inline]
+    fn free_node(&mut self, id: usize) -> Result<()> {
+        self.storage.entry_mut(id).ok_or(Error::OutOfBounds)?.clear();
+        self.free.push(id);
+        Ok(())
+    }
+}
+
+impl<B, V> Trie<B, V>
+    where
+        B: GrowableBackend,
+        V: Deser,
+{
+    /// Inserts a key with its value and returns the previous value of the key.
+    pub fn insert(&mut self, key: impl AsRef<[u8]>, v: V) -> Result<Option<V>> {
+        let mut rest = key.as_ref();
+        let mut id = ROOT_ID;
+        let mut node = self.load_node(id)?;
+
+        loop {
+            let Some(&b) = rest.first() else {
+                let old = node.value.replace(v);
+                self.write_node(id, &node)?;
+                if old.is_none() {
+                    self.len += 1;
+                    self.write_metadata()?;
+                }
+                return Ok(old);
+            };
+
+            let i = match node.child(b) {
+                Ok(i) => i,
+                Err(i) => {
+                    let leaf_id = self.alloc_node()?;
+                    self.write_node(leaf_id, &Node::new(rest.to_vec(), Some(v)))?;
+                    node.children.insert(i, (b, leaf_id as u64));
+                    self.write_node(id, &node)?;
+                    self.len += 1;
+                    self.write_metadata()?;
+                    return Ok(None);
+                }
+            };
+
+            let child_id = node.children[i].1 as usize;
+            let mut child = self.load_node(child_id)?;
+            let common = common_prefix(&child.label, rest);
+
+            if common < child.label.len() {
+                // The key diverges within the childs label, so the label gets split by a new node in between.
+                let mid_id = self.alloc_node()?;
+                let mut mid = Node::new(child.label[..common].to_vec(), None);
+                mid.children.push((child.label[common], child_id as u64));
+                child.label.drain(..common);
+                self.write_node(child_id, &child)?;
+                node.children[i].1 = mid_id as u64;
+                self.write_node(id, &node)?;
+                child = mid;
+                id = mid_id;
+            } else {
+                id = child_id;
+            }
+
+            rest = &rest[common..];
+            node = child;
+        }
+    }
+
+    /// Removes a key and returns its value.
+    pub fn remove(&mut self, key: impl AsRef<[u8]>) -> Result<Option<V>> {
+        let mut rest = key.as_ref();
+
+        // IDs of the nodes on the path to the key and the index of the next node in their children.
+        let mut path = vec![];
+        let mut id = ROOT_ID;
+        let mut node = self.load_node(id)?;
+
+        while let Some(&b) = rest.first() {
+            let Ok(i) = node.child(b) else {
+                return Ok(None);
+            };
+            let child_id = node.children[i].1 as usize;
+            let child = self.load_node(child_id)?;
+            if !rest.starts_with(&child.label) {
+                return Ok(None);
+            }
+            rest = &rest[child.label.len()..];
+            path.push((id, i));
+            (id, node) = (child_id, child);
+        }
+
+        let Some(value) = node.value.take() else {
+            return Ok(None);
+        };
+        self.len -= 1;
+
+        match path.pop() {
+            Some((parent_id, i)) if node.children.is_empty() => {
+                self.free_node(id)?;
+                let mut parent = self.load_node(parent_id)?;
+                parent.children.remove(i);
+
+                // Removing the child might leave the parent with a single one.
+                match path.last() {
+                    Some(&(grandparent_id, j)) if parent.value.is_none() && parent.children.len() == 1 => {
+                        self.merge_child(grandparent_id, j, parent_id, parent)?;
+                    }
+                    _ => self.write_node(parent_id, &parent)?,
+                }
+            }
+            Some((parent_id, i)) if node.children.len() == 1 => self.merge_child(parent_id, i, id, node)?,
+            _ => self.write_node(id, &node)?,
+        }
+
+        self.write_metadata()?;
+        Ok(Some(value))
+    }
+
+    /// Replaces `node`, which has no value and a single child, with that child by prepending its label.
+    fn merge_child(&mut self, parent_id: usize, i: usize, id: usize, mut node: Node<V>) -> Result<()> {
+        let child_id = node.children[0].1 as usize;
+        let mut child = self.load_node(child_id)?;
+        node.label.extend_from_slice(&child.label);
+        child.label = node.label;
+        self.write_node(child_id, &child)?;
+
+        let mut parent = self.load_node(parent_id)?;
+        parent.children[i].1 = child_id as u64;
+        self.write_node(parent_id, &parent)?;
+        self.free_node(id)
+    }
+
+    #[inline]
+    fn write_node(&mut self, id: usize, node: &Node<V>) -> Result<()> {
+        self.storage.set_entry(id, &serialize_impl(node)?)
+    }
+}
+
+impl<B, V> Trie<B, V>
+    where
+        B: Backend,
+        V: DeserializeOwned,
+{
+    /// Returns the value of a key.
+    pub fn get(&self, key: impl AsRef<[u8]>) -> Option<V> {
+        let mut rest = key.as_ref();
+        let mut node = self.load_node(ROOT_ID).ok()?;
+        while let Some(&b) = rest.first() {
+            let i = node.child(b).ok()?;
+            node = self.load_node(node.children[i].1 as usize).ok()?;
+            rest = rest.strip_prefix(node.label.as_slice())?;
+        }
+        node.value
+    }
+
+    /// Returns `true` if the trie contains the key.
+    #[inline]
+    pub fn contains_key(&self, key: impl AsRef<[u8]>) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns an iterator over all items, ordered by their keys.
+    #[inline]
+    pub fn iter(&self) -> PrefixIter<'_, B, V> {
+        self.iter_prefix([])
+    }
+
+    /// Returns an iterator over all items whose key starts with `prefix`, ordered by their keys.
+    pub fn iter_prefix(&self, prefix: impl AsRef<[u8]>) -> PrefixIter<'_, B, V> {
+        let prefix = prefix.as_ref();
+        let mut id = ROOT_ID;
+        let mut rest = prefix;
+
+        // Length of the part of the key leading to the node `id`, excluding its label.
+        let mut start = 0;
+
+        while let Some(&b) = rest.first() {
+            let Some(node) = self.load_node(id).ok() else {
+                return PrefixIter::empty(self);
+            };
+            let Ok(i) = node.child(b) else {
+                return PrefixIter::empty(self);
+            };
+            let child_id = node.children[i].1 as usize;
+            let Some(child) = self.load_node(child_id).ok() else {
+                return PrefixIter::empty(self);
+            };
+
+            // The prefix may end within the label of the child, in which case all keys below it match.
+            let common = common_prefix(&child.label, rest);
+            if common < rest.len() && common < child.label.len() {
+                return PrefixIter::empty(self);
+            }
+            start = prefix.len() - rest.len();
+            rest = &rest[common..];
+            id = child_id;
+        }
+
+        PrefixIter::new(self, id, prefix[..start].to_vec())
+    }
+
+    /// Returns all items whose key is at most `max_edits` byte insertions, deletions or substitutions away from `key`,
+    /// ordered by their keys. The trie gets intersected with a Levenshtein automaton of `key`, so nodes below a part of
+    /// a key that can't lead to a match anymore don't get loaded.
+    pub fn search_fuzzy(&self, key: impl AsRef<[u8]>, max_edits: usize) -> Result<Vec<(Vec<u8>, V)>> {
+        let automaton = LevenshteinAutomaton::new(key.as_ref(), max_edits);
+        let mut items = vec![];
+
+        // Nodes left to visit with the part of the key leading to them, excluding their label, and the state of the
+        // automaton after consuming it.
+        let mut stack = vec![(ROOT_ID, vec![], automaton.start())];
+        'nodes: while let Some((id, mut prefix, mut state)) = stack.pop() {
+            let node = self.load_node(id)?;
+            for b in node.label.iter() {
+                state = automaton.step(&state, *b);
+                if !automaton.can_match(&state) {
+                    continue 'nodes;
+                }
+            }
+            prefix.extend_from_slice(&node.label);
+
+            // Children are sorted by their first byte, so visiting a node before its children yields sorted keys.
+            for (_, child) in node.children.iter().rev() {
+                stack.push((*child as usize, prefix.clone(), state.clone()));
+            }
+
+            if let Some(value) = node.value.filter(|_| automaton.is_match(&state)) {
+                items.push((prefix, value));
+            }
+        }
+
+        Ok(items)
+    }
+
+    pub(crate) fn load_node(&self, id: usize) -> Result<Node<V>> {
+        let entry = self.storage.get(id).ok_or(Error::OutOfBounds)?;
+        deserialize_impl(entry.content_data())
+    }
+}
+
+impl<B, V> Trie<B, V> {
+    /// Returns the amount of items in the trie.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the trie is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// Returns the length of the common prefix of `a` and `b`.
+#[inline]
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+impl<B, V> Creatable<B> for Trie<B, V>
+    where
+        B: GrowableBackend,
+{
+    fn with_capacity(backend: B, _: usize) -> Result<Self> {
+        let mut trie = Self {
+            storage: MultiFile::with_capacity(backend, 2)?,
+            len: 0,
+            free: vec![],
+            p: PhantomData,
+        };
+        trie.init_storage()?;
+        Ok(trie)
+    }
+}
+
+impl<B, V> Initiable<B> for Trie<B, V>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let storage = MultiFile::init(backend)?;
+        let metadata: TrieMetadata = storage
+            .get(METADATA_ID)
+            .ok_or(Error::Initialization)
+            .and_then(|i| deserialize_impl(i.content_data()))?;
+
+        if !storage.has_id(ROOT_ID) {
+            return Err(Error::Initialization);
+        }
+
+        Ok(Self {
+            storage,
+            len: metadata.len as usize,
+            free: metadata.free.iter().map(|i| *i as usize).collect(),
+            p: PhantomData,
+        })
+    }
+}
+
+impl<B, V> MType for Trie<B, V>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.storage.raw_data()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_insert(&mut mem_backend);
+        test_remove(&mut mem_backend);
+        test_prefix(&mut mem_backend);
+        test_fuzzy(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./trie", 10);
+        test_insert(&mut mmap_backend);
+        test_remove(&mut mmap_backend);
+        test_prefix(&mut mmap_backend);
+        test_fuzzy(&mut mmap_backend);
+    }
+
+    fn words() -> Vec<String> {
+        let mut seed = 3u64;
+        (0..2000)
+            .map(|_| {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                let len = (seed >> 60) as usize + 1;
+                (0..len).map(|i| (b'a' + ((seed >> (i * 3)) & 3) as u8) as char).collect()
+            })
+            .collect()
+    }
+
+    fn test_insert<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut expected = BTreeMap::new();
+        {
+            let mut trie: Trie<_, u32> = Trie::create(&mut backend).unwrap();
+            assert_eq!(trie.get(""), None);
+            for (i, word) in words().into_iter().enumerate() {
+                assert_eq!(trie.insert(&word, i as u32), Ok(expected.insert(word, i as u32)));
+            }
+            assert_eq!(trie.insert("", 7), Ok(None));
+            expected.insert(String::new(), 7);
+        }
+
+        let trie: Trie<_, u32> = Trie::init(&mut backend).unwrap();
+        assert_eq!(trie.len(), expected.len());
+        for (k, v) in expected.iter() {
+            assert_eq!(trie.get(k), Some(*v));
+        }
+        assert_eq!(trie.get("e"), None);
+        assert!(!trie.contains_key("aaaaaaaaaaaaaaaaaaaaa"));
+
+        let items: Vec<_> = trie.iter().collect();
+        let expected: Vec<_> = expected.into_iter().map(|(k, v)| (k.into_bytes(), v)).collect();
+        assert_eq!(items, expected);
+    }
+
+    fn test_remove<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut trie: Trie<_, u32> = Trie::create(&mut backend).unwrap();
+        let mut expected = BTreeMap::new();
+        for (i, word) in words().into_iter().enumerate() {
+            trie.insert(&word, i as u32).unwrap();
+            expected.insert(word, i as u32);
+        }
+
+        for (i, word) in words().into_iter().enumerate().filter(|i| i.0 % 3 != 0) {
+            assert_eq!(trie.remove(&word), Ok(expected.remove(&word)), "{i}");
+        }
+        assert_eq!(trie.remove("x"), Ok(None));
+        assert_eq!(trie.len(), expected.len());
+
+        let items: Vec<_> = trie.iter().collect();
+        let items_exp: Vec<_> = expected.iter().map(|(k, v)| (k.clone().into_bytes(), *v)).collect();
+        assert_eq!(items, items_exp);
+
+        // Freed nodes get reused.
+        let count = trie.storage.count();
+        for word in words().into_iter().filter(|i| !expected.contains_key(i)).take(100) {
+            trie.insert(&word, 0).unwrap();
+        }
+        assert_eq!(trie.storage.count(), count);
+
+        trie.clear().unwrap();
+        assert!(trie.is_empty());
+        assert_eq!(trie.iter().count(), 0);
+    }
+
+    fn test_prefix<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut trie: Trie<_, u32> = Trie::create(&mut backend).unwrap();
+        let words = ["car", "card", "care", "careful", "cat", "dog", "do"];
+        for (i, word) in words.iter().enumerate() {
+            trie.insert(word, i as u32).unwrap();
+        }
+
+        let keys = |prefix: &str| -> Vec<String> {
+            trie.iter_prefix(prefix).map(|(k, _)| String::from_utf8(k).unwrap()).collect()
+        };
+        assert_eq!(keys("car"), vec!["car", "card", "care", "careful"]);
+        assert_eq!(keys("care"), vec!["care", "careful"]);
+        assert_eq!(keys("caref"), vec!["careful"]);
+        assert_eq!(keys("ca"), vec!["car", "card", "care", "careful", "cat"]);
+        assert_eq!(keys("d"), vec!["do", "dog"]);
+        assert_eq!(keys(""), vec!["car", "card", "care", "careful", "cat", "do", "dog"]);
+        assert!(keys("cb").is_empty());
+        assert!(keys("carefully").is_empty());
+        assert!(keys("x").is_empty());
+        assert_eq!(trie.iter_prefix("dog").next(), Some((b"dog".to_vec(), 5)));
+    }
+
+    fn test_fuzzy<B: GrowableBackend>(mut backend: &mut B) {
+        fn edit_distance(a: &[u8], b: &[u8]) -> usize {
+            let mut row: Vec<_> = (0..=b.len()).collect();
+            for (i, x) in a.iter().enumerate() {
+                let mut prev = row[0];
+                row[0] = i + 1;
+                for (j, y) in b.iter().enumerate() {
+                    let dist = (prev + (x != y) as usize).min(row[j] + 1).min(row[j + 1] + 1);
+                    prev = row[j + 1];
+                    row[j + 1] = dist;
+                }
+            }
+            row[b.len()]
+        }
+
+        backend.clear();
+        let mut trie: Trie<_, u32> = Trie::create(&mut backend).unwrap();
+        let list = ["car", "card", "care", "careful", "cart", "cat", "do", "dog", "scar"];
+        for (i, word) in list.iter().enumerate() {
+            trie.insert(word, i as u32).unwrap();
+        }
+
+        let keys = |key: &str, max_edits: usize| -> Vec<String> {
+            let items = trie.search_fuzzy(key, max_edits).unwrap();
+            items.into_iter().map(|(k, _)| String::from_utf8(k).unwrap()).collect()
+        };
+        assert_eq!(keys("car", 0), vec!["car"]);
+        assert_eq!(keys("car", 1), vec!["car", "card", "care", "cart", "cat", "scar"]);
+        assert_eq!(keys("carful", 1), vec!["careful"]);
+        assert_eq!(keys("dg", 1), vec!["do", "dog"]);
+        assert_eq!(keys("", 2), vec!["do"]);
+        assert!(keys("xyz", 2).is_empty());
+        assert_eq!(keys("dgo", 1), vec!["do"]);
+        assert_eq!(trie.search_fuzzy("dgo", 2).unwrap(), vec![(b"do".to_vec(), 6), (b"dog".to_vec(), 7)]);
+
+        trie.clear().unwrap();
+        let mut expected = BTreeMap::new();
+        for (i, word) in words().into_iter().enumerate() {
+            trie.insert(&word, i as u32).unwrap();
+            expected.insert(word.into_bytes(), i as u32);
+        }
+        for key in ["abc", "dddd", "a", "abcdabcdab"] {
+            for max_edits in 0..3 {
+                let exp: Vec<_> = expected
+                    .iter()
+                    .filter(|i| edit_distance(i.0, key.as_bytes()) <= max_edits)
+                    .map(|(k, v)| (k.clone(), *v))
+                    .collect();
+                assert_eq!(trie.search_fuzzy(key, max_edits).unwrap(), exp);
+            }
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use crate::backend::base::sub_mut::GeneralSubMutBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::multi_file::MultiFile;
+use crate::components::number_seq::NumberSequence;
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::traits::sized_deser::SizedDeser;
+use crate::{Error, Result};
+use num_traits::PrimInt;
+use std::marker::PhantomData;
+
+pub type UnionFindU32<B> = UnionFind<B, u32, 4>;
+pub type UnionFindU64<B> = UnionFind<B, u64, 8>;
+
+/// ID of the MultiFile entry holding the parent of each element.
+const PARENT_ID: usize = 0;
+
+/// ID of the MultiFile entry holding the rank of each element.
+const RANK_ID: usize = 1;
+
+type ParentSeq<'a, T, const N: usize> = NumberSequence<GeneralSubMutBackend<'a>, T, N>;
+type RankSeq<'a> = NumberSequence<GeneralSubMutBackend<'a>, u8, 1>;
+
+/// A disjoint set (union-find) over the IDs `0..len` using union by rank and path compression. Parents and ranks
+/// are stored as `NumberSequence`s in the backend.
+///
+/// The internal format: MultiFile(PARENTS | RANKS)
+pub struct UnionFind<B, T, const N: usize> {
+    backend: MultiFile<B>,
+    len: usize,
+    p: PhantomData<T>,
+}
+
+impl<B, T, const N: usize> UnionFind<B, T, N> {
+    /// Returns the amount of elements.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    fn check_bounds(&self, id: T) -> Result<usize>
+        where
+            T: PrimInt,
+    {
+        let index = id.to_usize().ok_or(Error::OutOfBounds)?;
+        if index >= self.len {
+            return Err(Error::OutOfBounds);
+        }
+        Ok(index)
+    }
+}
+
+impl<B, T, const N: usize> UnionFind<B, T, N>
+    where
+        B: GrowableBackend,
+        T: SizedDeser<N> + PrimInt,
+{
+    /// Adds a new element in its own set and returns its ID.
+    pub fn make_set(&mut self) -> Result<T> {
+        let id = T::from(self.len).ok_or(Error::OutOfBounds)?;
+
+        let mut parents: NumberSequence<_, T, N> = self.backend.get_backend_mut(PARENT_ID).unwrap();
+        parents.append(&[id])?;
+
+        let mut ranks: NumberSequence<_, u8, 1> = self.backend.get_backend_mut(RANK_ID).unwrap();
+        ranks.append(&[0])?;
+
+        self.len += 1;
+        Ok(id)
+    }
+
+    /// Adds new elements, each in its own set, until there are `len` elements in total.
+    pub fn grow_to(&mut self, len: usize) -> Result<()> {
+        if len <= self.len {
+            return Ok(());
+        }
+
+        let new_ids = (self.len..len)
+            .map(|i| T::from(i).ok_or(Error::OutOfBounds))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut parents: NumberSequence<_, T, N> = self.backend.get_backend_mut(PARENT_ID).unwrap();
+        parents.append(&new_ids)?;
+
+        let mut ranks: NumberSequence<_, u8, 1> = self.backend.get_backend_mut(RANK_ID).unwrap();
+        ranks.append(&vec![0; new_ids.len()])?;
+
+        self.len = len;
+        Ok(())
+    }
+}
+
+impl<B, T, const N: usize> UnionFind<B, T, N>
+    where
+        B: Backend,
+        T: SizedDeser<N> + PrimInt,
+{
+    /// Returns the representative of the set `id` belongs to, compressing the path to it.
+    pub fn find(&mut self, id: T) -> Result<T> {
+        self.check_bounds(id)?;
+        let (mut parents, _) = self.sequences()?;
+        Self::find_compress(&mut parents, id)
+    }
+
+    /// Returns the representative of the set `id` belongs to without compressing the path.
+    pub fn root(&self, id: T) -> Result<T> {
+        self.check_bounds(id)?;
+        let parents: NumberSequence<_, T, N> = NumberSequence::init(self.backend.get(PARENT_ID).unwrap())?;
+
+        let mut current = id;
+        loop {
+            let parent = parents.get(current.to_usize().unwrap())?;
+            if parent == current {
+                return Ok(current);
+            }
+            current = parent;
+        }
+    }
+
+    /// Merges the sets of `a` and `b`. Returns `false` if both already were in the same set.
+    pub fn union(&mut self, a: T, b: T) -> Result<bool> {
+        self.check_bounds(a)?;
+        self.check_bounds(b)?;
+
+        let (mut parents, mut ranks) = self.sequences()?;
+        let a = Self::find_compress(&mut parents, a)?;
+        let b = Self::find_compress(&mut parents, b)?;
+        if a == b {
+            return Ok(false);
+        }
+
+        let (a_idx, b_idx) = (a.to_usize().unwrap(), b.to_usize().unwrap());
+        let a_rank = ranks.get(a_idx)?;
+        let b_rank = ranks.get(b_idx)?;
+
+        if a_rank < b_rank {
+            parents.set(a_idx, b)?;
+        } else {
+            parents.set(b_idx, a)?;
+            if a_rank == b_rank {
+                ranks.set(a_idx, a_rank + 1)?;
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Returns `true` if `a` and `b` are in the same set.
+    #[inline]
+    pub fn connected(&mut self, a: T, b: T) -> Result<bool> {
+        Ok(self.find(a)? == self.find(b)?)
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.backend.flush()
+    }
+
+    /// Returns the parent and rank sequences mutable.
+    fn sequences(&mut self) -> Result<(ParentSeq<'_, T, N>, RankSeq<'_>)> {
+        let (parents, ranks) = self.backend.get_two_mut(PARENT_ID, RANK_ID)?;
+        Ok((NumberSequence::init(parents)?, NumberSequence::init(ranks)?))
+    }
+
+    /// Finds the root of `id` and lets all elements on the path point to the root directly.
+    fn find_compress(parents: &mut ParentSeq<'_, T, N>, id: T) -> Result<T> {
+        let mut root = id;
+        loop {
+            let parent = parents.get(root.to_usize().unwrap())?;
+            if parent == root {
+                break;
+            }
+            root = parent;
+        }
+
+        let mut current = id;
+        while current != root {
+            let index = current.to_usize().unwrap();
+            current = parents.get(index)?;
+            parents.set(index, root)?;
+        }
+
+        Ok(root)
+    }
+}
+
+impl<B, T, const N: usize> Creatable<B> for UnionFind<B, T, N>
+    where
+        B: GrowableBackend,
+        T: SizedDeser<N> + PrimInt,
+{
+    /// Creates a new union-find with `capacity` elements, each in its own set.
+    fn with_capacity(backend: B, capacity: usize) -> Result<Self> {
+        let mut backend = MultiFile::create(backend)?;
+        backend.insert_new_backend::<NumberSequence<_, T, N>>()?;
+        backend.insert_new_backend::<NumberSequence<_, u8, 1>>()?;
+
+        let mut uf = Self {
+            backend,
+            len: 0,
+            p: PhantomData,
+        };
+        uf.grow_to(capacity)?;
+        Ok(uf)
+    }
+}
+
+impl<B, T, const N: usize> Initiable<B> for UnionFind<B, T, N>
+    where
+        B: Backend,
+        T: SizedDeser<N> + PrimInt,
+{
+    fn init(backend: B) -> Result<Self> {
+        let backend = MultiFile::init(backend)?;
+        let parents = backend.get(PARENT_ID).ok_or(Error::Initialization)?;
+        let ranks = backend.get(RANK_ID).ok_or(Error::Initialization)?;
+
+        let len = parents.len() / N;
+        if parents.len() % N != 0 || ranks.len() != len {
+            return Err(Error::Initialization);
+        }
+
+        Ok(Self {
+            backend,
+            len,
+            p: PhantomData,
+        })
+    }
+}
+
+impl<B, T, const N: usize> MType for UnionFind<B, T, N>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.backend.raw_data()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_union(&mut mem_backend);
+        test_components(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./union_find", 10);
+        test_union(&mut mmap_backend);
+        test_components(&mut mmap_backend);
+    }
+
+    fn test_union<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        {
+            let mut uf: UnionFindU32<_> = UnionFind::with_capacity(&mut backend, 5).unwrap();
+            assert_eq!(uf.len(), 5);
+            assert_eq!(uf.make_set(), Ok(5));
+            assert_eq!(uf.len(), 6);
+
+            assert_eq!(uf.union(0, 1), Ok(true));
+            assert_eq!(uf.union(2, 3), Ok(true));
+            assert_eq!(uf.union(1, 0), Ok(false));
+            assert_eq!(uf.connected(0, 1), Ok(true));
+            assert_eq!(uf.connected(0, 2), Ok(false));
+
+            assert_eq!(uf.union(1, 3), Ok(true));
+            assert_eq!(uf.connected(0, 2), Ok(true));
+            assert_eq!(uf.connected(4, 5), Ok(false));
+            assert_eq!(uf.find(6), Err(Error::OutOfBounds));
+        }
+
+        let mut uf: UnionFindU32<_> = UnionFind::init(&mut backend).unwrap();
+        assert_eq!(uf.len(), 6);
+        assert_eq!(uf.root(3), uf.root(0));
+        assert_eq!(uf.connected(2, 1), Ok(true));
+        assert_eq!(uf.connected(4, 1), Ok(false));
+    }
+
+    fn test_components<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut uf: UnionFindU64<_> = UnionFind::with_capacity(&mut backend, 1000).unwrap();
+
+        // Connect all IDs with the same remainder of 7
+        for i in 7..1000u64 {
+            uf.union(i, i - 7).unwrap();
+        }
+
+        for i in 0..1000u64 {
+            let root = uf.find(i).unwrap();
+            assert_eq!(root % 7, i % 7);
+            assert_eq!(uf.root(i), Ok(root));
+        }
+
+        let mut roots: Vec<_> = (0..1000).map(|i| uf.find(i).unwrap()).collect();
+        roots.sort_unstable();
+        roots.dedup();
+        assert_eq!(roots.len(), 7);
+    }
+}
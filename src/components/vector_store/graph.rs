@@ -0,0 +1,168 @@
+use crate::backend::Backend;
+use crate::components::number_seq::NumberSequence;
+use crate::Result;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashSet};
+
+/// A node of the graph along with its distance to a query.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct Candidate {
+    pub dist: f32,
+    pub id: u32,
+}
+
+impl Candidate {
+    #[inline]
+    pub fn new(dist: f32, id: u32) -> Self {
+        Self { dist, id }
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.dist
+            .total_cmp(&other.dist)
+            .then(self.id.cmp(&other.id))
+    }
+}
+
+/// Returns the amount of neighbor slots of a node in the given level.
+#[inline]
+pub(super) fn slots(m: usize, level: usize) -> usize {
+    if level == 0 {
+        m * 2
+    } else {
+        m
+    }
+}
+
+/// Returns the total amount of link entries a node with the given top level needs.
+#[inline]
+pub(super) fn links_len(m: usize, level: usize) -> usize {
+    (0..=level).map(|l| 1 + slots(m, l)).sum()
+}
+
+/// View over the stored vectors and the HNSW layers.
+///
+/// The links of each node are stored consecutively starting at the nodes offset. Each layer of a node consists of
+/// the amount of neighbors followed by a fixed amount of neighbor slots. Layer 0 has `2 * m` slots, all upper layers
+/// have `m` slots.
+pub(super) struct Graph<S> {
+    pub vectors: NumberSequence<S, f32, 4>,
+    pub offsets: NumberSequence<S, u64, 8>,
+    pub links: NumberSequence<S, u32, 4>,
+    pub dim: usize,
+    pub m: usize,
+}
+
+impl<S> Graph<S>
+    where
+        S: Backend,
+{
+    /// Returns the squared euclidean distance between `query` and the vector of `node`.
+    pub fn dist(&self, query: &[f32], node: u32) -> Result<f32> {
+        let start = node as usize * self.dim;
+        let mut sum = 0.0;
+        for (i, q) in query.iter().enumerate() {
+            let d = self.vectors.get(start + i)? - q;
+            sum += d * d;
+        }
+        Ok(sum)
+    }
+
+    /// Returns the vector of `node`.
+    pub fn vector(&self, node: u32) -> Result<Vec<f32>> {
+        let start = node as usize * self.dim;
+        (start..start + self.dim).map(|i| self.vectors.get(i)).collect()
+    }
+
+    /// Returns the neighbors of `node` in the given level.
+    pub fn neighbors(&self, node: u32, level: usize) -> Result<Vec<u32>> {
+        let start = self.layer_start(node, level)?;
+        let count = self.links.get(start)? as usize;
+        (start + 1..start + 1 + count)
+            .map(|i| self.links.get(i))
+            .collect()
+    }
+
+    /// Sets the neighbors of `node` in the given level. `neighbors` must fit into the layers slots.
+    pub fn set_neighbors(&mut self, node: u32, level: usize, neighbors: &[u32]) -> Result<()> {
+        debug_assert!(neighbors.len() <= slots(self.m, level));
+        let start = self.layer_start(node, level)?;
+        self.links.set(start, neighbors.len() as u32)?;
+        for (i, n) in neighbors.iter().enumerate() {
+            self.links.set(start + 1 + i, *n)?;
+        }
+        Ok(())
+    }
+
+    /// Walks greedily towards the node closest to `query` in the given level.
+    pub fn greedy_closest(&self, query: &[f32], mut current: Candidate, level: usize) -> Result<Candidate> {
+        loop {
+            let mut changed = false;
+            for n in self.neighbors(current.id, level)? {
+                let dist = self.dist(query, n)?;
+                if dist < current.dist {
+                    current = Candidate::new(dist, n);
+                    changed = true;
+                }
+            }
+            if !changed {
+                return Ok(current);
+            }
+        }
+    }
+
+    /// Returns the `ef` nodes closest to `query` in the given level, ordered by their distance.
+    pub fn search_layer(
+        &self,
+        query: &[f32],
+        entries: &[Candidate],
+        ef: usize,
+        level: usize,
+    ) -> Result<Vec<Candidate>> {
+        let mut visited: HashSet<u32> = entries.iter().map(|i| i.id).collect();
+        let mut candidates: BinaryHeap<Reverse<Candidate>> = entries.iter().map(|i| Reverse(*i)).collect();
+        let mut results: BinaryHeap<Candidate> = entries.iter().copied().collect();
+
+        while let Some(Reverse(closest)) = candidates.pop() {
+            let furthest = results.peek().unwrap().dist;
+            if results.len() >= ef && closest.dist > furthest {
+                break;
+            }
+
+            for n in self.neighbors(closest.id, level)? {
+                if !visited.insert(n) {
+                    continue;
+                }
+
+                let dist = self.dist(query, n)?;
+                if results.len() < ef || dist < results.peek().unwrap().dist {
+                    candidates.push(Reverse(Candidate::new(dist, n)));
+                    results.push(Candidate::new(dist, n));
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+
+        Ok(results.into_sorted_vec())
+    }
+
+    /// Returns the index of the first link entry of `node`s layer `level`.
+    fn layer_start(&self, node: u32, level: usize) -> Result<usize> {
+        let offset = self.offsets.get(node as usize)? as usize;
+        Ok(offset + links_len(self.m, level) - (1 + slots(self.m, level)))
+    }
+}
@@ -0,0 +1,397 @@
+mod graph;
+
+use crate::backend::base::sub::GeneralSubBackend;
+use crate::backend::base::sub_mut::GeneralSubMutBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::multi_file::entry_mut::MFileEntryMut;
+use crate::components::multi_file::MultiFile;
+use crate::components::number_seq::NumberSequence;
+use crate::components::sketch::mix;
+use crate::components::vector_store::graph::{links_len, slots, Candidate, Graph};
+use crate::traits::creatable::Creatable;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::traits::sized_deser::SizedDeser;
+use crate::{Error, Result};
+
+/// Default amount of neighbors per node and layer.
+pub const DEFAULT_M: usize = 16;
+
+/// Default size of the candidate list while inserting.
+pub const DEFAULT_EF_CONSTRUCTION: usize = 100;
+
+/// Default size of the candidate list while searching.
+pub const DEFAULT_EF_SEARCH: usize = 64;
+
+/// Highest layer a node can be placed in.
+const MAX_LEVEL: usize = 16;
+
+/// Value of the entry point if the store is empty.
+const NO_ENTRY: u32 = u32::MAX;
+
+/// Size of the metadata: dim u32 | m u32 | ef_construction u32 | entry point u32 | max level u32
+const METADATA_LEN: usize = 20;
+
+const METADATA_ID: usize = 0;
+const VECTORS_ID: usize = 1;
+const IDS_ID: usize = 2;
+const LEVELS_ID: usize = 3;
+const OFFSETS_ID: usize = 4;
+const LINKS_ID: usize = 5;
+
+/// Stores fixed-dimension `f32` vectors along with an ID and indexes them in a HNSW graph for approximate nearest
+/// neighbor search using the squared euclidean distance.
+///
+/// The internal format: MultiFile(METADATA | VECTORS | IDS | LEVELS | LINK OFFSETS | LINKS)
+pub struct VectorStore<B> {
+    backend: MultiFile<B>,
+    dim: usize,
+    m: usize,
+    ef_construction: usize,
+    entry: u32,
+    max_level: usize,
+}
+
+impl<B> VectorStore<B>
+    where
+        B: GrowableBackend,
+{
+    /// Creates a new store for vectors of dimension `dim` using default HNSW parameters.
+    #[inline]
+    pub fn new(backend: B, dim: usize) -> Result<Self> {
+        Self::with_params(backend, dim, DEFAULT_M, DEFAULT_EF_CONSTRUCTION)
+    }
+
+    /// Creates a new store for vectors of dimension `dim`. `m` is the amount of neighbors each node gets linked to
+    /// and `ef_construction` the size of the candidate list used while inserting. Higher values increase the search
+    /// quality but slow down insertions.
+    pub fn with_params(backend: B, dim: usize, m: usize, ef_construction: usize) -> Result<Self> {
+        if dim == 0 || m < 2 || ef_construction == 0 {
+            return Err(Error::UnexpectedValue);
+        }
+
+        let mut backend = MultiFile::create(backend)?;
+        backend.insert_empty()?.grow_to(METADATA_LEN)?;
+        backend.insert_new_backend::<NumberSequence<_, f32, 4>>()?;
+        backend.insert_new_backend::<NumberSequence<_, u64, 8>>()?;
+        backend.insert_new_backend::<NumberSequence<_, u8, 1>>()?;
+        backend.insert_new_backend::<NumberSequence<_, u64, 8>>()?;
+        backend.insert_new_backend::<NumberSequence<_, u32, 4>>()?;
+
+        let mut store = Self {
+            backend,
+            dim,
+            m,
+            ef_construction,
+            entry: NO_ENTRY,
+            max_level: 0,
+        };
+        store.write_metadata()?;
+        Ok(store)
+    }
+
+    /// Adds a vector with the given ID. IDs are not checked for uniqueness.
+    pub fn add(&mut self, id: u64, vector: &[f32]) -> Result<()> {
+        if vector.len() != self.dim {
+            return Err(Error::UnexpectedValue);
+        }
+
+        let node = self.len() as u32;
+        let level = self.random_level(node);
+
+        let links_offset = self.backend.get(LINKS_ID).unwrap().len() / 4;
+        let links_len = links_len(self.m, level);
+        self.seq_mut::<f32, 4>(VECTORS_ID).append(vector)?;
+        self.seq_mut::<u64, 8>(IDS_ID).append(&[id])?;
+        self.seq_mut::<u8, 1>(LEVELS_ID).append(&[level as u8])?;
+        self.seq_mut::<u64, 8>(OFFSETS_ID).append(&[links_offset as u64])?;
+        self.seq_mut::<u32, 4>(LINKS_ID).append(&vec![0; links_len])?;
+
+        if self.entry == NO_ENTRY {
+            self.entry = node;
+            self.max_level = level;
+            return self.write_metadata();
+        }
+
+        let (m, ef_construction, entry, max_level) = (self.m, self.ef_construction, self.entry, self.max_level);
+        let mut graph = self.graph_mut()?;
+
+        let mut current = Candidate::new(graph.dist(vector, entry)?, entry);
+        for l in (level + 1..=max_level).rev() {
+            current = graph.greedy_closest(vector, current, l)?;
+        }
+
+        let mut entries = vec![current];
+        for l in (0..=level.min(max_level)).rev() {
+            let found = graph.search_layer(vector, &entries, ef_construction, l)?;
+            let neighbors: Vec<u32> = found.iter().take(m).map(|i| i.id).collect();
+            graph.set_neighbors(node, l, &neighbors)?;
+
+            // Link back and prune the neighbors lists if they are full.
+            let slots = slots(m, l);
+            for n in neighbors {
+                let mut links = graph.neighbors(n, l)?;
+                links.push(node);
+                if links.len() > slots {
+                    let n_vec = graph.vector(n)?;
+                    let mut scored = links
+                        .into_iter()
+                        .map(|i| Ok(Candidate::new(graph.dist(&n_vec, i)?, i)))
+                        .collect::<Result<Vec<_>>>()?;
+                    scored.sort_unstable();
+                    links = scored.into_iter().take(slots).map(|i| i.id).collect();
+                }
+                graph.set_neighbors(n, l, &links)?;
+            }
+
+            entries = found;
+        }
+
+        if level > self.max_level {
+            self.entry = node;
+            self.max_level = level;
+        }
+        self.write_metadata()
+    }
+
+    /// Returns a random level for a new node. Levels are derived from the node index so they are reproducible.
+    fn random_level(&self, node: u32) -> usize {
+        let ml = 1.0 / (self.m as f64).ln();
+        let u = (mix(node as u64) >> 11) as f64 / (1u64 << 53) as f64;
+        ((-(1.0 - u).ln() * ml) as usize).min(MAX_LEVEL)
+    }
+
+    #[inline]
+    fn seq_mut<T, const N: usize>(&mut self, id: usize) -> NumberSequence<MFileEntryMut<'_, B>, T, N>
+        where
+            T: SizedDeser<N>,
+    {
+        self.backend.get_backend_mut(id).unwrap()
+    }
+}
+
+impl<B> VectorStore<B>
+    where
+        B: Backend,
+{
+    /// Returns the IDs and distances of the (approximately) `k` nearest vectors to `query`, ordered by their distance.
+    #[inline]
+    pub fn search(&self, query: &[f32], k: usize) -> Result<Vec<(u64, f32)>> {
+        self.search_with_ef(query, k, DEFAULT_EF_SEARCH)
+    }
+
+    /// Like `search` but with a custom size of the candidate list. Higher values of `ef` increase the search quality
+    /// but are slower.
+    pub fn search_with_ef(&self, query: &[f32], k: usize, ef: usize) -> Result<Vec<(u64, f32)>> {
+        if query.len() != self.dim {
+            return Err(Error::UnexpectedValue);
+        }
+        if self.entry == NO_ENTRY || k == 0 {
+            return Ok(vec![]);
+        }
+
+        let graph = self.graph();
+        let mut current = Candidate::new(graph.dist(query, self.entry)?, self.entry);
+        for l in (1..=self.max_level).rev() {
+            current = graph.greedy_closest(query, current, l)?;
+        }
+
+        let found = graph.search_layer(query, &[current], ef.max(k), 0)?;
+        let ids: NumberSequence<_, u64, 8> = self.backend.get_backend(IDS_ID).unwrap();
+        found
+            .into_iter()
+            .take(k)
+            .map(|i| Ok((ids.get(i.id as usize)?, i.dist)))
+            .collect()
+    }
+
+    /// Returns the amount of stored vectors.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.backend.get(IDS_ID).unwrap().len() / 8
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entry == NO_ENTRY
+    }
+
+    /// Returns the dimension of the vectors.
+    #[inline]
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.backend.flush()
+    }
+
+    fn graph(&self) -> Graph<GeneralSubBackend<'_>> {
+        Graph {
+            vectors: self.backend.get_backend(VECTORS_ID).unwrap(),
+            offsets: self.backend.get_backend(OFFSETS_ID).unwrap(),
+            links: self.backend.get_backend(LINKS_ID).unwrap(),
+            dim: self.dim,
+            m: self.m,
+        }
+    }
+
+    fn graph_mut(&mut self) -> Result<Graph<GeneralSubMutBackend<'_>>> {
+        let (dim, m) = (self.dim, self.m);
+        let [vectors, offsets, links] = self
+            .backend
+            .get_n_by_index_mut([VECTORS_ID, OFFSETS_ID, LINKS_ID])
+            .ok_or(Error::OutOfBounds)?;
+        Ok(Graph {
+            vectors: NumberSequence::init(vectors)?,
+            offsets: NumberSequence::init(offsets)?,
+            links: NumberSequence::init(links)?,
+            dim,
+            m,
+        })
+    }
+
+    fn write_metadata(&mut self) -> Result<()> {
+        let mut data = [0u8; METADATA_LEN];
+        let values = [
+            self.dim as u32,
+            self.m as u32,
+            self.ef_construction as u32,
+            self.entry,
+            self.max_level as u32,
+        ];
+        for (i, v) in values.iter().enumerate() {
+            data[i * 4..(i + 1) * 4].copy_from_slice(&v.to_le_bytes());
+        }
+
+        let mut metadata = self.backend.get_mut(METADATA_ID).unwrap();
+        metadata.replace_same_len(0, &data)?;
+        Ok(())
+    }
+}
+
+impl<B> Initiable<B> for VectorStore<B>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let backend = MultiFile::init(backend)?;
+
+        let metadata = backend.get(METADATA_ID).ok_or(Error::Initialization)?;
+        let metadata = metadata.get(0, METADATA_LEN)?;
+        let v = |i: usize| u32::from_le_bytes(metadata[i * 4..(i + 1) * 4].try_into().unwrap());
+        let (dim, m, ef_construction, entry, max_level) = (v(0), v(1), v(2), v(3), v(4));
+
+        if !(VECTORS_ID..=LINKS_ID).all(|i| backend.has_id(i)) || dim == 0 || m < 2 {
+            return Err(Error::Initialization);
+        }
+
+        Ok(Self {
+            backend,
+            dim: dim as usize,
+            m: m as usize,
+            ef_construction: ef_construction as usize,
+            entry,
+            max_level: max_level as usize,
+        })
+    }
+}
+
+impl<B> MType for VectorStore<B>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.backend.raw_data()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_add_search(&mut mem_backend);
+        test_recall(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./vector_store", 10);
+        test_add_search(&mut mmap_backend);
+        test_recall(&mut mmap_backend);
+    }
+
+    /// Returns a deterministic pseudo random vector.
+    fn make_vector(seed: u64, dim: usize) -> Vec<f32> {
+        (0..dim as u64)
+            .map(|i| (mix(seed * 31 + i) % 10_000) as f32 / 10_000.0)
+            .collect()
+    }
+
+    fn test_add_search<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        {
+            let mut store = VectorStore::new(&mut backend, 2).unwrap();
+            assert!(store.is_empty());
+            assert_eq!(store.search(&[0.0, 0.0], 3), Ok(vec![]));
+
+            store.add(10, &[0.0, 0.0]).unwrap();
+            store.add(11, &[1.0, 0.0]).unwrap();
+            store.add(12, &[0.0, 2.0]).unwrap();
+            store.add(13, &[5.0, 5.0]).unwrap();
+            assert_eq!(store.add(14, &[1.0]), Err(Error::UnexpectedValue));
+            assert_eq!(store.len(), 4);
+        }
+
+        let store = VectorStore::init(&mut backend).unwrap();
+        assert_eq!(store.dim(), 2);
+        assert_eq!(store.len(), 4);
+        assert_eq!(
+            store.search(&[0.1, 0.0], 2),
+            Ok(vec![(10, 0.010000001), (11, 0.80999994)])
+        );
+        assert_eq!(store.search(&[5.0, 5.0], 1), Ok(vec![(13, 0.0)]));
+        assert_eq!(store.search(&[5.0], 1), Err(Error::UnexpectedValue));
+    }
+
+    fn test_recall<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let dim = 8;
+        let vectors: Vec<_> = (0..1000).map(|i| make_vector(i, dim)).collect();
+
+        let mut store = VectorStore::with_params(&mut backend, dim, 8, 64).unwrap();
+        for (id, vector) in vectors.iter().enumerate() {
+            store.add(id as u64, vector).unwrap();
+        }
+
+        let k = 10;
+        let mut hits = 0;
+        for q in 0..50 {
+            let query = make_vector(10_000 + q, dim);
+
+            let mut exact: Vec<_> = vectors
+                .iter()
+                .enumerate()
+                .map(|(id, v)| {
+                    let dist: f32 = v.iter().zip(query.iter()).map(|(a, b)| (a - b) * (a - b)).sum();
+                    (dist, id as u64)
+                })
+                .collect();
+            exact.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+            let exact: Vec<_> = exact.iter().take(k).map(|i| i.1).collect();
+
+            let found = store.search(&query, k).unwrap();
+            assert_eq!(found.len(), k);
+            hits += found.iter().filter(|i| exact.contains(&i.0)).count();
+        }
+
+        let recall = hits as f64 / (50 * k) as f64;
+        assert!(recall > 0.9, "recall too low: {recall}");
+    }
+}
@@ -0,0 +1,353 @@
+use crate::backend::base::sub::GeneralSubBackend;
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::components::indexed_file::IndexedFile;
+use crate::components::map::hashing::Hash;
+use crate::components::map::FMap;
+use crate::components::multi_file::entry_mut::MFileEntryMut;
+use crate::components::multi_file::MultiFile;
+use crate::deser::{deserialize_impl, serialize_impl};
+use crate::traits::creatable::Creatable;
+use crate::traits::deser::Deser;
+use crate::traits::initiable::Initiable;
+use crate::traits::mtype::MType;
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+/// ID of the entry holding the stores metadata.
+const METADATA_ID: usize = 0;
+
+/// ID of the entry holding the map of keys to chain IDs.
+const MAP_ID: usize = 1;
+
+/// ID of the entry holding the version chains.
+const CHAINS_ID: usize = 2;
+
+/// Size of the header of each record in a chain: VERSION (8 bytes) + LEN (4 bytes)
+const RECORD_HEADER_LEN: usize = 12;
+
+#[derive(Serialize, Deserialize)]
+struct VersionedMetadata {
+    /// The version of the latest write.
+    version: u64,
+
+    /// Amount of versions kept per key.
+    max_versions: u32,
+}
+
+/// A key value store keeping up to `max_versions` historical values of each key. Every write gets a new, store wide
+/// version number, which allows reading the value a key had at any (not yet pruned) point in time.
+///
+/// The internal format: MultiFile(METADATA | FMap<K, CHAIN ID> | IndexedFile<CHAIN>)
+/// where each chain holds the records of a single key in ascending order of their version and each record is encoded
+/// as (VERSION (8 bytes) | LEN (4 bytes) | VALUE).
+pub struct VersionedStore<B, K, V> {
+    storage: MultiFile<B>,
+    version: u64,
+    max_versions: usize,
+    p: PhantomData<(K, V)>,
+}
+
+impl<B, K, V> VersionedStore<B, K, V>
+    where
+        B: GrowableBackend,
+        K: Hash + Eq + Deser,
+        V: Deser,
+{
+    /// Creates a new empty store keeping up to `max_versions` values per key. Returns `Error::UnexpectedValue` if
+    /// `max_versions` is 0.
+    pub fn new(backend: B, max_versions: usize) -> Result<Self> {
+        if max_versions == 0 || max_versions > u32::MAX as usize {
+            return Err(Error::UnexpectedValue);
+        }
+
+        let mut store = Self {
+            storage: MultiFile::with_capacity(backend, 3)?,
+            version: 0,
+            max_versions,
+            p: PhantomData,
+        };
+        store.storage.insert_empty()?;
+        store.write_metadata()?;
+        FMap::<_, K, u32>::create(store.storage.insert_empty()?)?;
+        IndexedFile::create(store.storage.insert_empty()?)?;
+        Ok(store)
+    }
+
+    /// Writes a new value for `key` and returns the version of the write. If the key already has `max_versions`
+    /// values, its oldest one gets dropped.
+    pub fn insert(&mut self, key: &K, value: &V) -> Result<u64> {
+        let version = self.version + 1;
+        let data = serialize_impl(value)?;
+        let mut record = Vec::with_capacity(RECORD_HEADER_LEN + data.len());
+        record.extend_from_slice(&version.to_le_bytes());
+        record.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        record.extend_from_slice(&data);
+
+        match self.map().get(key) {
+            Some(id) => {
+                let max_versions = self.max_versions;
+                let mut chains = self.chains_mut();
+                let mut chain = chains.entry(id as usize)?;
+                if records(&chain).count() < max_versions {
+                    chain.grow_with_data(&record)?;
+                } else {
+                    // Drop the oldest record.
+                    let oldest = records(&chain).next().map(|i| RECORD_HEADER_LEN + i.1.len()).unwrap_or(0);
+                    let mut new = chain[oldest..].to_vec();
+                    new.extend_from_slice(&record);
+                    chain.set(&new)?;
+                }
+            }
+            None => {
+                let id = self.chains_mut().insert(&record)?;
+                self.map_mut().insert(key, &(id as u32))?;
+            }
+        }
+
+        self.version = version;
+        self.write_metadata()?;
+        Ok(version)
+    }
+
+    /// Drops all values that got overwritten before `version`. Reads at `version` and later still return the same
+    /// values, while reads at older versions might return `None`.
+    pub fn prune(&mut self, version: u64) -> Result<()> {
+        let mut chains = self.chains_mut();
+        for id in 0..chains.count() {
+            let mut chain = chains.entry(id)?;
+            let visible = records(&chain).take_while(|i| i.0 <= version).count();
+            if visible <= 1 {
+                continue;
+            }
+            let dropped: usize = records(&chain).take(visible - 1).map(|i| RECORD_HEADER_LEN + i.1.len()).sum();
+            let new = chain[dropped..].to_vec();
+            chain.set(&new)?;
+        }
+        Ok(())
+    }
+
+    /// Removes all keys and values and resets the version.
+    pub fn clear(&mut self) -> Result<()> {
+        self.map_mut().clear()?;
+        self.chains_mut().clear();
+        self.version = 0;
+        self.write_metadata()
+    }
+
+    #[inline]
+    fn map_mut(&mut self) -> FMap<MFileEntryMut<'_, B>, K, u32> {
+        self.storage.get_backend_mut(MAP_ID).unwrap()
+    }
+
+    #[inline]
+    fn chains_mut(&mut self) -> IndexedFile<MFileEntryMut<'_, B>> {
+        self.storage.get_backend_mut(CHAINS_ID).unwrap()
+    }
+
+    fn write_metadata(&mut self) -> Result<()> {
+        let metadata = VersionedMetadata {
+            version: self.version,
+            max_versions: self.max_versions as u32,
+        };
+        self.storage.set_entry(METADATA_ID, &serialize_impl(&metadata)?)
+    }
+}
+
+impl<B, K, V> VersionedStore<B, K, V>
+    where
+        B: Backend,
+        K: Hash + Eq + Deser,
+        V: Deser,
+{
+    /// Returns the version of the latest write or 0 if nothing was written yet.
+    #[inline]
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns the amount of versions kept per key.
+    #[inline]
+    pub fn max_versions(&self) -> usize {
+        self.max_versions
+    }
+
+    /// Returns the amount of keys.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map().len()
+    }
+
+    /// Returns `true` if the store doesn't contain any keys.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the store contains `key`.
+    #[inline]
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map().contains_key(key)
+    }
+
+    /// Returns the latest value of `key`.
+    #[inline]
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.get_at(key, self.version)
+    }
+
+    /// Returns the value `key` had at `version`. Returns `None` if the key wasn't written yet at that version or the
+    /// value got pruned.
+    pub fn get_at(&self, key: &K, version: u64) -> Option<V> {
+        let id = self.map().get(key)?;
+        let chains = self.chains();
+        let chain = chains.get(id as usize).ok()?;
+        let (_, data) = records(chain).take_while(|i| i.0 <= version).last()?;
+        deserialize_impl(data).ok()
+    }
+
+    /// Returns the versions of all stored values of `key` in ascending order.
+    pub fn versions(&self, key: &K) -> Vec<u64> {
+        let Some(id) = self.map().get(key) else {
+            return vec![];
+        };
+        let chains = self.chains();
+        let chain = chains.get(id as usize).unwrap_or_default();
+        records(chain).map(|i| i.0).collect()
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.storage.flush()
+    }
+
+    #[inline]
+    fn map(&self) -> FMap<GeneralSubBackend<'_>, K, u32> {
+        self.storage.get_backend(MAP_ID).unwrap()
+    }
+
+    #[inline]
+    fn chains(&self) -> IndexedFile<GeneralSubBackend<'_>> {
+        self.storage.get_backend(CHAINS_ID).unwrap()
+    }
+}
+
+/// Returns an iterator over the (version, value) records of an encoded chain.
+fn records(mut chain: &[u8]) -> impl Iterator<Item = (u64, &[u8])> {
+    std::iter::from_fn(move || {
+        if chain.is_empty() {
+            return None;
+        }
+        let version = u64::from_le_bytes(chain[..8].try_into().unwrap());
+        let len = u32::from_le_bytes(chain[8..RECORD_HEADER_LEN].try_into().unwrap()) as usize;
+        let data = &chain[RECORD_HEADER_LEN..RECORD_HEADER_LEN + len];
+        chain = &chain[RECORD_HEADER_LEN + len..];
+        Some((version, data))
+    })
+}
+
+impl<B, K, V> Initiable<B> for VersionedStore<B, K, V>
+    where
+        B: Backend,
+{
+    fn init(backend: B) -> Result<Self> {
+        let storage = MultiFile::init(backend)?;
+        let metadata: VersionedMetadata = storage
+            .get(METADATA_ID)
+            .ok_or(Error::Initialization)
+            .and_then(|i| deserialize_impl(i.content_data()))?;
+
+        if metadata.max_versions == 0 || !storage.has_id(MAP_ID) || !storage.has_id(CHAINS_ID) {
+            return Err(Error::Initialization);
+        }
+
+        Ok(Self {
+            storage,
+            version: metadata.version,
+            max_versions: metadata.max_versions as usize,
+            p: PhantomData,
+        })
+    }
+}
+
+impl<B, K, V> MType for VersionedStore<B, K, V>
+    where
+        B: Backend,
+{
+    #[inline]
+    fn raw_data(&self) -> &[u8] {
+        self.storage.raw_data()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mmap")]
+mod test {
+    use super::*;
+    use crate::backend::memory::test::make_mem_backend;
+    use crate::backend::mmap_mut::test::make_mmap_backend;
+
+    #[test]
+    fn test_all() {
+        let mut mem_backend = make_mem_backend(10);
+        test_history(&mut mem_backend);
+        test_prune(&mut mem_backend);
+
+        let mut mmap_backend = make_mmap_backend("./versioned", 10);
+        test_history(&mut mmap_backend);
+        test_prune(&mut mmap_backend);
+    }
+
+    fn test_history<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        {
+            let mut store = VersionedStore::<_, String, u64>::new(&mut backend, 3).unwrap();
+            assert!(store.is_empty());
+            for i in 0..300u64 {
+                let key = format!("key{}", i % 50);
+                assert_eq!(store.insert(&key, &i), Ok(i + 1));
+            }
+        }
+
+        let store = VersionedStore::<_, String, u64>::init(&mut backend).unwrap();
+        assert_eq!(store.len(), 50);
+        assert_eq!(store.version(), 300);
+        assert_eq!(store.max_versions(), 3);
+
+        // key7 was written at versions 8, 58, ..., 258 but only the latest 3 are kept.
+        let key = "key7".to_string();
+        assert_eq!(store.versions(&key), vec![158, 208, 258]);
+        assert_eq!(store.get(&key), Some(257));
+        assert_eq!(store.get_at(&key, 300), Some(257));
+        assert_eq!(store.get_at(&key, 257), Some(207));
+        assert_eq!(store.get_at(&key, 158), Some(157));
+        assert_eq!(store.get_at(&key, 157), None);
+        assert_eq!(store.get(&"key50".to_string()), None);
+        assert!(store.versions(&"key50".to_string()).is_empty());
+        assert!(VersionedStore::<_, String, u64>::new(&mut backend, 0).is_err());
+    }
+
+    fn test_prune<B: GrowableBackend>(mut backend: &mut B) {
+        backend.clear();
+        let mut store = VersionedStore::<_, u32, String>::new(&mut backend, 10).unwrap();
+        store.insert(&1, &"a".to_string()).unwrap();
+        store.insert(&2, &"b".to_string()).unwrap();
+        store.insert(&1, &"c".to_string()).unwrap();
+        store.insert(&1, &"d".to_string()).unwrap();
+
+        store.prune(3).unwrap();
+        assert_eq!(store.versions(&1), vec![3, 4]);
+        assert_eq!(store.versions(&2), vec![2]);
+        assert_eq!(store.get_at(&1, 3), Some("c".to_string()));
+        assert_eq!(store.get_at(&1, 2), None);
+        assert_eq!(store.get_at(&2, 3), Some("b".to_string()));
+        assert_eq!(store.get(&1), Some("d".to_string()));
+
+        store.clear().unwrap();
+        assert!(store.is_empty());
+        assert_eq!(store.version(), 0);
+        assert_eq!(store.insert(&1, &"e".to_string()), Ok(1));
+        assert_eq!(store.versions(&1), vec![1]);
+    }
+}
@@ -11,3 +11,30 @@ pub fn serialize_impl<T: Serialize>(item: &T) -> Result<Vec<u8>, Error> {
 pub fn deserialize_impl<T: DeserializeOwned>(data: &[u8]) -> Result<T, Error> {
     Ok(bitcode::deserialize(data)?)
 }
+
+/// Reusable state to deserialize many values without allocating new internal buffers for each of them.
+pub struct ScratchBuffer {
+    buffer: bitcode::Buffer,
+}
+
+impl ScratchBuffer {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            buffer: bitcode::Buffer::new(),
+        }
+    }
+}
+
+impl Default for ScratchBuffer {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Same as `deserialize_impl` but reuses the allocations of `scratch`.
+#[inline]
+pub fn deserialize_into<T: DeserializeOwned>(scratch: &mut ScratchBuffer, data: &[u8]) -> Result<T, Error> {
+    Ok(scratch.buffer.deserialize(data)?)
+}
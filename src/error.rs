@@ -11,6 +11,11 @@ pub enum Error {
     Initialization,
     UnexpectedValue,
     UnsupportedOperation,
+    RelevanceLost,
+    QuotaExceeded,
+    AllocationFailed,
+    Locked,
+    UnsupportedVersion,
 }
 
 impl PartialEq for Error {
@@ -24,6 +29,11 @@ impl PartialEq for Error {
             (Self::Initialization, Self::Initialization) => true,
             (Self::UnexpectedValue, Self::UnexpectedValue) => true,
             (Self::UnsupportedOperation, Self::UnsupportedOperation) => true,
+            (Self::RelevanceLost, Self::RelevanceLost) => true,
+            (Self::QuotaExceeded, Self::QuotaExceeded) => true,
+            (Self::AllocationFailed, Self::AllocationFailed) => true,
+            (Self::Locked, Self::Locked) => true,
+            (Self::UnsupportedVersion, Self::UnsupportedVersion) => true,
             (_, _) => false,
         }
     }
@@ -1,4 +1,5 @@
 pub mod backend;
+pub mod budget;
 pub mod components;
 pub mod deser;
 pub mod error;
@@ -0,0 +1,95 @@
+use crate::backend::growable::GrowableBackend;
+use crate::backend::Backend;
+use crate::Result;
+use std::ops::Range;
+
+/// Components that can apply a series of mutations as a single batch.
+pub trait Batch {
+    /// Runs `f` on the component and flushes once afterwards. If `f` returns an error, all mutations done within the
+    /// batch are rolled back and the error gets returned.
+    fn batch<F, R>(&mut self, f: F) -> Result<R>
+        where
+            F: FnOnce(&mut Self) -> Result<R>;
+}
+
+/// Undo log holding the previous data of all ranges of a backend written while a batch is running, so rolling back
+/// only costs as much as the batch has written. Batches can be nested, each one starting a new savepoint.
+#[derive(Default)]
+pub(crate) struct UndoLog {
+    /// The raw start index and previous data of each written range, in the order they were written.
+    images: Vec<(usize, Vec<u8>)>,
+    savepoints: Vec<Savepoint>,
+}
+
+/// State of a backend at the start of a batch.
+struct Savepoint {
+    images: usize,
+    size: usize,
+    len: usize,
+}
+
+impl UndoLog {
+    /// Returns `true` if writes have to be recorded.
+    #[inline]
+    pub fn is_recording(&self) -> bool {
+        !self.savepoints.is_empty()
+    }
+
+    /// Starts recording writes to `backend` until the savepoint gets committed or rolled back.
+    pub fn begin<B: Backend>(&mut self, backend: &B) {
+        self.savepoints.push(Savepoint {
+            images: self.images.len(),
+            size: backend.data().len(),
+            len: backend.len(),
+        });
+    }
+
+    /// Keeps all writes since the last savepoint. The recorded data is only dropped once no outer savepoint is left.
+    pub fn commit(&mut self) {
+        self.savepoints.pop();
+        if self.savepoints.is_empty() {
+            self.images = vec![];
+        }
+    }
+
+    /// Records the raw data of `backend` within `range` before it gets written.
+    #[inline]
+    pub fn record<B: Backend>(&mut self, backend: &B, range: Range<usize>) {
+        if !self.is_recording() {
+            return;
+        }
+
+        let end = range.end.min(backend.data().len());
+        if range.start < end {
+            self.images.push((range.start, backend.data()[range.start..end].to_vec()));
+        }
+    }
+
+    /// Restores `backend` to the state it had at the last savepoint, including its capacity.
+    pub fn rollback<B: GrowableBackend>(&mut self, backend: &mut B) -> Result<()> {
+        let Some(savepoint) = self.savepoints.pop() else {
+            return Ok(());
+        };
+
+        backend.set_len(0)?;
+
+        let size = backend.data().len();
+        if size != savepoint.size {
+            backend.resize(savepoint.size as isize - size as isize)?;
+        }
+
+        // Apply the oldest data last, as ranges might have been written multiple times.
+        for (start, image) in self.images.drain(savepoint.images..).rev() {
+            let end = (start + image.len()).min(savepoint.size);
+            if start < end {
+                backend.data_mut_range(start..end).copy_from_slice(&image[..end - start]);
+            }
+        }
+
+        if self.savepoints.is_empty() {
+            self.images = vec![];
+        }
+
+        backend.set_len(savepoint.len)
+    }
+}
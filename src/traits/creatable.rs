@@ -1,4 +1,6 @@
 use crate::backend::memory::{MemoryBackend, MemoryData};
+use crate::backend::Backend;
+use crate::traits::initiable::Initiable;
 use crate::header::BaseHeader;
 use crate::Result;
 
@@ -31,3 +33,20 @@ pub trait MemCreatable: Creatable<MemoryBackend> {
 }
 
 impl<T: Creatable<MemoryBackend>> MemCreatable for T {}
+
+pub trait OpenOrInit<B>: Creatable<B> + Initiable<B>
+where
+    B: Backend,
+{
+    /// Initializes the component from `backend` if it holds any data and creates a new one otherwise.
+    #[inline]
+    fn open_or_init(backend: B) -> Result<Self> {
+        if backend.len() == 0 {
+            Self::create(backend)
+        } else {
+            Self::init(backend)
+        }
+    }
+}
+
+impl<B: Backend, T: Creatable<B> + Initiable<B>> OpenOrInit<B> for T {}
@@ -1,3 +1,4 @@
+use crate::traits::read_only::ReadOnly;
 use crate::Result;
 
 pub trait Initiable<B>
@@ -5,4 +6,10 @@ where
     Self: Sized,
 {
     fn init(backend: B) -> Result<Self>;
+
+    /// Initializes the component from `backend` and wraps it in a `ReadOnly` so it can't be modified.
+    #[inline]
+    fn init_readonly(backend: B) -> Result<ReadOnly<Self>> {
+        Self::init(backend).map(ReadOnly::new)
+    }
 }
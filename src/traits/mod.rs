@@ -1,6 +1,10 @@
+pub mod batch;
 pub mod collection;
 pub mod creatable;
 pub mod deser;
 pub mod initiable;
 pub mod mtype;
+pub mod read_only;
+pub mod shrink;
 pub mod sized_deser;
+pub mod varint;
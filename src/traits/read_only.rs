@@ -0,0 +1,28 @@
+use std::ops::Deref;
+
+/// Wrapper that only gives out shared references to the wrapped component. Components opened on read only backends,
+/// eg. `MmapBackend`, can be wrapped in it to reject mutations at compile time instead of panicking at runtime.
+#[derive(Debug)]
+pub struct ReadOnly<T>(T);
+
+impl<T> ReadOnly<T> {
+    #[inline]
+    pub fn new(inner: T) -> Self {
+        Self(inner)
+    }
+
+    /// Returns the wrapped component.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for ReadOnly<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
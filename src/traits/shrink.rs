@@ -0,0 +1,72 @@
+/// Policy to automatically shrink a growable component once its free space exceeds `max_free` of its capacity for
+/// `ops` consecutive operations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShrinkPolicy {
+    /// Fraction of free space (0.0..=1.0) that has to be exceeded for an operation to count towards shrinking.
+    pub max_free: f32,
+
+    /// Amount of consecutive operations the free space has to exceed `max_free` before the component gets shrunk.
+    pub ops: usize,
+}
+
+impl ShrinkPolicy {
+    #[inline]
+    pub fn new(max_free: f32, ops: usize) -> Self {
+        Self { max_free, ops }
+    }
+}
+
+impl Default for ShrinkPolicy {
+    #[inline]
+    fn default() -> Self {
+        Self::new(0.75, 64)
+    }
+}
+
+/// Components that can shrink themselves according to a `ShrinkPolicy`. The policy gets checked on each operation
+/// that might grow the component. Policies are not persisted and have to be set again after initializing a component.
+pub trait AutoShrink {
+    /// Sets the shrink policy. `None` disables automatic shrinking.
+    fn set_shrink_policy(&mut self, policy: Option<ShrinkPolicy>);
+
+    /// Returns the current shrink policy.
+    fn shrink_policy(&self) -> Option<ShrinkPolicy>;
+}
+
+/// Counts the operations a component exceeded the free space of its `ShrinkPolicy`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ShrinkTracker {
+    policy: ShrinkPolicy,
+    exceeded: usize,
+}
+
+impl ShrinkTracker {
+    #[inline]
+    pub(crate) fn new(policy: ShrinkPolicy) -> Self {
+        Self {
+            policy,
+            exceeded: 0,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn policy(&self) -> ShrinkPolicy {
+        self.policy
+    }
+
+    /// Records an operation with the given free space and capacity. Returns `true` if the component should be shrunk.
+    pub(crate) fn record(&mut self, free: usize, capacity: usize) -> bool {
+        if capacity == 0 || (free as f32 / capacity as f32) <= self.policy.max_free {
+            self.exceeded = 0;
+            return false;
+        }
+
+        self.exceeded += 1;
+        if self.exceeded < self.policy.ops {
+            return false;
+        }
+
+        self.exceeded = 0;
+        true
+    }
+}
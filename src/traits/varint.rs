@@ -0,0 +1,126 @@
+use crate::error::Error;
+
+/// Unsigned integers that can be encoded as LEB128 varint. On x86 the encoding uses SIMD instructions provided by
+/// `varint_simd`, other targets like `wasm32-unknown-unknown` use a scalar implementation producing the same bytes.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub trait VarInt: varint_simd::VarIntTarget {
+    fn to_u64(self) -> u64;
+
+    /// Returns `None` if `value` doesn't fit into `Self`.
+    fn from_u64(value: u64) -> Option<Self>;
+}
+
+/// Unsigned integers that can be encoded as LEB128 varint. On x86 the encoding uses SIMD instructions provided by
+/// `varint_simd`, other targets like `wasm32-unknown-unknown` use a scalar implementation producing the same bytes.
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+pub trait VarInt: Copy {
+    fn to_u64(self) -> u64;
+
+    /// Returns `None` if `value` doesn't fit into `Self`.
+    fn from_u64(value: u64) -> Option<Self>;
+}
+
+macro_rules! impl_varint {
+    ($e:ty) => {
+        impl VarInt for $e {
+            #[inline(always)]
+            fn to_u64(self) -> u64 {
+                self as u64
+            }
+
+            #[inline(always)]
+            fn from_u64(value: u64) -> Option<Self> {
+                <$e>::try_from(value).ok()
+            }
+        }
+    };
+}
+
+impl_varint!(u8);
+impl_varint!(u16);
+impl_varint!(u32);
+impl_varint!(u64);
+
+/// Maximum amount of bytes a varint of a u64 takes.
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+const MAX_LEN: usize = 10;
+
+/// Encodes `value` and returns a buffer holding the encoded bytes at its beginning along with their amount.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[inline]
+pub fn encode<T: VarInt>(value: T) -> ([u8; 16], u8) {
+    varint_simd::encode(value)
+}
+
+/// Decodes the varint at the beginning of `data` and returns it along with its encoded width.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[inline]
+pub fn decode<T: VarInt>(data: &[u8]) -> Result<(T, usize), Error> {
+    varint_simd::decode(data).map_err(|_| Error::UnexpectedValue)
+}
+
+/// Returns the encoded width of the varint at the beginning of `data`.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[inline]
+pub fn decode_len<T: VarInt>(data: &[u8]) -> Result<usize, Error> {
+    varint_simd::decode_len::<T>(data).map_err(|_| Error::UnexpectedValue)
+}
+
+/// Encodes `value` and returns a buffer holding the encoded bytes at its beginning along with their amount.
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+pub fn encode<T: VarInt>(value: T) -> ([u8; 16], u8) {
+    let mut value = value.to_u64();
+    let mut out = [0; 16];
+    let mut len = 0;
+    while value >= 0x80 {
+        out[len] = value as u8 | 0x80;
+        value >>= 7;
+        len += 1;
+    }
+    out[len] = value as u8;
+    (out, len as u8 + 1)
+}
+
+/// Decodes the varint at the beginning of `data` and returns it along with its encoded width.
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+pub fn decode<T: VarInt>(data: &[u8]) -> Result<(T, usize), Error> {
+    let len = decode_len::<T>(data)?;
+    let value = data[..len]
+        .iter()
+        .enumerate()
+        .fold(0u64, |value, (i, byte)| value | ((byte & 0x7f) as u64) << (i * 7));
+    Ok((T::from_u64(value).ok_or(Error::UnexpectedValue)?, len))
+}
+
+/// Returns the encoded width of the varint at the beginning of `data`.
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+#[inline]
+pub fn decode_len<T: VarInt>(data: &[u8]) -> Result<usize, Error> {
+    data.iter()
+        .take(MAX_LEN)
+        .position(|byte| byte & 0x80 == 0)
+        .map(|i| i + 1)
+        .ok_or(Error::UnexpectedValue)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        for value in [0u64, 1, 127, 128, 300, 16_383, 16_384, u32::MAX as u64, u64::MAX] {
+            let (data, len) = encode(value);
+            let data = &data[..len as usize];
+            assert_eq!(decode_len::<u64>(data).unwrap(), data.len());
+            assert_eq!(decode::<u64>(data).unwrap(), (value, data.len()));
+        }
+
+        let (data, len) = encode(300u16);
+        assert_eq!(&data[..len as usize], &[0xac, 0x02]);
+        assert_eq!(decode::<u16>(&data[..len as usize]).unwrap(), (300, 2));
+
+        // Trailing data is ignored.
+        assert_eq!(decode::<u32>(&[0x05, 0xff, 0xff]).unwrap(), (5, 1));
+    }
+}
@@ -1,4 +1,8 @@
+use crate::backend::Advice;
+use std::fs::File;
 use std::ops::Range;
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
 
 /// Returns `true` if the ranges overlap
 #[inline]
@@ -16,3 +20,113 @@ pub fn smallest_two_power_for(len: usize) -> u32 {
 
     req_len
 }
+
+/// Hints the OS that the pages holding `data` will be accessed soon. This is a no-op on non unix systems.
+#[inline]
+pub fn prefetch(data: &[u8]) {
+    advise(data, Advice::WillNeed);
+}
+
+/// Returns the page size of the system.
+#[cfg(unix)]
+#[inline]
+pub fn page_size() -> usize {
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+}
+
+/// Returns the page size of the system.
+#[cfg(not(unix))]
+#[inline]
+pub fn page_size() -> usize {
+    4096
+}
+
+/// Places an advisory lock on `file` which is held until the file gets closed. Multiple shared locks can exist at the
+/// same time while an exclusive lock excludes all others. Returns `Error::Locked` if a conflicting lock is held by any
+/// other open file, even within the same process.
+#[cfg(unix)]
+pub fn lock_file(file: &File, exclusive: bool) -> crate::Result<()> {
+    let op = if exclusive { libc::LOCK_EX } else { libc::LOCK_SH };
+    if unsafe { libc::flock(file.as_raw_fd(), op | libc::LOCK_NB) } == 0 {
+        return Ok(());
+    }
+
+    let err = std::io::Error::last_os_error();
+    if err.kind() == std::io::ErrorKind::WouldBlock {
+        return Err(crate::Error::Locked);
+    }
+    Err(err.into())
+}
+
+/// Locking files is only supported on unix systems so this is a no-op everywhere else.
+#[cfg(not(unix))]
+#[inline]
+pub fn lock_file(_file: &File, _exclusive: bool) -> crate::Result<()> {
+    Ok(())
+}
+
+/// Passes `advice` for the pages holding `data` to the OS. This is a no-op on non unix systems.
+#[cfg(unix)]
+pub fn advise(data: &[u8], advice: Advice) {
+    if data.is_empty() {
+        return;
+    }
+
+    let page_size = page_size();
+    let start = data.as_ptr() as usize;
+    let aligned = start - start % page_size;
+    let len = data.len() + (start - aligned);
+
+    let advice = match advice {
+        Advice::Normal => libc::MADV_NORMAL,
+        Advice::Sequential => libc::MADV_SEQUENTIAL,
+        Advice::Random => libc::MADV_RANDOM,
+        Advice::WillNeed => libc::MADV_WILLNEED,
+    };
+
+    // Safety:
+    // madvise doesn't access the memory and the range lies within the pages `data` is located in. Failing is fine
+    // since this is only a hint.
+    unsafe {
+        libc::madvise(aligned as *mut libc::c_void, len, advice);
+    }
+}
+
+/// Passes `advice` for the pages holding `data` to the OS. This is a no-op on non unix systems.
+#[cfg(not(unix))]
+#[inline]
+pub fn advise(_data: &[u8], _advice: Advice) {}
+
+/// Tracks which items of a sequential scan have already been prefetched, so read-ahead hints can be issued in
+/// windows of `distance` items instead of for each item.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct ReadAhead {
+    distance: usize,
+    hinted: usize,
+}
+
+impl ReadAhead {
+    #[inline]
+    pub fn new(distance: usize) -> Self {
+        Self { distance, hinted: 0 }
+    }
+
+    /// Returns the range of items that should be prefetched now that the scan is at `pos`, if any. `end` is the
+    /// amount of items in total.
+    #[inline]
+    pub fn window(&mut self, pos: usize, end: usize) -> Option<Range<usize>> {
+        // Only hint again once less than `distance` upcoming items are covered.
+        if self.distance == 0 || self.hinted >= pos + self.distance {
+            return None;
+        }
+
+        let start = self.hinted.max(pos);
+        let stop = (pos + self.distance * 2).min(end);
+        if start >= stop {
+            return None;
+        }
+
+        self.hinted = stop;
+        Some(start..stop)
+    }
+}